@@ -11,6 +11,8 @@ pub fn CategoryManagement() -> Element {
     let mut categories = use_signal(|| Vec::<life_tracker::storage::models::CategoryModel>::new());
     let mut search_term = use_signal(|| String::new());
     let mut show_create_dialog = use_signal(|| false);
+    let mut editing_category = use_signal(|| None::<life_tracker::storage::models::CategoryModel>);
+    let mut error_message = use_signal(|| None::<String>);
 
     // 获取分类列表
     let fetch_categories = use_callback(move |_| {
@@ -35,6 +37,58 @@ pub fn CategoryManagement() -> Element {
         fetch_categories(());
     });
 
+    // 删除分类（连同其下的任务移入回收站，可撤销）
+    let delete_category = move |category_id: uuid::Uuid| {
+        if let Some(commands) = get_app_state_sync().get_command_manager() {
+            match commands.delete_category(category_id) {
+                Ok(_) => {
+                    log::info!("分类删除成功: {}", category_id);
+                    error_message.set(None);
+                    fetch_categories(());
+                }
+                Err(e) => {
+                    log::error!("分类删除失败: {}", e);
+                    error_message.set(Some(format!("分类删除失败: {}", e)));
+                }
+            }
+        }
+    };
+
+    // 撤销或重做上一次修改
+    let mut step_history = move |redo: bool| {
+        if let Some(commands) = get_app_state_sync().get_command_manager() {
+            let result = if redo {
+                commands.redo()
+            } else {
+                commands.undo()
+            };
+            match result {
+                Ok(command) => {
+                    if let Some(command) = command {
+                        log::info!(
+                            "{}: {}",
+                            if redo { "已重做" } else { "已撤销" },
+                            command.description()
+                        );
+                    }
+                    error_message.set(None);
+                    fetch_categories(());
+                }
+                Err(e) => {
+                    log::error!("撤销/重做失败: {}", e);
+                    error_message.set(Some(format!("操作失败: {}", e)));
+                }
+            }
+        }
+    };
+    let commands = get_app_state_sync().get_command_manager();
+    let can_undo = commands
+        .as_ref()
+        .is_some_and(|commands| commands.can_undo());
+    let can_redo = commands
+        .as_ref()
+        .is_some_and(|commands| commands.can_redo());
+
     // 过滤分类
     let filtered_categories: Vec<_> = categories
         .read()
@@ -63,11 +117,36 @@ pub fn CategoryManagement() -> Element {
                 div {
                     class: "flex items-center justify-between mb-4",
                     h2 { class: "text-2xl font-bold text-gray-900 dark:text-white", "分类管理" }
-                    button {
-                        class: "flex items-center space-x-2 px-4 py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-lg transition-colors shadow-md",
-                        onclick: move |_| show_create_dialog.set(true),
-                        span { class: "text-lg", "➕" }
-                        span { "新建分类" }
+                    div {
+                        class: "flex items-center space-x-2",
+                        button {
+                            class: "px-3 py-2 border border-gray-300 dark:border-gray-600 text-gray-700 dark:text-gray-300 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors disabled:opacity-50 disabled:cursor-not-allowed",
+                            title: "撤销",
+                            disabled: !can_undo,
+                            onclick: move |_| step_history(false),
+                            "↶"
+                        }
+                        button {
+                            class: "px-3 py-2 border border-gray-300 dark:border-gray-600 text-gray-700 dark:text-gray-300 rounded-lg hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors disabled:opacity-50 disabled:cursor-not-allowed",
+                            title: "重做",
+                            disabled: !can_redo,
+                            onclick: move |_| step_history(true),
+                            "↷"
+                        }
+                        button {
+                            class: "flex items-center space-x-2 px-4 py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-lg transition-colors shadow-md",
+                            onclick: move |_| show_create_dialog.set(true),
+                            span { class: "text-lg", "➕" }
+                            span { "新建分类" }
+                        }
+                    }
+                }
+
+                // 错误信息
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "bg-red-50 border border-red-200 text-red-700 px-3 py-2 mb-4 rounded-lg text-sm",
+                        "{error}"
                     }
                 }
 
@@ -108,7 +187,9 @@ pub fn CategoryManagement() -> Element {
                     for category in filtered_categories.iter() {
                         CategoryCard {
                             key: "{category.id}",
-                            category: category.clone()
+                            category: category.clone(),
+                            on_edit: move |category| editing_category.set(Some(category)),
+                            on_delete: delete_category,
                         }
                     }
                 }
@@ -116,21 +197,37 @@ pub fn CategoryManagement() -> Element {
 
             // 创建分类对话框
             if show_create_dialog() {
-                CreateCategoryModal {
+                CategoryFormModal {
                     on_close: move |_| show_create_dialog.set(false),
-                    on_created: move |_| {
+                    on_saved: move |_| {
                         show_create_dialog.set(false);
                         fetch_categories(());
                     }
                 }
             }
+
+            // 编辑分类对话框
+            if let Some(category) = editing_category() {
+                CategoryFormModal {
+                    category,
+                    on_close: move |_| editing_category.set(None),
+                    on_saved: move |_| {
+                        editing_category.set(None);
+                        fetch_categories(());
+                    }
+                }
+            }
         }
     }
 }
 
 /// 分类卡片组件
 #[component]
-fn CategoryCard(category: life_tracker::storage::models::CategoryModel) -> Element {
+fn CategoryCard(
+    category: life_tracker::storage::models::CategoryModel,
+    on_edit: EventHandler<life_tracker::storage::models::CategoryModel>,
+    on_delete: EventHandler<uuid::Uuid>,
+) -> Element {
     let mut show_actions = use_signal(|| false);
 
     rsx! {
@@ -193,11 +290,8 @@ fn CategoryCard(category: life_tracker::storage::models::CategoryModel) -> Eleme
                         class: "p-2 text-gray-400 hover:text-blue-600 hover:bg-blue-50 dark:hover:bg-blue-900/20 rounded transition-colors",
                         title: "编辑分类",
                         onclick: {
-                            let category_name = category.name.clone();
-                            move |_| {
-                                // TODO: 实现编辑功能
-                                log::info!("编辑分类: {}", category_name);
-                            }
+                            let category = category.clone();
+                            move |_| on_edit.call(category.clone())
                         },
                         "✏️"
                     }
@@ -206,11 +300,8 @@ fn CategoryCard(category: life_tracker::storage::models::CategoryModel) -> Eleme
                         class: "p-2 text-gray-400 hover:text-red-600 hover:bg-red-50 dark:hover:bg-red-900/20 rounded transition-colors",
                         title: "删除分类",
                         onclick: {
-                            let category_name = category.name.clone();
-                            move |_| {
-                                // TODO: 实现删除功能
-                                log::info!("删除分类: {}", category_name);
-                            }
+                            let category_id = category.id;
+                            move |_| on_delete.call(category_id)
                         },
                         "🗑️"
                     }
@@ -220,10 +311,20 @@ fn CategoryCard(category: life_tracker::storage::models::CategoryModel) -> Eleme
     }
 }
 
-/// 创建分类模态框组件
+/// 创建或编辑分类模态框组件（传入 `category` 时为编辑）
 #[component]
-fn CreateCategoryModal(on_close: EventHandler<()>, on_created: EventHandler<()>) -> Element {
-    let mut form_data = use_signal(|| CategoryFormData::default());
+fn CategoryFormModal(
+    category: Option<life_tracker::storage::models::CategoryModel>,
+    on_close: EventHandler<()>,
+    on_saved: EventHandler<()>,
+) -> Element {
+    let editing_id = category.as_ref().map(|category| category.id);
+    let mut form_data = use_signal(|| {
+        category
+            .as_ref()
+            .map(CategoryFormData::from)
+            .unwrap_or_default()
+    });
     let mut error_message = use_signal(|| None::<String>);
 
     // 预设颜色选项
@@ -238,8 +339,8 @@ fn CreateCategoryModal(on_close: EventHandler<()>, on_created: EventHandler<()>)
         "📝",
     ];
 
-    // 创建分类功能
-    let mut create_category = move || {
+    // 保存分类功能
+    let mut save_category = move || {
         let data = form_data.read().clone();
 
         if data.name.trim().is_empty() {
@@ -247,38 +348,55 @@ fn CreateCategoryModal(on_close: EventHandler<()>, on_created: EventHandler<()>)
             return;
         }
 
+        let description = if data.description.trim().is_empty() {
+            None
+        } else {
+            Some(data.description.trim().to_string())
+        };
+
         let app_state = get_app_state_sync();
-        if let Some(database) = app_state.get_database() {
-            let category = life_tracker::storage::models::CategoryInsert {
-                id: uuid::Uuid::new_v4(),
-                name: data.name.trim().to_string(),
-                description: if data.description.trim().is_empty() {
-                    None
-                } else {
-                    Some(data.description.trim().to_string())
+        let Some(commands) = app_state.get_command_manager() else {
+            error_message.set(Some("数据库未初始化".to_string()));
+            return;
+        };
+
+        let result = match editing_id {
+            Some(id) => commands.update_category(
+                id,
+                &life_tracker::storage::models::CategoryUpdate {
+                    name: Some(data.name.trim().to_string()),
+                    description: Some(description),
+                    color: Some(data.color),
+                    icon: Some(data.icon),
+                    ..Default::default()
                 },
-                color: data.color,
-                icon: data.icon,
-                daily_target_seconds: None,
-                weekly_target_seconds: None,
-                is_active: true,
-                sort_order: 0,
-                parent_id: None,
-                created_at: chrono::Local::now(),
-            };
+            ),
+            None => commands
+                .insert_category(&life_tracker::storage::models::CategoryInsert {
+                    id: uuid::Uuid::new_v4(),
+                    name: data.name.trim().to_string(),
+                    description,
+                    color: data.color,
+                    icon: data.icon,
+                    daily_target_seconds: None,
+                    weekly_target_seconds: None,
+                    is_active: true,
+                    sort_order: 0,
+                    parent_id: None,
+                    created_at: chrono::Local::now(),
+                })
+                .map(|_| ()),
+        };
 
-            match database.insert_category(&category) {
-                Ok(_) => {
-                    log::info!("分类创建成功: {}", category.name);
-                    on_created.call(());
-                }
-                Err(e) => {
-                    log::error!("分类创建失败: {}", e);
-                    error_message.set(Some(format!("分类创建失败: {}", e)));
-                }
+        match result {
+            Ok(_) => {
+                log::info!("分类保存成功: {}", data.name.trim());
+                on_saved.call(());
+            }
+            Err(e) => {
+                log::error!("分类保存失败: {}", e);
+                error_message.set(Some(format!("分类保存失败: {}", e)));
             }
-        } else {
-            error_message.set(Some("数据库未初始化".to_string()));
         }
     };
 
@@ -293,7 +411,7 @@ fn CreateCategoryModal(on_close: EventHandler<()>, on_created: EventHandler<()>)
 
                 h3 {
                     class: "text-xl font-semibold text-gray-900 dark:text-white mb-6 text-center",
-                    "创建新分类"
+                    if editing_id.is_some() { "编辑分类" } else { "创建新分类" }
                 }
 
                 div {
@@ -464,10 +582,10 @@ fn CreateCategoryModal(on_close: EventHandler<()>, on_created: EventHandler<()>)
                             disabled: form_data.read().name.trim().is_empty(),
                             onclick: move |_| {
                                 if !form_data.read().name.trim().is_empty() {
-                                    create_category();
+                                    save_category();
                                 }
                             },
-                            "创建分类"
+                            if editing_id.is_some() { "保存修改" } else { "创建分类" }
                         }
                     }
                 }
@@ -485,6 +603,17 @@ struct CategoryFormData {
     icon: String,
 }
 
+impl From<&life_tracker::storage::models::CategoryModel> for CategoryFormData {
+    fn from(category: &life_tracker::storage::models::CategoryModel) -> Self {
+        Self {
+            name: category.name.clone(),
+            description: category.description.clone().unwrap_or_default(),
+            color: category.color.clone(),
+            icon: category.icon.clone(),
+        }
+    }
+}
+
 impl Default for CategoryFormData {
     fn default() -> Self {
         Self {
@@ -2,6 +2,7 @@
 //!
 //! 包含计时器、统计卡片、快速操作等功能
 
+use crate::components::app_state_provider::use_data_version;
 use crate::components::common::{Button, ButtonSize, ButtonVariant, Card};
 use dioxus::prelude::*;
 use life_tracker::get_app_state_sync;
@@ -46,19 +47,23 @@ pub fn TimingDashboard() -> Element {
     let timer_state = use_signal(|| TimerState::Stopped);
     let selected_task_id = use_signal(|| None::<String>);
 
-    // 获取任务列表用于显示
-    let app_state = get_app_state_sync();
-    let tasks = if let Some(database) = app_state.get_database() {
-        match database.get_all_tasks() {
+    let data_version = use_data_version();
+
+    // 在后台线程获取任务列表用于显示，数据修改后重新加载
+    let tasks_resource = use_resource(move || async move {
+        let _ = data_version();
+        let Some(database) = get_app_state_sync().get_database() else {
+            return Vec::new();
+        };
+        match database.run_blocking(|db| db.get_all_tasks()).await {
             Ok(tasks) => tasks,
             Err(e) => {
                 log::error!("获取任务失败: {}", e);
                 Vec::new()
             }
         }
-    } else {
-        Vec::new()
-    };
+    });
+    let tasks: Vec<TaskModel> = tasks_resource.read().clone().unwrap_or_default();
 
     rsx! {
         div {
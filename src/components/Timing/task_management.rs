@@ -2,6 +2,7 @@
 //!
 //! 包含任务CRUD操作、列表展示、模态框等功能
 
+use crate::components::app_state_provider::use_data_version;
 use dioxus::prelude::*;
 use life_tracker::get_app_state_sync;
 use life_tracker::storage::models::CategoryModel;
@@ -30,36 +31,31 @@ pub fn TaskManagementContent() -> Element {
     let mut selected_category = use_signal(|| None::<uuid::Uuid>);
     let mut expanded_tasks = use_signal(|| HashSet::<uuid::Uuid>::new());
 
-    // 同步获取应用状态和数据
-    let app_state = get_app_state_sync();
+    // 数据版本，任务修改后递增以重新加载列表（仪表板等页面共用）
+    let mut data_version = use_data_version();
 
-    // 获取任务列表
-    let tasks = if let Some(database) = app_state.get_database() {
-        match database.get_all_tasks() {
-            Ok(tasks) => tasks,
+    // 在后台线程加载任务和分类，避免阻塞界面
+    let task_data = use_resource(move || async move {
+        let _ = data_version();
+        let Some(database) = get_app_state_sync().get_database() else {
+            error_message.set(Some("数据库未初始化".to_string()));
+            return (Vec::new(), Vec::new());
+        };
+
+        match database
+            .run_blocking(|db| Ok((db.get_all_tasks()?, db.get_all_categories()?)))
+            .await
+        {
+            Ok(data) => data,
             Err(e) => {
                 log::error!("获取任务失败: {}", e);
                 error_message.set(Some(format!("获取任务失败: {}", e)));
-                Vec::new()
-            }
-        }
-    } else {
-        error_message.set(Some("数据库未初始化".to_string()));
-        Vec::new()
-    };
-
-    // 获取分类列表
-    let categories = if let Some(database) = app_state.get_database() {
-        match database.get_all_categories() {
-            Ok(categories) => categories,
-            Err(e) => {
-                log::error!("获取分类失败: {}", e);
-                Vec::new()
+                (Vec::new(), Vec::new())
             }
         }
-    } else {
-        Vec::new()
-    };
+    });
+    let (tasks, categories): (Vec<TaskModel>, Vec<CategoryModel>) =
+        task_data.read().clone().unwrap_or_default();
 
     // 过滤任务
     let filtered_tasks: Vec<TaskModel> = tasks
@@ -92,7 +88,7 @@ pub fn TaskManagementContent() -> Element {
         }
 
         let app_state = get_app_state_sync();
-        if let Some(database) = app_state.get_database() {
+        if let Some(commands) = app_state.get_command_manager() {
             // 解析标签
             let tags_json = if form_data.tags.trim().is_empty() {
                 "[]".to_string()
@@ -128,12 +124,13 @@ pub fn TaskManagementContent() -> Element {
             };
 
             // 执行数据库插入
-            match database.insert_task(&task) {
+            match commands.insert_task(&task) {
                 Ok(_) => {
                     log::info!("任务创建成功: {}", task.name);
                     success_message.set(Some("任务创建成功".to_string()));
                     error_message.set(None);
                     show_create_form.set(false);
+                    data_version += 1;
                 }
                 Err(e) => {
                     log::error!("任务创建失败: {}", e);
@@ -154,7 +151,7 @@ pub fn TaskManagementContent() -> Element {
         }
 
         let app_state = get_app_state_sync();
-        if let Some(database) = app_state.get_database() {
+        if let Some(commands) = app_state.get_command_manager() {
             // 解析标签
             let tags_json = if form_data.tags.trim().is_empty() {
                 "[]".to_string()
@@ -181,12 +178,13 @@ pub fn TaskManagementContent() -> Element {
                 ..Default::default()
             };
 
-            match database.update_task(task_id, &update) {
+            match commands.update_task(task_id, &update) {
                 Ok(_) => {
                     log::info!("任务更新成功: {}", task_id);
                     success_message.set(Some("任务更新成功".to_string()));
                     error_message.set(None);
                     editing_task.set(None);
+                    data_version += 1;
                 }
                 Err(e) => {
                     log::error!("任务更新失败: {}", e);
@@ -202,12 +200,13 @@ pub fn TaskManagementContent() -> Element {
     // 删除任务功能
     let mut delete_task = move |task_id: uuid::Uuid| {
         let app_state = get_app_state_sync();
-        if let Some(database) = app_state.get_database() {
-            match database.delete_task(task_id) {
+        if let Some(commands) = app_state.get_command_manager() {
+            match commands.delete_task(task_id) {
                 Ok(_) => {
                     log::info!("任务删除成功: {}", task_id);
                     success_message.set(Some("任务删除成功".to_string()));
                     error_message.set(None);
+                    data_version += 1;
                 }
                 Err(e) => {
                     log::error!("任务删除失败: {}", e);
@@ -220,6 +219,27 @@ pub fn TaskManagementContent() -> Element {
         }
     };
 
+    // 撤销上一次修改
+    let undo_last = move |_: MouseEvent| {
+        let app_state = get_app_state_sync();
+        if let Some(commands) = app_state.get_command_manager() {
+            match commands.undo() {
+                Ok(Some(command)) => {
+                    log::info!("已撤销: {}", command.description());
+                    success_message.set(Some(format!("已撤销：{}", command.description())));
+                    error_message.set(None);
+                    data_version += 1;
+                }
+                Ok(None) => success_message.set(None),
+                Err(e) => {
+                    log::error!("撤销失败: {}", e);
+                    error_message.set(Some(format!("撤销失败: {}", e)));
+                    success_message.set(None);
+                }
+            }
+        }
+    };
+
     // 切换任务展开状态
     let mut toggle_expanded = move |task_id: uuid::Uuid| {
         let mut expanded = expanded_tasks.write();
@@ -317,6 +337,16 @@ pub fn TaskManagementContent() -> Element {
                     class: "bg-green-50 border border-green-200 text-green-700 px-4 py-3 rounded-lg flex items-center space-x-2",
                     span { class: "text-lg", "✅" }
                     span { "{success}" }
+                    if get_app_state_sync()
+                        .get_command_manager()
+                        .is_some_and(|commands| commands.can_undo())
+                    {
+                        button {
+                            class: "ml-auto text-sm font-medium text-green-600 hover:text-green-800 underline",
+                            onclick: undo_last,
+                            "撤销"
+                        }
+                    }
                     button {
                         class: "ml-auto text-green-500 hover:text-green-700",
                         onclick: move |_| success_message.set(None),
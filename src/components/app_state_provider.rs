@@ -3,7 +3,13 @@
 //! 统一的全局状态管理，使用Dioxus Context API替代重复的get_app_state_sync调用
 
 use dioxus::prelude::*;
-use life_tracker::{get_app_state_sync, initialize_app_sync, AppState, Database};
+use life_tracker::{
+    get_app_state_sync, initialize_app_sync, shutdown_app_sync, AppState, Database,
+};
+#[cfg(feature = "encryption")]
+use life_tracker::storage::encryption::DatabaseKey;
+#[cfg(feature = "encryption")]
+use life_tracker::{initialize_app_with_passphrase, passphrase_prompt, PassphrasePrompt};
 use std::sync::Arc;
 
 /// 应用上下文状态
@@ -13,12 +19,26 @@ pub struct AppContext {
     pub app_state: Signal<AppState>,
     /// 初始化状态
     pub initialized: bool,
+    /// 等待输入的数据库口令操作
+    #[cfg(feature = "encryption")]
+    pub passphrase_prompt: Option<PassphrasePrompt>,
 }
 
 impl AppContext {
     /// 创建新的应用上下文
     pub fn new() -> Self {
         log::info!("Initializing application context...");
+
+        // 加密数据库或首次加密需要先输入口令
+        #[cfg(feature = "encryption")]
+        if let Some(prompt) = passphrase_prompt() {
+            log::info!("Database passphrase required ({:?}), waiting for input", prompt);
+            return Self {
+                app_state: Signal::new(AppState::default()),
+                initialized: false,
+                passphrase_prompt: Some(prompt),
+            };
+        }
         
         // 同步初始化应用
         let (app_state, initialized) = match initialize_app_sync() {
@@ -36,8 +56,20 @@ impl AppContext {
         Self {
             app_state: Signal::new(app_state),
             initialized,
+            #[cfg(feature = "encryption")]
+            passphrase_prompt: None,
         }
     }
+
+    /// 使用口令解锁数据库并完成初始化
+    #[cfg(feature = "encryption")]
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), String> {
+        initialize_app_with_passphrase(passphrase).map_err(|e| e.to_string())?;
+        log::info!("Database unlocked");
+        self.passphrase_prompt = None;
+        self.update_state(get_app_state_sync());
+        Ok(())
+    }
     
     /// 获取数据库连接的便捷方法
     pub fn get_database(&self) -> Option<Arc<Database>> {
@@ -59,9 +91,21 @@ pub fn AppStateProvider(children: Element) -> Element {
     
     // 提供上下文到子组件
     use_context_provider(|| app_context);
+    use_context_provider(|| DataVersion(Signal::new(0)));
+
+    // 关闭窗口时执行退出同步并停止后台调度器
+    use_drop(|| {
+        if let Err(e) = shutdown_app_sync() {
+            log::error!("Application shutdown failed: {}", e);
+        }
+    });
     
     // 检查初始化状态
     let context = app_context.read();
+    #[cfg(feature = "encryption")]
+    if let Some(prompt) = context.passphrase_prompt {
+        return rsx! { UnlockScreen { prompt } };
+    }
     if !context.initialized {
         return rsx! {
             div {
@@ -87,11 +131,104 @@ pub fn AppStateProvider(children: Element) -> Element {
     rsx! { {children} }
 }
 
+/// 数据库解锁界面，首次加密时用于设置口令
+#[cfg(feature = "encryption")]
+#[component]
+fn UnlockScreen(prompt: PassphrasePrompt) -> Element {
+    let mut app_context = use_app_context();
+    let mut passphrase = use_signal(String::new);
+    let mut confirmation = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let mut submit = move || {
+        let value = passphrase.read().clone();
+        // 首次加密时口令需要输入两次，避免输错后无法解锁
+        let result = match prompt {
+            PassphrasePrompt::Unlock => app_context.write().unlock(&value),
+            PassphrasePrompt::Encrypt => DatabaseKey::confirmed(value, &confirmation.read())
+                .map_err(|e| e.to_string())
+                .and_then(|key| app_context.write().unlock(key.expose())),
+        };
+        if let Err(e) = result {
+            error.set(Some(e));
+        }
+    };
+
+    let (title, hint, action) = match prompt {
+        PassphrasePrompt::Unlock => ("🔒 数据库已加密", "请输入数据库口令以解锁", "解锁"),
+        PassphrasePrompt::Encrypt => (
+            "🔑 设置数据库口令",
+            "设置中已开启数据库加密，请设置口令，数据库将使用该口令加密。口令遗失后无法恢复数据",
+            "加密并继续",
+        ),
+    };
+
+    rsx! {
+        div {
+            class: "min-h-screen bg-gray-100 dark:bg-gray-900 flex items-center justify-center",
+            div {
+                class: "bg-white dark:bg-gray-800 rounded-lg shadow-lg p-8 w-full max-w-sm",
+                h2 {
+                    class: "text-xl font-semibold text-gray-900 dark:text-white mb-2 text-center",
+                    "{title}"
+                }
+                p {
+                    class: "text-gray-500 dark:text-gray-400 mb-4 text-center",
+                    "{hint}"
+                }
+                input {
+                    r#type: "password",
+                    autofocus: true,
+                    value: passphrase.read().clone(),
+                    oninput: move |e| passphrase.set(e.value()),
+                    onkeydown: move |e| {
+                        if e.key() == Key::Enter {
+                            submit();
+                        }
+                    },
+                    class: "w-full px-3 py-2 mb-3 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
+                }
+                if prompt == PassphrasePrompt::Encrypt {
+                    input {
+                        r#type: "password",
+                        placeholder: "再次输入口令",
+                        value: confirmation.read().clone(),
+                        oninput: move |e| confirmation.set(e.value()),
+                        onkeydown: move |e| {
+                            if e.key() == Key::Enter {
+                                submit();
+                            }
+                        },
+                        class: "w-full px-3 py-2 mb-3 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
+                    }
+                }
+                if let Some(message) = error.read().as_ref() {
+                    p { class: "text-sm text-red-500 mb-3", "{message}" }
+                }
+                button {
+                    class: "w-full px-4 py-2 bg-blue-600 hover:bg-blue-700 text-white rounded-md transition-colors",
+                    onclick: move |_| submit(),
+                    "{action}"
+                }
+            }
+        }
+    }
+}
+
 /// 获取应用上下文钩子
 pub fn use_app_context() -> Signal<AppContext> {
     use_context::<Signal<AppContext>>()
 }
 
+/// 数据版本，任何页面修改数据后递增，依赖数据的资源读取它以便重新加载
+#[derive(Clone, Copy)]
+pub struct DataVersion(pub Signal<u32>);
+
+/// 获取数据版本钩子
+pub fn use_data_version() -> Signal<u32> {
+    use_context::<DataVersion>().0
+}
+
 /// 获取数据库连接钩子
 pub fn use_database() -> Option<Arc<Database>> {
     let context = use_app_context();
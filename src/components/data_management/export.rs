@@ -1,8 +1,19 @@
 //! # 数据导出组件
 //!
 //! 提供数据导出功能，支持多种格式和选项
+//!
+//! JSON、CSV、XML 和 Markdown 从数据库流式写出，导出过程中显示进度并可以取消；
+//! 完整归档把所有模块的数据、设置和附件打包为 ZIP，可以在导入页面恢复
 
+use chrono::{Local, NaiveDate, TimeZone};
 use dioxus::prelude::*;
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::StreamExt;
+use life_tracker::get_app_state_sync;
+use life_tracker::utils::archive::{default_attachments_dir, ArchiveExportOptions, ArchiveExporter};
+use life_tracker::utils::export::stream::{export_storage_to_file, CancelToken, ExportProgress};
+use life_tracker::utils::export::{self as exporter, ExportFormat as FileFormat};
+use std::path::PathBuf;
 
 #[derive(Props, Clone, PartialEq)]
 pub struct DataExportProps {
@@ -53,6 +64,7 @@ impl Default for DateRange {
 enum ExportResult {
     None,
     Success(String),
+    Cancelled,
     Error(String),
 }
 
@@ -72,6 +84,8 @@ pub fn DataExport(props: DataExportProps) -> Element {
     let mut export_options = use_signal(|| ExportOptions::default());
     let mut date_range = use_signal(|| DateRange::default());
     let export_result = use_signal(|| ExportResult::None);
+    let export_progress = use_signal(|| None::<ExportProgress>);
+    let cancel_token = use_signal(|| None::<CancelToken>);
 
     // 导出格式选项
     let export_formats = use_memo(|| {
@@ -101,6 +115,26 @@ pub fn DataExport(props: DataExportProps) -> Element {
                 label: "Markdown".to_string(),
                 description: "文档格式".to_string(),
             },
+            ExportFormat {
+                value: "pdf".to_string(),
+                label: "PDF".to_string(),
+                description: "打印报告".to_string(),
+            },
+            ExportFormat {
+                value: "ics".to_string(),
+                label: "iCalendar".to_string(),
+                description: "日历订阅".to_string(),
+            },
+            ExportFormat {
+                value: "xlsx".to_string(),
+                label: "Excel".to_string(),
+                description: "电子表格".to_string(),
+            },
+            ExportFormat {
+                value: ARCHIVE_FORMAT.to_string(),
+                label: "完整归档".to_string(),
+                description: "ZIP，包含全部数据、设置和附件".to_string(),
+            },
         ]
     });
 
@@ -295,30 +329,46 @@ pub fn DataExport(props: DataExportProps) -> Element {
                             },
                             disabled: is_exporting(),
                             onclick: move |_| {
-                                let mut is_exporting = is_exporting.clone();
-                                let mut export_result = export_result.clone();
+                                let mut is_exporting = is_exporting;
+                                let mut export_result = export_result;
+                                let mut export_progress = export_progress;
+                                let mut cancel_token = cancel_token;
                                 let export_format = export_format.read().clone();
                                 let export_options = export_options.read().clone();
                                 let date_range = date_range.read().clone();
+                                let token = CancelToken::new();
+                                cancel_token.set(Some(token.clone()));
 
                                 spawn(async move {
                                     is_exporting.set(true);
                                     export_result.set(ExportResult::None);
+                                    export_progress.set(None);
+
+                                    // 导出在阻塞线程上运行，进度通过通道传回界面
+                                    let (sender, mut receiver) = unbounded();
+                                    let task = tokio::task::spawn_blocking(move || {
+                                        perform_export(&export_format, &export_options, &date_range, sender, token)
+                                    });
+                                    while let Some(progress) = receiver.next().await {
+                                        export_progress.set(Some(progress));
+                                    }
 
-                                    // 模拟导出过程
-                                    tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
-
-                                    match perform_export(&export_format, &export_options, &date_range).await {
-                                        Ok(message) => {
+                                    match task.await.map_err(anyhow::Error::from).and_then(|result| result) {
+                                        Ok(Some(message)) => {
                                             log::info!("Export completed successfully: {}", message);
                                             export_result.set(ExportResult::Success(message));
                                         }
+                                        Ok(None) => {
+                                            log::info!("Export cancelled");
+                                            export_result.set(ExportResult::Cancelled);
+                                        }
                                         Err(e) => {
                                             export_result.set(ExportResult::Error(format!("导出失败: {}", e)));
                                             log::error!("Export failed: {}", e);
                                         }
                                     }
 
+                                    cancel_token.set(None);
                                     is_exporting.set(false);
                                 });
                             },
@@ -335,6 +385,32 @@ pub fn DataExport(props: DataExportProps) -> Element {
                                 }
                             }
                         }
+
+                        // 导出进度
+                        if is_exporting() {
+                            div { class: "mt-4",
+                                if let Some(progress) = export_progress() {
+                                    div { class: "w-full h-2 bg-gray-200 dark:bg-gray-700 rounded-full overflow-hidden",
+                                        div {
+                                            class: "h-full bg-green-600 transition-all",
+                                            style: "width: {progress.fraction() * 100.0:.0}%",
+                                        }
+                                    }
+                                    p { class: "mt-2 text-xs text-gray-500 dark:text-gray-400",
+                                        "已导出 {progress.processed} / {progress.total} 条时间记录"
+                                    }
+                                }
+                                button {
+                                    class: "mt-3 w-full px-4 py-2 rounded-md text-sm font-medium text-gray-700 dark:text-gray-300 border border-gray-300 dark:border-gray-600 hover:bg-gray-50 dark:hover:bg-gray-700 transition-colors",
+                                    onclick: move |_| {
+                                        if let Some(token) = cancel_token.read().as_ref() {
+                                            token.cancel();
+                                        }
+                                    },
+                                    "取消导出"
+                                }
+                            }
+                        }
                     }
 
                     // 导出结果
@@ -347,6 +423,14 @@ pub fn DataExport(props: DataExportProps) -> Element {
                                 }
                             }
                         },
+                        ExportResult::Cancelled => rsx! {
+                            div { class: "bg-yellow-50 dark:bg-yellow-900/20 border border-yellow-200 dark:border-yellow-800 rounded-lg p-4",
+                                div { class: "flex items-start",
+                                    span { class: "text-yellow-600 dark:text-yellow-400 mr-2 mt-0.5 flex-shrink-0", "⚠️" }
+                                    p { class: "text-sm text-yellow-700 dark:text-yellow-300", "导出已取消，未生成文件" }
+                                }
+                            }
+                        },
                         ExportResult::Error(message) => rsx! {
                             div { class: "bg-red-50 dark:bg-red-900/20 border border-red-200 dark:border-red-800 rounded-lg p-4",
                                 div { class: "flex items-start",
@@ -371,7 +455,9 @@ pub fn DataExport(props: DataExportProps) -> Element {
                                     li { "• 日期范围可用于筛选特定时间段的数据" }
                                     li { "• 导出选项可以自定义包含的数据类型" }
                                     li { "• 支持按日期或分类对数据进行分组" }
-                                    li { "• 导出的文件将保存到您选择的位置" }
+                                    li { "• JSON、CSV、XML 和 Markdown 逐条写出，大量数据导出时可以随时取消" }
+                                    li { "• 完整归档忽略日期范围和导出选项，可以在数据导入页面恢复" }
+                                    li { "• 导出的文件保存在下载目录" }
                                 }
                             }
                         }
@@ -382,45 +468,132 @@ pub fn DataExport(props: DataExportProps) -> Element {
     }
 }
 
-// 模拟导出函数
-async fn perform_export(
-    format: &str,
-    options: &ExportOptions,
-    date_range: &DateRange,
-) -> Result<String, Box<dyn std::error::Error>> {
-    // 模拟导出过程
-    tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
-
-    // 模拟数据处理
-    let mut export_info = Vec::new();
-    export_info.push(format!("导出格式: {}", format.to_uppercase()));
+/// 完整归档在格式列表中的取值
+const ARCHIVE_FORMAT: &str = "archive";
 
-    if options.include_categories {
-        export_info.push("包含分类信息".to_string());
+/// 导出文件保存的目录：下载目录，其次文档目录和应用数据目录
+fn export_dir() -> anyhow::Result<PathBuf> {
+    match dirs::download_dir().or_else(dirs::document_dir) {
+        Some(dir) => Ok(dir),
+        None => Ok(life_tracker::utils::get_app_data_dir()?.join("exports")),
     }
-    if options.include_statistics {
-        export_info.push("包含统计数据".to_string());
+}
+
+/// 解析界面中填写的日期范围，两端都填写时才生效
+fn parse_date_range(
+    date_range: &DateRange,
+) -> anyhow::Result<Option<(chrono::DateTime<Local>, chrono::DateTime<Local>)>> {
+    if date_range.start.is_empty() || date_range.end.is_empty() {
+        return Ok(None);
     }
-    if options.include_metadata {
-        export_info.push("包含元数据".to_string());
+    let start = NaiveDate::parse_from_str(&date_range.start, "%Y-%m-%d")?;
+    let end = NaiveDate::parse_from_str(&date_range.end, "%Y-%m-%d")?;
+    if start > end {
+        anyhow::bail!("开始日期不能晚于结束日期");
     }
+    let local = |time: chrono::NaiveDateTime| {
+        Local
+            .from_local_datetime(&time)
+            .earliest()
+            .ok_or_else(|| anyhow::anyhow!("无效的本地时间: {}", time))
+    };
+    Ok(Some((
+        local(start.and_hms_opt(0, 0, 0).unwrap())?,
+        local(end.and_hms_opt(23, 59, 59).unwrap())?,
+    )))
+}
 
-    if !date_range.start.is_empty() && !date_range.end.is_empty() {
-        export_info.push(format!(
-            "日期范围: {} 至 {}",
-            date_range.start, date_range.end
-        ));
+/// 执行导出，返回结果说明；取消时返回 `None`
+fn perform_export(
+    format: &str,
+    options: &ExportOptions,
+    date_range: &DateRange,
+    progress: UnboundedSender<ExportProgress>,
+    cancel: CancelToken,
+) -> anyhow::Result<Option<String>> {
+    if format == ARCHIVE_FORMAT {
+        return perform_archive_export().map(Some);
     }
 
-    let filename = format!(
+    let database = get_app_state_sync()
+        .get_database()
+        .ok_or_else(|| anyhow::anyhow!("数据库未初始化"))?;
+    let file_format = FileFormat::from_extension(format)
+        .ok_or_else(|| anyhow::anyhow!("不支持的导出格式: {}", format))?;
+    let export_options = exporter::ExportOptions {
+        format: file_format,
+        include_categories: options.include_categories,
+        include_statistics: options.include_statistics,
+        include_metadata: options.include_metadata,
+        group_by_date: options.group_by_date,
+        group_by_category: options.group_by_category,
+        date_range: parse_date_range(date_range)?,
+        ..Default::default()
+    };
+
+    let dir = export_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
         "lifetracker-export-{}.{}",
-        chrono::Utc::now().format("%Y%m%d_%H%M%S"),
-        format
-    );
+        Local::now().format("%Y%m%d_%H%M%S"),
+        file_format.extension()
+    ));
+
+    let result = export_storage_to_file(
+        database.as_ref(),
+        export_options,
+        &path,
+        |update| {
+            let _ = progress.unbounded_send(update);
+        },
+        cancel,
+    )?;
+    if result.cancelled {
+        return Ok(None);
+    }
+
+    Ok(Some(format!(
+        "数据导出成功！\n文件: {}\n共导出 {} 条时间记录",
+        path.display(),
+        result.entries
+    )))
+}
+
+/// 导出完整归档，返回结果说明
+fn perform_archive_export() -> anyhow::Result<String> {
+    let app_state = get_app_state_sync();
+    let database = app_state
+        .get_database()
+        .ok_or_else(|| anyhow::anyhow!("数据库未初始化"))?;
+
+    let dir = export_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "lifetracker-archive-{}.zip",
+        Local::now().format("%Y%m%d_%H%M%S")
+    ));
+
+    let options = ArchiveExportOptions {
+        attachments_dir: Some(default_attachments_dir()?),
+        ..Default::default()
+    };
+    let manifest = match ArchiveExporter::new(database.as_ref())
+        .with_settings(&app_state.config)
+        .export_to_file(&options, &path)
+    {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            let _ = std::fs::remove_file(&path);
+            return Err(error);
+        }
+    };
+    let records: usize = manifest.modules.iter().map(|module| module.count).sum();
 
     Ok(format!(
-        "数据导出成功！\n文件名: {}\n导出内容: {}",
-        filename,
-        export_info.join(", ")
+        "完整归档导出成功！\n文件: {}\n共 {} 个模块、{} 条记录、{} 个附件",
+        path.display(),
+        manifest.modules.len(),
+        records,
+        manifest.attachments.len()
     ))
 }
@@ -1,8 +1,16 @@
 //! # 数据导入组件
 //!
 //! 提供数据导入功能，支持多种数据格式
+//!
+//! 完整归档（ZIP）导入前先读取清单预览各模块的记录数；导入时同名数据合并到现有记录，
+//! ID 冲突的记录分配新 ID，不会覆盖现有数据
 
 use dioxus::prelude::*;
+use life_tracker::get_app_state_sync;
+use life_tracker::utils::archive::{
+    default_attachments_dir, ArchiveImportOptions, ArchiveImporter, ArchiveModule,
+};
+use std::collections::BTreeMap;
 
 #[derive(Props, Clone, PartialEq)]
 pub struct DataImportProps {
@@ -33,6 +41,8 @@ pub fn DataImport(props: DataImportProps) -> Element {
     let import_result = use_signal(|| ImportResult::None);
     let show_confirm_dialog = use_signal(|| false);
     let selected_file_path = use_signal(|| String::new());
+    let mut file_path = use_signal(String::new);
+    let archive_preview = use_signal(|| None::<String>);
 
     // 支持的文件格式
     let supported_formats = use_memo(|| {
@@ -52,6 +62,11 @@ pub fn DataImport(props: DataImportProps) -> Element {
                 extension: "xml".to_string(),
                 description: "标记语言格式".to_string(),
             },
+            FileFormat {
+                name: "完整归档".to_string(),
+                extension: "zip".to_string(),
+                description: "本应用导出的 ZIP 归档".to_string(),
+            },
         ]
     });
 
@@ -59,17 +74,37 @@ pub fn DataImport(props: DataImportProps) -> Element {
     let handle_file_selection = {
         let mut show_confirm_dialog = show_confirm_dialog.clone();
         let mut selected_file_path = selected_file_path.clone();
+        let mut archive_preview = archive_preview.clone();
+        let mut import_result = import_result.clone();
 
         move || {
+            let path = file_path.read().trim().to_string();
             spawn(async move {
-                // 模拟文件选择对话框
-                // 在实际应用中，这里会调用系统文件选择器
-                let file_path = simulate_file_selection().await;
+                if path.is_empty() {
+                    import_result.set(ImportResult::Error("请输入要导入的文件路径".to_string()));
+                    return;
+                }
 
-                if let Some(path) = file_path {
-                    selected_file_path.set(path);
-                    show_confirm_dialog.set(true);
+                // 完整归档先读取清单，在确认对话框中预览
+                if is_archive(&path) {
+                    let preview_path = path.clone();
+                    let preview = tokio::task::spawn_blocking(move || preview_archive(&preview_path))
+                        .await
+                        .map_err(anyhow::Error::from)
+                        .and_then(|result| result);
+                    match preview {
+                        Ok(preview) => archive_preview.set(Some(preview)),
+                        Err(e) => {
+                            import_result.set(ImportResult::Error(format!("无法读取归档: {}", e)));
+                            return;
+                        }
+                    }
+                } else {
+                    archive_preview.set(None);
                 }
+
+                selected_file_path.set(path);
+                show_confirm_dialog.set(true);
             });
         }
     };
@@ -87,10 +122,20 @@ pub fn DataImport(props: DataImportProps) -> Element {
                 import_result.set(ImportResult::None);
                 show_confirm_dialog.set(false);
 
-                // 模拟导入过程
-                tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
-
-                match perform_import(&selected_file_path).await {
+                let result = if is_archive(&selected_file_path) {
+                    tokio::task::spawn_blocking(move || import_archive(&selected_file_path))
+                        .await
+                        .map_err(anyhow::Error::from)
+                        .and_then(|result| result)
+                } else {
+                    // 模拟导入过程
+                    tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+                    perform_import(&selected_file_path)
+                        .await
+                        .map_err(|e| anyhow::anyhow!("{}", e))
+                };
+
+                match result {
                     Ok(message) => {
                         log::info!("Import completed successfully: {}", message);
                         import_result.set(ImportResult::Success(message));
@@ -154,7 +199,8 @@ pub fn DataImport(props: DataImportProps) -> Element {
                                 p { class: "font-medium mb-2", "导入注意事项：" }
                                 ul { class: "list-disc list-inside space-y-1",
                                     li { "导入操作将覆盖现有数据" }
-                                    li { "支持 JSON、CSV、XML 格式" }
+                                    li { "完整归档按模块合并导入，不覆盖现有数据和设置" }
+                                    li { "支持完整归档（ZIP）以及 JSON、CSV、XML 格式" }
                                     li { "建议在导入前先导出备份" }
                                     li { "大文件导入可能需要较长时间" }
                                 }
@@ -176,10 +222,18 @@ pub fn DataImport(props: DataImportProps) -> Element {
                                     "选择数据文件"
                                 }
                                 p { class: "text-sm text-gray-600 dark:text-gray-400 mb-4",
-                                    "支持 JSON、CSV、XML 格式的数据文件"
+                                    "支持完整归档（ZIP）以及 JSON、CSV、XML 格式的数据文件"
                                 }
                             }
 
+                            input {
+                                r#type: "text",
+                                class: "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md shadow-sm bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500",
+                                placeholder: "输入文件的完整路径",
+                                value: file_path.read().clone(),
+                                oninput: move |e| file_path.set(e.value()),
+                            }
+
                             button {
                                 class: if is_importing() {
                                     "w-full px-6 py-3 rounded-lg font-medium text-white transition-colors bg-gray-400 cursor-not-allowed"
@@ -197,7 +251,7 @@ pub fn DataImport(props: DataImportProps) -> Element {
                                 } else {
                                     span { class: "flex items-center justify-center",
                                         span { class: "mr-2", "📁" }
-                                        "选择文件导入"
+                                        "导入文件"
                                     }
                                 }
                             }
@@ -230,7 +284,7 @@ pub fn DataImport(props: DataImportProps) -> Element {
                         h3 { class: "text-lg font-semibold text-gray-900 dark:text-gray-100 mb-4",
                             "支持的文件格式"
                         }
-                        div { class: "grid grid-cols-1 md:grid-cols-3 gap-4",
+                        div { class: "grid grid-cols-1 md:grid-cols-4 gap-4",
                             for format in supported_formats.read().iter() {
                                 div { class: "text-center p-4 bg-gray-50 dark:bg-gray-800 rounded-lg",
                                     div { class: "text-sm font-medium text-gray-900 dark:text-gray-100 mb-1",
@@ -272,8 +326,17 @@ pub fn DataImport(props: DataImportProps) -> Element {
                         h3 { class: "text-lg font-semibold text-gray-900 dark:text-gray-100 mb-4",
                             "确认导入"
                         }
-                        p { class: "text-gray-600 dark:text-gray-400 mb-6",
-                            "导入数据将覆盖现有数据，确定要继续吗？"
+                        if let Some(preview) = archive_preview() {
+                            p { class: "text-gray-600 dark:text-gray-400 mb-2",
+                                "将导入以下数据，同名数据合并，ID 冲突的记录分配新 ID："
+                            }
+                            p { class: "text-sm text-gray-600 dark:text-gray-400 mb-6 whitespace-pre-line",
+                                "{preview}"
+                            }
+                        } else {
+                            p { class: "text-gray-600 dark:text-gray-400 mb-6",
+                                "导入数据将覆盖现有数据，确定要继续吗？"
+                            }
                         }
                         div { class: "flex justify-end space-x-3",
                             button {
@@ -297,13 +360,61 @@ pub fn DataImport(props: DataImportProps) -> Element {
     }
 }
 
-// 模拟文件选择
-async fn simulate_file_selection() -> Option<String> {
-    // 模拟用户选择文件
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+/// 是否为完整归档文件
+fn is_archive(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
 
-    // 模拟选择了一个文件
-    Some("lifetracker-backup-20241215.json".to_string())
+/// 读取归档清单，返回各模块记录数的说明
+fn preview_archive(path: &str) -> anyhow::Result<String> {
+    let manifest = ArchiveImporter::read_manifest(&std::fs::read(path)?)?;
+    let mut lines = vec![format!(
+        "创建于 {}（应用版本 {}）",
+        manifest.created_at.format("%Y-%m-%d %H:%M"),
+        manifest.app_version
+    )];
+    for entry in &manifest.modules {
+        if entry.module != ArchiveModule::Settings {
+            lines.push(format!("{}: {} 条", entry.module.display_name(), entry.count));
+        }
+    }
+    if !manifest.attachments.is_empty() {
+        lines.push(format!("附件: {} 个", manifest.attachments.len()));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// 导入完整归档的数据和附件，返回结果说明
+fn import_archive(path: &str) -> anyhow::Result<String> {
+    let database = get_app_state_sync()
+        .get_database()
+        .ok_or_else(|| anyhow::anyhow!("数据库未初始化"))?;
+    let options = ArchiveImportOptions {
+        // 归档中的设置不在这里应用，避免绕过设置页面覆盖当前配置
+        modules: ArchiveModule::ALL
+            .into_iter()
+            .filter(|module| *module != ArchiveModule::Settings)
+            .collect(),
+        attachments_dir: Some(default_attachments_dir()?),
+        ..Default::default()
+    };
+    let report = ArchiveImporter::new(database.as_ref()).import_from_file(path, &options)?;
+
+    let total = |counts: &BTreeMap<ArchiveModule, usize>| counts.values().sum::<usize>();
+    let mut message = format!(
+        "归档导入成功！\n文件: {}\n新增 {} 条记录，合并 {} 条，跳过 {} 条，导入 {} 个附件",
+        path,
+        total(&report.imported),
+        total(&report.merged),
+        total(&report.skipped),
+        report.attachments
+    );
+    for warning in &report.warnings {
+        message.push_str(&format!("\n⚠️ {}", warning));
+    }
+    Ok(message)
 }
 
 // 模拟导入函数
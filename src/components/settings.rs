@@ -5,10 +5,14 @@
 use super::common::{
     Button, ButtonVariant, Card, Loading,
 };
+use super::app_state_provider::use_database;
 use super::theme_provider::{use_theme_state, use_theme_setter};
 use dioxus::prelude::*;
 use life_tracker::config::{get_default_config_path, AppConfig, ConfigManager};
-use life_tracker::ThemeMode;
+use life_tracker::storage::backup::{BackupPolicy, BackupReason, BackupService};
+use life_tracker::storage::encryption::DatabaseKey;
+use life_tracker::storage::retention::{RetentionPlan, RetentionPolicy, RetentionService};
+use life_tracker::{reschedule_reports, ThemeMode};
 
 #[derive(Props, Clone, PartialEq)]
 pub struct SettingsPageProps {
@@ -28,6 +32,20 @@ pub fn SettingsPage(props: SettingsPageProps) -> Element {
     let theme_state = use_theme_state();
     let theme_color = use_signal(|| "blue".to_string());
 
+    // 数据库口令修改
+    let database = use_database();
+    let database_encrypted = database.as_ref().map_or(false, |db| db.is_encrypted());
+    let mut old_passphrase = use_signal(String::new);
+    let mut new_passphrase = use_signal(String::new);
+    let mut confirm_passphrase = use_signal(String::new);
+    let mut passphrase_message = use_signal(|| None::<String>);
+
+    // 过期数据清理：先预览，确认后执行
+    let preview_database = database.clone();
+    let cleanup_database = database.clone();
+    let mut retention_plan = use_signal(|| None::<RetentionPlan>);
+    let mut retention_message = use_signal(|| None::<String>);
+
     // 加载配置
     let load_config = {
         let mut config = config.clone();
@@ -145,11 +163,22 @@ pub fn SettingsPage(props: SettingsPageProps) -> Element {
                         current_config.data.auto_backup = val;
                     }
                 }
+                "data.encrypt_database" => {
+                    if let Some(val) = value.as_bool() {
+                        current_config.data.encrypt_database = val;
+                    }
+                }
                 "data.data_retention_days" => {
                     if let Some(val) = value.as_i64() {
                         current_config.data.data_retention_days = Some(val as u32);
                     }
                 }
+                "data.cleanup_on_startup" => {
+                    if let Some(val) = value.as_bool() {
+                        current_config.data.auto_cleanup = val;
+                        current_config.data.cleanup_on_startup = val;
+                    }
+                }
                 _ => {}
             }
 
@@ -463,6 +492,85 @@ pub fn SettingsPage(props: SettingsPageProps) -> Element {
                             }
                         }
 
+                        // 数据库加密
+                        div { class: "flex items-center justify-between",
+                            div {
+                                label { class: "text-sm font-medium text-gray-700 dark:text-gray-300",
+                                    "加密数据库"
+                                }
+                                p { class: "text-sm text-gray-500 dark:text-gray-400",
+                                    "使用口令加密本地数据库，下次启动时输入口令完成转换"
+                                }
+                            }
+                            input {
+                                r#type: "checkbox",
+                                checked: config.read().data.encrypt_database,
+                                onchange: move |e| {
+                                    handle_config_update("data.encrypt_database".to_string(), SettingValue::Boolean(e.value() == "true"));
+                                },
+                                class: "h-4 w-4 text-blue-600 rounded border-gray-300 focus:ring-blue-500"
+                            }
+                        }
+
+                        // 修改数据库口令
+                        if database_encrypted {
+                            div { class: "space-y-2",
+                                label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300",
+                                    "修改数据库口令"
+                                }
+                                input {
+                                    r#type: "password",
+                                    placeholder: "当前口令",
+                                    value: old_passphrase.read().clone(),
+                                    oninput: move |e| old_passphrase.set(e.value()),
+                                    class: "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
+                                }
+                                input {
+                                    r#type: "password",
+                                    placeholder: "新口令",
+                                    value: new_passphrase.read().clone(),
+                                    oninput: move |e| new_passphrase.set(e.value()),
+                                    class: "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
+                                }
+                                input {
+                                    r#type: "password",
+                                    placeholder: "确认新口令",
+                                    value: confirm_passphrase.read().clone(),
+                                    oninput: move |e| confirm_passphrase.set(e.value()),
+                                    class: "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
+                                }
+                                Button {
+                                    variant: ButtonVariant::Secondary,
+                                    onclick: move |_| {
+                                        let Some(db) = database.clone() else {
+                                            return;
+                                        };
+                                        let result = DatabaseKey::new(old_passphrase.read().clone())
+                                            .and_then(|old_key| {
+                                                let new_key = DatabaseKey::confirmed(
+                                                    new_passphrase.read().clone(),
+                                                    &confirm_passphrase.read(),
+                                                )?;
+                                                db.change_passphrase(&old_key, new_key)
+                                            });
+                                        match result {
+                                            Ok(_) => {
+                                                old_passphrase.set(String::new());
+                                                new_passphrase.set(String::new());
+                                                confirm_passphrase.set(String::new());
+                                                passphrase_message.set(Some("口令已修改".to_string()));
+                                            }
+                                            Err(e) => passphrase_message.set(Some(format!("修改口令失败: {}", e))),
+                                        }
+                                    },
+                                    "修改口令"
+                                }
+                                if let Some(message) = passphrase_message.read().as_ref() {
+                                    p { class: "text-sm text-gray-500 dark:text-gray-400", "{message}" }
+                                }
+                            }
+                        }
+
                         // 数据保留天数
                         div {
                             label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300 mb-2",
@@ -481,6 +589,94 @@ pub fn SettingsPage(props: SettingsPageProps) -> Element {
                                 class: "w-full px-3 py-2 border border-gray-300 dark:border-gray-600 rounded-md bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
                             }
                         }
+
+                        // 清理过期数据
+                        div { class: "space-y-2",
+                            div {
+                                label { class: "text-sm font-medium text-gray-700 dark:text-gray-300",
+                                    "清理过期数据"
+                                }
+                                p { class: "text-sm text-gray-500 dark:text-gray-400",
+                                    "按保留规则预览将被删除或归档的数据，确认后再执行"
+                                }
+                            }
+                            Button {
+                                variant: ButtonVariant::Secondary,
+                                onclick: move |_| {
+                                    let Some(db) = preview_database.clone() else {
+                                        return;
+                                    };
+                                    let service = RetentionService::new(RetentionPolicy::from(&config.read().data));
+                                    retention_message.set(None);
+                                    match service.plan(&db) {
+                                        Ok(plan) => retention_plan.set(Some(plan)),
+                                        Err(e) => {
+                                            retention_plan.set(None);
+                                            retention_message.set(Some(format!("生成清理预览失败: {}", e)));
+                                        }
+                                    }
+                                },
+                                "预览清理"
+                            }
+                            if let Some(plan) = retention_plan.read().as_ref() {
+                                pre { class: "text-sm whitespace-pre-wrap text-gray-700 dark:text-gray-300 bg-gray-50 dark:bg-gray-900 rounded-md p-3",
+                                    "{plan.summary()}"
+                                }
+                                if !plan.is_empty() {
+                                    Button {
+                                        variant: ButtonVariant::Danger,
+                                        onclick: move |_| {
+                                            let Some(db) = cleanup_database.clone() else {
+                                                return;
+                                            };
+                                            let Some(plan) = retention_plan.read().clone() else {
+                                                return;
+                                            };
+                                            let data_config = config.read().data.clone();
+                                            let backup = BackupService::new(BackupPolicy::from(&data_config));
+                                            let result = if data_config.auto_backup {
+                                                backup.create_backup(&db, BackupReason::PreCleanup).map(|_| ())
+                                            } else {
+                                                Ok(())
+                                            }
+                                            .and_then(|_| RetentionService::new(RetentionPolicy::from(&data_config)).execute(&db, &plan));
+                                            retention_plan.set(None);
+                                            match result {
+                                                Ok(report) => {
+                                                    let removed: u64 = report.items.iter().map(|item| item.removed).sum();
+                                                    retention_message.set(Some(format!("已清理 {} 条记录", removed)));
+                                                }
+                                                Err(e) => retention_message.set(Some(format!("清理失败: {}", e))),
+                                            }
+                                        },
+                                        "确认清理"
+                                    }
+                                }
+                            }
+                            if let Some(message) = retention_message.read().as_ref() {
+                                p { class: "text-sm text-gray-500 dark:text-gray-400", "{message}" }
+                            }
+                        }
+
+                        // 启动时自动清理
+                        div { class: "flex items-center justify-between",
+                            div {
+                                label { class: "text-sm font-medium text-gray-700 dark:text-gray-300",
+                                    "启动时自动清理"
+                                }
+                                p { class: "text-sm text-gray-500 dark:text-gray-400",
+                                    "不经预览确认，每次启动时直接按保留规则清理过期数据"
+                                }
+                            }
+                            input {
+                                r#type: "checkbox",
+                                checked: config.read().data.cleanup_on_startup,
+                                onchange: move |e| {
+                                    handle_config_update("data.cleanup_on_startup".to_string(), SettingValue::Boolean(e.value() == "true"));
+                                },
+                                class: "h-4 w-4 text-blue-600 rounded border-gray-300 focus:ring-blue-500"
+                            }
+                        }
                     }
                 }
             }
@@ -538,5 +734,9 @@ async fn save_app_config(config: &AppConfig) -> Result<(), Box<dyn std::error::E
         .save()
         .map_err(|e| format!("Failed to save config: {}", e))?;
 
+    // 按新的定时报告设置重新调度
+    let scheduled = reschedule_reports(&config.data.scheduled_reports).await;
+    log::info!("Scheduled reports updated, {} reports scheduled", scheduled);
+
     Ok(())
 }
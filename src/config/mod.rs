@@ -118,16 +118,162 @@ pub struct DataConfig {
     pub backup_retention: u32,
     /// 备份目录
     pub backup_directory: PathBuf,
+    /// 加密数据库（下次启动时按口令完成明文与加密数据库的转换）
+    #[serde(default)]
+    pub encrypt_database: bool,
     /// 数据导出格式
     pub export_format: String,
     /// 自动清理旧数据
     pub auto_cleanup: bool,
+    /// 启动时不经预览确认直接清理（需同时开启自动清理）
+    #[serde(default)]
+    pub cleanup_on_startup: bool,
     /// 数据保留天数
     pub data_retention_days: Option<u32>,
+    /// 按数据类型的保留规则（未配置的类型按 data_retention_days 归档）
+    #[serde(default)]
+    pub retention_rules: Vec<RetentionRule>,
+    /// 回收站保留天数（超过后自动彻底删除，0 表示不自动清空）
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// 保存的撤销历史条数
+    #[serde(default = "default_undo_history_limit")]
+    pub undo_history_limit: usize,
+    /// 定时生成的报告
+    #[serde(default)]
+    pub scheduled_reports: Vec<ScheduledReport>,
     /// 同步配置
     pub sync: SyncConfig,
 }
 
+/// 受保留策略管理的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionDataType {
+    /// 时间记录
+    TimeEntries,
+    /// 已完成的任务
+    CompletedTasks,
+    /// 交易记录
+    Transactions,
+}
+
+impl RetentionDataType {
+    /// 所有数据类型
+    pub const ALL: [RetentionDataType; 3] = [
+        RetentionDataType::TimeEntries,
+        RetentionDataType::CompletedTasks,
+        RetentionDataType::Transactions,
+    ];
+}
+
+/// 超出保留期限的数据的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// 直接删除
+    Delete,
+    /// 移入归档数据库
+    Archive,
+    /// 保留不处理
+    Keep,
+}
+
+/// 数据保留规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionRule {
+    /// 数据类型
+    pub data_type: RetentionDataType,
+    /// 处理方式
+    pub action: RetentionAction,
+    /// 保留天数（为空时使用 data_retention_days）
+    pub retention_days: Option<u32>,
+}
+
+/// 报告包含的数据模块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportModule {
+    /// 时间记录明细
+    TimeEntries,
+    /// 时间统计
+    Statistics,
+    /// 期间内完成或到期的任务
+    Tasks,
+    /// 财务汇总
+    Finance,
+}
+
+impl ReportModule {
+    /// 所有数据模块
+    pub const ALL: [ReportModule; 4] = [
+        ReportModule::TimeEntries,
+        ReportModule::Statistics,
+        ReportModule::Tasks,
+        ReportModule::Finance,
+    ];
+}
+
+/// 相对于运行时间的报告日期范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRange {
+    /// 今天
+    Today,
+    /// 昨天
+    Yesterday,
+    /// 本周（周一至今）
+    ThisWeek,
+    /// 上周（周一至周日）
+    LastWeek,
+    /// 本月（1 日至今）
+    ThisMonth,
+    /// 上个月
+    LastMonth,
+    /// 截至昨天的最近 N 天
+    LastDays(u32),
+}
+
+/// 定时报告定义
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledReport {
+    /// 报告ID
+    pub id: String,
+    /// 报告名称
+    pub name: String,
+    /// 是否启用
+    pub enabled: bool,
+    /// cron 表达式（含秒字段，按本地时间），如 "0 0 8 * * Mon"
+    pub schedule: String,
+    /// 包含的数据模块
+    pub modules: Vec<ReportModule>,
+    /// 日期范围
+    pub range: ReportRange,
+    /// 输出格式（html、markdown 等导出格式的扩展名）
+    pub format: String,
+    /// 输出目录
+    pub destination: PathBuf,
+    /// 文件名模板，支持 {name}、{id}、{start}、{end}、{date}、{week} 和 {ext}
+    pub filename_template: String,
+}
+
+impl ScheduledReport {
+    /// 每周一早上生成上周回顾
+    pub fn weekly_review(destination: PathBuf) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "周回顾".to_string(),
+            enabled: true,
+            schedule: "0 0 8 * * Mon".to_string(),
+            modules: ReportModule::ALL.to_vec(),
+            range: ReportRange::LastWeek,
+            format: "html".to_string(),
+            destination,
+            filename_template: "{name}-{week}.{ext}".to_string(),
+        }
+    }
+}
+
 /// 同步配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
@@ -272,6 +418,16 @@ impl Default for NotificationConfig {
     }
 }
 
+/// 回收站默认保留天数
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+/// 默认保存的撤销历史条数
+fn default_undo_history_limit() -> usize {
+    crate::core::command::DEFAULT_HISTORY_LIMIT
+}
+
 impl Default for DataConfig {
     fn default() -> Self {
         let app_dir = crate::utils::get_app_data_dir().unwrap_or_else(|_| PathBuf::from("."));
@@ -282,9 +438,15 @@ impl Default for DataConfig {
             backup_interval: 7,
             backup_retention: 30,
             backup_directory: app_dir.join("backups"),
+            encrypt_database: false,
             export_format: "json".to_string(),
             auto_cleanup: false,
+            cleanup_on_startup: false,
             data_retention_days: None,
+            retention_rules: Vec::new(),
+            trash_retention_days: default_trash_retention_days(),
+            undo_history_limit: default_undo_history_limit(),
+            scheduled_reports: Vec::new(),
             sync: SyncConfig::default(),
         }
     }
@@ -459,6 +621,37 @@ impl ConfigManager {
             }
         }
 
+        // 验证数据保留设置
+        if self.config.data.data_retention_days == Some(0)
+            || self
+                .config
+                .data
+                .retention_rules
+                .iter()
+                .any(|rule| rule.retention_days == Some(0))
+        {
+            errors.push("数据保留天数不能为0".to_string());
+        }
+
+        // 验证定时报告
+        for report in &self.config.data.scheduled_reports {
+            if report.schedule.trim().is_empty() || report.filename_template.trim().is_empty() {
+                errors.push(format!("定时报告 {} 缺少执行时间或文件名模板", report.name));
+            }
+            if crate::utils::export::ExportFormat::from_extension(&report.format).is_none() {
+                errors.push(format!(
+                    "定时报告 {} 的格式不受支持: {}",
+                    report.name, report.format
+                ));
+            }
+            if report.modules.is_empty() {
+                errors.push(format!("定时报告 {} 未选择数据模块", report.name));
+            }
+            if report.range == ReportRange::LastDays(0) {
+                errors.push(format!("定时报告 {} 的天数不能为0", report.name));
+            }
+        }
+
         // 验证提醒间隔
         if let Some(interval) = self.config.general.work_reminder_interval {
             if interval == 0 || interval > 480 {
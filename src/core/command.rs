@@ -0,0 +1,783 @@
+//! # 撤销/重做命令模块
+//!
+//! 在界面和数据库之间记录每一次数据修改（新建、更新、删除），保存修改前后的行数据，
+//! 支持多级撤销和重做，并将最近的命令保存到设置表中，重启后仍可撤销。
+//!
+//! 目前界面中只有任务和分类页面修改数据，均通过命令管理器完成；时间记录、笔记和交易的
+//! 编辑界面尚未实现，实现时应调用这里对应的方法，而不是直接写入数据库。
+
+use crate::errors::{AppError, Result};
+use crate::storage::database::{TrashEntityType, TrashRepository};
+use crate::storage::models::{CategoryInsert, CategoryUpdate, Note, NoteUpdate, TimeEntryInsert};
+use crate::storage::task_models::{TaskInsert, TaskUpdate};
+use crate::storage::{Database, TransactionInsert, TransactionUpdate};
+use chrono::{DateTime, Local};
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// 命令历史在设置表中的键
+const HISTORY_SETTING_KEY: &str = "command_history";
+
+/// 默认保留的命令数量
+pub const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// 数据行快照（列名到值）
+pub type RowSnapshot = Map<String, Value>;
+
+/// 命令类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandKind {
+    /// 新建
+    Create,
+    /// 更新
+    Update,
+    /// 删除（移入回收站）
+    Delete,
+}
+
+impl CommandKind {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            CommandKind::Create => "新建",
+            CommandKind::Update => "修改",
+            CommandKind::Delete => "删除",
+        }
+    }
+}
+
+/// 可撤销的数据修改命令
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Command {
+    /// 命令ID
+    pub id: Uuid,
+    /// 命令类型
+    pub kind: CommandKind,
+    /// 数据类型
+    pub entity_type: TrashEntityType,
+    /// 数据ID
+    pub entity_id: Uuid,
+    /// 修改前的行数据（新建时为空）
+    pub before: Option<RowSnapshot>,
+    /// 修改后的行数据
+    pub after: Option<RowSnapshot>,
+    /// 执行时间
+    pub executed_at: DateTime<Local>,
+}
+
+impl Command {
+    /// 命令描述，例如“删除任务”
+    pub fn description(&self) -> String {
+        format!(
+            "{}{}",
+            self.kind.display_name(),
+            self.entity_type.display_name()
+        )
+    }
+}
+
+/// 撤销和重做栈
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandHistory {
+    /// 可撤销的命令（最新的在末尾）
+    pub undo_stack: VecDeque<Command>,
+    /// 可重做的命令（最新撤销的在末尾）
+    pub redo_stack: Vec<Command>,
+}
+
+/// 命令管理器
+///
+/// 所有需要支持撤销的数据修改都通过它执行
+#[derive(Debug)]
+pub struct CommandManager {
+    /// 数据库
+    database: Arc<Database>,
+    /// 命令历史
+    history: Mutex<CommandHistory>,
+    /// 保留的命令数量
+    limit: usize,
+}
+
+impl CommandManager {
+    /// 创建命令管理器并加载上次保存的命令历史
+    pub fn new(database: Arc<Database>, limit: usize) -> Result<Self> {
+        let history = match database.get_setting(HISTORY_SETTING_KEY)? {
+            Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                log::warn!("Failed to parse command history, starting empty: {}", e);
+                CommandHistory::default()
+            }),
+            None => CommandHistory::default(),
+        };
+
+        Ok(Self {
+            database,
+            history: Mutex::new(history),
+            limit: limit.max(1),
+        })
+    }
+
+    /// 获取数据库
+    pub fn database(&self) -> &Arc<Database> {
+        &self.database
+    }
+
+    /// 是否可以撤销
+    pub fn can_undo(&self) -> bool {
+        self.with_history(|history| !history.undo_stack.is_empty())
+    }
+
+    /// 是否可以重做
+    pub fn can_redo(&self) -> bool {
+        self.with_history(|history| !history.redo_stack.is_empty())
+    }
+
+    /// 下一个可撤销的命令
+    pub fn peek_undo(&self) -> Option<Command> {
+        self.with_history(|history| history.undo_stack.back().cloned())
+    }
+
+    /// 下一个可重做的命令
+    pub fn peek_redo(&self) -> Option<Command> {
+        self.with_history(|history| history.redo_stack.last().cloned())
+    }
+
+    /// 当前命令历史
+    pub fn history(&self) -> CommandHistory {
+        self.with_history(|history| history.clone())
+    }
+
+    // ==================== 记录命令 ====================
+
+    /// 执行新建操作并记录命令
+    pub fn create<T, F>(&self, entity_type: TrashEntityType, id: Uuid, operation: F) -> Result<T>
+    where
+        F: FnOnce(&Database) -> Result<T>,
+    {
+        let result = operation(&self.database)?;
+        let after = self.snapshot(entity_type, id)?;
+        self.record(CommandKind::Create, entity_type, id, None, after)?;
+        Ok(result)
+    }
+
+    /// 执行更新操作并记录命令
+    pub fn update<T, F>(&self, entity_type: TrashEntityType, id: Uuid, operation: F) -> Result<T>
+    where
+        F: FnOnce(&Database) -> Result<T>,
+    {
+        let before = self.snapshot(entity_type, id)?;
+        let result = operation(&self.database)?;
+        let after = self.snapshot(entity_type, id)?;
+        if before != after {
+            self.record(CommandKind::Update, entity_type, id, before, after)?;
+        }
+        Ok(result)
+    }
+
+    /// 将数据移入回收站并记录命令
+    pub fn delete(&self, entity_type: TrashEntityType, id: Uuid) -> Result<()> {
+        let before = self.snapshot(entity_type, id)?;
+        self.trash().move_to_trash(entity_type, id)?;
+        let after = self.snapshot(entity_type, id)?;
+        self.record(CommandKind::Delete, entity_type, id, before, after)
+    }
+
+    // ==================== 常用操作 ====================
+
+    /// 新建任务
+    pub fn insert_task(&self, task: &TaskInsert) -> Result<i64> {
+        self.create(TrashEntityType::Task, task.id, |db| db.insert_task(task))
+    }
+
+    /// 更新任务
+    pub fn update_task(&self, id: Uuid, task: &TaskUpdate) -> Result<()> {
+        self.update(TrashEntityType::Task, id, |db| db.update_task(id, task))
+    }
+
+    /// 删除任务
+    pub fn delete_task(&self, id: Uuid) -> Result<()> {
+        self.delete(TrashEntityType::Task, id)
+    }
+
+    /// 新建时间记录
+    pub fn insert_time_entry(&self, entry: &TimeEntryInsert) -> Result<i64> {
+        self.create(TrashEntityType::TimeEntry, entry.id, |db| {
+            db.insert_time_entry(entry)
+        })
+    }
+
+    /// 更新时间记录
+    pub fn update_time_entry(&self, id: Uuid, entry: &TimeEntryInsert) -> Result<()> {
+        self.update(TrashEntityType::TimeEntry, id, |db| {
+            db.update_time_entry(id, entry)
+        })
+    }
+
+    /// 删除时间记录
+    pub fn delete_time_entry(&self, id: Uuid) -> Result<()> {
+        self.delete(TrashEntityType::TimeEntry, id)
+    }
+
+    /// 新建笔记
+    pub fn insert_note(&self, note: &Note) -> Result<i64> {
+        self.create(TrashEntityType::Note, note.id, |db| db.insert_note(note))
+    }
+
+    /// 更新笔记
+    pub fn update_note(&self, id: Uuid, update: &NoteUpdate) -> Result<()> {
+        self.update(TrashEntityType::Note, id, |db| db.update_note(id, update))
+    }
+
+    /// 删除笔记
+    pub fn delete_note(&self, id: Uuid) -> Result<()> {
+        self.delete(TrashEntityType::Note, id)
+    }
+
+    /// 新建分类
+    pub fn insert_category(&self, category: &CategoryInsert) -> Result<i64> {
+        self.create(TrashEntityType::Category, category.id, |db| {
+            db.insert_category(category)
+        })
+    }
+
+    /// 更新分类
+    pub fn update_category(&self, id: Uuid, update: &CategoryUpdate) -> Result<()> {
+        self.update(TrashEntityType::Category, id, |db| {
+            db.update_category(id, update)
+        })
+    }
+
+    /// 删除分类
+    pub fn delete_category(&self, id: Uuid) -> Result<()> {
+        self.delete(TrashEntityType::Category, id)
+    }
+
+    /// 新建交易记录
+    pub fn insert_transaction(&self, transaction: &TransactionInsert) -> Result<i64> {
+        self.create(TrashEntityType::Transaction, transaction.id, |db| {
+            db.insert_transaction(transaction)
+        })
+    }
+
+    /// 更新交易记录
+    pub fn update_transaction(&self, id: Uuid, update: &TransactionUpdate) -> Result<()> {
+        self.update(TrashEntityType::Transaction, id, |db| {
+            db.update_transaction(id, update)
+        })
+    }
+
+    /// 删除交易记录
+    pub fn delete_transaction(&self, id: Uuid) -> Result<()> {
+        self.delete(TrashEntityType::Transaction, id)
+    }
+
+    // ==================== 撤销和重做 ====================
+
+    /// 撤销最近的命令，没有可撤销的命令时返回 None
+    ///
+    /// 数据在命令之后又被修改过时放弃该命令并返回错误
+    pub fn undo(&self) -> Result<Option<Command>> {
+        let Some(command) = self.with_history(|history| history.undo_stack.pop_back()) else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.revert(&command) {
+            self.persist()?;
+            return Err(e);
+        }
+
+        self.with_history(|history| history.redo_stack.push(command.clone()));
+        self.persist()?;
+        log::info!("Undo: {}", command.description());
+        Ok(Some(command))
+    }
+
+    /// 重做最近撤销的命令，没有可重做的命令时返回 None
+    pub fn redo(&self) -> Result<Option<Command>> {
+        let Some(command) = self.with_history(|history| history.redo_stack.pop()) else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.reapply(&command) {
+            self.persist()?;
+            return Err(e);
+        }
+
+        self.with_history(|history| history.undo_stack.push_back(command.clone()));
+        self.persist()?;
+        log::info!("Redo: {}", command.description());
+        Ok(Some(command))
+    }
+
+    /// 清空命令历史
+    pub fn clear(&self) -> Result<()> {
+        self.with_history(|history| *history = CommandHistory::default());
+        self.persist()
+    }
+
+    /// 撤销命令：将数据恢复到命令执行前的状态
+    fn revert(&self, command: &Command) -> Result<()> {
+        self.ensure_current(command, command.after.as_ref())?;
+
+        match command.kind {
+            CommandKind::Create => self.delete_row(command.entity_type, command.entity_id),
+            CommandKind::Update => self.write_row(command.entity_type, command.before.as_ref()),
+            CommandKind::Delete => self.trash().restore(command.entity_type, command.entity_id),
+        }
+    }
+
+    /// 重做命令：将数据恢复到命令执行后的状态
+    fn reapply(&self, command: &Command) -> Result<()> {
+        self.ensure_current(command, command.before.as_ref())?;
+
+        match command.kind {
+            CommandKind::Create => self.insert_row(command.entity_type, command.after.as_ref()),
+            CommandKind::Update => self.write_row(command.entity_type, command.after.as_ref()),
+            CommandKind::Delete => self
+                .trash()
+                .move_to_trash(command.entity_type, command.entity_id),
+        }
+    }
+
+    /// 确认数据仍是命令记录的状态，防止覆盖之后的修改
+    fn ensure_current(&self, command: &Command, expected: Option<&RowSnapshot>) -> Result<()> {
+        let current = self.snapshot(command.entity_type, command.entity_id)?;
+        if current.as_ref() != expected {
+            return Err(AppError::Business(format!(
+                "{}之后数据已被修改，无法撤销或重做",
+                command.description()
+            )));
+        }
+        Ok(())
+    }
+
+    fn record(
+        &self,
+        kind: CommandKind,
+        entity_type: TrashEntityType,
+        entity_id: Uuid,
+        before: Option<RowSnapshot>,
+        after: Option<RowSnapshot>,
+    ) -> Result<()> {
+        let command = Command {
+            id: Uuid::new_v4(),
+            kind,
+            entity_type,
+            entity_id,
+            before,
+            after,
+            executed_at: Local::now(),
+        };
+
+        self.with_history(|history| {
+            history.undo_stack.push_back(command);
+            while history.undo_stack.len() > self.limit {
+                history.undo_stack.pop_front();
+            }
+            history.redo_stack.clear();
+        });
+        self.persist()
+    }
+
+    /// 将命令历史保存到设置表
+    fn persist(&self) -> Result<()> {
+        let json = serde_json::to_string(&self.history())?;
+        self.database.set_setting(HISTORY_SETTING_KEY, &json)
+    }
+
+    fn with_history<T>(&self, f: impl FnOnce(&mut CommandHistory) -> T) -> T {
+        let mut history = self
+            .history
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut history)
+    }
+
+    fn trash(&self) -> TrashRepository<'_> {
+        self.database.trash()
+    }
+
+    // ==================== 行数据读写 ====================
+
+    /// 读取数据行的快照（包括回收站中的数据）
+    fn snapshot(&self, entity_type: TrashEntityType, id: Uuid) -> Result<Option<RowSnapshot>> {
+        let sql = format!("SELECT * FROM {} WHERE id = ?1", entity_type.table());
+        self.database.get_connection()?.read(|conn| {
+            let snapshot = conn
+                .query_row(&sql, [id.to_string()], |row| {
+                    let mut snapshot = RowSnapshot::new();
+                    for (index, column) in row.as_ref().column_names().into_iter().enumerate() {
+                        snapshot.insert(column.to_string(), json_from_sql(row.get_ref(index)?));
+                    }
+                    Ok(snapshot)
+                })
+                .optional()?;
+            Ok(snapshot)
+        })
+    }
+
+    /// 按快照更新已有的数据行
+    fn write_row(
+        &self,
+        entity_type: TrashEntityType,
+        snapshot: Option<&RowSnapshot>,
+    ) -> Result<()> {
+        let snapshot = required_snapshot(snapshot)?;
+        let id = snapshot_id(snapshot)?;
+        let columns: Vec<&String> = snapshot.keys().filter(|column| *column != "id").collect();
+        let assignments: Vec<String> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| format!("{} = ?{}", column, index + 1))
+            .collect();
+        let sql = format!(
+            "UPDATE {} SET {} WHERE id = ?{}",
+            entity_type.table(),
+            assignments.join(", "),
+            columns.len() + 1
+        );
+
+        let mut values: Vec<SqlValue> = columns
+            .iter()
+            .map(|column| sql_from_json(&snapshot[column.as_str()]))
+            .collect();
+        values.push(SqlValue::Text(id));
+
+        self.database.get_connection()?.write(|conn| {
+            conn.execute(&sql, params_from_iter(values))?;
+            Ok(())
+        })
+    }
+
+    /// 按快照重新插入数据行
+    fn insert_row(
+        &self,
+        entity_type: TrashEntityType,
+        snapshot: Option<&RowSnapshot>,
+    ) -> Result<()> {
+        let snapshot = required_snapshot(snapshot)?;
+        let columns: Vec<&str> = snapshot.keys().map(String::as_str).collect();
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            entity_type.table(),
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let values: Vec<SqlValue> = snapshot.values().map(sql_from_json).collect();
+
+        self.database.get_connection()?.write(|conn| {
+            conn.execute(&sql, params_from_iter(values))?;
+            Ok(())
+        })
+    }
+
+    /// 删除新建的数据行
+    fn delete_row(&self, entity_type: TrashEntityType, id: Uuid) -> Result<()> {
+        let sql = format!("DELETE FROM {} WHERE id = ?1", entity_type.table());
+        self.database.get_connection()?.write(|conn| {
+            conn.execute(&sql, [id.to_string()])?;
+            Ok(())
+        })
+    }
+}
+
+fn required_snapshot(snapshot: Option<&RowSnapshot>) -> Result<&RowSnapshot> {
+    snapshot.ok_or_else(|| AppError::Business("命令缺少行数据，无法撤销或重做".to_string()))
+}
+
+fn snapshot_id(snapshot: &RowSnapshot) -> Result<String> {
+    snapshot
+        .get("id")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Business("命令行数据缺少ID".to_string()))
+}
+
+/// SQLite 值转为 JSON 值
+fn json_from_sql(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::from(bytes.to_vec()),
+    }
+}
+
+/// JSON 值转为 SQLite 值
+fn sql_from_json(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(items) => SqlValue::Blob(
+            items
+                .iter()
+                .filter_map(|item| item.as_u64().map(|b| b as u8))
+                .collect(),
+        ),
+        Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::{new_task, temp_database};
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, Arc<Database>) {
+        let (temp_dir, database) = temp_database();
+        (temp_dir, Arc::new(database))
+    }
+
+    fn rename(name: &str) -> TaskUpdate {
+        TaskUpdate {
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_undo_redo_task_commands() {
+        let (_temp_dir, database) = setup();
+        let commands = CommandManager::new(database.clone(), DEFAULT_HISTORY_LIMIT).unwrap();
+        let task = new_task("原任务");
+
+        commands.insert_task(&task).unwrap();
+        commands.update_task(task.id, &rename("新名称")).unwrap();
+        commands.delete_task(task.id).unwrap();
+        assert!(database.get_task_by_id(task.id).unwrap().is_none());
+
+        // 撤销删除和修改
+        commands.undo().unwrap();
+        assert_eq!(
+            database.get_task_by_id(task.id).unwrap().unwrap().name,
+            "新名称"
+        );
+        commands.undo().unwrap();
+        assert_eq!(
+            database.get_task_by_id(task.id).unwrap().unwrap().name,
+            "原任务"
+        );
+
+        // 重做修改，再撤销到新建之前
+        commands.redo().unwrap();
+        assert_eq!(
+            database.get_task_by_id(task.id).unwrap().unwrap().name,
+            "新名称"
+        );
+        commands.undo().unwrap();
+        commands.undo().unwrap();
+        assert!(database.get_task_by_id(task.id).unwrap().is_none());
+        assert!(!commands.can_undo());
+
+        commands.redo().unwrap();
+        assert_eq!(
+            database.get_task_by_id(task.id).unwrap().unwrap().name,
+            "原任务"
+        );
+    }
+
+    #[test]
+    fn test_undo_redo_category_and_transaction_commands() {
+        use crate::storage::{AccountInsert, AccountType, TransactionStatus, TransactionType};
+
+        let (_temp_dir, database) = setup();
+        let commands = CommandManager::new(database.clone(), DEFAULT_HISTORY_LIMIT).unwrap();
+        let category = CategoryInsert {
+            id: Uuid::new_v4(),
+            name: "原分类".to_string(),
+            description: None,
+            color: "#3B82F6".to_string(),
+            icon: "💼".to_string(),
+            daily_target_seconds: None,
+            weekly_target_seconds: None,
+            is_active: true,
+            sort_order: 0,
+            parent_id: None,
+            created_at: Local::now(),
+        };
+        let category_name = |database: &Database| {
+            database
+                .get_all_categories()
+                .unwrap()
+                .into_iter()
+                .find(|c| c.id == category.id)
+                .map(|c| c.name)
+        };
+
+        commands.insert_category(&category).unwrap();
+        let rename = CategoryUpdate {
+            name: Some("新分类".to_string()),
+            ..Default::default()
+        };
+        commands.update_category(category.id, &rename).unwrap();
+        assert_eq!(category_name(&database).as_deref(), Some("新分类"));
+        commands.undo().unwrap();
+        assert_eq!(category_name(&database).as_deref(), Some("原分类"));
+        commands.undo().unwrap();
+        assert_eq!(category_name(&database), None);
+        commands.redo().unwrap();
+        assert_eq!(category_name(&database).as_deref(), Some("原分类"));
+
+        let account = AccountInsert {
+            id: Uuid::new_v4(),
+            name: "现金".to_string(),
+            account_type: AccountType::Cash,
+            currency: "CNY".to_string(),
+            balance: 0.0,
+            initial_balance: 0.0,
+            description: None,
+            is_active: true,
+            is_default: true,
+            created_at: Local::now(),
+        };
+        database.insert_account(&account).unwrap();
+        let transaction = TransactionInsert {
+            id: Uuid::new_v4(),
+            transaction_type: TransactionType::Expense,
+            amount: 12.5,
+            currency: "CNY".to_string(),
+            description: "午餐".to_string(),
+            account_id: account.id,
+            category_id: None,
+            to_account_id: None,
+            status: TransactionStatus::Completed,
+            transaction_date: Local::now().date_naive(),
+            tags: vec![],
+            receipt_path: None,
+            created_at: Local::now(),
+        };
+        let amounts = |database: &Database| {
+            database
+                .get_all_transactions()
+                .unwrap()
+                .into_iter()
+                .map(|t| t.amount)
+                .collect::<Vec<_>>()
+        };
+
+        commands.insert_transaction(&transaction).unwrap();
+        let update = TransactionUpdate {
+            amount: Some(30.0),
+            ..Default::default()
+        };
+        commands
+            .update_transaction(transaction.id, &update)
+            .unwrap();
+        assert_eq!(amounts(&database), vec![30.0]);
+        commands.undo().unwrap();
+        assert_eq!(amounts(&database), vec![12.5]);
+        commands.undo().unwrap();
+        assert!(amounts(&database).is_empty());
+        commands.redo().unwrap();
+        assert_eq!(amounts(&database), vec![12.5]);
+    }
+
+    #[test]
+    fn test_undo_redo_time_entry_and_note_commands() {
+        let (_temp_dir, database) = setup();
+        let commands = CommandManager::new(database.clone(), DEFAULT_HISTORY_LIMIT).unwrap();
+        let start_time = Local::now() - chrono::Duration::hours(1);
+        let mut entry = TimeEntryInsert {
+            id: Uuid::new_v4(),
+            task_name: "写代码".to_string(),
+            category_id: None,
+            start_time,
+            end_time: Some(start_time + chrono::Duration::minutes(30)),
+            duration_seconds: 1800,
+            description: None,
+            tags: vec![],
+            created_at: start_time,
+        };
+        let duration = |database: &Database| {
+            database
+                .get_time_entry_by_id(entry.id)
+                .unwrap()
+                .map(|e| e.duration_seconds)
+        };
+
+        commands.insert_time_entry(&entry).unwrap();
+        entry.end_time = Some(start_time + chrono::Duration::minutes(45));
+        entry.duration_seconds = 2700;
+        commands.update_time_entry(entry.id, &entry).unwrap();
+        commands.delete_time_entry(entry.id).unwrap();
+        assert_eq!(duration(&database), None);
+        commands.undo().unwrap();
+        assert_eq!(duration(&database), Some(2700));
+        commands.undo().unwrap();
+        assert_eq!(duration(&database), Some(1800));
+        commands.redo().unwrap();
+        assert_eq!(duration(&database), Some(2700));
+
+        let note = Note {
+            id: Uuid::new_v4(),
+            title: "日记".to_string(),
+            content: "原内容".to_string(),
+            mood: None,
+            tags: vec![],
+            is_favorite: false,
+            is_archived: false,
+            created_at: Local::now(),
+            updated_at: Local::now(),
+        };
+        let content =
+            |database: &Database| database.get_note_by_id(note.id).unwrap().map(|n| n.content);
+
+        commands.insert_note(&note).unwrap();
+        let update = NoteUpdate {
+            title: None,
+            content: Some("新内容".to_string()),
+            mood: None,
+            tags: None,
+            is_favorite: None,
+            is_archived: None,
+            updated_at: Local::now(),
+        };
+        commands.update_note(note.id, &update).unwrap();
+        commands.delete_note(note.id).unwrap();
+        assert_eq!(content(&database), None);
+        commands.undo().unwrap();
+        assert_eq!(content(&database).as_deref(), Some("新内容"));
+        commands.undo().unwrap();
+        assert_eq!(content(&database).as_deref(), Some("原内容"));
+        commands.undo().unwrap();
+        assert_eq!(content(&database), None);
+    }
+
+    #[test]
+    fn test_history_persistence_and_conflicts() {
+        let (_temp_dir, database) = setup();
+        let task = new_task("任务");
+        {
+            let commands = CommandManager::new(database.clone(), 2).unwrap();
+            commands.insert_task(&task).unwrap();
+            commands.update_task(task.id, &rename("一")).unwrap();
+            commands.update_task(task.id, &rename("二")).unwrap();
+            assert_eq!(commands.history().undo_stack.len(), 2);
+        }
+
+        // 重启后仍可撤销
+        let commands = CommandManager::new(database.clone(), 2).unwrap();
+        assert_eq!(commands.peek_undo().unwrap().kind, CommandKind::Update);
+
+        // 命令之后数据被直接修改时不能撤销
+        database.update_task(task.id, &rename("外部修改")).unwrap();
+        assert!(commands.undo().is_err());
+        assert_eq!(
+            database.get_task_by_id(task.id).unwrap().unwrap().name,
+            "外部修改"
+        );
+    }
+}
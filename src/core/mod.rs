@@ -6,10 +6,12 @@
 //! - 分类管理
 //! - 数据分析
 //! - 记账功能
+//! - 撤销/重做
 
 pub mod accounting; // 记账功能核心逻辑
 pub mod analytics;
 pub mod category; // 分类管理
+pub mod command; // 撤销/重做命令
 pub mod task; // 任务管理
 pub mod timer; // 计时器核心逻辑 // 数据分析和统计
 
@@ -17,11 +19,14 @@ pub mod timer; // 计时器核心逻辑 // 数据分析和统计
 pub use accounting::{AccountingManager, BudgetStatus, BudgetWarning, WarningSeverity};
 pub use analytics::{Analytics, AnalyticsReport};
 pub use category::{Category, CategoryColor, CategoryIcon, CategoryManager};
+pub use command::{Command, CommandKind, CommandManager};
 pub use task::{Priority, Task, TaskManager, TaskStatus};
 pub use timer::{Timer, TimerState};
 
 use crate::errors::{AppError, Result};
-use chrono::Duration;
+use crate::storage::{StorageBackend, TaskInsert, TaskUpdate};
+use chrono::{Duration, Local, TimeZone};
+use std::sync::Arc;
 
 /// 应用程序核心状态管理器
 ///
@@ -38,6 +43,8 @@ pub struct AppCore {
     pub analytics: Analytics,
     /// 当前活动任务ID
     current_task_id: Option<uuid::Uuid>,
+    /// 持久化存储（可选，未设置时只在内存中管理任务）
+    storage: Option<Arc<dyn StorageBackend>>,
 }
 
 impl AppCore {
@@ -49,6 +56,59 @@ impl AppCore {
             category_manager: CategoryManager::new(),
             analytics: Analytics::new(),
             current_task_id: None,
+            storage: None,
+        }
+    }
+
+    /// 创建使用持久化存储的应用核心实例
+    ///
+    /// 从存储中加载已有的任务和分类，之后的任务变更会同步写入存储
+    pub fn with_storage(storage: Arc<dyn StorageBackend>) -> Result<Self> {
+        let mut core = Self::new();
+
+        for task in storage.get_all_tasks()? {
+            core.task_manager.add_task(task.into())?;
+        }
+        for category in storage.get_all_categories()? {
+            core.category_manager.add_category(category.into())?;
+        }
+
+        core.storage = Some(storage);
+        Ok(core)
+    }
+
+    /// 获取持久化存储
+    pub fn storage(&self) -> Option<&Arc<dyn StorageBackend>> {
+        self.storage.as_ref()
+    }
+
+    /// 将任务的当前状态写入存储（不存在时插入）
+    fn persist_task(
+        &self,
+        task_id: uuid::Uuid,
+        due_date: Option<chrono::NaiveDate>,
+    ) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        let task = self
+            .task_manager
+            .get_task(task_id)
+            .ok_or_else(|| AppError::TaskNotFound(task_id.to_string()))?;
+        let due_date = due_date
+            .and_then(|date| date.and_hms_opt(23, 59, 59))
+            .and_then(|date| Local.from_local_datetime(&date).earliest());
+
+        if storage.get_task_by_id(task_id)?.is_some() {
+            let mut update = TaskUpdate::from(task);
+            if due_date.is_some() {
+                update.due_date = Some(due_date);
+            }
+            storage.update_task(task_id, &update)
+        } else {
+            let mut insert = TaskInsert::from(task);
+            insert.due_date = due_date;
+            storage.insert_task(&insert).map(|_| ())
         }
     }
 
@@ -75,6 +135,7 @@ impl AppCore {
 
         // 添加到任务管理器
         self.task_manager.add_task(task)?;
+        self.persist_task(task_id, None)?;
 
         // 启动计时器
         self.timer.start()?;
@@ -117,6 +178,7 @@ impl AppCore {
 
         // 更新任务状态
         self.task_manager.complete_task(task_id, duration)?;
+        self.persist_task(task_id, None)?;
 
         self.current_task_id = None;
         log::info!("Stopping task: {}, duration: {:?}", task_id, duration);
@@ -264,6 +326,7 @@ impl AppCore {
 
         let task_id = task.id;
         self.task_manager.add_task(task)?;
+        self.persist_task(task_id, due_date)?;
 
         log::info!("Creating task: {}, due date: {:?}", task_id, due_date);
         Ok(task_id)
@@ -317,6 +380,7 @@ impl AppCore {
                 .push(format!("截止日期:{}", due_date.format("%Y-%m-%d")));
         }
 
+        self.persist_task(task_id, due_date)?;
         log::info!("Updating task: {}, due date: {:?}", task_id, due_date);
         Ok(())
     }
@@ -324,6 +388,9 @@ impl AppCore {
     /// 删除任务
     pub fn delete_task(&mut self, task_id: uuid::Uuid) -> Result<()> {
         self.task_manager.remove_task(task_id)?;
+        if let Some(storage) = &self.storage {
+            storage.delete_task(task_id)?;
+        }
 
         // 如果删除的是当前任务，清除当前任务ID
         if self.current_task_id == Some(task_id) {
@@ -375,6 +442,7 @@ impl AppCore {
             // 直接标记为完成
             self.task_manager.complete_task(task_id, Duration::zero())?;
         }
+        self.persist_task(task_id, None)?;
 
         log::info!("Completing task: {}", task_id);
         Ok(())
@@ -439,4 +507,36 @@ mod tests {
         assert!(!core.has_active_task());
         assert!(duration >= Duration::zero());
     }
+
+    #[test]
+    fn test_tasks_persist_to_storage() {
+        let storage: Arc<dyn StorageBackend> = Arc::new(crate::storage::MemoryStorage::new());
+        let mut core = AppCore::with_storage(storage.clone()).unwrap();
+
+        let task_id = core
+            .create_task(
+                "写周报".to_string(),
+                "整理本周工作".to_string(),
+                None,
+                Priority::High,
+                None,
+                vec!["工作".to_string()],
+                None,
+            )
+            .unwrap();
+        core.complete_task(task_id).unwrap();
+
+        let stored = storage.get_task_by_id(task_id).unwrap().unwrap();
+        assert_eq!(stored.priority, "high");
+        assert!(stored.is_completed);
+
+        // 重新加载后任务仍然存在
+        let reloaded = AppCore::with_storage(storage.clone()).unwrap();
+        let task = reloaded.task_manager.get_task(task_id).unwrap();
+        assert_eq!(task.status, TaskStatus::Completed);
+        assert_eq!(task.tags, vec!["工作".to_string()]);
+
+        core.delete_task(task_id).unwrap();
+        assert!(storage.get_task_by_id(task_id).unwrap().is_none());
+    }
 }
@@ -32,6 +32,12 @@ pub mod utils;
 pub use errors::{AppError, Result};
 pub use storage::database::Database;
 
+use sync::scheduler::{RetryPolicy, SimpleSyncScheduler, SyncRunner};
+use utils::scheduled_report::{ReportRunner, ReportScheduler};
+
+/// 应用数据库文件路径
+const DATABASE_PATH: &str = "./data/lifetracker.db";
+
 /// 主题模式枚举
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum ThemeMode {
@@ -87,6 +93,8 @@ impl ThemeMode {
 pub struct AppState {
     /// 数据库连接
     pub database: Option<Arc<Database>>,
+    /// 可撤销的数据修改命令管理器
+    pub commands: Option<Arc<core::CommandManager>>,
     /// 应用配置
     pub config: config::AppConfig,
     /// 初始化状态
@@ -99,6 +107,7 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             database: None,
+            commands: None,
             config: config::AppConfig::default(),
             initialized: false,
             theme_mode: ThemeMode::default(),
@@ -114,11 +123,14 @@ impl AppState {
 
     /// 同步初始化应用状态（避免runtime嵌套）
     pub fn initialize_sync(&mut self) -> Result<()> {
-        log::info!("Starting synchronous application state initialization");
+        self.initialize_with_passphrase(None)
+    }
 
-        // 初始化数据库（同步方式）
-        let database = Database::new("./data/lifetracker.db")?;
-        database.run_migrations()?;
+    /// 使用数据库口令同步初始化应用状态
+    ///
+    /// 数据库已加密或设置中开启了加密时必须提供口令，否则返回权限错误
+    pub fn initialize_with_passphrase(&mut self, passphrase: Option<&str>) -> Result<()> {
+        log::info!("Starting synchronous application state initialization");
 
         // 加载配置并设置主题
         if let Ok(config_path) = config::get_default_config_path() {
@@ -138,7 +150,63 @@ impl AppState {
             }
         }
 
-        self.database = Some(Arc::new(database));
+        // 打开数据库前先解锁，并按设置完成加密状态的转换
+        let encryption_key = self.prepare_database_encryption(passphrase)?;
+
+        // 初始化数据库（同步方式），迁移前按备份设置自动备份
+        let database_config = storage::DatabaseConfig {
+            database_path: DATABASE_PATH.to_string(),
+            pool_size: self.config.advanced.db_pool_size,
+            timeout_seconds: self.config.advanced.db_query_timeout as u64,
+            encryption_key,
+            ..Default::default()
+        };
+        let mut storage = storage::StorageManager::new(database_config.clone())?;
+        let backup_policy = storage::backup::BackupPolicy::from(&self.config.data);
+        let backup_service = storage::backup::BackupService::new(backup_policy);
+        storage.set_backup_service(backup_service.clone());
+        storage.initialize()?;
+
+        // 启动时检查定时备份
+        if let Err(e) = backup_service.run_scheduled(&storage) {
+            log::warn!("Scheduled backup failed: {}", e);
+        }
+
+        // 启动时的自动清理在审计日志中记为系统操作
+        let audit = storage.get_database().audit();
+        audit.with_source(storage::database::AuditSource::System, || {
+            // 按数据保留设置清理过期数据
+            let retention_policy = storage::retention::RetentionPolicy::from(&self.config.data);
+            if let Err(e) = storage::retention::RetentionService::new(retention_policy)
+                .run_automatic(&storage)
+            {
+                log::warn!("Data retention cleanup failed: {}", e);
+            }
+
+            // 彻底删除在回收站中超过保留天数的数据
+            if self.config.data.trash_retention_days > 0 {
+                if let Err(e) = storage
+                    .get_database()
+                    .trash()
+                    .purge_expired(self.config.data.trash_retention_days)
+                {
+                    log::warn!("Trash purge failed: {}", e);
+                }
+            }
+            Ok(())
+        })?;
+        let database = Arc::new(storage.into_database());
+
+        // 在后台启动定时报告调度器和自动同步
+        self.start_report_scheduler(database.clone());
+        self.start_sync_scheduler(database_config, database.clone());
+
+        // 加载上次保存的撤销历史
+        self.commands = Some(Arc::new(core::CommandManager::new(
+            database.clone(),
+            self.config.data.undo_history_limit,
+        )?));
+        self.database = Some(database);
         self.initialized = true;
 
         log::info!("Application state synchronous initialization completed");
@@ -149,11 +217,189 @@ impl AppState {
     pub fn get_database(&self) -> Option<Arc<Database>> {
         self.database.clone()
     }
+
+    /// 获取可撤销的数据修改命令管理器
+    pub fn get_command_manager(&self) -> Option<Arc<core::CommandManager>> {
+        self.commands.clone()
+    }
+
+    /// 在当前 tokio 运行时中启动定时报告调度器，替换之前启动的调度器
+    fn start_report_scheduler(&self, database: Arc<Database>) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            log::warn!("No tokio runtime available, scheduled reports are disabled");
+            return;
+        };
+        let log_path = match utils::scheduled_report::default_run_log_path() {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("Cannot resolve report run log path: {}", e);
+                return;
+            }
+        };
+
+        let runner = ReportRunner::new(database, log_path);
+        let reports = self.config.data.scheduled_reports.clone();
+        handle.spawn(async move {
+            if let Err(e) = start_report_scheduler(runner, &reports).await {
+                log::error!("Failed to start report scheduler: {}", e);
+            }
+        });
+    }
+
+    /// 开启自动同步时在当前 tokio 运行时中启动同步调度器
+    ///
+    /// 同步引擎使用独立的存储管理器，连接建立和测试在后台完成
+    fn start_sync_scheduler(
+        &self,
+        database_config: storage::DatabaseConfig,
+        database: Arc<Database>,
+    ) {
+        if !self.config.data.sync.enabled || !self.config.data.sync.auto_sync {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            log::warn!("No tokio runtime available, automatic sync is disabled");
+            return;
+        };
+
+        // 密码保持加密形式，由同步提供者解密
+        let mut sync_config = sync::SyncConfig::from(&self.config.data.sync);
+        if let Some(password) = &self.config.data.sync.webdav_password_encrypted {
+            sync_config
+                .settings
+                .insert("password".to_string(), password.clone());
+        }
+        handle.spawn(async move {
+            if let Err(e) = start_sync_scheduler(database_config, sync_config, database).await {
+                log::error!("Failed to start sync scheduler: {}", e);
+            }
+        });
+    }
+
+    /// 根据数据库文件和加密设置确定口令
+    ///
+    /// 设置开启加密而数据库仍为明文时先加密数据库，设置关闭加密而数据库已加密时还原为明文，
+    /// 归档数据库随主数据库一起转换
+    fn prepare_database_encryption(
+        &self,
+        passphrase: Option<&str>,
+    ) -> Result<Option<storage::encryption::DatabaseKey>> {
+        use storage::encryption;
+
+        let database_path = std::path::Path::new(DATABASE_PATH);
+        let encrypted = encryption::is_encrypted(database_path)?;
+        let encrypt_database = self.config.data.encrypt_database;
+        if !encrypted && !encrypt_database {
+            return Ok(None);
+        }
+        if !encryption::is_supported() {
+            if encrypted {
+                return Err(AppError::Permission(
+                    "数据库已加密，但当前构建未启用 encryption 功能".to_string(),
+                ));
+            }
+            log::warn!("Database encryption is enabled in settings but not supported by this build, keeping plaintext database");
+            return Ok(None);
+        }
+
+        let key = match passphrase {
+            Some(passphrase) => encryption::DatabaseKey::new(passphrase)?,
+            None => return Err(AppError::Permission("数据库已加密，请输入口令解锁".to_string())),
+        };
+        let archive_path = storage::retention::RetentionPolicy::from(&self.config.data)
+            .archive_path_for(database_path);
+
+        match (encrypted, encrypt_database) {
+            (true, true) => {
+                encryption::verify_passphrase(database_path, &key)?;
+                Ok(Some(key))
+            }
+            (false, _) => {
+                for path in [database_path, archive_path.as_path()] {
+                    if path.exists() && !encryption::is_encrypted(path)? {
+                        encryption::encrypt_database(path, &key)?;
+                    }
+                }
+                Ok(Some(key))
+            }
+            (true, false) => {
+                for path in [database_path, archive_path.as_path()] {
+                    if encryption::is_encrypted(path)? {
+                        encryption::decrypt_database(path, &key)?;
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
 }
 
 /// 全局应用状态实例（使用 Lazy 延迟初始化）
 static APP_STATE: Lazy<RwLock<AppState>> = Lazy::new(|| RwLock::new(AppState::default()));
 
+/// 定时报告调度器，应用初始化后在后台启动
+static REPORT_SCHEDULER: Lazy<tokio::sync::Mutex<Option<ReportScheduler>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// 创建并启动定时报告调度器
+async fn start_report_scheduler(
+    runner: ReportRunner,
+    reports: &[config::ScheduledReport],
+) -> anyhow::Result<()> {
+    let scheduler = ReportScheduler::new(runner).await?;
+    let scheduled = scheduler.schedule_all(reports).await;
+    scheduler.start().await?;
+    log::info!("Report scheduler started, {} reports scheduled", scheduled);
+
+    let previous = REPORT_SCHEDULER.lock().await.replace(scheduler);
+    if let Some(mut previous) = previous {
+        previous.stop().await?;
+    }
+    Ok(())
+}
+
+/// 设置中的定时报告变更后重新调度，返回成功添加的数量
+pub async fn reschedule_reports(reports: &[config::ScheduledReport]) -> usize {
+    match REPORT_SCHEDULER.lock().await.as_ref() {
+        Some(scheduler) => scheduler.reschedule_all(reports).await,
+        None => 0,
+    }
+}
+
+/// 自动同步调度器，开启自动同步时在应用初始化后启动
+static SYNC_SCHEDULER: Lazy<tokio::sync::Mutex<Option<SimpleSyncScheduler>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(None));
+
+/// 退出时最后一次同步的超时时间
+const EXIT_SYNC_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// 创建同步引擎并启动自动同步调度器
+async fn start_sync_scheduler(
+    database_config: storage::DatabaseConfig,
+    sync_config: sync::SyncConfig,
+    database: Arc<Database>,
+) -> Result<()> {
+    let storage = Arc::new(storage::StorageManager::new(database_config)?);
+    let mut engine = sync::engine::SyncEngine::new(storage, sync_config.clone())?;
+    engine.initialize().await?;
+
+    let runner = SyncRunner::new(Arc::new(engine).scheduler_task(), RetryPolicy::default());
+    runner.change_notifier().watch_database(&database)?;
+    let scheduler = SimpleSyncScheduler::new(sync_config.interval);
+    scheduler.start_with_runner(runner).await?;
+    set_sync_scheduler(scheduler).await;
+    Ok(())
+}
+
+/// 注册自动同步调度器，替换并停止之前的调度器
+///
+/// 应用关闭时由 [`shutdown_app`] 停止调度器并执行退出同步
+pub async fn set_sync_scheduler(scheduler: SimpleSyncScheduler) {
+    if let Some(previous) = SYNC_SCHEDULER.lock().await.replace(scheduler) {
+        previous.stop();
+    }
+}
+
 /// 同步初始化应用（避免runtime嵌套）
 pub fn initialize_app_sync() -> Result<()> {
     let mut state = AppState::new();
@@ -169,6 +415,50 @@ pub fn initialize_app_sync() -> Result<()> {
     }
 }
 
+/// 使用数据库口令同步初始化应用
+pub fn initialize_app_with_passphrase(passphrase: &str) -> Result<()> {
+    let mut state = AppState::new();
+    state.initialize_with_passphrase(Some(passphrase))?;
+
+    match APP_STATE.try_write() {
+        Ok(mut app_state) => {
+            *app_state = state;
+            Ok(())
+        }
+        Err(_) => Err(AppError::System("无法获取应用状态写锁".to_string())),
+    }
+}
+
+/// 启动时需要的数据库口令操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassphrasePrompt {
+    /// 数据库已加密，输入口令解锁
+    Unlock,
+    /// 设置中开启了加密而数据库仍为明文，设置口令后首次加密
+    Encrypt,
+}
+
+/// 启动时需要的数据库口令操作
+///
+/// 未启用 `encryption` 功能时始终返回 None
+pub fn passphrase_prompt() -> Option<PassphrasePrompt> {
+    if !cfg!(feature = "encryption") {
+        return None;
+    }
+
+    if storage::encryption::is_encrypted(DATABASE_PATH).unwrap_or(false) {
+        return Some(PassphrasePrompt::Unlock);
+    }
+
+    let encrypt_database = config::get_default_config_path()
+        .ok()
+        .and_then(|path| config::ConfigManager::new(path).ok())
+        .map(|manager| manager.config().data.encrypt_database)
+        .unwrap_or(false);
+
+    encrypt_database.then_some(PassphrasePrompt::Encrypt)
+}
+
 /// 获取全局应用状态（同步版本）
 pub fn get_app_state_sync() -> AppState {
     match APP_STATE.try_read() {
@@ -230,7 +520,70 @@ pub fn toggle_theme() -> Result<ThemeMode> {
 pub async fn shutdown_app(_app_state: &AppState) -> Result<()> {
     log::info!("Starting application shutdown");
 
-    // 这里可以添加清理逻辑
+    // 停止自动同步并执行退出前的最后一次同步
+    if let Some(scheduler) = SYNC_SCHEDULER.lock().await.take() {
+        if let Err(e) = scheduler.shutdown(EXIT_SYNC_TIMEOUT).await {
+            log::warn!("Exit sync failed: {}", e);
+        }
+    }
+
+    // 停止定时报告调度器
+    if let Some(mut scheduler) = REPORT_SCHEDULER.lock().await.take() {
+        if let Err(e) = scheduler.stop().await {
+            log::warn!("Failed to stop report scheduler: {}", e);
+        }
+    }
+
     log::info!("Application shutdown completed");
     Ok(())
 }
+
+/// 同步关闭应用（窗口关闭时调用）
+///
+/// 需要在多线程 tokio 运行时中调用，否则跳过异步清理
+pub fn shutdown_app_sync() -> Result<()> {
+    let state = get_app_state_sync();
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(shutdown_app(&state)))
+        }
+        _ => {
+            log::warn!("No multi-threaded tokio runtime, skipping application shutdown");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_shutdown_runs_exit_sync() {
+        let sync_count = Arc::new(AtomicUsize::new(0));
+        let scheduler = SimpleSyncScheduler::new(60);
+        {
+            let sync_count = sync_count.clone();
+            scheduler
+                .start(move || {
+                    sync_count.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                })
+                .await
+                .unwrap();
+        }
+        // 启动后立即执行一次定时同步
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(sync_count.load(Ordering::SeqCst), 1);
+
+        set_sync_scheduler(scheduler).await;
+        shutdown_app(&AppState::default()).await.unwrap();
+        assert_eq!(sync_count.load(Ordering::SeqCst), 2);
+        assert!(SYNC_SCHEDULER.lock().await.is_none());
+
+        // 没有调度器时关闭不执行同步
+        shutdown_app(&AppState::default()).await.unwrap();
+        assert_eq!(sync_count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -183,7 +183,7 @@ pub struct TransactionInsert {
 }
 
 /// 交易记录更新模型
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransactionUpdate {
     pub transaction_type: Option<TransactionType>,
     pub amount: Option<f64>,
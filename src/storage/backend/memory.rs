@@ -0,0 +1,621 @@
+//! # 内存存储后端
+//!
+//! 所有数据保存在内存中，不依赖数据库，用于测试业务逻辑和同步流程。
+//! 删除即彻底删除（没有回收站），事务通过提交前的数据快照实现回滚。
+
+use super::{
+    AccountStore, BudgetStore, CategoryStore, NoteStore, SettingsStore, StorageBackend, TaskStore,
+    TimeEntryStore, TransactionCategoryStore, TransactionStore,
+};
+use crate::errors::{AppError, Result};
+use crate::storage::models::{
+    Account, CategoryInsert, CategoryModel, Note, NoteUpdate, TimeEntry, TimeEntryInsert,
+    Transaction,
+};
+use crate::storage::task_models::{TaskInsert, TaskModel, TaskUpdate};
+use crate::storage::{
+    AccountInsert, Budget, BudgetInsert, TransactionCategory, TransactionCategoryInsert,
+    TransactionInsert,
+};
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+/// 内存中的全部数据
+#[derive(Debug, Clone, Default)]
+struct MemoryData {
+    time_entries: BTreeMap<Uuid, TimeEntry>,
+    tasks: BTreeMap<Uuid, TaskModel>,
+    categories: BTreeMap<Uuid, CategoryModel>,
+    notes: BTreeMap<Uuid, Note>,
+    accounts: BTreeMap<Uuid, Account>,
+    transactions: BTreeMap<Uuid, Transaction>,
+    transaction_categories: BTreeMap<Uuid, TransactionCategory>,
+    budgets: BTreeMap<Uuid, Budget>,
+    settings: BTreeMap<String, String>,
+    /// 下一个插入行号
+    next_row_id: i64,
+}
+
+impl MemoryData {
+    /// 分配插入行号
+    fn row_id(&mut self) -> i64 {
+        self.next_row_id += 1;
+        self.next_row_id
+    }
+}
+
+/// 内存存储后端
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    /// 当前数据
+    data: Mutex<MemoryData>,
+    /// 事务开始时的数据快照
+    snapshot: Mutex<Option<MemoryData>>,
+}
+
+impl MemoryStorage {
+    /// 创建空的内存存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> Result<MutexGuard<'_, MemoryData>> {
+        self.data
+            .lock()
+            .map_err(|_| AppError::System("Failed to acquire memory storage lock".to_string()))
+    }
+
+    fn snapshot(&self) -> Result<MutexGuard<'_, Option<MemoryData>>> {
+        self.snapshot
+            .lock()
+            .map_err(|_| AppError::System("Failed to acquire memory storage lock".to_string()))
+    }
+}
+
+impl TimeEntryStore for MemoryStorage {
+    fn insert_time_entry(&self, entry: &TimeEntryInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.time_entries, entry.id)?;
+        data.time_entries.insert(
+            entry.id,
+            TimeEntry {
+                id: entry.id,
+                task_name: entry.task_name.clone(),
+                category_id: entry.category_id,
+                start_time: entry.start_time,
+                end_time: entry.end_time,
+                duration_seconds: entry.duration_seconds,
+                description: entry.description.clone(),
+                tags: entry.tags.clone(),
+                created_at: entry.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_time_entry_by_id(&self, id: Uuid) -> Result<Option<TimeEntry>> {
+        Ok(self.data()?.time_entries.get(&id).cloned())
+    }
+
+    fn get_all_time_entries(&self) -> Result<Vec<TimeEntry>> {
+        let mut entries: Vec<TimeEntry> = self.data()?.time_entries.values().cloned().collect();
+        entries.sort_by_key(|entry| Reverse(entry.start_time));
+        Ok(entries)
+    }
+
+    fn update_time_entry(&self, id: Uuid, entry: &TimeEntryInsert) -> Result<()> {
+        let mut data = self.data()?;
+        let existing = data
+            .time_entries
+            .get_mut(&id)
+            .ok_or_else(|| AppError::TaskNotFound(id.to_string()))?;
+        existing.task_name = entry.task_name.clone();
+        existing.category_id = entry.category_id;
+        existing.start_time = entry.start_time;
+        existing.end_time = entry.end_time;
+        existing.duration_seconds = entry.duration_seconds;
+        existing.description = entry.description.clone();
+        existing.tags = entry.tags.clone();
+        existing.updated_at = Some(Local::now());
+        Ok(())
+    }
+
+    fn delete_time_entry(&self, id: Uuid) -> Result<()> {
+        remove_existing(&mut self.data()?.time_entries, id, "时间记录")
+    }
+
+    fn clear_time_entries(&self) -> Result<()> {
+        self.data()?.time_entries.clear();
+        Ok(())
+    }
+}
+
+impl TaskStore for MemoryStorage {
+    fn insert_task(&self, task: &TaskInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.tasks, task.id)?;
+        data.tasks.insert(task.id, TaskModel::from(task.clone()));
+        Ok(data.row_id())
+    }
+
+    fn get_all_tasks(&self) -> Result<Vec<TaskModel>> {
+        let mut tasks: Vec<TaskModel> = self.data()?.tasks.values().cloned().collect();
+        tasks.sort_by_key(|task| Reverse(task.created_at));
+        Ok(tasks)
+    }
+
+    fn get_task_by_id(&self, id: Uuid) -> Result<Option<TaskModel>> {
+        Ok(self.data()?.tasks.get(&id).cloned())
+    }
+
+    fn update_task(&self, id: Uuid, update: &TaskUpdate) -> Result<()> {
+        let mut data = self.data()?;
+        let task = data
+            .tasks
+            .get_mut(&id)
+            .ok_or_else(|| AppError::TaskNotFound(id.to_string()))?;
+
+        if let Some(name) = &update.name {
+            task.name = name.clone();
+        }
+        if let Some(description) = &update.description {
+            task.description = description.clone();
+        }
+        if let Some(category_id) = update.category_id {
+            task.category_id = category_id;
+        }
+        if let Some(status) = &update.status {
+            task.status = status.clone();
+        }
+        if let Some(priority) = &update.priority {
+            task.priority = priority.clone();
+        }
+        if let Some(estimated_duration_seconds) = update.estimated_duration_seconds {
+            task.estimated_duration_seconds = estimated_duration_seconds;
+        }
+        if let Some(total_duration_seconds) = update.total_duration_seconds {
+            task.total_duration_seconds = total_duration_seconds;
+        }
+        if let Some(tags) = &update.tags {
+            task.tags = tags.clone();
+        }
+        if let Some(due_date) = update.due_date {
+            task.due_date = due_date;
+        }
+        if let Some(is_completed) = update.is_completed {
+            task.is_completed = is_completed;
+        }
+        if let Some(completed_at) = update.completed_at {
+            task.completed_at = completed_at;
+        }
+        task.updated_at = Some(Local::now());
+        Ok(())
+    }
+
+    fn delete_task(&self, id: Uuid) -> Result<()> {
+        remove_existing(&mut self.data()?.tasks, id, "任务")
+    }
+
+    fn clear_tasks(&self) -> Result<()> {
+        self.data()?.tasks.clear();
+        Ok(())
+    }
+}
+
+impl CategoryStore for MemoryStorage {
+    fn insert_category(&self, category: &CategoryInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.categories, category.id)?;
+        data.categories.insert(
+            category.id,
+            CategoryModel {
+                id: category.id,
+                name: category.name.clone(),
+                description: category.description.clone(),
+                color: category.color.clone(),
+                icon: category.icon.clone(),
+                daily_target_seconds: category.daily_target_seconds,
+                weekly_target_seconds: category.weekly_target_seconds,
+                is_active: category.is_active,
+                sort_order: category.sort_order,
+                parent_id: category.parent_id,
+                created_at: category.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_all_categories(&self) -> Result<Vec<CategoryModel>> {
+        let mut categories: Vec<CategoryModel> = self
+            .data()?
+            .categories
+            .values()
+            .filter(|category| category.is_active)
+            .cloned()
+            .collect();
+        categories.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(categories)
+    }
+
+    /// 删除分类及其子分类，以及属于这些分类的任务和时间记录
+    fn delete_category(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data()?;
+        if !data.categories.contains_key(&id) {
+            return Err(AppError::CategoryNotFound(id.to_string()));
+        }
+
+        let mut removed = HashSet::from([id]);
+        loop {
+            let children: Vec<Uuid> = data
+                .categories
+                .values()
+                .filter(|category| {
+                    category
+                        .parent_id
+                        .is_some_and(|parent| removed.contains(&parent))
+                        && !removed.contains(&category.id)
+                })
+                .map(|category| category.id)
+                .collect();
+            if children.is_empty() {
+                break;
+            }
+            removed.extend(children);
+        }
+
+        let in_removed = |category_id: Option<Uuid>| {
+            category_id.is_some_and(|category_id| removed.contains(&category_id))
+        };
+        data.categories.retain(|id, _| !removed.contains(id));
+        data.tasks.retain(|_, task| !in_removed(task.category_id));
+        data.time_entries
+            .retain(|_, entry| !in_removed(entry.category_id));
+        Ok(())
+    }
+
+    fn clear_categories(&self) -> Result<()> {
+        self.data()?.categories.clear();
+        Ok(())
+    }
+}
+
+impl NoteStore for MemoryStorage {
+    fn insert_note(&self, note: &Note) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.notes, note.id)?;
+        data.notes.insert(note.id, note.clone());
+        Ok(data.row_id())
+    }
+
+    fn get_all_notes(&self) -> Result<Vec<Note>> {
+        let mut notes: Vec<Note> = self.data()?.notes.values().cloned().collect();
+        notes.sort_by_key(|note| Reverse(note.created_at));
+        Ok(notes)
+    }
+
+    fn get_note_by_id(&self, id: Uuid) -> Result<Option<Note>> {
+        Ok(self.data()?.notes.get(&id).cloned())
+    }
+
+    fn update_note(&self, id: Uuid, update: &NoteUpdate) -> Result<()> {
+        let mut data = self.data()?;
+        let note = data
+            .notes
+            .get_mut(&id)
+            .ok_or_else(|| AppError::NotFound(format!("笔记未找到: {}", id)))?;
+
+        if let Some(title) = &update.title {
+            note.title = title.clone();
+        }
+        if let Some(content) = &update.content {
+            note.content = content.clone();
+        }
+        if let Some(mood) = &update.mood {
+            note.mood = mood.clone();
+        }
+        if let Some(tags) = &update.tags {
+            note.tags = tags.clone();
+        }
+        if let Some(is_favorite) = update.is_favorite {
+            note.is_favorite = is_favorite;
+        }
+        if let Some(is_archived) = update.is_archived {
+            note.is_archived = is_archived;
+        }
+        note.updated_at = update.updated_at;
+        Ok(())
+    }
+
+    fn delete_note(&self, id: Uuid) -> Result<()> {
+        remove_existing(&mut self.data()?.notes, id, "笔记")
+    }
+
+    fn clear_notes(&self) -> Result<()> {
+        self.data()?.notes.clear();
+        Ok(())
+    }
+}
+
+impl AccountStore for MemoryStorage {
+    fn insert_account(&self, account: &AccountInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.accounts, account.id)?;
+        data.accounts.insert(
+            account.id,
+            Account {
+                id: account.id,
+                name: account.name.clone(),
+                account_type: enum_name(&account.account_type),
+                bank_name: None,
+                account_number: None,
+                routing_number: None,
+                balance: account.balance,
+                currency: account.currency.clone(),
+                is_active: account.is_active,
+                created_at: account.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_all_accounts(&self) -> Result<Vec<Account>> {
+        let mut accounts: Vec<Account> = self.data()?.accounts.values().cloned().collect();
+        accounts.sort_by_key(|account| account.created_at);
+        Ok(accounts)
+    }
+
+    fn clear_accounts(&self) -> Result<()> {
+        self.data()?.accounts.clear();
+        Ok(())
+    }
+}
+
+impl TransactionStore for MemoryStorage {
+    fn insert_transaction(&self, transaction: &TransactionInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.transactions, transaction.id)?;
+        let transaction_date = transaction
+            .transaction_date
+            .and_hms_opt(0, 0, 0)
+            .and_then(|date| Local.from_local_datetime(&date).earliest())
+            .unwrap_or(transaction.created_at);
+        data.transactions.insert(
+            transaction.id,
+            Transaction {
+                id: transaction.id,
+                account_id: transaction.account_id,
+                amount: transaction.amount,
+                description: transaction.description.clone(),
+                payee: None,
+                category_id: transaction.category_id,
+                transaction_date,
+                transaction_type: enum_name(&transaction.transaction_type),
+                tags: transaction.tags.clone(),
+                created_at: transaction.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        let mut transactions: Vec<Transaction> =
+            self.data()?.transactions.values().cloned().collect();
+        transactions.sort_by_key(|transaction| Reverse(transaction.transaction_date));
+        Ok(transactions)
+    }
+
+    fn clear_transactions(&self) -> Result<()> {
+        self.data()?.transactions.clear();
+        Ok(())
+    }
+}
+
+impl TransactionCategoryStore for MemoryStorage {
+    fn insert_transaction_category(&self, category: &TransactionCategoryInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.transaction_categories, category.id)?;
+        data.transaction_categories.insert(
+            category.id,
+            TransactionCategory {
+                id: category.id,
+                name: category.name.clone(),
+                transaction_type: category.transaction_type,
+                description: category.description.clone(),
+                color: category.color.clone(),
+                icon: category.icon.clone(),
+                parent_id: category.parent_id,
+                is_active: category.is_active,
+                created_at: category.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_all_transaction_categories(&self) -> Result<Vec<TransactionCategory>> {
+        let mut categories: Vec<TransactionCategory> = self
+            .data()?
+            .transaction_categories
+            .values()
+            .cloned()
+            .collect();
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(categories)
+    }
+
+    fn clear_transaction_categories(&self) -> Result<()> {
+        self.data()?.transaction_categories.clear();
+        Ok(())
+    }
+}
+
+impl BudgetStore for MemoryStorage {
+    fn insert_budget(&self, budget: &BudgetInsert) -> Result<i64> {
+        let mut data = self.data()?;
+        ensure_absent(&data.budgets, budget.id)?;
+        data.budgets.insert(
+            budget.id,
+            Budget {
+                id: budget.id,
+                name: budget.name.clone(),
+                category_id: budget.category_id,
+                amount: budget.amount,
+                currency: budget.currency.clone(),
+                period: budget.period,
+                start_date: budget.start_date,
+                end_date: budget.end_date,
+                spent_amount: 0.0,
+                remaining_amount: budget.amount,
+                is_active: budget.is_active,
+                created_at: budget.created_at,
+                updated_at: None,
+            },
+        );
+        Ok(data.row_id())
+    }
+
+    fn get_all_budgets(&self) -> Result<Vec<Budget>> {
+        let mut budgets: Vec<Budget> = self.data()?.budgets.values().cloned().collect();
+        budgets.sort_by_key(|budget| (budget.start_date, budget.created_at));
+        Ok(budgets)
+    }
+
+    fn clear_budgets(&self) -> Result<()> {
+        self.data()?.budgets.clear();
+        Ok(())
+    }
+}
+
+impl SettingsStore for MemoryStorage {
+    fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.data()?.settings.get(key).cloned())
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.data()?
+            .settings
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn begin_transaction(&self) -> Result<()> {
+        let mut snapshot = self.snapshot()?;
+        if snapshot.is_some() {
+            return Err(AppError::Storage("事务已经开始".to_string()));
+        }
+        *snapshot = Some(self.data()?.clone());
+        Ok(())
+    }
+
+    fn commit_transaction(&self) -> Result<()> {
+        self.snapshot()?
+            .take()
+            .map(|_| ())
+            .ok_or_else(|| AppError::Storage("没有进行中的事务".to_string()))
+    }
+
+    fn rollback_transaction(&self) -> Result<()> {
+        let snapshot = self
+            .snapshot()?
+            .take()
+            .ok_or_else(|| AppError::Storage("没有进行中的事务".to_string()))?;
+        *self.data()? = snapshot;
+        Ok(())
+    }
+}
+
+/// 插入前检查ID是否已存在
+fn ensure_absent<T>(rows: &BTreeMap<Uuid, T>, id: Uuid) -> Result<()> {
+    if rows.contains_key(&id) {
+        return Err(AppError::AlreadyExists(id.to_string()));
+    }
+    Ok(())
+}
+
+/// 删除已存在的数据，不存在时返回错误
+fn remove_existing<T>(rows: &mut BTreeMap<Uuid, T>, id: Uuid, name: &str) -> Result<()> {
+    rows.remove(&id)
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound(format!("{}未找到: {}", name, id)))
+}
+
+/// 枚举值序列化后的名称（与数据库中保存的一致）
+fn enum_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    fn task(name: &str) -> TaskInsert {
+        TaskInsert {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            category_id: None,
+            status: "pending".to_string(),
+            priority: "medium".to_string(),
+            estimated_duration_seconds: None,
+            total_duration_seconds: 0,
+            tags: "[]".to_string(),
+            due_date: None,
+            is_completed: false,
+            completed_at: None,
+            created_at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_task_crud_and_rollback() {
+        let storage = MemoryStorage::new();
+        let first = task("写报告");
+        storage.insert_task(&first).unwrap();
+        assert!(storage.insert_task(&first).is_err());
+
+        storage
+            .update_task(
+                first.id,
+                &TaskUpdate {
+                    name: Some("写月报".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            storage.get_task_by_id(first.id).unwrap().unwrap().name,
+            "写月报"
+        );
+
+        storage.begin_transaction().unwrap();
+        storage.clear_tasks().unwrap();
+        storage.insert_task(&task("临时")).unwrap();
+        storage.rollback_transaction().unwrap();
+
+        let tasks = storage.get_all_tasks().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, first.id);
+
+        storage.delete_task(first.id).unwrap();
+        assert!(storage.delete_task(first.id).is_err());
+        assert!(storage.get_all_tasks().unwrap().is_empty());
+    }
+}
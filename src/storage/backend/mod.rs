@@ -0,0 +1,239 @@
+//! # 存储后端模块
+//!
+//! 定义与具体数据库无关的数据仓库接口，业务逻辑和同步引擎只依赖这些接口：
+//! - `sqlite`: 基于 SQLite 的实现（`Database` 和 `StorageManager`）
+//! - `memory`: 基于内存的实现，用于测试
+
+pub mod memory;
+pub mod sqlite;
+
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteDatabase;
+
+use crate::errors::Result;
+use crate::storage::database::AuditSource;
+use crate::storage::models::{
+    Account, CategoryInsert, CategoryModel, Note, NoteUpdate, TimeEntry, TimeEntryInsert,
+    Transaction,
+};
+use crate::storage::task_models::{TaskInsert, TaskModel, TaskUpdate};
+use crate::storage::{
+    AccountInsert, Budget, BudgetInsert, TransactionCategory, TransactionCategoryInsert,
+    TransactionInsert,
+};
+use chrono::{DateTime, Local};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// 时间范围（含两端）
+pub type TimeRange = (DateTime<Local>, DateTime<Local>);
+
+fn in_range(entry: &TimeEntry, range: Option<TimeRange>) -> bool {
+    range.is_none_or(|(start, end)| entry.start_time >= start && entry.start_time <= end)
+}
+
+/// 时间记录仓库
+pub trait TimeEntryStore {
+    /// 插入时间记录
+    fn insert_time_entry(&self, entry: &TimeEntryInsert) -> Result<i64>;
+
+    /// 根据ID获取时间记录
+    fn get_time_entry_by_id(&self, id: Uuid) -> Result<Option<TimeEntry>>;
+
+    /// 获取所有时间记录（按开始时间从新到旧）
+    fn get_all_time_entries(&self) -> Result<Vec<TimeEntry>>;
+
+    /// 统计时间记录数量，`range` 按开始时间过滤（含两端）
+    fn count_time_entries(&self, range: Option<TimeRange>) -> Result<usize> {
+        Ok(self
+            .get_all_time_entries()?
+            .iter()
+            .filter(|entry| in_range(entry, range))
+            .count())
+    }
+
+    /// 按开始时间从旧到新逐条读取时间记录，回调返回 `false` 时停止
+    ///
+    /// 默认实现先读取全部记录；SQLite 实现直接遍历游标，内存占用与记录数无关
+    fn for_each_time_entry(
+        &self,
+        range: Option<TimeRange>,
+        f: &mut dyn FnMut(TimeEntry) -> bool,
+    ) -> Result<()> {
+        let mut entries = self.get_all_time_entries()?;
+        entries.retain(|entry| in_range(entry, range));
+        entries.sort_by_key(|entry| entry.start_time);
+        for entry in entries {
+            if !f(entry) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 更新时间记录
+    fn update_time_entry(&self, id: Uuid, entry: &TimeEntryInsert) -> Result<()>;
+
+    /// 删除时间记录
+    fn delete_time_entry(&self, id: Uuid) -> Result<()>;
+
+    /// 清空所有时间记录
+    fn clear_time_entries(&self) -> Result<()>;
+}
+
+/// 任务仓库
+pub trait TaskStore {
+    /// 插入任务
+    fn insert_task(&self, task: &TaskInsert) -> Result<i64>;
+
+    /// 获取所有任务（按创建时间从新到旧）
+    fn get_all_tasks(&self) -> Result<Vec<TaskModel>>;
+
+    /// 根据ID获取任务
+    fn get_task_by_id(&self, id: Uuid) -> Result<Option<TaskModel>>;
+
+    /// 更新任务
+    fn update_task(&self, id: Uuid, task: &TaskUpdate) -> Result<()>;
+
+    /// 删除任务
+    fn delete_task(&self, id: Uuid) -> Result<()>;
+
+    /// 清空所有任务
+    fn clear_tasks(&self) -> Result<()>;
+}
+
+/// 分类仓库
+pub trait CategoryStore {
+    /// 插入分类
+    fn insert_category(&self, category: &CategoryInsert) -> Result<i64>;
+
+    /// 获取所有启用的分类（按排序值和名称）
+    fn get_all_categories(&self) -> Result<Vec<CategoryModel>>;
+
+    /// 删除分类
+    fn delete_category(&self, id: Uuid) -> Result<()>;
+
+    /// 清空所有分类
+    fn clear_categories(&self) -> Result<()>;
+}
+
+/// 笔记仓库
+pub trait NoteStore {
+    /// 插入笔记
+    fn insert_note(&self, note: &Note) -> Result<i64>;
+
+    /// 获取所有笔记
+    fn get_all_notes(&self) -> Result<Vec<Note>>;
+
+    /// 根据ID获取笔记
+    fn get_note_by_id(&self, id: Uuid) -> Result<Option<Note>>;
+
+    /// 更新笔记
+    fn update_note(&self, id: Uuid, update: &NoteUpdate) -> Result<()>;
+
+    /// 删除笔记
+    fn delete_note(&self, id: Uuid) -> Result<()>;
+
+    /// 清空所有笔记
+    fn clear_notes(&self) -> Result<()>;
+}
+
+/// 账户仓库
+pub trait AccountStore {
+    /// 插入账户
+    fn insert_account(&self, account: &AccountInsert) -> Result<i64>;
+
+    /// 获取所有账户
+    fn get_all_accounts(&self) -> Result<Vec<Account>>;
+
+    /// 清空所有账户
+    fn clear_accounts(&self) -> Result<()>;
+}
+
+/// 交易记录仓库
+pub trait TransactionStore {
+    /// 插入交易记录
+    fn insert_transaction(&self, transaction: &TransactionInsert) -> Result<i64>;
+
+    /// 获取所有交易记录
+    fn get_all_transactions(&self) -> Result<Vec<Transaction>>;
+
+    /// 清空所有交易记录
+    fn clear_transactions(&self) -> Result<()>;
+}
+
+/// 交易分类仓库
+pub trait TransactionCategoryStore {
+    /// 插入交易分类
+    fn insert_transaction_category(&self, category: &TransactionCategoryInsert) -> Result<i64>;
+
+    /// 获取所有交易分类
+    fn get_all_transaction_categories(&self) -> Result<Vec<TransactionCategory>>;
+
+    /// 清空所有交易分类
+    fn clear_transaction_categories(&self) -> Result<()>;
+}
+
+/// 预算仓库
+pub trait BudgetStore {
+    /// 插入预算
+    fn insert_budget(&self, budget: &BudgetInsert) -> Result<i64>;
+
+    /// 获取所有预算，已花费和剩余金额需要调用方根据交易记录计算
+    fn get_all_budgets(&self) -> Result<Vec<Budget>>;
+
+    /// 清空所有预算
+    fn clear_budgets(&self) -> Result<()>;
+}
+
+/// 设置仓库
+pub trait SettingsStore {
+    /// 获取设置值
+    fn get_setting(&self, key: &str) -> Result<Option<String>>;
+
+    /// 保存设置值
+    fn set_setting(&self, key: &str, value: &str) -> Result<()>;
+}
+
+/// 完整的存储后端
+///
+/// 组合所有数据仓库，并提供事务控制
+pub trait StorageBackend:
+    TimeEntryStore
+    + TaskStore
+    + CategoryStore
+    + NoteStore
+    + AccountStore
+    + TransactionStore
+    + TransactionCategoryStore
+    + BudgetStore
+    + SettingsStore
+    + Send
+    + Sync
+    + Debug
+{
+    /// 开始事务
+    fn begin_transaction(&self) -> Result<()>;
+
+    /// 提交事务
+    fn commit_transaction(&self) -> Result<()>;
+
+    /// 回滚事务
+    fn rollback_transaction(&self) -> Result<()>;
+
+    /// 开始整体替换数据（先清空再重新写入），支持审计日志的后端据此只记录实际变化
+    fn begin_replace(&self, _source: AuditSource) -> Result<()> {
+        Ok(())
+    }
+
+    /// 结束整体替换数据
+    fn finish_replace(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// 回收站中所有数据的ID，整体替换数据时保留这些数据，不再重新写入
+    fn trashed_ids(&self) -> Result<HashSet<Uuid>> {
+        Ok(HashSet::new())
+    }
+}
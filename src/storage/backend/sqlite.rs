@@ -0,0 +1,253 @@
+//! # SQLite 存储后端
+//!
+//! 通过 [`SqliteDatabase`] 为 `Database` 和 `StorageManager` 实现所有数据仓库接口
+
+use super::{
+    AccountStore, BudgetStore, CategoryStore, NoteStore, SettingsStore, StorageBackend, TaskStore,
+    TimeEntryStore, TimeRange, TransactionCategoryStore, TransactionStore,
+};
+use crate::errors::Result;
+use crate::storage::database::AuditSource;
+use crate::storage::models::{
+    Account, CategoryInsert, CategoryModel, Note, NoteUpdate, TimeEntry, TimeEntryInsert,
+    Transaction,
+};
+use crate::storage::task_models::{TaskInsert, TaskModel, TaskUpdate};
+use crate::storage::{
+    AccountInsert, Budget, BudgetInsert, Database, StorageManager, TransactionCategory,
+    TransactionCategoryInsert, TransactionInsert,
+};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use uuid::Uuid;
+
+/// 持有 SQLite 数据库的类型，自动获得所有数据仓库接口的实现
+pub trait SqliteDatabase: Send + Sync + Debug {
+    /// 底层数据库
+    fn sqlite(&self) -> &Database;
+
+    /// 硬删除表中未移入回收站的数据，回收站中的数据保留
+    fn clear_table(&self, table: &str) -> Result<()> {
+        self.sqlite().execute(
+            &format!("DELETE FROM {} WHERE deleted_at IS NULL", table),
+            &[],
+        )?;
+        Ok(())
+    }
+}
+
+impl SqliteDatabase for Database {
+    fn sqlite(&self) -> &Database {
+        self
+    }
+}
+
+impl SqliteDatabase for StorageManager {
+    fn sqlite(&self) -> &Database {
+        self.get_database()
+    }
+}
+
+impl<T: SqliteDatabase> TimeEntryStore for T {
+    fn insert_time_entry(&self, entry: &TimeEntryInsert) -> Result<i64> {
+        self.sqlite().insert_time_entry(entry)
+    }
+
+    fn get_time_entry_by_id(&self, id: Uuid) -> Result<Option<TimeEntry>> {
+        self.sqlite().get_time_entry_by_id(id)
+    }
+
+    fn get_all_time_entries(&self) -> Result<Vec<TimeEntry>> {
+        self.sqlite().get_all_time_entries()
+    }
+
+    fn count_time_entries(&self, range: Option<TimeRange>) -> Result<usize> {
+        self.sqlite().count_time_entries(range)
+    }
+
+    fn for_each_time_entry(
+        &self,
+        range: Option<TimeRange>,
+        f: &mut dyn FnMut(TimeEntry) -> bool,
+    ) -> Result<()> {
+        self.sqlite().for_each_time_entry(range, f)
+    }
+
+    fn update_time_entry(&self, id: Uuid, entry: &TimeEntryInsert) -> Result<()> {
+        self.sqlite().update_time_entry(id, entry)
+    }
+
+    fn delete_time_entry(&self, id: Uuid) -> Result<()> {
+        self.sqlite().delete_time_entry(id)
+    }
+
+    fn clear_time_entries(&self) -> Result<()> {
+        self.clear_table("time_entries")
+    }
+}
+
+impl<T: SqliteDatabase> TaskStore for T {
+    fn insert_task(&self, task: &TaskInsert) -> Result<i64> {
+        self.sqlite().insert_task(task)
+    }
+
+    fn get_all_tasks(&self) -> Result<Vec<TaskModel>> {
+        self.sqlite().get_all_tasks()
+    }
+
+    fn get_task_by_id(&self, id: Uuid) -> Result<Option<TaskModel>> {
+        self.sqlite().get_task_by_id(id)
+    }
+
+    fn update_task(&self, id: Uuid, task: &TaskUpdate) -> Result<()> {
+        self.sqlite().update_task(id, task)
+    }
+
+    fn delete_task(&self, id: Uuid) -> Result<()> {
+        self.sqlite().delete_task(id)
+    }
+
+    fn clear_tasks(&self) -> Result<()> {
+        self.clear_table("tasks")
+    }
+}
+
+impl<T: SqliteDatabase> CategoryStore for T {
+    fn insert_category(&self, category: &CategoryInsert) -> Result<i64> {
+        self.sqlite().insert_category(category)
+    }
+
+    fn get_all_categories(&self) -> Result<Vec<CategoryModel>> {
+        self.sqlite().get_all_categories()
+    }
+
+    fn delete_category(&self, id: Uuid) -> Result<()> {
+        self.sqlite().delete_category(id)
+    }
+
+    fn clear_categories(&self) -> Result<()> {
+        self.clear_table("categories")
+    }
+}
+
+impl<T: SqliteDatabase> NoteStore for T {
+    fn insert_note(&self, note: &Note) -> Result<i64> {
+        self.sqlite().insert_note(note)
+    }
+
+    fn get_all_notes(&self) -> Result<Vec<Note>> {
+        self.sqlite().get_all_notes()
+    }
+
+    fn get_note_by_id(&self, id: Uuid) -> Result<Option<Note>> {
+        self.sqlite().get_note_by_id(id)
+    }
+
+    fn update_note(&self, id: Uuid, update: &NoteUpdate) -> Result<()> {
+        self.sqlite().update_note(id, update)
+    }
+
+    fn delete_note(&self, id: Uuid) -> Result<()> {
+        self.sqlite().delete_note(id)
+    }
+
+    fn clear_notes(&self) -> Result<()> {
+        self.clear_table("notes")
+    }
+}
+
+impl<T: SqliteDatabase> AccountStore for T {
+    fn insert_account(&self, account: &AccountInsert) -> Result<i64> {
+        self.sqlite().insert_account(account)
+    }
+
+    fn get_all_accounts(&self) -> Result<Vec<Account>> {
+        self.sqlite().get_all_accounts()
+    }
+
+    fn clear_accounts(&self) -> Result<()> {
+        self.clear_table("accounts")
+    }
+}
+
+impl<T: SqliteDatabase> TransactionStore for T {
+    fn insert_transaction(&self, transaction: &TransactionInsert) -> Result<i64> {
+        self.sqlite().insert_transaction(transaction)
+    }
+
+    fn get_all_transactions(&self) -> Result<Vec<Transaction>> {
+        self.sqlite().get_all_transactions()
+    }
+
+    fn clear_transactions(&self) -> Result<()> {
+        self.clear_table("transactions")
+    }
+}
+
+// 交易分类和预算不进入回收站，清空时直接删除整张表的数据
+impl<T: SqliteDatabase> TransactionCategoryStore for T {
+    fn insert_transaction_category(&self, category: &TransactionCategoryInsert) -> Result<i64> {
+        self.sqlite().insert_transaction_category(category)
+    }
+
+    fn get_all_transaction_categories(&self) -> Result<Vec<TransactionCategory>> {
+        self.sqlite().get_all_transaction_categories()
+    }
+
+    fn clear_transaction_categories(&self) -> Result<()> {
+        self.sqlite()
+            .execute("DELETE FROM transaction_categories", &[])?;
+        Ok(())
+    }
+}
+
+impl<T: SqliteDatabase> BudgetStore for T {
+    fn insert_budget(&self, budget: &BudgetInsert) -> Result<i64> {
+        self.sqlite().insert_budget(budget)
+    }
+
+    fn get_all_budgets(&self) -> Result<Vec<Budget>> {
+        self.sqlite().get_all_budgets()
+    }
+
+    fn clear_budgets(&self) -> Result<()> {
+        self.sqlite().execute("DELETE FROM budgets", &[])?;
+        Ok(())
+    }
+}
+
+impl<T: SqliteDatabase> SettingsStore for T {
+    fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.sqlite().get_setting(key)
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.sqlite().set_setting(key, value)
+    }
+}
+
+impl<T: SqliteDatabase> StorageBackend for T {
+    fn begin_transaction(&self) -> Result<()> {
+        self.sqlite().begin_transaction()
+    }
+
+    fn commit_transaction(&self) -> Result<()> {
+        self.sqlite().commit_transaction()
+    }
+
+    fn rollback_transaction(&self) -> Result<()> {
+        self.sqlite().rollback_transaction()
+    }
+
+    fn begin_replace(&self, source: AuditSource) -> Result<()> {
+        self.sqlite().audit().begin_replace(source)
+    }
+
+    fn finish_replace(&self) -> Result<()> {
+        self.sqlite().audit().finish_replace()
+    }
+
+    fn trashed_ids(&self) -> Result<HashSet<Uuid>> {
+        self.sqlite().trash().trashed_ids()
+    }
+}
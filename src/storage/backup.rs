@@ -0,0 +1,518 @@
+//! # 数据库备份模块
+//!
+//! 按 `DataConfig` 中的备份设置定期备份数据库，并在迁移、导入、恢复和清理前自动备份。
+//! 每个备份都会进行完整性检查，元数据保存在同名的 `.json` 文件中。
+//! 保留策略：保留最近 N 个备份，另外按天、周、月各保留一个较早的备份
+
+use super::encryption::DatabaseKey;
+use super::{Database, DatabaseConfig, StorageManager};
+use crate::config::DataConfig;
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 备份文件名前缀
+const BACKUP_PREFIX: &str = "lifetracker-";
+
+/// 备份文件扩展名
+const BACKUP_EXTENSION: &str = "db";
+
+/// 定时备份的检查间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 备份原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    /// 定时备份
+    Scheduled,
+    /// 手动备份
+    Manual,
+    /// 数据库迁移前
+    PreMigration,
+    /// 数据导入前
+    PreImport,
+    /// 恢复备份前
+    PreRestore,
+    /// 清理过期数据前
+    PreCleanup,
+}
+
+impl BackupReason {
+    /// 文件名中使用的标识
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+            Self::PreMigration => "pre_migration",
+            Self::PreImport => "pre_import",
+            Self::PreRestore => "pre_restore",
+            Self::PreCleanup => "pre_cleanup",
+        }
+    }
+
+    /// 从文件名标识解析
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "scheduled" => Some(Self::Scheduled),
+            "manual" => Some(Self::Manual),
+            "pre_migration" => Some(Self::PreMigration),
+            "pre_import" => Some(Self::PreImport),
+            "pre_restore" => Some(Self::PreRestore),
+            "pre_cleanup" => Some(Self::PreCleanup),
+            _ => None,
+        }
+    }
+}
+
+/// 备份策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupPolicy {
+    /// 是否启用自动备份（定时备份以及迁移、导入、恢复和清理前的备份）
+    pub auto_backup: bool,
+    /// 定时备份间隔（天）
+    pub interval_days: u32,
+    /// 保留最近的备份数量
+    pub keep_last: u32,
+    /// 按天保留的备份数量
+    pub keep_daily: u32,
+    /// 按周保留的备份数量
+    pub keep_weekly: u32,
+    /// 按月保留的备份数量
+    pub keep_monthly: u32,
+    /// 备份目录
+    pub directory: PathBuf,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self::from(&DataConfig::default())
+    }
+}
+
+impl From<&DataConfig> for BackupPolicy {
+    fn from(config: &DataConfig) -> Self {
+        Self {
+            auto_backup: config.auto_backup,
+            interval_days: config.backup_interval.max(1),
+            keep_last: config.backup_retention.max(1),
+            keep_daily: 7,
+            keep_weekly: 4,
+            keep_monthly: 12,
+            directory: config.backup_directory.clone(),
+        }
+    }
+}
+
+/// 备份信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// 备份ID（备份文件名）
+    pub id: String,
+    /// 备份文件路径
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// 创建时间
+    pub created_at: DateTime<Local>,
+    /// 备份原因
+    pub reason: BackupReason,
+    /// 文件大小（字节）
+    pub size_bytes: u64,
+    /// 数据库结构版本
+    pub schema_version: i32,
+    /// 创建备份的应用版本
+    pub app_version: String,
+    /// 是否通过完整性检查
+    pub verified: bool,
+    /// 是否为加密备份（与数据库使用相同口令）
+    #[serde(default)]
+    pub encrypted: bool,
+}
+
+/// 备份服务
+#[derive(Debug, Clone)]
+pub struct BackupService {
+    /// 备份策略
+    policy: BackupPolicy,
+}
+
+impl BackupService {
+    /// 创建备份服务
+    pub fn new(policy: BackupPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// 获取备份策略
+    pub fn policy(&self) -> &BackupPolicy {
+        &self.policy
+    }
+
+    /// 创建备份并进行完整性检查
+    ///
+    /// 定时和手动备份完成后会按保留策略清理旧备份
+    pub fn create_backup(&self, database: &Database, reason: BackupReason) -> Result<BackupInfo> {
+        std::fs::create_dir_all(&self.policy.directory).map_err(|e| {
+            AppError::Storage(format!(
+                "无法创建备份目录 {}: {}",
+                self.policy.directory.display(),
+                e
+            ))
+        })?;
+
+        let created_at = Local::now();
+        let id = format!(
+            "{}{}-{}.{}",
+            BACKUP_PREFIX,
+            created_at.format("%Y%m%d-%H%M%S%3f"),
+            reason.as_str(),
+            BACKUP_EXTENSION
+        );
+        let path = self.policy.directory.join(&id);
+
+        database.backup_to(&path)?;
+
+        let key = database.key()?;
+        let encrypted = key.is_some();
+        let schema_version = match verify_backup(&path, key) {
+            Ok(version) => version,
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(e);
+            }
+        };
+
+        let info = BackupInfo {
+            id,
+            size_bytes: std::fs::metadata(&path)?.len(),
+            path,
+            created_at,
+            reason,
+            schema_version,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            verified: true,
+            encrypted,
+        };
+        std::fs::write(metadata_path(&info.path), serde_json::to_vec_pretty(&info)?)?;
+        log::info!("已创建备份 {}（{}）", info.id, reason.as_str());
+
+        if matches!(reason, BackupReason::Scheduled | BackupReason::Manual) {
+            if let Err(e) = self.apply_retention() {
+                log::warn!("清理旧备份失败: {}", e);
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// 列出所有备份（按创建时间从新到旧）
+    pub fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        let entries = match std::fs::read_dir(&self.policy.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut backups: Vec<BackupInfo> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_backup_file(path))
+            .filter_map(|path| load_backup_info(&path))
+            .collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// 获取最近一次备份
+    pub fn latest_backup(&self) -> Result<Option<BackupInfo>> {
+        Ok(self.list_backups()?.into_iter().next())
+    }
+
+    /// 是否到了定时备份的时间
+    pub fn is_backup_due(&self) -> Result<bool> {
+        if !self.policy.auto_backup {
+            return Ok(false);
+        }
+        Ok(match self.latest_backup()? {
+            Some(latest) => {
+                Local::now() - latest.created_at
+                    >= chrono::Duration::days(self.policy.interval_days as i64)
+            }
+            None => true,
+        })
+    }
+
+    /// 到期时执行定时备份
+    pub fn run_scheduled(&self, storage: &StorageManager) -> Result<Option<BackupInfo>> {
+        if !self.is_backup_due()? {
+            return Ok(None);
+        }
+        self.create_backup(storage.get_database(), BackupReason::Scheduled)
+            .map(Some)
+    }
+
+    /// 在后台定期检查并执行定时备份
+    pub fn start_schedule(&self, storage: Arc<StorageManager>) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+            loop {
+                timer.tick().await;
+                let service = service.clone();
+                let storage = storage.clone();
+                let result =
+                    tokio::task::spawn_blocking(move || service.run_scheduled(&storage)).await;
+                match result {
+                    Ok(Err(e)) => log::error!("定时备份失败: {}", e),
+                    Err(e) => log::error!("定时备份任务异常: {}", e),
+                    Ok(Ok(_)) => {}
+                }
+            }
+        })
+    }
+
+    /// 恢复指定的备份
+    ///
+    /// 启用自动备份时会先备份当前数据库
+    pub fn restore_backup(&self, storage: &StorageManager, backup_id: &str) -> Result<BackupInfo> {
+        let info = self
+            .list_backups()?
+            .into_iter()
+            .find(|backup| backup.id == backup_id)
+            .ok_or_else(|| AppError::NotFound(format!("备份不存在: {}", backup_id)))?;
+
+        // 存储管理器已配置备份服务时由其负责恢复前备份
+        if storage.backup_service().is_none() && self.policy.auto_backup {
+            self.create_backup(storage.get_database(), BackupReason::PreRestore)?;
+        }
+        storage.restore_database_from_backup(&info.path)?;
+
+        log::info!("已从备份 {} 恢复数据库", info.id);
+        Ok(info)
+    }
+
+    /// 按保留策略删除旧备份，返回被删除的备份
+    pub fn apply_retention(&self) -> Result<Vec<BackupInfo>> {
+        let backups = self.list_backups()?;
+        let retained = select_retained(&backups, &self.policy);
+
+        let mut removed = Vec::new();
+        for backup in backups {
+            if retained.contains(&backup.id) {
+                continue;
+            }
+            std::fs::remove_file(&backup.path)?;
+            let _ = std::fs::remove_file(metadata_path(&backup.path));
+            log::info!("已删除过期备份: {}", backup.id);
+            removed.push(backup);
+        }
+        Ok(removed)
+    }
+}
+
+/// 计算需要保留的备份（输入按创建时间从新到旧排列）
+///
+/// 保留最近 `keep_last` 个备份，并在最近的 `keep_daily` 天、`keep_weekly` 周和
+/// `keep_monthly` 个月中各保留当期最新的一个备份
+pub fn select_retained(backups: &[BackupInfo], policy: &BackupPolicy) -> HashSet<String> {
+    let mut retained: HashSet<String> = backups
+        .iter()
+        .take(policy.keep_last as usize)
+        .map(|backup| backup.id.clone())
+        .collect();
+
+    let mut keep_per_period = |limit: u32, period: fn(&DateTime<Local>) -> (i32, u32)| {
+        let mut periods = HashSet::new();
+        for backup in backups {
+            if periods.len() >= limit as usize {
+                break;
+            }
+            if periods.insert(period(&backup.created_at)) {
+                retained.insert(backup.id.clone());
+            }
+        }
+    };
+    keep_per_period(policy.keep_daily, |time| (time.year(), time.ordinal()));
+    keep_per_period(policy.keep_weekly, |time| {
+        let week = time.iso_week();
+        (week.year(), week.week())
+    });
+    keep_per_period(policy.keep_monthly, |time| (time.year(), time.month()));
+
+    retained
+}
+
+/// 对备份文件进行完整性检查，返回备份的数据库结构版本
+fn verify_backup(path: &Path, key: Option<DatabaseKey>) -> Result<i32> {
+    let backup = StorageManager::new(DatabaseConfig {
+        database_path: path.to_string_lossy().to_string(),
+        encryption_key: key,
+        ..Default::default()
+    })?;
+
+    if !backup.check_integrity()? {
+        return Err(AppError::Storage(format!(
+            "备份完整性检查失败: {}",
+            path.display()
+        )));
+    }
+
+    backup
+        .get_database()
+        .get_connection()?
+        .read(super::migrations::get_schema_version)
+}
+
+/// 是否为备份文件
+fn is_backup_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    name.starts_with(BACKUP_PREFIX)
+        && path.extension().and_then(|ext| ext.to_str()) == Some(BACKUP_EXTENSION)
+}
+
+/// 备份元数据文件路径
+fn metadata_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".json");
+    PathBuf::from(name)
+}
+
+/// 读取备份信息，元数据文件缺失时根据文件名和文件属性推断
+fn load_backup_info(path: &Path) -> Option<BackupInfo> {
+    let id = path.file_name()?.to_str()?.to_string();
+
+    if let Ok(data) = std::fs::read(metadata_path(path)) {
+        match serde_json::from_slice::<BackupInfo>(&data) {
+            Ok(mut info) => {
+                info.id = id;
+                info.path = path.to_path_buf();
+                return Some(info);
+            }
+            Err(e) => log::warn!("备份元数据无法解析 {}: {}", path.display(), e),
+        }
+    }
+
+    let metadata = std::fs::metadata(path).ok()?;
+    let reason = id
+        .trim_end_matches(&format!(".{}", BACKUP_EXTENSION))
+        .rsplit('-')
+        .next()
+        .and_then(BackupReason::from_str)
+        .unwrap_or(BackupReason::Manual);
+
+    Some(BackupInfo {
+        id,
+        path: path.to_path_buf(),
+        created_at: metadata.modified().ok()?.into(),
+        reason,
+        size_bytes: metadata.len(),
+        schema_version: 0,
+        app_version: String::new(),
+        verified: false,
+        encrypted: super::encryption::is_encrypted(path).unwrap_or(false),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::temp_storage;
+    use chrono::TimeZone;
+
+    fn backup_at(id: &str, created_at: DateTime<Local>) -> BackupInfo {
+        BackupInfo {
+            id: id.to_string(),
+            path: PathBuf::from(id),
+            created_at,
+            reason: BackupReason::Scheduled,
+            size_bytes: 0,
+            schema_version: 0,
+            app_version: String::new(),
+            verified: true,
+            encrypted: false,
+        }
+    }
+
+    #[test]
+    fn test_retention_thinning() {
+        // 2024-01-01 起每 12 小时一个备份，共 120 天
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut backups: Vec<BackupInfo> = (0..240)
+            .map(|i| backup_at(&format!("b{}", i), start + chrono::Duration::hours(12 * i)))
+            .collect();
+        backups.reverse();
+
+        let policy = BackupPolicy {
+            auto_backup: true,
+            interval_days: 1,
+            keep_last: 3,
+            keep_daily: 5,
+            keep_weekly: 3,
+            keep_monthly: 4,
+            directory: PathBuf::new(),
+        };
+        let retained = select_retained(&backups, &policy);
+
+        // 最近 3 个
+        assert!(
+            retained.contains("b239") && retained.contains("b238") && retained.contains("b237")
+        );
+        // 最近 5 天每天最新一个：b239、b237、b235、b233、b231
+        assert!(retained.contains("b231") && !retained.contains("b230"));
+        // 每月最新一个：1 月最后一个备份是 2024-01-31 12:00（b61）
+        assert!(retained.contains("b61"));
+        assert!(!retained.contains("b60"));
+        // 最早的备份不在任何保留范围内
+        assert!(!retained.contains("b0"));
+        assert!(retained.len() <= 3 + 5 + 3 + 4);
+    }
+
+    #[test]
+    fn test_backup_list_and_restore() {
+        let (temp_dir, storage) = temp_storage();
+
+        let service = BackupService::new(BackupPolicy {
+            directory: temp_dir.path().join("backups"),
+            ..Default::default()
+        });
+
+        storage
+            .get_database()
+            .set_setting("marker", "before")
+            .unwrap();
+        let backup = service
+            .run_scheduled(&storage)
+            .unwrap()
+            .expect("首次检查时应执行备份");
+        assert!(backup.verified);
+        assert!(backup.schema_version > 0);
+        assert!(service.run_scheduled(&storage).unwrap().is_none());
+
+        storage
+            .get_database()
+            .set_setting("marker", "after")
+            .unwrap();
+        service.restore_backup(&storage, &backup.id).unwrap();
+        assert_eq!(
+            storage
+                .get_database()
+                .get_setting("marker")
+                .unwrap()
+                .as_deref(),
+            Some("before")
+        );
+
+        // 恢复前自动备份了当前数据库
+        let backups = service.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].reason, BackupReason::PreRestore);
+        assert_eq!(backups[1], backup);
+
+        assert!(service.restore_backup(&storage, "missing.db").is_err());
+    }
+}
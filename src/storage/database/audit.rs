@@ -0,0 +1,721 @@
+//! # 审计日志模块
+//!
+//! 记录业务数据表的每一次新建、修改和删除，包括变更的字段及其新旧值、变更时间和来源（界面、导入、同步）。
+//!
+//! 审计记录由写连接上的临时触发器写入 `audit_log` 表，因此直接执行 SQL 的修改同样会被记录；
+//! `audit_log` 表只允许追加，修改或删除记录会被数据库拒绝。
+
+use super::connection::DatabaseConnection;
+use super::trash::TrashEntityType;
+use super::utils::{datetime_from_str, uuid_from_str};
+use crate::errors::{AppError, Result};
+use crate::utils::export::ExportFormat;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use uuid::Uuid;
+
+/// 不记录变更的列（每次修改都会变化）
+const IGNORED_COLUMNS: &[&str] = &["updated_at"];
+
+/// 当前时间（UTC，精确到毫秒）的 SQL 表达式
+const NOW_SQL: &str = "strftime('%Y-%m-%dT%H:%M:%fZ', 'now')";
+
+/// 数据变更来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditSource {
+    /// 界面操作
+    Ui,
+    /// 数据导入
+    Import,
+    /// 云同步
+    Sync,
+    /// 系统维护（数据保留、清理回收站等）
+    System,
+}
+
+impl AuditSource {
+    /// 存储在数据库中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditSource::Ui => "ui",
+            AuditSource::Import => "import",
+            AuditSource::Sync => "sync",
+            AuditSource::System => "system",
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AuditSource::Ui => "界面",
+            AuditSource::Import => "导入",
+            AuditSource::Sync => "同步",
+            AuditSource::System => "系统",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "ui" => Some(AuditSource::Ui),
+            "import" => Some(AuditSource::Import),
+            "sync" => Some(AuditSource::Sync),
+            "system" => Some(AuditSource::System),
+            _ => None,
+        }
+    }
+}
+
+/// 数据变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// 新建
+    Insert,
+    /// 修改
+    Update,
+    /// 移入回收站
+    Delete,
+    /// 从回收站恢复
+    Restore,
+    /// 彻底删除
+    Purge,
+}
+
+impl AuditAction {
+    /// 存储在数据库中的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Insert => "insert",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+            AuditAction::Purge => "purge",
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AuditAction::Insert => "新建",
+            AuditAction::Update => "修改",
+            AuditAction::Delete => "删除",
+            AuditAction::Restore => "恢复",
+            AuditAction::Purge => "彻底删除",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "insert" => Some(AuditAction::Insert),
+            "update" => Some(AuditAction::Update),
+            "delete" => Some(AuditAction::Delete),
+            "restore" => Some(AuditAction::Restore),
+            "purge" => Some(AuditAction::Purge),
+            _ => None,
+        }
+    }
+}
+
+/// 字段的新旧值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// 修改前的值（新建时为 null）
+    pub old: Value,
+    /// 修改后的值（彻底删除时为 null）
+    pub new: Value,
+}
+
+/// 审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 记录ID（递增）
+    pub id: i64,
+    /// 数据类型
+    pub entity_type: TrashEntityType,
+    /// 数据ID
+    pub entity_id: Uuid,
+    /// 变更类型
+    pub action: AuditAction,
+    /// 变更的字段
+    pub changes: BTreeMap<String, FieldChange>,
+    /// 变更来源
+    pub source: AuditSource,
+    /// 变更时间
+    pub changed_at: DateTime<Local>,
+}
+
+/// 审计记录查询条件
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// 数据类型
+    pub entity_type: Option<TrashEntityType>,
+    /// 数据ID
+    pub entity_id: Option<Uuid>,
+    /// 变更来源
+    pub source: Option<AuditSource>,
+    /// 起始时间（包含）
+    pub start: Option<DateTime<Local>>,
+    /// 结束时间（不包含）
+    pub end: Option<DateTime<Local>>,
+    /// 最多返回的记录数
+    pub limit: Option<usize>,
+}
+
+/// 审计日志数据库操作
+pub struct AuditRepository<'a> {
+    connection: &'a DatabaseConnection,
+}
+
+impl<'a> AuditRepository<'a> {
+    /// 创建新的审计日志仓库实例
+    pub fn new(connection: &'a DatabaseConnection) -> Self {
+        Self { connection }
+    }
+
+    // ==================== 变更来源 ====================
+
+    /// 当前写入的变更来源
+    pub fn source(&self) -> Result<AuditSource> {
+        self.connection.write(|conn| {
+            let source: String =
+                conn.query_row("SELECT source FROM audit_context", [], |row| row.get(0))?;
+            AuditSource::from_str(&source)
+                .ok_or_else(|| AppError::Storage(format!("未知的变更来源: {}", source)))
+        })
+    }
+
+    /// 设置之后写入的变更来源
+    pub fn set_source(&self, source: AuditSource) -> Result<()> {
+        self.connection
+            .execute("UPDATE audit_context SET source = ?1", &[&source.as_str()])?;
+        Ok(())
+    }
+
+    /// 以指定来源执行写操作，结束后恢复原来的来源
+    ///
+    /// 设置来源、执行操作和恢复来源在同一个独占写入会话中完成，
+    /// 期间其他线程的写入不会被记为该来源
+    pub fn with_source<T, F>(&self, source: AuditSource, operation: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        self.connection.exclusive(|| {
+            let previous = self.source()?;
+            self.set_source(source)?;
+            let result = operation();
+            self.set_source(previous)?;
+            result
+        })
+    }
+
+    /// 开始整表替换（先清空再重新写入，例如同步导入）
+    ///
+    /// 替换期间删除的行暂不记录，重新写入同一ID时只记录实际变化的字段，
+    /// 调用 [`finish_replace`](Self::finish_replace) 时未重新写入的行记为彻底删除
+    pub fn begin_replace(&self, source: AuditSource) -> Result<()> {
+        self.connection.write(|conn| {
+            conn.execute(
+                "UPDATE audit_context SET source = ?1, replacing = 1",
+                params![source.as_str()],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// 结束整表替换，并将来源恢复为界面
+    pub fn finish_replace(&self) -> Result<()> {
+        self.connection.write(|conn| {
+            conn.execute_batch(&format!(
+                "INSERT INTO audit_log (entity_type, entity_id, action, changes, source, changed_at)
+                 SELECT d.entity_type, d.entity_id, 'purge', d.changes, c.source, {}
+                 FROM audit_replaced d, audit_context c
+                 ORDER BY d.rowid;
+                 DELETE FROM audit_replaced;
+                 UPDATE audit_context SET source = 'ui', replacing = 0;",
+                NOW_SQL
+            ))?;
+            Ok(())
+        })
+    }
+
+    // ==================== 查询和导出 ====================
+
+    /// 按条件查询审计记录（按时间从旧到新）
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let mut sql = String::from(
+            "SELECT id, entity_type, entity_id, action, changes, source, changed_at
+             FROM audit_log WHERE 1 = 1",
+        );
+        let mut values: Vec<SqlValue> = Vec::new();
+
+        if let Some(entity_type) = query.entity_type {
+            values.push(entity_type.table().to_string().into());
+            sql.push_str(&format!(" AND entity_type = ?{}", values.len()));
+        }
+        if let Some(entity_id) = query.entity_id {
+            values.push(entity_id.to_string().into());
+            sql.push_str(&format!(" AND entity_id = ?{}", values.len()));
+        }
+        if let Some(source) = query.source {
+            values.push(source.as_str().to_string().into());
+            sql.push_str(&format!(" AND source = ?{}", values.len()));
+        }
+        if let Some(start) = query.start {
+            values.push(to_utc_string(start).into());
+            sql.push_str(&format!(" AND changed_at >= ?{}", values.len()));
+        }
+        if let Some(end) = query.end {
+            values.push(to_utc_string(end).into());
+            sql.push_str(&format!(" AND changed_at < ?{}", values.len()));
+        }
+        sql.push_str(" ORDER BY id ASC");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {}", limit));
+        }
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    uuid_from_str(row.get(2)?)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    datetime_from_str(row.get(6)?)?,
+                ))
+            })?;
+
+            let mut entries = Vec::new();
+            for row in rows {
+                let (id, table, entity_id, action, changes, source, changed_at) = row?;
+                entries.push(AuditEntry {
+                    id,
+                    entity_type: TrashEntityType::ALL
+                        .into_iter()
+                        .find(|entity_type| entity_type.table() == table)
+                        .ok_or_else(|| AppError::Storage(format!("未知的数据表: {}", table)))?,
+                    entity_id,
+                    action: AuditAction::from_str(&action)
+                        .ok_or_else(|| AppError::Storage(format!("未知的变更类型: {}", action)))?,
+                    changes: serde_json::from_str(&changes)?,
+                    source: AuditSource::from_str(&source)
+                        .ok_or_else(|| AppError::Storage(format!("未知的变更来源: {}", source)))?,
+                    changed_at,
+                });
+            }
+            Ok(entries)
+        })
+    }
+
+    /// 单条数据的变更历史
+    pub fn history(&self, entity_type: TrashEntityType, id: Uuid) -> Result<Vec<AuditEntry>> {
+        self.query(&AuditQuery {
+            entity_type: Some(entity_type),
+            entity_id: Some(id),
+            ..Default::default()
+        })
+    }
+
+    /// 导出审计记录，支持 JSON 和 CSV 格式
+    ///
+    /// CSV 中每个变更字段占一行
+    pub fn export<W: Write>(
+        &self,
+        query: &AuditQuery,
+        format: ExportFormat,
+        writer: &mut W,
+    ) -> Result<usize> {
+        let entries = self.query(query)?;
+
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(&mut *writer, &entries)?;
+            }
+            ExportFormat::Csv => {
+                writeln!(writer, "ID,时间,来源,数据类型,数据ID,操作,字段,原值,新值")?;
+                for entry in &entries {
+                    let changed_at = entry.changed_at.format("%Y-%m-%d %H:%M:%S").to_string();
+                    let fields: Vec<(&str, Option<&FieldChange>)> = if entry.changes.is_empty() {
+                        vec![("", None)]
+                    } else {
+                        entry
+                            .changes
+                            .iter()
+                            .map(|(field, change)| (field.as_str(), Some(change)))
+                            .collect()
+                    };
+
+                    for (field, change) in fields {
+                        writeln!(
+                            writer,
+                            "{},{},{},{},{},{},{},{},{}",
+                            entry.id,
+                            changed_at,
+                            entry.source.display_name(),
+                            entry.entity_type.display_name(),
+                            entry.entity_id,
+                            entry.action.display_name(),
+                            escape_csv(field),
+                            escape_csv(&change.map(|c| value_text(&c.old)).unwrap_or_default()),
+                            escape_csv(&change.map(|c| value_text(&c.new)).unwrap_or_default()),
+                        )?;
+                    }
+                }
+            }
+            _ => {
+                return Err(AppError::Validation(format!(
+                    "审计日志不支持导出为 {} 格式",
+                    format.extension()
+                )));
+            }
+        }
+
+        Ok(entries.len())
+    }
+
+    /// 导出审计记录到文件，按扩展名选择格式，返回导出的记录数
+    pub fn export_to_file<P: AsRef<Path>>(&self, query: &AuditQuery, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let format = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(ExportFormat::from_extension)
+            .ok_or_else(|| AppError::Validation(format!("无法识别导出格式: {}", path.display())))?;
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let count = self.export(query, format, &mut writer)?;
+        writer.flush()?;
+
+        log::info!("Exported {} audit entries to {}", count, path.display());
+        Ok(count)
+    }
+}
+
+/// 在写连接上安装审计触发器
+///
+/// 触发器和变更来源都是连接级的临时对象，需要在迁移完成后（表结构确定后）调用
+pub(crate) fn install_triggers(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TEMP TABLE IF NOT EXISTS audit_context (
+             source TEXT NOT NULL,
+             replacing INTEGER NOT NULL DEFAULT 0
+         );
+         INSERT INTO audit_context (source)
+             SELECT 'ui' WHERE NOT EXISTS (SELECT 1 FROM audit_context);
+         CREATE TEMP TABLE IF NOT EXISTS audit_replaced (
+             entity_type TEXT NOT NULL,
+             entity_id TEXT NOT NULL,
+             image TEXT NOT NULL,
+             changes TEXT NOT NULL,
+             PRIMARY KEY (entity_type, entity_id)
+         );",
+    )?;
+
+    for entity_type in TrashEntityType::ALL {
+        let table = entity_type.table();
+        let columns = audited_columns(conn, table)?;
+        let old_image = row_image("OLD", &columns);
+        let new_image = row_image("NEW", &columns);
+
+        conn.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS temp.audit_{table}_insert;
+             DROP TRIGGER IF EXISTS temp.audit_{table}_update;
+             DROP TRIGGER IF EXISTS temp.audit_{table}_delete;
+
+             CREATE TEMP TRIGGER audit_{table}_insert AFTER INSERT ON main.{table}
+             BEGIN
+                 INSERT INTO audit_log (entity_type, entity_id, action, changes, source, changed_at)
+                 SELECT * FROM (
+                     SELECT '{table}', NEW.id,
+                            CASE WHEN r.image IS NULL THEN 'insert' ELSE 'update' END AS action,
+                            {insert_changes} AS changes,
+                            c.source, {now}
+                     FROM audit_context c
+                     LEFT JOIN audit_replaced r ON r.entity_type = '{table}' AND r.entity_id = NEW.id
+                 )
+                 WHERE action = 'insert' OR changes <> '{{}}';
+                 DELETE FROM audit_replaced WHERE entity_type = '{table}' AND entity_id = NEW.id;
+             END;
+
+             CREATE TEMP TRIGGER audit_{table}_update AFTER UPDATE ON main.{table}
+             BEGIN
+                 INSERT INTO audit_log (entity_type, entity_id, action, changes, source, changed_at)
+                 SELECT * FROM (
+                     SELECT '{table}', NEW.id,
+                            CASE
+                                WHEN OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL THEN 'delete'
+                                WHEN OLD.deleted_at IS NOT NULL AND NEW.deleted_at IS NULL THEN 'restore'
+                                ELSE 'update'
+                            END,
+                            {update_changes} AS changes,
+                            c.source, {now}
+                     FROM audit_context c
+                 )
+                 WHERE changes <> '{{}}';
+             END;
+
+             CREATE TEMP TRIGGER audit_{table}_delete AFTER DELETE ON main.{table}
+             BEGIN
+                 INSERT OR REPLACE INTO audit_replaced (entity_type, entity_id, image, changes)
+                 SELECT '{table}', OLD.id, {old_image}, {delete_changes}
+                 FROM audit_context WHERE replacing = 1;
+                 INSERT INTO audit_log (entity_type, entity_id, action, changes, source, changed_at)
+                 SELECT '{table}', OLD.id, 'purge', {delete_changes}, source, {now}
+                 FROM audit_context WHERE replacing = 0;
+             END;",
+            table = table,
+            now = NOW_SQL,
+            old_image = old_image,
+            insert_changes = changes_sql("COALESCE(r.image, '{}')", &new_image),
+            update_changes = changes_sql(&old_image, &new_image),
+            delete_changes = removed_sql(&old_image),
+        ))?;
+    }
+
+    log::debug!("Audit triggers installed");
+    Ok(())
+}
+
+/// 需要记录变更的列
+fn audited_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA main.table_info({})", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>("name"))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(columns
+        .into_iter()
+        .filter(|column| !IGNORED_COLUMNS.contains(&column.as_str()))
+        .collect())
+}
+
+/// 将一行数据转换为 JSON 对象的 SQL 表达式
+fn row_image(row: &str, columns: &[String]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|column| format!("'{0}', {1}.\"{0}\"", column, row))
+        .collect();
+    format!("json_object({})", fields.join(", "))
+}
+
+/// 比较两行数据，生成变化字段的新旧值
+fn changes_sql(old_image: &str, new_image: &str) -> String {
+    format!(
+        "(SELECT json_group_object(n.key, json_object('old', o.value, 'new', n.value))
+          FROM json_each({new_image}) AS n
+          LEFT JOIN json_each({old_image}) AS o ON o.key = n.key
+          WHERE o.value IS NOT n.value)"
+    )
+}
+
+/// 彻底删除时记录被删除行的非空字段
+fn removed_sql(old_image: &str) -> String {
+    format!(
+        "(SELECT json_group_object(o.key, json_object('old', o.value, 'new', NULL))
+          FROM json_each({old_image}) AS o
+          WHERE o.value IS NOT NULL)"
+    )
+}
+
+/// 与触发器写入的 changed_at 相同格式的时间文本
+fn to_utc_string(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 字段值的文本形式
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 转义CSV字段
+fn escape_csv(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::task_models::TaskUpdate;
+    use crate::storage::test_support::{new_task, temp_database};
+    use crate::storage::Database;
+
+    fn insert_task(database: &Database, name: &str) -> Uuid {
+        let task = new_task(name);
+        database.insert_task(&task).unwrap();
+        task.id
+    }
+
+    #[test]
+    fn test_records_changes_with_source() {
+        let (temp_dir, database) = temp_database();
+        let audit = database.audit();
+        let start = Local::now() - chrono::Duration::seconds(1);
+
+        let id = insert_task(&database, "写报告");
+        audit
+            .with_source(AuditSource::Import, || {
+                database.update_task(
+                    id,
+                    &TaskUpdate {
+                        name: Some("写月报".to_string()),
+                        ..Default::default()
+                    },
+                )
+            })
+            .unwrap();
+        database.delete_task(id).unwrap();
+        database.trash().restore(TrashEntityType::Task, id).unwrap();
+        database
+            .trash()
+            .move_to_trash(TrashEntityType::Task, id)
+            .unwrap();
+        database.trash().purge(TrashEntityType::Task, id).unwrap();
+
+        let history = audit.history(TrashEntityType::Task, id).unwrap();
+        let actions: Vec<AuditAction> = history.iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Insert,
+                AuditAction::Update,
+                AuditAction::Delete,
+                AuditAction::Restore,
+                AuditAction::Delete,
+                AuditAction::Purge,
+            ]
+        );
+
+        let update = &history[1];
+        assert_eq!(update.source, AuditSource::Import);
+        assert_eq!(update.changes.len(), 1);
+        assert_eq!(update.changes["name"].old, Value::from("写报告"));
+        assert_eq!(update.changes["name"].new, Value::from("写月报"));
+        assert_eq!(history[2].source, AuditSource::Ui);
+        assert_eq!(history[5].changes["name"].old, Value::from("写月报"));
+
+        // 按来源和时间范围查询
+        let imported = audit
+            .query(&AuditQuery {
+                source: Some(AuditSource::Import),
+                start: Some(start),
+                end: Some(Local::now() + chrono::Duration::seconds(1)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(imported.len(), 1);
+        assert!(audit
+            .query(&AuditQuery {
+                end: Some(start),
+                ..Default::default()
+            })
+            .unwrap()
+            .is_empty());
+
+        // 审计记录只允许追加
+        assert!(database.execute("DELETE FROM audit_log", &[]).is_err());
+        assert!(database
+            .execute("UPDATE audit_log SET source = 'ui'", &[])
+            .is_err());
+
+        let export_path = temp_dir.path().join("audit.csv");
+        assert_eq!(
+            audit
+                .export_to_file(&AuditQuery::default(), &export_path)
+                .unwrap(),
+            6
+        );
+        let csv = std::fs::read_to_string(export_path).unwrap();
+        assert!(csv.contains("写报告,写月报"));
+    }
+
+    #[test]
+    fn test_with_source_does_not_leak_to_other_threads() {
+        let (_temp_dir, database) = temp_database();
+        let database = std::sync::Arc::new(database);
+        let imported = insert_task(&database, "导入任务");
+        let edited = insert_task(&database, "界面任务");
+        let rename = |name: &str| TaskUpdate {
+            name: Some(name.to_string()),
+            ..Default::default()
+        };
+
+        let (entered_tx, entered_rx) = std::sync::mpsc::channel();
+        let importer = {
+            let database = database.clone();
+            std::thread::spawn(move || {
+                database
+                    .audit()
+                    .with_source(AuditSource::Import, || {
+                        entered_tx.send(()).unwrap();
+                        std::thread::sleep(std::time::Duration::from_millis(100));
+                        database.update_task(imported, &rename("导入后"))
+                    })
+                    .unwrap();
+            })
+        };
+
+        // 导入进行中时界面修改另一条数据
+        entered_rx.recv().unwrap();
+        database.update_task(edited, &rename("界面修改")).unwrap();
+        importer.join().unwrap();
+
+        let audit = database.audit();
+        let last_source = |id| audit.history(TrashEntityType::Task, id).unwrap()[1].source;
+        assert_eq!(last_source(imported), AuditSource::Import);
+        assert_eq!(last_source(edited), AuditSource::Ui);
+        assert_eq!(audit.source().unwrap(), AuditSource::Ui);
+    }
+
+    #[test]
+    fn test_replace_records_only_net_changes() {
+        let (_temp_dir, database) = temp_database();
+        let audit = database.audit();
+        let kept = new_task("保留");
+        let mut changed = new_task("修改前");
+        let removed = new_task("删除");
+        for task in [&kept, &changed, &removed] {
+            database.insert_task(task).unwrap();
+        }
+
+        // 模拟同步导入：清空后重新写入
+        audit.begin_replace(AuditSource::Sync).unwrap();
+        database.execute("DELETE FROM tasks", &[]).unwrap();
+        changed.name = "修改后".to_string();
+        database.insert_task(&kept).unwrap();
+        database.insert_task(&changed).unwrap();
+        audit.finish_replace().unwrap();
+        assert_eq!(audit.source().unwrap(), AuditSource::Ui);
+
+        let synced = audit
+            .query(&AuditQuery {
+                source: Some(AuditSource::Sync),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(synced.len(), 2);
+        assert_eq!(synced[0].entity_id, changed.id);
+        assert_eq!(synced[0].action, AuditAction::Update);
+        assert_eq!(synced[0].changes.len(), 1);
+        assert_eq!(synced[0].changes["name"].old, Value::from("修改前"));
+        assert_eq!(synced[0].changes["name"].new, Value::from("修改后"));
+        assert_eq!(synced[1].entity_id, removed.id);
+        assert_eq!(synced[1].action, AuditAction::Purge);
+    }
+}
@@ -3,24 +3,154 @@
 //! 提供SQLite数据库的连接管理和基本操作
 
 use crate::errors::{AppError, Result};
+use crate::storage::encryption::{self, DatabaseKey};
 use rusqlite::{Connection, Result as SqliteResult};
+use std::ops::Deref;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+/// 连接池配置
+#[derive(Debug, Clone)]
+pub struct PoolConfig {
+    /// 最多同时打开的只读连接数
+    pub max_readers: usize,
+    /// 数据库被锁定时的等待时间，也是等待空闲读连接的最长时间
+    pub busy_timeout: Duration,
+    /// 是否启用WAL模式（读写可以并发进行）
+    pub enable_wal: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_readers: 4,
+            busy_timeout: Duration::from_secs(30),
+            enable_wal: true,
+        }
+    }
+}
+
+impl From<&crate::storage::DatabaseConfig> for PoolConfig {
+    fn from(config: &crate::storage::DatabaseConfig) -> Self {
+        Self {
+            max_readers: (config.pool_size as usize).max(1),
+            busy_timeout: Duration::from_secs(config.timeout_seconds.max(1)),
+            enable_wal: config.enable_wal,
+        }
+    }
+}
+
+/// 只读连接池的状态
+#[derive(Debug, Default)]
+struct ReaderPool {
+    /// 空闲的只读连接
+    idle: Vec<Connection>,
+    /// 已打开（空闲和使用中）的只读连接数
+    open: usize,
+    /// 连接代数，口令变更后递增，旧连接归还时直接关闭
+    generation: u64,
+}
+
+/// 从连接池借出的只读连接，离开作用域时自动归还
+struct PooledReader<'a> {
+    owner: &'a DatabaseConnection,
+    connection: Option<Connection>,
+    generation: u64,
+}
+
+impl Deref for PooledReader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection
+            .as_ref()
+            .expect("pooled connection is present until drop")
+    }
+}
+
+impl Drop for PooledReader<'_> {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            self.owner.release_reader(connection, self.generation);
+        }
+    }
+}
+
+/// 独占写入会话的状态
+#[derive(Debug, Default)]
+struct WriteSession {
+    /// 持有会话的线程
+    owner: Option<ThreadId>,
+    /// 同一线程重入的层数
+    depth: usize,
+}
+
+/// 独占写入会话的守卫，离开作用域时退出会话
+struct WriteSessionGuard<'a> {
+    owner: &'a DatabaseConnection,
+}
+
+impl Drop for WriteSessionGuard<'_> {
+    fn drop(&mut self) {
+        let mut session = match self.owner.session.lock() {
+            Ok(session) => session,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        session.depth -= 1;
+        if session.depth == 0 {
+            session.owner = None;
+            self.owner.session_released.notify_all();
+        }
+    }
+}
 
 /// 数据库连接池
 ///
-/// 提供读写分离的数据库连接管理
+/// 一个互斥的写连接加上按需创建、可复用的只读连接池
 #[derive(Debug)]
 pub struct DatabaseConnection {
     /// 数据库文件路径
     database_path: String,
     /// 写连接（互斥）
     write_connection: Arc<Mutex<Connection>>,
+    /// 加密口令（未加密时为 None）
+    key: RwLock<Option<DatabaseKey>>,
+    /// 连接池配置
+    config: PoolConfig,
+    /// 只读连接池
+    readers: Mutex<ReaderPool>,
+    /// 有只读连接归还时通知等待者
+    reader_available: Condvar,
+    /// 独占写入会话，会话期间其他线程的写操作等待
+    session: Mutex<WriteSession>,
+    /// 会话结束时通知等待者
+    session_released: Condvar,
 }
 
 impl DatabaseConnection {
     /// 创建新的数据库连接池
     pub fn new<P: AsRef<Path>>(database_path: P) -> Result<Self> {
+        Self::new_with_key(database_path, None)
+    }
+
+    /// 使用口令打开加密数据库
+    ///
+    /// 读写连接在执行其他语句前都会先设置口令，口令错误时返回权限错误
+    pub fn new_with_key<P: AsRef<Path>>(
+        database_path: P,
+        key: Option<DatabaseKey>,
+    ) -> Result<Self> {
+        Self::with_config(database_path, key, PoolConfig::default())
+    }
+
+    /// 按连接池配置打开数据库
+    pub fn with_config<P: AsRef<Path>>(
+        database_path: P,
+        key: Option<DatabaseKey>,
+        config: PoolConfig,
+    ) -> Result<Self> {
         let path_str = database_path.as_ref().to_string_lossy().to_string();
 
         // 确保数据库父目录存在
@@ -31,39 +161,157 @@ impl DatabaseConnection {
         }
 
         // 创建写连接
-        let write_conn = Connection::open(&database_path)?;
+        let write_conn = encryption::open_connection(&database_path, key.as_ref())?;
 
         // 配置数据库参数
-        let _journal_mode: String =
-            write_conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
-        write_conn.busy_timeout(std::time::Duration::from_secs(30))?;
+        if config.enable_wal {
+            let _journal_mode: String =
+                write_conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
+            // 写入约4MB后自动检查点，避免WAL文件无限增长
+            let _pages: i64 =
+                write_conn.query_row("PRAGMA wal_autocheckpoint=1000", [], |row| row.get(0))?;
+        }
+        write_conn.busy_timeout(config.busy_timeout)?;
         write_conn.execute("PRAGMA foreign_keys=ON", [])?;
         write_conn.execute("PRAGMA synchronous=NORMAL", [])?;
 
         Ok(Self {
             database_path: path_str,
             write_connection: Arc::new(Mutex::new(write_conn)),
+            key: RwLock::new(key),
+            config,
+            readers: Mutex::new(ReaderPool::default()),
+            reader_available: Condvar::new(),
+            session: Mutex::new(WriteSession::default()),
+            session_released: Condvar::new(),
         })
     }
 
+    /// 连接池配置
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
     /// 创建只读连接
     fn create_read_connection(&self) -> Result<Connection> {
-        let conn = Connection::open(&self.database_path)?;
-        // 只读连接配置
-        let _journal_mode: String =
-            conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
-        conn.busy_timeout(std::time::Duration::from_secs(10))?;
+        let conn = encryption::open_connection(&self.database_path, self.key()?.as_ref())?;
+        // 只读连接配置（WAL模式由写连接设置并持久化在数据库文件中）
+        conn.busy_timeout(self.config.busy_timeout)?;
         conn.execute("PRAGMA query_only=ON", [])?;
+        conn.execute("PRAGMA temp_store=MEMORY", [])?;
         Ok(conn)
     }
 
-    /// 执行读操作（不需要锁）
+    fn lock_readers(&self) -> Result<MutexGuard<'_, ReaderPool>> {
+        self.readers
+            .lock()
+            .map_err(|_| AppError::System("Failed to acquire reader pool lock".to_string()))
+    }
+
+    /// 借出一个只读连接，连接池已满时等待其他读操作归还
+    fn acquire_reader(&self) -> Result<PooledReader<'_>> {
+        let mut pool = self.lock_readers()?;
+        loop {
+            if let Some(connection) = pool.idle.pop() {
+                return Ok(PooledReader {
+                    owner: self,
+                    connection: Some(connection),
+                    generation: pool.generation,
+                });
+            }
+
+            if pool.open < self.config.max_readers {
+                pool.open += 1;
+                let generation = pool.generation;
+                drop(pool);
+
+                return match self.create_read_connection() {
+                    Ok(connection) => Ok(PooledReader {
+                        owner: self,
+                        connection: Some(connection),
+                        generation,
+                    }),
+                    Err(e) => {
+                        self.lock_readers()?.open -= 1;
+                        self.reader_available.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+
+            let (guard, timeout) = self
+                .reader_available
+                .wait_timeout(pool, self.config.busy_timeout)
+                .map_err(|_| AppError::System("Failed to acquire reader pool lock".to_string()))?;
+            pool = guard;
+            if timeout.timed_out() && pool.idle.is_empty() && pool.open >= self.config.max_readers {
+                return Err(AppError::Storage("等待数据库读连接超时".to_string()));
+            }
+        }
+    }
+
+    /// 归还只读连接
+    fn release_reader(&self, connection: Connection, generation: u64) {
+        let Ok(mut pool) = self.readers.lock() else {
+            return;
+        };
+        if generation == pool.generation {
+            pool.idle.push(connection);
+        } else {
+            pool.open -= 1;
+        }
+        drop(pool);
+        self.reader_available.notify_one();
+    }
+
+    /// 关闭所有空闲的只读连接，使用中的连接在归还时关闭
+    pub fn reset_readers(&self) -> Result<()> {
+        let mut pool = self.lock_readers()?;
+        pool.open -= pool.idle.len();
+        pool.idle.clear();
+        pool.generation += 1;
+        drop(pool);
+        self.reader_available.notify_all();
+        Ok(())
+    }
+
+    /// 执行读操作（从只读连接池借用连接，不占用写锁）
     pub fn read<T, F>(&self, f: F) -> Result<T>
     where
         F: FnOnce(&Connection) -> Result<T>,
     {
-        let read_conn = self.create_read_connection()?;
-        f(&read_conn)
+        let reader = self.acquire_reader()?;
+        f(&reader)
+    }
+
+    /// 进入独占写入会话，其他线程持有会话时等待，同一线程可重入
+    fn enter_session(&self) -> Result<WriteSessionGuard<'_>> {
+        let current = thread::current().id();
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| AppError::System("Failed to acquire write session".to_string()))?;
+        while session.owner.is_some_and(|owner| owner != current) {
+            session = self
+                .session_released
+                .wait(session)
+                .map_err(|_| AppError::System("Failed to acquire write session".to_string()))?;
+        }
+        session.owner = Some(current);
+        session.depth += 1;
+        Ok(WriteSessionGuard { owner: self })
+    }
+
+    /// 在独占写入会话中执行一组操作
+    ///
+    /// 会话期间其他线程的写操作等待会话结束，当前线程的写操作照常执行，
+    /// 用于需要和连接级状态（如审计来源）保持一致的多步写入
+    pub fn exclusive<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let _session = self.enter_session()?;
+        f()
     }
 
     /// 执行写操作（需要独占锁）
@@ -71,6 +319,7 @@ impl DatabaseConnection {
     where
         F: FnOnce(&mut Connection) -> Result<T>,
     {
+        let _session = self.enter_session()?;
         let mut write_conn = self
             .write_connection
             .lock()
@@ -115,8 +364,134 @@ impl DatabaseConnection {
         })
     }
 
+    /// 设置数据变更回调
+    ///
+    /// 写连接每插入、更新或删除一行都会以表名调用回调，传入 None 时移除回调
+    pub fn set_change_hook<F>(&self, hook: Option<F>) -> Result<()>
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
+        self.write(|conn| {
+            conn.update_hook(hook.map(|mut hook| {
+                move |_action: rusqlite::hooks::Action, _db: &str, table: &str, _rowid: i64| {
+                    hook(table)
+                }
+            }));
+            Ok(())
+        })
+    }
+
+    /// 当前的加密口令
+    pub fn key(&self) -> Result<Option<DatabaseKey>> {
+        self.key
+            .read()
+            .map(|key| key.clone())
+            .map_err(|_| AppError::System("Failed to acquire key lock".to_string()))
+    }
+
+    /// 是否为加密数据库
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self.key(), Ok(Some(_)))
+    }
+
+    /// 修改加密数据库的口令
+    ///
+    /// 在写连接上执行 rekey，之后新建的读连接使用新口令
+    pub fn change_key(&self, new_key: DatabaseKey) -> Result<()> {
+        if !self.is_encrypted() {
+            return Err(AppError::Business("数据库未加密，无法修改口令".to_string()));
+        }
+
+        let mut key = self
+            .key
+            .write()
+            .map_err(|_| AppError::System("Failed to acquire key lock".to_string()))?;
+        self.write(|conn| encryption::rekey(conn, &new_key))?;
+        *key = Some(new_key);
+        drop(key);
+
+        // 旧的只读连接使用的是旧口令
+        self.reset_readers()
+    }
+
     /// 获取写连接的引用（用于迁移等特殊操作）
     pub fn get_raw_connection(&self) -> Arc<Mutex<Connection>> {
         self.write_connection.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use tempfile::TempDir;
+
+    fn open(dir: &TempDir, max_readers: usize) -> DatabaseConnection {
+        DatabaseConnection::with_config(
+            dir.path().join("pool.db"),
+            None,
+            PoolConfig {
+                max_readers,
+                busy_timeout: Duration::from_secs(5),
+                enable_wal: true,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reader_pool_is_bounded_and_reused() {
+        let dir = TempDir::new().unwrap();
+        let connection = Arc::new(open(&dir, 2));
+        connection
+            .execute("CREATE TABLE items (value INTEGER NOT NULL)", &[])
+            .unwrap();
+        connection
+            .execute("INSERT INTO items (value) VALUES (1)", &[])
+            .unwrap();
+
+        let barrier = Arc::new(Barrier::new(4));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let connection = connection.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    connection
+                        .query_row("SELECT COUNT(*) FROM items", &[], |row| {
+                            row.get::<_, i64>(0)
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+
+        let pool = connection.lock_readers().unwrap();
+        assert!(pool.open <= 2);
+        assert_eq!(pool.idle.len(), pool.open);
+        drop(pool);
+
+        // 只读连接不能写入
+        assert!(connection
+            .read(|conn| Ok(conn.execute("INSERT INTO items (value) VALUES (2)", [])?))
+            .is_err());
+    }
+
+    #[test]
+    fn test_reset_readers_closes_idle_connections() {
+        let dir = TempDir::new().unwrap();
+        let connection = open(&dir, 3);
+        connection
+            .read(|conn| Ok(conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?))
+            .unwrap();
+        assert_eq!(connection.lock_readers().unwrap().open, 1);
+
+        connection.reset_readers().unwrap();
+        let pool = connection.lock_readers().unwrap();
+        assert_eq!(pool.open, 0);
+        assert_eq!(pool.generation, 1);
+    }
+}
@@ -2,18 +2,26 @@
 //!
 //! 重构后的模块化数据库操作接口
 
+pub mod audit;
 pub mod connection;
+pub mod notes;
 pub mod tasks;
 pub mod time_entries;
+pub mod trash;
 pub mod utils;
 
 // 重新导出主要结构体和函数
-pub use connection::DatabaseConnection;
+pub use audit::{AuditAction, AuditEntry, AuditQuery, AuditRepository, AuditSource, FieldChange};
+pub use connection::{DatabaseConnection, PoolConfig};
+pub use notes::NotesRepository;
 pub use tasks::TasksRepository;
 pub use time_entries::TimeEntriesRepository;
+pub use trash::{TrashEntityType, TrashItem, TrashRepository};
 
-use crate::errors::Result;
+use crate::errors::{AppError, Result};
+use crate::storage::encryption::DatabaseKey;
 use std::path::Path;
+use std::sync::Arc;
 
 /// 数据库管理器
 ///
@@ -32,8 +40,34 @@ impl Database {
     /// # 参数
     /// * `database_path` - 数据库文件路径
     pub fn new<P: AsRef<Path>>(database_path: P) -> Result<Self> {
+        Self::new_with_key(database_path, None)
+    }
+
+    /// 使用口令打开数据库实例
+    ///
+    /// # 参数
+    /// * `database_path` - 数据库文件路径
+    /// * `key` - 加密口令，明文数据库传入 None
+    pub fn new_with_key<P: AsRef<Path>>(
+        database_path: P,
+        key: Option<DatabaseKey>,
+    ) -> Result<Self> {
+        Self::with_config(database_path, key, PoolConfig::default())
+    }
+
+    /// 按连接池配置打开数据库实例
+    ///
+    /// # 参数
+    /// * `database_path` - 数据库文件路径
+    /// * `key` - 加密口令，明文数据库传入 None
+    /// * `config` - 只读连接数、忙等待超时和WAL设置
+    pub fn with_config<P: AsRef<Path>>(
+        database_path: P,
+        key: Option<DatabaseKey>,
+        config: PoolConfig,
+    ) -> Result<Self> {
         let path_str = database_path.as_ref().to_string_lossy().to_string();
-        let connection = DatabaseConnection::new(database_path)?;
+        let connection = DatabaseConnection::with_config(database_path, key, config)?;
 
         Ok(Self {
             connection,
@@ -50,15 +84,80 @@ impl Database {
             let mut migration_manager = MigrationManager::new_with_connection(conn);
             migration_manager.run_migrations()?;
             log::info!("Database migration completed");
-            Ok(())
+
+            // 表结构确定后安装审计触发器
+            audit::install_triggers(conn)
         })
     }
 
+    /// 是否有待执行的迁移（新建的空数据库返回 false）
+    pub fn needs_migration(&self) -> Result<bool> {
+        self.connection
+            .read(crate::storage::migrations::needs_migration)
+    }
+
+    /// 数据库文件路径
+    pub fn path(&self) -> &Path {
+        Path::new(&self.database_path)
+    }
+
+    /// 当前使用的加密口令（明文数据库为 None）
+    pub fn key(&self) -> Result<Option<DatabaseKey>> {
+        self.connection.key()
+    }
+
+    /// 使用 SQLite 备份 API 复制数据库，加密数据库的备份使用相同口令
+    pub fn backup_to<P: AsRef<Path>>(&self, backup_path: P) -> Result<()> {
+        let key = self.key()?;
+        let source_conn = self.connection.get_raw_connection();
+        let source_conn = source_conn.lock().unwrap();
+        let mut backup_conn =
+            crate::storage::encryption::open_connection(backup_path, key.as_ref())?;
+
+        let backup = rusqlite::backup::Backup::new(&source_conn, &mut backup_conn)?;
+        backup.run_to_completion(5, std::time::Duration::from_millis(250), None)?;
+
+        log::info!("Database backup completed");
+        Ok(())
+    }
+
+    /// 是否为加密数据库
+    pub fn is_encrypted(&self) -> bool {
+        self.connection.is_encrypted()
+    }
+
+    /// 修改数据库加密口令，需提供当前口令
+    pub fn change_passphrase(&self, old_key: &DatabaseKey, new_key: DatabaseKey) -> Result<()> {
+        if self.connection.key()?.as_ref() != Some(old_key) {
+            return Err(crate::errors::AppError::Permission(
+                "当前数据库口令不正确".to_string(),
+            ));
+        }
+        self.connection.change_key(new_key)?;
+        log::info!("Database passphrase changed");
+        Ok(())
+    }
+
     /// 获取数据库连接
     pub fn get_connection(&self) -> Result<&DatabaseConnection> {
         Ok(&self.connection)
     }
 
+    /// 在阻塞线程池中执行数据库操作
+    ///
+    /// 查询在 `spawn_blocking` 线程上运行，读操作使用只读连接池，
+    /// 适合在界面的异步任务中加载大量历史数据而不阻塞窗口
+    pub async fn run_blocking<T, F>(self: &Arc<Self>, f: F) -> Result<T>
+    where
+        F: FnOnce(&Database) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let database = Arc::clone(self);
+        tokio::task::spawn_blocking(move || f(&database))
+            .await
+            .map_err(|e| AppError::System(format!("数据库后台任务失败: {}", e)))?
+    }
+
     /// 关闭数据库连接
     pub fn close(self) -> Result<()> {
         // 连接会在Drop时自动关闭
@@ -76,6 +175,21 @@ impl Database {
         TasksRepository::new(&self.connection)
     }
 
+    /// 获取笔记仓库
+    pub fn notes(&self) -> NotesRepository<'_> {
+        NotesRepository::new(&self.connection)
+    }
+
+    /// 获取回收站仓库
+    pub fn trash(&self) -> TrashRepository<'_> {
+        TrashRepository::new(&self.connection)
+    }
+
+    /// 获取审计日志仓库
+    pub fn audit(&self) -> AuditRepository<'_> {
+        AuditRepository::new(&self.connection)
+    }
+
     // ==================== 时间记录操作代理方法 ====================
 
     /// 插入时间记录
@@ -111,6 +225,23 @@ impl Database {
         self.time_entries().get_by_category(category_id)
     }
 
+    /// 统计时间记录数量（可按开始时间范围过滤）
+    pub fn count_time_entries(
+        &self,
+        range: Option<(chrono::DateTime<chrono::Local>, chrono::DateTime<chrono::Local>)>,
+    ) -> Result<usize> {
+        self.time_entries().count(range)
+    }
+
+    /// 按开始时间从旧到新遍历时间记录，回调返回 `false` 时停止
+    pub fn for_each_time_entry(
+        &self,
+        range: Option<(chrono::DateTime<chrono::Local>, chrono::DateTime<chrono::Local>)>,
+        f: &mut dyn FnMut(crate::storage::models::TimeEntry) -> bool,
+    ) -> Result<()> {
+        self.time_entries().for_each(range, f)
+    }
+
     /// 更新时间记录
     pub fn update_time_entry(
         &self,
@@ -176,6 +307,7 @@ impl Database {
             SELECT id, task_name, category_id, start_time, end_time,
                    duration_seconds, description, tags, created_at, updated_at
             FROM time_entries 
+            WHERE deleted_at IS NULL
             ORDER BY created_at DESC
             LIMIT ?1
         "#;
@@ -230,6 +362,7 @@ impl Database {
             SELECT id, task_name, category_id, start_time, end_time,
                    duration_seconds, description, tags, created_at, updated_at
             FROM time_entries 
+            WHERE deleted_at IS NULL
             ORDER BY start_time DESC
         "#;
 
@@ -283,7 +416,7 @@ impl Database {
             SELECT id, name, description, color, icon, created_at, updated_at,
                    is_active, sort_order, parent_id, daily_target_seconds, weekly_target_seconds
             FROM categories 
-            WHERE is_active = true
+            WHERE is_active = true AND deleted_at IS NULL
             ORDER BY sort_order ASC, name ASC
         "#;
 
@@ -323,46 +456,127 @@ impl Database {
         })
     }
 
-    // ==================== 笔记操作（占位方法） ====================
+    /// 插入分类
+    pub fn insert_category(&self, category: &crate::storage::CategoryInsert) -> Result<i64> {
+        let sql = r#"
+            INSERT INTO categories (
+                id, name, description, color, icon, daily_target_seconds,
+                weekly_target_seconds, is_active, sort_order, parent_id, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        "#;
+
+        self.connection.write(|conn| {
+            conn.execute(
+                sql,
+                rusqlite::params![
+                    category.id.to_string(),
+                    category.name,
+                    category.description,
+                    category.color,
+                    category.icon,
+                    category.daily_target_seconds,
+                    category.weekly_target_seconds,
+                    category.is_active,
+                    category.sort_order,
+                    category.parent_id.map(|id| id.to_string()),
+                    category.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// 更新分类
+    pub fn update_category(
+        &self,
+        id: uuid::Uuid,
+        update: &crate::storage::models::CategoryUpdate,
+    ) -> Result<()> {
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(name) = &update.name {
+            sql_parts.push("name = ?");
+            params.push(Box::new(name.clone()));
+        }
+
+        if let Some(description) = &update.description {
+            sql_parts.push("description = ?");
+            params.push(Box::new(description.clone()));
+        }
+
+        if let Some(color) = &update.color {
+            sql_parts.push("color = ?");
+            params.push(Box::new(color.clone()));
+        }
+
+        if let Some(icon) = &update.icon {
+            sql_parts.push("icon = ?");
+            params.push(Box::new(icon.clone()));
+        }
+
+        if let Some(daily_target_seconds) = &update.daily_target_seconds {
+            sql_parts.push("daily_target_seconds = ?");
+            params.push(Box::new(*daily_target_seconds));
+        }
+
+        if let Some(weekly_target_seconds) = &update.weekly_target_seconds {
+            sql_parts.push("weekly_target_seconds = ?");
+            params.push(Box::new(*weekly_target_seconds));
+        }
+
+        if let Some(is_active) = &update.is_active {
+            sql_parts.push("is_active = ?");
+            params.push(Box::new(*is_active));
+        }
+
+        if let Some(sort_order) = &update.sort_order {
+            sql_parts.push("sort_order = ?");
+            params.push(Box::new(*sort_order));
+        }
+
+        if let Some(parent_id) = &update.parent_id {
+            sql_parts.push("parent_id = ?");
+            params.push(Box::new(parent_id.map(|id| id.to_string())));
+        }
+
+        self.update_row("categories", "分类", id, sql_parts, params)
+    }
+
+    /// 删除分类（连同其子分类、任务和时间记录移入回收站）
+    pub fn delete_category(&self, id: uuid::Uuid) -> Result<()> {
+        self.trash().move_to_trash(TrashEntityType::Category, id)
+    }
+
+    // ==================== 笔记操作代理方法 ====================
 
     /// 插入笔记
-    pub fn insert_note(&self, _note: &crate::storage::models::Note) -> Result<i64> {
-        // TODO: 实现笔记插入逻辑
-        Err(crate::errors::AppError::System(
-            "笔记功能尚未实现".to_string(),
-        ))
+    pub fn insert_note(&self, note: &crate::storage::models::Note) -> Result<i64> {
+        self.notes().insert(note)
     }
 
     /// 获取所有笔记
     pub fn get_all_notes(&self) -> Result<Vec<crate::storage::models::Note>> {
-        // TODO: 实现获取所有笔记逻辑
-        Ok(vec![])
+        self.notes().get_all()
     }
 
     /// 根据ID获取笔记
-    pub fn get_note_by_id(&self, _id: uuid::Uuid) -> Result<Option<crate::storage::models::Note>> {
-        // TODO: 实现根据ID获取笔记逻辑
-        Ok(None)
+    pub fn get_note_by_id(&self, id: uuid::Uuid) -> Result<Option<crate::storage::models::Note>> {
+        self.notes().get_by_id(id)
     }
 
     /// 更新笔记
     pub fn update_note(
         &self,
-        _id: uuid::Uuid,
-        _update: &crate::storage::models::NoteUpdate,
+        id: uuid::Uuid,
+        update: &crate::storage::models::NoteUpdate,
     ) -> Result<()> {
-        // TODO: 实现笔记更新逻辑
-        Err(crate::errors::AppError::System(
-            "笔记功能尚未实现".to_string(),
-        ))
+        self.notes().update(id, update)
     }
 
     /// 删除笔记
-    pub fn delete_note(&self, _id: uuid::Uuid) -> Result<()> {
-        // TODO: 实现笔记删除逻辑
-        Err(crate::errors::AppError::System(
-            "笔记功能尚未实现".to_string(),
-        ))
+    pub fn delete_note(&self, id: uuid::Uuid) -> Result<()> {
+        self.notes().delete(id)
     }
 
     /// 搜索笔记
@@ -418,39 +632,405 @@ impl Database {
         })
     }
 
-    // ==================== 记账数据方法（占位实现） ====================
+    // ==================== 记账数据方法 ====================
 
     /// 获取所有账户
     pub fn get_all_accounts(&self) -> Result<Vec<crate::storage::models::Account>> {
-        // TODO: 实现账户查询逻辑
-        Ok(vec![])
+        let sql = r#"
+            SELECT id, name, account_type, currency, balance, is_active, created_at, updated_at
+            FROM accounts
+            WHERE deleted_at IS NULL
+            ORDER BY created_at ASC
+        "#;
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let accounts = stmt.query_map([], |row| {
+                Ok(crate::storage::models::Account {
+                    id: utils::uuid_from_str(row.get("id")?)?,
+                    name: row.get("name")?,
+                    account_type: row.get("account_type")?,
+                    bank_name: None,
+                    account_number: None,
+                    routing_number: None,
+                    balance: row.get("balance")?,
+                    currency: row.get("currency")?,
+                    is_active: row.get("is_active")?,
+                    created_at: utils::datetime_from_str(row.get("created_at")?)?,
+                    updated_at: row
+                        .get::<_, Option<String>>("updated_at")?
+                        .map(utils::datetime_from_str)
+                        .transpose()?,
+                })
+            })?;
+
+            let mut result = Vec::new();
+            for account in accounts {
+                result.push(account?);
+            }
+            Ok(result)
+        })
     }
 
-    /// 获取所有交易记录
+    /// 获取所有交易记录（按交易日期从新到旧）
     pub fn get_all_transactions(&self) -> Result<Vec<crate::storage::models::Transaction>> {
-        // TODO: 实现交易记录查询逻辑
-        Ok(vec![])
+        let sql = r#"
+            SELECT id, account_id, amount, description, category_id, transaction_date,
+                   transaction_type, tags, created_at, updated_at
+            FROM transactions
+            WHERE deleted_at IS NULL
+            ORDER BY transaction_date DESC, created_at DESC
+        "#;
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let transactions = stmt.query_map([], |row| {
+                let tags_json: String = row.get("tags")?;
+                let created_at = utils::datetime_from_str(row.get("created_at")?)?;
+                let transaction_date = utils::naive_date_from_str(row.get("transaction_date")?)?
+                    .and_hms_opt(0, 0, 0)
+                    .and_then(|date| date.and_local_timezone(chrono::Local).earliest())
+                    .unwrap_or(created_at);
+
+                Ok(crate::storage::models::Transaction {
+                    id: utils::uuid_from_str(row.get("id")?)?,
+                    account_id: utils::uuid_from_str(row.get("account_id")?)?,
+                    amount: row.get("amount")?,
+                    description: row.get("description")?,
+                    payee: None,
+                    category_id: row
+                        .get::<_, Option<String>>("category_id")?
+                        .map(utils::uuid_from_str)
+                        .transpose()?,
+                    transaction_date,
+                    transaction_type: row.get("transaction_type")?,
+                    tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+                    created_at,
+                    updated_at: row
+                        .get::<_, Option<String>>("updated_at")?
+                        .map(utils::datetime_from_str)
+                        .transpose()?,
+                })
+            })?;
+
+            let mut result = Vec::new();
+            for transaction in transactions {
+                result.push(transaction?);
+            }
+            Ok(result)
+        })
     }
 
     /// 插入账户
-    pub fn insert_account(&self, _account: &crate::storage::AccountInsert) -> Result<i64> {
-        // TODO: 实现账户插入逻辑
-        Ok(0)
+    pub fn insert_account(&self, account: &crate::storage::AccountInsert) -> Result<i64> {
+        let sql = r#"
+            INSERT INTO accounts (
+                id, name, account_type, currency, balance, initial_balance,
+                description, is_active, is_default, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#;
+
+        self.connection.write(|conn| {
+            conn.execute(
+                sql,
+                rusqlite::params![
+                    account.id.to_string(),
+                    account.name,
+                    format!("{:?}", account.account_type).to_lowercase(),
+                    account.currency,
+                    account.balance,
+                    account.initial_balance,
+                    account.description,
+                    account.is_active,
+                    account.is_default,
+                    account.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
     }
 
     /// 插入交易记录
     pub fn insert_transaction(
         &self,
-        _transaction: &crate::storage::TransactionInsert,
+        transaction: &crate::storage::TransactionInsert,
     ) -> Result<i64> {
-        // TODO: 实现交易记录插入逻辑
-        Ok(0)
+        let sql = r#"
+            INSERT INTO transactions (
+                id, transaction_type, amount, currency, description, account_id,
+                category_id, to_account_id, status, transaction_date, tags,
+                receipt_path, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+        "#;
+
+        self.connection.write(|conn| {
+            conn.execute(
+                sql,
+                rusqlite::params![
+                    transaction.id.to_string(),
+                    format!("{:?}", transaction.transaction_type).to_lowercase(),
+                    transaction.amount,
+                    transaction.currency,
+                    transaction.description,
+                    transaction.account_id.to_string(),
+                    transaction.category_id.map(|id| id.to_string()),
+                    transaction.to_account_id.map(|id| id.to_string()),
+                    format!("{:?}", transaction.status).to_lowercase(),
+                    transaction.transaction_date.format("%Y-%m-%d").to_string(),
+                    serde_json::to_string(&transaction.tags)?,
+                    transaction.receipt_path,
+                    transaction.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
     }
 
-    /// 插入分类
-    pub fn insert_category(&self, _category: &crate::storage::CategoryInsert) -> Result<i64> {
-        // TODO: 实现分类插入逻辑
-        Ok(0)
+    /// 获取所有交易分类（按排序值和名称）
+    pub fn get_all_transaction_categories(
+        &self,
+    ) -> Result<Vec<crate::storage::TransactionCategory>> {
+        let sql = r#"
+            SELECT id, name, transaction_type, color, icon, description, parent_id,
+                   is_active, created_at, updated_at
+            FROM transaction_categories
+            ORDER BY sort_order ASC, name ASC
+        "#;
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let categories = stmt.query_map([], |row| {
+                Ok(crate::storage::TransactionCategory {
+                    id: utils::uuid_from_str(row.get("id")?)?,
+                    name: row.get("name")?,
+                    transaction_type: utils::enum_from_str(row.get("transaction_type")?)?,
+                    description: row.get("description")?,
+                    color: row.get("color")?,
+                    icon: row.get("icon")?,
+                    parent_id: row
+                        .get::<_, Option<String>>("parent_id")?
+                        .map(utils::uuid_from_str)
+                        .transpose()?,
+                    is_active: row.get("is_active")?,
+                    created_at: utils::datetime_from_str(row.get("created_at")?)?,
+                    updated_at: row
+                        .get::<_, Option<String>>("updated_at")?
+                        .map(utils::datetime_from_str)
+                        .transpose()?,
+                })
+            })?;
+
+            let mut result = Vec::new();
+            for category in categories {
+                result.push(category?);
+            }
+            Ok(result)
+        })
+    }
+
+    /// 插入交易分类
+    pub fn insert_transaction_category(
+        &self,
+        category: &crate::storage::TransactionCategoryInsert,
+    ) -> Result<i64> {
+        let sql = r#"
+            INSERT INTO transaction_categories (
+                id, name, transaction_type, color, icon, description, parent_id,
+                is_active, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#;
+
+        self.connection.write(|conn| {
+            conn.execute(
+                sql,
+                rusqlite::params![
+                    category.id.to_string(),
+                    category.name,
+                    format!("{:?}", category.transaction_type).to_lowercase(),
+                    category.color,
+                    category.icon,
+                    category.description,
+                    category.parent_id.map(|id| id.to_string()),
+                    category.is_active,
+                    category.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// 获取所有预算（按开始日期从旧到新）
+    ///
+    /// 数据库不保存已花费金额，返回的 `spent_amount` 为 0，`remaining_amount` 等于预算金额
+    pub fn get_all_budgets(&self) -> Result<Vec<crate::storage::Budget>> {
+        let sql = r#"
+            SELECT id, name, category_id, amount, currency, period, start_date, end_date,
+                   is_active, created_at, updated_at
+            FROM budgets
+            ORDER BY start_date ASC, created_at ASC
+        "#;
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let budgets = stmt.query_map([], |row| {
+                let amount: f64 = row.get("amount")?;
+                Ok(crate::storage::Budget {
+                    id: utils::uuid_from_str(row.get("id")?)?,
+                    name: row.get("name")?,
+                    category_id: utils::uuid_from_str(row.get("category_id")?)?,
+                    amount,
+                    currency: row.get("currency")?,
+                    period: utils::enum_from_str(row.get("period")?)?,
+                    start_date: utils::naive_date_from_str(row.get("start_date")?)?,
+                    end_date: row
+                        .get::<_, Option<String>>("end_date")?
+                        .map(utils::naive_date_from_str)
+                        .transpose()?,
+                    spent_amount: 0.0,
+                    remaining_amount: amount,
+                    is_active: row.get("is_active")?,
+                    created_at: utils::datetime_from_str(row.get("created_at")?)?,
+                    updated_at: row
+                        .get::<_, Option<String>>("updated_at")?
+                        .map(utils::datetime_from_str)
+                        .transpose()?,
+                })
+            })?;
+
+            let mut result = Vec::new();
+            for budget in budgets {
+                result.push(budget?);
+            }
+            Ok(result)
+        })
+    }
+
+    /// 插入预算
+    pub fn insert_budget(&self, budget: &crate::storage::BudgetInsert) -> Result<i64> {
+        let sql = r#"
+            INSERT INTO budgets (
+                id, name, category_id, amount, currency, period, start_date, end_date,
+                is_active, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#;
+
+        self.connection.write(|conn| {
+            conn.execute(
+                sql,
+                rusqlite::params![
+                    budget.id.to_string(),
+                    budget.name,
+                    budget.category_id.to_string(),
+                    budget.amount,
+                    budget.currency,
+                    format!("{:?}", budget.period).to_lowercase(),
+                    budget.start_date.format("%Y-%m-%d").to_string(),
+                    budget
+                        .end_date
+                        .map(|date| date.format("%Y-%m-%d").to_string()),
+                    budget.is_active,
+                    budget.created_at.to_rfc3339(),
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// 更新交易记录
+    pub fn update_transaction(
+        &self,
+        id: uuid::Uuid,
+        update: &crate::storage::TransactionUpdate,
+    ) -> Result<()> {
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(transaction_type) = &update.transaction_type {
+            sql_parts.push("transaction_type = ?");
+            params.push(Box::new(format!("{:?}", transaction_type).to_lowercase()));
+        }
+
+        if let Some(amount) = &update.amount {
+            sql_parts.push("amount = ?");
+            params.push(Box::new(*amount));
+        }
+
+        if let Some(currency) = &update.currency {
+            sql_parts.push("currency = ?");
+            params.push(Box::new(currency.clone()));
+        }
+
+        if let Some(description) = &update.description {
+            sql_parts.push("description = ?");
+            params.push(Box::new(description.clone()));
+        }
+
+        if let Some(account_id) = &update.account_id {
+            sql_parts.push("account_id = ?");
+            params.push(Box::new(account_id.to_string()));
+        }
+
+        if let Some(category_id) = &update.category_id {
+            sql_parts.push("category_id = ?");
+            params.push(Box::new(category_id.map(|id| id.to_string())));
+        }
+
+        if let Some(to_account_id) = &update.to_account_id {
+            sql_parts.push("to_account_id = ?");
+            params.push(Box::new(to_account_id.map(|id| id.to_string())));
+        }
+
+        if let Some(status) = &update.status {
+            sql_parts.push("status = ?");
+            params.push(Box::new(format!("{:?}", status).to_lowercase()));
+        }
+
+        if let Some(transaction_date) = &update.transaction_date {
+            sql_parts.push("transaction_date = ?");
+            params.push(Box::new(transaction_date.format("%Y-%m-%d").to_string()));
+        }
+
+        if let Some(tags) = &update.tags {
+            sql_parts.push("tags = ?");
+            params.push(Box::new(serde_json::to_string(tags)?));
+        }
+
+        if let Some(receipt_path) = &update.receipt_path {
+            sql_parts.push("receipt_path = ?");
+            params.push(Box::new(receipt_path.clone()));
+        }
+
+        self.update_row("transactions", "交易记录", id, sql_parts, params)
+    }
+
+    /// 按给定的列更新一行未删除的数据，并更新 `updated_at`
+    fn update_row(
+        &self,
+        table: &str,
+        display_name: &str,
+        id: uuid::Uuid,
+        mut sql_parts: Vec<&str>,
+        mut params: Vec<Box<dyn rusqlite::ToSql>>,
+    ) -> Result<()> {
+        if sql_parts.is_empty() {
+            return Ok(()); // 没有要更新的字段
+        }
+
+        sql_parts.push("updated_at = ?");
+        params.push(Box::new(chrono::Local::now().to_rfc3339()));
+        params.push(Box::new(id.to_string()));
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE id = ? AND deleted_at IS NULL",
+            table,
+            sql_parts.join(", ")
+        );
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        if self.connection.execute(&sql, &param_refs)? == 0 {
+            return Err(AppError::NotFound(format!("{}未找到: {}", display_name, id)));
+        }
+        Ok(())
     }
 
     // ==================== 遗留的方法（待迁移） ====================
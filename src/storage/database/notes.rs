@@ -0,0 +1,178 @@
+//! # 笔记数据库操作模块
+//!
+//! 提供笔记相关的数据库操作功能
+
+use super::connection::DatabaseConnection;
+use super::trash::{TrashEntityType, TrashRepository};
+use super::utils::{datetime_from_str, uuid_from_str};
+use crate::errors::{AppError, Result};
+use crate::storage::models::{Note, NoteUpdate};
+use uuid::Uuid;
+
+/// 笔记查询的公共列
+const NOTE_COLUMNS: &str =
+    "id, title, content, mood, tags, is_favorite, is_archived, created_at, updated_at";
+
+/// 笔记数据库操作
+pub struct NotesRepository<'a> {
+    connection: &'a DatabaseConnection,
+}
+
+impl<'a> NotesRepository<'a> {
+    /// 创建新的笔记仓库实例
+    pub fn new(connection: &'a DatabaseConnection) -> Self {
+        Self { connection }
+    }
+
+    /// 将查询行映射为笔记
+    fn map_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Note> {
+        let tags_json: String = row.get(4)?;
+        Ok(Note {
+            id: uuid_from_str(row.get(0)?)?,
+            title: row.get(1)?,
+            content: row.get(2)?,
+            mood: row.get(3)?,
+            tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+            is_favorite: row.get(5)?,
+            is_archived: row.get(6)?,
+            created_at: datetime_from_str(row.get(7)?)?,
+            updated_at: datetime_from_str(row.get(8)?)?,
+        })
+    }
+
+    /// 插入笔记
+    pub fn insert(&self, note: &Note) -> Result<i64> {
+        let sql = r#"
+            INSERT INTO notes (
+                id, title, content, mood, tags, is_favorite, is_archived, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#;
+
+        let tags_json = serde_json::to_string(&note.tags)?;
+
+        self.connection.execute(
+            sql,
+            &[
+                &note.id.to_string(),
+                &note.title,
+                &note.content,
+                &note.mood,
+                &tags_json,
+                &note.is_favorite,
+                &note.is_archived,
+                &note.created_at.to_rfc3339(),
+                &note.updated_at.to_rfc3339(),
+            ],
+        )?;
+
+        let row_id = self
+            .connection
+            .query_row("SELECT last_insert_rowid()", &[], |row| {
+                row.get::<_, i64>(0)
+            })?;
+
+        log::debug!("Inserting note: {}", note.id);
+        Ok(row_id)
+    }
+
+    /// 获取所有笔记
+    pub fn get_all(&self) -> Result<Vec<Note>> {
+        let sql = format!(
+            "SELECT {} FROM notes WHERE deleted_at IS NULL ORDER BY created_at DESC",
+            NOTE_COLUMNS
+        );
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let note_iter = stmt.query_map([], Self::map_row)?;
+
+            let mut notes = Vec::new();
+            for note in note_iter {
+                notes.push(note?);
+            }
+
+            log::debug!("Retrieved {} notes", notes.len());
+            Ok(notes)
+        })
+    }
+
+    /// 根据ID获取笔记
+    pub fn get_by_id(&self, id: Uuid) -> Result<Option<Note>> {
+        let sql = format!(
+            "SELECT {} FROM notes WHERE id = ?1 AND deleted_at IS NULL",
+            NOTE_COLUMNS
+        );
+
+        match self
+            .connection
+            .query_row(&sql, &[&id.to_string()], Self::map_row)
+        {
+            Ok(note) => Ok(Some(note)),
+            Err(AppError::Database(rusqlite::Error::QueryReturnedNoRows)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 更新笔记
+    pub fn update(&self, id: Uuid, update: &NoteUpdate) -> Result<()> {
+        let mut sql_parts = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(title) = &update.title {
+            sql_parts.push("title = ?");
+            params.push(Box::new(title.clone()));
+        }
+
+        if let Some(content) = &update.content {
+            sql_parts.push("content = ?");
+            params.push(Box::new(content.clone()));
+        }
+
+        if let Some(mood) = &update.mood {
+            sql_parts.push("mood = ?");
+            params.push(Box::new(mood.clone()));
+        }
+
+        if let Some(tags) = &update.tags {
+            sql_parts.push("tags = ?");
+            params.push(Box::new(serde_json::to_string(tags)?));
+        }
+
+        if let Some(is_favorite) = &update.is_favorite {
+            sql_parts.push("is_favorite = ?");
+            params.push(Box::new(*is_favorite));
+        }
+
+        if let Some(is_archived) = &update.is_archived {
+            sql_parts.push("is_archived = ?");
+            params.push(Box::new(*is_archived));
+        }
+
+        sql_parts.push("updated_at = ?");
+        params.push(Box::new(update.updated_at.to_rfc3339()));
+
+        let sql = format!(
+            "UPDATE notes SET {} WHERE id = ? AND deleted_at IS NULL",
+            sql_parts.join(", ")
+        );
+        params.push(Box::new(id.to_string()));
+
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let rows_affected = self.connection.execute(&sql, &param_refs)?;
+
+        if rows_affected == 0 {
+            return Err(AppError::NotFound(format!("笔记未找到: {}", id)));
+        }
+
+        log::debug!("Updating note: {}", id);
+        Ok(())
+    }
+
+    /// 删除笔记（移入回收站）
+    pub fn delete(&self, id: Uuid) -> Result<()> {
+        TrashRepository::new(self.connection).move_to_trash(TrashEntityType::Note, id)?;
+
+        log::debug!("Deleting note: {}", id);
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 //! 提供任务相关的数据库操作功能
 
 use super::connection::DatabaseConnection;
+use super::trash::{TrashEntityType, TrashRepository};
 use super::utils::{datetime_from_str, uuid_from_str};
 use crate::errors::{AppError, Result};
 use crate::storage::task_models::{TaskInsert, TaskModel, TaskUpdate};
@@ -68,6 +69,7 @@ impl<'a> TasksRepository<'a> {
                    estimated_duration_seconds, total_duration_seconds, tags,
                    due_date, is_completed, completed_at, created_at, updated_at
             FROM tasks 
+            WHERE deleted_at IS NULL
             ORDER BY created_at DESC
         "#;
 
@@ -124,7 +126,7 @@ impl<'a> TasksRepository<'a> {
             SELECT id, name, description, category_id, status, priority,
                    estimated_duration_seconds, total_duration_seconds, tags,
                    due_date, is_completed, completed_at, created_at, updated_at
-            FROM tasks WHERE id = ?1
+            FROM tasks WHERE id = ?1 AND deleted_at IS NULL
         "#;
 
         let result = self.connection.query_row(sql, &[&id.to_string()], |row| {
@@ -234,7 +236,10 @@ impl<'a> TasksRepository<'a> {
         sql_parts.push("updated_at = ?");
         params.push(Box::new(Local::now().to_rfc3339()));
 
-        let sql = format!("UPDATE tasks SET {} WHERE id = ?", sql_parts.join(", "));
+        let sql = format!(
+            "UPDATE tasks SET {} WHERE id = ? AND deleted_at IS NULL",
+            sql_parts.join(", ")
+        );
 
         params.push(Box::new(id.to_string()));
 
@@ -251,14 +256,9 @@ impl<'a> TasksRepository<'a> {
         Ok(())
     }
 
-    /// 删除任务
+    /// 删除任务（移入回收站）
     pub fn delete(&self, id: Uuid) -> Result<()> {
-        let sql = "DELETE FROM tasks WHERE id = ?1";
-        let rows_affected = self.connection.execute(sql, &[&id.to_string()])?;
-
-        if rows_affected == 0 {
-            return Err(AppError::System(format!("任务未找到: {}", id)));
-        }
+        TrashRepository::new(self.connection).move_to_trash(TrashEntityType::Task, id)?;
 
         log::debug!("Deleting task: {}", id);
         Ok(())
@@ -271,7 +271,7 @@ impl<'a> TasksRepository<'a> {
                    estimated_duration_seconds, total_duration_seconds, tags,
                    due_date, is_completed, completed_at, created_at, updated_at
             FROM tasks 
-            WHERE category_id = ?1
+            WHERE category_id = ?1 AND deleted_at IS NULL
             ORDER BY created_at DESC
         "#;
 
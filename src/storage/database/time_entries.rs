@@ -3,6 +3,7 @@
 //! 提供时间记录相关的数据库操作功能
 
 use super::connection::DatabaseConnection;
+use super::trash::{TrashEntityType, TrashRepository};
 use crate::errors::{AppError, Result};
 use crate::storage::models::{TimeEntry, TimeEntryInsert};
 use chrono::{DateTime, Local, NaiveDate};
@@ -63,7 +64,7 @@ impl<'a> TimeEntriesRepository<'a> {
         let sql = r#"
             SELECT id, task_name, category_id, start_time, end_time,
                    duration_seconds, description, tags, created_at, updated_at
-            FROM time_entries WHERE id = ?1
+            FROM time_entries WHERE id = ?1 AND deleted_at IS NULL
         "#;
 
         let result = self.connection.query_row(sql, &[&id.to_string()], |row| {
@@ -113,7 +114,7 @@ impl<'a> TimeEntriesRepository<'a> {
             SELECT id, task_name, category_id, start_time, end_time,
                    duration_seconds, description, tags, created_at, updated_at
             FROM time_entries 
-            WHERE DATE(start_time) BETWEEN ?1 AND ?2
+            WHERE DATE(start_time) BETWEEN ?1 AND ?2 AND deleted_at IS NULL
             ORDER BY start_time DESC
         "#;
 
@@ -170,7 +171,7 @@ impl<'a> TimeEntriesRepository<'a> {
             SELECT id, task_name, category_id, start_time, end_time,
                    duration_seconds, description, tags, created_at, updated_at
             FROM time_entries 
-            WHERE category_id = ?1
+            WHERE category_id = ?1 AND deleted_at IS NULL
             ORDER BY start_time DESC
         "#;
 
@@ -216,13 +217,62 @@ impl<'a> TimeEntriesRepository<'a> {
         Ok(result)
     }
 
+    /// 统计未删除的时间记录数量，`range` 按开始时间过滤（含两端）
+    pub fn count(&self, range: Option<(DateTime<Local>, DateTime<Local>)>) -> Result<usize> {
+        let (filter, params) = range_filter(range);
+        let sql = format!(
+            "SELECT COUNT(*) FROM time_entries WHERE deleted_at IS NULL{}",
+            filter
+        );
+
+        self.connection.read(|conn| {
+            let count: i64 =
+                conn.query_row(&sql, rusqlite::params_from_iter(params.iter()), |row| {
+                    row.get(0)
+                })?;
+            Ok(count as usize)
+        })
+    }
+
+    /// 按开始时间从旧到新逐行读取时间记录，回调返回 `false` 时停止
+    ///
+    /// 直接遍历游标，不会把结果集读入内存
+    pub fn for_each(
+        &self,
+        range: Option<(DateTime<Local>, DateTime<Local>)>,
+        f: &mut dyn FnMut(TimeEntry) -> bool,
+    ) -> Result<()> {
+        let (filter, params) = range_filter(range);
+        let sql = format!(
+            r#"
+            SELECT id, task_name, category_id, start_time, end_time,
+                   duration_seconds, description, tags, created_at, updated_at
+            FROM time_entries
+            WHERE deleted_at IS NULL{}
+            ORDER BY start_time ASC
+            "#,
+            filter
+        );
+
+        self.connection.read(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let mut rows = stmt.query(rusqlite::params_from_iter(params.iter()))?;
+            while let Some(row) = rows.next()? {
+                if !f(time_entry_from_row(row)?) {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// 更新时间记录
     pub fn update(&self, id: Uuid, entry: &TimeEntryInsert) -> Result<()> {
         let sql = r#"
             UPDATE time_entries SET
                 task_name = ?2, category_id = ?3, start_time = ?4, end_time = ?5,
                 duration_seconds = ?6, description = ?7, tags = ?8, updated_at = ?9
-            WHERE id = ?1
+            WHERE id = ?1 AND deleted_at IS NULL
         "#;
 
         let tags_json = serde_json::to_string(&entry.tags)?;
@@ -251,17 +301,56 @@ impl<'a> TimeEntriesRepository<'a> {
         Ok(())
     }
 
-    /// 删除时间记录
+    /// 删除时间记录（移入回收站）
     pub fn delete(&self, id: Uuid) -> Result<()> {
-        let sql = "DELETE FROM time_entries WHERE id = ?1";
-
-        let rows_affected = self.connection.execute(sql, &[&id.to_string()])?;
-
-        if rows_affected == 0 {
-            return Err(AppError::TaskNotFound(id.to_string()));
-        }
+        TrashRepository::new(self.connection).move_to_trash(TrashEntityType::TimeEntry, id)?;
 
         log::debug!("Deleting time entry: {}", id);
         Ok(())
     }
 }
+
+/// 开始时间范围过滤条件，时间字符串带时区，用 julianday 比较
+fn range_filter(range: Option<(DateTime<Local>, DateTime<Local>)>) -> (&'static str, Vec<String>) {
+    match range {
+        Some((start, end)) => (
+            " AND julianday(start_time) BETWEEN julianday(?1) AND julianday(?2)",
+            vec![start.to_rfc3339(), end.to_rfc3339()],
+        ),
+        None => ("", Vec::new()),
+    }
+}
+
+fn time_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<TimeEntry> {
+    let tags_json: String = row.get("tags")?;
+    let parse_time = |value: String| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|dt| dt.with_timezone(&Local))
+            .map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+            })
+    };
+
+    Ok(TimeEntry {
+        id: Uuid::parse_str(&row.get::<_, String>("id")?).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        task_name: row.get("task_name")?,
+        category_id: row
+            .get::<_, Option<String>>("category_id")?
+            .and_then(|s| Uuid::parse_str(&s).ok()),
+        start_time: parse_time(row.get("start_time")?)?,
+        end_time: row
+            .get::<_, Option<String>>("end_time")?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local)),
+        duration_seconds: row.get("duration_seconds")?,
+        description: row.get("description")?,
+        tags: serde_json::from_str(&tags_json).unwrap_or_default(),
+        created_at: parse_time(row.get("created_at")?)?,
+        updated_at: row
+            .get::<_, Option<String>>("updated_at")?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Local)),
+    })
+}
@@ -0,0 +1,501 @@
+//! # 回收站数据库操作模块
+//!
+//! 提供软删除、回收站列表、恢复和彻底删除功能。
+//! 删除分类或账户时，其下的数据会以相同的删除时间一并移入回收站，恢复和彻底删除时同样一并处理。
+
+use super::connection::DatabaseConnection;
+use super::utils::{datetime_from_str, uuid_from_str};
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Duration, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// 支持软删除的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashEntityType {
+    /// 任务
+    Task,
+    /// 时间记录
+    TimeEntry,
+    /// 分类
+    Category,
+    /// 笔记
+    Note,
+    /// 账户
+    Account,
+    /// 交易记录
+    Transaction,
+}
+
+impl TrashEntityType {
+    /// 所有数据类型（按彻底删除的顺序，被引用的类型在后）
+    pub const ALL: [TrashEntityType; 6] = [
+        TrashEntityType::Transaction,
+        TrashEntityType::TimeEntry,
+        TrashEntityType::Task,
+        TrashEntityType::Note,
+        TrashEntityType::Category,
+        TrashEntityType::Account,
+    ];
+
+    /// 对应的数据表
+    pub fn table(&self) -> &'static str {
+        match self {
+            TrashEntityType::Task => "tasks",
+            TrashEntityType::TimeEntry => "time_entries",
+            TrashEntityType::Category => "categories",
+            TrashEntityType::Note => "notes",
+            TrashEntityType::Account => "accounts",
+            TrashEntityType::Transaction => "transactions",
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TrashEntityType::Task => "任务",
+            TrashEntityType::TimeEntry => "时间记录",
+            TrashEntityType::Category => "分类",
+            TrashEntityType::Note => "笔记",
+            TrashEntityType::Account => "账户",
+            TrashEntityType::Transaction => "交易记录",
+        }
+    }
+
+    /// 回收站列表中用作标题的列
+    fn title_column(&self) -> &'static str {
+        match self {
+            TrashEntityType::Task | TrashEntityType::Category | TrashEntityType::Account => "name",
+            TrashEntityType::TimeEntry => "task_name",
+            TrashEntityType::Note => "title",
+            TrashEntityType::Transaction => "description",
+        }
+    }
+
+    /// 引用该类型的数据（类型和外键列），删除时一并移入回收站
+    fn dependents(&self) -> &'static [(TrashEntityType, &'static str)] {
+        match self {
+            TrashEntityType::Category => &[
+                (TrashEntityType::Category, "parent_id"),
+                (TrashEntityType::Task, "category_id"),
+                (TrashEntityType::TimeEntry, "category_id"),
+            ],
+            TrashEntityType::Account => &[
+                (TrashEntityType::Transaction, "account_id"),
+                (TrashEntityType::Transaction, "to_account_id"),
+            ],
+            _ => &[],
+        }
+    }
+
+    /// 该类型引用的数据（类型和外键列），恢复前需要先恢复
+    fn parents(&self) -> &'static [(TrashEntityType, &'static str)] {
+        match self {
+            TrashEntityType::Category => &[(TrashEntityType::Category, "parent_id")],
+            TrashEntityType::Task | TrashEntityType::TimeEntry => {
+                &[(TrashEntityType::Category, "category_id")]
+            }
+            TrashEntityType::Transaction => &[
+                (TrashEntityType::Account, "account_id"),
+                (TrashEntityType::Account, "to_account_id"),
+            ],
+            _ => &[],
+        }
+    }
+}
+
+/// 回收站条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashItem {
+    /// 数据类型
+    pub entity_type: TrashEntityType,
+    /// 数据ID
+    pub id: Uuid,
+    /// 标题
+    pub title: String,
+    /// 删除时间
+    pub deleted_at: DateTime<Local>,
+    /// 随之一起删除的数据数量
+    pub dependent_count: usize,
+}
+
+/// 回收站数据库操作
+pub struct TrashRepository<'a> {
+    connection: &'a DatabaseConnection,
+}
+
+impl<'a> TrashRepository<'a> {
+    /// 创建新的回收站仓库实例
+    pub fn new(connection: &'a DatabaseConnection) -> Self {
+        Self { connection }
+    }
+
+    /// 将数据及其下的数据移入回收站
+    pub fn move_to_trash(&self, entity_type: TrashEntityType, id: Uuid) -> Result<()> {
+        let deleted_at = Local::now().to_rfc3339();
+
+        self.connection.write(|conn| {
+            let tx = conn.transaction()?;
+            let updated = tx.execute(
+                &format!(
+                    "UPDATE {} SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+                    entity_type.table()
+                ),
+                params![deleted_at, id.to_string()],
+            )?;
+            if updated == 0 {
+                return Err(not_found(entity_type, id));
+            }
+
+            for (dependent_type, dependent_id) in collect_dependents(&tx, entity_type, id, None)? {
+                tx.execute(
+                    &format!(
+                        "UPDATE {} SET deleted_at = ?1 WHERE id = ?2",
+                        dependent_type.table()
+                    ),
+                    params![deleted_at, dependent_id.to_string()],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(())
+        })?;
+
+        log::debug!("Moved {} {} to trash", entity_type.display_name(), id);
+        Ok(())
+    }
+
+    /// 列出回收站中的数据（按删除时间从新到旧）
+    ///
+    /// 随分类或账户一起删除的数据不单独列出，计入其 `dependent_count`
+    pub fn list(&self) -> Result<Vec<TrashItem>> {
+        self.connection.read(|conn| {
+            let mut items = Vec::new();
+            for entity_type in TrashEntityType::ALL {
+                items.extend(trashed_items(conn, entity_type)?);
+            }
+
+            let mut dependents = HashSet::new();
+            for (item, deleted_at) in items.iter_mut() {
+                let item_dependents =
+                    collect_dependents(conn, item.entity_type, item.id, Some(deleted_at))?;
+                item.dependent_count = item_dependents.len();
+                dependents.extend(item_dependents);
+            }
+
+            let mut items: Vec<TrashItem> = items.into_iter().map(|(item, _)| item).collect();
+            items.retain(|item| !dependents.contains(&(item.entity_type, item.id)));
+            items.sort_by_key(|item| std::cmp::Reverse(item.deleted_at));
+            Ok(items)
+        })
+    }
+
+    /// 回收站中所有数据的ID（包括随分类或账户一起删除的数据）
+    pub fn trashed_ids(&self) -> Result<HashSet<Uuid>> {
+        self.connection.read(|conn| {
+            let mut ids = HashSet::new();
+            for entity_type in TrashEntityType::ALL {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT id FROM {} WHERE deleted_at IS NOT NULL",
+                    entity_type.table()
+                ))?;
+                for id in stmt.query_map([], |row| uuid_from_str(row.get(0)?))? {
+                    ids.insert(id?);
+                }
+            }
+            Ok(ids)
+        })
+    }
+
+    /// 从回收站恢复数据及随之一起删除的数据
+    pub fn restore(&self, entity_type: TrashEntityType, id: Uuid) -> Result<()> {
+        self.connection.write(|conn| {
+            let tx = conn.transaction()?;
+            let deleted_at = trashed_at(&tx, entity_type, id)?;
+
+            // 所属的分类或账户仍在回收站中时需要先恢复它们
+            for (parent_type, column) in entity_type.parents() {
+                let parent_trashed: bool = tx.query_row(
+                    &format!(
+                        "SELECT EXISTS(SELECT 1 FROM {0} WHERE id = (SELECT {1} FROM {2} WHERE id = ?1) AND deleted_at IS NOT NULL)",
+                        parent_type.table(),
+                        column,
+                        entity_type.table()
+                    ),
+                    params![id.to_string()],
+                    |row| row.get(0),
+                )?;
+                if parent_trashed {
+                    return Err(AppError::Business(format!(
+                        "所属的{}在回收站中，请先恢复",
+                        parent_type.display_name()
+                    )));
+                }
+            }
+
+            let mut rows = collect_dependents(&tx, entity_type, id, Some(&deleted_at))?;
+            rows.push((entity_type, id));
+            for (row_type, row_id) in rows {
+                tx.execute(
+                    &format!(
+                        "UPDATE {} SET deleted_at = NULL WHERE id = ?1",
+                        row_type.table()
+                    ),
+                    params![row_id.to_string()],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(())
+        })?;
+
+        log::info!("Restored {} {} from trash", entity_type.display_name(), id);
+        Ok(())
+    }
+
+    /// 彻底删除回收站中的数据及随之一起删除的数据
+    pub fn purge(&self, entity_type: TrashEntityType, id: Uuid) -> Result<()> {
+        self.connection.write(|conn| {
+            let tx = conn.transaction()?;
+            let deleted_at = trashed_at(&tx, entity_type, id)?;
+
+            // 先删除下层数据，最后删除自身
+            let mut rows = collect_dependents(&tx, entity_type, id, Some(&deleted_at))?;
+            rows.reverse();
+            rows.push((entity_type, id));
+            for (row_type, row_id) in rows {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE id = ?1", row_type.table()),
+                    params![row_id.to_string()],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(())
+        })?;
+
+        log::info!("Purged {} {} from trash", entity_type.display_name(), id);
+        Ok(())
+    }
+
+    /// 彻底删除在回收站中超过指定天数的数据，返回删除的行数
+    pub fn purge_expired(&self, retention_days: u32) -> Result<usize> {
+        let cutoff = (Local::now() - Duration::days(retention_days as i64)).to_rfc3339();
+        self.purge_deleted_before(Some(&cutoff))
+    }
+
+    /// 清空回收站，返回删除的行数
+    pub fn empty(&self) -> Result<usize> {
+        self.purge_deleted_before(None)
+    }
+
+    /// 彻底删除在指定时间之前删除的数据，未指定时间时删除全部
+    fn purge_deleted_before(&self, cutoff: Option<&str>) -> Result<usize> {
+        let purged = self.connection.write(|conn| {
+            let tx = conn.transaction()?;
+            let mut purged = 0;
+            for entity_type in TrashEntityType::ALL {
+                purged += tx.execute(
+                    &format!(
+                        "DELETE FROM {} WHERE deleted_at IS NOT NULL AND (?1 IS NULL OR deleted_at < ?1)",
+                        entity_type.table()
+                    ),
+                    params![cutoff],
+                )?;
+            }
+            tx.commit()?;
+            Ok(purged)
+        })?;
+
+        if purged > 0 {
+            log::info!("Purged {} rows from trash", purged);
+        }
+        Ok(purged)
+    }
+}
+
+/// 查询数据的删除时间，不在回收站中时返回未找到错误
+fn trashed_at(conn: &Connection, entity_type: TrashEntityType, id: Uuid) -> Result<String> {
+    conn.query_row(
+        &format!(
+            "SELECT deleted_at FROM {} WHERE id = ?1 AND deleted_at IS NOT NULL",
+            entity_type.table()
+        ),
+        params![id.to_string()],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| not_found(entity_type, id))
+}
+
+/// 查询某一类型在回收站中的数据及其原始删除时间文本
+fn trashed_items(
+    conn: &Connection,
+    entity_type: TrashEntityType,
+) -> Result<Vec<(TrashItem, String)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, {}, deleted_at FROM {} WHERE deleted_at IS NOT NULL",
+        entity_type.title_column(),
+        entity_type.table()
+    ))?;
+    let items = stmt
+        .query_map([], |row| {
+            let deleted_at: String = row.get(2)?;
+            let item = TrashItem {
+                entity_type,
+                id: uuid_from_str(row.get(0)?)?,
+                title: row.get(1)?,
+                deleted_at: datetime_from_str(deleted_at.clone())?,
+                dependent_count: 0,
+            };
+            Ok((item, deleted_at))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(items)
+}
+
+/// 递归收集引用指定数据的下层数据
+///
+/// `deleted_at` 为 None 时收集未删除的数据（用于移入回收站），否则收集以相同时间删除的数据
+fn collect_dependents(
+    conn: &Connection,
+    entity_type: TrashEntityType,
+    id: Uuid,
+    deleted_at: Option<&str>,
+) -> Result<Vec<(TrashEntityType, Uuid)>> {
+    let mut collected = Vec::new();
+    let mut seen = HashSet::from([(entity_type, id)]);
+    let mut pending = vec![(entity_type, id)];
+
+    while let Some((parent_type, parent_id)) = pending.pop() {
+        for (dependent_type, column) in parent_type.dependents() {
+            let sql = format!(
+                "SELECT id FROM {} WHERE {} = ?1 AND deleted_at IS ?2",
+                dependent_type.table(),
+                column
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            let ids = stmt
+                .query_map(params![parent_id.to_string(), deleted_at], |row| {
+                    uuid_from_str(row.get(0)?)
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            for dependent_id in ids {
+                if seen.insert((*dependent_type, dependent_id)) {
+                    collected.push((*dependent_type, dependent_id));
+                    pending.push((*dependent_type, dependent_id));
+                }
+            }
+        }
+    }
+
+    Ok(collected)
+}
+
+fn not_found(entity_type: TrashEntityType, id: Uuid) -> AppError {
+    AppError::NotFound(format!("{}未找到: {}", entity_type.display_name(), id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::temp_storage;
+    use crate::storage::StorageManager;
+
+    fn insert_category(storage: &StorageManager, id: Uuid, name: &str) {
+        storage
+            .get_database()
+            .execute(
+                "INSERT INTO categories (id, name, created_at) VALUES (?1, ?2, ?3)",
+                &[&id.to_string(), &name, &Local::now().to_rfc3339()],
+            )
+            .unwrap();
+    }
+
+    fn insert_task(storage: &StorageManager, id: Uuid, category_id: Option<Uuid>) {
+        storage
+            .get_database()
+            .execute(
+                "INSERT INTO tasks (id, name, category_id, created_at) VALUES (?1, 'task', ?2, ?3)",
+                &[
+                    &id.to_string(),
+                    &category_id.map(|id| id.to_string()),
+                    &Local::now().to_rfc3339(),
+                ],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_category_trash_and_restore() {
+        let (_temp_dir, storage) = temp_storage();
+        let db = storage.get_database();
+        let category_id = Uuid::new_v4();
+        let task_id = Uuid::new_v4();
+        let other_task_id = Uuid::new_v4();
+        insert_category(&storage, category_id, "回收站测试");
+        insert_task(&storage, task_id, Some(category_id));
+        insert_task(&storage, other_task_id, Some(category_id));
+
+        // 先单独删除一个任务，再删除分类
+        db.delete_task(other_task_id).unwrap();
+        db.delete_category(category_id).unwrap();
+        assert!(db.get_task_by_id(task_id).unwrap().is_none());
+        assert!(db.get_tasks_by_category(category_id).unwrap().is_empty());
+        assert!(db
+            .get_all_categories()
+            .unwrap()
+            .iter()
+            .all(|category| category.id != category_id));
+
+        let items = db.trash().list().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].entity_type, TrashEntityType::Category);
+        assert_eq!(items[0].dependent_count, 1);
+
+        // 恢复分类时一并恢复随之删除的任务，单独删除的任务仍在回收站中
+        assert!(db.trash().restore(TrashEntityType::Task, task_id).is_err());
+        db.trash()
+            .restore(TrashEntityType::Category, category_id)
+            .unwrap();
+        assert!(db.get_task_by_id(task_id).unwrap().is_some());
+        assert!(db.get_task_by_id(other_task_id).unwrap().is_none());
+        assert_eq!(db.trash().list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_purge() {
+        let (_temp_dir, storage) = temp_storage();
+        let db = storage.get_database();
+        let category_id = Uuid::new_v4();
+        let task_id = Uuid::new_v4();
+        insert_category(&storage, category_id, "回收站测试");
+        insert_task(&storage, task_id, Some(category_id));
+
+        db.delete_category(category_id).unwrap();
+        assert_eq!(db.trash().purge_expired(30).unwrap(), 0);
+        assert_eq!(db.trash().list().unwrap().len(), 1);
+
+        db.trash()
+            .purge(TrashEntityType::Category, category_id)
+            .unwrap();
+        assert!(db.trash().list().unwrap().is_empty());
+        let remaining: i64 = db
+            .query_row(
+                "SELECT COUNT(*) FROM tasks WHERE id = ?1",
+                &[&task_id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        let other_task_id = Uuid::new_v4();
+        insert_task(&storage, other_task_id, None);
+        db.delete_task(other_task_id).unwrap();
+        assert_eq!(db.trash().empty().unwrap(), 1);
+    }
+}
@@ -28,3 +28,12 @@ pub fn naive_date_from_str(text: String) -> std::result::Result<NaiveDate, rusql
         rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
     })
 }
+
+/// 将 snake_case 文本解析为枚举值
+pub fn enum_from_str<T: serde::de::DeserializeOwned>(
+    text: String,
+) -> std::result::Result<T, rusqlite::Error> {
+    serde_json::from_value(serde_json::Value::String(text)).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
@@ -0,0 +1,355 @@
+//! # 数据库加密模块
+//!
+//! 基于 SQLCipher 的整库静态加密，提供：
+//! - 加密口令的应用与校验
+//! - 明文数据库与加密数据库之间的相互转换
+//! - 修改口令
+//!
+//! 需要启用 `encryption` 特性（链接 SQLCipher）编译，未启用时加解密操作会返回错误。
+
+use crate::errors::{AppError, Result};
+use rusqlite::Connection;
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 明文 SQLite 数据库的文件头
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 转换过程中使用的临时文件后缀
+const CONVERT_SUFFIX: &str = "converting";
+
+/// 数据库加密口令
+///
+/// Debug 输出不包含口令内容，避免写入日志
+#[derive(Clone, PartialEq, Eq)]
+pub struct DatabaseKey(String);
+
+impl DatabaseKey {
+    /// 创建加密口令，口令不能为空
+    pub fn new(passphrase: impl Into<String>) -> Result<Self> {
+        let passphrase = passphrase.into();
+        if passphrase.is_empty() {
+            return Err(AppError::Validation("数据库口令不能为空".to_string()));
+        }
+        Ok(Self(passphrase))
+    }
+
+    /// 创建新设置的加密口令，两次输入不一致时返回校验错误
+    pub fn confirmed(passphrase: impl Into<String>, confirmation: &str) -> Result<Self> {
+        let passphrase = passphrase.into();
+        if passphrase != confirmation {
+            return Err(AppError::Validation("两次输入的口令不一致".to_string()));
+        }
+        Self::new(passphrase)
+    }
+
+    /// 口令原文
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// 转为 PRAGMA 语句中使用的字符串字面量
+    fn to_sql_literal(&self) -> String {
+        format!("'{}'", self.0.replace('\'', "''"))
+    }
+}
+
+impl fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DatabaseKey(***)")
+    }
+}
+
+/// 当前构建是否支持数据库加密（链接的是 SQLCipher）
+pub fn is_supported() -> bool {
+    Connection::open_in_memory()
+        .and_then(|conn| conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)))
+        .is_ok()
+}
+
+/// 数据库文件是否已加密
+///
+/// 不存在或为空的文件视为未加密
+pub fn is_encrypted<P: AsRef<Path>>(path: P) -> Result<bool> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let mut header = Vec::with_capacity(SQLITE_HEADER.len());
+    std::fs::File::open(path)?
+        .take(SQLITE_HEADER.len() as u64)
+        .read_to_end(&mut header)?;
+
+    Ok(!header.is_empty() && header != SQLITE_HEADER)
+}
+
+/// 为连接设置口令并校验
+///
+/// 必须在连接执行任何其他语句之前调用
+pub fn apply_key(conn: &Connection, key: &DatabaseKey) -> Result<()> {
+    ensure_supported()?;
+    conn.execute_batch(&format!("PRAGMA key = {};", key.to_sql_literal()))?;
+
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AppError::Permission("数据库口令错误或数据库文件已损坏".to_string()))?;
+    Ok(())
+}
+
+/// 打开数据库连接，提供口令时先解锁
+pub fn open_connection<P: AsRef<Path>>(path: P, key: Option<&DatabaseKey>) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    if let Some(key) = key {
+        apply_key(&conn, key)?;
+    }
+    Ok(conn)
+}
+
+/// 校验加密数据库的口令
+pub fn verify_passphrase<P: AsRef<Path>>(path: P, key: &DatabaseKey) -> Result<()> {
+    open_connection(path, Some(key)).map(|_| ())
+}
+
+/// 将明文数据库转换为加密数据库
+pub fn encrypt_database<P: AsRef<Path>>(path: P, key: &DatabaseKey) -> Result<()> {
+    let path = path.as_ref();
+    ensure_supported()?;
+    if is_encrypted(path)? {
+        return Err(AppError::Business("数据库已经加密".to_string()));
+    }
+
+    convert_database(path, None, Some(key))?;
+    log::info!("Database encrypted: {}", path.display());
+    Ok(())
+}
+
+/// 将加密数据库还原为明文数据库
+pub fn decrypt_database<P: AsRef<Path>>(path: P, key: &DatabaseKey) -> Result<()> {
+    let path = path.as_ref();
+    ensure_supported()?;
+    if !is_encrypted(path)? {
+        return Err(AppError::Business("数据库未加密".to_string()));
+    }
+
+    convert_database(path, Some(key), None)?;
+    log::info!("Database decrypted: {}", path.display());
+    Ok(())
+}
+
+/// 修改加密数据库的口令
+pub fn change_passphrase<P: AsRef<Path>>(
+    path: P,
+    old_key: &DatabaseKey,
+    new_key: &DatabaseKey,
+) -> Result<()> {
+    let conn = open_connection(path, Some(old_key))?;
+    rekey(&conn, new_key)
+}
+
+/// 在已解锁的连接上更换口令
+pub(crate) fn rekey(conn: &Connection, new_key: &DatabaseKey) -> Result<()> {
+    ensure_supported()?;
+    conn.execute_batch(&format!("PRAGMA rekey = {};", new_key.to_sql_literal()))?;
+    Ok(())
+}
+
+/// 通过 sqlcipher_export 将数据库导出为新文件后替换原文件
+fn convert_database(
+    path: &Path,
+    source_key: Option<&DatabaseKey>,
+    target_key: Option<&DatabaseKey>,
+) -> Result<()> {
+    let temp_path = sibling_path(path, CONVERT_SUFFIX);
+    remove_database_files(&temp_path)?;
+
+    let result = (|| -> Result<()> {
+        let conn = open_connection(path, source_key)?;
+        // 先把 WAL 中的内容写回主文件，导出和替换都只针对主文件
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+        let target_passphrase = target_key.map(DatabaseKey::expose).unwrap_or("");
+        conn.execute(
+            "ATTACH DATABASE ?1 AS converted KEY ?2",
+            rusqlite::params![temp_path.to_string_lossy(), target_passphrase],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('converted')", [], |_| Ok(()))?;
+        conn.execute_batch("DETACH DATABASE converted;")?;
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = remove_database_files(&temp_path);
+        return Err(AppError::Storage(format!("数据库加密转换失败: {}", e)));
+    }
+
+    // 转换结果可以正常打开后再替换原文件
+    if let Err(e) = verify_converted(&temp_path, target_key) {
+        let _ = remove_database_files(&temp_path);
+        return Err(e);
+    }
+
+    // rename 原子地替换原文件，替换成功后旧的 WAL/SHM 文件才失效
+    std::fs::rename(&temp_path, path)?;
+    remove_sidecar_files(path)
+}
+
+/// 校验转换后的数据库
+fn verify_converted(path: &Path, key: Option<&DatabaseKey>) -> Result<()> {
+    let conn = open_connection(path, key)?;
+    let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if integrity != "ok" {
+        return Err(AppError::Storage(format!(
+            "加密转换后的数据库完整性检查失败: {}",
+            integrity
+        )));
+    }
+    Ok(())
+}
+
+/// 删除数据库文件及其 WAL/SHM 文件
+fn remove_database_files(path: &Path) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    remove_sidecar_files(path)
+}
+
+/// 删除数据库的 WAL/SHM 文件
+fn remove_sidecar_files(path: &Path) -> Result<()> {
+    for file in [sibling_path(path, "wal"), sibling_path(path, "shm")] {
+        if file.exists() {
+            std::fs::remove_file(&file)?;
+        }
+    }
+    Ok(())
+}
+
+/// 在文件名后追加 `-suffix`
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!("-{}", suffix));
+    PathBuf::from(name)
+}
+
+fn ensure_supported() -> Result<()> {
+    if is_supported() {
+        Ok(())
+    } else {
+        Err(AppError::System(
+            "当前版本未启用数据库加密支持（需要 encryption 特性）".to_string(),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_plain_database(path: &Path) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL;
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, content TEXT);
+             INSERT INTO notes (content) VALUES ('日记内容');",
+        )
+        .unwrap();
+    }
+
+    fn read_note(path: &Path, key: Option<&DatabaseKey>) -> Result<String> {
+        let conn = open_connection(path, key)?;
+        Ok(conn.query_row("SELECT content FROM notes", [], |row| row.get(0))?)
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+        create_plain_database(&path);
+        let key = DatabaseKey::new("it's a secret").unwrap();
+
+        encrypt_database(&path, &key).unwrap();
+        assert!(is_encrypted(&path).unwrap());
+        assert_eq!(read_note(&path, Some(&key)).unwrap(), "日记内容");
+        assert!(read_note(&path, None).is_err());
+        assert!(verify_passphrase(&path, &DatabaseKey::new("wrong").unwrap()).is_err());
+
+        decrypt_database(&path, &key).unwrap();
+        assert!(!is_encrypted(&path).unwrap());
+        assert_eq!(read_note(&path, None).unwrap(), "日记内容");
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+        create_plain_database(&path);
+        let old_key = DatabaseKey::new("old").unwrap();
+        let new_key = DatabaseKey::new("new").unwrap();
+
+        encrypt_database(&path, &old_key).unwrap();
+        change_passphrase(&path, &old_key, &new_key).unwrap();
+
+        assert!(verify_passphrase(&path, &old_key).is_err());
+        assert_eq!(read_note(&path, Some(&new_key)).unwrap(), "日记内容");
+    }
+
+    #[test]
+    fn test_confirmed_key() {
+        assert_eq!(
+            DatabaseKey::confirmed("secret", "secret").unwrap(),
+            DatabaseKey::new("secret").unwrap()
+        );
+        assert!(matches!(
+            DatabaseKey::confirmed("secret", "secert"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(DatabaseKey::confirmed("", "").is_err());
+    }
+
+    #[test]
+    fn test_encrypted_storage_backup() {
+        use crate::storage::{DatabaseConfig, StorageManager};
+
+        let temp_dir = TempDir::new().unwrap();
+        let key = DatabaseKey::new("secret").unwrap();
+        let mut storage = StorageManager::new(DatabaseConfig {
+            database_path: temp_dir
+                .path()
+                .join("test.db")
+                .to_string_lossy()
+                .to_string(),
+            encryption_key: Some(key.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        storage.initialize().unwrap();
+        assert!(storage.get_database().is_encrypted());
+
+        let backup_path = temp_dir.path().join("backup.db");
+        storage.backup_database(&backup_path).unwrap();
+        assert!(is_encrypted(&backup_path).unwrap());
+        verify_passphrase(&backup_path, &key).unwrap();
+        storage.restore_database_from_backup(&backup_path).unwrap();
+
+        let new_key = DatabaseKey::new("changed").unwrap();
+        storage
+            .get_database()
+            .change_passphrase(&key, new_key.clone())
+            .unwrap();
+        assert!(storage.check_integrity().unwrap());
+        assert!(StorageManager::new(DatabaseConfig {
+            database_path: temp_dir
+                .path()
+                .join("test.db")
+                .to_string_lossy()
+                .to_string(),
+            encryption_key: Some(key),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -7,7 +7,7 @@ use log::{debug, info, warn};
 use rusqlite::Connection;
 
 /// 数据库版本
-const CURRENT_DB_VERSION: i32 = 5;
+const CURRENT_DB_VERSION: i32 = 11;
 
 /// 迁移管理器
 ///
@@ -70,20 +70,7 @@ impl<'conn> MigrationManager<'conn> {
 
     /// 获取当前数据库版本
     fn get_current_version(&self) -> Result<i32> {
-        let result =
-            self.connection
-                .query_row("SELECT MAX(version) FROM schema_version", [], |row| {
-                    row.get::<_, Option<i32>>(0)
-                });
-
-        match result {
-            Ok(Some(version)) => Ok(version),
-            Ok(None) => Ok(0), // 没有版本记录，从0开始
-            Err(rusqlite::Error::SqliteFailure(_, Some(msg))) if msg.contains("no such table") => {
-                Ok(0) // 版本表不存在，从0开始
-            }
-            Err(e) => Err(AppError::Database(e)),
-        }
+        get_schema_version(self.connection)
     }
 
     /// 更新版本号
@@ -104,6 +91,12 @@ impl<'conn> MigrationManager<'conn> {
             3 => self.migration_v3(),
             4 => self.migration_v4(),
             5 => self.migration_v5(),
+            6 => self.migration_v6(),
+            7 => self.migration_v7(),
+            8 => self.migration_v8(),
+            9 => self.migration_v9(),
+            10 => self.migration_v10(),
+            11 => self.migration_v11(),
             _ => {
                 warn!("Unknown migration version: {}", version);
                 Err(AppError::InvalidInput(format!(
@@ -505,6 +498,237 @@ impl<'conn> MigrationManager<'conn> {
         Ok(())
     }
 
+    /// 迁移到版本6：创建同步快照表
+    fn migration_v6(&self) -> Result<()> {
+        info!("Running migration v6: Creating sync snapshots table");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        // 创建同步快照表（保存上次成功同步的数据集，用于三方合并）
+        tx.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_snapshots (
+                id TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                data TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        tx.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sync_snapshots_kind ON sync_snapshots(kind, created_at)",
+            [],
+        )?;
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v6 completed");
+        Ok(())
+    }
+
+    /// 迁移到版本7：创建同步历史表
+    fn migration_v7(&self) -> Result<()> {
+        info!("Running migration v7: Creating sync history table");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        // 创建同步历史表（同步前的本地快照以 pre_sync 类型保存在 sync_snapshots 中）
+        tx.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS sync_history (
+                id TEXT PRIMARY KEY,
+                provider TEXT NOT NULL,
+                started_at DATETIME NOT NULL,
+                finished_at DATETIME NOT NULL,
+                success BOOLEAN NOT NULL,
+                uploaded_count INTEGER NOT NULL DEFAULT 0,
+                downloaded_count INTEGER NOT NULL DEFAULT 0,
+                skipped_count INTEGER NOT NULL DEFAULT 0,
+                failed_count INTEGER NOT NULL DEFAULT 0,
+                total_bytes INTEGER NOT NULL DEFAULT 0,
+                errors TEXT NOT NULL DEFAULT '[]',
+                conflicts TEXT NOT NULL DEFAULT '[]',
+                snapshot_id TEXT
+            )
+            "#,
+            [],
+        )?;
+
+        tx.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sync_history_started_at ON sync_history(started_at)",
+            [],
+        )?;
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v7 completed");
+        Ok(())
+    }
+
+    /// 迁移到版本8：同步历史记录执行同步的设备和远程数据来源设备
+    fn migration_v8(&self) -> Result<()> {
+        info!("Running migration v8: Adding device attribution to sync history");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        tx.execute("ALTER TABLE sync_history ADD COLUMN device_id TEXT", [])?;
+        tx.execute(
+            "ALTER TABLE sync_history ADD COLUMN remote_device_id TEXT",
+            [],
+        )?;
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v8 completed");
+        Ok(())
+    }
+
+    /// 迁移到版本9：添加归档数据的汇总统计表
+    fn migration_v9(&self) -> Result<()> {
+        info!("Running migration v9: Adding archived statistics tables");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        // 被清理的时间记录按日期和分类汇总
+        tx.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS archived_time_stats (
+                date DATE NOT NULL,
+                category_id TEXT NOT NULL DEFAULT '',
+                entry_count INTEGER NOT NULL DEFAULT 0,
+                total_seconds INTEGER NOT NULL DEFAULT 0,
+                max_seconds INTEGER NOT NULL DEFAULT 0,
+                min_seconds INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, category_id)
+            )
+            "#,
+            [],
+        )?;
+
+        // 被清理的已完成任务按完成日期和分类汇总
+        tx.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS archived_task_stats (
+                date DATE NOT NULL,
+                category_id TEXT NOT NULL DEFAULT '',
+                completed_count INTEGER NOT NULL DEFAULT 0,
+                total_duration_seconds INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (date, category_id)
+            )
+            "#,
+            [],
+        )?;
+
+        // 被清理的交易按日期、分类、类型和币种汇总
+        tx.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS archived_transaction_stats (
+                date DATE NOT NULL,
+                category_id TEXT NOT NULL DEFAULT '',
+                transaction_type TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                transaction_count INTEGER NOT NULL DEFAULT 0,
+                total_amount REAL NOT NULL DEFAULT 0.0,
+                PRIMARY KEY (date, category_id, transaction_type, currency)
+            )
+            "#,
+            [],
+        )?;
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v9 completed");
+        Ok(())
+    }
+
+    /// 迁移到版本10：添加软删除字段，支持回收站
+    fn migration_v10(&self) -> Result<()> {
+        info!("Running migration v10: Adding soft delete columns");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        for table in [
+            "tasks",
+            "time_entries",
+            "categories",
+            "notes",
+            "accounts",
+            "transactions",
+        ] {
+            tx.execute(
+                &format!("ALTER TABLE {} ADD COLUMN deleted_at DATETIME", table),
+                [],
+            )?;
+            tx.execute(
+                &format!(
+                    "CREATE INDEX IF NOT EXISTS idx_{0}_deleted_at ON {0}(deleted_at)",
+                    table
+                ),
+                [],
+            )?;
+        }
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v10 completed");
+        Ok(())
+    }
+
+    /// 迁移到版本11：添加审计日志表
+    fn migration_v11(&self) -> Result<()> {
+        info!("Running migration v11: Creating audit log table");
+
+        // 开始事务
+        let tx = self.connection.unchecked_transaction()?;
+
+        tx.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                changes TEXT NOT NULL,
+                source TEXT NOT NULL,
+                changed_at DATETIME NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity_type, entity_id);
+            CREATE INDEX IF NOT EXISTS idx_audit_log_changed_at ON audit_log(changed_at);
+
+            -- 审计日志只允许追加
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;
+            "#,
+        )?;
+
+        // 提交事务
+        tx.commit()?;
+
+        info!("Migration v11 completed");
+        Ok(())
+    }
+
     /// 创建数据库索引
     fn create_indexes(&self, tx: &rusqlite::Transaction) -> Result<()> {
         debug!("创建数据库索引...");
@@ -963,6 +1187,28 @@ impl DatabaseStats {
     }
 }
 
+/// 读取数据库结构版本，没有版本记录时返回0
+pub fn get_schema_version(connection: &Connection) -> Result<i32> {
+    let result = connection.query_row("SELECT MAX(version) FROM schema_version", [], |row| {
+        row.get::<_, Option<i32>>(0)
+    });
+
+    match result {
+        Ok(Some(version)) => Ok(version),
+        Ok(None) => Ok(0), // 没有版本记录，从0开始
+        Err(rusqlite::Error::SqliteFailure(_, Some(msg))) if msg.contains("no such table") => {
+            Ok(0) // 版本表不存在，从0开始
+        }
+        Err(e) => Err(AppError::Database(e)),
+    }
+}
+
+/// 是否有待执行的迁移（尚未初始化的空数据库返回 false）
+pub fn needs_migration(connection: &Connection) -> Result<bool> {
+    let version = get_schema_version(connection)?;
+    Ok(version > 0 && version < CURRENT_DB_VERSION)
+}
+
 /// 便捷函数：运行迁移
 ///
 /// 创建迁移管理器并运行所有必要的迁移
@@ -6,12 +6,19 @@
 //! - 数据库迁移
 
 pub mod accounting_models;
+pub mod backend;
+pub mod backup;
 pub mod database;
+pub mod encryption;
 pub mod migrations;
 pub mod models;
+pub mod retention;
 pub mod task_models;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // 重新导出主要类型
+pub use backend::{MemoryStorage, StorageBackend};
 pub use database::Database;
 pub use models::*;
 pub use task_models::*;
@@ -51,7 +58,6 @@ pub use accounting_models::{
 };
 
 use crate::errors::AppError;
-use rusqlite::Connection;
 use std::path::Path;
 
 /// 数据库配置
@@ -61,10 +67,12 @@ pub struct DatabaseConfig {
     pub database_path: String,
     /// 是否启用WAL模式
     pub enable_wal: bool,
-    /// 连接池大小
+    /// 连接池大小（只读连接数）
     pub pool_size: u32,
-    /// 查询超时时间（秒）
+    /// 查询超时时间（秒），也用作数据库忙等待时间
     pub timeout_seconds: u64,
+    /// 加密口令（数据库未加密时为 None）
+    pub encryption_key: Option<encryption::DatabaseKey>,
 }
 
 impl Default for DatabaseConfig {
@@ -78,6 +86,7 @@ impl Default for DatabaseConfig {
             enable_wal: true,
             pool_size: 10,
             timeout_seconds: 30,
+            encryption_key: None,
         }
     }
 }
@@ -91,6 +100,8 @@ pub struct StorageManager {
     database: Database,
     /// 配置信息
     config: DatabaseConfig,
+    /// 备份服务（迁移、导入和恢复前自动备份）
+    backup_service: Option<backup::BackupService>,
 }
 
 impl StorageManager {
@@ -99,9 +110,17 @@ impl StorageManager {
     /// # 参数
     /// * `config` - 数据库配置
     pub fn new(config: DatabaseConfig) -> crate::errors::Result<Self> {
-        let database = Database::new(&config.database_path)?;
-
-        let storage_manager = Self { database, config };
+        let database = Database::with_config(
+            &config.database_path,
+            config.encryption_key.clone(),
+            database::PoolConfig::from(&config),
+        )?;
+
+        let storage_manager = Self {
+            database,
+            config,
+            backup_service: None,
+        };
 
         log::info!("Storage manager initialization completed");
         Ok(storage_manager)
@@ -113,8 +132,33 @@ impl StorageManager {
         Self::new(config)
     }
 
+    /// 设置备份服务
+    pub fn set_backup_service(&mut self, service: backup::BackupService) {
+        self.backup_service = Some(service);
+    }
+
+    /// 获取备份服务
+    pub fn backup_service(&self) -> Option<&backup::BackupService> {
+        self.backup_service.as_ref()
+    }
+
+    /// 按备份策略在迁移、导入、恢复或清理前备份数据库
+    pub(crate) fn backup_before(&self, reason: backup::BackupReason) -> crate::errors::Result<()> {
+        match &self.backup_service {
+            Some(service) if service.policy().auto_backup => {
+                service.create_backup(&self.database, reason)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// 初始化存储系统
     pub fn initialize(&mut self) -> crate::errors::Result<()> {
+        if self.database.needs_migration()? {
+            self.backup_before(backup::BackupReason::PreMigration)?;
+        }
+
         // 确保数据库表结构存在
         self.database.run_migrations()?;
 
@@ -151,6 +195,9 @@ impl StorageManager {
         // 设置临时存储
         conn.execute("PRAGMA temp_store = MEMORY", rusqlite::params![])?;
 
+        // 修改 temp_store 会删除连接上的临时表和触发器，需要重新安装审计触发器
+        database::audit::install_triggers(&conn)?;
+
         log::debug!("Database configuration completed");
         Ok(())
     }
@@ -165,21 +212,18 @@ impl StorageManager {
         &mut self.database
     }
 
+    /// 取出数据库实例
+    pub fn into_database(self) -> Database {
+        self.database
+    }
+
     /// 备份数据库到文件
     ///
     /// # 参数
     /// * `backup_path` - 备份文件路径
     pub fn backup_database<P: AsRef<Path>>(&self, backup_path: P) -> crate::errors::Result<()> {
-        let source_conn = self.database.get_connection()?.get_raw_connection();
-        let source_conn = source_conn.lock().unwrap();
-        let mut backup_conn = Connection::open(backup_path)?;
-
-        // 使用SQLite的备份API
-        let backup = rusqlite::backup::Backup::new(&*source_conn, &mut backup_conn)?;
-        backup.run_to_completion(5, std::time::Duration::from_millis(250), None)?;
-
-        log::info!("Database backup completed");
-        Ok(())
+        // 加密数据库的备份使用相同口令，备份文件同样保持加密
+        self.database.backup_to(backup_path)
     }
 
     /// 从备份恢复数据库（共享引用版本）
@@ -212,9 +256,12 @@ impl StorageManager {
             return Err(AppError::Storage("备份文件为空".to_string()));
         }
 
+        self.backup_before(backup::BackupReason::PreRestore)?;
+
         log::info!("Opening backup file connection...");
-        let backup_conn = Connection::open(backup_path)
-            .map_err(|e| AppError::Storage(format!("无法打开备份文件: {}", e)))?;
+        let backup_conn =
+            encryption::open_connection(backup_path, self.config.encryption_key.as_ref())
+                .map_err(|e| AppError::Storage(format!("无法打开备份文件: {}", e)))?;
 
         log::info!("Validating backup file integrity...");
         // 检查备份文件的完整性
@@ -408,6 +455,8 @@ impl StorageManager {
                 most_productive_day: None,
                 most_used_category: None,
             }),
+            financial: None,
+            tasks: Vec::new(),
         };
 
         // 导出为JSON格式
@@ -423,9 +472,26 @@ impl StorageManager {
         let import_path = import_path.as_ref();
         log::info!("Starting data import from file: {}", import_path.display());
 
+        self.backup_before(backup::BackupReason::PreImport)?;
+
         // 从JSON文件导入
         let import_data = crate::utils::import::import_from_json(import_path)?;
 
+        self.database
+            .audit()
+            .with_source(database::AuditSource::Import, || {
+                self.write_import_data(import_data)
+            })?;
+
+        log::info!("Data import completed");
+        Ok(())
+    }
+
+    /// 写入导入的数据，已存在的数据会被覆盖
+    fn write_import_data(
+        &self,
+        import_data: crate::utils::export::ExportData,
+    ) -> crate::errors::Result<()> {
         // 获取数据库连接
         let conn = self.database.get_connection()?.get_raw_connection();
         let conn = conn.lock().unwrap();
@@ -476,7 +542,6 @@ impl StorageManager {
             }
         }
 
-        log::info!("Data import completed");
         Ok(())
     }
 
@@ -506,15 +571,142 @@ impl StorageManager {
         Ok(())
     }
 
+    /// 按日期和分类汇总时间记录（包含已清理数据的汇总统计）
+    fn query_time_totals(
+        &self,
+        start_date: chrono::NaiveDate,
+        end_date: chrono::NaiveDate,
+    ) -> crate::errors::Result<Vec<TimeTotalRow>> {
+        let sql = r#"
+            SELECT day, category_id, SUM(entry_count), SUM(total_seconds),
+                   MAX(max_seconds), MIN(min_seconds)
+            FROM (
+                SELECT DATE(start_time) AS day, COALESCE(category_id, '') AS category_id,
+                       COUNT(*) AS entry_count, SUM(duration_seconds) AS total_seconds,
+                       MAX(duration_seconds) AS max_seconds, MIN(duration_seconds) AS min_seconds
+                FROM time_entries
+                WHERE DATE(start_time) BETWEEN ?1 AND ?2 AND deleted_at IS NULL
+                GROUP BY 1, 2
+                UNION ALL
+                SELECT date, category_id, entry_count, total_seconds, max_seconds, min_seconds
+                FROM archived_time_stats
+                WHERE date BETWEEN ?1 AND ?2
+            )
+            GROUP BY day, category_id
+            ORDER BY day, category_id
+        "#;
+
+        self.database.get_connection()?.read(|conn| {
+            let mut stmt = conn.prepare(sql)?;
+            let rows = stmt.query_map(
+                rusqlite::params![start_date.to_string(), end_date.to_string()],
+                |row| {
+                    let date: String = row.get(0)?;
+                    Ok(TimeTotalRow {
+                        date: date.parse().map_err(|e| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                0,
+                                rusqlite::types::Type::Text,
+                                Box::new(e),
+                            )
+                        })?,
+                        category_id: row.get(1)?,
+                        count: row.get(2)?,
+                        total_seconds: row.get(3)?,
+                        max_seconds: row.get(4)?,
+                        min_seconds: row.get(5)?,
+                    })
+                },
+            )?;
+            Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+        })
+    }
+
+    /// 将汇总行合并为分类统计
+    fn build_category_stats(
+        &self,
+        rows: &[&TimeTotalRow],
+    ) -> crate::errors::Result<Vec<crate::storage::models::CategoryStats>> {
+        let names: std::collections::HashMap<String, String> = self
+            .database
+            .get_all_categories()?
+            .into_iter()
+            .map(|category| (category.id.to_string(), category.name))
+            .collect();
+
+        let mut stats: Vec<crate::storage::models::CategoryStats> = Vec::new();
+        for row in rows {
+            let category_id = uuid::Uuid::parse_str(&row.category_id).unwrap_or(uuid::Uuid::nil());
+            let last_used = row
+                .date
+                .and_hms_opt(0, 0, 0)
+                .and_then(|time| time.and_local_timezone(chrono::Local).single());
+
+            match stats.iter_mut().find(|s| s.category_id == category_id) {
+                Some(existing) => {
+                    existing.task_count += row.count as usize;
+                    existing.total_seconds += row.total_seconds;
+                    existing.last_used = existing.last_used.max(last_used);
+                }
+                None => stats.push(crate::storage::models::CategoryStats {
+                    category_id,
+                    category_name: names
+                        .get(&row.category_id)
+                        .cloned()
+                        .unwrap_or_else(|| "未分类".to_string()),
+                    task_count: row.count as usize,
+                    total_seconds: row.total_seconds,
+                    average_seconds: 0.0,
+                    last_used,
+                }),
+            }
+        }
+
+        for category in &mut stats {
+            if category.task_count > 0 {
+                category.average_seconds =
+                    category.total_seconds as f64 / category.task_count as f64;
+            }
+        }
+        stats.sort_by_key(|s| std::cmp::Reverse(s.total_seconds));
+        Ok(stats)
+    }
+
     /// 获取每日统计范围
+    ///
+    /// 已按保留策略清理的时间记录通过汇总统计计入
     pub fn get_daily_stats_range(
         &self,
-        _start_date: chrono::NaiveDate,
-        _end_date: chrono::NaiveDate,
+        start_date: chrono::NaiveDate,
+        end_date: chrono::NaiveDate,
     ) -> crate::errors::Result<Vec<crate::storage::models::DailyStats>> {
-        // 这里应该实现实际的查询逻辑
-        // 暂时返回空向量
-        Ok(vec![])
+        let rows = self.query_time_totals(start_date, end_date)?;
+
+        let mut daily_stats = Vec::new();
+        for date in rows
+            .iter()
+            .map(|row| row.date)
+            .collect::<std::collections::BTreeSet<_>>()
+        {
+            let day_rows: Vec<&TimeTotalRow> = rows.iter().filter(|r| r.date == date).collect();
+
+            let mut stats = crate::storage::models::DatabaseTimeStats::empty();
+            stats.task_count = day_rows.iter().map(|r| r.count).sum();
+            stats.total_seconds = day_rows.iter().map(|r| r.total_seconds).sum();
+            stats.max_seconds = day_rows.iter().map(|r| r.max_seconds).max().unwrap_or(0);
+            stats.min_seconds = day_rows.iter().map(|r| r.min_seconds).min().unwrap_or(0);
+            if stats.task_count > 0 {
+                stats.average_seconds = stats.total_seconds as f64 / stats.task_count as f64;
+            }
+
+            daily_stats.push(crate::storage::models::DailyStats {
+                date,
+                stats,
+                category_stats: self.build_category_stats(&day_rows)?,
+            });
+        }
+
+        Ok(daily_stats)
     }
 
     /// 获取每周统计范围
@@ -539,15 +731,14 @@ impl StorageManager {
         Ok(vec![])
     }
 
-    /// 获取分类统计
+    /// 获取分类统计（包含已清理数据的汇总统计）
     pub fn get_category_stats(
         &self,
-        _start_date: chrono::NaiveDate,
-        _end_date: chrono::NaiveDate,
+        start_date: chrono::NaiveDate,
+        end_date: chrono::NaiveDate,
     ) -> crate::errors::Result<Vec<crate::storage::models::CategoryStats>> {
-        // 这里应该实现实际的查询逻辑
-        // 暂时返回空向量
-        Ok(vec![])
+        let rows = self.query_time_totals(start_date, end_date)?;
+        self.build_category_stats(&rows.iter().collect::<Vec<_>>())
     }
 
     /// 获取配置信息
@@ -637,6 +828,22 @@ impl StorageManager {
     }
 }
 
+/// 按日期和分类汇总的时间记录
+struct TimeTotalRow {
+    /// 日期
+    date: chrono::NaiveDate,
+    /// 分类ID（未分类为空字符串）
+    category_id: String,
+    /// 记录数
+    count: i64,
+    /// 总时长（秒）
+    total_seconds: i64,
+    /// 最长时长（秒）
+    max_seconds: i64,
+    /// 最短时长（秒）
+    min_seconds: i64,
+}
+
 /// 数据库统计信息
 #[derive(Debug, Clone)]
 pub struct DatabaseStats {
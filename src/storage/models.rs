@@ -146,7 +146,7 @@ pub struct CategoryInsert {
 /// 分类更新模型
 ///
 /// 用于更新现有的分类
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CategoryUpdate {
     /// 分类名称（可选）
     pub name: Option<String>,
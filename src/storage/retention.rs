@@ -0,0 +1,693 @@
+//! # 数据保留模块
+//!
+//! 按 `DataConfig` 中的 `data_retention_days` 和各数据类型的保留规则，
+//! 删除或归档超出保留期限的时间记录、已完成任务和交易记录。
+//! 清理前先生成预览（dry-run）摘要，由用户在设置页确认后执行；只有同时开启
+//! `auto_cleanup` 和 `cleanup_on_startup` 时才在启动时直接清理。
+//! 被清理的数据会汇总到 `archived_*_stats` 表，历史统计报表仍可使用。
+//! 归档的数据移入单独的归档数据库
+
+use super::{Database, StorageManager};
+use crate::config::{DataConfig, RetentionAction, RetentionDataType};
+use crate::errors::{AppError, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 默认归档数据库文件名
+const ARCHIVE_DATABASE_FILE: &str = "lifetracker-archive.db";
+
+/// 归档数据库的附加名称
+const ARCHIVE_SCHEMA: &str = "archive";
+
+/// 数据类型对应的表和清理条件（`?1` 为截止日期）
+struct RetentionTarget {
+    /// 数据表
+    table: &'static str,
+    /// 清理条件
+    condition: &'static str,
+    /// 用于预览的日期表达式
+    date_expr: &'static str,
+    /// 写入汇总统计的 SQL
+    aggregate_sql: &'static str,
+}
+
+impl RetentionTarget {
+    /// 获取数据类型对应的清理目标
+    fn of(data_type: RetentionDataType) -> Self {
+        match data_type {
+            RetentionDataType::TimeEntries => Self {
+                table: "time_entries",
+                condition: "deleted_at IS NULL AND DATE(start_time) < ?1",
+                date_expr: "DATE(start_time)",
+                aggregate_sql: r#"
+                    INSERT INTO archived_time_stats
+                        (date, category_id, entry_count, total_seconds, max_seconds, min_seconds)
+                    SELECT DATE(start_time), COALESCE(category_id, ''), COUNT(*),
+                           SUM(duration_seconds), MAX(duration_seconds), MIN(duration_seconds)
+                    FROM time_entries
+                    WHERE deleted_at IS NULL AND DATE(start_time) < ?1
+                    GROUP BY 1, 2
+                    ON CONFLICT(date, category_id) DO UPDATE SET
+                        entry_count = entry_count + excluded.entry_count,
+                        total_seconds = total_seconds + excluded.total_seconds,
+                        max_seconds = MAX(max_seconds, excluded.max_seconds),
+                        min_seconds = MIN(min_seconds, excluded.min_seconds)
+                "#,
+            },
+            RetentionDataType::CompletedTasks => Self {
+                table: "tasks",
+                condition: "deleted_at IS NULL AND is_completed = 1 AND completed_at IS NOT NULL \
+                            AND DATE(completed_at) < ?1",
+                date_expr: "DATE(completed_at)",
+                aggregate_sql: r#"
+                    INSERT INTO archived_task_stats
+                        (date, category_id, completed_count, total_duration_seconds)
+                    SELECT DATE(completed_at), COALESCE(category_id, ''), COUNT(*),
+                           SUM(total_duration_seconds)
+                    FROM tasks
+                    WHERE deleted_at IS NULL AND is_completed = 1 AND completed_at IS NOT NULL
+                      AND DATE(completed_at) < ?1
+                    GROUP BY 1, 2
+                    ON CONFLICT(date, category_id) DO UPDATE SET
+                        completed_count = completed_count + excluded.completed_count,
+                        total_duration_seconds =
+                            total_duration_seconds + excluded.total_duration_seconds
+                "#,
+            },
+            RetentionDataType::Transactions => Self {
+                table: "transactions",
+                condition: "deleted_at IS NULL AND status != 'pending' \
+                            AND DATE(transaction_date) < ?1",
+                date_expr: "DATE(transaction_date)",
+                aggregate_sql: r#"
+                    INSERT INTO archived_transaction_stats
+                        (date, category_id, transaction_type, currency,
+                         transaction_count, total_amount)
+                    SELECT DATE(transaction_date), COALESCE(category_id, ''), transaction_type,
+                           currency, COUNT(*), SUM(amount)
+                    FROM transactions
+                    WHERE deleted_at IS NULL AND status != 'pending'
+                      AND DATE(transaction_date) < ?1
+                    GROUP BY 1, 2, 3, 4
+                    ON CONFLICT(date, category_id, transaction_type, currency) DO UPDATE SET
+                        transaction_count = transaction_count + excluded.transaction_count,
+                        total_amount = total_amount + excluded.total_amount
+                "#,
+            },
+        }
+    }
+}
+
+/// 单个数据类型的保留规则（已合并默认值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedRetentionRule {
+    /// 数据类型
+    pub data_type: RetentionDataType,
+    /// 处理方式
+    pub action: RetentionAction,
+    /// 保留天数
+    pub retention_days: u32,
+}
+
+/// 数据保留策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionPolicy {
+    /// 是否在启动时不经确认直接清理
+    pub run_on_startup: bool,
+    /// 各数据类型的规则（不处理的类型不包含在内）
+    pub rules: Vec<ResolvedRetentionRule>,
+    /// 归档数据库路径（为空时放在数据库文件旁边）
+    pub archive_path: Option<PathBuf>,
+}
+
+impl From<&DataConfig> for RetentionPolicy {
+    fn from(config: &DataConfig) -> Self {
+        let rules = RetentionDataType::ALL
+            .iter()
+            .filter_map(|data_type| {
+                let rule = config
+                    .retention_rules
+                    .iter()
+                    .find(|rule| rule.data_type == *data_type);
+                let action = rule.map_or(RetentionAction::Archive, |rule| rule.action);
+                let retention_days = rule
+                    .and_then(|rule| rule.retention_days)
+                    .or(config.data_retention_days)?;
+
+                (action != RetentionAction::Keep && retention_days > 0).then_some(
+                    ResolvedRetentionRule {
+                        data_type: *data_type,
+                        action,
+                        retention_days,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            run_on_startup: config.auto_cleanup && config.cleanup_on_startup,
+            rules,
+            archive_path: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// 指定数据库对应的归档数据库路径
+    pub fn archive_path_for(&self, database_path: &Path) -> PathBuf {
+        self.archive_path.clone().unwrap_or_else(|| {
+            database_path
+                .parent()
+                .map(|dir| dir.join(ARCHIVE_DATABASE_FILE))
+                .unwrap_or_else(|| PathBuf::from(ARCHIVE_DATABASE_FILE))
+        })
+    }
+}
+
+/// 清理预览中的单项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPlanItem {
+    /// 规则
+    pub rule: ResolvedRetentionRule,
+    /// 截止日期（早于该日期的数据会被清理）
+    pub cutoff: NaiveDate,
+    /// 将被清理的记录数
+    pub record_count: u64,
+    /// 最早的记录日期
+    pub oldest: Option<NaiveDate>,
+    /// 最晚的记录日期
+    pub newest: Option<NaiveDate>,
+}
+
+/// 清理预览（dry-run 结果）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPlan {
+    /// 生成时间
+    pub created_at: DateTime<Local>,
+    /// 归档数据库路径
+    pub archive_path: PathBuf,
+    /// 各数据类型的预览
+    pub items: Vec<RetentionPlanItem>,
+}
+
+impl RetentionPlan {
+    /// 将被清理的记录总数
+    pub fn total_records(&self) -> u64 {
+        self.items.iter().map(|item| item.record_count).sum()
+    }
+
+    /// 是否没有需要清理的数据
+    pub fn is_empty(&self) -> bool {
+        self.total_records() == 0
+    }
+
+    /// 生成可读的预览摘要
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "没有超出保留期限的数据".to_string();
+        }
+
+        self.items
+            .iter()
+            .filter(|item| item.record_count > 0)
+            .map(|item| {
+                let action = match item.rule.action {
+                    RetentionAction::Delete => "删除",
+                    RetentionAction::Archive => "归档",
+                    RetentionAction::Keep => "保留",
+                };
+                let range = match (item.oldest, item.newest) {
+                    (Some(oldest), Some(newest)) => format!("（{} 至 {}）", oldest, newest),
+                    _ => String::new(),
+                };
+                format!(
+                    "{}：将{} {} 条 {} 之前的记录{}",
+                    data_type_name(item.rule.data_type),
+                    action,
+                    item.record_count,
+                    item.cutoff,
+                    range
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 清理结果中的单项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionReportItem {
+    /// 数据类型
+    pub data_type: RetentionDataType,
+    /// 处理方式
+    pub action: RetentionAction,
+    /// 移入归档数据库的记录数
+    pub archived: u64,
+    /// 从数据库中移除的记录数
+    pub removed: u64,
+}
+
+/// 清理结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// 执行时间
+    pub executed_at: DateTime<Local>,
+    /// 各数据类型的结果
+    pub items: Vec<RetentionReportItem>,
+}
+
+/// 数据保留服务
+#[derive(Debug, Clone)]
+pub struct RetentionService {
+    /// 保留策略
+    policy: RetentionPolicy,
+}
+
+impl RetentionService {
+    /// 创建数据保留服务
+    pub fn new(policy: RetentionPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// 获取保留策略
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// 归档数据库路径
+    fn archive_path(&self, database: &Database) -> PathBuf {
+        self.policy.archive_path_for(database.path())
+    }
+
+    /// 生成清理预览，不修改任何数据
+    pub fn plan(&self, database: &Database) -> Result<RetentionPlan> {
+        let today = Local::now().date_naive();
+        let connection = database.get_connection()?;
+
+        let items = self
+            .policy
+            .rules
+            .iter()
+            .map(|rule| {
+                let target = RetentionTarget::of(rule.data_type);
+                let cutoff = today - chrono::Duration::days(rule.retention_days as i64);
+                let sql = format!(
+                    "SELECT COUNT(*), MIN({date}), MAX({date}) FROM {table} WHERE {condition}",
+                    date = target.date_expr,
+                    table = target.table,
+                    condition = target.condition
+                );
+                let (record_count, oldest, newest) = connection.read(|conn| {
+                    Ok(conn.query_row(&sql, params![cutoff.to_string()], |row| {
+                        Ok((
+                            row.get::<_, i64>(0)? as u64,
+                            row.get::<_, Option<String>>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                        ))
+                    })?)
+                })?;
+
+                Ok(RetentionPlanItem {
+                    rule: *rule,
+                    cutoff,
+                    record_count,
+                    oldest: oldest.and_then(|d| d.parse().ok()),
+                    newest: newest.and_then(|d| d.parse().ok()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RetentionPlan {
+            created_at: Local::now(),
+            archive_path: self.archive_path(database),
+            items,
+        })
+    }
+
+    /// 按预览执行清理
+    ///
+    /// 使用预览中的截止日期，在一个事务中汇总统计、归档并删除数据
+    pub fn execute(&self, database: &Database, plan: &RetentionPlan) -> Result<RetentionReport> {
+        let needs_archive = plan
+            .items
+            .iter()
+            .any(|item| item.rule.action == RetentionAction::Archive && item.record_count > 0);
+        if needs_archive {
+            if let Some(parent) = plan.archive_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let key = database.key()?;
+        let key = key.as_ref();
+        let items = database.get_connection()?.write(|conn| {
+            if needs_archive {
+                // 加密数据库的归档库使用相同口令
+                match key {
+                    Some(key) => conn.execute(
+                        &format!("ATTACH DATABASE ?1 AS {} KEY ?2", ARCHIVE_SCHEMA),
+                        params![plan.archive_path.to_string_lossy(), key.expose()],
+                    )?,
+                    None => conn.execute(
+                        &format!("ATTACH DATABASE ?1 AS {}", ARCHIVE_SCHEMA),
+                        params![plan.archive_path.to_string_lossy()],
+                    )?,
+                };
+            }
+
+            let result = execute_plan(conn, plan);
+
+            if needs_archive {
+                if let Err(e) = conn.execute(&format!("DETACH DATABASE {}", ARCHIVE_SCHEMA), []) {
+                    log::warn!("分离归档数据库失败: {}", e);
+                }
+            }
+            result
+        })?;
+
+        for item in &items {
+            if item.removed > 0 {
+                log::info!(
+                    "数据保留：{} 已移除 {} 条，归档 {} 条",
+                    data_type_name(item.data_type),
+                    item.removed,
+                    item.archived
+                );
+            }
+        }
+
+        Ok(RetentionReport {
+            executed_at: Local::now(),
+            items,
+        })
+    }
+
+    /// 用户明确选择启动时直接清理时执行清理
+    ///
+    /// 先生成并记录预览摘要，有数据需要清理时按备份设置先备份数据库
+    pub fn run_automatic(&self, storage: &StorageManager) -> Result<Option<RetentionReport>> {
+        if !self.policy.run_on_startup || self.policy.rules.is_empty() {
+            return Ok(None);
+        }
+
+        let plan = self.plan(storage.get_database())?;
+        log::info!("数据保留预览:\n{}", plan.summary());
+        if plan.is_empty() {
+            return Ok(None);
+        }
+
+        storage.backup_before(super::backup::BackupReason::PreCleanup)?;
+        self.execute(storage.get_database(), &plan).map(Some)
+    }
+}
+
+/// 在事务中执行清理计划
+fn execute_plan(conn: &mut Connection, plan: &RetentionPlan) -> Result<Vec<RetentionReportItem>> {
+    let tx = conn.transaction()?;
+    let mut items = Vec::new();
+
+    for item in &plan.items {
+        let rule = item.rule;
+        let target = RetentionTarget::of(rule.data_type);
+        let cutoff = item.cutoff.to_string();
+
+        tx.execute(target.aggregate_sql, params![cutoff])?;
+
+        let archived = if rule.action == RetentionAction::Archive {
+            ensure_archive_table(&tx, target.table)?;
+            let columns = table_columns(&tx, "main", target.table)?.join(", ");
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {schema}.{table} ({columns}) SELECT {columns} FROM main.{table} WHERE {condition}",
+                    schema = ARCHIVE_SCHEMA,
+                    table = target.table,
+                    columns = columns,
+                    condition = target.condition
+                ),
+                params![cutoff],
+            )? as u64
+        } else {
+            0
+        };
+
+        let removed = tx.execute(
+            &format!(
+                "DELETE FROM main.{} WHERE {}",
+                target.table, target.condition
+            ),
+            params![cutoff],
+        )? as u64;
+
+        items.push(RetentionReportItem {
+            data_type: rule.data_type,
+            action: rule.action,
+            archived,
+            removed,
+        });
+    }
+
+    tx.commit()?;
+    Ok(items)
+}
+
+/// 确保归档数据库中存在对应的表，并补齐主库新增的列
+fn ensure_archive_table(conn: &Connection, table: &str) -> Result<()> {
+    let exists = conn
+        .query_row(
+            &format!(
+                "SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = ?1",
+                ARCHIVE_SCHEMA
+            ),
+            params![table],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+
+    if !exists {
+        conn.execute(
+            &format!(
+                "CREATE TABLE {schema}.{table} AS SELECT * FROM main.{table} WHERE 0",
+                schema = ARCHIVE_SCHEMA,
+                table = table
+            ),
+            [],
+        )?;
+        conn.execute(
+            &format!(
+                "CREATE UNIQUE INDEX {schema}.idx_{table}_id ON {table}(id)",
+                schema = ARCHIVE_SCHEMA,
+                table = table
+            ),
+            [],
+        )?;
+        return Ok(());
+    }
+
+    let archived_columns = table_columns(conn, ARCHIVE_SCHEMA, table)?;
+    for column in table_columns(conn, "main", table)? {
+        if !archived_columns.contains(&column) {
+            conn.execute(
+                &format!(
+                    "ALTER TABLE {}.{} ADD COLUMN {}",
+                    ARCHIVE_SCHEMA, table, column
+                ),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 读取表的列名
+fn table_columns(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>("name"))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if columns.is_empty() {
+        return Err(AppError::Storage(format!(
+            "数据表不存在: {}.{}",
+            schema, table
+        )));
+    }
+    Ok(columns)
+}
+
+/// 数据类型的显示名称
+fn data_type_name(data_type: RetentionDataType) -> &'static str {
+    match data_type {
+        RetentionDataType::TimeEntries => "时间记录",
+        RetentionDataType::CompletedTasks => "已完成任务",
+        RetentionDataType::Transactions => "交易记录",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetentionRule;
+    use crate::storage::test_support::temp_storage;
+
+    fn insert_time_entry(storage: &StorageManager, id: &str, start: &str, seconds: i64) {
+        storage
+            .get_database()
+            .execute(
+                "INSERT INTO time_entries (id, task_name, start_time, duration_seconds, created_at) VALUES (?1, 'task', ?2, ?3, ?2)",
+                &[&id, &start, &seconds],
+            )
+            .unwrap();
+    }
+
+    fn insert_task(storage: &StorageManager, id: &str, completed_at: &str) {
+        storage
+            .get_database()
+            .execute(
+                "INSERT INTO tasks (id, name, is_completed, completed_at, total_duration_seconds, created_at) VALUES (?1, 'task', 1, ?2, 60, ?2)",
+                &[&id, &completed_at],
+            )
+            .unwrap();
+    }
+
+    fn count(storage: &StorageManager, sql: &str) -> i64 {
+        storage
+            .get_database()
+            .query_row(sql, &[], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_from_config() {
+        let config = DataConfig {
+            data_retention_days: Some(365),
+            retention_rules: vec![
+                RetentionRule {
+                    data_type: RetentionDataType::CompletedTasks,
+                    action: RetentionAction::Delete,
+                    retention_days: Some(30),
+                },
+                RetentionRule {
+                    data_type: RetentionDataType::Transactions,
+                    action: RetentionAction::Keep,
+                    retention_days: None,
+                },
+            ],
+            ..Default::default()
+        };
+        let policy = RetentionPolicy::from(&config);
+        assert_eq!(
+            policy.rules,
+            vec![
+                ResolvedRetentionRule {
+                    data_type: RetentionDataType::TimeEntries,
+                    action: RetentionAction::Archive,
+                    retention_days: 365,
+                },
+                ResolvedRetentionRule {
+                    data_type: RetentionDataType::CompletedTasks,
+                    action: RetentionAction::Delete,
+                    retention_days: 30,
+                },
+            ]
+        );
+
+        // 未设置保留天数时不清理
+        assert!(RetentionPolicy::from(&DataConfig::default())
+            .rules
+            .is_empty());
+
+        // 启动时直接清理需要单独开启
+        let auto_cleanup = DataConfig {
+            auto_cleanup: true,
+            ..config.clone()
+        };
+        assert!(!RetentionPolicy::from(&auto_cleanup).run_on_startup);
+        assert!(
+            RetentionPolicy::from(&DataConfig {
+                cleanup_on_startup: true,
+                ..auto_cleanup
+            })
+            .run_on_startup
+        );
+    }
+
+    #[test]
+    fn test_plan_then_archive_and_delete() {
+        let (temp_dir, storage) = temp_storage();
+
+        let recent = (Local::now() - chrono::Duration::days(1)).to_rfc3339();
+        insert_time_entry(&storage, "old-1", "2020-03-01T09:00:00+00:00", 600);
+        insert_time_entry(&storage, "old-2", "2020-03-01T14:00:00+00:00", 1200);
+        insert_time_entry(&storage, "recent", &recent, 300);
+        insert_task(&storage, "done-old", "2020-05-01T10:00:00+00:00");
+        insert_task(&storage, "done-recent", &recent);
+
+        let service = RetentionService::new(RetentionPolicy {
+            run_on_startup: true,
+            rules: vec![
+                ResolvedRetentionRule {
+                    data_type: RetentionDataType::TimeEntries,
+                    action: RetentionAction::Archive,
+                    retention_days: 365,
+                },
+                ResolvedRetentionRule {
+                    data_type: RetentionDataType::CompletedTasks,
+                    action: RetentionAction::Delete,
+                    retention_days: 365,
+                },
+            ],
+            archive_path: Some(temp_dir.path().join("archive.db")),
+        });
+
+        // 预览不修改数据
+        let plan = service.plan(storage.get_database()).unwrap();
+        assert_eq!(plan.total_records(), 3);
+        assert_eq!(plan.items[0].record_count, 2);
+        assert_eq!(plan.items[0].oldest, NaiveDate::from_ymd_opt(2020, 3, 1));
+        assert!(plan.summary().contains("时间记录：将归档 2 条"));
+        assert_eq!(count(&storage, "SELECT COUNT(*) FROM time_entries"), 3);
+
+        let report = service.execute(storage.get_database(), &plan).unwrap();
+        assert_eq!(report.items[0].archived, 2);
+        assert_eq!(report.items[0].removed, 2);
+        assert_eq!(report.items[1].archived, 0);
+        assert_eq!(report.items[1].removed, 1);
+        assert_eq!(count(&storage, "SELECT COUNT(*) FROM time_entries"), 1);
+        assert_eq!(count(&storage, "SELECT COUNT(*) FROM tasks"), 1);
+
+        // 汇总统计保留了被清理数据
+        assert_eq!(
+            count(
+                &storage,
+                "SELECT total_seconds FROM archived_time_stats WHERE date = '2020-03-01'"
+            ),
+            1800
+        );
+        assert_eq!(
+            count(&storage, "SELECT completed_count FROM archived_task_stats"),
+            1
+        );
+        let stats = storage
+            .get_daily_stats_range(
+                NaiveDate::from_ymd_opt(2020, 3, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2020, 3, 31).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].stats.total_seconds, 1800);
+        assert_eq!(stats[0].stats.task_count, 2);
+
+        // 归档数据库中保存了完整记录
+        let archive = Connection::open(temp_dir.path().join("archive.db")).unwrap();
+        let archived: i64 = archive
+            .query_row("SELECT COUNT(*) FROM time_entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(archived, 2);
+
+        // 再次执行时没有需要清理的数据
+        assert!(service.plan(storage.get_database()).unwrap().is_empty());
+        assert!(service.run_automatic(&storage).unwrap().is_none());
+    }
+}
@@ -182,6 +182,106 @@ impl From<TaskInsert> for TaskModel {
     }
 }
 
+// ==================== 与核心Task的转换 ====================
+
+/// 核心任务状态对应的数据库状态字符串
+fn status_name(task: &crate::core::Task) -> &'static str {
+    use crate::core::TaskStatus;
+    match task.status {
+        TaskStatus::Active if task.started_at.is_some() => "in_progress",
+        TaskStatus::Active => "pending",
+        TaskStatus::Paused => "paused",
+        TaskStatus::Completed => "completed",
+        TaskStatus::Cancelled => "cancelled",
+    }
+}
+
+/// 核心任务优先级对应的数据库优先级字符串
+fn priority_name(priority: crate::core::Priority) -> &'static str {
+    use crate::core::Priority;
+    match priority {
+        Priority::Low => "low",
+        Priority::Medium => "medium",
+        Priority::High => "high",
+        Priority::Urgent => "urgent",
+    }
+}
+
+/// 从核心Task转换为数据库TaskInsert
+impl From<&crate::core::Task> for TaskInsert {
+    fn from(task: &crate::core::Task) -> Self {
+        Self {
+            id: task.id,
+            name: task.name.clone(),
+            description: task.description.clone(),
+            category_id: task.category_id,
+            status: status_name(task).to_string(),
+            priority: priority_name(task.priority).to_string(),
+            estimated_duration_seconds: task.estimated_duration.map(|d| d.num_seconds()),
+            total_duration_seconds: task.total_duration.num_seconds(),
+            tags: serde_json::to_string(&task.tags).unwrap_or_else(|_| "[]".to_string()),
+            due_date: None,
+            is_completed: task.is_completed(),
+            completed_at: task.completed_at,
+            created_at: task.created_at,
+        }
+    }
+}
+
+/// 从核心Task转换为数据库TaskUpdate（截止日期由数据库单独维护，不覆盖）
+impl From<&crate::core::Task> for TaskUpdate {
+    fn from(task: &crate::core::Task) -> Self {
+        let insert = TaskInsert::from(task);
+        Self {
+            name: Some(insert.name),
+            description: Some(insert.description),
+            category_id: Some(insert.category_id),
+            status: Some(insert.status),
+            priority: Some(insert.priority),
+            estimated_duration_seconds: Some(insert.estimated_duration_seconds),
+            total_duration_seconds: Some(insert.total_duration_seconds),
+            tags: Some(insert.tags),
+            due_date: None,
+            is_completed: Some(insert.is_completed),
+            completed_at: Some(insert.completed_at),
+        }
+    }
+}
+
+/// 从数据库TaskModel转换为核心Task
+impl From<TaskModel> for crate::core::Task {
+    fn from(model: TaskModel) -> Self {
+        use crate::core::{Priority, TaskStatus};
+        let status = match model.status.as_str() {
+            "completed" => TaskStatus::Completed,
+            "cancelled" => TaskStatus::Cancelled,
+            "paused" => TaskStatus::Paused,
+            _ if model.is_completed => TaskStatus::Completed,
+            _ => TaskStatus::Active,
+        };
+        let priority = match model.priority.as_str() {
+            "low" => Priority::Low,
+            "high" => Priority::High,
+            "urgent" => Priority::Urgent,
+            _ => Priority::Medium,
+        };
+        Self {
+            id: model.id,
+            name: model.name,
+            description: model.description,
+            category_id: model.category_id,
+            started_at: (model.status == "in_progress").then_some(model.created_at),
+            status,
+            created_at: model.created_at,
+            completed_at: model.completed_at,
+            total_duration: chrono::Duration::seconds(model.total_duration_seconds),
+            tags: serde_json::from_str(&model.tags).unwrap_or_default(),
+            priority,
+            estimated_duration: model.estimated_duration_seconds.map(chrono::Duration::seconds),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,139 @@
+//! # 存储测试辅助
+//!
+//! 各模块测试共用的临时数据库和测试数据
+
+use super::task_models::TaskInsert;
+use super::{
+    AccountInsert, AccountType, BudgetInsert, BudgetPeriod, Database, DatabaseConfig,
+    StorageBackend, StorageManager, TransactionCategoryInsert, TransactionInsert,
+    TransactionStatus, TransactionType,
+};
+use chrono::{Local, NaiveDate};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+/// 在临时目录中创建并初始化存储管理器（目录在返回值释放时删除）
+pub(crate) fn temp_storage() -> (TempDir, StorageManager) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut storage = StorageManager::new(DatabaseConfig {
+        database_path: temp_dir
+            .path()
+            .join("test.db")
+            .to_string_lossy()
+            .to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    storage.initialize().unwrap();
+    (temp_dir, storage)
+}
+
+/// 在临时目录中创建并初始化数据库
+pub(crate) fn temp_database() -> (TempDir, Database) {
+    let (temp_dir, storage) = temp_storage();
+    (temp_dir, storage.into_database())
+}
+
+/// 未完成的普通任务
+pub(crate) fn new_task(name: &str) -> TaskInsert {
+    TaskInsert {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        description: None,
+        category_id: None,
+        status: "pending".to_string(),
+        priority: "medium".to_string(),
+        estimated_duration_seconds: None,
+        total_duration_seconds: 0,
+        tags: "[]".to_string(),
+        due_date: None,
+        is_completed: false,
+        completed_at: None,
+        created_at: Local::now(),
+    }
+}
+
+/// 写入一组记账数据：一个人民币账户、"餐饮"支出分类及每月 100 元的预算，
+/// 2024-02-10 工资收入 5000，2024-03-05 和 2024-03-20 两笔餐饮支出 30 和 50
+pub(crate) fn insert_sample_finance(storage: &dyn StorageBackend) {
+    let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+    let account = AccountInsert {
+        id: Uuid::new_v4(),
+        name: "现金".to_string(),
+        account_type: AccountType::Cash,
+        currency: "CNY".to_string(),
+        balance: 1000.0,
+        initial_balance: 1000.0,
+        description: None,
+        is_active: true,
+        is_default: true,
+        created_at: Local::now(),
+    };
+    storage.insert_account(&account).unwrap();
+
+    let category = TransactionCategoryInsert {
+        id: Uuid::new_v4(),
+        name: "餐饮".to_string(),
+        transaction_type: TransactionType::Expense,
+        description: None,
+        color: "#F44336".to_string(),
+        icon: None,
+        parent_id: None,
+        is_active: true,
+        created_at: Local::now(),
+    };
+    storage.insert_transaction_category(&category).unwrap();
+
+    storage
+        .insert_budget(&BudgetInsert {
+            id: Uuid::new_v4(),
+            name: "餐饮预算".to_string(),
+            category_id: category.id,
+            amount: 100.0,
+            currency: "CNY".to_string(),
+            period: BudgetPeriod::Monthly,
+            start_date: date(1, 1),
+            end_date: None,
+            spent_amount: 0.0,
+            remaining_amount: 100.0,
+            is_active: true,
+            created_at: Local::now(),
+        })
+        .unwrap();
+
+    for (transaction_type, amount, description, category_id, transaction_date) in [
+        (TransactionType::Income, 5000.0, "工资", None, date(2, 10)),
+        (
+            TransactionType::Expense,
+            30.0,
+            "午饭",
+            Some(category.id),
+            date(3, 5),
+        ),
+        (
+            TransactionType::Expense,
+            50.0,
+            "晚饭",
+            Some(category.id),
+            date(3, 20),
+        ),
+    ] {
+        storage
+            .insert_transaction(&TransactionInsert {
+                id: Uuid::new_v4(),
+                transaction_type,
+                amount,
+                currency: "CNY".to_string(),
+                description: description.to_string(),
+                account_id: account.id,
+                category_id,
+                to_account_id: None,
+                status: TransactionStatus::Completed,
+                transaction_date,
+                tags: vec![],
+                receipt_path: None,
+                created_at: Local::now(),
+            })
+            .unwrap();
+    }
+}
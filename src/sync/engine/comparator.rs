@@ -3,6 +3,8 @@
 //! 负责比较本地和远程数据，检测数据差异和变化
 
 use super::integrity_checker::{ConflictDetectionResult, DataIntegrityChecker, RiskLevel};
+use super::snapshot::{entity_content_hash, BaseSnapshotStore};
+use super::transfer::DataTransfer;
 use super::types::*;
 use crate::errors::{AppError, Result};
 use crate::storage::StorageManager;
@@ -14,12 +16,14 @@ use std::sync::Arc;
 pub struct DataComparator {
     storage: Arc<StorageManager>,
     integrity_checker: DataIntegrityChecker,
+    base_snapshot: BaseSnapshotStore,
 }
 
 impl DataComparator {
     /// 创建新的数据比较器
     pub fn new(storage: Arc<StorageManager>) -> Self {
         Self {
+            base_snapshot: BaseSnapshotStore::new(storage.clone()),
             storage,
             integrity_checker: DataIntegrityChecker::new(),
         }
@@ -139,6 +143,11 @@ impl DataComparator {
             return Ok(DataComparisonResult::LocalNewer);
         }
 
+        // 存在基线快照时，根据双方相对基线的变化决定同步方向
+        if let Some(result) = self.compare_with_base(local_data, &remote_data)? {
+            return Ok(result);
+        }
+
         // ========== 使用完整性检查器进行深度分析 ==========
         log::info!("开始使用完整性检查器进行深度冲突分析");
 
@@ -188,6 +197,39 @@ impl DataComparator {
         }
     }
 
+    /// 基于上次同步的基线快照比较，无基线时返回 None
+    fn compare_with_base(
+        &self,
+        local_data: &serde_json::Value,
+        remote_data: &serde_json::Value,
+    ) -> Result<Option<DataComparisonResult>> {
+        let base_hash = match self.base_snapshot.load_hash()? {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+
+        let local_hash = entity_content_hash(local_data);
+        let remote_hash = entity_content_hash(remote_data);
+        let local_changed = local_hash != base_hash;
+        let remote_changed = remote_hash != base_hash;
+
+        log::info!(
+            "基线比较 - 本地已变更: {}, 远程已变更: {}",
+            local_changed,
+            remote_changed
+        );
+
+        let result = match (local_changed, remote_changed) {
+            (false, false) => DataComparisonResult::Same,
+            (true, false) => DataComparisonResult::LocalNewer,
+            (false, true) => DataComparisonResult::RemoteNewer,
+            (true, true) if local_hash == remote_hash => DataComparisonResult::Same,
+            (true, true) => DataComparisonResult::NeedsMerge,
+        };
+
+        Ok(Some(result))
+    }
+
     /// 基于实际数据时间戳比较
     fn compare_by_timestamp_with_data(
         &self,
@@ -240,7 +282,9 @@ impl DataComparator {
         log::info!("下载远程数据进行比较: {}", remote_item.name);
 
         // 下载远程文件
-        let data = provider.download_file(remote_item).await?;
+        let data = DataTransfer::for_download(provider, &self.storage)
+            .download_dataset(remote_item)
+            .await?;
 
         // 解析JSON数据
         let json_data: serde_json::Value = serde_json::from_slice(&data)
@@ -2,6 +2,7 @@
 //!
 //! 负责处理数据同步时的冲突，提供多种冲突解决策略
 
+use super::transfer::DataTransfer;
 use super::types::*;
 use super::DataMerger;
 use crate::errors::{AppError, Result};
@@ -100,7 +101,9 @@ impl ConflictResolver {
         let local_json: serde_json::Value = serde_json::from_slice(&local_data)?;
 
         // 获取远程数据
-        let remote_data = provider.download_file(conflict).await?;
+        let remote_data = DataTransfer::for_download(provider, &self.storage)
+            .download_dataset(conflict)
+            .await?;
         let remote_json: serde_json::Value = serde_json::from_slice(&remote_data)?;
 
         // 创建数据合并器
@@ -154,7 +157,10 @@ impl ConflictResolver {
             let local_data = self.export_local_data().await?;
             let local_json: serde_json::Value = serde_json::from_slice(&local_data)?;
 
-            let remote_data = match provider.download_file(conflict).await {
+            let remote_data = match DataTransfer::for_download(provider, &self.storage)
+                .download_dataset(conflict)
+                .await
+            {
                 Ok(data) => match serde_json::from_slice::<serde_json::Value>(&data) {
                     Ok(json) => Some(json),
                     Err(_) => None,
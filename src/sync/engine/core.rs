@@ -3,7 +3,12 @@
 //! 实现数据同步的核心逻辑和管理
 
 use super::{
-    types::*, ConflictResolver, DataComparator, DataMerger, DataSerializer, DataValidator,
+    device::{DeviceIdentity, DeviceInfo, DeviceRegistry},
+    history::{SyncHistoryEntry, SyncHistoryStore},
+    session::{ConflictChoice, ConflictSession, FieldChoice},
+    transfer::{DataTransfer, TransferOptions, TransferStats},
+    types::*,
+    BaseSnapshotStore, ConflictResolver, DataComparator, DataMerger, DataSerializer, DataValidator,
 };
 use crate::errors::{AppError, Result};
 use crate::storage::StorageManager;
@@ -43,6 +48,18 @@ pub struct SyncEngine {
     serializer: DataSerializer,
     /// 数据验证器
     validator: DataValidator,
+    /// 同步基线快照
+    base_snapshot: BaseSnapshotStore,
+    /// 等待用户处理的冲突会话
+    pending_session: Arc<Mutex<Option<ConflictSession>>>,
+    /// 同步历史
+    history: SyncHistoryStore,
+    /// 本次同步中已解决的冲突
+    resolutions: Arc<Mutex<Vec<ResolvedConflictRecord>>>,
+    /// 设备注册表
+    devices: DeviceRegistry,
+    /// 本次同步读取的远程数据来源设备
+    remote_device: Arc<Mutex<Option<String>>>,
 }
 
 impl SyncEngine {
@@ -63,6 +80,12 @@ impl SyncEngine {
             merger: DataMerger::new(storage.clone()),
             serializer: DataSerializer::new(storage.clone()),
             validator: DataValidator::new(),
+            base_snapshot: BaseSnapshotStore::new(storage.clone()),
+            pending_session: Arc::new(Mutex::new(None)),
+            history: SyncHistoryStore::new(storage.clone()),
+            resolutions: Arc::new(Mutex::new(Vec::new())),
+            devices: DeviceRegistry::new(storage.clone()),
+            remote_device: Arc::new(Mutex::new(None)),
         })
     }
 
@@ -129,6 +152,10 @@ impl SyncEngine {
         self.emit_event(SyncEvent::Started);
 
         let mut result = SyncResult::new();
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let snapshot_id = self.create_restore_point(&run_id).await;
+        self.resolutions.lock().unwrap().clear();
+        *self.remote_device.lock().unwrap() = None;
 
         // 执行同步
         match self.perform_sync().await {
@@ -136,11 +163,14 @@ impl SyncEngine {
                 result = sync_result;
                 {
                     let mut status = self.status.lock().unwrap();
-                    *status = if result.success {
-                        SyncStatus::Success
-                    } else {
-                        SyncStatus::Failed("同步过程中出现错误".to_string())
-                    };
+                    // 等待手动解决冲突时保持冲突状态
+                    if !matches!(*status, SyncStatus::ConflictPending) {
+                        *status = if result.success {
+                            SyncStatus::Success
+                        } else {
+                            SyncStatus::Failed("同步过程中出现错误".to_string())
+                        };
+                    }
                 }
             }
             Err(e) => {
@@ -159,6 +189,12 @@ impl SyncEngine {
             let mut last_result = self.last_result.lock().unwrap();
             *last_result = Some(result.clone());
         }
+        self.record_history(run_id, &result, snapshot_id);
+
+        if matches!(self.get_status(), SyncStatus::ConflictPending) {
+            log::info!("同步暂停，等待手动解决冲突");
+            return Ok(result);
+        }
 
         // 发送完成事件
         if result.success {
@@ -190,6 +226,11 @@ impl SyncEngine {
         // 确保远程目录存在
         provider.create_remote_directory(&remote_directory).await?;
 
+        // 登记当前设备，已撤销的设备不允许同步
+        self.devices
+            .check_in(provider.as_ref(), &remote_directory)
+            .await?;
+
         // 获取远程文件列表
         let remote_files = provider.list_remote_files(&remote_directory).await?;
 
@@ -204,9 +245,63 @@ impl SyncEngine {
             )
             .await?;
 
+        let mut final_upload_items = upload_items;
+        let mut upload_data = local_data.clone();
+
+        // 存在基线快照时优先进行三方合并，只把真正的冲突交给冲突策略
+        let three_way = if conflicts.is_empty() {
+            None
+        } else {
+            self.try_three_way_merge(&local_data, &remote_files, provider.as_ref())
+                .await?
+        };
+
         // 处理冲突
         let mut resolved_conflicts = Vec::new();
-        if !conflicts.is_empty() {
+        if let Some(mut merge) = three_way.filter(|m| {
+            !m.has_conflicts()
+                || !matches!(self.config.conflict_strategy, ConflictStrategy::KeepBoth)
+        }) {
+            match self.config.conflict_strategy {
+                ConflictStrategy::Manual if merge.has_conflicts() => {
+                    for conflict in &conflicts {
+                        result.add_conflict(conflict.clone());
+                        self.emit_event(SyncEvent::ConflictDetected {
+                            file: conflict.name.clone(),
+                        });
+                    }
+                    log::info!(
+                        "三方合并后仍有 {} 条记录冲突，等待手动解决",
+                        merge.conflicts.len()
+                    );
+                    {
+                        let mut pending = self.pending_session.lock().unwrap();
+                        *pending = Some(ConflictSession::new(merge, conflicts.clone()));
+                    }
+                    {
+                        let mut status = self.status.lock().unwrap();
+                        *status = SyncStatus::ConflictPending;
+                    }
+                    return Ok(result);
+                }
+                ConflictStrategy::LocalWins => self.resolve_merge_conflicts(&mut merge, true),
+                ConflictStrategy::RemoteWins => self.resolve_merge_conflicts(&mut merge, false),
+                _ => {}
+            }
+
+            // 导入合并结果并上传
+            let merged_data = serde_json::to_vec(&merge.merged)?;
+            self.serializer.import_data(&merged_data).await?;
+            upload_data = merged_data;
+
+            for conflict in &conflicts {
+                let mut item = conflict.clone();
+                item.size = upload_data.len() as u64;
+                item.direction = SyncDirection::Upload;
+                final_upload_items.push(item);
+            }
+            log::info!("三方合并结果已应用到本地，准备上传");
+        } else if !conflicts.is_empty() {
             log::info!("发现 {} 个冲突项", conflicts.len());
 
             for conflict in &conflicts {
@@ -235,13 +330,24 @@ impl SyncEngine {
                         )
                         .await?;
                     log::info!("自动解决了 {} 个冲突", resolved_conflicts.len());
+
+                    let resolution = strategy_key(&self.config.conflict_strategy);
+                    let mut resolutions = self.resolutions.lock().unwrap();
+                    for item in &resolved_conflicts {
+                        resolutions.push(ResolvedConflictRecord {
+                            collection: "file".to_string(),
+                            record_id: item.name.clone(),
+                            resolution: resolution.to_string(),
+                            device_id: None,
+                        });
+                    }
                 }
             }
         }
 
         // 合并解决的冲突项到上传/下载队列
-        let mut final_upload_items = upload_items;
         let mut final_download_items = download_items;
+        let mut synced_data: Option<serde_json::Value> = None;
 
         for resolved_item in resolved_conflicts {
             match resolved_item.direction {
@@ -265,10 +371,11 @@ impl SyncEngine {
                 file: item.name.clone(),
             });
 
-            match self.upload_item(item, &local_data).await {
-                Ok(_) => {
+            match self.upload_item(item, &upload_data).await {
+                Ok(stats) => {
+                    synced_data = Some(serde_json::from_slice(&upload_data)?);
                     result.uploaded_count += 1;
-                    result.total_bytes += item.size;
+                    result.total_bytes += stats.transferred_bytes;
                     self.emit_event(SyncEvent::UploadCompleted {
                         file: item.name.clone(),
                     });
@@ -296,7 +403,8 @@ impl SyncEngine {
             });
 
             match self.download_item(item).await {
-                Ok(_) => {
+                Ok(remote_data) => {
+                    synced_data = Some(remote_data);
                     result.downloaded_count += 1;
                     result.total_bytes += item.size;
                     self.emit_event(SyncEvent::DownloadCompleted {
@@ -330,6 +438,11 @@ impl SyncEngine {
 
         result.complete(result.failed_count == 0 && result.errors.is_empty());
 
+        if result.success {
+            self.update_base_snapshot(synced_data, &local_data);
+            self.mark_device_synced().await;
+        }
+
         log::info!(
             "同步操作完成 - 成功: {}, 上传: {}, 下载: {}, 失败: {}",
             result.success,
@@ -341,6 +454,186 @@ impl SyncEngine {
         Ok(result)
     }
 
+    /// 下载远程数据并与基线快照进行三方合并，无远程数据时返回 None
+    ///
+    /// 没有基线快照时，仅手动策略会以空基线进行合并，以便逐条列出冲突记录
+    async fn try_three_way_merge(
+        &self,
+        local_data: &[u8],
+        remote_files: &[SyncItem],
+        provider: &dyn crate::sync::SyncProvider,
+    ) -> Result<Option<ThreeWayMergeResult>> {
+        let base_data = match self.base_snapshot.load()? {
+            Some(data) => data,
+            None if matches!(self.config.conflict_strategy, ConflictStrategy::Manual) => {
+                log::info!("没有同步基线快照，以空基线比较本地与远程记录");
+                serde_json::Value::Null
+            }
+            None => {
+                log::info!("没有同步基线快照，无法进行三方合并");
+                return Ok(None);
+            }
+        };
+        let remote_item = match remote_files.iter().find(|item| item.name == "data.json") {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+
+        let remote_bytes = DataTransfer::for_download(provider, &self.storage)
+            .download_dataset(remote_item)
+            .await?;
+        let remote_data: serde_json::Value = serde_json::from_slice(&remote_bytes)
+            .map_err(|e| AppError::Sync(format!("解析远程数据失败: {}", e)))?;
+        self.note_remote_device(&remote_data);
+        let local_json: serde_json::Value = serde_json::from_slice(local_data)?;
+
+        Ok(Some(self.merger.three_way_merge(
+            &base_data,
+            &local_json,
+            &remote_data,
+        )))
+    }
+
+    /// 按整体策略解决三方合并中剩余的冲突
+    fn resolve_merge_conflicts(&self, merge: &mut ThreeWayMergeResult, prefer_local: bool) {
+        if merge.has_conflicts() {
+            log::info!(
+                "按{}策略解决 {} 条记录冲突",
+                if prefer_local {
+                    "本地优先"
+                } else {
+                    "远程优先"
+                },
+                merge.conflicts.len()
+            );
+
+            let resolution = if prefer_local {
+                "local_wins"
+            } else {
+                "remote_wins"
+            };
+            self.resolutions
+                .lock()
+                .unwrap()
+                .extend(merge.conflicts.iter().map(|c| ResolvedConflictRecord {
+                    collection: c.collection.clone(),
+                    record_id: c.record_id.clone(),
+                    resolution: resolution.to_string(),
+                    device_id: None,
+                }));
+
+            super::ThreeWayMerger::new().resolve_remaining(merge, prefer_local);
+        }
+    }
+
+    /// 同步前保存本地数据还原点，失败时只记录警告
+    async fn create_restore_point(&self, run_id: &str) -> Option<String> {
+        if self.config.restore_points == 0 {
+            return None;
+        }
+
+        let data = match self.serializer.create_backup().await {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("创建同步还原点失败: {}", e);
+                return None;
+            }
+        };
+        if let Err(e) = self.history.save_restore_point(run_id, &data) {
+            log::warn!("保存同步还原点失败: {}", e);
+            return None;
+        }
+        if let Err(e) = self
+            .history
+            .prune_restore_points(self.config.restore_points)
+        {
+            log::warn!("清理同步还原点失败: {}", e);
+        }
+
+        Some(run_id.to_string())
+    }
+
+    /// 写入同步历史，失败时只记录警告
+    fn record_history(&self, run_id: String, result: &SyncResult, snapshot_id: Option<String>) {
+        let provider = self
+            .provider
+            .as_ref()
+            .map(|p| p.name().to_string())
+            .unwrap_or_else(|| self.config.provider.clone());
+        let device_id = self.devices.identity().ok().map(|d| d.id);
+        let remote_device_id = self.remote_device.lock().unwrap().clone();
+
+        // 冲突记录归属到保留版本的来源设备
+        let mut conflicts = std::mem::take(&mut *self.resolutions.lock().unwrap());
+        for conflict in &mut conflicts {
+            if conflict.device_id.is_none() {
+                conflict.device_id = match conflict.resolution.as_str() {
+                    "keep_remote" | "remote_wins" => remote_device_id.clone(),
+                    _ => device_id.clone(),
+                };
+            }
+        }
+
+        let mut entry =
+            SyncHistoryEntry::from_result(run_id, provider, result, conflicts, snapshot_id);
+        entry.device_id = device_id;
+        entry.remote_device_id = remote_device_id;
+        if let Err(e) = self.history.record(&entry) {
+            log::warn!("记录同步历史失败: {}", e);
+        }
+    }
+
+    /// 记录远程数据的来源设备
+    fn note_remote_device(&self, remote_data: &serde_json::Value) {
+        if let Some(device_id) = remote_data.get("device_id").and_then(|v| v.as_str()) {
+            *self.remote_device.lock().unwrap() = Some(device_id.to_string());
+        }
+    }
+
+    /// 同步成功后在设备清单中记录当前设备同步的数据版本，失败时只记录警告
+    async fn mark_device_synced(&self) {
+        let Some(provider) = self.provider.as_ref() else {
+            return;
+        };
+        let revision = match self.base_snapshot.load_hash() {
+            Ok(Some(hash)) => hash,
+            _ => return,
+        };
+        if let Err(e) = self
+            .devices
+            .mark_synced(provider.as_ref(), &self.get_remote_directory(), &revision)
+            .await
+        {
+            log::warn!("更新设备清单失败: {}", e);
+        }
+    }
+
+    /// 同步成功后更新基线快照
+    ///
+    /// 有数据传输时以传输的数据为基线；数据已一致时仅在缺少基线时补建
+    fn update_base_snapshot(&self, synced_data: Option<serde_json::Value>, local_data: &[u8]) {
+        let data = match synced_data {
+            Some(data) => data,
+            None => match self.base_snapshot.load_hash() {
+                Ok(Some(_)) => return,
+                _ => match serde_json::from_slice(local_data) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        log::warn!("解析本地数据失败，跳过基线快照更新: {}", e);
+                        return;
+                    }
+                },
+            },
+        };
+
+        if let Err(e) = self.base_snapshot.save(&data) {
+            log::warn!("保存同步基线快照失败: {}", e);
+        }
+
+        let mut pending = self.pending_session.lock().unwrap();
+        *pending = None;
+    }
+
     /// 验证同步结果
     async fn verify_sync_result(&self, result: &SyncResult) -> Result<()> {
         log::info!("验证同步结果");
@@ -430,9 +723,9 @@ impl SyncEngine {
             let mut result = SyncResult::new();
 
             match self.upload_item(&incremental_item, &incremental_data).await {
-                Ok(_) => {
+                Ok(stats) => {
                     result.uploaded_count = 1;
-                    result.total_bytes = incremental_data.len() as u64;
+                    result.total_bytes = stats.transferred_bytes;
                     result.complete(true);
                 }
                 Err(e) => {
@@ -448,45 +741,28 @@ impl SyncEngine {
         }
     }
 
-    /// 上传数据项
-    async fn upload_item(&self, item: &SyncItem, data: &[u8]) -> Result<()> {
+    /// 上传数据项（按配置压缩、分块并检查大小限制）
+    async fn upload_item(&self, item: &SyncItem, data: &[u8]) -> Result<TransferStats> {
         let provider = self.provider.as_ref().unwrap();
+        let options = TransferOptions::from_config(&self.config, &self.storage);
 
-        // 检查文件大小限制
-        if item.size > (self.config.max_file_size as u64 * 1024 * 1024) {
-            return Err(AppError::Sync(format!(
-                "文件 {} 超过大小限制 {}MB",
-                item.name, self.config.max_file_size
-            )));
-        }
-
-        // 如果启用压缩，压缩数据
-        let upload_data = if self.config.compression {
-            // 这里可以添加压缩逻辑
-            data.to_vec()
-        } else {
-            data.to_vec()
-        };
-
-        provider.upload_file(item, &upload_data).await
+        DataTransfer::new(provider.as_ref(), options)
+            .upload_dataset(data, item)
+            .await
     }
 
-    /// 下载数据项
-    async fn download_item(&self, item: &SyncItem) -> Result<()> {
+    /// 下载数据项，返回下载的远程数据
+    async fn download_item(&self, item: &SyncItem) -> Result<serde_json::Value> {
         let provider = self.provider.as_ref().unwrap();
 
-        let data = provider.download_file(item).await?;
-
-        // 如果启用压缩，解压数据
-        let decompressed_data = if self.config.compression {
-            // 这里可以添加解压逻辑
-            data
-        } else {
-            data
-        };
+        // 解压并重组分块数据（兼容旧版未压缩数据）
+        let decompressed_data = DataTransfer::for_download(provider.as_ref(), &self.storage)
+            .download_dataset(item)
+            .await?;
 
         // 解析数据以获取远程哈希
         let remote_data: serde_json::Value = serde_json::from_slice(&decompressed_data)?;
+        self.note_remote_device(&remote_data);
         let remote_content_hash = {
             let content = self
                 .serializer
@@ -502,7 +778,7 @@ impl SyncEngine {
             .update_origin_tracking(&remote_data, Some(&remote_content_hash))
             .await?;
 
-        Ok(())
+        Ok(remote_data)
     }
 
     /// 计算数据哈希
@@ -580,6 +856,25 @@ impl SyncEngine {
         Ok(())
     }
 
+    /// 创建供调度器调用的同步任务
+    ///
+    /// 同步失败（包括提供者返回的错误）时任务返回错误，由调度器负责退避重试；
+    /// 等待手动解决冲突不视为失败
+    pub fn scheduler_task(self: &Arc<Self>) -> crate::sync::scheduler::SyncTask {
+        let engine = self.clone();
+        Arc::new(move || {
+            let engine = engine.clone();
+            Box::pin(async move {
+                let result = engine.sync().await?;
+                if result.success || matches!(engine.get_status(), SyncStatus::ConflictPending) {
+                    Ok(())
+                } else {
+                    Err(AppError::Sync(result.errors.join(", ")))
+                }
+            })
+        })
+    }
+
     /// 获取冲突详情
     pub async fn get_conflict_details(
         &self,
@@ -591,14 +886,258 @@ impl SyncEngine {
             .await
     }
 
+    /// 获取当前的冲突解决会话
+    pub fn get_conflict_session(&self) -> Option<ConflictSession> {
+        let pending = self.pending_session.lock().unwrap();
+        pending.clone()
+    }
+
+    /// 对冲突记录做出整条记录的决定
+    pub fn decide_conflict_record(
+        &self,
+        collection: &str,
+        record_id: &str,
+        choice: ConflictChoice,
+    ) -> Result<()> {
+        let mut pending = self.pending_session.lock().unwrap();
+        let session = pending
+            .as_mut()
+            .ok_or_else(|| AppError::Sync("没有待处理的冲突".to_string()))?;
+        session.decide_record(collection, record_id, choice)
+    }
+
+    /// 对冲突记录中的单个字段做出决定
+    pub fn decide_conflict_field(
+        &self,
+        collection: &str,
+        record_id: &str,
+        field: &str,
+        choice: FieldChoice,
+    ) -> Result<()> {
+        let mut pending = self.pending_session.lock().unwrap();
+        let session = pending
+            .as_mut()
+            .ok_or_else(|| AppError::Sync("没有待处理的冲突".to_string()))?;
+        session.decide_field(collection, record_id, field, choice)
+    }
+
+    /// 放弃当前冲突会话，下次同步时重新检测
+    pub fn discard_conflict_session(&self) {
+        {
+            let mut pending = self.pending_session.lock().unwrap();
+            *pending = None;
+        }
+        let mut status = self.status.lock().unwrap();
+        *status = SyncStatus::Idle;
+    }
+
+    /// 应用冲突会话中的所有决定并继续同步
+    ///
+    /// 决定结果在一个事务中导入本地数据库，成功后上传到远程
+    pub async fn apply_conflict_session(&self) -> Result<SyncResult> {
+        let session = self
+            .get_conflict_session()
+            .ok_or_else(|| AppError::Sync("没有待处理的冲突".to_string()))?;
+        let resolved_data = session.build_resolved_data()?;
+        let data = serde_json::to_vec(&resolved_data)?;
+
+        log::info!(
+            "应用冲突会话 {}，共 {} 条冲突记录",
+            session.id,
+            session.conflicts.len()
+        );
+
+        {
+            let mut status = self.status.lock().unwrap();
+            *status = SyncStatus::Syncing;
+        }
+        self.emit_event(SyncEvent::Started);
+
+        let mut result = SyncResult::new();
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let snapshot_id = self.create_restore_point(&run_id).await;
+        *self.resolutions.lock().unwrap() = session.resolution_log();
+
+        if let Err(e) = self.serializer.import_data(&data).await {
+            log::error!("应用冲突决定失败: {}", e);
+            result.add_error(format!("应用冲突决定失败: {}", e));
+            result.complete(false);
+            self.record_history(run_id, &result, snapshot_id);
+            let mut status = self.status.lock().unwrap();
+            *status = SyncStatus::ConflictPending;
+            return Err(e);
+        }
+
+        for item in &session.sync_items {
+            let mut item = item.clone();
+            item.size = data.len() as u64;
+            item.direction = SyncDirection::Upload;
+
+            self.emit_event(SyncEvent::UploadStarted {
+                file: item.name.clone(),
+            });
+            match self.upload_item(&item, &data).await {
+                Ok(stats) => {
+                    result.uploaded_count += 1;
+                    result.total_bytes += stats.transferred_bytes;
+                    self.emit_event(SyncEvent::UploadCompleted {
+                        file: item.name.clone(),
+                    });
+                }
+                Err(e) => {
+                    log::error!("上传文件失败 {}: {}", item.name, e);
+                    result.failed_count += 1;
+                    result.add_error(format!("上传 {} 失败: {}", item.name, e));
+                }
+            }
+        }
+
+        result.complete(result.failed_count == 0 && result.errors.is_empty());
+
+        {
+            let mut status = self.status.lock().unwrap();
+            *status = if result.success {
+                SyncStatus::Success
+            } else {
+                SyncStatus::Failed("同步过程中出现错误".to_string())
+            };
+        }
+        if result.success {
+            // 本地已应用决定，会话随基线更新一起清除
+            self.update_base_snapshot(Some(resolved_data), &data);
+            self.mark_device_synced().await;
+        }
+
+        {
+            let mut last_result = self.last_result.lock().unwrap();
+            *last_result = Some(result.clone());
+        }
+        self.record_history(run_id, &result, snapshot_id);
+
+        if result.success {
+            self.emit_event(SyncEvent::Completed {
+                result: result.clone(),
+            });
+        } else {
+            self.emit_event(SyncEvent::Failed {
+                error: result.errors.join(", "),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// 获取当前设备标识
+    pub fn get_device_identity(&self) -> Result<DeviceIdentity> {
+        self.devices.identity()
+    }
+
+    /// 修改当前设备名称
+    pub fn rename_device(&self, name: &str) -> Result<DeviceIdentity> {
+        self.devices.rename(name)
+    }
+
+    /// 获取远程设备清单中的所有设备
+    pub async fn list_devices(&self) -> Result<Vec<DeviceInfo>> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| AppError::Sync("同步提供者未初始化".to_string()))?;
+        let manifest = self
+            .devices
+            .load_manifest(provider.as_ref(), &self.get_remote_directory())
+            .await?;
+        Ok(manifest.devices)
+    }
+
+    /// 撤销或恢复设备的同步权限，被撤销的设备下次同步时会被拒绝
+    pub async fn set_device_revoked(&self, device_id: &str, revoked: bool) -> Result<()> {
+        let provider = self
+            .provider
+            .as_ref()
+            .ok_or_else(|| AppError::Sync("同步提供者未初始化".to_string()))?;
+        self.devices
+            .set_revoked(
+                provider.as_ref(),
+                &self.get_remote_directory(),
+                device_id,
+                revoked,
+            )
+            .await
+    }
+
+    /// 获取最近的同步历史
+    pub fn get_sync_history(&self, limit: usize) -> Result<Vec<SyncHistoryEntry>> {
+        self.history.list(limit)
+    }
+
+    /// 回滚到指定同步开始前的本地数据
+    pub async fn rollback_sync(&self, history_id: &str) -> Result<()> {
+        if matches!(self.get_status(), SyncStatus::Syncing) {
+            return Err(AppError::Sync("同步进行中，无法回滚".to_string()));
+        }
+
+        let entry = self
+            .history
+            .get(history_id)?
+            .ok_or_else(|| AppError::NotFound(format!("同步记录不存在: {}", history_id)))?;
+        let data = match &entry.snapshot_id {
+            Some(snapshot_id) => self.history.load_restore_point(snapshot_id)?,
+            None => None,
+        }
+        .ok_or_else(|| AppError::NotFound(format!("同步记录 {} 的还原点已被清理", history_id)))?;
+
+        log::info!(
+            "回滚到同步 {} 开始前的数据（{}）",
+            history_id,
+            entry.started_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        self.serializer.restore_from_backup(&data).await?;
+
+        log::info!("同步回滚完成");
+        Ok(())
+    }
+
     /// 手动解决冲突
+    ///
+    /// 记录标识为 "集合/记录ID" 或记录ID；所有冲突都有决定后立即应用并返回已上传的同步项
     pub async fn resolve_conflicts_manually(
         &self,
-        _resolutions: &[(String, super::types::ConflictResolution)],
+        resolutions: &[(String, super::types::ConflictResolution)],
     ) -> Result<Vec<SyncItem>> {
-        // 这里可以实现手动冲突解决逻辑
-        // 目前返回空列表
-        Ok(Vec::new())
+        let session = self
+            .get_conflict_session()
+            .ok_or_else(|| AppError::Sync("没有待处理的冲突".to_string()))?;
+
+        for (record, resolution) in resolutions {
+            let choice = match resolution {
+                ConflictResolution::UseLocal => ConflictChoice::KeepLocal,
+                ConflictResolution::UseRemote => ConflictChoice::KeepRemote,
+                ConflictResolution::Merge => ConflictChoice::KeepBoth,
+                ConflictResolution::Skip => continue,
+            };
+            let conflict = match record.split_once('/') {
+                Some((collection, record_id)) => session.get_conflict(collection, record_id),
+                None => session.conflicts.iter().find(|c| &c.record_id == record),
+            }
+            .ok_or_else(|| AppError::NotFound(format!("冲突记录不存在: {}", record)))?;
+
+            self.decide_conflict_record(&conflict.collection, &conflict.record_id, choice)?;
+        }
+
+        let ready = self
+            .get_conflict_session()
+            .is_some_and(|session| session.is_complete());
+        if !ready {
+            return Ok(Vec::new());
+        }
+
+        let result = self.apply_conflict_session().await?;
+        if result.success {
+            Ok(session.sync_items)
+        } else {
+            Err(AppError::Sync(result.errors.join(", ")))
+        }
     }
 
     /// 执行智能合并
@@ -647,6 +1186,16 @@ impl SyncEngine {
     }
 }
 
+/// 冲突策略在同步历史中的标识
+fn strategy_key(strategy: &ConflictStrategy) -> &'static str {
+    match strategy {
+        ConflictStrategy::Manual => "manual",
+        ConflictStrategy::LocalWins => "local_wins",
+        ConflictStrategy::RemoteWins => "remote_wins",
+        ConflictStrategy::KeepBoth => "keep_both",
+    }
+}
+
 /// 同步统计信息
 #[derive(Debug, Clone)]
 pub struct SyncStatistics {
@@ -689,6 +1238,14 @@ impl SyncEventListener for DefaultSyncEventListener {
                 )
             }
             SyncEvent::Failed { error } => log::error!("同步失败: {}", error),
+            SyncEvent::AutoSyncPaused {
+                consecutive_failures,
+                error,
+            } => log::warn!(
+                "连续 {} 次同步失败，自动同步已暂停: {}",
+                consecutive_failures,
+                error
+            ),
         }
     }
 }
@@ -0,0 +1,368 @@
+//! # 设备注册模块
+//!
+//! 为每个安装生成稳定的设备ID和名称，并在远程维护设备清单（devices.json），
+//! 记录各设备的最后在线时间和最后同步的数据版本。被撤销的设备再次同步时会被拒绝
+
+use crate::errors::{AppError, Result};
+use crate::storage::backend::SettingsStore;
+use crate::storage::StorageManager;
+use crate::sync::{SyncDirection, SyncItem, SyncProvider, SyncStatus};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 设备ID在设置表中的键
+const DEVICE_ID_KEY: &str = "sync_device_id";
+
+/// 设备名称在设置表中的键
+const DEVICE_NAME_KEY: &str = "sync_device_name";
+
+/// 远程设备清单文件名
+const DEVICE_MANIFEST_FILE: &str = "devices.json";
+
+/// 当前设备标识
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    /// 设备ID
+    pub id: String,
+    /// 设备名称
+    pub name: String,
+}
+
+impl DeviceIdentity {
+    /// 读取当前设备标识，首次调用时生成并保存
+    pub fn load_or_create(storage: &dyn SettingsStore) -> Result<Self> {
+        let id = match storage.get_setting(DEVICE_ID_KEY)? {
+            Some(id) if !id.is_empty() => id,
+            _ => {
+                let id = uuid::Uuid::new_v4().to_string();
+                storage.set_setting(DEVICE_ID_KEY, &id)?;
+                log::info!("已生成设备ID: {}", id);
+                id
+            }
+        };
+
+        let name = match storage.get_setting(DEVICE_NAME_KEY)? {
+            Some(name) if !name.is_empty() => name,
+            _ => {
+                let name = default_device_name(&id);
+                storage.set_setting(DEVICE_NAME_KEY, &name)?;
+                name
+            }
+        };
+
+        Ok(Self { id, name })
+    }
+}
+
+/// 默认设备名称（主机名，获取失败时使用设备ID前缀）
+fn default_device_name(id: &str) -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| format!("LifeTracker-{}", &id[..8.min(id.len())]))
+}
+
+/// 设备清单中的设备信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// 设备ID
+    pub id: String,
+    /// 设备名称
+    pub name: String,
+    /// 首次同步时间
+    pub first_seen: DateTime<Local>,
+    /// 最后在线时间
+    pub last_seen: DateTime<Local>,
+    /// 最后同步的数据版本（同步后数据的内容哈希）
+    pub last_synced_revision: Option<String>,
+    /// 最后同步成功时间
+    pub last_synced_at: Option<DateTime<Local>>,
+    /// 是否已撤销
+    #[serde(default)]
+    pub revoked: bool,
+    /// 撤销时间
+    pub revoked_at: Option<DateTime<Local>>,
+}
+
+impl DeviceInfo {
+    /// 根据设备标识创建设备信息
+    fn new(identity: &DeviceIdentity) -> Self {
+        let now = Local::now();
+        Self {
+            id: identity.id.clone(),
+            name: identity.name.clone(),
+            first_seen: now,
+            last_seen: now,
+            last_synced_revision: None,
+            last_synced_at: None,
+            revoked: false,
+            revoked_at: None,
+        }
+    }
+}
+
+/// 远程设备清单
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceManifest {
+    /// 已注册的设备
+    pub devices: Vec<DeviceInfo>,
+}
+
+impl DeviceManifest {
+    /// 根据ID查找设备
+    pub fn get(&self, device_id: &str) -> Option<&DeviceInfo> {
+        self.devices.iter().find(|d| d.id == device_id)
+    }
+
+    /// 根据ID查找设备（可变）
+    fn get_mut(&mut self, device_id: &str) -> Option<&mut DeviceInfo> {
+        self.devices.iter_mut().find(|d| d.id == device_id)
+    }
+
+    /// 登记设备在线，设备已被撤销时返回错误
+    pub fn check_in(&mut self, identity: &DeviceIdentity) -> Result<()> {
+        match self.get_mut(&identity.id) {
+            Some(device) if device.revoked => Err(AppError::Sync(format!(
+                "设备 {} 已被撤销同步权限，请在其他设备上恢复后再同步",
+                device.name
+            ))),
+            Some(device) => {
+                device.name = identity.name.clone();
+                device.last_seen = Local::now();
+                Ok(())
+            }
+            None => {
+                self.devices.push(DeviceInfo::new(identity));
+                Ok(())
+            }
+        }
+    }
+
+    /// 记录设备同步成功的数据版本
+    pub fn mark_synced(&mut self, identity: &DeviceIdentity, revision: &str) {
+        if self.get(&identity.id).is_none() {
+            self.devices.push(DeviceInfo::new(identity));
+        }
+        if let Some(device) = self.get_mut(&identity.id) {
+            let now = Local::now();
+            device.last_seen = now;
+            device.last_synced_at = Some(now);
+            device.last_synced_revision = Some(revision.to_string());
+        }
+    }
+
+    /// 设置设备撤销状态
+    pub fn set_revoked(&mut self, device_id: &str, revoked: bool) -> Result<()> {
+        let device = self
+            .get_mut(device_id)
+            .ok_or_else(|| AppError::NotFound(format!("设备不存在: {}", device_id)))?;
+        device.revoked = revoked;
+        device.revoked_at = revoked.then(Local::now);
+        Ok(())
+    }
+}
+
+/// 设备注册表
+pub struct DeviceRegistry {
+    storage: Arc<StorageManager>,
+}
+
+impl DeviceRegistry {
+    /// 创建新的设备注册表
+    pub fn new(storage: Arc<StorageManager>) -> Self {
+        Self { storage }
+    }
+
+    /// 获取当前设备标识
+    pub fn identity(&self) -> Result<DeviceIdentity> {
+        DeviceIdentity::load_or_create(self.storage.as_ref())
+    }
+
+    /// 修改当前设备名称
+    pub fn rename(&self, name: &str) -> Result<DeviceIdentity> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("设备名称不能为空".to_string()));
+        }
+        self.storage
+            .get_database()
+            .set_setting(DEVICE_NAME_KEY, name)?;
+        self.identity()
+    }
+
+    /// 读取远程设备清单，不存在时返回空清单
+    pub async fn load_manifest(
+        &self,
+        provider: &dyn SyncProvider,
+        remote_directory: &str,
+    ) -> Result<DeviceManifest> {
+        let item = manifest_item(remote_directory, SyncDirection::Download);
+        match provider.download_file(&item).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|e| AppError::Sync(format!("解析设备清单失败: {}", e))),
+            Err(AppError::NotFound(_)) => Ok(DeviceManifest::default()),
+            Err(e) => {
+                // 部分提供者对不存在的文件返回通用错误，先确认文件是否存在
+                let exists = provider
+                    .list_remote_files(remote_directory)
+                    .await
+                    .map(|files| files.iter().any(|f| f.name == DEVICE_MANIFEST_FILE))
+                    .unwrap_or(true);
+                if exists {
+                    Err(e)
+                } else {
+                    Ok(DeviceManifest::default())
+                }
+            }
+        }
+    }
+
+    /// 保存远程设备清单
+    async fn save_manifest(
+        &self,
+        provider: &dyn SyncProvider,
+        remote_directory: &str,
+        manifest: &DeviceManifest,
+    ) -> Result<()> {
+        let data = serde_json::to_vec_pretty(manifest)?;
+        let mut item = manifest_item(remote_directory, SyncDirection::Upload);
+        item.size = data.len() as u64;
+        provider.upload_file(&item, &data).await
+    }
+
+    /// 同步开始时登记当前设备，设备已被撤销时拒绝同步
+    pub async fn check_in(
+        &self,
+        provider: &dyn SyncProvider,
+        remote_directory: &str,
+    ) -> Result<DeviceIdentity> {
+        let identity = self.identity()?;
+        let mut manifest = self.load_manifest(provider, remote_directory).await?;
+        manifest.check_in(&identity)?;
+        self.save_manifest(provider, remote_directory, &manifest)
+            .await?;
+        Ok(identity)
+    }
+
+    /// 同步成功后记录当前设备同步的数据版本
+    pub async fn mark_synced(
+        &self,
+        provider: &dyn SyncProvider,
+        remote_directory: &str,
+        revision: &str,
+    ) -> Result<()> {
+        let identity = self.identity()?;
+        let mut manifest = self.load_manifest(provider, remote_directory).await?;
+        manifest.mark_synced(&identity, revision);
+        self.save_manifest(provider, remote_directory, &manifest)
+            .await
+    }
+
+    /// 撤销或恢复设备的同步权限（不能撤销当前设备）
+    pub async fn set_revoked(
+        &self,
+        provider: &dyn SyncProvider,
+        remote_directory: &str,
+        device_id: &str,
+        revoked: bool,
+    ) -> Result<()> {
+        let identity = self.identity()?;
+        if revoked && identity.id == device_id {
+            return Err(AppError::Validation("不能撤销当前设备".to_string()));
+        }
+
+        let mut manifest = self.load_manifest(provider, remote_directory).await?;
+        manifest.set_revoked(device_id, revoked)?;
+        self.save_manifest(provider, remote_directory, &manifest)
+            .await?;
+
+        log::info!(
+            "设备 {} 已{}",
+            device_id,
+            if revoked { "撤销" } else { "恢复" }
+        );
+        Ok(())
+    }
+}
+
+/// 远程设备清单对应的同步项
+fn manifest_item(remote_directory: &str, direction: SyncDirection) -> SyncItem {
+    SyncItem {
+        id: "device_manifest".to_string(),
+        name: DEVICE_MANIFEST_FILE.to_string(),
+        local_path: "local".to_string(),
+        remote_path: format!("{}/{}", remote_directory, DEVICE_MANIFEST_FILE),
+        size: 0,
+        local_modified: Local::now(),
+        remote_modified: None,
+        hash: String::new(),
+        status: SyncStatus::Idle,
+        direction,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+    use tempfile::tempdir;
+
+    fn identity(id: &str) -> DeviceIdentity {
+        DeviceIdentity {
+            id: id.to_string(),
+            name: format!("设备-{}", id),
+        }
+    }
+
+    #[test]
+    fn test_identity_is_stable() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            database_path: temp_dir
+                .path()
+                .join("test.db")
+                .to_string_lossy()
+                .to_string(),
+            ..Default::default()
+        };
+        let mut manager = StorageManager::new(config).unwrap();
+        manager.initialize().unwrap();
+        let registry = DeviceRegistry::new(Arc::new(manager));
+
+        let first = registry.identity().unwrap();
+        assert_eq!(registry.identity().unwrap(), first);
+
+        let renamed = registry.rename("工作电脑").unwrap();
+        assert_eq!(renamed.id, first.id);
+        assert_eq!(renamed.name, "工作电脑");
+    }
+
+    #[test]
+    fn test_revoked_device_is_refused() {
+        let mut manifest = DeviceManifest::default();
+        let laptop = identity("laptop");
+        let phone = identity("phone");
+
+        manifest.check_in(&laptop).unwrap();
+        manifest.check_in(&phone).unwrap();
+        manifest.mark_synced(&laptop, "rev-1");
+        assert_eq!(
+            manifest
+                .get("laptop")
+                .unwrap()
+                .last_synced_revision
+                .as_deref(),
+            Some("rev-1")
+        );
+
+        manifest.set_revoked("phone", true).unwrap();
+        assert!(manifest.check_in(&phone).is_err());
+        assert!(manifest.check_in(&laptop).is_ok());
+
+        manifest.set_revoked("phone", false).unwrap();
+        assert!(manifest.check_in(&phone).is_ok());
+        assert!(manifest.set_revoked("missing", true).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! # 同步历史模块
+//!
+//! 记录每次同步的执行情况，并在同步前保存本地数据快照作为还原点，
+//! 以便从历史列表中回滚任意一次同步
+
+use super::types::ResolvedConflictRecord;
+use crate::errors::{AppError, Result};
+use crate::storage::database::utils::datetime_from_str;
+use crate::storage::StorageManager;
+use crate::sync::SyncResult;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// 同步前快照在 sync_snapshots 表中的类型标记
+const RESTORE_POINT_KIND: &str = "pre_sync";
+
+/// 同步历史查询的公共列
+const HISTORY_COLUMNS: &str = "id, provider, started_at, finished_at, success, uploaded_count, \
+     downloaded_count, skipped_count, failed_count, total_bytes, errors, conflicts, snapshot_id, \
+     device_id, remote_device_id";
+
+/// 同步历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncHistoryEntry {
+    /// 记录ID
+    pub id: String,
+    /// 同步提供者
+    pub provider: String,
+    /// 开始时间
+    pub started_at: DateTime<Local>,
+    /// 结束时间
+    pub finished_at: DateTime<Local>,
+    /// 是否成功
+    pub success: bool,
+    /// 上传数量
+    pub uploaded_count: u32,
+    /// 下载数量
+    pub downloaded_count: u32,
+    /// 跳过数量
+    pub skipped_count: u32,
+    /// 失败数量
+    pub failed_count: u32,
+    /// 传输字节数
+    pub total_bytes: u64,
+    /// 错误信息
+    pub errors: Vec<String>,
+    /// 本次同步解决的冲突
+    pub conflicts: Vec<ResolvedConflictRecord>,
+    /// 同步前的还原点ID（已被清理时为空）
+    pub snapshot_id: Option<String>,
+    /// 执行同步的设备ID
+    pub device_id: Option<String>,
+    /// 下载或合并的远程数据来源设备ID
+    pub remote_device_id: Option<String>,
+}
+
+impl SyncHistoryEntry {
+    /// 根据同步结果创建历史记录
+    pub fn from_result(
+        id: String,
+        provider: String,
+        result: &SyncResult,
+        conflicts: Vec<ResolvedConflictRecord>,
+        snapshot_id: Option<String>,
+    ) -> Self {
+        Self {
+            id,
+            provider,
+            started_at: result.start_time,
+            finished_at: result.end_time,
+            success: result.success,
+            uploaded_count: result.uploaded_count,
+            downloaded_count: result.downloaded_count,
+            skipped_count: result.skipped_count,
+            failed_count: result.failed_count,
+            total_bytes: result.total_bytes,
+            errors: result.errors.clone(),
+            conflicts,
+            snapshot_id,
+            device_id: None,
+            remote_device_id: None,
+        }
+    }
+
+    /// 是否可以回滚
+    pub fn can_rollback(&self) -> bool {
+        self.snapshot_id.is_some()
+    }
+}
+
+/// 同步历史存储
+pub struct SyncHistoryStore {
+    storage: Arc<StorageManager>,
+}
+
+impl SyncHistoryStore {
+    /// 创建新的同步历史存储
+    pub fn new(storage: Arc<StorageManager>) -> Self {
+        Self { storage }
+    }
+
+    /// 将查询行映射为历史记录
+    fn map_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SyncHistoryEntry> {
+        let errors: String = row.get(10)?;
+        let conflicts: String = row.get(11)?;
+        Ok(SyncHistoryEntry {
+            id: row.get(0)?,
+            provider: row.get(1)?,
+            started_at: datetime_from_str(row.get(2)?)?,
+            finished_at: datetime_from_str(row.get(3)?)?,
+            success: row.get(4)?,
+            uploaded_count: row.get(5)?,
+            downloaded_count: row.get(6)?,
+            skipped_count: row.get(7)?,
+            failed_count: row.get(8)?,
+            total_bytes: row.get::<_, i64>(9)? as u64,
+            errors: serde_json::from_str(&errors).unwrap_or_default(),
+            conflicts: serde_json::from_str(&conflicts).unwrap_or_default(),
+            snapshot_id: row.get(12)?,
+            device_id: row.get(13)?,
+            remote_device_id: row.get(14)?,
+        })
+    }
+
+    /// 写入同步历史记录
+    pub fn record(&self, entry: &SyncHistoryEntry) -> Result<()> {
+        let sql = format!(
+            "INSERT OR REPLACE INTO sync_history ({}) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            HISTORY_COLUMNS
+        );
+
+        self.storage.get_database().get_connection()?.execute(
+            &sql,
+            &[
+                &entry.id,
+                &entry.provider,
+                &entry.started_at.to_rfc3339(),
+                &entry.finished_at.to_rfc3339(),
+                &entry.success,
+                &entry.uploaded_count,
+                &entry.downloaded_count,
+                &entry.skipped_count,
+                &entry.failed_count,
+                &(entry.total_bytes as i64),
+                &serde_json::to_string(&entry.errors)?,
+                &serde_json::to_string(&entry.conflicts)?,
+                &entry.snapshot_id,
+                &entry.device_id,
+                &entry.remote_device_id,
+            ],
+        )?;
+
+        log::debug!("已记录同步历史: {}", entry.id);
+        Ok(())
+    }
+
+    /// 获取最近的同步历史（按开始时间倒序）
+    pub fn list(&self, limit: usize) -> Result<Vec<SyncHistoryEntry>> {
+        let sql = format!(
+            "SELECT {} FROM sync_history ORDER BY started_at DESC LIMIT ?1",
+            HISTORY_COLUMNS
+        );
+
+        self.storage.get_database().get_connection()?.read(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let entries = stmt
+                .query_map([limit as i64], Self::map_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(entries)
+        })
+    }
+
+    /// 根据ID获取同步历史
+    pub fn get(&self, id: &str) -> Result<Option<SyncHistoryEntry>> {
+        let sql = format!("SELECT {} FROM sync_history WHERE id = ?1", HISTORY_COLUMNS);
+
+        match self
+            .storage
+            .get_database()
+            .get_connection()?
+            .query_row(&sql, &[&id], Self::map_row)
+        {
+            Ok(entry) => Ok(Some(entry)),
+            Err(AppError::Database(rusqlite::Error::QueryReturnedNoRows)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 保存同步前的本地数据快照
+    pub fn save_restore_point(&self, id: &str, data: &[u8]) -> Result<()> {
+        let data_str = String::from_utf8(data.to_vec())
+            .map_err(|e| AppError::Sync(format!("快照数据不是有效的UTF-8: {}", e)))?;
+        let content_hash = format!("{:x}", md5::compute(data));
+
+        self.storage.get_database().get_connection()?.execute(
+            r#"
+            INSERT OR REPLACE INTO sync_snapshots (id, kind, data, content_hash, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            &[
+                &id,
+                &RESTORE_POINT_KIND,
+                &data_str,
+                &content_hash,
+                &Local::now().to_rfc3339(),
+            ],
+        )?;
+
+        log::info!("已创建同步还原点: {} ({} 字节)", id, data.len());
+        Ok(())
+    }
+
+    /// 读取还原点数据
+    pub fn load_restore_point(&self, id: &str) -> Result<Option<Vec<u8>>> {
+        let sql = "SELECT data FROM sync_snapshots WHERE id = ?1 AND kind = ?2";
+        match self.storage.get_database().get_connection()?.query_row(
+            sql,
+            &[&id, &RESTORE_POINT_KIND],
+            |row| row.get::<_, String>(0),
+        ) {
+            Ok(data) => Ok(Some(data.into_bytes())),
+            Err(AppError::Database(rusqlite::Error::QueryReturnedNoRows)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 只保留最近 keep 个还原点，返回删除的数量
+    pub fn prune_restore_points(&self, keep: usize) -> Result<usize> {
+        let connection = self.storage.get_database().get_connection()?;
+
+        let removed = connection.execute(
+            r#"
+            DELETE FROM sync_snapshots
+            WHERE kind = ?1 AND id NOT IN (
+                SELECT id FROM sync_snapshots WHERE kind = ?1
+                ORDER BY created_at DESC LIMIT ?2
+            )
+            "#,
+            &[&RESTORE_POINT_KIND, &(keep as i64)],
+        )?;
+
+        if removed > 0 {
+            connection.execute(
+                r#"
+                UPDATE sync_history SET snapshot_id = NULL
+                WHERE snapshot_id IS NOT NULL
+                  AND snapshot_id NOT IN (SELECT id FROM sync_snapshots)
+                "#,
+                &[],
+            )?;
+            log::info!("已清理 {} 个过期的同步还原点", removed);
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::DatabaseConfig;
+    use tempfile::tempdir;
+
+    fn create_store(dir: &std::path::Path) -> SyncHistoryStore {
+        let config = DatabaseConfig {
+            database_path: dir.join("test.db").to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let mut manager = StorageManager::new(config).unwrap();
+        manager.initialize().unwrap();
+        SyncHistoryStore::new(Arc::new(manager))
+    }
+
+    fn entry(id: &str, snapshot_id: Option<String>) -> SyncHistoryEntry {
+        let mut result = SyncResult::new();
+        result.uploaded_count = 1;
+        result.add_error("网络错误".to_string());
+        result.complete(false);
+
+        let conflicts = vec![ResolvedConflictRecord {
+            collection: "tasks".to_string(),
+            record_id: "t1".to_string(),
+            resolution: "keep_local".to_string(),
+            device_id: Some("laptop".to_string()),
+        }];
+        SyncHistoryEntry::from_result(
+            id.to_string(),
+            "webdav".to_string(),
+            &result,
+            conflicts,
+            snapshot_id,
+        )
+    }
+
+    #[test]
+    fn test_record_and_list_history() {
+        let temp_dir = tempdir().unwrap();
+        let store = create_store(temp_dir.path());
+
+        store.record(&entry("run-1", None)).unwrap();
+
+        let entries = store.list(10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].success);
+        assert_eq!(entries[0].uploaded_count, 1);
+        assert_eq!(entries[0].errors, vec!["网络错误".to_string()]);
+        assert_eq!(entries[0].conflicts[0].resolution, "keep_local");
+        assert_eq!(entries[0].conflicts[0].device_id.as_deref(), Some("laptop"));
+        assert!(store.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restore_points_are_pruned() {
+        let temp_dir = tempdir().unwrap();
+        let store = create_store(temp_dir.path());
+
+        for i in 0..3 {
+            let id = format!("run-{}", i);
+            store.save_restore_point(&id, b"{\"tasks\":[]}").unwrap();
+            store.record(&entry(&id, Some(id.clone()))).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        assert_eq!(store.prune_restore_points(2).unwrap(), 1);
+        assert!(store.load_restore_point("run-0").unwrap().is_none());
+        assert!(store.load_restore_point("run-2").unwrap().is_some());
+        assert!(!store.get("run-0").unwrap().unwrap().can_rollback());
+        assert!(store.get("run-1").unwrap().unwrap().can_rollback());
+    }
+}
@@ -49,6 +49,18 @@ impl DataMerger {
         Ok(merged_data)
     }
 
+    /// 以共同祖先为基准执行三方合并
+    ///
+    /// 只有双方对同一字段做出不同修改时才产生冲突
+    pub fn three_way_merge(
+        &self,
+        base_data: &serde_json::Value,
+        local_data: &serde_json::Value,
+        remote_data: &serde_json::Value,
+    ) -> ThreeWayMergeResult {
+        super::ThreeWayMerger::new().merge(base_data, local_data, remote_data)
+    }
+
     /// 合并新安装后的本地数据与远程数据
     /// 策略：保留所有数据，去除重复项
     async fn merge_fresh_data(
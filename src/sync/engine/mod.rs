@@ -4,16 +4,25 @@
 //! - 同步引擎核心逻辑
 //! - 数据序列化和反序列化
 //! - 数据比较和冲突检测
-//! - 冲突解决策略
-//! - 数据合并逻辑
+//! - 冲突解决策略与手动冲突解决会话
+//! - 数据合并逻辑（含基于基线快照的三方合并）
 //! - 数据完整性验证
+//! - 同步历史与还原点
+//! - 多设备注册与撤销
+//! - 压缩、分块与可恢复的数据传输
 
 pub mod comparator;
 pub mod conflict_resolver;
 pub mod core;
+pub mod device;
+pub mod history;
 pub mod integrity_checker;
 pub mod merger;
 pub mod serializer;
+pub mod session;
+pub mod snapshot;
+pub mod three_way;
+pub mod transfer;
 pub mod types;
 pub mod validator;
 
@@ -21,8 +30,14 @@ pub mod validator;
 pub use comparator::DataComparator;
 pub use conflict_resolver::ConflictResolver;
 pub use core::SyncEngine;
+pub use device::{DeviceIdentity, DeviceInfo, DeviceManifest, DeviceRegistry};
+pub use history::{SyncHistoryEntry, SyncHistoryStore};
 pub use integrity_checker::{ConflictDetectionResult, DataIntegrityChecker, RiskLevel};
 pub use merger::DataMerger;
 pub use serializer::DataSerializer;
+pub use session::{ConflictChoice, ConflictSession, FieldChoice, RecordDecision};
+pub use snapshot::BaseSnapshotStore;
+pub use three_way::ThreeWayMerger;
+pub use transfer::{CompressionCodec, DataTransfer, TransferOptions, TransferStats};
 pub use types::*;
 pub use validator::DataValidator;
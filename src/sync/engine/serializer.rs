@@ -3,18 +3,21 @@
 //! 负责数据的序列化、反序列化、导入和导出操作
 
 use crate::errors::{AppError, Result};
-use crate::storage::StorageManager;
+use crate::storage::database::AuditSource;
+use crate::storage::StorageBackend;
 use chrono::{DateTime, Local};
+use std::collections::HashSet;
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// 数据序列化器
 pub struct DataSerializer {
-    storage: Arc<StorageManager>,
+    storage: Arc<dyn StorageBackend>,
 }
 
 impl DataSerializer {
     /// 创建新的数据序列化器
-    pub fn new(storage: Arc<StorageManager>) -> Self {
+    pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
         Self { storage }
     }
 
@@ -23,16 +26,18 @@ impl DataSerializer {
         log::info!("序列化所有应用数据");
 
         // 获取所有数据
-        let tasks = self.storage.get_database().get_all_tasks()?;
-        let categories = self.storage.get_database().get_all_categories()?;
-        let time_entries = self.storage.get_database().get_all_time_entries()?;
-        let transactions = self.storage.get_database().get_all_transactions()?;
-        let accounts = self.storage.get_database().get_all_accounts()?;
+        let tasks = self.storage.get_all_tasks()?;
+        let categories = self.storage.get_all_categories()?;
+        let time_entries = self.storage.get_all_time_entries()?;
+        let transactions = self.storage.get_all_transactions()?;
+        let accounts = self.storage.get_all_accounts()?;
+        let notes = self.storage.get_all_notes()?;
 
         // 获取数据来源追踪信息
         let base_remote_hash = self.get_base_remote_hash().await.unwrap_or(String::new());
         let is_fresh_install = self.is_fresh_install().await.unwrap_or(false);
         let last_sync_time = self.get_last_sync_time_from_storage().await;
+        let device = super::device::DeviceIdentity::load_or_create(self.storage.as_ref()).ok();
 
         // 创建导出数据结构
         let export_data = serde_json::json!({
@@ -41,12 +46,16 @@ impl DataSerializer {
             "time_entries": time_entries,
             "transactions": transactions,
             "accounts": accounts,
+            "notes": notes,
             "export_time": Local::now(),
             "version": env!("CARGO_PKG_VERSION"),
             // 数据来源追踪字段
             "base_remote_hash": base_remote_hash,
             "is_fresh_install": is_fresh_install,
-            "last_sync_time": last_sync_time
+            "last_sync_time": last_sync_time,
+            // 写入数据的设备
+            "device_id": device.as_ref().map(|d| d.id.clone()),
+            "device_name": device.as_ref().map(|d| d.name.clone())
         });
 
         // 序列化为JSON
@@ -72,9 +81,11 @@ impl DataSerializer {
         // 3. 创建数据备份
         let backup_data = self.create_backup().await?;
 
-        // 4. 开始事务
-        let db = self.storage.get_database();
-        if let Err(e) = db.get_connection()?.begin_transaction() {
+        // 4. 开始事务（审计日志只记录导入前后实际变化的数据）
+        let db = self.storage.as_ref();
+        db.begin_replace(AuditSource::Sync)?;
+        if let Err(e) = db.begin_transaction() {
+            let _ = db.finish_replace();
             log::error!("开始事务失败: {}", e);
             return Err(AppError::Sync(format!("开始事务失败: {}", e)));
         }
@@ -91,7 +102,8 @@ impl DataSerializer {
                 }
 
                 // 7. 提交事务
-                if let Err(e) = db.get_connection()?.commit_transaction() {
+                db.finish_replace()?;
+                if let Err(e) = db.commit_transaction() {
                     log::error!("提交事务失败: {}", e);
                     // 尝试回滚到备份数据
                     let _ = self.restore_from_backup(&backup_data).await;
@@ -103,9 +115,10 @@ impl DataSerializer {
             Err(e) => {
                 // 7. 回滚事务
                 log::error!("导入失败: {}", e);
-                if let Err(rollback_err) = db.get_connection()?.rollback_transaction() {
+                if let Err(rollback_err) = db.rollback_transaction() {
                     log::error!("回滚事务失败: {}", rollback_err);
                 }
+                let _ = db.finish_replace();
 
                 // 8. 恢复备份数据
                 if let Err(restore_err) = self.restore_from_backup(&backup_data).await {
@@ -118,13 +131,13 @@ impl DataSerializer {
     }
 
     /// 创建数据备份
-    async fn create_backup(&self) -> Result<Vec<u8>> {
+    pub async fn create_backup(&self) -> Result<Vec<u8>> {
         log::info!("创建数据备份");
         self.serialize_all_data().await
     }
 
     /// 从备份恢复数据
-    async fn restore_from_backup(&self, backup_data: &[u8]) -> Result<()> {
+    pub async fn restore_from_backup(&self, backup_data: &[u8]) -> Result<()> {
         log::info!("开始从备份恢复数据，大小: {} 字节", backup_data.len());
 
         // 验证备份数据
@@ -136,18 +149,24 @@ impl DataSerializer {
         let backup_json: serde_json::Value = serde_json::from_slice(backup_data)?;
 
         // 开始恢复事务
-        let db = self.storage.get_database();
-        db.get_connection()?.begin_transaction()?;
+        let db = self.storage.as_ref();
+        db.begin_replace(AuditSource::Sync)?;
+        if let Err(e) = db.begin_transaction() {
+            let _ = db.finish_replace();
+            return Err(e);
+        }
 
         match self.restore_all_data(&backup_json).await {
             Ok(_) => {
-                db.get_connection()?.commit_transaction()?;
+                db.finish_replace()?;
+                db.commit_transaction()?;
                 log::info!("数据恢复成功");
                 Ok(())
             }
             Err(e) => {
                 log::error!("数据恢复失败: {}", e);
-                db.get_connection()?.rollback_transaction()?;
+                db.rollback_transaction()?;
+                db.finish_replace()?;
                 Err(AppError::Sync(format!("数据恢复失败: {}", e)))
             }
         }
@@ -156,38 +175,7 @@ impl DataSerializer {
     /// 恢复所有数据
     async fn restore_all_data(&self, backup_data: &serde_json::Value) -> Result<()> {
         log::info!("恢复所有数据");
-
-        let db = self.storage.get_database();
-
-        // 首先清空当前数据
-        self.clear_existing_data().await?;
-
-        // 按正确的依赖顺序恢复数据
-        // 1. 先恢复分类数据（被任务引用）
-        if let Some(categories) = backup_data.get("categories") {
-            self.import_categories(categories, db).await?;
-        }
-
-        // 2. 恢复账户数据（被交易引用）
-        if let Some(accounts) = backup_data.get("accounts") {
-            self.import_accounts(accounts, db).await?;
-        }
-
-        // 3. 恢复任务数据（引用分类）
-        if let Some(tasks) = backup_data.get("tasks") {
-            self.import_tasks(tasks, db).await?;
-        }
-
-        // 4. 恢复时间记录（引用任务）
-        if let Some(time_entries) = backup_data.get("time_entries") {
-            self.import_time_entries(time_entries, db).await?;
-        }
-
-        // 5. 恢复交易数据（引用账户）
-        if let Some(transactions) = backup_data.get("transactions") {
-            self.import_transactions(transactions, db).await?;
-        }
-
+        self.replace_all_data(backup_data).await?;
         log::info!("所有数据恢复完成");
         Ok(())
     }
@@ -195,55 +183,72 @@ impl DataSerializer {
     /// 执行实际的数据导入
     async fn perform_import(&self, import_data: &serde_json::Value) -> Result<()> {
         log::info!("执行数据导入");
+        self.replace_all_data(import_data).await?;
+        log::info!("数据导入完成");
+        Ok(())
+    }
 
-        let db = self.storage.get_database();
+    /// 清空现有数据后按依赖顺序写入全部数据，回收站中的记录不会被覆盖
+    async fn replace_all_data(&self, data: &serde_json::Value) -> Result<()> {
+        let db = self.storage.as_ref();
 
         // 清空现有数据 (谨慎操作)
         self.clear_existing_data().await?;
+        let trashed = db.trashed_ids()?;
 
-        // 按正确的依赖顺序导入数据
-        // 1. 先导入分类数据（被任务引用）
-        if let Some(categories) = import_data.get("categories") {
-            self.import_categories(categories, db).await?;
+        // 按正确的依赖顺序写入数据
+        // 1. 先写入分类数据（被任务引用）
+        if let Some(categories) = data.get("categories") {
+            self.import_categories(&without_trashed(categories, &trashed), db)
+                .await?;
         }
 
-        // 2. 导入账户数据（被交易引用）
-        if let Some(accounts) = import_data.get("accounts") {
-            self.import_accounts(accounts, db).await?;
+        // 2. 写入账户数据（被交易引用）
+        if let Some(accounts) = data.get("accounts") {
+            self.import_accounts(&without_trashed(accounts, &trashed), db)
+                .await?;
         }
 
-        // 3. 导入任务数据（引用分类）
-        if let Some(tasks) = import_data.get("tasks") {
-            self.import_tasks(tasks, db).await?;
+        // 3. 写入任务数据（引用分类）
+        if let Some(tasks) = data.get("tasks") {
+            self.import_tasks(&without_trashed(tasks, &trashed), db)
+                .await?;
         }
 
-        // 4. 导入时间记录（引用任务）
-        if let Some(time_entries) = import_data.get("time_entries") {
-            self.import_time_entries(time_entries, db).await?;
+        // 4. 写入时间记录（引用任务）
+        if let Some(time_entries) = data.get("time_entries") {
+            self.import_time_entries(&without_trashed(time_entries, &trashed), db)
+                .await?;
         }
 
-        // 5. 导入交易数据（引用账户）
-        if let Some(transactions) = import_data.get("transactions") {
-            self.import_transactions(transactions, db).await?;
+        // 5. 写入交易数据（引用账户）
+        if let Some(transactions) = data.get("transactions") {
+            self.import_transactions(&without_trashed(transactions, &trashed), db)
+                .await?;
+        }
+
+        // 6. 写入笔记数据
+        if let Some(notes) = data.get("notes") {
+            self.import_notes(&without_trashed(notes, &trashed), db)
+                .await?;
         }
 
-        log::info!("数据导入完成");
         Ok(())
     }
 
-    /// 清空现有数据
+    /// 清空现有数据（回收站中的数据保留）
     async fn clear_existing_data(&self) -> Result<()> {
         log::info!("清空现有数据");
 
-        let db = self.storage.get_database();
-        let connection = db.get_connection()?;
+        let db = self.storage.as_ref();
 
         // 按依赖关系顺序删除
-        connection.execute("DELETE FROM time_entries", &[])?;
-        connection.execute("DELETE FROM transactions", &[])?;
-        connection.execute("DELETE FROM tasks", &[])?;
-        connection.execute("DELETE FROM categories", &[])?;
-        connection.execute("DELETE FROM accounts", &[])?;
+        db.clear_time_entries()?;
+        db.clear_transactions()?;
+        db.clear_tasks()?;
+        db.clear_categories()?;
+        db.clear_accounts()?;
+        db.clear_notes()?;
 
         log::info!("现有数据已清空");
         Ok(())
@@ -253,7 +258,7 @@ impl DataSerializer {
     async fn import_tasks(
         &self,
         tasks_data: &serde_json::Value,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<()> {
         if let Some(tasks_array) = tasks_data.as_array() {
             for task_value in tasks_array {
@@ -269,7 +274,7 @@ impl DataSerializer {
     async fn import_categories(
         &self,
         categories_data: &serde_json::Value,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<()> {
         if let Some(categories_array) = categories_data.as_array() {
             for category_value in categories_array {
@@ -286,7 +291,7 @@ impl DataSerializer {
     async fn import_time_entries(
         &self,
         time_entries_data: &serde_json::Value,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<()> {
         if let Some(time_entries_array) = time_entries_data.as_array() {
             for time_entry_value in time_entries_array {
@@ -303,7 +308,7 @@ impl DataSerializer {
     async fn import_accounts(
         &self,
         accounts_data: &serde_json::Value,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<()> {
         if let Some(accounts_array) = accounts_data.as_array() {
             for account_value in accounts_array {
@@ -320,7 +325,7 @@ impl DataSerializer {
     async fn import_transactions(
         &self,
         transactions_data: &serde_json::Value,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<()> {
         if let Some(transactions_array) = transactions_data.as_array() {
             for transaction_value in transactions_array {
@@ -333,6 +338,22 @@ impl DataSerializer {
         Ok(())
     }
 
+    /// 导入笔记数据
+    async fn import_notes(
+        &self,
+        notes_data: &serde_json::Value,
+        db: &dyn StorageBackend,
+    ) -> Result<()> {
+        if let Some(notes_array) = notes_data.as_array() {
+            for note_value in notes_array {
+                let note: crate::storage::Note = serde_json::from_value(note_value.clone())?;
+                db.insert_note(&note)?;
+            }
+            log::info!("导入了 {} 条笔记", notes_array.len());
+        }
+        Ok(())
+    }
+
     /// 验证数据完整性
     pub fn validate_data(&self, data: &[u8]) -> Result<bool> {
         log::info!("验证数据完整性，大小: {} 字节", data.len());
@@ -443,6 +464,12 @@ impl DataSerializer {
             }
         }
 
+        if let Some(notes) = data.get("notes") {
+            if let Some(notes_array) = notes.as_array() {
+                total += notes_array.len();
+            }
+        }
+
         Ok(total)
     }
 
@@ -475,6 +502,8 @@ impl DataSerializer {
             obj.remove("export_time");
             obj.remove("import_time");
             obj.remove("sync_time");
+            obj.remove("device_id");
+            obj.remove("device_name");
         }
 
         Ok(content)
@@ -489,7 +518,7 @@ impl DataSerializer {
     /// 获取本地数据基于的远程版本哈希
     async fn get_base_remote_hash(&self) -> Result<String> {
         // 从存储中读取上次同步时的远程数据哈希
-        match self.storage.get_database().get_setting("base_remote_hash") {
+        match self.storage.get_setting("base_remote_hash") {
             Ok(Some(hash)) => Ok(hash),
             _ => Ok(String::new()),
         }
@@ -497,9 +526,7 @@ impl DataSerializer {
 
     /// 设置远程数据哈希
     async fn set_base_remote_hash(&self, hash: &str) -> Result<()> {
-        self.storage
-            .get_database()
-            .set_setting("base_remote_hash", hash)?;
+        self.storage.set_setting("base_remote_hash", hash)?;
         log::info!("已更新远程数据哈希: {}", hash);
         Ok(())
     }
@@ -509,7 +536,6 @@ impl DataSerializer {
         // 检查是否从未进行过同步
         let has_synced = self
             .storage
-            .get_database()
             .get_setting("has_synced")
             .unwrap_or(None)
             .map(|v| v == "true")
@@ -525,24 +551,22 @@ impl DataSerializer {
 
     /// 设置已同步标记
     async fn set_synced_flag(&self) -> Result<()> {
-        self.storage
-            .get_database()
-            .set_setting("has_synced", "true")?;
+        self.storage.set_setting("has_synced", "true")?;
         Ok(())
     }
 
     /// 检查是否有本地数据
     async fn has_local_data(&self) -> Result<bool> {
-        let tasks = self.storage.get_database().get_all_tasks()?;
-        let time_entries = self.storage.get_database().get_all_time_entries()?;
-        let transactions = self.storage.get_database().get_all_transactions()?;
+        let tasks = self.storage.get_all_tasks()?;
+        let time_entries = self.storage.get_all_time_entries()?;
+        let transactions = self.storage.get_all_transactions()?;
 
         Ok(!tasks.is_empty() || !time_entries.is_empty() || !transactions.is_empty())
     }
 
     /// 从存储中获取上次同步时间
     async fn get_last_sync_time_from_storage(&self) -> Option<DateTime<Local>> {
-        if let Ok(Some(time_str)) = self.storage.get_database().get_setting("last_sync_time") {
+        if let Ok(Some(time_str)) = self.storage.get_setting("last_sync_time") {
             if let Ok(parsed_time) = DateTime::parse_from_rfc3339(&time_str) {
                 return Some(parsed_time.with_timezone(&Local));
             }
@@ -553,9 +577,7 @@ impl DataSerializer {
     /// 设置上次同步时间
     async fn set_last_sync_time(&self, time: DateTime<Local>) -> Result<()> {
         let time_str = time.to_rfc3339();
-        self.storage
-            .get_database()
-            .set_setting("last_sync_time", &time_str)?;
+        self.storage.set_setting("last_sync_time", &time_str)?;
         log::info!("已更新同步时间: {}", time_str);
         Ok(())
     }
@@ -601,31 +623,35 @@ impl DataSerializer {
         for data_type in data_types {
             match *data_type {
                 "tasks" => {
-                    let tasks = self.storage.get_database().get_all_tasks()?;
+                    let tasks = self.storage.get_all_tasks()?;
                     export_data.insert("tasks".to_string(), serde_json::to_value(tasks)?);
                 }
                 "categories" => {
-                    let categories = self.storage.get_database().get_all_categories()?;
+                    let categories = self.storage.get_all_categories()?;
                     export_data.insert("categories".to_string(), serde_json::to_value(categories)?);
                 }
                 "time_entries" => {
-                    let time_entries = self.storage.get_database().get_all_time_entries()?;
+                    let time_entries = self.storage.get_all_time_entries()?;
                     export_data.insert(
                         "time_entries".to_string(),
                         serde_json::to_value(time_entries)?,
                     );
                 }
                 "transactions" => {
-                    let transactions = self.storage.get_database().get_all_transactions()?;
+                    let transactions = self.storage.get_all_transactions()?;
                     export_data.insert(
                         "transactions".to_string(),
                         serde_json::to_value(transactions)?,
                     );
                 }
                 "accounts" => {
-                    let accounts = self.storage.get_database().get_all_accounts()?;
+                    let accounts = self.storage.get_all_accounts()?;
                     export_data.insert("accounts".to_string(), serde_json::to_value(accounts)?);
                 }
+                "notes" => {
+                    let notes = self.storage.get_all_notes()?;
+                    export_data.insert("notes".to_string(), serde_json::to_value(notes)?);
+                }
                 _ => {
                     log::warn!("未知的数据类型: {}", data_type);
                 }
@@ -659,7 +685,7 @@ impl DataSerializer {
             since.format("%Y-%m-%d %H:%M:%S")
         );
 
-        let db = self.storage.get_database();
+        let db = self.storage.as_ref();
 
         // 获取自指定时间以来的更改
         let changed_tasks = self.get_changed_tasks_since(since, db)?;
@@ -691,7 +717,7 @@ impl DataSerializer {
     fn get_changed_tasks_since(
         &self,
         _since: DateTime<Local>,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<Vec<serde_json::Value>> {
         // 这里需要实现具体的查询逻辑
         // 为了简化，我们返回所有任务
@@ -707,7 +733,7 @@ impl DataSerializer {
     fn get_changed_categories_since(
         &self,
         _since: DateTime<Local>,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<Vec<serde_json::Value>> {
         let categories = db.get_all_categories()?;
         let categories_json: Vec<serde_json::Value> = categories
@@ -721,7 +747,7 @@ impl DataSerializer {
     fn get_changed_time_entries_since(
         &self,
         _since: DateTime<Local>,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<Vec<serde_json::Value>> {
         let time_entries = db.get_all_time_entries()?;
         let time_entries_json: Vec<serde_json::Value> = time_entries
@@ -735,7 +761,7 @@ impl DataSerializer {
     fn get_changed_accounts_since(
         &self,
         _since: DateTime<Local>,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<Vec<serde_json::Value>> {
         let accounts = db.get_all_accounts()?;
         let accounts_json: Vec<serde_json::Value> = accounts
@@ -749,7 +775,7 @@ impl DataSerializer {
     fn get_changed_transactions_since(
         &self,
         _since: DateTime<Local>,
-        db: &crate::storage::Database,
+        db: &dyn StorageBackend,
     ) -> Result<Vec<serde_json::Value>> {
         let transactions = db.get_all_transactions()?;
         let transactions_json: Vec<serde_json::Value> = transactions
@@ -759,3 +785,84 @@ impl DataSerializer {
         Ok(transactions_json)
     }
 }
+
+/// 去掉本地回收站中的数据（回收站中的数据在清空时保留，不能重复写入）
+fn without_trashed(records: &serde_json::Value, trashed: &HashSet<Uuid>) -> serde_json::Value {
+    match records.as_array() {
+        Some(records) => records
+            .iter()
+            .filter(|record| {
+                record
+                    .get("id")
+                    .and_then(|id| id.as_str())
+                    .and_then(|id| Uuid::parse_str(id).ok())
+                    .is_none_or(|id| !trashed.contains(&id))
+            })
+            .cloned()
+            .collect(),
+        None => records.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::{NoteStore, TaskStore};
+    use crate::storage::{MemoryStorage, TaskInsert};
+
+    fn storage_with_task(name: &str) -> Arc<MemoryStorage> {
+        let storage = Arc::new(MemoryStorage::new());
+        let task = crate::core::Task::new(name.to_string(), None, None);
+        storage.insert_task(&TaskInsert::from(&task)).unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_import_replaces_data_in_memory_storage() {
+        let source = storage_with_task("远程任务");
+        let data = DataSerializer::new(source.clone())
+            .serialize_all_data()
+            .await
+            .unwrap();
+
+        let target = storage_with_task("本地任务");
+        DataSerializer::new(target.clone())
+            .import_data(&data)
+            .await
+            .unwrap();
+
+        let tasks = target.get_all_tasks().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, source.get_all_tasks().unwrap()[0].id);
+        assert!(target.get_all_notes().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_keeps_trashed_data() {
+        let (_temp_dir, storage) = crate::storage::test_support::temp_storage();
+        let trashed = crate::storage::test_support::new_task("已删除的任务");
+        storage.insert_task(&trashed).unwrap();
+        storage.get_database().delete_task(trashed.id).unwrap();
+
+        // 远程数据中该任务仍未删除
+        let source = storage_with_task("远程任务");
+        source.insert_task(&trashed).unwrap();
+        let data = DataSerializer::new(source.clone())
+            .serialize_all_data()
+            .await
+            .unwrap();
+
+        let storage = Arc::new(storage);
+        DataSerializer::new(storage.clone())
+            .import_data(&data)
+            .await
+            .unwrap();
+
+        let trash = storage.get_database().trash().list().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, trashed.id);
+        let tasks = storage.get_all_tasks().unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "远程任务");
+    }
+}
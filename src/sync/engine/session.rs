@@ -0,0 +1,432 @@
+//! # 冲突解决会话模块
+//!
+//! 手动冲突策略下，三方合并无法自动解决的记录会被收集到冲突会话中：
+//! - 每条冲突记录附带本地、远程、基线三个版本和字段差异
+//! - 支持按记录或按字段做出决定
+//! - 所有冲突都有决定后生成最终数据集，由同步引擎事务性地应用并继续同步
+
+use super::snapshot::record_key;
+use super::three_way::replace_record;
+use super::types::*;
+use crate::errors::{AppError, Result};
+use crate::sync::SyncItem;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 记录级冲突决定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConflictChoice {
+    /// 保留本地版本
+    KeepLocal,
+    /// 保留远程版本
+    KeepRemote,
+    /// 两个版本都保留（远程版本以新ID作为副本保存）
+    KeepBoth,
+    /// 使用自定义编辑后的记录
+    Custom(Value),
+}
+
+/// 字段级冲突决定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FieldChoice {
+    /// 使用本地值
+    KeepLocal,
+    /// 使用远程值
+    KeepRemote,
+    /// 使用自定义值
+    Custom(Value),
+}
+
+/// 单条冲突记录的决定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordDecision {
+    /// 整条记录的决定
+    Record(ConflictChoice),
+    /// 逐字段的决定
+    Fields(HashMap<String, FieldChoice>),
+}
+
+/// 冲突解决会话
+#[derive(Debug, Clone)]
+pub struct ConflictSession {
+    /// 会话ID
+    pub id: String,
+    /// 创建时间
+    pub created_at: DateTime<Local>,
+    /// 待解决的冲突记录
+    pub conflicts: Vec<RecordConflict>,
+    /// 已做出的决定，键为 "集合/记录ID"
+    decisions: HashMap<String, RecordDecision>,
+    /// 三方合并的结果（已包含自动合并的部分）
+    merged: Value,
+    /// 解决后需要上传的同步项
+    pub(crate) sync_items: Vec<SyncItem>,
+}
+
+impl ConflictSession {
+    /// 从三方合并结果创建冲突会话
+    pub fn new(merge: ThreeWayMergeResult, sync_items: Vec<SyncItem>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: Local::now(),
+            conflicts: merge.conflicts,
+            decisions: HashMap::new(),
+            merged: merge.merged,
+            sync_items,
+        }
+    }
+
+    /// 查找冲突记录
+    pub fn get_conflict(&self, collection: &str, record_id: &str) -> Option<&RecordConflict> {
+        self.conflicts
+            .iter()
+            .find(|c| c.collection == collection && c.record_id == record_id)
+    }
+
+    /// 获取某条记录的决定
+    pub fn get_decision(&self, collection: &str, record_id: &str) -> Option<&RecordDecision> {
+        self.decisions.get(&decision_key(collection, record_id))
+    }
+
+    /// 对整条记录做出决定
+    pub fn decide_record(
+        &mut self,
+        collection: &str,
+        record_id: &str,
+        choice: ConflictChoice,
+    ) -> Result<()> {
+        if self.get_conflict(collection, record_id).is_none() {
+            return Err(AppError::NotFound(format!(
+                "冲突记录不存在: {}/{}",
+                collection, record_id
+            )));
+        }
+        if let ConflictChoice::Custom(record) = &choice {
+            if !record.is_object() {
+                return Err(AppError::Validation("自定义记录必须是JSON对象".to_string()));
+            }
+        }
+
+        self.decisions.insert(
+            decision_key(collection, record_id),
+            RecordDecision::Record(choice),
+        );
+        Ok(())
+    }
+
+    /// 对冲突记录中的单个字段做出决定
+    pub fn decide_field(
+        &mut self,
+        collection: &str,
+        record_id: &str,
+        field: &str,
+        choice: FieldChoice,
+    ) -> Result<()> {
+        let conflict = self.get_conflict(collection, record_id).ok_or_else(|| {
+            AppError::NotFound(format!("冲突记录不存在: {}/{}", collection, record_id))
+        })?;
+        if !conflict.field_conflicts.iter().any(|f| f.field == field) {
+            return Err(AppError::Validation(format!(
+                "字段 {} 不是记录 {}/{} 的冲突字段",
+                field, collection, record_id
+            )));
+        }
+
+        let key = decision_key(collection, record_id);
+        match self.decisions.get_mut(&key) {
+            Some(RecordDecision::Fields(fields)) => {
+                fields.insert(field.to_string(), choice);
+            }
+            _ => {
+                let mut fields = HashMap::new();
+                fields.insert(field.to_string(), choice);
+                self.decisions.insert(key, RecordDecision::Fields(fields));
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取尚未解决的冲突
+    pub fn unresolved(&self) -> Vec<&RecordConflict> {
+        self.conflicts
+            .iter()
+            .filter(|c| !self.is_resolved(c))
+            .collect()
+    }
+
+    /// 是否所有冲突都已做出决定
+    pub fn is_complete(&self) -> bool {
+        self.conflicts.iter().all(|c| self.is_resolved(c))
+    }
+
+    /// 判断单条冲突是否已解决
+    fn is_resolved(&self, conflict: &RecordConflict) -> bool {
+        match self
+            .decisions
+            .get(&decision_key(&conflict.collection, &conflict.record_id))
+        {
+            Some(RecordDecision::Record(_)) => true,
+            // 删除冲突没有字段可选，必须按记录决定
+            Some(RecordDecision::Fields(fields)) => {
+                !conflict.field_conflicts.is_empty()
+                    && conflict
+                        .field_conflicts
+                        .iter()
+                        .all(|f| fields.contains_key(&f.field))
+            }
+            None => false,
+        }
+    }
+
+    /// 汇总各冲突记录的解决方式（用于同步历史）
+    pub fn resolution_log(&self) -> Vec<ResolvedConflictRecord> {
+        self.conflicts
+            .iter()
+            .filter_map(|conflict| {
+                let key = decision_key(&conflict.collection, &conflict.record_id);
+                let resolution = match self.decisions.get(&key)? {
+                    RecordDecision::Record(ConflictChoice::KeepLocal) => "keep_local",
+                    RecordDecision::Record(ConflictChoice::KeepRemote) => "keep_remote",
+                    RecordDecision::Record(ConflictChoice::KeepBoth) => "keep_both",
+                    RecordDecision::Record(ConflictChoice::Custom(_)) => "custom",
+                    RecordDecision::Fields(_) => "per_field",
+                };
+                Some(ResolvedConflictRecord {
+                    collection: conflict.collection.clone(),
+                    record_id: conflict.record_id.clone(),
+                    resolution: resolution.to_string(),
+                    device_id: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 根据所有决定生成最终数据集
+    pub fn build_resolved_data(&self) -> Result<Value> {
+        let unresolved = self.unresolved().len();
+        if unresolved > 0 {
+            return Err(AppError::Sync(format!("仍有 {} 条冲突未解决", unresolved)));
+        }
+
+        let mut data = self.merged.clone();
+        for conflict in &self.conflicts {
+            let key = decision_key(&conflict.collection, &conflict.record_id);
+            match self.decisions.get(&key) {
+                Some(RecordDecision::Record(choice)) => {
+                    apply_record_choice(&mut data, conflict, choice);
+                }
+                Some(RecordDecision::Fields(fields)) => {
+                    apply_field_choices(&mut data, conflict, fields);
+                }
+                None => {}
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// 决定映射的键
+fn decision_key(collection: &str, record_id: &str) -> String {
+    format!("{}/{}", collection, record_id)
+}
+
+/// 应用记录级决定
+fn apply_record_choice(data: &mut Value, conflict: &RecordConflict, choice: &ConflictChoice) {
+    let collection = &conflict.collection;
+    let record_id = &conflict.record_id;
+
+    match choice {
+        ConflictChoice::KeepLocal => {
+            replace_record(data, collection, record_id, conflict.local.clone());
+        }
+        ConflictChoice::KeepRemote => {
+            replace_record(data, collection, record_id, conflict.remote.clone());
+        }
+        ConflictChoice::KeepBoth => match (&conflict.local, &conflict.remote) {
+            (Some(local), Some(remote)) => {
+                replace_record(data, collection, record_id, Some(local.clone()));
+                let duplicate = make_duplicate(remote);
+                let duplicate_id = record_key(&duplicate);
+                replace_record(data, collection, &duplicate_id, Some(duplicate));
+            }
+            (local, remote) => {
+                replace_record(
+                    data,
+                    collection,
+                    record_id,
+                    local.clone().or(remote.clone()),
+                );
+            }
+        },
+        ConflictChoice::Custom(record) => {
+            let mut record = record.clone();
+            if let Some(obj) = record.as_object_mut() {
+                obj.insert("id".to_string(), Value::String(record_id.clone()));
+            }
+            replace_record(data, collection, record_id, Some(record));
+        }
+    }
+}
+
+/// 应用字段级决定（未冲突的字段保留自动合并的结果）
+fn apply_field_choices(
+    data: &mut Value,
+    conflict: &RecordConflict,
+    fields: &HashMap<String, FieldChoice>,
+) {
+    let current = data
+        .get(&conflict.collection)
+        .and_then(|v| v.as_array())
+        .and_then(|records| {
+            records
+                .iter()
+                .find(|r| record_key(r) == conflict.record_id)
+                .cloned()
+        });
+    let Some(mut record) = current.or_else(|| conflict.local.clone()) else {
+        return;
+    };
+    let Some(obj) = record.as_object_mut() else {
+        return;
+    };
+
+    for field_conflict in &conflict.field_conflicts {
+        let value = match fields.get(&field_conflict.field) {
+            Some(FieldChoice::KeepLocal) => field_conflict.local.clone(),
+            Some(FieldChoice::KeepRemote) => field_conflict.remote.clone(),
+            Some(FieldChoice::Custom(value)) => Some(value.clone()),
+            None => continue,
+        };
+        match value {
+            Some(value) => obj.insert(field_conflict.field.clone(), value),
+            None => obj.remove(&field_conflict.field),
+        };
+    }
+
+    replace_record(
+        data,
+        &conflict.collection,
+        &conflict.record_id,
+        Some(record),
+    );
+}
+
+/// 为保留双方版本生成远程记录的副本
+fn make_duplicate(record: &Value) -> Value {
+    let mut duplicate = record.clone();
+    if let Some(obj) = duplicate.as_object_mut() {
+        obj.insert(
+            "id".to_string(),
+            Value::String(uuid::Uuid::new_v4().to_string()),
+        );
+        // 名称可能有唯一约束，副本加上后缀以便区分
+        for field in ["name", "title"] {
+            if let Some(Value::String(text)) = obj.get(field) {
+                let renamed = format!("{} (远程副本)", text);
+                obj.insert(field.to_string(), Value::String(renamed));
+            }
+        }
+        obj.insert(
+            "updated_at".to_string(),
+            Value::String(Local::now().to_rfc3339()),
+        );
+    }
+    duplicate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::engine::ThreeWayMerger;
+    use serde_json::json;
+
+    fn conflicting_session() -> ConflictSession {
+        let base = json!({ "tasks": [{ "id": "t1", "name": "A", "priority": "low" }] });
+        let local = json!({ "tasks": [{ "id": "t1", "name": "B", "priority": "high" }] });
+        let remote = json!({ "tasks": [{ "id": "t1", "name": "C", "priority": "medium" }] });
+        let merge = ThreeWayMerger::new().merge(&base, &local, &remote);
+        ConflictSession::new(merge, Vec::new())
+    }
+
+    fn tasks(data: &Value) -> &Vec<Value> {
+        data["tasks"].as_array().unwrap()
+    }
+
+    #[test]
+    fn test_incomplete_session_is_rejected() {
+        let mut session = conflicting_session();
+        assert_eq!(session.unresolved().len(), 1);
+        assert!(session.build_resolved_data().is_err());
+
+        session
+            .decide_field("tasks", "t1", "name", FieldChoice::KeepRemote)
+            .unwrap();
+        // 仍有字段未决定
+        assert!(!session.is_complete());
+    }
+
+    #[test]
+    fn test_per_field_decisions() {
+        let mut session = conflicting_session();
+        session
+            .decide_field("tasks", "t1", "name", FieldChoice::KeepRemote)
+            .unwrap();
+        session
+            .decide_field(
+                "tasks",
+                "t1",
+                "priority",
+                FieldChoice::Custom(json!("urgent")),
+            )
+            .unwrap();
+        assert!(session.is_complete());
+
+        let data = session.build_resolved_data().unwrap();
+        assert_eq!(tasks(&data)[0]["name"], "C");
+        assert_eq!(tasks(&data)[0]["priority"], "urgent");
+    }
+
+    #[test]
+    fn test_keep_both_creates_duplicate() {
+        let mut session = conflicting_session();
+        session
+            .decide_record("tasks", "t1", ConflictChoice::KeepBoth)
+            .unwrap();
+
+        let data = session.build_resolved_data().unwrap();
+        assert_eq!(tasks(&data).len(), 2);
+        assert_eq!(tasks(&data)[0]["name"], "B");
+        assert_eq!(tasks(&data)[1]["name"], "C (远程副本)");
+        assert_ne!(tasks(&data)[1]["id"], "t1");
+    }
+
+    #[test]
+    fn test_custom_record_keeps_id() {
+        let mut session = conflicting_session();
+        session
+            .decide_record(
+                "tasks",
+                "t1",
+                ConflictChoice::Custom(json!({ "id": "other", "name": "D" })),
+            )
+            .unwrap();
+
+        let data = session.build_resolved_data().unwrap();
+        assert_eq!(tasks(&data)[0]["id"], "t1");
+        assert_eq!(tasks(&data)[0]["name"], "D");
+    }
+
+    #[test]
+    fn test_unknown_record_or_field() {
+        let mut session = conflicting_session();
+        assert!(session
+            .decide_record("tasks", "missing", ConflictChoice::KeepLocal)
+            .is_err());
+        assert!(session
+            .decide_field("tasks", "t1", "id", FieldChoice::KeepLocal)
+            .is_err());
+    }
+}
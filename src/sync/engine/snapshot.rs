@@ -0,0 +1,133 @@
+//! # 同步基线快照模块
+//!
+//! 保存上一次成功同步时的数据集，作为三方合并的共同祖先
+
+use crate::errors::Result;
+use crate::storage::StorageManager;
+use chrono::Local;
+use std::sync::Arc;
+
+/// 参与同步与合并的数据集合（按依赖顺序排列）
+pub const SYNC_COLLECTIONS: &[&str] = &[
+    "categories",
+    "accounts",
+    "tasks",
+    "time_entries",
+    "transactions",
+    "notes",
+];
+
+/// 基线快照在 sync_snapshots 表中的类型标记
+const BASE_SNAPSHOT_KIND: &str = "base";
+
+/// 基线快照在 sync_snapshots 表中的固定ID
+const BASE_SNAPSHOT_ID: &str = "base";
+
+/// 基线快照存储
+pub struct BaseSnapshotStore {
+    storage: Arc<StorageManager>,
+}
+
+impl BaseSnapshotStore {
+    /// 创建新的快照存储
+    pub fn new(storage: Arc<StorageManager>) -> Self {
+        Self { storage }
+    }
+
+    /// 读取基线快照
+    pub fn load(&self) -> Result<Option<serde_json::Value>> {
+        let sql = "SELECT data FROM sync_snapshots WHERE id = ?1";
+        let data = self.storage.get_database().get_connection()?.read(|conn| {
+            match conn.query_row(sql, [BASE_SNAPSHOT_ID], |row| row.get::<_, String>(0)) {
+                Ok(data) => Ok(Some(data)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })?;
+
+        match data {
+            Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 读取基线快照的内容哈希
+    pub fn load_hash(&self) -> Result<Option<String>> {
+        let sql = "SELECT content_hash FROM sync_snapshots WHERE id = ?1";
+        self.storage.get_database().get_connection()?.read(|conn| {
+            match conn.query_row(sql, [BASE_SNAPSHOT_ID], |row| row.get::<_, String>(0)) {
+                Ok(hash) => Ok(Some(hash)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    /// 保存基线快照（只保留实体数据，丢弃导出时间等元数据）
+    pub fn save(&self, data: &serde_json::Value) -> Result<()> {
+        let content = extract_entity_content(data);
+        let content_hash = entity_content_hash(&content);
+        let content_str = serde_json::to_string(&content)?;
+
+        let sql = r#"
+            INSERT OR REPLACE INTO sync_snapshots (id, kind, data, content_hash, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+        "#;
+        self.storage.get_database().get_connection()?.execute(
+            sql,
+            &[
+                &BASE_SNAPSHOT_ID,
+                &BASE_SNAPSHOT_KIND,
+                &content_str,
+                &content_hash,
+                &Local::now().to_rfc3339(),
+            ],
+        )?;
+
+        log::info!("已更新同步基线快照，哈希: {}", content_hash);
+        Ok(())
+    }
+
+    /// 清除基线快照（下次同步将退回到双方比较）
+    pub fn clear(&self) -> Result<()> {
+        self.storage.get_database().get_connection()?.execute(
+            "DELETE FROM sync_snapshots WHERE id = ?1",
+            &[&BASE_SNAPSHOT_ID],
+        )?;
+        log::info!("已清除同步基线快照");
+        Ok(())
+    }
+}
+
+/// 提取数据集中的实体集合，并按ID排序以获得稳定的表示
+pub fn extract_entity_content(data: &serde_json::Value) -> serde_json::Value {
+    let mut content = serde_json::Map::new();
+
+    for collection in SYNC_COLLECTIONS {
+        let mut records = data
+            .get(*collection)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        records.sort_by_key(record_key);
+        content.insert(collection.to_string(), serde_json::Value::Array(records));
+    }
+
+    serde_json::Value::Object(content)
+}
+
+/// 计算数据集实体内容的哈希
+pub fn entity_content_hash(data: &serde_json::Value) -> String {
+    let content = extract_entity_content(data);
+    let content_str = serde_json::to_string(&content).unwrap_or_default();
+    format!("{:x}", md5::compute(content_str.as_bytes()))
+}
+
+/// 获取记录的比较键（优先使用ID）
+pub fn record_key(record: &serde_json::Value) -> String {
+    match record.get("id") {
+        Some(serde_json::Value::String(id)) => id.clone(),
+        Some(other) => other.to_string(),
+        None => record.to_string(),
+    }
+}
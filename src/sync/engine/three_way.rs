@@ -0,0 +1,537 @@
+//! # 三方合并模块
+//!
+//! 以上次成功同步的基线快照为共同祖先，对本地与远程数据进行字段级合并：
+//! - 只有一方修改的字段自动采用修改方的值
+//! - 双方修改为相同值的字段视为一致
+//! - 双方修改为不同值的字段才作为冲突上报
+
+use super::snapshot::{record_key, SYNC_COLLECTIONS};
+use super::types::*;
+use chrono::{DateTime, Local};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// 比较记录是否变更时忽略的字段
+const VOLATILE_FIELDS: &[&str] = &["updated_at"];
+
+/// 三方合并器
+pub struct ThreeWayMerger;
+
+impl ThreeWayMerger {
+    /// 创建新的三方合并器
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 执行三方合并
+    ///
+    /// 非实体字段（版本、导出时间等）取自本地数据集
+    pub fn merge(&self, base: &Value, local: &Value, remote: &Value) -> ThreeWayMergeResult {
+        let mut merged = match local.as_object() {
+            Some(obj) => obj.clone(),
+            None => Map::new(),
+        };
+        let mut result = ThreeWayMergeResult {
+            merged: Value::Null,
+            conflicts: Vec::new(),
+            auto_merged_count: 0,
+            local_changes: 0,
+            remote_changes: 0,
+        };
+
+        for collection in SYNC_COLLECTIONS {
+            let records = self.merge_collection(
+                collection,
+                base.get(*collection),
+                local.get(*collection),
+                remote.get(*collection),
+                &mut result,
+            );
+            merged.insert(collection.to_string(), Value::Array(records));
+        }
+
+        let now = Local::now().to_rfc3339();
+        merged.insert("export_time".to_string(), Value::String(now.clone()));
+        merged.insert("merged_at".to_string(), Value::String(now));
+
+        log::info!(
+            "三方合并完成: 本地变更 {}, 远程变更 {}, 自动合并 {}, 冲突 {}",
+            result.local_changes,
+            result.remote_changes,
+            result.auto_merged_count,
+            result.conflicts.len()
+        );
+
+        result.merged = Value::Object(merged);
+        result
+    }
+
+    /// 按整体策略解决剩余冲突（本地优先或远程优先）
+    ///
+    /// 字段冲突只在自动合并结果上改写冲突字段，删除与修改的冲突整条采用胜出方
+    pub fn resolve_remaining(&self, result: &mut ThreeWayMergeResult, prefer_local: bool) {
+        for conflict in std::mem::take(&mut result.conflicts) {
+            let chosen = if prefer_local {
+                conflict.local.clone()
+            } else {
+                conflict.remote.clone()
+            };
+            let by_field = matches!(
+                conflict.kind,
+                RecordConflictKind::BothModified | RecordConflictKind::BothAdded
+            ) && conflict.field_conflicts.iter().all(|f| !f.field.is_empty());
+
+            let record = if by_field {
+                merged_record(&result.merged, &conflict).map(|mut record| {
+                    if let Some(obj) = record.as_object_mut() {
+                        for field_conflict in &conflict.field_conflicts {
+                            let value = if prefer_local {
+                                &field_conflict.local
+                            } else {
+                                &field_conflict.remote
+                            };
+                            match value {
+                                Some(value) => {
+                                    obj.insert(field_conflict.field.clone(), value.clone())
+                                }
+                                None => obj.remove(&field_conflict.field),
+                            };
+                        }
+                    }
+                    record
+                })
+            } else {
+                None
+            };
+
+            replace_record(
+                &mut result.merged,
+                &conflict.collection,
+                &conflict.record_id,
+                record.or(chosen),
+            );
+        }
+    }
+
+    /// 合并单个数据集合
+    fn merge_collection(
+        &self,
+        collection: &str,
+        base: Option<&Value>,
+        local: Option<&Value>,
+        remote: Option<&Value>,
+        result: &mut ThreeWayMergeResult,
+    ) -> Vec<Value> {
+        let base_map = index_records(base);
+        let local_map = index_records(local);
+        let remote_map = index_records(remote);
+
+        // 保持本地顺序，远程新增的记录追加在后
+        let mut keys: Vec<String> = Vec::new();
+        let mut seen = HashSet::new();
+        for records in [local, remote, base] {
+            for record in records.and_then(|v| v.as_array()).into_iter().flatten() {
+                let key = record_key(record);
+                if seen.insert(key.clone()) {
+                    keys.push(key);
+                }
+            }
+        }
+
+        let mut merged = Vec::new();
+        for key in keys {
+            let b = base_map.get(&key).copied();
+            let l = local_map.get(&key).copied();
+            let r = remote_map.get(&key).copied();
+
+            let local_changed = !same_record(b, l);
+            let remote_changed = !same_record(b, r);
+            if local_changed {
+                result.local_changes += 1;
+            }
+            if remote_changed {
+                result.remote_changes += 1;
+            }
+
+            if let Some(record) = self.merge_record(collection, &key, b, l, r, result) {
+                merged.push(record);
+            }
+        }
+
+        merged
+    }
+
+    /// 合并单条记录，返回合并后的记录（None 表示记录被删除）
+    fn merge_record(
+        &self,
+        collection: &str,
+        key: &str,
+        base: Option<&Value>,
+        local: Option<&Value>,
+        remote: Option<&Value>,
+        result: &mut ThreeWayMergeResult,
+    ) -> Option<Value> {
+        let conflict =
+            |kind: RecordConflictKind, field_conflicts: Vec<FieldConflict>| RecordConflict {
+                collection: collection.to_string(),
+                record_id: key.to_string(),
+                kind,
+                base: base.cloned(),
+                local: local.cloned(),
+                remote: remote.cloned(),
+                field_conflicts,
+            };
+
+        match (local, remote) {
+            (None, None) => None,
+            (Some(l), None) => {
+                if base.is_none() {
+                    // 本地新增
+                    Some(l.clone())
+                } else if same_record(base, local) {
+                    // 远程删除，本地未修改
+                    None
+                } else {
+                    result
+                        .conflicts
+                        .push(conflict(RecordConflictKind::DeletedRemotely, Vec::new()));
+                    Some(l.clone())
+                }
+            }
+            (None, Some(r)) => {
+                if base.is_none() {
+                    // 远程新增
+                    Some(r.clone())
+                } else if same_record(base, remote) {
+                    // 本地删除，远程未修改
+                    None
+                } else {
+                    result
+                        .conflicts
+                        .push(conflict(RecordConflictKind::DeletedLocally, Vec::new()));
+                    Some(r.clone())
+                }
+            }
+            (Some(l), Some(r)) => {
+                if l == r {
+                    return Some(l.clone());
+                }
+                if same_record(base, local) {
+                    return Some(r.clone());
+                }
+                if same_record(base, remote) {
+                    return Some(l.clone());
+                }
+
+                let (record, field_conflicts) = merge_fields(base, l, r);
+                if field_conflicts.is_empty() {
+                    result.auto_merged_count += 1;
+                } else {
+                    let kind = if base.is_none() {
+                        RecordConflictKind::BothAdded
+                    } else {
+                        RecordConflictKind::BothModified
+                    };
+                    result.conflicts.push(conflict(kind, field_conflicts));
+                }
+                Some(record)
+            }
+        }
+    }
+}
+
+impl Default for ThreeWayMerger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 字段级合并，返回合并后的记录与冲突字段（冲突字段暂取本地值）
+fn merge_fields(
+    base: Option<&Value>,
+    local: &Value,
+    remote: &Value,
+) -> (Value, Vec<FieldConflict>) {
+    let empty = Map::new();
+    let base_obj = base.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let (local_obj, remote_obj) = match (local.as_object(), remote.as_object()) {
+        (Some(l), Some(r)) => (l, r),
+        // 非对象记录无法按字段合并
+        _ => {
+            let conflict = FieldConflict {
+                field: String::new(),
+                base: base.cloned(),
+                local: Some(local.clone()),
+                remote: Some(remote.clone()),
+            };
+            return (local.clone(), vec![conflict]);
+        }
+    };
+
+    let mut fields: Vec<&String> = local_obj.keys().collect();
+    for key in remote_obj.keys().chain(base_obj.keys()) {
+        if !fields.contains(&key) {
+            fields.push(key);
+        }
+    }
+
+    let mut merged = Map::new();
+    let mut conflicts = Vec::new();
+
+    for field in fields {
+        let b = base_obj.get(field);
+        let l = local_obj.get(field);
+        let r = remote_obj.get(field);
+
+        let value = if l == r {
+            l
+        } else if VOLATILE_FIELDS.contains(&field.as_str()) {
+            later_timestamp(l, r)
+        } else if l == b {
+            r
+        } else if r == b {
+            l
+        } else {
+            conflicts.push(FieldConflict {
+                field: field.clone(),
+                base: b.cloned(),
+                local: l.cloned(),
+                remote: r.cloned(),
+            });
+            l
+        };
+
+        if let Some(value) = value {
+            merged.insert(field.clone(), value.clone());
+        }
+    }
+
+    (Value::Object(merged), conflicts)
+}
+
+/// 合并结果中冲突记录当前的内容
+fn merged_record(merged: &Value, conflict: &RecordConflict) -> Option<Value> {
+    merged
+        .get(&conflict.collection)
+        .and_then(|v| v.as_array())
+        .and_then(|records| {
+            records
+                .iter()
+                .find(|r| record_key(r) == conflict.record_id)
+                .cloned()
+        })
+}
+
+/// 替换合并结果中的指定记录（None 表示删除）
+pub(crate) fn replace_record(
+    merged: &mut Value,
+    collection: &str,
+    record_id: &str,
+    record: Option<Value>,
+) {
+    let Some(obj) = merged.as_object_mut() else {
+        return;
+    };
+    let records = obj
+        .entry(collection.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let Some(records) = records.as_array_mut() else {
+        return;
+    };
+
+    let position = records.iter().position(|r| record_key(r) == record_id);
+    match (position, record) {
+        (Some(index), Some(record)) => records[index] = record,
+        (Some(index), None) => {
+            records.remove(index);
+        }
+        (None, Some(record)) => records.push(record),
+        (None, None) => {}
+    }
+}
+
+/// 按ID索引记录
+fn index_records(records: Option<&Value>) -> HashMap<String, &Value> {
+    records
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().map(|r| (record_key(r), r)).collect())
+        .unwrap_or_default()
+}
+
+/// 判断两条记录内容是否一致（忽略易变字段）
+fn same_record(a: Option<&Value>, b: Option<&Value>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => match (a.as_object(), b.as_object()) {
+            (Some(a_obj), Some(b_obj)) => {
+                let strip = |obj: &Map<String, Value>| -> Map<String, Value> {
+                    obj.iter()
+                        .filter(|(k, _)| !VOLATILE_FIELDS.contains(&k.as_str()))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect()
+                };
+                strip(a_obj) == strip(b_obj)
+            }
+            _ => a == b,
+        },
+        _ => false,
+    }
+}
+
+/// 取两个时间戳中较新的一个
+fn later_timestamp<'v>(a: Option<&'v Value>, b: Option<&'v Value>) -> Option<&'v Value> {
+    let parse = |v: Option<&Value>| {
+        v.and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+    };
+    match (parse(a), parse(b)) {
+        (Some(ta), Some(tb)) if tb > ta => b,
+        (None, Some(_)) => b,
+        _ => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn dataset(tasks: Value) -> Value {
+        json!({ "tasks": tasks, "version": "0.1.0" })
+    }
+
+    #[test]
+    fn test_non_overlapping_field_changes_auto_merge() {
+        let base = dataset(json!([{ "id": "t1", "name": "写报告", "priority": "low" }]));
+        let local = dataset(json!([{ "id": "t1", "name": "写周报", "priority": "low" }]));
+        let remote = dataset(json!([{ "id": "t1", "name": "写报告", "priority": "high" }]));
+
+        let result = ThreeWayMerger::new().merge(&base, &local, &remote);
+
+        assert!(!result.has_conflicts());
+        assert_eq!(result.auto_merged_count, 1);
+        let task = &result.merged["tasks"][0];
+        assert_eq!(task["name"], "写周报");
+        assert_eq!(task["priority"], "high");
+    }
+
+    #[test]
+    fn test_same_field_changed_differently_is_conflict() {
+        let base = dataset(json!([{ "id": "t1", "name": "A" }]));
+        let local = dataset(json!([{ "id": "t1", "name": "B" }]));
+        let remote = dataset(json!([{ "id": "t1", "name": "C" }]));
+
+        let result = ThreeWayMerger::new().merge(&base, &local, &remote);
+
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.kind, RecordConflictKind::BothModified);
+        assert_eq!(conflict.field_conflicts.len(), 1);
+        assert_eq!(conflict.field_conflicts[0].field, "name");
+        assert_eq!(conflict.field_conflicts[0].base, Some(json!("A")));
+        // 冲突字段暂取本地值
+        assert_eq!(result.merged["tasks"][0]["name"], "B");
+    }
+
+    #[test]
+    fn test_additions_and_deletions() {
+        let base = dataset(json!([
+            { "id": "t1", "name": "保留" },
+            { "id": "t2", "name": "远程删除" }
+        ]));
+        let local = dataset(json!([
+            { "id": "t1", "name": "保留" },
+            { "id": "t2", "name": "远程删除" },
+            { "id": "t3", "name": "本地新增" }
+        ]));
+        let remote = dataset(json!([
+            { "id": "t1", "name": "保留" },
+            { "id": "t4", "name": "远程新增" }
+        ]));
+
+        let result = ThreeWayMerger::new().merge(&base, &local, &remote);
+
+        assert!(!result.has_conflicts());
+        let ids: Vec<&str> = result.merged["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["t1", "t3", "t4"]);
+    }
+
+    #[test]
+    fn test_delete_versus_modify_is_conflict() {
+        let base = dataset(json!([{ "id": "t1", "name": "A" }]));
+        let local = dataset(json!([]));
+        let remote = dataset(json!([{ "id": "t1", "name": "B" }]));
+
+        let result = ThreeWayMerger::new().merge(&base, &local, &remote);
+
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].kind, RecordConflictKind::DeletedLocally);
+        // 修改方的数据暂时保留，避免丢失
+        assert_eq!(result.merged["tasks"][0]["name"], "B");
+    }
+
+    #[test]
+    fn test_resolve_remaining_prefers_remote() {
+        let base = dataset(json!([{ "id": "t1", "name": "A" }]));
+        let local = dataset(json!([{ "id": "t1", "name": "B" }]));
+        let remote = dataset(json!([{ "id": "t1", "name": "C" }]));
+
+        let merger = ThreeWayMerger::new();
+        let mut result = merger.merge(&base, &local, &remote);
+        merger.resolve_remaining(&mut result, false);
+
+        assert!(!result.has_conflicts());
+        assert_eq!(result.merged["tasks"][0]["name"], "C");
+    }
+
+    #[test]
+    fn test_resolve_remaining_keeps_non_conflicting_edits() {
+        let base =
+            dataset(json!([{ "id": "t1", "name": "A", "priority": "low", "status": "todo" }]));
+        let local =
+            dataset(json!([{ "id": "t1", "name": "B", "priority": "high", "status": "todo" }]));
+        let remote =
+            dataset(json!([{ "id": "t1", "name": "C", "priority": "low", "status": "done" }]));
+
+        for prefer_local in [true, false] {
+            let merger = ThreeWayMerger::new();
+            let mut result = merger.merge(&base, &local, &remote);
+            merger.resolve_remaining(&mut result, prefer_local);
+
+            assert!(!result.has_conflicts());
+            let task = &result.merged["tasks"][0];
+            assert_eq!(task["name"], if prefer_local { "B" } else { "C" });
+            assert_eq!(task["priority"], "high");
+            assert_eq!(task["status"], "done");
+        }
+    }
+
+    #[test]
+    fn test_updated_at_takes_later_value() {
+        let base = dataset(json!([{
+            "id": "t1", "name": "A", "priority": "low",
+            "updated_at": "2024-01-01T00:00:00+08:00"
+        }]));
+        let local = dataset(json!([{
+            "id": "t1", "name": "B", "priority": "low",
+            "updated_at": "2024-01-02T00:00:00+08:00"
+        }]));
+        let remote = dataset(json!([{
+            "id": "t1", "name": "A", "priority": "high",
+            "updated_at": "2024-01-03T00:00:00+08:00"
+        }]));
+
+        let result = ThreeWayMerger::new().merge(&base, &local, &remote);
+
+        assert!(!result.has_conflicts());
+        assert_eq!(
+            result.merged["tasks"][0]["updated_at"],
+            "2024-01-03T00:00:00+08:00"
+        );
+    }
+}
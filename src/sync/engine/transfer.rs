@@ -0,0 +1,838 @@
+//! # 数据传输模块
+//!
+//! 负责同步数据的压缩、分块和可恢复传输：
+//! - 数据按内容切分为分块，以 SHA-256 哈希命名，只上传远程缺少的分块
+//! - 每个分块带有描述压缩算法的头部，可选 gzip 压缩
+//! - 远程 data.json 保存分块清单，所有分块上传完成后才写入，保证远程数据始终完整
+//! - 已下载的分块缓存在本地，网络中断后重试时无需重新下载
+//! - 上传前检查数据大小是否超过最大同步文件大小
+
+use crate::errors::{AppError, Result};
+use crate::storage::StorageManager;
+use crate::sync::{SyncConfig, SyncDirection, SyncItem, SyncProvider, SyncStatus};
+use chrono::{DateTime, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 负载头部魔数
+const PAYLOAD_MAGIC: &[u8; 4] = b"LTSY";
+
+/// 负载格式版本
+const PAYLOAD_VERSION: u8 = 1;
+
+/// 负载头部长度（魔数 + 版本 + 编码 + 原始长度）
+const PAYLOAD_HEADER_LEN: usize = 4 + 1 + 1 + 8;
+
+/// 分块清单格式标识
+const MANIFEST_FORMAT: &str = "lifetracker-chunked";
+
+/// 分块最小长度
+const MIN_CHUNK_SIZE: usize = 16 * 1024;
+
+/// 分块最大长度
+const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+/// 分块边界掩码（平均分块约 64KB）
+const CHUNK_MASK: u64 = (1 << 16) - 1;
+
+/// 远程分块目录名
+const CHUNK_DIRECTORY: &str = "chunks";
+
+/// 内容分块使用的 Gear 哈希表
+static GEAR_TABLE: [u64; 256] = gear_table();
+
+/// 生成固定的 Gear 哈希表（splitmix64），保证各设备分块边界一致
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// 压缩算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionCodec {
+    /// 不压缩
+    None,
+    /// gzip 压缩
+    Gzip,
+}
+
+impl CompressionCodec {
+    /// 头部中的编码标识
+    fn to_byte(self) -> u8 {
+        match self {
+            CompressionCodec::None => 0,
+            CompressionCodec::Gzip => 1,
+        }
+    }
+
+    /// 从头部编码标识解析
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(CompressionCodec::None),
+            1 => Ok(CompressionCodec::Gzip),
+            other => Err(AppError::Sync(format!("不支持的压缩编码: {}", other))),
+        }
+    }
+}
+
+/// 分块引用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    /// 原始内容的 SHA-256 哈希
+    pub hash: String,
+    /// 原始长度
+    pub size: u64,
+    /// 编码后长度
+    pub stored_size: u64,
+}
+
+/// 分块清单（保存为远程 data.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    /// 格式标识
+    pub format: String,
+    /// 格式版本
+    pub version: u8,
+    /// 分块使用的压缩算法
+    pub codec: CompressionCodec,
+    /// 原始数据长度
+    pub size: u64,
+    /// 编码后总长度
+    pub stored_size: u64,
+    /// 原始数据的 SHA-256 哈希
+    pub content_hash: String,
+    /// 按顺序排列的分块
+    pub chunks: Vec<ChunkRef>,
+    /// 创建时间
+    pub created_at: DateTime<Local>,
+}
+
+impl ChunkManifest {
+    /// 尝试将数据解析为分块清单
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<Self>(data)
+            .ok()
+            .filter(|manifest| manifest.format == MANIFEST_FORMAT)
+    }
+
+    /// 清单引用的分块哈希
+    fn chunk_hashes(&self) -> HashSet<String> {
+        self.chunks.iter().map(|c| c.hash.clone()).collect()
+    }
+}
+
+/// 传输选项
+#[derive(Debug, Clone)]
+pub struct TransferOptions {
+    /// 压缩算法
+    pub codec: CompressionCodec,
+    /// 最大同步数据大小（字节，0 表示不限制）
+    pub max_size: u64,
+    /// 本地分块缓存目录
+    pub cache_dir: Option<PathBuf>,
+    /// 单个文件的最大重试次数
+    pub max_retries: u32,
+}
+
+impl TransferOptions {
+    /// 根据同步配置创建传输选项
+    pub fn from_config(config: &SyncConfig, storage: &StorageManager) -> Self {
+        Self {
+            codec: if config.compression {
+                CompressionCodec::Gzip
+            } else {
+                CompressionCodec::None
+            },
+            max_size: config.max_file_size as u64 * 1024 * 1024,
+            cache_dir: Some(chunk_cache_dir(storage)),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        Self {
+            codec: CompressionCodec::Gzip,
+            max_size: 0,
+            cache_dir: None,
+            max_retries: 3,
+        }
+    }
+}
+
+/// 传输统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferStats {
+    /// 分块总数
+    pub chunk_count: usize,
+    /// 实际传输的分块数
+    pub transferred_chunks: usize,
+    /// 复用的分块数（远程已存在或本地已缓存）
+    pub reused_chunks: usize,
+    /// 原始数据长度
+    pub original_size: u64,
+    /// 实际传输的字节数
+    pub transferred_bytes: u64,
+}
+
+/// 数据传输器
+pub struct DataTransfer<'a> {
+    provider: &'a dyn SyncProvider,
+    options: TransferOptions,
+}
+
+impl<'a> DataTransfer<'a> {
+    /// 创建新的数据传输器
+    pub fn new(provider: &'a dyn SyncProvider, options: TransferOptions) -> Self {
+        Self { provider, options }
+    }
+
+    /// 创建只用于下载的数据传输器（使用本地分块缓存）
+    pub fn for_download(provider: &'a dyn SyncProvider, storage: &StorageManager) -> Self {
+        Self::new(
+            provider,
+            TransferOptions {
+                cache_dir: Some(chunk_cache_dir(storage)),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// 分块上传数据集，最后写入清单
+    pub async fn upload_dataset(&self, data: &[u8], target: &SyncItem) -> Result<TransferStats> {
+        let chunk_dir = chunk_directory(&target.remote_path);
+
+        // 切分并编码分块
+        let mut chunks = Vec::new();
+        let mut stored_size = 0u64;
+        for chunk in split_chunks(data) {
+            let encoded = encode_payload(chunk, self.options.codec)?;
+            stored_size += encoded.len() as u64;
+            let chunk_ref = ChunkRef {
+                hash: sha256_hex(chunk),
+                size: chunk.len() as u64,
+                stored_size: encoded.len() as u64,
+            };
+            chunks.push((chunk_ref, encoded));
+        }
+
+        self.check_size_limit(data.len() as u64, stored_size)?;
+
+        let previous = self.fetch_manifest(target).await;
+        let existing: HashSet<String> = match self.provider.list_remote_files(&chunk_dir).await {
+            Ok(files) => files.into_iter().map(|f| f.name).collect(),
+            Err(e) => {
+                log::debug!("列出远程分块失败，视为没有已上传的分块: {}", e);
+                HashSet::new()
+            }
+        };
+
+        let mut stats = TransferStats {
+            chunk_count: chunks.len(),
+            original_size: data.len() as u64,
+            ..Default::default()
+        };
+
+        // 只上传远程缺少的分块，中断后重新上传时会跳过已完成的分块
+        let mut uploaded = HashSet::new();
+        for (chunk_ref, encoded) in &chunks {
+            if existing.contains(&chunk_ref.hash) || !uploaded.insert(chunk_ref.hash.clone()) {
+                stats.reused_chunks += 1;
+                continue;
+            }
+            let item = chunk_item(&chunk_dir, chunk_ref, SyncDirection::Upload);
+            self.upload_with_retry(&item, encoded).await?;
+            stats.transferred_chunks += 1;
+            stats.transferred_bytes += encoded.len() as u64;
+        }
+
+        // 所有分块就绪后写入清单
+        let manifest = ChunkManifest {
+            format: MANIFEST_FORMAT.to_string(),
+            version: PAYLOAD_VERSION,
+            codec: self.options.codec,
+            size: data.len() as u64,
+            stored_size,
+            content_hash: sha256_hex(data),
+            chunks: chunks.into_iter().map(|(chunk_ref, _)| chunk_ref).collect(),
+            created_at: Local::now(),
+        };
+        let manifest_data = serde_json::to_vec(&manifest)?;
+        self.upload_with_retry(target, &manifest_data).await?;
+        stats.transferred_bytes += manifest_data.len() as u64;
+
+        // 清理既不属于新清单也不属于上一版清单的分块
+        let mut keep = manifest.chunk_hashes();
+        if let Some(previous) = previous {
+            keep.extend(previous.chunk_hashes());
+        }
+        for name in existing.difference(&keep) {
+            if !is_chunk_name(name) {
+                continue;
+            }
+            let item = SyncItem {
+                remote_path: format!("{}/{}", chunk_dir, name),
+                name: name.clone(),
+                ..chunk_template(SyncDirection::Upload)
+            };
+            if let Err(e) = self.provider.delete_remote_file(&item).await {
+                log::warn!("删除过期分块 {} 失败: {}", name, e);
+            }
+        }
+
+        log::info!(
+            "数据上传完成: {} 个分块, 上传 {}, 复用 {}, 原始 {} 字节, 传输 {} 字节",
+            stats.chunk_count,
+            stats.transferred_chunks,
+            stats.reused_chunks,
+            stats.original_size,
+            stats.transferred_bytes
+        );
+        Ok(stats)
+    }
+
+    /// 下载数据集（兼容分块清单、带头部的负载和旧版纯JSON）
+    pub async fn download_dataset(&self, item: &SyncItem) -> Result<Vec<u8>> {
+        Ok(self.download_dataset_with_stats(item).await?.0)
+    }
+
+    /// 下载数据集并返回传输统计
+    pub async fn download_dataset_with_stats(
+        &self,
+        item: &SyncItem,
+    ) -> Result<(Vec<u8>, TransferStats)> {
+        let raw = self.download_with_retry(item).await?;
+
+        let manifest = match ChunkManifest::parse(&raw) {
+            Some(manifest) => manifest,
+            None => {
+                let data = decode_payload(&raw)?;
+                let stats = TransferStats {
+                    chunk_count: 1,
+                    transferred_chunks: 1,
+                    original_size: data.len() as u64,
+                    transferred_bytes: raw.len() as u64,
+                    ..Default::default()
+                };
+                return Ok((data, stats));
+            }
+        };
+
+        let chunk_dir = chunk_directory(&item.remote_path);
+        let mut stats = TransferStats {
+            chunk_count: manifest.chunks.len(),
+            original_size: manifest.size,
+            transferred_bytes: raw.len() as u64,
+            ..Default::default()
+        };
+        let mut data = Vec::with_capacity(manifest.size as usize);
+
+        for chunk_ref in &manifest.chunks {
+            if let Some(cached) = self.read_cached_chunk(&chunk_ref.hash) {
+                data.extend_from_slice(&cached);
+                stats.reused_chunks += 1;
+                continue;
+            }
+
+            let chunk_item = chunk_item(&chunk_dir, chunk_ref, SyncDirection::Download);
+            let encoded = self.download_with_retry(&chunk_item).await?;
+            let chunk = decode_payload(&encoded)?;
+            if sha256_hex(&chunk) != chunk_ref.hash {
+                return Err(AppError::Sync(format!("分块 {} 校验失败", chunk_ref.hash)));
+            }
+            self.write_cached_chunk(&chunk_ref.hash, &chunk);
+            data.extend_from_slice(&chunk);
+            stats.transferred_chunks += 1;
+            stats.transferred_bytes += encoded.len() as u64;
+        }
+
+        if data.len() as u64 != manifest.size || sha256_hex(&data) != manifest.content_hash {
+            return Err(AppError::Sync("下载的数据与清单不一致".to_string()));
+        }
+
+        self.prune_cache(&manifest.chunk_hashes());
+        log::info!(
+            "数据下载完成: {} 个分块, 下载 {}, 本地复用 {}",
+            stats.chunk_count,
+            stats.transferred_chunks,
+            stats.reused_chunks
+        );
+        Ok((data, stats))
+    }
+
+    /// 检查数据大小限制
+    fn check_size_limit(&self, original_size: u64, stored_size: u64) -> Result<()> {
+        if self.options.max_size > 0 && stored_size > self.options.max_size {
+            return Err(AppError::Sync(format!(
+                "同步数据过大：{:.2} MB（压缩后 {:.2} MB），超过最大同步文件大小 {} MB，请清理数据或调高该设置",
+                original_size as f64 / 1024.0 / 1024.0,
+                stored_size as f64 / 1024.0 / 1024.0,
+                self.options.max_size / 1024 / 1024
+            )));
+        }
+        Ok(())
+    }
+
+    /// 读取远程现有的清单
+    async fn fetch_manifest(&self, target: &SyncItem) -> Option<ChunkManifest> {
+        match self.provider.download_file(target).await {
+            Ok(data) => ChunkManifest::parse(&data),
+            Err(_) => None,
+        }
+    }
+
+    /// 带重试的上传
+    async fn upload_with_retry(&self, item: &SyncItem, data: &[u8]) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.provider.upload_file(item, data).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.options.max_retries && is_retryable(&e) => {
+                    attempt += 1;
+                    log::warn!("上传 {} 失败，第 {} 次重试: {}", item.name, attempt, e);
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 带重试的下载
+    async fn download_with_retry(&self, item: &SyncItem) -> Result<Vec<u8>> {
+        let mut attempt = 0;
+        loop {
+            match self.provider.download_file(item).await {
+                Ok(data) => return Ok(data),
+                Err(e) if attempt < self.options.max_retries && is_retryable(&e) => {
+                    attempt += 1;
+                    log::warn!("下载 {} 失败，第 {} 次重试: {}", item.name, attempt, e);
+                    tokio::time::sleep(retry_delay(attempt)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 读取本地缓存的分块（校验失败视为未缓存）
+    fn read_cached_chunk(&self, hash: &str) -> Option<Vec<u8>> {
+        let path = self.options.cache_dir.as_ref()?.join(hash);
+        let data = std::fs::read(path).ok()?;
+        (sha256_hex(&data) == hash).then_some(data)
+    }
+
+    /// 写入本地分块缓存，失败时只记录日志
+    fn write_cached_chunk(&self, hash: &str, data: &[u8]) {
+        let Some(dir) = &self.options.cache_dir else {
+            return;
+        };
+        if let Err(e) =
+            std::fs::create_dir_all(dir).and_then(|_| std::fs::write(dir.join(hash), data))
+        {
+            log::debug!("写入分块缓存失败: {}", e);
+        }
+    }
+
+    /// 清理不再被清单引用的缓存分块
+    fn prune_cache(&self, keep: &HashSet<String>) {
+        let Some(dir) = &self.options.cache_dir else {
+            return;
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if is_chunk_name(&name) && !keep.contains(&name) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// 为数据添加编码头部并按需压缩
+pub fn encode_payload(data: &[u8], codec: CompressionCodec) -> Result<Vec<u8>> {
+    let mut payload = Vec::with_capacity(PAYLOAD_HEADER_LEN + data.len());
+    payload.extend_from_slice(PAYLOAD_MAGIC);
+    payload.push(PAYLOAD_VERSION);
+    payload.push(codec.to_byte());
+    payload.extend_from_slice(&(data.len() as u64).to_le_bytes());
+
+    match codec {
+        CompressionCodec::None => payload.extend_from_slice(data),
+        CompressionCodec::Gzip => {
+            let mut encoder = GzEncoder::new(payload, flate2::Compression::default());
+            encoder.write_all(data)?;
+            payload = encoder.finish()?;
+        }
+    }
+
+    Ok(payload)
+}
+
+/// 解析编码头部并解压；没有头部的数据原样返回（兼容旧版纯JSON）
+pub fn decode_payload(payload: &[u8]) -> Result<Vec<u8>> {
+    if payload.len() < PAYLOAD_HEADER_LEN || !payload.starts_with(PAYLOAD_MAGIC) {
+        return Ok(payload.to_vec());
+    }
+
+    let version = payload[4];
+    if version != PAYLOAD_VERSION {
+        return Err(AppError::Sync(format!(
+            "不支持的同步数据格式版本: {}",
+            version
+        )));
+    }
+    let codec = CompressionCodec::from_byte(payload[5])?;
+    let mut size_bytes = [0u8; 8];
+    size_bytes.copy_from_slice(&payload[6..PAYLOAD_HEADER_LEN]);
+    let size = u64::from_le_bytes(size_bytes) as usize;
+    let body = &payload[PAYLOAD_HEADER_LEN..];
+
+    let data = match codec {
+        CompressionCodec::None => body.to_vec(),
+        CompressionCodec::Gzip => {
+            let mut data = Vec::with_capacity(size);
+            GzDecoder::new(body)
+                .read_to_end(&mut data)
+                .map_err(|e| AppError::Sync(format!("解压同步数据失败: {}", e)))?;
+            data
+        }
+    };
+
+    if data.len() != size {
+        return Err(AppError::Sync(format!(
+            "同步数据长度不符: 期望 {} 字节，实际 {} 字节",
+            size,
+            data.len()
+        )));
+    }
+    Ok(data)
+}
+
+/// 按内容切分数据（Gear 滚动哈希），插入或修改只影响附近的分块
+pub fn split_chunks(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let end = start + find_chunk_boundary(&data[start..]);
+        chunks.push(&data[start..end]);
+        start = end;
+    }
+    chunks
+}
+
+/// 查找下一个分块边界
+fn find_chunk_boundary(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK_SIZE {
+        return data.len();
+    }
+
+    let limit = data.len().min(MAX_CHUNK_SIZE);
+    let mut hash = 0u64;
+    for (i, byte) in data.iter().enumerate().take(limit).skip(MIN_CHUNK_SIZE) {
+        hash = (hash << 1).wrapping_add(GEAR_TABLE[*byte as usize]);
+        if hash & CHUNK_MASK == 0 {
+            return i + 1;
+        }
+    }
+    limit
+}
+
+/// 计算 SHA-256 十六进制哈希
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// 是否为分块文件名（64位十六进制）
+fn is_chunk_name(name: &str) -> bool {
+    name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// 是否为可重试的错误（网络中断或超时；远程版本冲突等错误不重试）
+fn is_retryable(error: &AppError) -> bool {
+    matches!(error, AppError::Network(_) | AppError::Timeout(_))
+}
+
+/// 重试等待时间（指数退避）
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(500 * 2u64.pow(attempt.saturating_sub(1).min(5)))
+}
+
+/// 清单对应的远程分块目录
+fn chunk_directory(manifest_path: &str) -> String {
+    match manifest_path.rsplit_once('/') {
+        Some((parent, _)) => format!("{}/{}", parent, CHUNK_DIRECTORY),
+        None => CHUNK_DIRECTORY.to_string(),
+    }
+}
+
+/// 本地分块缓存目录（与数据库文件同级）
+pub fn chunk_cache_dir(storage: &StorageManager) -> PathBuf {
+    std::path::Path::new(&storage.get_config().database_path)
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("sync_cache")
+}
+
+/// 分块同步项模板
+fn chunk_template(direction: SyncDirection) -> SyncItem {
+    SyncItem {
+        id: String::new(),
+        name: String::new(),
+        local_path: "local".to_string(),
+        remote_path: String::new(),
+        size: 0,
+        local_modified: Local::now(),
+        remote_modified: None,
+        hash: String::new(),
+        status: SyncStatus::Idle,
+        direction,
+    }
+}
+
+/// 构造分块对应的同步项
+fn chunk_item(chunk_dir: &str, chunk_ref: &ChunkRef, direction: SyncDirection) -> SyncItem {
+    SyncItem {
+        id: chunk_ref.hash.clone(),
+        name: chunk_ref.hash.clone(),
+        remote_path: format!("{}/{}", chunk_dir, chunk_ref.hash),
+        size: chunk_ref.stored_size,
+        hash: chunk_ref.hash.clone(),
+        ..chunk_template(direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// 内存中的同步提供者，可模拟前若干次上传失败
+    #[derive(Default)]
+    struct MemoryProvider {
+        files: Mutex<HashMap<String, Vec<u8>>>,
+        upload_failures: Mutex<u32>,
+        uploads: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SyncProvider for MemoryProvider {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn test_connection(&self) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn list_remote_files(&self, path: &str) -> Result<Vec<SyncItem>> {
+            let prefix = format!("{}/", path);
+            let files = self.files.lock().unwrap();
+            Ok(files
+                .keys()
+                .filter_map(|key| key.strip_prefix(&prefix))
+                .map(|name| SyncItem {
+                    name: name.to_string(),
+                    ..chunk_template(SyncDirection::Download)
+                })
+                .collect())
+        }
+
+        async fn upload_file(&self, item: &SyncItem, data: &[u8]) -> Result<()> {
+            {
+                let mut failures = self.upload_failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(AppError::Network("连接中断".to_string()));
+                }
+            }
+            self.uploads.lock().unwrap().push(item.remote_path.clone());
+            self.files
+                .lock()
+                .unwrap()
+                .insert(item.remote_path.clone(), data.to_vec());
+            Ok(())
+        }
+
+        async fn download_file(&self, item: &SyncItem) -> Result<Vec<u8>> {
+            self.files
+                .lock()
+                .unwrap()
+                .get(&item.remote_path)
+                .cloned()
+                .ok_or_else(|| AppError::NotFound(item.remote_path.clone()))
+        }
+
+        async fn delete_remote_file(&self, item: &SyncItem) -> Result<()> {
+            self.files.lock().unwrap().remove(&item.remote_path);
+            Ok(())
+        }
+
+        async fn create_remote_directory(&self, _path: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_file_metadata(&self, path: &str) -> Result<SyncItem> {
+            Err(AppError::NotFound(path.to_string()))
+        }
+
+        fn clone_provider(&self) -> Box<dyn SyncProvider> {
+            Box::new(MemoryProvider::default())
+        }
+    }
+
+    fn target() -> SyncItem {
+        SyncItem {
+            name: "data.json".to_string(),
+            remote_path: "LifeTracker/data.json".to_string(),
+            ..chunk_template(SyncDirection::Upload)
+        }
+    }
+
+    /// 生成不易压缩的测试数据
+    fn sample_data(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    fn options() -> TransferOptions {
+        TransferOptions {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_payload_round_trip() {
+        let data = br#"{"tasks":[],"categories":[]}"#.repeat(100);
+        for codec in [CompressionCodec::None, CompressionCodec::Gzip] {
+            let encoded = encode_payload(&data, codec).unwrap();
+            assert_eq!(decode_payload(&encoded).unwrap(), data);
+        }
+
+        let compressed = encode_payload(&data, CompressionCodec::Gzip).unwrap();
+        assert!(compressed.len() < data.len());
+        // 没有头部的旧版数据原样返回
+        assert_eq!(
+            decode_payload(b"{\"tasks\":[]}").unwrap(),
+            b"{\"tasks\":[]}"
+        );
+    }
+
+    #[test]
+    fn test_chunks_are_stable_after_append() {
+        let data = sample_data(1024 * 1024, 7);
+        let mut appended = data.clone();
+        appended.extend_from_slice(&sample_data(4096, 9));
+
+        let original: Vec<String> = split_chunks(&data).iter().map(|c| sha256_hex(c)).collect();
+        let changed: Vec<String> = split_chunks(&appended)
+            .iter()
+            .map(|c| sha256_hex(c))
+            .collect();
+
+        assert!(original.len() > 1);
+        assert_eq!(split_chunks(&data).concat(), data);
+        // 追加数据只影响最后的分块
+        assert_eq!(
+            original[..original.len() - 1],
+            changed[..original.len() - 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_reuses_existing_chunks() {
+        let provider = MemoryProvider::default();
+        let transfer = DataTransfer::new(&provider, options());
+
+        let data = sample_data(1024 * 1024, 1);
+        let first = transfer.upload_dataset(&data, &target()).await.unwrap();
+        assert_eq!(first.transferred_chunks, first.chunk_count);
+
+        let mut changed = data.clone();
+        changed.extend_from_slice(b"appended");
+        let second = transfer.upload_dataset(&changed, &target()).await.unwrap();
+        assert!(second.reused_chunks > 0);
+        assert!(second.transferred_chunks < second.chunk_count);
+
+        let downloaded = transfer.download_dataset(&target()).await.unwrap();
+        assert_eq!(downloaded, changed);
+    }
+
+    #[tokio::test]
+    async fn test_upload_retries_after_network_failure() {
+        let provider = MemoryProvider::default();
+        *provider.upload_failures.lock().unwrap() = 2;
+        let transfer = DataTransfer::new(
+            &provider,
+            TransferOptions {
+                max_retries: 2,
+                ..Default::default()
+            },
+        );
+
+        let data = sample_data(64 * 1024, 3);
+        transfer.upload_dataset(&data, &target()).await.unwrap();
+        assert_eq!(transfer.download_dataset(&target()).await.unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_size_limit_is_enforced() {
+        let provider = MemoryProvider::default();
+        let transfer = DataTransfer::new(
+            &provider,
+            TransferOptions {
+                codec: CompressionCodec::None,
+                max_size: 1024 * 1024,
+                ..options()
+            },
+        );
+
+        let data = sample_data(2 * 1024 * 1024, 5);
+        let err = transfer.upload_dataset(&data, &target()).await.unwrap_err();
+        assert!(err.to_string().contains("超过最大同步文件大小"));
+        // 超限时不会上传任何内容
+        assert!(provider.uploads.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_legacy_json() {
+        let provider = MemoryProvider::default();
+        let legacy = br#"{"tasks":[]}"#.to_vec();
+        provider
+            .files
+            .lock()
+            .unwrap()
+            .insert(target().remote_path, legacy.clone());
+
+        let transfer = DataTransfer::new(&provider, options());
+        assert_eq!(transfer.download_dataset(&target()).await.unwrap(), legacy);
+    }
+}
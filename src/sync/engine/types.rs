@@ -3,6 +3,7 @@
 //! 定义同步引擎各模块间共享的数据结构和枚举
 
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 
 /// 数据比较结果
 #[derive(Debug, Clone, PartialEq)]
@@ -148,3 +149,84 @@ impl Default for MergeConfig {
         }
     }
 }
+
+/// 记录级冲突类型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RecordConflictKind {
+    /// 双方修改了同一字段
+    BothModified,
+    /// 本地删除而远程修改
+    DeletedLocally,
+    /// 远程删除而本地修改
+    DeletedRemotely,
+    /// 双方新增了同一ID但内容不同的记录
+    BothAdded,
+}
+
+/// 字段级冲突
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldConflict {
+    /// 字段名
+    pub field: String,
+    /// 共同祖先中的值
+    pub base: Option<serde_json::Value>,
+    /// 本地值
+    pub local: Option<serde_json::Value>,
+    /// 远程值
+    pub remote: Option<serde_json::Value>,
+}
+
+/// 记录级冲突
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordConflict {
+    /// 数据集合名称（如 tasks、notes）
+    pub collection: String,
+    /// 记录ID
+    pub record_id: String,
+    /// 冲突类型
+    pub kind: RecordConflictKind,
+    /// 共同祖先版本
+    pub base: Option<serde_json::Value>,
+    /// 本地版本
+    pub local: Option<serde_json::Value>,
+    /// 远程版本
+    pub remote: Option<serde_json::Value>,
+    /// 冲突字段（删除冲突时为空）
+    pub field_conflicts: Vec<FieldConflict>,
+}
+
+/// 三方合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreeWayMergeResult {
+    /// 合并后的数据集（冲突字段暂取本地值，删除冲突暂保留修改方）
+    pub merged: serde_json::Value,
+    /// 无法自动解决的冲突
+    pub conflicts: Vec<RecordConflict>,
+    /// 自动合并的记录数
+    pub auto_merged_count: usize,
+    /// 本地变更的记录数
+    pub local_changes: usize,
+    /// 远程变更的记录数
+    pub remote_changes: usize,
+}
+
+impl ThreeWayMergeResult {
+    /// 是否存在需要处理的冲突
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// 已解决冲突的记录（写入同步历史）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedConflictRecord {
+    /// 数据集合名称
+    pub collection: String,
+    /// 记录ID
+    pub record_id: String,
+    /// 解决方式（如 keep_local、remote_wins）
+    pub resolution: String,
+    /// 保留版本的来源设备ID
+    #[serde(default)]
+    pub device_id: Option<String>,
+}
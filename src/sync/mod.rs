@@ -107,6 +107,14 @@ pub struct SyncConfig {
     pub max_file_size: u32,
     /// 是否启用压缩
     pub compression: bool,
+    /// 保留的同步前还原点数量（0 表示不创建）
+    #[serde(default = "default_restore_points")]
+    pub restore_points: usize,
+}
+
+/// 默认保留的同步前还原点数量
+fn default_restore_points() -> usize {
+    10
 }
 
 /// 冲突解决策略
@@ -174,6 +182,11 @@ pub enum SyncEvent {
     Completed { result: SyncResult },
     /// 同步失败
     Failed { error: String },
+    /// 连续失败次数过多，自动同步已暂停
+    AutoSyncPaused {
+        consecutive_failures: u32,
+        error: String,
+    },
 }
 
 /// 同步事件监听器
@@ -198,6 +211,7 @@ impl Default for SyncConfig {
             ],
             max_file_size: 10,
             compression: true,
+            restore_points: default_restore_points(),
         }
     }
 }
@@ -286,6 +300,41 @@ pub fn create_webdav_config(
     }
 }
 
+impl From<&crate::config::SyncConfig> for SyncConfig {
+    /// 从应用配置生成同步引擎配置
+    ///
+    /// 密码在应用配置中加密保存，需要调用方解密后写入 settings["password"]
+    fn from(config: &crate::config::SyncConfig) -> Self {
+        let mut settings = HashMap::new();
+        if let Some(url) = &config.webdav_url {
+            settings.insert("url".to_string(), url.clone());
+        }
+        if let Some(username) = &config.webdav_username {
+            settings.insert("username".to_string(), username.clone());
+        }
+        settings.insert("directory".to_string(), config.sync_directory.clone());
+
+        let conflict_strategy = match config.conflict_strategy.as_str() {
+            "local_wins" => ConflictStrategy::LocalWins,
+            "remote_wins" => ConflictStrategy::RemoteWins,
+            "keep_both" => ConflictStrategy::KeepBoth,
+            _ => ConflictStrategy::Manual,
+        };
+
+        SyncConfig {
+            provider: config.provider.clone(),
+            settings,
+            interval: config.sync_interval,
+            auto_sync: config.auto_sync,
+            conflict_strategy,
+            ignore_patterns: config.ignore_patterns.clone(),
+            max_file_size: config.max_sync_file_size,
+            compression: config.enable_compression,
+            ..Default::default()
+        }
+    }
+}
+
 /// 验证同步配置
 pub fn validate_sync_config(config: &SyncConfig) -> Result<()> {
     if config.provider.is_empty() {
@@ -405,4 +454,24 @@ mod tests {
         assert_eq!(config.settings.get("username").unwrap(), "testuser");
         assert_eq!(config.settings.get("directory").unwrap(), "LifeTracker");
     }
+
+    #[test]
+    fn test_config_from_app_settings() {
+        let app_config = crate::config::SyncConfig {
+            enable_compression: false,
+            max_sync_file_size: 25,
+            conflict_strategy: "remote_wins".to_string(),
+            webdav_url: Some("https://example.com/webdav".to_string()),
+            ..Default::default()
+        };
+
+        let config = SyncConfig::from(&app_config);
+        assert!(!config.compression);
+        assert_eq!(config.max_file_size, 25);
+        assert_eq!(config.conflict_strategy, ConflictStrategy::RemoteWins);
+        assert_eq!(
+            config.settings.get("url").unwrap(),
+            "https://example.com/webdav"
+        );
+    }
 }
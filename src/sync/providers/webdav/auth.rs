@@ -0,0 +1,270 @@
+//! # WebDAV 认证
+//!
+//! 默认使用 Basic 认证；服务器返回 Digest 质询后改用 HTTP Digest 认证
+//! （RFC 7616，支持 MD5、MD5-sess、SHA-256 和 qop=auth）
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Digest 摘要算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    /// 解析质询中的算法名称
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Self::Md5),
+            "MD5-SESS" => Some(Self::Md5Sess),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-256-SESS" => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    /// 算法名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    /// 是否为会话算法
+    fn is_session(&self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+
+    /// 计算十六进制摘要
+    fn hash(&self, data: &str) -> String {
+        match self {
+            Self::Md5 | Self::Md5Sess => format!("{:x}", md5::compute(data.as_bytes())),
+            Self::Sha256 | Self::Sha256Sess => hex::encode(Sha256::digest(data.as_bytes())),
+        }
+    }
+}
+
+/// 服务器返回的 Digest 质询
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestChallenge {
+    /// 认证域
+    pub realm: String,
+    /// 服务器随机数
+    pub nonce: String,
+    /// 不透明数据（原样返回）
+    pub opaque: Option<String>,
+    /// 摘要算法
+    pub algorithm: DigestAlgorithm,
+    /// 是否使用 qop=auth
+    pub qop_auth: bool,
+    /// nonce 是否已过期（此时应使用新 nonce 重试）
+    pub stale: bool,
+}
+
+impl DigestChallenge {
+    /// 解析 WWW-Authenticate 头，不是 Digest 质询或不受支持时返回 None
+    pub fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(char::is_whitespace)?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let params = parse_params(params);
+        let algorithm = match params.get("algorithm") {
+            Some(name) => DigestAlgorithm::parse(name)?,
+            None => DigestAlgorithm::Md5,
+        };
+        let qop_auth = match params.get("qop") {
+            Some(qop) => {
+                // 只支持 auth，不支持 auth-int
+                if !qop
+                    .split(',')
+                    .any(|q| q.trim().eq_ignore_ascii_case("auth"))
+                {
+                    return None;
+                }
+                true
+            }
+            None => false,
+        };
+
+        Some(Self {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce")?.clone(),
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop_auth,
+            stale: params
+                .get("stale")
+                .is_some_and(|s| s.eq_ignore_ascii_case("true")),
+        })
+    }
+
+    /// 计算请求摘要
+    pub fn response(
+        &self,
+        method: &str,
+        uri: &str,
+        username: &str,
+        password: &str,
+        nc: &str,
+        cnonce: &str,
+    ) -> String {
+        let algorithm = self.algorithm;
+        let mut ha1 = algorithm.hash(&format!("{}:{}:{}", username, self.realm, password));
+        if algorithm.is_session() {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+
+        if self.qop_auth {
+            algorithm.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, self.nonce, nc, cnonce, ha2
+            ))
+        } else {
+            algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        }
+    }
+
+    /// 生成 Authorization 头
+    pub fn authorization(
+        &self,
+        method: &str,
+        uri: &str,
+        username: &str,
+        password: &str,
+        nonce_count: u32,
+        cnonce: &str,
+    ) -> String {
+        let nc = format!("{:08x}", nonce_count);
+        let response = self.response(method, uri, username, password, &nc, cnonce);
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            username,
+            self.realm,
+            self.nonce,
+            uri,
+            self.algorithm.name(),
+            response
+        );
+        if self.qop_auth {
+            header.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, opaque));
+        }
+        header
+    }
+}
+
+/// 解析认证参数列表（key=value 或 key="value"，逗号分隔，引号内允许逗号）
+pub fn parse_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        // 跳过分隔符
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let key: String = chars
+            .by_ref()
+            .take_while(|c| *c != '=')
+            .collect::<String>()
+            .trim()
+            .to_ascii_lowercase();
+        if key.is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    _ => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.peek() {
+                if *c == ',' {
+                    break;
+                }
+                value.push(*c);
+                chars.next();
+            }
+            value = value.trim().to_string();
+        }
+        params.insert(key, value);
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc2617_example() {
+        let challenge = DigestChallenge::parse(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        )
+        .unwrap();
+
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Md5);
+        assert!(challenge.qop_auth);
+        assert_eq!(
+            challenge.response(
+                "GET",
+                "/dir/index.html",
+                "Mufasa",
+                "Circle Of Life",
+                "00000001",
+                "0a4f113b"
+            ),
+            "6629fae49393a05397450978507c4ef1"
+        );
+
+        let header = challenge.authorization(
+            "GET",
+            "/dir/index.html",
+            "Mufasa",
+            "Circle Of Life",
+            1,
+            "0a4f113b",
+        );
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains("nc=00000001"));
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn test_unsupported_challenges() {
+        assert!(DigestChallenge::parse(r#"Basic realm="dav""#).is_none());
+        assert!(
+            DigestChallenge::parse(r#"Digest realm="dav", nonce="n", qop="auth-int""#).is_none()
+        );
+        assert!(
+            DigestChallenge::parse(r#"Digest realm="dav", nonce="n", algorithm=SHA-512"#).is_none()
+        );
+
+        let stale = DigestChallenge::parse(
+            r#"Digest realm="dav", nonce="n2", algorithm=SHA-256, stale=TRUE"#,
+        )
+        .unwrap();
+        assert!(stale.stale);
+        assert_eq!(stale.algorithm, DigestAlgorithm::Sha256);
+    }
+}
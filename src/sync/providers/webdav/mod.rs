@@ -0,0 +1,1080 @@
+//! # WebDAV 同步提供者
+//!
+//! 实现基于 WebDAV 协议的数据同步功能：
+//! - 按命名空间解析 PROPFIND 的 multistatus 响应
+//! - 基于 ETag 的条件上传（If-Match / If-None-Match），检测其他设备的并发写入
+//! - Basic 认证与 HTTP Digest 认证
+//! - 递归列出文件，服务器不支持 `Depth: infinity` 时逐级列出
+
+mod auth;
+mod multistatus;
+#[cfg(test)]
+mod test_server;
+
+use crate::errors::{AppError, Result};
+use crate::sync::{SyncConfig, SyncDirection, SyncItem, SyncProvider, SyncStatus};
+use crate::utils::crypto::decrypt_password;
+use auth::DigestChallenge;
+use chrono::{DateTime, Local, TimeZone};
+use multistatus::DavResource;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use reqwest::header::{AUTHORIZATION, ETAG, WWW_AUTHENTICATE};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 路径中需要编码的字符
+const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'%');
+
+/// PROPFIND 请求体
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<D:propfind xmlns:D="DAV:">
+    <D:allprop/>
+</D:propfind>"#;
+
+/// WebDAV 同步提供者
+#[derive(Clone)]
+pub struct WebDavProvider {
+    /// HTTP 客户端
+    client: Client,
+    /// 服务器 URL
+    base_url: String,
+    /// 用户名
+    username: String,
+    /// 密码
+    password: String,
+    /// 同步目录
+    directory: String,
+    /// 认证状态（克隆的提供者共享）
+    auth: Arc<Mutex<AuthState>>,
+    /// 已知的远程文件版本，按 URL 索引（克隆的提供者共享）
+    versions: Arc<Mutex<HashMap<String, RemoteVersion>>>,
+}
+
+/// 认证状态
+#[derive(Debug, Default)]
+struct AuthState {
+    /// 服务器要求 Digest 认证时的质询
+    digest: Option<DigestChallenge>,
+    /// 当前 nonce 的使用次数
+    nonce_count: u32,
+}
+
+/// 远程文件版本（用于条件上传）
+#[derive(Debug, Clone, PartialEq)]
+enum RemoteVersion {
+    /// 文件存在，带有 ETag
+    Exists(String),
+    /// 文件不存在
+    Missing,
+}
+
+/// WebDAV 响应解析器
+struct WebDavResponseParser;
+
+/// WebDAV 请求构建器
+#[allow(dead_code)]
+struct WebDavRequestBuilder {
+    client: Client,
+    base_url: String,
+    username: String,
+    password: String,
+}
+
+impl WebDavProvider {
+    /// 创建新的 WebDAV 提供者
+    pub async fn new(config: &SyncConfig) -> Result<Self> {
+        // 验证配置
+        validate_config(config)?;
+
+        // 提取配置参数
+        let base_url = config
+            .settings
+            .get("url")
+            .ok_or_else(|| AppError::Sync("WebDAV URL 未配置".to_string()))?
+            .clone();
+
+        let username = config
+            .settings
+            .get("username")
+            .ok_or_else(|| AppError::Sync("WebDAV 用户名未配置".to_string()))?
+            .clone();
+
+        // 获取密码 - 检查是否需要解密
+        let password = config
+            .settings
+            .get("password")
+            .ok_or_else(|| AppError::Sync("WebDAV 密码未配置".to_string()))?;
+
+        // 检查密码是否已加密（通过检查是否为有效JSON判断）
+        let password = if password.starts_with('[') || password.starts_with('{') {
+            // 可能是加密的JSON数据，尝试解密
+            match decrypt_password(password, "life_tracker_webdav") {
+                Ok(decrypted) => decrypted,
+                Err(e) => {
+                    log::warn!("解密密码失败，将使用原始密码: {}", e);
+                    password.clone()
+                }
+            }
+        } else {
+            // 明文密码，直接使用
+            password.clone()
+        };
+
+        let directory = config
+            .settings
+            .get("directory")
+            .cloned()
+            .unwrap_or_else(|| "LifeTracker".to_string());
+
+        // 创建 HTTP 客户端
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| AppError::Network(format!("创建 HTTP 客户端失败: {}", e)))?;
+
+        Ok(Self {
+            client,
+            base_url,
+            username,
+            password,
+            directory,
+            auth: Arc::new(Mutex::new(AuthState::default())),
+            versions: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// 构建完整的远程路径
+    fn build_remote_path(&self, path: &str) -> String {
+        // 确保base_url不以斜杠结尾
+        let base_url = self.base_url.trim_end_matches('/');
+
+        if path.is_empty() {
+            // 空路径时返回同步目录
+            format!("{}/{}", base_url, self.directory)
+        } else if path.starts_with('/') {
+            // 路径以斜杠开头，直接拼接
+            format!("{}/{}{}", base_url, self.directory, path)
+        } else {
+            // 路径不以斜杠开头，需要添加斜杠
+            format!("{}/{}/{}", base_url, self.directory, path)
+        }
+    }
+
+    /// 构建完整的URL（用于文件操作）
+    ///
+    /// 以服务器根路径开头的路径（如 PROPFIND 返回的 href `/dav/...`）直接与域名组合
+    fn build_full_url(&self, path: &str) -> String {
+        if path.starts_with("http") {
+            // 如果已经是完整URL，直接使用
+            return path.to_string();
+        }
+
+        // base_url 格式: https://dav.jianguoyun.com/dav/
+        if let Some(protocol_end) = self.base_url.find("://") {
+            let after_protocol = &self.base_url[protocol_end + 3..];
+            let (origin, base_path) = match after_protocol.find('/') {
+                Some(domain_end) => (
+                    &self.base_url[..protocol_end + 3 + domain_end],
+                    &after_protocol[domain_end..],
+                ),
+                None => (self.base_url.trim_end_matches('/'), "/"),
+            };
+            let base_path = format!("{}/", base_path.trim_end_matches('/'));
+
+            if path.starts_with(&base_path) {
+                return format!("{}{}", origin, encode_path(path));
+            }
+        }
+
+        // 相对路径，使用 build_remote_path
+        self.build_remote_path(path)
+    }
+
+    /// 发送请求，处理 Basic/Digest 认证
+    ///
+    /// 服务器返回 Digest 质询时切换到 Digest 认证并重试一次
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, String)],
+        body: Option<Vec<u8>>,
+    ) -> Result<reqwest::Response> {
+        let mut retried = false;
+        loop {
+            let mut request = self.client.request(method.clone(), url);
+            for (name, value) in headers {
+                request = request.header(*name, value);
+            }
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+            request = self.authorize(request, &method, url);
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AppError::Network(format!("{} 请求失败: {}", method, e)))?;
+
+            if response.status() == StatusCode::UNAUTHORIZED && !retried {
+                let challenge = response
+                    .headers()
+                    .get_all(WWW_AUTHENTICATE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find_map(DigestChallenge::parse);
+
+                if let Some(challenge) = challenge {
+                    let mut auth = self.auth.lock().unwrap();
+                    // 相同 nonce 再次被拒绝说明凭据错误，不再重试
+                    let same_nonce = auth
+                        .digest
+                        .as_ref()
+                        .is_some_and(|current| current.nonce == challenge.nonce);
+                    if !same_nonce || challenge.stale {
+                        log::info!(
+                            "服务器要求 Digest 认证，使用 {}",
+                            challenge.algorithm.name()
+                        );
+                        auth.digest = Some(challenge);
+                        auth.nonce_count = 0;
+                        retried = true;
+                        continue;
+                    }
+                }
+            }
+
+            return Ok(response);
+        }
+    }
+
+    /// 为请求添加认证信息
+    fn authorize(&self, request: RequestBuilder, method: &Method, url: &str) -> RequestBuilder {
+        let mut auth = self.auth.lock().unwrap();
+        match auth.digest.clone() {
+            Some(challenge) => {
+                auth.nonce_count += 1;
+                let cnonce = format!("{:016x}", rand::random::<u64>());
+                let header = challenge.authorization(
+                    method.as_str(),
+                    &request_uri(url),
+                    &self.username,
+                    &self.password,
+                    auth.nonce_count,
+                    &cnonce,
+                );
+                request.header(AUTHORIZATION, header)
+            }
+            None => request.basic_auth(&self.username, Some(&self.password)),
+        }
+    }
+
+    /// 记录远程文件版本
+    fn remember_version(&self, url: &str, version: Option<RemoteVersion>) {
+        let mut versions = self.versions.lock().unwrap();
+        match version {
+            Some(version) => versions.insert(version_key(url), version),
+            None => versions.remove(&version_key(url)),
+        };
+    }
+
+    /// 查询已知的远程文件版本
+    fn known_version(&self, url: &str) -> Option<RemoteVersion> {
+        self.versions
+            .lock()
+            .unwrap()
+            .get(&version_key(url))
+            .cloned()
+    }
+
+    /// 发送 PROPFIND 请求，返回状态码和响应内容
+    async fn propfind_raw(&self, path: &str, depth: &str) -> Result<(StatusCode, String)> {
+        let url = self.build_full_url(path);
+
+        let response = self
+            .send(
+                Method::from_bytes(b"PROPFIND").unwrap(),
+                &url,
+                &[
+                    ("Depth", depth.to_string()),
+                    ("Content-Type", "application/xml".to_string()),
+                ],
+                Some(PROPFIND_BODY.as_bytes().to_vec()),
+            )
+            .await?;
+
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AppError::Network(format!("读取响应失败: {}", e)))?;
+        Ok((status, text))
+    }
+
+    /// 发送 PROPFIND 请求
+    async fn propfind(&self, path: &str) -> Result<String> {
+        let (status, text) = self.propfind_raw(path, "1").await?;
+
+        if status.is_success() {
+            Ok(text)
+        } else {
+            Err(AppError::Network(format!(
+                "PROPFIND 请求失败，状态码: {}",
+                status
+            )))
+        }
+    }
+
+    /// 发送 MKCOL 请求创建目录
+    async fn mkcol(&self, path: &str) -> Result<()> {
+        let url = self.build_full_url(path);
+
+        log::info!("尝试创建目录: {}", url);
+
+        let response = self
+            .send(Method::from_bytes(b"MKCOL").unwrap(), &url, &[], None)
+            .await?;
+
+        let status = response.status();
+        log::info!("MKCOL 请求响应状态: {}", status);
+
+        if status.is_success() {
+            log::info!("目录创建成功");
+            Ok(())
+        } else if status == 405 {
+            // 405 Method Not Allowed 表示目录已存在
+            log::info!("目录已存在");
+            Ok(())
+        } else if status == 409 {
+            // 409 Conflict 表示父目录不存在
+            log::warn!("父目录不存在，状态码: {}", status);
+            Err(AppError::Network(format!(
+                "创建目录失败：父目录不存在，状态码: {}",
+                status
+            )))
+        } else if status == 403 {
+            // 403 Forbidden 表示权限不足
+            log::warn!("权限不足，状态码: {}", status);
+            Err(AppError::Network(format!(
+                "创建目录失败：权限不足，状态码: {}",
+                status
+            )))
+        } else {
+            log::warn!("创建目录失败，状态码: {}", status);
+            Err(AppError::Network(format!(
+                "创建目录失败，状态码: {}",
+                status
+            )))
+        }
+    }
+
+    /// 发送 PUT 请求上传文件
+    ///
+    /// 已知远程 ETag 时附带 If-Match，已知文件不存在时附带 If-None-Match: *，
+    /// 远程文件在此期间被其他设备修改时返回错误而不是覆盖
+    async fn put_file(&self, path: &str, data: &[u8]) -> Result<()> {
+        let url = self.build_full_url(path);
+
+        let headers = match self.known_version(&url) {
+            Some(RemoteVersion::Exists(etag)) => vec![("If-Match", etag)],
+            Some(RemoteVersion::Missing) => vec![("If-None-Match", "*".to_string())],
+            None => Vec::new(),
+        };
+
+        let response = self
+            .send(Method::PUT, &url, &headers, Some(data.to_vec()))
+            .await?;
+
+        let status = response.status();
+        if status.is_success() {
+            let etag = response_etag(&response).map(RemoteVersion::Exists);
+            self.remember_version(&url, etag);
+            Ok(())
+        } else if status == StatusCode::PRECONDITION_FAILED {
+            Err(AppError::Sync(format!(
+                "远程文件 {} 已被其他设备修改，请重新同步",
+                path
+            )))
+        } else {
+            Err(AppError::Network(format!(
+                "上传文件失败，状态码: {}",
+                status
+            )))
+        }
+    }
+
+    /// 发送 GET 请求下载文件
+    async fn get_file(&self, path: &str) -> Result<Vec<u8>> {
+        let url = self.build_full_url(path);
+
+        let response = self.send(Method::GET, &url, &[], None).await?;
+
+        let status = response.status();
+        if status.is_success() {
+            let etag = response_etag(&response).map(RemoteVersion::Exists);
+            let data = response
+                .bytes()
+                .await
+                .map(|bytes| bytes.to_vec())
+                .map_err(|e| AppError::Network(format!("读取文件内容失败: {}", e)))?;
+            self.remember_version(&url, etag);
+            Ok(data)
+        } else if status == StatusCode::NOT_FOUND {
+            self.remember_version(&url, Some(RemoteVersion::Missing));
+            Err(AppError::NotFound(format!("远程文件不存在: {}", path)))
+        } else {
+            Err(AppError::Network(format!(
+                "下载文件失败，状态码: {}",
+                status
+            )))
+        }
+    }
+
+    /// 发送 DELETE 请求删除文件
+    async fn delete_file(&self, path: &str) -> Result<()> {
+        let url = self.build_full_url(path);
+
+        let response = self.send(Method::DELETE, &url, &[], None).await?;
+
+        if response.status().is_success() || response.status() == 404 {
+            // 404 表示文件不存在，也算删除成功
+            self.remember_version(&url, Some(RemoteVersion::Missing));
+            Ok(())
+        } else {
+            Err(AppError::Network(format!(
+                "删除文件失败，状态码: {}",
+                response.status()
+            )))
+        }
+    }
+
+    /// 将 PROPFIND 结果转换为文件列表，并记录文件版本
+    ///
+    /// 文件名为相对于 root 的路径，目录不包含在结果中
+    fn collect_files(&self, root: &str, resources: Vec<DavResource>) -> Vec<SyncItem> {
+        let root = format!("{}/", root.trim_end_matches('/'));
+        resources
+            .into_iter()
+            .filter(|resource| !resource.is_collection)
+            .map(|resource| {
+                if let Some(etag) = &resource.etag {
+                    let url = self.build_full_url(&resource.href);
+                    self.remember_version(&url, Some(RemoteVersion::Exists(etag.clone())));
+                }
+                let name = resource
+                    .href
+                    .strip_prefix(&root)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| file_name(&resource.href));
+                WebDavResponseParser::to_sync_item(resource, name)
+            })
+            .collect()
+    }
+
+    /// 递归列出目录下的所有文件，文件名为相对路径（如 `chunks/abc`）
+    ///
+    /// 优先使用 `Depth: infinity`，服务器拒绝时逐级发送 `Depth: 1` 请求
+    pub async fn list_remote_files_recursive(&self, path: &str) -> Result<Vec<SyncItem>> {
+        let root = request_path(&self.build_full_url(path));
+
+        let (status, xml) = self.propfind_raw(path, "infinity").await?;
+        if status.is_success() {
+            let resources = WebDavResponseParser::parse_propfind_response(&xml)?;
+            return Ok(self.collect_files(&root, resources));
+        }
+        if !matches!(status.as_u16(), 400 | 403 | 501) {
+            return Err(AppError::Network(format!(
+                "PROPFIND 请求失败，状态码: {}",
+                status
+            )));
+        }
+
+        log::info!("服务器不支持 Depth: infinity（{}），逐级列出目录", status);
+        let mut resources = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = VecDeque::from([root.clone()]);
+        while let Some(directory) = pending.pop_front() {
+            if !visited.insert(directory.trim_end_matches('/').to_string()) {
+                continue;
+            }
+            let xml = self
+                .propfind(&format!("{}/", directory.trim_end_matches('/')))
+                .await?;
+            for resource in WebDavResponseParser::parse_propfind_response(&xml)? {
+                if resource.is_collection {
+                    pending.push_back(resource.href);
+                } else {
+                    resources.push(resource);
+                }
+            }
+        }
+
+        Ok(self.collect_files(&root, resources))
+    }
+
+    /// 测试服务器根目录连接
+    async fn test_server_root(&self) -> Result<bool> {
+        // 直接访问服务器根目录，不包含同步目录
+        log::info!("测试服务器根目录: {}", self.base_url);
+
+        let response = self
+            .send(
+                Method::from_bytes(b"PROPFIND").unwrap(),
+                &self.base_url,
+                &[
+                    ("Depth", "0".to_string()),
+                    ("Content-Type", "application/xml".to_string()),
+                ],
+                Some(
+                    r#"<?xml version="1.0" encoding="utf-8" ?>
+<D:propfind xmlns:D="DAV:">
+    <D:prop>
+        <D:resourcetype/>
+    </D:prop>
+</D:propfind>"#
+                        .as_bytes()
+                        .to_vec(),
+                ),
+            )
+            .await
+            .map_err(|e| AppError::Network(format!("测试服务器根目录失败: {}", e)))?;
+
+        let status = response.status();
+        log::info!("服务器根目录测试响应状态: {}", status);
+
+        // 对于WebDAV服务器，200、207(Multi-Status)都表示成功
+        // 404可能表示路径问题，但服务器可达
+        // 401表示认证失败
+        match status.as_u16() {
+            200 | 207 => {
+                log::info!("服务器根目录访问成功");
+                Ok(true)
+            }
+            404 => {
+                log::info!("根目录返回404，但服务器可达");
+                Ok(true) // 服务器可达，认为基本连接成功
+            }
+            401 => {
+                log::warn!("身份验证失败");
+                Ok(false)
+            }
+            403 => {
+                log::info!("权限限制，但服务器连接正常");
+                Ok(true)
+            }
+            _ => {
+                log::warn!("服务器根目录测试失败，状态码: {}", status);
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SyncProvider for WebDavProvider {
+    fn name(&self) -> &str {
+        "WebDAV"
+    }
+
+    async fn test_connection(&self) -> Result<bool> {
+        // 第一步：测试服务器根目录连接
+        log::info!("测试服务器基本连接...");
+        match self.test_server_root().await {
+            Ok(true) => {
+                log::info!("服务器基本连接正常");
+            }
+            Ok(false) => {
+                log::warn!("服务器基本连接失败");
+                return Ok(false);
+            }
+            Err(e) => {
+                log::warn!("无法连接到服务器: {}", e);
+                return Ok(false);
+            }
+        }
+
+        // 第二步：尝试访问同步目录
+        log::info!("测试同步目录访问...");
+        match self.propfind("").await {
+            Ok(_) => {
+                log::info!("同步目录访问成功");
+                return Ok(true);
+            }
+            Err(e) => {
+                log::warn!("同步目录访问失败: {}", e);
+
+                // 第三步：如果是404错误，尝试创建目录
+                if e.to_string().contains("404") {
+                    log::info!("目录不存在，尝试创建...");
+                    match self.mkcol("").await {
+                        Ok(_) => {
+                            log::info!("目录创建成功，重新测试访问");
+                            // 再次尝试访问
+                            match self.propfind("").await {
+                                Ok(_) => {
+                                    log::info!("目录创建后访问成功");
+                                    return Ok(true);
+                                }
+                                Err(e2) => {
+                                    log::warn!("目录创建后仍然无法访问: {}", e2);
+                                    // 即使无法访问目录，但能创建目录说明连接和权限都正常
+                                    return Ok(true);
+                                }
+                            }
+                        }
+                        Err(create_err) => {
+                            log::warn!("无法创建目录: {}", create_err);
+                            // 如果是权限问题但服务器连接正常，也认为测试成功
+                            if create_err.to_string().contains("403")
+                                || create_err.to_string().contains("405")
+                            {
+                                log::info!("服务器连接正常，但目录操作受限（这是正常的）");
+                                return Ok(true);
+                            }
+                            return Ok(false);
+                        }
+                    }
+                } else if e.to_string().contains("409") {
+                    log::info!("目录冲突，但连接正常");
+                    return Ok(true);
+                } else if e.to_string().contains("403") {
+                    log::info!("权限限制，但连接正常");
+                    return Ok(true);
+                } else {
+                    log::warn!("其他错误，连接失败");
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    async fn list_remote_files(&self, path: &str) -> Result<Vec<SyncItem>> {
+        log::info!("列出远程文件，路径: {}", path);
+        let response_xml = self.propfind(path).await?;
+        log::debug!("PROPFIND 响应内容: {}", response_xml);
+
+        let resources = WebDavResponseParser::parse_propfind_response(&response_xml)?;
+        let files = self.collect_files(&request_path(&self.build_full_url(path)), resources);
+        log::info!("解析到 {} 个远程文件", files.len());
+
+        for file in &files {
+            log::info!("远程文件: {} (大小: {} 字节)", file.name, file.size);
+        }
+
+        Ok(files)
+    }
+
+    async fn upload_file(&self, item: &SyncItem, data: &[u8]) -> Result<()> {
+        // 确保目录存在
+        if let Some(parent) = std::path::Path::new(&item.remote_path).parent() {
+            if let Some(parent_str) = parent.to_str() {
+                self.create_remote_directory(parent_str).await?;
+            }
+        }
+
+        log::info!("上传文件: {}", self.build_full_url(&item.remote_path));
+        self.put_file(&item.remote_path, data).await
+    }
+
+    async fn download_file(&self, item: &SyncItem) -> Result<Vec<u8>> {
+        log::info!("下载文件: {}", self.build_full_url(&item.remote_path));
+        self.get_file(&item.remote_path).await
+    }
+
+    async fn delete_remote_file(&self, item: &SyncItem) -> Result<()> {
+        log::info!("删除文件: {}", self.build_full_url(&item.remote_path));
+        self.delete_file(&item.remote_path).await
+    }
+
+    async fn create_remote_directory(&self, path: &str) -> Result<()> {
+        self.mkcol(path).await
+    }
+
+    async fn get_file_metadata(&self, path: &str) -> Result<SyncItem> {
+        let files = self
+            .list_remote_files(
+                &std::path::Path::new(path)
+                    .parent()
+                    .and_then(|p| p.to_str())
+                    .unwrap_or(""),
+            )
+            .await?;
+
+        let filename = std::path::Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+
+        files
+            .into_iter()
+            .find(|item| item.name == filename)
+            .ok_or_else(|| AppError::Sync(format!("文件 {} 不存在", path)))
+    }
+
+    fn clone_provider(&self) -> Box<dyn SyncProvider> {
+        Box::new(self.clone())
+    }
+}
+
+impl WebDavResponseParser {
+    /// 解析 PROPFIND 响应
+    fn parse_propfind_response(xml: &str) -> Result<Vec<DavResource>> {
+        let resources = multistatus::parse_multistatus(xml)?;
+        log::debug!("解析完成，总共 {} 个项目", resources.len());
+        Ok(resources)
+    }
+
+    /// 将资源转换为同步项
+    fn to_sync_item(resource: DavResource, name: String) -> SyncItem {
+        let size = resource.content_length.unwrap_or(0);
+        let remote_modified = resource
+            .last_modified
+            .as_deref()
+            .and_then(Self::parse_http_date);
+
+        // 优先使用 ETag 作为远程版本标识
+        let hash = resource
+            .etag
+            .clone()
+            .unwrap_or_else(|| format!("{}-{}", name, size));
+
+        SyncItem {
+            id: resource.href.clone(),
+            name,
+            local_path: resource.href.clone(),
+            remote_path: resource.href,
+            size,
+            local_modified: Local::now(),
+            remote_modified,
+            hash,
+            status: SyncStatus::Idle,
+            direction: SyncDirection::Bidirectional,
+        }
+    }
+
+    /// 解析HTTP日期格式
+    fn parse_http_date(date_str: &str) -> Option<DateTime<Local>> {
+        // 尝试解析多种日期格式
+        let formats = [
+            "%a, %d %b %Y %H:%M:%S GMT",
+            "%Y-%m-%dT%H:%M:%SZ",
+            "%Y-%m-%d %H:%M:%S",
+        ];
+
+        for format in &formats {
+            if let Ok(naive_dt) = chrono::NaiveDateTime::parse_from_str(date_str, format) {
+                return Local.from_utc_datetime(&naive_dt).into();
+            }
+        }
+
+        None
+    }
+}
+
+/// 对路径进行百分号编码（保留 `/`）
+fn encode_path(path: &str) -> String {
+    utf8_percent_encode(path, PATH_ENCODE_SET).to_string()
+}
+
+/// URL 中的路径和查询部分（Digest 认证的 uri 参数）
+fn request_uri(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+/// URL 解码后的路径部分
+fn request_path(url: &str) -> String {
+    let path = reqwest::Url::parse(url)
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or_else(|_| url.to_string());
+    multistatus::decode_href(&path)
+}
+
+/// 版本缓存使用的规范化 URL
+fn version_key(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .map(|parsed| parsed.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// 响应中的 ETag
+fn response_etag(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 路径中的文件名
+fn file_name(path: &str) -> String {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// 验证 WebDAV 配置
+pub fn validate_config(config: &SyncConfig) -> Result<()> {
+    if config.provider != "webdav" {
+        return Err(AppError::Validation("不是 WebDAV 配置".to_string()));
+    }
+
+    let url = config
+        .settings
+        .get("url")
+        .ok_or_else(|| AppError::Validation("WebDAV URL 未配置".to_string()))?;
+
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(AppError::Validation("WebDAV URL 格式无效".to_string()));
+    }
+
+    if config.settings.get("username").is_none() {
+        return Err(AppError::Validation("WebDAV 用户名未配置".to_string()));
+    }
+
+    if config.settings.get("password").is_none() {
+        return Err(AppError::Validation("WebDAV 密码未配置".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_server::{TestAuth, TestServer, PASSWORD, USERNAME};
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = SyncConfig::default();
+        config.provider = "webdav".to_string();
+
+        // 测试缺少配置
+        assert!(validate_config(&config).is_err());
+
+        // 添加配置
+        config
+            .settings
+            .insert("url".to_string(), "https://example.com/webdav".to_string());
+        config
+            .settings
+            .insert("username".to_string(), "user".to_string());
+        config
+            .settings
+            .insert("password".to_string(), "pass".to_string());
+
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_xml_value_extraction() {
+        let xml = r#"<D:multistatus xmlns:D="DAV:"><D:response>
+            <D:href>/path/to/file.txt</D:href>
+            <D:propstat><D:prop>
+                <D:getcontentlength>1024</D:getcontentlength>
+            </D:prop></D:propstat>
+        </D:response></D:multistatus>"#;
+
+        let resources = WebDavResponseParser::parse_propfind_response(xml).unwrap();
+        assert_eq!(resources[0].href, "/path/to/file.txt");
+        assert_eq!(resources[0].content_length, Some(1024));
+    }
+
+    #[test]
+    fn test_http_date_parsing() {
+        let date_str = "Mon, 01 Jan 2024 12:00:00 GMT";
+        let parsed = WebDavResponseParser::parse_http_date(date_str);
+        assert!(parsed.is_some());
+    }
+
+    #[test]
+    fn test_remote_path_building() {
+        let config = crate::sync::create_webdav_config(
+            "https://dav.jianguoyun.com/dav/",
+            "user",
+            "pass",
+            "LifeTracker",
+        );
+        let provider = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(WebDavProvider::new(&config))
+            .unwrap();
+
+        assert_eq!(
+            provider.build_full_url("data.json"),
+            "https://dav.jianguoyun.com/dav/LifeTracker/data.json"
+        );
+        assert_eq!(
+            provider.build_full_url("/dav/LifeTracker/my notes.json"),
+            "https://dav.jianguoyun.com/dav/LifeTracker/my%20notes.json"
+        );
+    }
+
+    /// 连接到测试服务器的提供者
+    async fn provider(server: &TestServer) -> WebDavProvider {
+        let config =
+            crate::sync::create_webdav_config(&server.base_url, USERNAME, PASSWORD, "LifeTracker");
+        WebDavProvider::new(&config).await.unwrap()
+    }
+
+    /// 同步目录下的文件项
+    fn item(name: &str) -> SyncItem {
+        SyncItem {
+            id: name.to_string(),
+            name: name.to_string(),
+            local_path: "local".to_string(),
+            remote_path: name.to_string(),
+            size: 0,
+            local_modified: Local::now(),
+            remote_modified: None,
+            hash: String::new(),
+            status: SyncStatus::Idle,
+            direction: SyncDirection::Upload,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_against_server() {
+        let server = TestServer::start(TestAuth::Basic).await;
+        server.state.lock().unwrap().upper_prefix = true;
+        let provider = provider(&server).await;
+        provider.create_remote_directory("").await.unwrap();
+
+        provider
+            .upload_file(&item("data.json"), b"{}")
+            .await
+            .unwrap();
+        provider
+            .upload_file(&item("日记 备份.json"), b"[1]")
+            .await
+            .unwrap();
+
+        let mut files = provider.list_remote_files("").await.unwrap();
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["data.json", "日记 备份.json"]);
+        assert_eq!(files[1].size, 3);
+
+        // 使用列表返回的 href 下载
+        assert_eq!(provider.download_file(&files[1]).await.unwrap(), b"[1]");
+        assert!(matches!(
+            provider.download_file(&item("missing.json")).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writer_is_detected() {
+        let server = TestServer::start(TestAuth::Basic).await;
+        let device_a = provider(&server).await;
+        let device_b = provider(&server).await;
+        device_a.create_remote_directory("").await.unwrap();
+        device_a
+            .upload_file(&item("data.json"), b"v1")
+            .await
+            .unwrap();
+
+        // 两台设备都读取了 v1，B 先写入
+        device_a.download_file(&item("data.json")).await.unwrap();
+        device_b.download_file(&item("data.json")).await.unwrap();
+        device_b
+            .upload_file(&item("data.json"), b"v2-b")
+            .await
+            .unwrap();
+
+        let err = device_a
+            .upload_file(&item("data.json"), b"v2-a")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("已被其他设备修改"));
+        assert_eq!(
+            server
+                .state
+                .lock()
+                .unwrap()
+                .file("/dav/LifeTracker/data.json"),
+            Some(b"v2-b".to_vec())
+        );
+
+        // 重新读取后可以写入
+        device_a.download_file(&item("data.json")).await.unwrap();
+        device_a
+            .upload_file(&item("data.json"), b"v3-a")
+            .await
+            .unwrap();
+
+        // 已确认不存在的文件被其他设备创建时也会检测到
+        assert!(device_a.download_file(&item("devices.json")).await.is_err());
+        device_b
+            .upload_file(&item("devices.json"), b"b")
+            .await
+            .unwrap();
+        assert!(device_a
+            .upload_file(&item("devices.json"), b"a")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_digest_authentication() {
+        let server = TestServer::start(TestAuth::Digest).await;
+        let provider = provider(&server).await;
+
+        provider.create_remote_directory("").await.unwrap();
+        provider
+            .upload_file(&item("data.json"), b"{}")
+            .await
+            .unwrap();
+        assert_eq!(
+            provider.download_file(&item("data.json")).await.unwrap(),
+            b"{}"
+        );
+
+        let state = server.state.lock().unwrap();
+        assert!(state.authorizations[0].starts_with("Basic "));
+        assert!(state.authorizations.last().unwrap().starts_with("Digest "));
+    }
+
+    #[tokio::test]
+    async fn test_recursive_listing_falls_back() {
+        let server = TestServer::start(TestAuth::Basic).await;
+        {
+            let mut state = server.state.lock().unwrap();
+            state.mkdir("/dav/LifeTracker");
+            state.mkdir("/dav/LifeTracker/chunks");
+            state.mkdir("/dav/LifeTracker/chunks/归档");
+            state.put("/dav/LifeTracker/data.json", b"{}");
+            state.put("/dav/LifeTracker/chunks/aa", b"1");
+            state.put("/dav/LifeTracker/chunks/归档/bb", b"22");
+        }
+        let provider = provider(&server).await;
+        let expected = vec!["chunks/aa", "chunks/归档/bb", "data.json"];
+
+        for allow_infinity in [true, false] {
+            server.state.lock().unwrap().allow_infinity = allow_infinity;
+            let files = provider.list_remote_files_recursive("").await.unwrap();
+            let mut names: Vec<_> = files.iter().map(|f| f.name.clone()).collect();
+            names.sort();
+            assert_eq!(names, expected);
+        }
+    }
+}
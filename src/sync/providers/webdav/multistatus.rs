@@ -0,0 +1,203 @@
+//! # WebDAV multistatus 响应解析
+//!
+//! 按 `DAV:` 命名空间解析 PROPFIND 返回的 207 Multi-Status 响应，
+//! 不依赖 `d:`/`D:` 等具体前缀，并对 href 进行百分号解码
+
+use crate::errors::{AppError, Result};
+use percent_encoding::percent_decode_str;
+use roxmltree::Node;
+
+/// WebDAV 命名空间
+const DAV_NAMESPACE: &str = "DAV:";
+
+/// PROPFIND 响应中的单个资源
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DavResource {
+    /// 解码后的服务器路径
+    pub href: String,
+    /// 是否为目录
+    pub is_collection: bool,
+    /// 文件大小
+    pub content_length: Option<u64>,
+    /// 最后修改时间（HTTP 日期格式原文）
+    pub last_modified: Option<String>,
+    /// 实体标签（保留引号，可直接用于 If-Match）
+    pub etag: Option<String>,
+}
+
+/// 解析 multistatus 响应
+pub fn parse_multistatus(xml: &str) -> Result<Vec<DavResource>> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| AppError::Sync(format!("解析 PROPFIND 响应失败: {}", e)))?;
+
+    let root = document.root_element();
+    if !is_dav(root, "multistatus") {
+        return Err(AppError::Sync(format!(
+            "PROPFIND 响应不是 multistatus: {}",
+            root.tag_name().name()
+        )));
+    }
+
+    Ok(root
+        .children()
+        .filter(|node| is_dav(*node, "response"))
+        .filter_map(parse_response)
+        .collect())
+}
+
+/// 解析单个 response 元素
+fn parse_response(node: Node<'_, '_>) -> Option<DavResource> {
+    let href = decode_href(&text_of(dav_child(node, "href")?)?);
+    let mut resource = DavResource {
+        is_collection: href.ends_with('/'),
+        href,
+        ..Default::default()
+    };
+
+    for propstat in node.children().filter(|c| is_dav(*c, "propstat")) {
+        // 只采用状态为 2xx 的属性，404 等状态表示属性不存在
+        let success = dav_child(propstat, "status")
+            .and_then(text_of)
+            .is_none_or(|status| status_is_success(&status));
+        if !success {
+            continue;
+        }
+
+        let Some(prop) = dav_child(propstat, "prop") else {
+            continue;
+        };
+        for property in prop
+            .children()
+            .filter(|c| c.is_element() && c.tag_name().namespace() == Some(DAV_NAMESPACE))
+        {
+            match property.tag_name().name() {
+                "resourcetype" => {
+                    resource.is_collection |= dav_child(property, "collection").is_some()
+                }
+                "getcontentlength" => {
+                    resource.content_length = text_of(property).and_then(|v| v.parse().ok())
+                }
+                "getlastmodified" => resource.last_modified = text_of(property),
+                "getetag" => resource.etag = text_of(property),
+                _ => {}
+            }
+        }
+    }
+
+    Some(resource)
+}
+
+/// 是否为指定名称的 DAV 元素
+fn is_dav(node: Node<'_, '_>, name: &str) -> bool {
+    node.is_element()
+        && node.tag_name().name() == name
+        && node.tag_name().namespace() == Some(DAV_NAMESPACE)
+}
+
+/// 查找指定名称的 DAV 子元素
+fn dav_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is_dav(*child, name))
+}
+
+/// 元素的文本内容（去除首尾空白，空文本返回 None）
+fn text_of(node: Node<'_, '_>) -> Option<String> {
+    let text: String = node
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// 状态行是否表示成功（如 "HTTP/1.1 200 OK"）
+fn status_is_success(status: &str) -> bool {
+    status
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|code| code.starts_with('2'))
+}
+
+/// 解码 href：去掉协议和主机部分，并进行百分号解码
+pub fn decode_href(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(index) => {
+            let rest = &href[index + 3..];
+            rest.find('/').map_or("/", |slash| &rest[slash..])
+        }
+        None => href,
+    };
+    percent_decode_str(path).decode_utf8_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_any_prefix() {
+        let lower = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/LifeTracker/</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/dav/LifeTracker/data.json</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:resourcetype/>
+        <d:getcontentlength>1024</d:getcontentlength>
+        <d:getetag>"abc"</d:getetag>
+        <d:getlastmodified>Mon, 01 Jan 2024 12:00:00 GMT</d:getlastmodified>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let upper = lower.replace("d:", "D:").replace("xmlns:d", "xmlns:D");
+        let unprefixed = lower.replace("d:", "").replace("xmlns:d", "xmlns");
+
+        for xml in [lower.to_string(), upper, unprefixed] {
+            let resources = parse_multistatus(&xml).unwrap();
+            assert_eq!(resources.len(), 2);
+            assert!(resources[0].is_collection);
+            assert_eq!(resources[1].href, "/dav/LifeTracker/data.json");
+            assert!(!resources[1].is_collection);
+            assert_eq!(resources[1].content_length, Some(1024));
+            assert_eq!(resources[1].etag.as_deref(), Some("\"abc\""));
+        }
+    }
+
+    #[test]
+    fn test_encoded_href_and_failed_propstat() {
+        let xml = r#"<multistatus xmlns="DAV:" xmlns:x="urn:other">
+  <response>
+    <href>https://example.com/dav/%E6%97%A5%E8%AE%B0/my%20notes.json</href>
+    <propstat>
+      <prop><getcontentlength>12</getcontentlength><x:getetag>"other"</x:getetag></prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+    <propstat>
+      <prop><getetag>"missing"</getetag></prop>
+      <status>HTTP/1.1 404 Not Found</status>
+    </propstat>
+  </response>
+</multistatus>"#;
+
+        let resources = parse_multistatus(xml).unwrap();
+        assert_eq!(resources[0].href, "/dav/日记/my notes.json");
+        assert_eq!(resources[0].content_length, Some(12));
+        // 其他命名空间的同名属性和 404 状态的属性都被忽略
+        assert_eq!(resources[0].etag, None);
+    }
+
+    #[test]
+    fn test_rejects_invalid_response() {
+        assert!(parse_multistatus("<html><body>502</body></html>").is_err());
+        assert!(parse_multistatus("not xml").is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//! 测试用的本地 WebDAV 服务器
+//!
+//! 在内存中保存文件，支持 PROPFIND/GET/PUT/DELETE/MKCOL、ETag 条件请求、
+//! Basic 与 Digest 认证，并可模拟不支持 `Depth: infinity` 的服务器
+
+use super::auth::{parse_params, DigestChallenge};
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 测试账号
+pub const USERNAME: &str = "tester";
+/// 测试密码
+pub const PASSWORD: &str = "secret";
+/// Digest 认证域
+const REALM: &str = "lifetracker-test";
+/// Digest 随机数
+const NONCE: &str = "4f8b2c1d9e";
+
+/// 认证方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestAuth {
+    Basic,
+    Digest,
+}
+
+/// 服务器状态
+pub struct ServerState {
+    /// 文件内容和 ETag
+    files: BTreeMap<String, (Vec<u8>, String)>,
+    /// 目录（不含末尾斜杠）
+    collections: BTreeSet<String>,
+    /// 认证方式
+    pub auth: TestAuth,
+    /// 是否支持 Depth: infinity
+    pub allow_infinity: bool,
+    /// multistatus 使用大写前缀 D:
+    pub upper_prefix: bool,
+    /// 收到的 Authorization 头
+    pub authorizations: Vec<String>,
+    /// ETag 计数器
+    version: u64,
+}
+
+impl ServerState {
+    /// 读取文件内容
+    pub fn file(&self, path: &str) -> Option<Vec<u8>> {
+        self.files.get(path).map(|(data, _)| data.clone())
+    }
+
+    /// 直接写入文件（模拟其他客户端）
+    pub fn put(&mut self, path: &str, data: &[u8]) {
+        self.version += 1;
+        let etag = format!("\"v{}\"", self.version);
+        self.files.insert(path.to_string(), (data.to_vec(), etag));
+    }
+
+    /// 创建目录
+    pub fn mkdir(&mut self, path: &str) {
+        self.collections
+            .insert(path.trim_end_matches('/').to_string());
+    }
+}
+
+/// 测试服务器
+pub struct TestServer {
+    /// WebDAV 根地址（形如 http://127.0.0.1:port/dav/）
+    pub base_url: String,
+    /// 服务器状态
+    pub state: Arc<Mutex<ServerState>>,
+}
+
+impl TestServer {
+    /// 启动服务器，预先创建 /dav 目录
+    pub async fn start(auth: TestAuth) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let state = Arc::new(Mutex::new(ServerState {
+            files: BTreeMap::new(),
+            collections: BTreeSet::from(["/dav".to_string()]),
+            auth,
+            allow_infinity: true,
+            upper_prefix: false,
+            authorizations: Vec::new(),
+            version: 0,
+        }));
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let _ = handle_connection(stream, state).await;
+                });
+            }
+        });
+
+        Self {
+            base_url: format!("http://{}/dav/", address),
+            state,
+        }
+    }
+}
+
+/// 解析后的请求
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// 响应
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// 处理一个连接上的请求（支持 keep-alive）
+async fn handle_connection(
+    mut stream: TcpStream,
+    state: Arc<Mutex<ServerState>>,
+) -> std::io::Result<()> {
+    let mut buffer = Vec::new();
+    loop {
+        let Some(request) = read_request(&mut stream, &mut buffer).await? else {
+            return Ok(());
+        };
+        let response = handle_request(&request, &mut state.lock().unwrap());
+
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Length: {}\r\n",
+            response.status,
+            reason(response.status),
+            response.body.len()
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&response.body).await?;
+    }
+}
+
+/// 读取一个完整请求，连接关闭时返回 None
+async fn read_request(
+    stream: &mut TcpStream,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<Option<Request>> {
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_ascii_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let body_start = header_end + 4;
+    while buffer.len() < body_start + length {
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+    let body = buffer[body_start..body_start + length].to_vec();
+    buffer.drain(..body_start + length);
+
+    let path = target.split('?').next().unwrap_or_default();
+    Ok(Some(Request {
+        method,
+        path: percent_decode_str(path).decode_utf8_lossy().to_string(),
+        headers,
+        body,
+    }))
+}
+
+/// 处理请求
+fn handle_request(request: &Request, state: &mut ServerState) -> Response {
+    if let Some(response) = check_auth(request, state) {
+        return response;
+    }
+
+    let path = request.path.trim_end_matches('/').to_string();
+    match request.method.as_str() {
+        "PROPFIND" => propfind(request, &path, state),
+        "GET" => match state.files.get(&path) {
+            Some((data, etag)) => Response {
+                status: 200,
+                headers: vec![("ETag", etag.clone())],
+                body: data.clone(),
+            },
+            None => Response::new(404),
+        },
+        "PUT" => put(request, &path, state),
+        "DELETE" => {
+            if state.files.remove(&path).is_some() {
+                Response::new(204)
+            } else {
+                Response::new(404)
+            }
+        }
+        "MKCOL" => {
+            if state.collections.contains(&path) || state.files.contains_key(&path) {
+                Response::new(405)
+            } else if !state.collections.contains(parent(&path)) {
+                Response::new(409)
+            } else {
+                state.collections.insert(path);
+                Response::new(201)
+            }
+        }
+        _ => Response::new(405),
+    }
+}
+
+/// 检查认证，失败时返回 401 响应
+fn check_auth(request: &Request, state: &mut ServerState) -> Option<Response> {
+    let authorization = request
+        .headers
+        .get("authorization")
+        .cloned()
+        .unwrap_or_default();
+    state.authorizations.push(authorization.clone());
+
+    let authorized = match state.auth {
+        TestAuth::Basic => {
+            let expected = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", USERNAME, PASSWORD));
+            authorization == format!("Basic {}", expected)
+        }
+        TestAuth::Digest => authorization
+            .strip_prefix("Digest ")
+            .is_some_and(|params| verify_digest(&request.method, &parse_params(params))),
+    };
+    if authorized {
+        return None;
+    }
+
+    let mut response = Response::new(401);
+    response.headers.push((
+        "WWW-Authenticate",
+        match state.auth {
+            TestAuth::Basic => format!(r#"Basic realm="{}""#, REALM),
+            TestAuth::Digest => format!(
+                r#"Digest realm="{}", qop="auth", nonce="{}", opaque="op4que", algorithm=MD5"#,
+                REALM, NONCE
+            ),
+        },
+    ));
+    Some(response)
+}
+
+/// 校验 Digest 认证参数
+fn verify_digest(method: &str, params: &HashMap<String, String>) -> bool {
+    let challenge = DigestChallenge::parse(&format!(
+        r#"Digest realm="{}", qop="auth", nonce="{}", algorithm=MD5"#,
+        REALM, NONCE
+    ))
+    .unwrap();
+    let (Some(uri), Some(nc), Some(cnonce), Some(response)) = (
+        params.get("uri"),
+        params.get("nc"),
+        params.get("cnonce"),
+        params.get("response"),
+    ) else {
+        return false;
+    };
+
+    params.get("username").map(String::as_str) == Some(USERNAME)
+        && params.get("nonce").map(String::as_str) == Some(NONCE)
+        && params.get("opaque").map(String::as_str) == Some("op4que")
+        && challenge.response(method, uri, USERNAME, PASSWORD, nc, cnonce) == *response
+}
+
+/// 处理 PUT（支持 If-Match / If-None-Match）
+fn put(request: &Request, path: &str, state: &mut ServerState) -> Response {
+    if !state.collections.contains(parent(path)) {
+        return Response::new(409);
+    }
+
+    let current = state.files.get(path).map(|(_, etag)| etag.clone());
+    if let Some(expected) = request.headers.get("if-match") {
+        if current.as_deref() != Some(expected.as_str()) {
+            return Response::new(412);
+        }
+    }
+    if request.headers.get("if-none-match").map(String::as_str) == Some("*") && current.is_some() {
+        return Response::new(412);
+    }
+
+    let created = current.is_none();
+    state.put(path, &request.body);
+    Response {
+        status: if created { 201 } else { 204 },
+        headers: vec![("ETag", state.files[path].1.clone())],
+        body: Vec::new(),
+    }
+}
+
+/// 处理 PROPFIND
+fn propfind(request: &Request, path: &str, state: &ServerState) -> Response {
+    let depth = request
+        .headers
+        .get("depth")
+        .map(String::as_str)
+        .unwrap_or("infinity");
+    if depth == "infinity" && !state.allow_infinity {
+        return Response {
+            status: 403,
+            headers: Vec::new(),
+            body: br#"<?xml version="1.0"?><d:error xmlns:d="DAV:"><d:propfind-finite-depth/></d:error>"#
+                .to_vec(),
+        };
+    }
+
+    let is_collection = state.collections.contains(path);
+    if !is_collection && !state.files.contains_key(path) {
+        return Response::new(404);
+    }
+
+    let p = if state.upper_prefix { "D" } else { "d" };
+    let mut xml =
+        format!(r#"<?xml version="1.0" encoding="utf-8"?><{p}:multistatus xmlns:{p}="DAV:">"#);
+    let within = |candidate: &str| match depth {
+        "0" => candidate == path,
+        "1" => candidate == path || parent(candidate) == path,
+        _ => candidate == path || candidate.starts_with(&format!("{}/", path)),
+    };
+
+    for collection in state.collections.iter().filter(|c| within(c)) {
+        xml.push_str(&format!(
+            "<{p}:response><{p}:href>{}/</{p}:href><{p}:propstat><{p}:prop>\
+             <{p}:resourcetype><{p}:collection/></{p}:resourcetype></{p}:prop>\
+             <{p}:status>HTTP/1.1 200 OK</{p}:status></{p}:propstat></{p}:response>",
+            encode_path(collection)
+        ));
+    }
+    for (file, (data, etag)) in state.files.iter().filter(|(f, _)| within(f)) {
+        xml.push_str(&format!(
+            "<{p}:response><{p}:href>{}</{p}:href><{p}:propstat><{p}:prop><{p}:resourcetype/>\
+             <{p}:getcontentlength>{}</{p}:getcontentlength><{p}:getetag>{}</{p}:getetag>\
+             <{p}:getlastmodified>Mon, 01 Jan 2024 12:00:00 GMT</{p}:getlastmodified></{p}:prop>\
+             <{p}:status>HTTP/1.1 200 OK</{p}:status></{p}:propstat>\
+             <{p}:propstat><{p}:prop><{p}:getcontenttype/></{p}:prop>\
+             <{p}:status>HTTP/1.1 404 Not Found</{p}:status></{p}:propstat></{p}:response>",
+            encode_path(file),
+            data.len(),
+            etag.replace('"', "&quot;")
+        ));
+    }
+    xml.push_str(&format!("</{p}:multistatus>"));
+
+    Response {
+        status: 207,
+        headers: vec![("Content-Type", "application/xml".to_string())],
+        body: xml.into_bytes(),
+    }
+}
+
+/// 父路径
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// 对路径的每一段进行百分号编码
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, NON_ALPHANUMERIC).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 状态码描述
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        207 => "Multi-Status",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        412 => "Precondition Failed",
+        _ => "Unknown",
+    }
+}
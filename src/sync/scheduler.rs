@@ -1,15 +1,389 @@
 //! # 同步调度器模块
 //!
-//! 管理定时同步任务
+//! 管理定时同步任务，并在本地数据变更后（防抖）、同步失败后（指数退避）
+//! 和应用退出时触发同步
 
 use crate::errors::{AppError, Result};
-use crate::sync::SyncConfig;
+use crate::storage::Database;
+use crate::sync::{SyncConfig, SyncEvent, SyncEventListener};
 use chrono::{DateTime, Local};
+use futures::future::BoxFuture;
+use rand::Rng;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::time::interval;
+use tokio::sync::Notify;
+use tokio::time::{interval, sleep, Instant};
 use tokio_cron_scheduler::{Job, JobScheduler};
 
+/// 默认防抖时间：最后一次本地修改后等待该时间再同步
+const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// 连续修改时最长等待的防抖周期数，避免持续编辑时一直不同步
+const MAX_DEBOUNCE_PERIODS: u32 = 6;
+
+/// 不触发同步的表（同步自身维护的数据）
+const IGNORED_TABLES: &[&str] = &[
+    "settings",
+    "sync_snapshots",
+    "sync_history",
+    "schema_version",
+    "audit_log",
+];
+
+/// 同步任务，由调度器在需要同步时调用
+pub type SyncTask = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// 触发同步的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncTrigger {
+    /// 定时同步
+    Interval,
+    /// 本地数据变更
+    LocalChange,
+    /// 失败后重试
+    Retry,
+    /// 手动触发
+    Manual,
+    /// 应用退出
+    Exit,
+}
+
+impl SyncTrigger {
+    /// 是否为自动触发（自动同步暂停时不执行）
+    pub fn is_automatic(&self) -> bool {
+        matches!(self, Self::Interval | Self::LocalChange | Self::Retry)
+    }
+}
+
+/// 失败重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 首次重试延迟
+    pub initial_delay: Duration,
+    /// 最大重试延迟
+    pub max_delay: Duration,
+    /// 随机抖动比例（0.2 表示 ±20%）
+    pub jitter: f64,
+    /// 连续失败达到该次数后暂停自动同步
+    pub max_consecutive_failures: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(30 * 60),
+            jitter: 0.2,
+            max_consecutive_failures: 5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 连续失败 `failures` 次后的重试延迟（指数退避并加入随机抖动）
+    pub fn delay_for(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(16);
+        let delay = self
+            .initial_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return delay;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        delay.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+/// 重试状态
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetryState {
+    /// 连续失败次数
+    pub consecutive_failures: u32,
+    /// 下次重试时间
+    pub next_retry_time: Option<DateTime<Local>>,
+    /// 最后一次失败的错误信息
+    pub last_error: Option<String>,
+    /// 自动同步是否已暂停
+    pub auto_sync_paused: bool,
+}
+
+/// 本地数据变更通知句柄
+///
+/// 可以克隆后交给数据库变更回调或业务代码，多次通知在防抖期间合并为一次同步
+#[derive(Clone, Default)]
+pub struct ChangeNotifier {
+    /// 变更信号
+    notify: Arc<Notify>,
+    /// 同步进行中时忽略变更（同步导入的数据不应再次触发同步）
+    suppressed: Arc<AtomicBool>,
+}
+
+impl ChangeNotifier {
+    /// 通知本地数据已变更
+    pub fn notify_change(&self) {
+        if !self.suppressed.load(Ordering::SeqCst) {
+            self.notify.notify_one();
+        }
+    }
+
+    /// 通知数据表已变更，同步自身维护的表会被忽略
+    pub fn notify_table_change(&self, table: &str) {
+        if !IGNORED_TABLES.contains(&table) {
+            self.notify_change();
+        }
+    }
+
+    /// 监听数据库写入，每次写入业务数据表时通知变更
+    pub fn watch_database(&self, database: &Database) -> Result<()> {
+        let notifier = self.clone();
+        database
+            .get_connection()?
+            .set_change_hook(Some(move |table: &str| notifier.notify_table_change(table)))
+    }
+
+    /// 等待下一次变更
+    async fn changed(&self) {
+        self.notify.notified().await
+    }
+}
+
+/// 同步执行器内部状态
+#[derive(Debug, Default)]
+struct RunnerState {
+    /// 重试状态
+    retry: RetryState,
+    /// 上次同步成功时间
+    last_sync_time: Option<DateTime<Local>>,
+}
+
+/// 同步执行器
+///
+/// 串行执行同步任务，记录连续失败次数并计算下次重试时间，
+/// 连续失败达到上限时暂停自动同步并发送 [`SyncEvent::AutoSyncPaused`]
+#[derive(Clone)]
+pub struct SyncRunner {
+    /// 同步任务
+    task: SyncTask,
+    /// 重试策略
+    policy: RetryPolicy,
+    /// 执行状态
+    state: Arc<Mutex<RunnerState>>,
+    /// 事件监听器
+    listeners: Arc<Mutex<Vec<Box<dyn SyncEventListener>>>>,
+    /// 保证同一时间只有一个同步在执行
+    guard: Arc<tokio::sync::Mutex<()>>,
+    /// 本地变更通知
+    changes: ChangeNotifier,
+}
+
+impl SyncRunner {
+    /// 创建同步执行器
+    pub fn new(task: SyncTask, policy: RetryPolicy) -> Self {
+        Self {
+            task,
+            policy,
+            state: Arc::new(Mutex::new(RunnerState::default())),
+            listeners: Arc::new(Mutex::new(Vec::new())),
+            guard: Arc::new(tokio::sync::Mutex::new(())),
+            changes: ChangeNotifier::default(),
+        }
+    }
+
+    /// 由同步回调创建执行器
+    pub fn from_fn<F>(callback: F, policy: RetryPolicy) -> Self
+    where
+        F: Fn() -> Result<()> + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        let task: SyncTask = Arc::new(move || {
+            let callback = callback.clone();
+            Box::pin(async move { callback() })
+        });
+        Self::new(task, policy)
+    }
+
+    /// 添加事件监听器
+    pub fn add_listener(&self, listener: Box<dyn SyncEventListener>) {
+        self.listeners.lock().unwrap().push(listener);
+    }
+
+    /// 发送事件
+    fn emit_event(&self, event: SyncEvent) {
+        for listener in self.listeners.lock().unwrap().iter() {
+            listener.on_sync_event(event.clone());
+        }
+    }
+
+    /// 获取本地变更通知句柄
+    pub fn change_notifier(&self) -> ChangeNotifier {
+        self.changes.clone()
+    }
+
+    /// 获取重试策略
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// 获取重试状态
+    pub fn retry_state(&self) -> RetryState {
+        self.state.lock().unwrap().retry.clone()
+    }
+
+    /// 获取上次同步成功时间
+    pub fn last_sync_time(&self) -> Option<DateTime<Local>> {
+        self.state.lock().unwrap().last_sync_time
+    }
+
+    /// 执行一次同步
+    ///
+    /// 自动同步已暂停时自动触发的同步会被拒绝；手动同步成功后自动同步恢复
+    pub async fn run(&self, trigger: SyncTrigger) -> Result<()> {
+        if trigger.is_automatic() && self.retry_state().auto_sync_paused {
+            return Err(AppError::Sync(
+                "连续同步失败次数过多，自动同步已暂停".to_string(),
+            ));
+        }
+
+        let _guard = self.guard.lock().await;
+        log::info!("执行同步（{:?}）", trigger);
+
+        self.changes.suppressed.store(true, Ordering::SeqCst);
+        let result = (self.task)().await;
+        self.changes.suppressed.store(false, Ordering::SeqCst);
+
+        match &result {
+            Ok(()) => {
+                let mut state = self.state.lock().unwrap();
+                state.retry = RetryState::default();
+                state.last_sync_time = Some(Local::now());
+            }
+            Err(e) => self.record_failure(e),
+        }
+        result
+    }
+
+    /// 记录同步失败，计算下次重试时间或暂停自动同步
+    fn record_failure(&self, error: &AppError) {
+        let paused = {
+            let mut state = self.state.lock().unwrap();
+            let retry = &mut state.retry;
+            retry.consecutive_failures += 1;
+            retry.last_error = Some(error.to_string());
+
+            if retry.consecutive_failures >= self.policy.max_consecutive_failures {
+                retry.auto_sync_paused = true;
+                retry.next_retry_time = None;
+                log::warn!(
+                    "同步连续失败 {} 次，已暂停自动同步: {}",
+                    retry.consecutive_failures,
+                    error
+                );
+                Some(retry.consecutive_failures)
+            } else {
+                let delay = self.policy.delay_for(retry.consecutive_failures);
+                retry.next_retry_time = chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|delay| Local::now() + delay);
+                log::warn!(
+                    "同步失败（第 {} 次），{:?} 后重试: {}",
+                    retry.consecutive_failures,
+                    delay,
+                    error
+                );
+                None
+            }
+        };
+
+        if let Some(consecutive_failures) = paused {
+            self.emit_event(SyncEvent::AutoSyncPaused {
+                consecutive_failures,
+                error: error.to_string(),
+            });
+        }
+    }
+
+    /// 暂停自动同步
+    pub fn pause(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.retry.auto_sync_paused = true;
+        state.retry.next_retry_time = None;
+    }
+
+    /// 恢复自动同步并清除失败计数
+    pub fn resume(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.retry = RetryState::default();
+    }
+
+    /// 应用退出时执行最后一次同步，超时后放弃
+    pub async fn sync_on_exit(&self, timeout: Duration) -> Result<()> {
+        match tokio::time::timeout(timeout, self.run(SyncTrigger::Exit)).await {
+            Ok(result) => result,
+            Err(_) => Err(AppError::Timeout(format!(
+                "退出同步超时（{} 秒）",
+                timeout.as_secs()
+            ))),
+        }
+    }
+
+    /// 距下次重试的等待时间
+    fn retry_delay(&self) -> Option<Duration> {
+        let retry = self.retry_state();
+        if retry.auto_sync_paused {
+            return None;
+        }
+        retry
+            .next_retry_time
+            .map(|time| (time - Local::now()).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+/// 后台同步循环：处理定时同步、本地变更防抖和失败重试，直到收到停止信号
+async fn drive(
+    runner: SyncRunner,
+    sync_interval: Option<Duration>,
+    debounce: Duration,
+    shutdown: Arc<Notify>,
+) {
+    let mut interval_timer = sync_interval.map(interval);
+    let changes = runner.change_notifier();
+
+    loop {
+        let retry_delay = runner.retry_delay();
+        let trigger = tokio::select! {
+            _ = shutdown.notified() => break,
+            _ = async { interval_timer.as_mut().unwrap().tick().await }, if interval_timer.is_some() => {
+                SyncTrigger::Interval
+            }
+            _ = sleep(retry_delay.unwrap_or_default()), if retry_delay.is_some() => SyncTrigger::Retry,
+            _ = changes.changed() => {
+                // 防抖：最后一次变更后等待 debounce，持续变更时最多等待若干个周期
+                let deadline = Instant::now() + debounce * MAX_DEBOUNCE_PERIODS;
+                loop {
+                    let wait = debounce.min(deadline.saturating_duration_since(Instant::now()));
+                    tokio::select! {
+                        _ = sleep(wait) => break,
+                        _ = changes.changed() => {}
+                    }
+                }
+                SyncTrigger::LocalChange
+            }
+        };
+
+        if trigger.is_automatic() && runner.retry_state().auto_sync_paused {
+            log::debug!("自动同步已暂停，忽略 {:?} 触发", trigger);
+            continue;
+        }
+        if let Err(e) = runner.run(trigger).await {
+            log::error!("自动同步执行失败: {}", e);
+        }
+    }
+}
+
 /// 同步调度器
 pub struct SyncScheduler {
     /// 调度器实例
@@ -18,6 +392,14 @@ pub struct SyncScheduler {
     is_running: Arc<Mutex<bool>>,
     /// 下次同步时间
     next_sync_time: Arc<Mutex<Option<DateTime<Local>>>>,
+    /// 同步间隔（分钟）
+    interval_minutes: Arc<Mutex<u32>>,
+    /// 同步执行器
+    runner: Option<SyncRunner>,
+    /// 本地变更防抖时间
+    debounce: Duration,
+    /// 后台循环停止信号
+    shutdown: Arc<Notify>,
 }
 
 /// 调度器状态
@@ -31,6 +413,8 @@ pub struct SchedulerStatus {
     pub last_sync_time: Option<DateTime<Local>>,
     /// 同步间隔（分钟）
     pub interval_minutes: u32,
+    /// 失败重试状态
+    pub retry: RetryState,
 }
 
 impl SyncScheduler {
@@ -44,9 +428,28 @@ impl SyncScheduler {
             scheduler,
             is_running: Arc::new(Mutex::new(false)),
             next_sync_time: Arc::new(Mutex::new(None)),
+            interval_minutes: Arc::new(Mutex::new(30)),
+            runner: None,
+            debounce: DEFAULT_DEBOUNCE,
+            shutdown: Arc::new(Notify::new()),
         })
     }
 
+    /// 设置同步执行器（需在启动前设置）
+    pub fn set_runner(&mut self, runner: SyncRunner) {
+        self.runner = Some(runner);
+    }
+
+    /// 设置本地变更防抖时间
+    pub fn set_debounce(&mut self, debounce: Duration) {
+        self.debounce = debounce;
+    }
+
+    /// 获取本地变更通知句柄
+    pub fn change_notifier(&self) -> Option<ChangeNotifier> {
+        self.runner.as_ref().map(|runner| runner.change_notifier())
+    }
+
     /// 启动调度器
     pub async fn start(&self) -> Result<()> {
         log::info!("启动同步调度器");
@@ -64,6 +467,16 @@ impl SyncScheduler {
             .await
             .map_err(|e| AppError::System(format!("启动调度器失败: {}", e)))?;
 
+        // 定时同步由 cron 任务负责，后台循环只处理本地变更和失败重试
+        if let Some(runner) = &self.runner {
+            tokio::spawn(drive(
+                runner.clone(),
+                None,
+                self.debounce,
+                self.shutdown.clone(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -75,6 +488,7 @@ impl SyncScheduler {
             let mut is_running = self.is_running.lock().unwrap();
             *is_running = false;
         }
+        self.shutdown.notify_one();
 
         self.scheduler
             .shutdown()
@@ -84,6 +498,15 @@ impl SyncScheduler {
         Ok(())
     }
 
+    /// 应用退出时停止调度器并执行最后一次同步
+    pub async fn shutdown(&mut self, timeout: Duration) -> Result<()> {
+        self.stop().await?;
+        match &self.runner {
+            Some(runner) => runner.sync_on_exit(timeout).await,
+            None => Ok(()),
+        }
+    }
+
     /// 添加定时同步任务
     pub async fn schedule_sync(&self, config: &SyncConfig) -> Result<String> {
         if !config.auto_sync {
@@ -94,13 +517,16 @@ impl SyncScheduler {
         if interval_minutes < 5 {
             return Err(AppError::Validation("同步间隔不能小于5分钟".to_string()));
         }
+        *self.interval_minutes.lock().unwrap() = interval_minutes;
 
         // 创建cron表达式，每N分钟执行一次
         let cron_expr = format!("0 */{} * * * *", interval_minutes);
 
         let next_sync_time = self.next_sync_time.clone();
+        let runner = self.runner.clone();
         let job = Job::new_async(cron_expr.as_str(), move |_uuid, _l| {
             let next_sync_time = next_sync_time.clone();
+            let runner = runner.clone();
             Box::pin(async move {
                 log::info!("执行定时同步任务");
 
@@ -111,9 +537,14 @@ impl SyncScheduler {
                         Some(Local::now() + chrono::Duration::minutes(interval_minutes as i64));
                 }
 
-                // TODO: 在这里调用同步引擎执行同步
-                // 由于需要访问存储管理器等，这里需要通过某种方式传递依赖
-                log::warn!("定时同步功能需要集成到应用状态中");
+                match runner {
+                    Some(runner) => {
+                        if let Err(e) = runner.run(SyncTrigger::Interval).await {
+                            log::error!("定时同步执行失败: {}", e);
+                        }
+                    }
+                    None => log::warn!("未设置同步执行器，跳过定时同步"),
+                }
             })
         })
         .map_err(|e| AppError::System(format!("创建定时任务失败: {}", e)))?;
@@ -158,11 +589,10 @@ impl SyncScheduler {
     pub async fn trigger_sync(&self) -> Result<()> {
         log::info!("手动触发同步");
 
-        // TODO: 在这里调用同步引擎执行同步
-        // 由于需要访问存储管理器等，这里需要通过某种方式传递依赖
-        log::warn!("手动同步功能需要集成到应用状态中");
-
-        Ok(())
+        match &self.runner {
+            Some(runner) => runner.run(SyncTrigger::Manual).await,
+            None => Err(AppError::Sync("未设置同步执行器".to_string())),
+        }
     }
 
     /// 获取调度器状态
@@ -180,8 +610,13 @@ impl SyncScheduler {
         SchedulerStatus {
             is_running,
             next_sync_time,
-            last_sync_time: None, // TODO: 实现最后同步时间跟踪
-            interval_minutes: 30, // TODO: 从配置中获取
+            last_sync_time: self.runner.as_ref().and_then(|r| r.last_sync_time()),
+            interval_minutes: *self.interval_minutes.lock().unwrap(),
+            retry: self
+                .runner
+                .as_ref()
+                .map(|r| r.retry_state())
+                .unwrap_or_default(),
         }
     }
 
@@ -198,23 +633,23 @@ impl SyncScheduler {
         Ok(())
     }
 
-    /// 暂停调度器
+    /// 暂停自动同步（定时、变更和重试触发均不执行，手动同步不受影响）
     pub async fn pause(&self) -> Result<()> {
         log::info!("暂停同步调度器");
 
-        // TODO: 实现暂停功能
-        // tokio-cron-scheduler 可能不直接支持暂停，需要通过移除任务来实现
-
+        if let Some(runner) = &self.runner {
+            runner.pause();
+        }
         Ok(())
     }
 
-    /// 恢复调度器
+    /// 恢复自动同步
     pub async fn resume(&self) -> Result<()> {
         log::info!("恢复同步调度器");
 
-        // TODO: 实现恢复功能
-        // 重新添加之前暂停的任务
-
+        if let Some(runner) = &self.runner {
+            runner.resume();
+        }
         Ok(())
     }
 }
@@ -225,6 +660,14 @@ pub struct SimpleSyncScheduler {
     is_running: Arc<Mutex<bool>>,
     /// 同步间隔
     interval: Duration,
+    /// 本地变更防抖时间
+    debounce: Duration,
+    /// 重试策略
+    retry_policy: RetryPolicy,
+    /// 当前的同步执行器
+    runner: Mutex<Option<SyncRunner>>,
+    /// 后台循环停止信号
+    shutdown: Arc<Notify>,
 }
 
 impl SimpleSyncScheduler {
@@ -233,14 +676,36 @@ impl SimpleSyncScheduler {
         Self {
             is_running: Arc::new(Mutex::new(false)),
             interval: Duration::from_secs(interval_minutes as u64 * 60),
+            debounce: DEFAULT_DEBOUNCE,
+            retry_policy: RetryPolicy::default(),
+            runner: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
         }
     }
 
+    /// 设置本地变更防抖时间
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// 设置失败重试策略
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
     /// 启动简单调度器
     pub async fn start<F>(&self, sync_callback: F) -> Result<()>
     where
         F: Fn() -> Result<()> + Send + Sync + 'static,
     {
+        let runner = SyncRunner::from_fn(sync_callback, self.retry_policy.clone());
+        self.start_with_runner(runner).await
+    }
+
+    /// 使用指定的同步执行器启动调度器
+    pub async fn start_with_runner(&self, runner: SyncRunner) -> Result<()> {
         {
             let mut is_running = self.is_running.lock().unwrap();
             if *is_running {
@@ -249,28 +714,16 @@ impl SimpleSyncScheduler {
             *is_running = true;
         }
 
+        *self.runner.lock().unwrap() = Some(runner.clone());
+
         let is_running = self.is_running.clone();
         let interval_duration = self.interval;
+        let debounce = self.debounce;
+        let shutdown = self.shutdown.clone();
 
         tokio::spawn(async move {
-            let mut interval_timer = interval(interval_duration);
-
-            loop {
-                {
-                    let running = is_running.lock().unwrap();
-                    if !*running {
-                        break;
-                    }
-                }
-
-                interval_timer.tick().await;
-
-                // 执行同步回调
-                if let Err(e) = sync_callback() {
-                    log::error!("定时同步执行失败: {}", e);
-                }
-            }
-
+            drive(runner, Some(interval_duration), debounce, shutdown).await;
+            *is_running.lock().unwrap() = false;
             log::info!("简单调度器已停止");
         });
 
@@ -281,26 +734,65 @@ impl SimpleSyncScheduler {
     /// 停止简单调度器
     pub fn stop(&self) {
         let mut is_running = self.is_running.lock().unwrap();
+        if *is_running {
+            self.shutdown.notify_one();
+        }
         *is_running = false;
     }
 
+    /// 应用退出时停止调度器并执行最后一次同步
+    pub async fn shutdown(&self, timeout: Duration) -> Result<()> {
+        self.stop();
+        match self.runner() {
+            Some(runner) => runner.sync_on_exit(timeout).await,
+            None => Ok(()),
+        }
+    }
+
     /// 检查是否正在运行
     pub fn is_running(&self) -> bool {
         let is_running = self.is_running.lock().unwrap();
         *is_running
     }
+
+    /// 获取当前的同步执行器
+    pub fn runner(&self) -> Option<SyncRunner> {
+        self.runner.lock().unwrap().clone()
+    }
+
+    /// 获取本地变更通知句柄
+    pub fn change_notifier(&self) -> Option<ChangeNotifier> {
+        self.runner().map(|runner| runner.change_notifier())
+    }
+
+    /// 获取失败重试状态
+    pub fn retry_state(&self) -> RetryState {
+        self.runner()
+            .map(|runner| runner.retry_state())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// 记录事件的监听器
+    struct RecordingListener(Arc<Mutex<Vec<SyncEvent>>>);
+
+    impl SyncEventListener for RecordingListener {
+        fn on_sync_event(&self, event: SyncEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
     #[tokio::test]
     async fn test_sync_scheduler_creation() {
         let scheduler = SyncScheduler::new().await.unwrap();
         let status = scheduler.get_status();
         assert!(!status.is_running);
         assert!(status.next_sync_time.is_none());
+        assert_eq!(status.retry, RetryState::default());
     }
 
     #[tokio::test]
@@ -318,7 +810,131 @@ mod tests {
             Ok(())
         };
 
-        // 由于是异步的，我们不在这里测试实际的回调执行
-        // 在实际应用中需要更复杂的测试设置
+        scheduler.start(callback).await.unwrap();
+        assert!(scheduler.is_running());
+        assert!(scheduler.start(|| Ok(())).await.is_err());
+
+        // 启动时立即执行一次定时同步
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(*sync_count.lock().unwrap(), 1);
+
+        scheduler.stop();
+        assert!(!scheduler.is_running());
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(60),
+            jitter: 0.2,
+            max_consecutive_failures: 5,
+        };
+
+        for _ in 0..20 {
+            let first = policy.delay_for(1);
+            assert!(first >= Duration::from_secs(8) && first <= Duration::from_secs(12));
+            let third = policy.delay_for(3);
+            assert!(third >= Duration::from_secs(32) && third <= Duration::from_secs(48));
+            assert!(policy.delay_for(10) <= Duration::from_secs(60));
+        }
+
+        let exact = RetryPolicy {
+            jitter: 0.0,
+            ..policy
+        };
+        assert_eq!(exact.delay_for(2), Duration::from_secs(20));
+        assert_eq!(exact.delay_for(100), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_failures_pause_auto_sync() {
+        let attempts = Arc::new(Mutex::new(0));
+        let fail = Arc::new(AtomicBool::new(true));
+        let runner = {
+            let attempts = attempts.clone();
+            let fail = fail.clone();
+            SyncRunner::from_fn(
+                move || {
+                    *attempts.lock().unwrap() += 1;
+                    if fail.load(Ordering::SeqCst) {
+                        Err(AppError::Network("连接被拒绝".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                },
+                RetryPolicy {
+                    max_consecutive_failures: 2,
+                    ..Default::default()
+                },
+            )
+        };
+        let events = Arc::new(Mutex::new(Vec::new()));
+        runner.add_listener(Box::new(RecordingListener(events.clone())));
+
+        assert!(runner.run(SyncTrigger::Interval).await.is_err());
+        let retry = runner.retry_state();
+        assert_eq!(retry.consecutive_failures, 1);
+        assert!(retry.next_retry_time.is_some());
+        assert!(!retry.auto_sync_paused);
+
+        assert!(runner.run(SyncTrigger::Retry).await.is_err());
+        let retry = runner.retry_state();
+        assert!(retry.auto_sync_paused);
+        assert!(retry.next_retry_time.is_none());
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [SyncEvent::AutoSyncPaused {
+                consecutive_failures: 2,
+                ..
+            }]
+        ));
+
+        // 暂停后自动触发不再执行同步任务
+        assert!(runner.run(SyncTrigger::LocalChange).await.is_err());
+        assert_eq!(*attempts.lock().unwrap(), 2);
+
+        // 手动同步成功后恢复自动同步
+        fail.store(false, Ordering::SeqCst);
+        runner.run(SyncTrigger::Manual).await.unwrap();
+        assert_eq!(runner.retry_state(), RetryState::default());
+        assert!(runner.last_sync_time().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_local_changes_are_debounced() {
+        let sync_count = Arc::new(Mutex::new(0));
+        let scheduler = SimpleSyncScheduler::new(60).with_debounce(Duration::from_millis(100));
+        {
+            let sync_count = sync_count.clone();
+            scheduler
+                .start(move || {
+                    *sync_count.lock().unwrap() += 1;
+                    Ok(())
+                })
+                .await
+                .unwrap();
+        }
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(*sync_count.lock().unwrap(), 1);
+
+        // 连续多次修改只触发一次同步
+        let notifier = scheduler.change_notifier().unwrap();
+        for _ in 0..5 {
+            notifier.notify_change();
+            sleep(Duration::from_millis(20)).await;
+        }
+        notifier.notify_table_change("settings");
+        sleep(Duration::from_millis(300)).await;
+        assert_eq!(*sync_count.lock().unwrap(), 2);
+
+        // 同步自身维护的表不触发同步
+        notifier.notify_table_change("sync_history");
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(*sync_count.lock().unwrap(), 2);
+
+        // 退出时执行最后一次同步
+        scheduler.shutdown(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(*sync_count.lock().unwrap(), 3);
     }
 }
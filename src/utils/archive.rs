@@ -0,0 +1,1180 @@
+//! # 完整归档模块
+//!
+//! 将所有模块的数据导出为带结构版本号的 ZIP 归档，并支持按模块选择性导入：
+//! - `manifest.json`：归档格式、结构版本、各模块文件的记录数和 SHA-256
+//! - `data/<模块>.json`：每类数据一个 JSON 文件
+//! - `attachments/<相对路径>`：附件目录中的文件（如收据图片）
+//!
+//! 导入到已有数据的数据库时，同名的分类、账户和交易分类会合并到现有记录，
+//! 其余 ID 冲突的记录按 [`IdConflictPolicy`] 分配新 ID 或跳过，引用会随之更新
+//!
+//! 习惯打卡页面目前只有示例数据，数据库中没有习惯表，因此归档不包含习惯
+
+use crate::config::AppConfig;
+use crate::storage::database::AuditSource;
+use crate::storage::models::{
+    Account, CategoryInsert, CategoryModel, Note, TimeEntry, Transaction,
+};
+use crate::storage::task_models::{TaskInsert, TaskModel};
+use crate::storage::{
+    AccountInsert, AccountType, Budget, BudgetInsert, StorageBackend, TransactionCategory,
+    TransactionCategoryInsert, TransactionInsert, TransactionStatus, TransactionType,
+};
+use crate::utils::zip::{ZipArchive, ZipWriter};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+
+/// 归档格式标识
+pub const ARCHIVE_FORMAT: &str = "lifetracker-archive";
+/// 当前归档结构版本（版本 2 增加交易分类和预算模块）
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 2;
+/// 清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+/// 附件在归档中的目录
+const ATTACHMENTS_PREFIX: &str = "attachments/";
+
+/// 应用默认的附件目录（应用数据目录下的 `attachments`）
+pub fn default_attachments_dir() -> Result<PathBuf> {
+    Ok(crate::utils::get_app_data_dir()?.join("attachments"))
+}
+
+/// 归档中的数据模块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveModule {
+    Categories,
+    TimeEntries,
+    Tasks,
+    Notes,
+    Accounts,
+    TransactionCategories,
+    Transactions,
+    Budgets,
+    Settings,
+}
+
+impl ArchiveModule {
+    /// 所有模块（按导入依赖顺序排列）
+    pub const ALL: [ArchiveModule; 9] = [
+        ArchiveModule::Categories,
+        ArchiveModule::Accounts,
+        ArchiveModule::TransactionCategories,
+        ArchiveModule::TimeEntries,
+        ArchiveModule::Tasks,
+        ArchiveModule::Notes,
+        ArchiveModule::Transactions,
+        ArchiveModule::Budgets,
+        ArchiveModule::Settings,
+    ];
+
+    /// 模块数据在归档中的文件名
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ArchiveModule::Categories => "data/categories.json",
+            ArchiveModule::TimeEntries => "data/time_entries.json",
+            ArchiveModule::Tasks => "data/tasks.json",
+            ArchiveModule::Notes => "data/notes.json",
+            ArchiveModule::Accounts => "data/accounts.json",
+            ArchiveModule::TransactionCategories => "data/transaction_categories.json",
+            ArchiveModule::Transactions => "data/transactions.json",
+            ArchiveModule::Budgets => "data/budgets.json",
+            ArchiveModule::Settings => "data/settings.json",
+        }
+    }
+
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ArchiveModule::Categories => "分类",
+            ArchiveModule::TimeEntries => "时间记录",
+            ArchiveModule::Tasks => "任务",
+            ArchiveModule::Notes => "笔记",
+            ArchiveModule::Accounts => "账户",
+            ArchiveModule::TransactionCategories => "交易分类",
+            ArchiveModule::Transactions => "交易",
+            ArchiveModule::Budgets => "预算",
+            ArchiveModule::Settings => "设置",
+        }
+    }
+}
+
+/// 归档清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// 归档格式标识
+    pub format: String,
+    /// 归档结构版本
+    pub schema_version: u32,
+    /// 创建归档的应用版本
+    pub app_version: String,
+    /// 创建时间
+    pub created_at: DateTime<Local>,
+    /// 包含的数据模块
+    pub modules: Vec<ArchiveModuleEntry>,
+    /// 包含的附件
+    #[serde(default)]
+    pub attachments: Vec<ArchiveAttachment>,
+}
+
+/// 清单中的模块条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveModuleEntry {
+    pub module: ArchiveModule,
+    /// 归档中的文件名
+    pub file: String,
+    /// 记录数量
+    pub count: usize,
+    /// 文件内容的 SHA-256
+    pub sha256: String,
+}
+
+/// 清单中的附件条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveAttachment {
+    /// 相对于附件目录的路径（使用 `/` 分隔）
+    pub path: String,
+    /// 文件大小
+    pub size: u64,
+    /// 文件内容的 SHA-256
+    pub sha256: String,
+}
+
+impl ArchiveManifest {
+    /// 查找模块条目
+    pub fn module(&self, module: ArchiveModule) -> Option<&ArchiveModuleEntry> {
+        self.modules.iter().find(|entry| entry.module == module)
+    }
+
+    /// 检查归档格式和结构版本是否受支持
+    pub fn check_version(&self) -> Result<()> {
+        if self.format != ARCHIVE_FORMAT {
+            bail!("不是 LifeTracker 归档文件: {}", self.format);
+        }
+        if self.schema_version == 0 {
+            bail!("归档结构版本无效");
+        }
+        if self.schema_version > ARCHIVE_SCHEMA_VERSION {
+            bail!(
+                "归档由较新版本的应用（{}）创建，结构版本 {} 高于当前支持的 {}，请先升级应用",
+                self.app_version,
+                self.schema_version,
+                ARCHIVE_SCHEMA_VERSION
+            );
+        }
+        Ok(())
+    }
+}
+
+/// 归档导出选项
+#[derive(Debug, Clone)]
+pub struct ArchiveExportOptions {
+    /// 导出的模块
+    pub modules: Vec<ArchiveModule>,
+    /// 附件目录（为空时不导出附件）
+    pub attachments_dir: Option<PathBuf>,
+}
+
+impl Default for ArchiveExportOptions {
+    fn default() -> Self {
+        Self {
+            modules: ArchiveModule::ALL.to_vec(),
+            attachments_dir: None,
+        }
+    }
+}
+
+/// ID 冲突时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdConflictPolicy {
+    /// 分配新 ID 并更新引用
+    #[default]
+    Remap,
+    /// 跳过已存在的记录
+    Skip,
+}
+
+/// 归档导入选项
+#[derive(Debug, Clone)]
+pub struct ArchiveImportOptions {
+    /// 导入的模块
+    pub modules: Vec<ArchiveModule>,
+    /// ID 冲突时的处理方式
+    pub on_conflict: IdConflictPolicy,
+    /// 附件解压目录（为空时不导入附件）
+    pub attachments_dir: Option<PathBuf>,
+    /// 试运行（只统计，不写入）
+    pub dry_run: bool,
+}
+
+impl Default for ArchiveImportOptions {
+    fn default() -> Self {
+        Self {
+            modules: ArchiveModule::ALL.to_vec(),
+            on_conflict: IdConflictPolicy::Remap,
+            attachments_dir: None,
+            dry_run: false,
+        }
+    }
+}
+
+/// 归档导入结果
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveImportReport {
+    /// 新增的记录数
+    pub imported: BTreeMap<ArchiveModule, usize>,
+    /// 合并到现有记录的数量（同名分类和账户）
+    pub merged: BTreeMap<ArchiveModule, usize>,
+    /// 跳过的记录数
+    pub skipped: BTreeMap<ArchiveModule, usize>,
+    /// 分配了新 ID 的记录数
+    pub remapped_ids: usize,
+    /// 导入的附件数
+    pub attachments: usize,
+    /// 归档中的设置（由调用方决定是否应用）
+    pub settings: Option<AppConfig>,
+    /// 警告信息
+    pub warnings: Vec<String>,
+}
+
+impl ArchiveImportReport {
+    fn count(map: &mut BTreeMap<ArchiveModule, usize>, module: ArchiveModule) {
+        *map.entry(module).or_insert(0) += 1;
+    }
+}
+
+/// 完整归档导出器
+pub struct ArchiveExporter<'a> {
+    storage: &'a dyn StorageBackend,
+    settings: Option<&'a AppConfig>,
+}
+
+impl<'a> ArchiveExporter<'a> {
+    pub fn new(storage: &'a dyn StorageBackend) -> Self {
+        Self {
+            storage,
+            settings: None,
+        }
+    }
+
+    /// 同时导出应用设置
+    pub fn with_settings(mut self, settings: &'a AppConfig) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// 导出到文件
+    pub fn export_to_file<P: AsRef<Path>>(
+        &self,
+        options: &ArchiveExportOptions,
+        path: P,
+    ) -> Result<ArchiveManifest> {
+        let file = fs::File::create(path)?;
+        self.export(options, std::io::BufWriter::new(file))
+    }
+
+    /// 导出归档
+    pub fn export<W: Write>(
+        &self,
+        options: &ArchiveExportOptions,
+        writer: W,
+    ) -> Result<ArchiveManifest> {
+        let mut zip = ZipWriter::new(writer);
+        let mut modules = Vec::new();
+
+        for module in ArchiveModule::ALL {
+            if !options.modules.contains(&module) {
+                continue;
+            }
+            let (content, count) = match module {
+                ArchiveModule::Categories => to_json(&self.storage.get_all_categories()?)?,
+                ArchiveModule::TimeEntries => to_json(&self.storage.get_all_time_entries()?)?,
+                ArchiveModule::Tasks => to_json(&self.storage.get_all_tasks()?)?,
+                ArchiveModule::Notes => to_json(&self.storage.get_all_notes()?)?,
+                ArchiveModule::Accounts => to_json(&self.storage.get_all_accounts()?)?,
+                ArchiveModule::TransactionCategories => {
+                    to_json(&self.storage.get_all_transaction_categories()?)?
+                }
+                ArchiveModule::Transactions => to_json(&self.storage.get_all_transactions()?)?,
+                ArchiveModule::Budgets => to_json(&self.storage.get_all_budgets()?)?,
+                ArchiveModule::Settings => match self.settings {
+                    Some(settings) => (serde_json::to_vec_pretty(settings)?, 1),
+                    None => continue,
+                },
+            };
+
+            zip.add_file(module.file_name(), &content)?;
+            modules.push(ArchiveModuleEntry {
+                module,
+                file: module.file_name().to_string(),
+                count,
+                sha256: sha256_hex(&content),
+            });
+        }
+
+        let mut attachments = Vec::new();
+        if let Some(ref dir) = options.attachments_dir {
+            for (path, relative) in list_files(dir)? {
+                let content =
+                    fs::read(&path).with_context(|| format!("读取附件失败: {}", path.display()))?;
+                zip.add_file(&format!("{}{}", ATTACHMENTS_PREFIX, relative), &content)?;
+                attachments.push(ArchiveAttachment {
+                    path: relative,
+                    size: content.len() as u64,
+                    sha256: sha256_hex(&content),
+                });
+            }
+        }
+
+        let manifest = ArchiveManifest {
+            format: ARCHIVE_FORMAT.to_string(),
+            schema_version: ARCHIVE_SCHEMA_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Local::now(),
+            modules,
+            attachments,
+        };
+        zip.add_file(MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?)?;
+        zip.finish()?;
+
+        log::info!(
+            "完整归档导出完成: {} 个模块, {} 个附件",
+            manifest.modules.len(),
+            manifest.attachments.len()
+        );
+        Ok(manifest)
+    }
+}
+
+/// 归档中读取出的数据
+#[derive(Default)]
+struct ArchiveContents {
+    categories: Vec<CategoryModel>,
+    time_entries: Vec<TimeEntry>,
+    tasks: Vec<TaskModel>,
+    notes: Vec<Note>,
+    accounts: Vec<Account>,
+    transaction_categories: Vec<TransactionCategory>,
+    transactions: Vec<Transaction>,
+    budgets: Vec<Budget>,
+}
+
+/// 完整归档导入器
+pub struct ArchiveImporter<'a> {
+    storage: &'a dyn StorageBackend,
+}
+
+impl<'a> ArchiveImporter<'a> {
+    pub fn new(storage: &'a dyn StorageBackend) -> Self {
+        Self { storage }
+    }
+
+    /// 读取并校验归档清单（用于导入前预览）
+    pub fn read_manifest(data: &[u8]) -> Result<ArchiveManifest> {
+        let archive = ZipArchive::new(data)?;
+        Self::manifest(&archive)
+    }
+
+    fn manifest(archive: &ZipArchive) -> Result<ArchiveManifest> {
+        if !archive.contains(MANIFEST_FILE) {
+            bail!("归档中缺少清单文件 {}", MANIFEST_FILE);
+        }
+        let manifest: ArchiveManifest =
+            serde_json::from_slice(&archive.read(MANIFEST_FILE)?).context("归档清单格式无效")?;
+        manifest.check_version()?;
+        Ok(manifest)
+    }
+
+    /// 从文件导入
+    pub fn import_from_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &ArchiveImportOptions,
+    ) -> Result<ArchiveImportReport> {
+        let data = fs::read(path)?;
+        self.import(&data, options)
+    }
+
+    /// 导入归档
+    pub fn import(
+        &self,
+        data: &[u8],
+        options: &ArchiveImportOptions,
+    ) -> Result<ArchiveImportReport> {
+        let archive = ZipArchive::new(data)?;
+        let manifest = Self::manifest(&archive)?;
+        let mut report = ArchiveImportReport::default();
+
+        let mut contents = ArchiveContents::default();
+        for module in &options.modules {
+            let Some(entry) = manifest.module(*module) else {
+                report
+                    .warnings
+                    .push(format!("归档中不包含{}数据", module.display_name()));
+                continue;
+            };
+
+            let content = archive.read(&entry.file)?;
+            if sha256_hex(&content) != entry.sha256 {
+                bail!("{}数据校验失败，归档可能已损坏", module.display_name());
+            }
+
+            match module {
+                ArchiveModule::Categories => contents.categories = from_json(*module, &content)?,
+                ArchiveModule::TimeEntries => contents.time_entries = from_json(*module, &content)?,
+                ArchiveModule::Tasks => contents.tasks = from_json(*module, &content)?,
+                ArchiveModule::Notes => contents.notes = from_json(*module, &content)?,
+                ArchiveModule::Accounts => contents.accounts = from_json(*module, &content)?,
+                ArchiveModule::TransactionCategories => {
+                    contents.transaction_categories = from_json(*module, &content)?
+                }
+                ArchiveModule::Transactions => {
+                    contents.transactions = from_json(*module, &content)?
+                }
+                ArchiveModule::Budgets => contents.budgets = from_json(*module, &content)?,
+                ArchiveModule::Settings => {
+                    report.settings = Some(from_json(*module, &content)?);
+                }
+            }
+        }
+
+        let storage = self.storage;
+        if options.dry_run {
+            self.apply(contents, options, &mut report)?;
+        } else {
+            storage.begin_replace(AuditSource::Import)?;
+            if let Err(e) = storage.begin_transaction() {
+                let _ = storage.finish_replace();
+                return Err(e.into());
+            }
+
+            match self.apply(contents, options, &mut report) {
+                Ok(()) => {
+                    storage.finish_replace()?;
+                    storage.commit_transaction()?;
+                }
+                Err(e) => {
+                    if let Err(rollback_err) = storage.rollback_transaction() {
+                        log::error!("回滚归档导入失败: {}", rollback_err);
+                    }
+                    let _ = storage.finish_replace();
+                    return Err(e);
+                }
+            }
+        }
+
+        if let Some(ref dir) = options.attachments_dir {
+            self.import_attachments(&archive, &manifest, dir, options.dry_run, &mut report)?;
+        }
+
+        log::info!(
+            "完整归档导入完成: 新增 {:?}, 合并 {:?}, 跳过 {:?}, 重新分配ID {}",
+            report.imported,
+            report.merged,
+            report.skipped,
+            report.remapped_ids
+        );
+        Ok(report)
+    }
+
+    /// 按依赖顺序写入数据，并重新映射冲突的ID
+    fn apply(
+        &self,
+        contents: ArchiveContents,
+        options: &ArchiveImportOptions,
+        report: &mut ArchiveImportReport,
+    ) -> Result<()> {
+        let storage = self.storage;
+        let write = !options.dry_run;
+        let mut remapper = IdRemapper::new(options.on_conflict);
+
+        // 1. 分类：同名合并，ID冲突时重新分配
+        let existing = storage.get_all_categories()?;
+        let mut category_ids: HashSet<Uuid> = existing.iter().map(|c| c.id).collect();
+        let mut pending = Vec::new();
+        for category in contents.categories {
+            let module = ArchiveModule::Categories;
+            if let Some(same) = existing.iter().find(|c| c.name == category.name) {
+                remapper.categories.insert(category.id, same.id);
+                ArchiveImportReport::count(&mut report.merged, module);
+                continue;
+            }
+            match remapper.assign(category.id, &category_ids, report) {
+                Some(id) => {
+                    remapper.categories.insert(category.id, id);
+                    category_ids.insert(id);
+                    pending.push(category);
+                }
+                None => ArchiveImportReport::count(&mut report.skipped, module),
+            }
+        }
+        parents_first(&mut pending, |c| (c.id, c.parent_id));
+        for category in pending {
+            let insert = CategoryInsert {
+                id: remapper.categories[&category.id],
+                name: category.name,
+                description: category.description,
+                color: category.color,
+                icon: category.icon,
+                daily_target_seconds: category.daily_target_seconds,
+                weekly_target_seconds: category.weekly_target_seconds,
+                is_active: category.is_active,
+                sort_order: category.sort_order,
+                parent_id: category
+                    .parent_id
+                    .map(|id| remapper.category(id))
+                    .filter(|id| category_ids.contains(id)),
+                created_at: category.created_at,
+            };
+            if write {
+                storage.insert_category(&insert)?;
+            }
+            ArchiveImportReport::count(&mut report.imported, ArchiveModule::Categories);
+        }
+
+        // 2. 账户：同名同币种合并
+        let existing = storage.get_all_accounts()?;
+        let mut account_ids: HashSet<Uuid> = existing.iter().map(|a| a.id).collect();
+        let mut currencies: HashMap<Uuid, String> = existing
+            .iter()
+            .map(|a| (a.id, a.currency.clone()))
+            .collect();
+        for account in contents.accounts {
+            let module = ArchiveModule::Accounts;
+            if let Some(same) = existing
+                .iter()
+                .find(|a| a.name == account.name && a.currency == account.currency)
+            {
+                remapper.accounts.insert(account.id, same.id);
+                ArchiveImportReport::count(&mut report.merged, module);
+                continue;
+            }
+            let Some(id) = remapper.assign(account.id, &account_ids, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+            remapper.accounts.insert(account.id, id);
+            account_ids.insert(id);
+            currencies.insert(id, account.currency.clone());
+
+            if write {
+                storage.insert_account(&AccountInsert {
+                    id,
+                    name: account.name,
+                    account_type: parse_enum(&account.account_type).unwrap_or(AccountType::Other),
+                    currency: account.currency,
+                    balance: account.balance,
+                    initial_balance: account.balance,
+                    description: None,
+                    is_active: account.is_active,
+                    is_default: false,
+                    created_at: account.created_at,
+                })?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        // 3. 交易分类：同名同类型合并
+        let existing = storage.get_all_transaction_categories()?;
+        let mut transaction_category_ids: HashSet<Uuid> = existing.iter().map(|c| c.id).collect();
+        let mut pending = Vec::new();
+        for category in contents.transaction_categories {
+            let module = ArchiveModule::TransactionCategories;
+            if let Some(same) = existing.iter().find(|c| {
+                c.name == category.name && c.transaction_type == category.transaction_type
+            }) {
+                remapper.transaction_categories.insert(category.id, same.id);
+                ArchiveImportReport::count(&mut report.merged, module);
+                continue;
+            }
+            match remapper.assign(category.id, &transaction_category_ids, report) {
+                Some(id) => {
+                    remapper.transaction_categories.insert(category.id, id);
+                    transaction_category_ids.insert(id);
+                    pending.push(category);
+                }
+                None => ArchiveImportReport::count(&mut report.skipped, module),
+            }
+        }
+        parents_first(&mut pending, |c| (c.id, c.parent_id));
+        for category in pending {
+            if write {
+                storage.insert_transaction_category(&TransactionCategoryInsert {
+                    id: remapper.transaction_category(category.id),
+                    name: category.name,
+                    transaction_type: category.transaction_type,
+                    description: category.description,
+                    color: category.color,
+                    icon: category.icon,
+                    parent_id: category
+                        .parent_id
+                        .map(|id| remapper.transaction_category(id))
+                        .filter(|id| transaction_category_ids.contains(id)),
+                    is_active: category.is_active,
+                    created_at: category.created_at,
+                })?;
+            }
+            ArchiveImportReport::count(&mut report.imported, ArchiveModule::TransactionCategories);
+        }
+
+        // 4. 时间记录
+        let existing: HashSet<Uuid> = storage
+            .get_all_time_entries()?
+            .iter()
+            .map(|e| e.id)
+            .collect();
+        for entry in contents.time_entries {
+            let module = ArchiveModule::TimeEntries;
+            let Some(id) = remapper.assign(entry.id, &existing, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+            let mut insert = crate::storage::TimeEntryInsert::from(entry);
+            insert.id = id;
+            insert.category_id = insert.category_id.map(|id| remapper.category(id));
+            if write {
+                storage.insert_time_entry(&insert)?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        // 5. 任务
+        let existing: HashSet<Uuid> = storage.get_all_tasks()?.iter().map(|t| t.id).collect();
+        for task in contents.tasks {
+            let module = ArchiveModule::Tasks;
+            let Some(id) = remapper.assign(task.id, &existing, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+            if write {
+                storage.insert_task(&TaskInsert {
+                    id,
+                    name: task.name,
+                    description: task.description,
+                    category_id: task.category_id.map(|id| remapper.category(id)),
+                    status: task.status,
+                    priority: task.priority,
+                    estimated_duration_seconds: task.estimated_duration_seconds,
+                    total_duration_seconds: task.total_duration_seconds,
+                    tags: task.tags,
+                    due_date: task.due_date,
+                    is_completed: task.is_completed,
+                    completed_at: task.completed_at,
+                    created_at: task.created_at,
+                })?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        // 6. 笔记
+        let existing: HashSet<Uuid> = storage.get_all_notes()?.iter().map(|n| n.id).collect();
+        for mut note in contents.notes {
+            let module = ArchiveModule::Notes;
+            let Some(id) = remapper.assign(note.id, &existing, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+            note.id = id;
+            if write {
+                storage.insert_note(&note)?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        // 7. 交易：账户必须存在
+        let existing: HashSet<Uuid> = storage
+            .get_all_transactions()?
+            .iter()
+            .map(|t| t.id)
+            .collect();
+        for transaction in contents.transactions {
+            let module = ArchiveModule::Transactions;
+            let account_id = remapper.account(transaction.account_id);
+            if !account_ids.contains(&account_id) {
+                report.warnings.push(format!(
+                    "交易 {} 引用的账户不存在，已跳过",
+                    transaction.description
+                ));
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            }
+            let Some(id) = remapper.assign(transaction.id, &existing, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+
+            if write {
+                storage.insert_transaction(&TransactionInsert {
+                    id,
+                    transaction_type: parse_enum(&transaction.transaction_type)
+                        .unwrap_or(TransactionType::Expense),
+                    amount: transaction.amount,
+                    currency: currencies
+                        .get(&account_id)
+                        .cloned()
+                        .unwrap_or_else(|| "CNY".to_string()),
+                    description: transaction.description,
+                    account_id,
+                    category_id: transaction
+                        .category_id
+                        .map(|id| remapper.transaction_category(id))
+                        .filter(|id| transaction_category_ids.contains(id)),
+                    to_account_id: None,
+                    status: TransactionStatus::Completed,
+                    transaction_date: transaction.transaction_date.date_naive(),
+                    tags: transaction.tags,
+                    receipt_path: None,
+                    created_at: transaction.created_at,
+                })?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        // 8. 预算：交易分类必须存在
+        let existing: HashSet<Uuid> = storage.get_all_budgets()?.iter().map(|b| b.id).collect();
+        for budget in contents.budgets {
+            let module = ArchiveModule::Budgets;
+            let category_id = remapper.transaction_category(budget.category_id);
+            if !transaction_category_ids.contains(&category_id) {
+                report
+                    .warnings
+                    .push(format!("预算 {} 引用的交易分类不存在，已跳过", budget.name));
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            }
+            let Some(id) = remapper.assign(budget.id, &existing, report) else {
+                ArchiveImportReport::count(&mut report.skipped, module);
+                continue;
+            };
+
+            if write {
+                storage.insert_budget(&BudgetInsert {
+                    id,
+                    name: budget.name,
+                    category_id,
+                    amount: budget.amount,
+                    currency: budget.currency,
+                    period: budget.period,
+                    start_date: budget.start_date,
+                    end_date: budget.end_date,
+                    spent_amount: budget.spent_amount,
+                    remaining_amount: budget.remaining_amount,
+                    is_active: budget.is_active,
+                    created_at: budget.created_at,
+                })?;
+            }
+            ArchiveImportReport::count(&mut report.imported, module);
+        }
+
+        Ok(())
+    }
+
+    /// 解压附件，已存在的同名文件不会被覆盖
+    fn import_attachments(
+        &self,
+        archive: &ZipArchive,
+        manifest: &ArchiveManifest,
+        dir: &Path,
+        dry_run: bool,
+        report: &mut ArchiveImportReport,
+    ) -> Result<()> {
+        for attachment in &manifest.attachments {
+            let Some(target) = safe_join(dir, &attachment.path) else {
+                report
+                    .warnings
+                    .push(format!("忽略路径无效的附件: {}", attachment.path));
+                continue;
+            };
+
+            let content = archive.read(&format!("{}{}", ATTACHMENTS_PREFIX, attachment.path))?;
+            if sha256_hex(&content) != attachment.sha256 {
+                bail!("附件 {} 校验失败，归档可能已损坏", attachment.path);
+            }
+
+            if target.exists() {
+                if sha256_hex(&fs::read(&target)?) != attachment.sha256 {
+                    report
+                        .warnings
+                        .push(format!("附件已存在且内容不同，未覆盖: {}", attachment.path));
+                }
+                continue;
+            }
+
+            if !dry_run {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&target, content)?;
+            }
+            report.attachments += 1;
+        }
+        Ok(())
+    }
+}
+
+/// ID 重新映射表
+struct IdRemapper {
+    policy: IdConflictPolicy,
+    categories: HashMap<Uuid, Uuid>,
+    accounts: HashMap<Uuid, Uuid>,
+    transaction_categories: HashMap<Uuid, Uuid>,
+}
+
+impl IdRemapper {
+    fn new(policy: IdConflictPolicy) -> Self {
+        Self {
+            policy,
+            categories: HashMap::new(),
+            accounts: HashMap::new(),
+            transaction_categories: HashMap::new(),
+        }
+    }
+
+    /// 为导入的记录确定ID，跳过时返回 None
+    fn assign(
+        &self,
+        id: Uuid,
+        existing: &HashSet<Uuid>,
+        report: &mut ArchiveImportReport,
+    ) -> Option<Uuid> {
+        if !existing.contains(&id) {
+            return Some(id);
+        }
+        match self.policy {
+            IdConflictPolicy::Remap => {
+                report.remapped_ids += 1;
+                Some(Uuid::new_v4())
+            }
+            IdConflictPolicy::Skip => None,
+        }
+    }
+
+    fn category(&self, id: Uuid) -> Uuid {
+        self.categories.get(&id).copied().unwrap_or(id)
+    }
+
+    fn account(&self, id: Uuid) -> Uuid {
+        self.accounts.get(&id).copied().unwrap_or(id)
+    }
+
+    fn transaction_category(&self, id: Uuid) -> Uuid {
+        self.transaction_categories.get(&id).copied().unwrap_or(id)
+    }
+}
+
+/// 按层级排序，父分类先于子分类写入
+fn parents_first<T>(items: &mut [T], key: impl Fn(&T) -> (Uuid, Option<Uuid>)) {
+    let parents: HashMap<Uuid, Option<Uuid>> = items.iter().map(&key).collect();
+    let depth = |mut id: Uuid| {
+        let mut depth = 0;
+        while let Some(Some(parent)) = parents.get(&id) {
+            depth += 1;
+            id = *parent;
+            if depth > parents.len() {
+                break;
+            }
+        }
+        depth
+    };
+    items.sort_by_key(|item| depth(key(item).0));
+}
+
+/// 序列化为 JSON，同时返回记录数
+fn to_json<T: Serialize>(items: &[T]) -> Result<(Vec<u8>, usize)> {
+    Ok((serde_json::to_vec_pretty(items)?, items.len()))
+}
+
+fn from_json<T: DeserializeOwned>(module: ArchiveModule, content: &[u8]) -> Result<T> {
+    serde_json::from_slice(content)
+        .with_context(|| format!("{}数据格式无效", module.display_name()))
+}
+
+/// 从 snake_case 名称解析枚举
+fn parse_enum<T: DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// 递归列出目录中的文件，返回（完整路径，`/` 分隔的相对路径），按相对路径排序
+fn list_files(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let relative = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((path.clone(), relative));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+/// 拼接附件路径，拒绝绝对路径和 `..`
+fn safe_join(dir: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        Some(dir.join(relative))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::{
+        BudgetStore, CategoryStore, TimeEntryStore, TransactionCategoryStore, TransactionStore,
+    };
+    use crate::storage::test_support::insert_sample_finance;
+    use crate::storage::MemoryStorage;
+    use chrono::TimeZone;
+
+    fn time(hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap()
+    }
+
+    fn category(name: &str) -> CategoryInsert {
+        CategoryInsert {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            color: "#FF0000".to_string(),
+            icon: "work".to_string(),
+            daily_target_seconds: None,
+            weekly_target_seconds: None,
+            is_active: true,
+            sort_order: 0,
+            parent_id: None,
+            created_at: time(8),
+        }
+    }
+
+    fn entry(category_id: Uuid) -> crate::storage::TimeEntryInsert {
+        crate::storage::TimeEntryInsert {
+            id: Uuid::new_v4(),
+            task_name: "编程".to_string(),
+            category_id: Some(category_id),
+            start_time: time(9),
+            end_time: Some(time(10)),
+            duration_seconds: 3600,
+            description: None,
+            tags: vec![],
+            created_at: time(9),
+        }
+    }
+
+    fn sample_storage() -> (
+        MemoryStorage,
+        CategoryInsert,
+        crate::storage::TimeEntryInsert,
+    ) {
+        let storage = MemoryStorage::new();
+        let work = category("工作");
+        storage.insert_category(&work).unwrap();
+        let study = category("学习");
+        storage.insert_category(&study).unwrap();
+        let entry = entry(study.id);
+        storage.insert_time_entry(&entry).unwrap();
+        (storage, study, entry)
+    }
+
+    #[test]
+    fn test_archive_round_trip_with_remapping() {
+        let (source, study, entry) = sample_storage();
+        let dir = tempfile::tempdir().unwrap();
+        let attachments = dir.path().join("attachments");
+        fs::create_dir_all(attachments.join("receipts")).unwrap();
+        fs::write(attachments.join("receipts/a.txt"), b"receipt").unwrap();
+
+        let mut bytes = Vec::new();
+        let manifest = ArchiveExporter::new(&source)
+            .export(
+                &ArchiveExportOptions {
+                    attachments_dir: Some(attachments),
+                    ..Default::default()
+                },
+                &mut bytes,
+            )
+            .unwrap();
+        assert_eq!(manifest.module(ArchiveModule::Categories).unwrap().count, 2);
+        assert!(manifest.module(ArchiveModule::Settings).is_none());
+        assert_eq!(manifest.attachments.len(), 1);
+
+        // 目标库已有同名分类“学习”（不同ID）和同ID的时间记录
+        let target = MemoryStorage::new();
+        let existing_study = category("学习");
+        target.insert_category(&existing_study).unwrap();
+        target.insert_time_entry(&entry).unwrap();
+
+        let restored = dir.path().join("restored");
+        let report = ArchiveImporter::new(&target)
+            .import(
+                &bytes,
+                &ArchiveImportOptions {
+                    attachments_dir: Some(restored.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(report.imported[&ArchiveModule::Categories], 1);
+        assert_eq!(report.merged[&ArchiveModule::Categories], 1);
+        assert_eq!(report.imported[&ArchiveModule::TimeEntries], 1);
+        assert_eq!(report.remapped_ids, 1);
+        assert_eq!(report.attachments, 1);
+        assert_eq!(
+            fs::read(restored.join("receipts/a.txt")).unwrap(),
+            b"receipt"
+        );
+
+        // 新记录的分类指向目标库中已有的同名分类
+        let entries = target.get_all_time_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        let imported = entries.iter().find(|e| e.id != entry.id).unwrap();
+        assert_eq!(imported.category_id, Some(existing_study.id));
+        assert_ne!(imported.category_id, Some(study.id));
+        assert_eq!(target.get_all_categories().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_archive_selective_import_and_skip() {
+        let (source, _, entry) = sample_storage();
+        let mut bytes = Vec::new();
+        ArchiveExporter::new(&source)
+            .export(&ArchiveExportOptions::default(), &mut bytes)
+            .unwrap();
+
+        let target = MemoryStorage::new();
+        target.insert_time_entry(&entry).unwrap();
+        let report = ArchiveImporter::new(&target)
+            .import(
+                &bytes,
+                &ArchiveImportOptions {
+                    modules: vec![ArchiveModule::TimeEntries],
+                    on_conflict: IdConflictPolicy::Skip,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(report.skipped[&ArchiveModule::TimeEntries], 1);
+        assert!(report.imported.is_empty());
+        assert!(target.get_all_categories().unwrap().is_empty());
+        assert_eq!(target.get_all_time_entries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_archive_finance_with_budgets() {
+        let source = MemoryStorage::new();
+        insert_sample_finance(&source);
+        let mut bytes = Vec::new();
+        let manifest = ArchiveExporter::new(&source)
+            .export(&ArchiveExportOptions::default(), &mut bytes)
+            .unwrap();
+        assert_eq!(manifest.module(ArchiveModule::Budgets).unwrap().count, 1);
+        assert_eq!(
+            manifest
+                .module(ArchiveModule::TransactionCategories)
+                .unwrap()
+                .count,
+            1
+        );
+
+        // 目标库已有同名同类型的交易分类，预算和交易应指向它
+        let target = MemoryStorage::new();
+        let existing = TransactionCategoryInsert {
+            id: Uuid::new_v4(),
+            name: "餐饮".to_string(),
+            transaction_type: TransactionType::Expense,
+            description: None,
+            color: "#F44336".to_string(),
+            icon: None,
+            parent_id: None,
+            is_active: true,
+            created_at: time(8),
+        };
+        target.insert_transaction_category(&existing).unwrap();
+
+        let report = ArchiveImporter::new(&target)
+            .import(&bytes, &ArchiveImportOptions::default())
+            .unwrap();
+        assert_eq!(report.merged[&ArchiveModule::TransactionCategories], 1);
+        assert_eq!(report.imported[&ArchiveModule::Transactions], 3);
+        assert_eq!(report.imported[&ArchiveModule::Budgets], 1);
+
+        let budgets = target.get_all_budgets().unwrap();
+        assert_eq!(budgets.len(), 1);
+        assert_eq!(budgets[0].category_id, existing.id);
+        let categorized: Vec<_> = target
+            .get_all_transactions()
+            .unwrap()
+            .into_iter()
+            .filter_map(|t| t.category_id)
+            .collect();
+        assert_eq!(categorized, vec![existing.id, existing.id]);
+
+        // 只导入预算时，归档中的交易分类不会写入，引用不存在的分类的预算被跳过
+        let target = MemoryStorage::new();
+        let report = ArchiveImporter::new(&target)
+            .import(
+                &bytes,
+                &ArchiveImportOptions {
+                    modules: vec![ArchiveModule::Budgets],
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(report.skipped[&ArchiveModule::Budgets], 1);
+        assert!(target.get_all_budgets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_rejects_newer_schema() {
+        let mut manifest = ArchiveManifest {
+            format: ARCHIVE_FORMAT.to_string(),
+            schema_version: ARCHIVE_SCHEMA_VERSION + 1,
+            app_version: "9.0.0".to_string(),
+            created_at: Local::now(),
+            modules: vec![],
+            attachments: vec![],
+        };
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file(MANIFEST_FILE, &serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        let bytes = zip.finish().unwrap();
+
+        let error = ArchiveImporter::read_manifest(&bytes).unwrap_err();
+        assert!(error.to_string().contains("较新版本"));
+
+        manifest.schema_version = ARCHIVE_SCHEMA_VERSION;
+        manifest.format = "other".to_string();
+        assert!(manifest.check_version().is_err());
+        assert_eq!(safe_join(Path::new("/tmp"), "../etc/passwd"), None);
+    }
+}
@@ -2,15 +2,25 @@
 //!
 //! 提供各种格式的数据导出功能
 
+pub mod finance;
+pub mod stream;
+
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::core::Category;
+use crate::storage::accounting_models::{
+    Account, Budget, CategoryBreakdown, FinancialStats, MonthlyTrend, Transaction,
+    TransactionCategory,
+};
 use crate::storage::models::{CategoryModel, TimeEntry};
+use crate::storage::task_models::TaskModel;
+use crate::utils::pdf::PdfFont;
+use crate::utils::report::{task_rows, Report, ReportBlock, TASK_HEADERS};
 use anyhow::Result;
 
 /// 导出格式
@@ -22,6 +32,8 @@ pub enum ExportFormat {
     Html,
     Markdown,
     Pdf,
+    Ics,
+    Xlsx,
 }
 
 impl ExportFormat {
@@ -33,6 +45,8 @@ impl ExportFormat {
             "html" | "htm" => Some(Self::Html),
             "md" | "markdown" => Some(Self::Markdown),
             "pdf" => Some(Self::Pdf),
+            "ics" | "ical" => Some(Self::Ics),
+            "xlsx" => Some(Self::Xlsx),
             _ => None,
         }
     }
@@ -45,6 +59,8 @@ impl ExportFormat {
             Self::Html => "html",
             Self::Markdown => "md",
             Self::Pdf => "pdf",
+            Self::Ics => "ics",
+            Self::Xlsx => "xlsx",
         }
     }
 
@@ -56,6 +72,8 @@ impl ExportFormat {
             Self::Html => "text/html",
             Self::Markdown => "text/markdown",
             Self::Pdf => "application/pdf",
+            Self::Ics => "text/calendar",
+            Self::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
         }
     }
 }
@@ -71,6 +89,8 @@ pub struct ExportOptions {
     pub group_by_date: bool,
     pub group_by_category: bool,
     pub include_metadata: bool,
+    /// PDF 嵌入的 TrueType 字体文件（为空时自动查找系统中文字体）
+    pub pdf_font: Option<PathBuf>,
 }
 
 impl Default for ExportOptions {
@@ -84,6 +104,7 @@ impl Default for ExportOptions {
             group_by_date: false,
             group_by_category: false,
             include_metadata: true,
+            pdf_font: None,
         }
     }
 }
@@ -95,6 +116,11 @@ pub struct ExportData {
     pub categories: Vec<Category>,
     pub time_entries: Vec<TimeEntry>,
     pub statistics: Option<ExportStatistics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub financial: Option<FinancialSummary>,
+    /// 任务（用于 ICS 导出截止日期和 XLSX 任务工作表）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskModel>,
 }
 
 /// 导出元数据
@@ -119,6 +145,23 @@ pub struct ExportStatistics {
     pub most_used_category: Option<String>,
 }
 
+/// 财务汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinancialSummary {
+    pub stats: FinancialStats,
+    pub category_breakdown: Vec<CategoryBreakdown>,
+    pub monthly_trends: Vec<MonthlyTrend>,
+    /// 以下明细目前只用于 XLSX 导出
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<Account>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transaction_categories: Vec<TransactionCategory>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transactions: Vec<Transaction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budgets: Vec<Budget>,
+}
+
 /// 数据导出器
 pub struct DataExporter {
     options: ExportOptions,
@@ -140,7 +183,9 @@ impl DataExporter {
             ExportFormat::Xml => self.export_xml(data, &mut writer)?,
             ExportFormat::Html => self.export_html(data, &mut writer)?,
             ExportFormat::Markdown => self.export_markdown(data, &mut writer)?,
-            ExportFormat::Pdf => return Err(anyhow::Error::msg("PDF导出暂未实现")),
+            ExportFormat::Pdf => self.export_pdf(data, &mut writer)?,
+            ExportFormat::Ics => self.export_ics(data, &mut writer)?,
+            ExportFormat::Xlsx => self.export_xlsx(data, &mut writer)?,
         }
 
         writer.flush()?;
@@ -156,52 +201,14 @@ impl DataExporter {
 
     /// 导出为CSV格式
     fn export_csv<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
-        // CSV头部
-        writeln!(writer, "ID,任务名称,分类,开始时间,结束时间,持续时间,描述")?;
+        write_csv_header(writer)?;
 
         // 创建分类映射
-        let category_map: HashMap<String, String> = data
-            .categories
-            .iter()
-            .map(|c| (c.id.to_string(), c.name.clone()))
-            .collect();
+        let category_map = category_names(&data.categories);
 
         // 导出时间记录
         for entry in &data.time_entries {
-            let category_name = category_map
-                .get(
-                    &entry
-                        .category_id
-                        .as_ref()
-                        .map(|id| id.to_string())
-                        .unwrap_or_default(),
-                )
-                .map(|s| s.as_str())
-                .unwrap_or("未知分类");
-
-            let end_time = entry
-                .end_time
-                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
-                .unwrap_or_else(|| "进行中".to_string());
-
-            let duration = if let Some(end) = entry.end_time {
-                let dur = end.signed_duration_since(entry.start_time);
-                crate::utils::format_duration(dur)
-            } else {
-                "进行中".to_string()
-            };
-
-            writeln!(
-                writer,
-                "{},{},{},{},{},{},{}",
-                escape_csv(&entry.id.to_string()),
-                escape_csv(&entry.task_name),
-                escape_csv(category_name),
-                entry.start_time.format("%Y-%m-%d %H:%M:%S"),
-                end_time,
-                duration,
-                escape_csv(entry.description.as_deref().unwrap_or(""))
-            )?;
+            write_csv_entry(writer, entry, &category_map)?;
         }
 
         Ok(())
@@ -209,6 +216,23 @@ impl DataExporter {
 
     /// 导出为XML格式
     fn export_xml<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        self.write_xml_head(writer, &data.metadata, &data.categories)?;
+
+        // 时间记录
+        for entry in &data.time_entries {
+            write_xml_entry(writer, entry)?;
+        }
+
+        write_xml_tail(writer)
+    }
+
+    /// 写入XML开头：元数据、分类和时间记录的开始标签
+    fn write_xml_head<W: Write>(
+        &self,
+        writer: &mut W,
+        metadata: &ExportMetadata,
+        categories: &[Category],
+    ) -> Result<()> {
         writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
         writeln!(writer, "<timetracker_export>")?;
 
@@ -218,22 +242,22 @@ impl DataExporter {
             writeln!(
                 writer,
                 "    <export_time>{}</export_time>",
-                data.metadata.export_time.format("%Y-%m-%d %H:%M:%S")
+                metadata.export_time.format("%Y-%m-%d %H:%M:%S")
             )?;
             writeln!(
                 writer,
                 "    <version>{}</version>",
-                escape_xml(&data.metadata.version)
+                escape_xml(&metadata.version)
             )?;
             writeln!(
                 writer,
                 "    <total_entries>{}</total_entries>",
-                data.metadata.total_entries
+                metadata.total_entries
             )?;
             writeln!(
                 writer,
                 "    <total_categories>{}</total_categories>",
-                data.metadata.total_categories
+                metadata.total_categories
             )?;
             writeln!(writer, "  </metadata>")?;
         }
@@ -241,7 +265,7 @@ impl DataExporter {
         // 分类
         if self.options.include_categories {
             writeln!(writer, "  <categories>")?;
-            for category in &data.categories {
+            for category in categories {
                 writeln!(writer, "    <category>")?;
                 writeln!(
                     writer,
@@ -271,60 +295,14 @@ impl DataExporter {
             writeln!(writer, "  </categories>")?;
         }
 
-        // 时间记录
         writeln!(writer, "  <time_entries>")?;
-        for entry in &data.time_entries {
-            writeln!(writer, "    <time_entry>")?;
-            writeln!(
-                writer,
-                "      <id>{}</id>",
-                escape_xml(&entry.id.to_string())
-            )?;
-            writeln!(
-                writer,
-                "      <task_name>{}</task_name>",
-                escape_xml(&entry.task_name)
-            )?;
-            writeln!(
-                writer,
-                "      <category_id>{}</category_id>",
-                escape_xml(
-                    &entry
-                        .category_id
-                        .as_ref()
-                        .map(|id| id.to_string())
-                        .unwrap_or_default()
-                )
-            )?;
-            writeln!(
-                writer,
-                "      <start_time>{}</start_time>",
-                entry.start_time.format("%Y-%m-%d %H:%M:%S")
-            )?;
-            if let Some(end_time) = entry.end_time {
-                writeln!(
-                    writer,
-                    "      <end_time>{}</end_time>",
-                    end_time.format("%Y-%m-%d %H:%M:%S")
-                )?;
-            }
-            if let Some(ref desc) = entry.description {
-                writeln!(
-                    writer,
-                    "      <description>{}</description>",
-                    escape_xml(desc)
-                )?;
-            }
-            writeln!(writer, "    </time_entry>")?;
-        }
-        writeln!(writer, "  </time_entries>")?;
-
-        writeln!(writer, "</timetracker_export>")?;
         Ok(())
     }
 
     /// 导出为HTML格式
     fn export_html<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        let report = Report::from_export(data, &self.options);
+
         writeln!(writer, "<!DOCTYPE html>")?;
         writeln!(writer, "<html lang=\"zh-CN\">")?;
         writeln!(writer, "<head>")?;
@@ -333,7 +311,7 @@ impl DataExporter {
             writer,
             "  <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">"
         )?;
-        writeln!(writer, "  <title>TimeTracker 导出报告</title>")?;
+        writeln!(writer, "  <title>{}</title>", escape_html(&report.title))?;
         writeln!(writer, "  <style>")?;
         writeln!(
             writer,
@@ -350,179 +328,141 @@ impl DataExporter {
         writeln!(writer, "    th {{ background-color: #f2f2f2; }}")?;
         writeln!(
             writer,
-            "    .metadata {{ background-color: #f9f9f9; padding: 15px; margin: 20px 0; }}"
+            "    .fields {{ background-color: #f9f9f9; padding: 15px; margin: 20px 0; }}"
+        )?;
+        writeln!(writer, "    .chart {{ margin: 20px 0; }}")?;
+        writeln!(
+            writer,
+            "    .bar-row {{ display: flex; align-items: center; margin: 4px 0; }}"
+        )?;
+        writeln!(
+            writer,
+            "    .bar-label {{ width: 160px; }} .bar-value {{ margin-left: 8px; color: #555; }}"
         )?;
         writeln!(
             writer,
-            "    .statistics {{ background-color: #e9f7ef; padding: 15px; margin: 20px 0; }}"
+            "    .bar {{ height: 14px; background-color: #4a90d9; }}"
         )?;
         writeln!(writer, "  </style>")?;
         writeln!(writer, "</head>")?;
         writeln!(writer, "<body>")?;
 
-        writeln!(writer, "  <h1>TimeTracker 导出报告</h1>")?;
-
-        // 元数据
-        if self.options.include_metadata {
-            writeln!(writer, "  <div class=\"metadata\">")?;
-            writeln!(writer, "    <h2>导出信息</h2>")?;
-            writeln!(
-                writer,
-                "    <p><strong>导出时间:</strong> {}</p>",
-                data.metadata.export_time.format("%Y-%m-%d %H:%M:%S")
-            )?;
-            writeln!(
-                writer,
-                "    <p><strong>版本:</strong> {}</p>",
-                escape_html(&data.metadata.version)
-            )?;
-            writeln!(
-                writer,
-                "    <p><strong>记录总数:</strong> {}</p>",
-                data.metadata.total_entries
-            )?;
-            writeln!(
-                writer,
-                "    <p><strong>分类总数:</strong> {}</p>",
-                data.metadata.total_categories
-            )?;
-            writeln!(writer, "  </div>")?;
-        }
-
-        // 统计信息
-        if let Some(ref stats) = data.statistics {
-            writeln!(writer, "  <div class=\"statistics\">")?;
-            writeln!(writer, "    <h2>统计信息</h2>")?;
-            writeln!(
-                writer,
-                "    <p><strong>总时间:</strong> {}</p>",
-                crate::utils::format_duration(stats.total_time)
-            )?;
-            writeln!(
-                writer,
-                "    <p><strong>平均会话时间:</strong> {}</p>",
-                crate::utils::format_duration(stats.average_session_time)
-            )?;
-            if let Some(ref day) = stats.most_productive_day {
-                writeln!(
-                    writer,
-                    "    <p><strong>最高效的一天:</strong> {}</p>",
-                    escape_html(day)
-                )?;
-            }
-            if let Some(ref category) = stats.most_used_category {
-                writeln!(
-                    writer,
-                    "    <p><strong>最常用分类:</strong> {}</p>",
-                    escape_html(category)
-                )?;
+        writeln!(writer, "  <h1>{}</h1>", escape_html(&report.title))?;
+        writeln!(writer, "  <p>{}</p>", escape_html(&report.subtitle))?;
+
+        for section in &report.sections {
+            writeln!(writer, "  <h2>{}</h2>", escape_html(&section.title))?;
+
+            for block in &section.blocks {
+                match block {
+                    ReportBlock::Fields(fields) => {
+                        writeln!(writer, "  <div class=\"fields\">")?;
+                        for (name, value) in fields {
+                            writeln!(
+                                writer,
+                                "    <p><strong>{}:</strong> {}</p>",
+                                escape_html(name),
+                                escape_html(value)
+                            )?;
+                        }
+                        writeln!(writer, "  </div>")?;
+                    }
+                    ReportBlock::Table(table) => {
+                        writeln!(writer, "  <table>")?;
+                        writeln!(writer, "    <thead>")?;
+                        write!(writer, "      <tr>")?;
+                        for header in &table.headers {
+                            write!(writer, "<th>{}</th>", escape_html(header))?;
+                        }
+                        writeln!(writer, "</tr>")?;
+                        writeln!(writer, "    </thead>")?;
+                        writeln!(writer, "    <tbody>")?;
+                        for row in &table.rows {
+                            writeln!(writer, "      <tr>")?;
+                            for cell in row {
+                                writeln!(writer, "        <td>{}</td>", escape_html(cell))?;
+                            }
+                            writeln!(writer, "      </tr>")?;
+                        }
+                        writeln!(writer, "    </tbody>")?;
+                        writeln!(writer, "  </table>")?;
+                    }
+                    ReportBlock::BarChart(chart) => {
+                        let max_value = chart.max_value();
+                        writeln!(writer, "  <div class=\"chart\">")?;
+                        writeln!(writer, "    <h3>{}</h3>", escape_html(&chart.title))?;
+                        for bar in &chart.bars {
+                            let percent = if max_value > 0.0 {
+                                bar.value / max_value * 70.0
+                            } else {
+                                0.0
+                            };
+                            writeln!(
+                                writer,
+                                "    <div class=\"bar-row\"><span class=\"bar-label\">{}</span><div class=\"bar\" style=\"width: {:.1}%\"></div><span class=\"bar-value\">{}</span></div>",
+                                escape_html(&bar.label),
+                                percent,
+                                escape_html(&bar.display)
+                            )?;
+                        }
+                        writeln!(writer, "  </div>")?;
+                    }
+                }
             }
-            writeln!(writer, "  </div>")?;
         }
 
-        // 分类表格
-        if self.options.include_categories && !data.categories.is_empty() {
-            writeln!(writer, "  <h2>分类列表</h2>")?;
-            writeln!(writer, "  <table>")?;
-            writeln!(writer, "    <thead>")?;
-            writeln!(
-                writer,
-                "      <tr><th>名称</th><th>描述</th><th>颜色</th><th>创建时间</th></tr>"
-            )?;
-            writeln!(writer, "    </thead>")?;
-            writeln!(writer, "    <tbody>")?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
 
-            for category in &data.categories {
-                writeln!(writer, "      <tr>")?;
-                writeln!(writer, "        <td>{}</td>", escape_html(&category.name))?;
-                writeln!(
-                    writer,
-                    "        <td>{}</td>",
-                    escape_html(category.description.as_deref().unwrap_or(""))
-                )?;
-                writeln!(
-                    writer,
-                    "        <td style=\"background-color: {}\">{}</td>",
-                    category.color,
-                    escape_html(&category.color.to_hex())
-                )?;
-                writeln!(
-                    writer,
-                    "        <td>{}</td>",
-                    category.created_at.format("%Y-%m-%d %H:%M")
-                )?;
-                writeln!(writer, "      </tr>")?;
-            }
+        Ok(())
+    }
 
-            writeln!(writer, "    </tbody>")?;
-            writeln!(writer, "  </table>")?;
-        }
+    /// 导出为PDF格式
+    fn export_pdf<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        let report = Report::from_export(data, &self.options);
+        let font = match &self.options.pdf_font {
+            Some(path) => PdfFont::load(path)?,
+            None => PdfFont::find_system(),
+        };
+        crate::utils::pdf::write_report(&report, font, writer)
+    }
 
-        // 时间记录表格
-        writeln!(writer, "  <h2>时间记录</h2>")?;
-        writeln!(writer, "  <table>")?;
-        writeln!(writer, "    <thead>")?;
-        writeln!(writer, "      <tr><th>任务名称</th><th>分类</th><th>开始时间</th><th>结束时间</th><th>持续时间</th><th>描述</th></tr>")?;
-        writeln!(writer, "    </thead>")?;
-        writeln!(writer, "    <tbody>")?;
+    /// 导出为iCalendar格式
+    fn export_ics<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        crate::utils::ical::write_calendar(data, writer)
+    }
 
-        let category_map: HashMap<String, String> = data
-            .categories
-            .iter()
-            .map(|c| (c.id.to_string(), c.name.clone()))
-            .collect();
+    /// 导出为Excel工作簿
+    fn export_xlsx<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        crate::utils::xlsx::write_workbook(data, &self.options, writer)?;
+        Ok(())
+    }
 
+    /// 导出为Markdown格式
+    fn export_markdown<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+        self.write_markdown_head(
+            writer,
+            &data.metadata,
+            data.statistics.as_ref(),
+            &data.categories,
+        )?;
+
+        let category_map = category_names(&data.categories);
         for entry in &data.time_entries {
-            let category_name = category_map
-                .get(
-                    &entry
-                        .category_id
-                        .as_ref()
-                        .map(|id| id.to_string())
-                        .unwrap_or_default(),
-                )
-                .map(|s| s.as_str())
-                .unwrap_or("未知分类");
-
-            let end_time = entry
-                .end_time
-                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
-                .unwrap_or_else(|| "进行中".to_string());
-
-            let duration = if let Some(end) = entry.end_time {
-                let dur = end.signed_duration_since(entry.start_time);
-                crate::utils::format_duration(dur)
-            } else {
-                "进行中".to_string()
-            };
-
-            writeln!(writer, "      <tr>")?;
-            writeln!(writer, "        <td>{}</td>", escape_html(&entry.task_name))?;
-            writeln!(writer, "        <td>{}</td>", escape_html(category_name))?;
-            writeln!(
-                writer,
-                "        <td>{}</td>",
-                entry.start_time.format("%Y-%m-%d %H:%M:%S")
-            )?;
-            writeln!(writer, "        <td>{}</td>", end_time)?;
-            writeln!(writer, "        <td>{}</td>", duration)?;
-            writeln!(
-                writer,
-                "        <td>{}</td>",
-                escape_html(entry.description.as_deref().unwrap_or(""))
-            )?
+            write_markdown_entry(writer, entry, &category_map)?;
         }
 
-        writeln!(writer, "    </tbody>")?;
-        writeln!(writer, "  </table>")?;
-        writeln!(writer, "</body>")?;
-        writeln!(writer, "</html>")?;
-
-        Ok(())
+        write_markdown_tail(writer, data)
     }
 
-    /// 导出为Markdown格式
-    fn export_markdown<W: Write>(&self, data: &ExportData, writer: &mut W) -> Result<()> {
+    /// 写入Markdown开头：元数据、统计、分类列表和时间记录表头
+    fn write_markdown_head<W: Write>(
+        &self,
+        writer: &mut W,
+        metadata: &ExportMetadata,
+        statistics: Option<&ExportStatistics>,
+        categories: &[Category],
+    ) -> Result<()> {
         writeln!(writer, "# TimeTracker 导出报告")?;
         writeln!(writer)?;
 
@@ -533,16 +473,16 @@ impl DataExporter {
             writeln!(
                 writer,
                 "- **导出时间:** {}",
-                data.metadata.export_time.format("%Y-%m-%d %H:%M:%S")
+                metadata.export_time.format("%Y-%m-%d %H:%M:%S")
             )?;
-            writeln!(writer, "- **版本:** {}", data.metadata.version)?;
-            writeln!(writer, "- **记录总数:** {}", data.metadata.total_entries)?;
-            writeln!(writer, "- **分类总数:** {}", data.metadata.total_categories)?;
+            writeln!(writer, "- **版本:** {}", metadata.version)?;
+            writeln!(writer, "- **记录总数:** {}", metadata.total_entries)?;
+            writeln!(writer, "- **分类总数:** {}", metadata.total_categories)?;
             writeln!(writer)?;
         }
 
         // 统计信息
-        if let Some(ref stats) = data.statistics {
+        if let Some(stats) = statistics {
             writeln!(writer, "## 统计信息")?;
             writeln!(writer)?;
             writeln!(
@@ -565,13 +505,13 @@ impl DataExporter {
         }
 
         // 分类列表
-        if self.options.include_categories && !data.categories.is_empty() {
+        if self.options.include_categories && !categories.is_empty() {
             writeln!(writer, "## 分类列表")?;
             writeln!(writer)?;
             writeln!(writer, "| 名称 | 描述 | 颜色 | 创建时间 |")?;
             writeln!(writer, "|------|------|------|----------|")?;
 
-            for category in &data.categories {
+            for category in categories {
                 writeln!(
                     writer,
                     "| {} | {} | {} | {} |",
@@ -596,50 +536,194 @@ impl DataExporter {
             "|----------|------|----------|----------|----------|------|"
         )?;
 
-        let category_map: HashMap<String, String> = data
-            .categories
-            .iter()
-            .map(|c| (c.id.to_string(), c.name.clone()))
-            .collect();
+        Ok(())
+    }
+}
 
-        for entry in &data.time_entries {
-            let category_name = category_map
-                .get(
-                    &entry
-                        .category_id
-                        .as_ref()
-                        .map(|id| id.to_string())
-                        .unwrap_or_default(),
-                )
-                .map(|s| s.as_str())
-                .unwrap_or("未知分类");
-
-            let end_time = entry
-                .end_time
-                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
-                .unwrap_or_else(|| "进行中".to_string());
-
-            let duration = if let Some(end) = entry.end_time {
-                let dur = end.signed_duration_since(entry.start_time);
-                crate::utils::format_duration(dur)
-            } else {
-                "进行中".to_string()
-            };
+/// 分类ID到名称的映射
+fn category_names(categories: &[Category]) -> HashMap<String, String> {
+    categories
+        .iter()
+        .map(|c| (c.id.to_string(), c.name.clone()))
+        .collect()
+}
 
-            writeln!(
-                writer,
-                "| {} | {} | {} | {} | {} | {} |",
-                escape_markdown(&entry.task_name),
-                escape_markdown(category_name),
-                entry.start_time.format("%Y-%m-%d %H:%M:%S"),
-                end_time,
-                duration,
-                escape_markdown(entry.description.as_deref().unwrap_or(""))
-            )?;
-        }
+/// 时间记录的分类名称、结束时间和持续时间文本
+fn entry_columns<'a>(
+    entry: &TimeEntry,
+    category_map: &'a HashMap<String, String>,
+) -> (&'a str, String, String) {
+    let category_name = category_map
+        .get(
+            &entry
+                .category_id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        )
+        .map(|s| s.as_str())
+        .unwrap_or("未知分类");
+
+    let end_time = entry
+        .end_time
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "进行中".to_string());
+
+    let duration = if let Some(end) = entry.end_time {
+        let dur = end.signed_duration_since(entry.start_time);
+        crate::utils::format_duration(dur)
+    } else {
+        "进行中".to_string()
+    };
 
-        Ok(())
+    (category_name, end_time, duration)
+}
+
+/// 写入CSV头部
+fn write_csv_header<W: Write>(writer: &mut W) -> Result<()> {
+    writeln!(writer, "ID,任务名称,分类,开始时间,结束时间,持续时间,描述")?;
+    Ok(())
+}
+
+/// 写入一行CSV时间记录
+fn write_csv_entry<W: Write>(
+    writer: &mut W,
+    entry: &TimeEntry,
+    category_map: &HashMap<String, String>,
+) -> Result<()> {
+    let (category_name, end_time, duration) = entry_columns(entry, category_map);
+    writeln!(
+        writer,
+        "{},{},{},{},{},{},{}",
+        escape_csv(&entry.id.to_string()),
+        escape_csv(&entry.task_name),
+        escape_csv(category_name),
+        entry.start_time.format("%Y-%m-%d %H:%M:%S"),
+        end_time,
+        duration,
+        escape_csv(entry.description.as_deref().unwrap_or(""))
+    )?;
+    Ok(())
+}
+
+/// 写入一条XML时间记录
+fn write_xml_entry<W: Write>(writer: &mut W, entry: &TimeEntry) -> Result<()> {
+    writeln!(writer, "    <time_entry>")?;
+    writeln!(
+        writer,
+        "      <id>{}</id>",
+        escape_xml(&entry.id.to_string())
+    )?;
+    writeln!(
+        writer,
+        "      <task_name>{}</task_name>",
+        escape_xml(&entry.task_name)
+    )?;
+    writeln!(
+        writer,
+        "      <category_id>{}</category_id>",
+        escape_xml(
+            &entry
+                .category_id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default()
+        )
+    )?;
+    writeln!(
+        writer,
+        "      <start_time>{}</start_time>",
+        entry.start_time.format("%Y-%m-%d %H:%M:%S")
+    )?;
+    if let Some(end_time) = entry.end_time {
+        writeln!(
+            writer,
+            "      <end_time>{}</end_time>",
+            end_time.format("%Y-%m-%d %H:%M:%S")
+        )?;
+    }
+    if let Some(ref desc) = entry.description {
+        writeln!(
+            writer,
+            "      <description>{}</description>",
+            escape_xml(desc)
+        )?;
     }
+    writeln!(writer, "    </time_entry>")?;
+    Ok(())
+}
+
+/// 写入XML结尾
+fn write_xml_tail<W: Write>(writer: &mut W) -> Result<()> {
+    writeln!(writer, "  </time_entries>")?;
+    writeln!(writer, "</timetracker_export>")?;
+    Ok(())
+}
+
+/// 写入一行Markdown时间记录
+fn write_markdown_entry<W: Write>(
+    writer: &mut W,
+    entry: &TimeEntry,
+    category_map: &HashMap<String, String>,
+) -> Result<()> {
+    let (category_name, end_time, duration) = entry_columns(entry, category_map);
+    writeln!(
+        writer,
+        "| {} | {} | {} | {} | {} | {} |",
+        escape_markdown(&entry.task_name),
+        escape_markdown(category_name),
+        entry.start_time.format("%Y-%m-%d %H:%M:%S"),
+        end_time,
+        duration,
+        escape_markdown(entry.description.as_deref().unwrap_or(""))
+    )?;
+    Ok(())
+}
+
+/// 写入Markdown结尾：财务汇总和任务（有数据时）
+fn write_markdown_tail<W: Write>(writer: &mut W, data: &ExportData) -> Result<()> {
+    if let Some(ref financial) = data.financial {
+        let stats = &financial.stats;
+        writeln!(writer)?;
+        writeln!(writer, "## 财务汇总")?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "- **统计期间:** {} 至 {}",
+            stats.period_start.format("%Y-%m-%d"),
+            stats.period_end.format("%Y-%m-%d")
+        )?;
+        writeln!(
+            writer,
+            "- **总收入:** {:.2} {}",
+            stats.total_income, stats.currency
+        )?;
+        writeln!(
+            writer,
+            "- **总支出:** {:.2} {}",
+            stats.total_expense, stats.currency
+        )?;
+        writeln!(
+            writer,
+            "- **净收入:** {:.2} {}",
+            stats.net_income, stats.currency
+        )?;
+        writeln!(writer, "- **交易笔数:** {}", stats.transaction_count)?;
+    }
+
+    if !data.tasks.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "## 任务")?;
+        writeln!(writer)?;
+        writeln!(writer, "| {} |", TASK_HEADERS.join(" | "))?;
+        writeln!(writer, "|{}", "------|".repeat(TASK_HEADERS.len()))?;
+        for row in task_rows(data) {
+            let cells: Vec<String> = row.iter().map(|cell| escape_markdown(cell)).collect();
+            writeln!(writer, "| {} |", cells.join(" | "))?;
+        }
+    }
+
+    Ok(())
 }
 
 /// 转义CSV字段
@@ -685,68 +769,91 @@ pub fn calculate_export_statistics(
     time_entries: &[TimeEntry],
     categories: &[Category],
 ) -> ExportStatistics {
-    let mut total_time = chrono::Duration::zero();
-    let mut category_breakdown = HashMap::new();
-    let mut daily_totals = HashMap::new();
-    let mut completed_entries = 0;
+    let mut accumulator = StatisticsAccumulator::new(categories);
+    for entry in time_entries {
+        accumulator.add(entry);
+    }
+    accumulator.finish()
+}
 
-    // 创建分类映射
-    let category_map: HashMap<String, String> = categories
-        .iter()
-        .map(|c| (c.id.to_string(), c.name.clone()))
-        .collect();
+/// 逐条累加的导出统计，流式导出时不需要持有全部时间记录
+pub(crate) struct StatisticsAccumulator {
+    category_map: HashMap<String, String>,
+    total_time: chrono::Duration,
+    category_breakdown: HashMap<String, chrono::Duration>,
+    daily_totals: HashMap<String, chrono::Duration>,
+    completed_entries: i32,
+}
 
-    for entry in time_entries {
-        if let Some(end_time) = entry.end_time {
-            let duration = end_time.signed_duration_since(entry.start_time);
-            total_time += duration;
-            completed_entries += 1;
-
-            // 按分类统计
-            let category_name = if let Some(category_id) = &entry.category_id {
-                category_map
-                    .get(&category_id.to_string())
-                    .cloned()
-                    .unwrap_or_else(|| "未知分类".to_string())
-            } else {
-                "未分类".to_string()
-            };
-
-            *category_breakdown
-                .entry(category_name)
-                .or_insert(chrono::Duration::zero()) += duration;
-
-            // 按日期统计
-            let date_key = entry.start_time.format("%Y-%m-%d").to_string();
-            *daily_totals
-                .entry(date_key)
-                .or_insert(chrono::Duration::zero()) += duration;
+impl StatisticsAccumulator {
+    pub(crate) fn new(categories: &[Category]) -> Self {
+        Self {
+            category_map: category_names(categories),
+            total_time: chrono::Duration::zero(),
+            category_breakdown: HashMap::new(),
+            daily_totals: HashMap::new(),
+            completed_entries: 0,
         }
     }
 
-    let average_session_time = if completed_entries > 0 {
-        total_time / completed_entries
-    } else {
-        chrono::Duration::zero()
-    };
+    pub(crate) fn add(&mut self, entry: &TimeEntry) {
+        let Some(end_time) = entry.end_time else {
+            return;
+        };
+        let duration = end_time.signed_duration_since(entry.start_time);
+        self.total_time += duration;
+        self.completed_entries += 1;
+
+        // 按分类统计
+        let category_name = if let Some(category_id) = &entry.category_id {
+            self.category_map
+                .get(&category_id.to_string())
+                .cloned()
+                .unwrap_or_else(|| "未知分类".to_string())
+        } else {
+            "未分类".to_string()
+        };
 
-    let most_productive_day = daily_totals
-        .iter()
-        .max_by_key(|(_, duration)| *duration)
-        .map(|(date, _)| date.clone());
+        *self
+            .category_breakdown
+            .entry(category_name)
+            .or_insert(chrono::Duration::zero()) += duration;
+
+        // 按日期统计
+        let date_key = entry.start_time.format("%Y-%m-%d").to_string();
+        *self
+            .daily_totals
+            .entry(date_key)
+            .or_insert(chrono::Duration::zero()) += duration;
+    }
 
-    let most_used_category = category_breakdown
-        .iter()
-        .max_by_key(|(_, duration)| *duration)
-        .map(|(category, _)| category.clone());
+    pub(crate) fn finish(self) -> ExportStatistics {
+        let average_session_time = if self.completed_entries > 0 {
+            self.total_time / self.completed_entries
+        } else {
+            chrono::Duration::zero()
+        };
 
-    ExportStatistics {
-        total_time,
-        average_session_time,
-        category_breakdown,
-        daily_totals,
-        most_productive_day,
-        most_used_category,
+        let most_productive_day = self
+            .daily_totals
+            .iter()
+            .max_by_key(|(_, duration)| *duration)
+            .map(|(date, _)| date.clone());
+
+        let most_used_category = self
+            .category_breakdown
+            .iter()
+            .max_by_key(|(_, duration)| *duration)
+            .map(|(category, _)| category.clone());
+
+        ExportStatistics {
+            total_time: self.total_time,
+            average_session_time,
+            category_breakdown: self.category_breakdown,
+            daily_totals: self.daily_totals,
+            most_productive_day,
+            most_used_category,
+        }
     }
 }
 
@@ -786,6 +893,8 @@ pub fn create_export_data(
         categories,
         time_entries,
         statistics,
+        financial: None,
+        tasks: Vec::new(),
     }
 }
 
@@ -846,6 +955,8 @@ pub fn create_simple_export_data(
         categories: converted_categories,
         time_entries,
         statistics: None,
+        financial: None,
+        tasks: Vec::new(),
     }
 }
 
@@ -903,6 +1014,8 @@ mod tests {
             categories,
             time_entries,
             statistics: None,
+            financial: None,
+            tasks: Vec::new(),
         }
     }
 
@@ -913,6 +1026,10 @@ mod tests {
             Some(ExportFormat::Json)
         );
         assert_eq!(ExportFormat::from_extension("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(
+            ExportFormat::from_extension("xlsx"),
+            Some(ExportFormat::Xlsx)
+        );
         assert_eq!(ExportFormat::from_extension("unknown"), None);
     }
 
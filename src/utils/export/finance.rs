@@ -0,0 +1,272 @@
+//! # 财务数据汇总
+//!
+//! 从存储中读取账户、交易、交易分类和预算，生成导出和报告使用的 [`FinancialSummary`]：
+//! - 统计、分类占比和月度趋势复用 [`AnalyticsManager`] 的计算
+//! - 存储接口返回的账户和交易是精简模型，这里补齐为记账模型，
+//!   交易的货币取所属账户的货币
+//! - 数据库不保存预算的已花费金额，按统计期末所在的预算周期从支出交易中计算
+
+use super::FinancialSummary;
+use crate::core::accounting::AnalyticsManager;
+use crate::storage::accounting_models::{
+    Account, AccountType, Budget, BudgetPeriod, Transaction, TransactionStatus, TransactionType,
+};
+use crate::storage::backend::TimeRange;
+use crate::storage::{models, StorageBackend};
+use anyhow::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 读取存储中的财务数据并生成汇总
+///
+/// 交易明细、统计和趋势只包含 `range` 内的交易，`range` 为空时包含全部交易。
+/// 预算的已花费金额按范围结束日期（未指定范围时为 `today`）所在的预算周期计算
+pub fn load_financial_summary(
+    storage: &dyn StorageBackend,
+    range: Option<TimeRange>,
+    today: NaiveDate,
+) -> Result<FinancialSummary> {
+    let accounts: Vec<Account> = storage
+        .get_all_accounts()?
+        .into_iter()
+        .map(full_account)
+        .collect();
+    let currencies: HashMap<Uuid, String> = accounts
+        .iter()
+        .map(|account| (account.id, account.currency.clone()))
+        .collect();
+    let all_transactions: Vec<Transaction> = storage
+        .get_all_transactions()?
+        .into_iter()
+        .map(|transaction| full_transaction(transaction, &currencies))
+        .collect();
+    let transaction_categories = storage.get_all_transaction_categories()?;
+
+    let (start, end) = match range {
+        Some((start, end)) => (start.date_naive(), end.date_naive()),
+        None => {
+            let dates = all_transactions.iter().map(|t| t.transaction_date);
+            (
+                dates.clone().min().unwrap_or(today).min(today),
+                dates.max().unwrap_or(today).max(today),
+            )
+        }
+    };
+    let reference = range.map_or(today, |_| end);
+    let transactions: Vec<Transaction> = all_transactions
+        .iter()
+        .filter(|t| t.transaction_date >= start && t.transaction_date <= end)
+        .cloned()
+        .collect();
+
+    let mut analytics = AnalyticsManager::new();
+    if let Some(account) = accounts.iter().find(|account| account.is_active) {
+        analytics.set_default_currency(account.currency.clone());
+    }
+    let stats = analytics.generate_financial_stats(&transactions, &accounts, start, end)?;
+    let category_breakdown = analytics.generate_category_breakdown(
+        &transactions,
+        &transaction_categories,
+        TransactionType::Expense,
+    )?;
+    let monthly_trends = analytics.generate_monthly_trend(&transactions, start, end)?;
+
+    let budgets = storage
+        .get_all_budgets()?
+        .into_iter()
+        .map(|budget| with_usage(budget, &all_transactions, reference))
+        .collect();
+
+    Ok(FinancialSummary {
+        stats,
+        category_breakdown,
+        monthly_trends,
+        accounts,
+        transaction_categories,
+        transactions,
+        budgets,
+    })
+}
+
+/// 计算预算在参考日期所在周期内的已花费和剩余金额
+fn with_usage(mut budget: Budget, transactions: &[Transaction], reference: NaiveDate) -> Budget {
+    let (start, end) = budget_window(&budget, reference);
+    budget.spent_amount = transactions
+        .iter()
+        .filter(|t| {
+            t.transaction_type == TransactionType::Expense
+                && t.category_id == Some(budget.category_id)
+                && t.transaction_date >= start
+                && t.transaction_date <= end
+        })
+        .map(|t| t.amount)
+        .sum();
+    budget.remaining_amount = (budget.amount - budget.spent_amount).max(0.0);
+    budget
+}
+
+/// 参考日期所在的预算周期（含两端），不超出预算本身的起止日期
+fn budget_window(budget: &Budget, reference: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let (start, end) = match budget.period {
+        BudgetPeriod::Daily => (reference, reference),
+        BudgetPeriod::Weekly => {
+            let start =
+                reference - Duration::days(reference.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::days(6))
+        }
+        BudgetPeriod::Monthly => {
+            let start = reference.with_day(1).unwrap_or(reference);
+            let end = start
+                .checked_add_months(Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .unwrap_or(reference);
+            (start, end)
+        }
+        BudgetPeriod::Yearly => (
+            NaiveDate::from_ymd_opt(reference.year(), 1, 1).unwrap_or(reference),
+            NaiveDate::from_ymd_opt(reference.year(), 12, 31).unwrap_or(reference),
+        ),
+    };
+    (
+        start.max(budget.start_date),
+        budget.end_date.map_or(end, |date| end.min(date)),
+    )
+}
+
+fn full_account(account: models::Account) -> Account {
+    Account {
+        id: account.id,
+        name: account.name,
+        account_type: parse_enum(&account.account_type).unwrap_or(AccountType::Other),
+        currency: account.currency,
+        balance: account.balance,
+        initial_balance: account.balance,
+        description: None,
+        is_active: account.is_active,
+        is_default: false,
+        created_at: account.created_at,
+        updated_at: account.updated_at,
+    }
+}
+
+fn full_transaction(
+    transaction: models::Transaction,
+    currencies: &HashMap<Uuid, String>,
+) -> Transaction {
+    Transaction {
+        id: transaction.id,
+        transaction_type: parse_enum(&transaction.transaction_type)
+            .unwrap_or(TransactionType::Expense),
+        amount: transaction.amount,
+        currency: currencies
+            .get(&transaction.account_id)
+            .cloned()
+            .unwrap_or_else(|| "CNY".to_string()),
+        description: transaction.description,
+        account_id: transaction.account_id,
+        category_id: transaction.category_id,
+        to_account_id: None,
+        status: TransactionStatus::Completed,
+        transaction_date: transaction.transaction_date.date_naive(),
+        tags: transaction.tags,
+        receipt_path: None,
+        created_at: transaction.created_at,
+        updated_at: transaction.updated_at,
+    }
+}
+
+fn parse_enum<T: DeserializeOwned>(name: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_support::{insert_sample_finance, temp_database};
+    use chrono::{Local, TimeZone};
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_load_financial_summary() {
+        let (_dir, database) = temp_database();
+        insert_sample_finance(&database);
+
+        let range = (
+            Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            Local.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap(),
+        );
+        let summary = load_financial_summary(&database, Some(range), date(6, 1)).unwrap();
+        assert_eq!(summary.stats.total_income, 0.0);
+        assert_eq!(summary.stats.total_expense, 80.0);
+        assert_eq!(summary.stats.account_balance, 1000.0);
+        assert_eq!(summary.transactions.len(), 2);
+        assert!(summary.transactions.iter().all(|t| t.currency == "CNY"));
+        assert_eq!(summary.accounts[0].account_type, AccountType::Cash);
+        assert_eq!(summary.category_breakdown.len(), 1);
+        assert_eq!(summary.category_breakdown[0].category_name, "餐饮");
+        assert_eq!(summary.monthly_trends.len(), 1);
+        assert_eq!(summary.monthly_trends[0].expense, 80.0);
+        assert!(summary
+            .transaction_categories
+            .iter()
+            .any(|c| c.name == "餐饮"));
+
+        // 范围结束于 3 月，预算按 3 月计算
+        let budget = &summary.budgets[0];
+        assert_eq!(budget.spent_amount, 80.0);
+        assert_eq!(budget.remaining_amount, 20.0);
+
+        // 不指定范围时包含全部交易，预算按当天所在的月份计算
+        let summary = load_financial_summary(&database, None, date(4, 15)).unwrap();
+        assert_eq!(summary.transactions.len(), 3);
+        assert_eq!(summary.stats.total_income, 5000.0);
+        assert_eq!(summary.stats.period_start, date(2, 10));
+        assert_eq!(summary.stats.period_end, date(4, 15));
+        assert_eq!(summary.budgets[0].spent_amount, 0.0);
+    }
+
+    #[test]
+    fn test_budget_window() {
+        let mut budget = Budget {
+            id: Uuid::new_v4(),
+            name: "预算".to_string(),
+            category_id: Uuid::new_v4(),
+            amount: 100.0,
+            currency: "CNY".to_string(),
+            period: BudgetPeriod::Weekly,
+            start_date: date(3, 1),
+            end_date: Some(date(3, 20)),
+            spent_amount: 0.0,
+            remaining_amount: 100.0,
+            is_active: true,
+            created_at: Local::now(),
+            updated_at: None,
+        };
+        // 2024-03-06 是周三
+        assert_eq!(
+            budget_window(&budget, date(3, 6)),
+            (date(3, 4), date(3, 10))
+        );
+        assert_eq!(
+            budget_window(&budget, date(3, 19)),
+            (date(3, 18), date(3, 20))
+        );
+
+        budget.period = BudgetPeriod::Monthly;
+        assert_eq!(
+            budget_window(&budget, date(3, 6)),
+            (date(3, 1), date(3, 20))
+        );
+        budget.period = BudgetPeriod::Yearly;
+        budget.end_date = None;
+        assert_eq!(
+            budget_window(&budget, date(2, 29)),
+            (date(3, 1), date(12, 31))
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! # 流式导出
+//!
+//! 直接从存储游标逐条读取时间记录并写入输出，内存占用不随记录数增长：
+//! - JSON：结构与 [`ExportData`](super::ExportData) 相同，`time_entries` 数组逐条写出，统计信息写在数组之后
+//! - CSV / XML：与 [`DataExporter`] 的输出格式一致
+//! - Markdown：统计信息位于表格之前，需要先扫描一遍计算统计，再逐行写出表格
+//!
+//! 导出过程中按固定间隔回调进度，可以通过 [`CancelToken`] 从其他线程取消
+
+use super::finance::load_financial_summary;
+use super::{
+    category_names, create_export_data, write_csv_entry, write_csv_header, write_markdown_entry,
+    write_xml_entry, write_xml_tail, DataExporter, ExportData, ExportFormat, ExportMetadata,
+    ExportOptions, ExportStatistics, StatisticsAccumulator,
+};
+use crate::core::Category;
+use crate::storage::models::TimeEntry;
+use crate::storage::StorageBackend;
+use anyhow::{bail, Result};
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 每写出多少条记录回调一次进度
+const PROGRESS_INTERVAL: usize = 100;
+
+/// 导出进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportProgress {
+    /// 已写出的记录数
+    pub processed: usize,
+    /// 记录总数（开始导出时统计）
+    pub total: usize,
+}
+
+impl ExportProgress {
+    /// 完成比例（0.0 - 1.0）
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.processed as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+/// 取消标记，克隆后可在其他线程调用 [`CancelToken::cancel`]
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 流式导出结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamExportResult {
+    /// 写出的时间记录数
+    pub entries: usize,
+    /// 是否被取消（取消时输出不完整）
+    pub cancelled: bool,
+}
+
+/// 流式导出器
+pub struct StreamingExporter<'a> {
+    storage: &'a dyn StorageBackend,
+    options: ExportOptions,
+    progress: Option<Box<dyn FnMut(ExportProgress) + 'a>>,
+    cancel: CancelToken,
+}
+
+impl<'a> StreamingExporter<'a> {
+    pub fn new(storage: &'a dyn StorageBackend, options: ExportOptions) -> Self {
+        Self {
+            storage,
+            options,
+            progress: None,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// 是否支持流式导出该格式
+    pub fn supports(format: ExportFormat) -> bool {
+        matches!(
+            format,
+            ExportFormat::Json | ExportFormat::Csv | ExportFormat::Xml | ExportFormat::Markdown
+        )
+    }
+
+    /// 设置进度回调
+    pub fn with_progress(mut self, callback: impl FnMut(ExportProgress) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// 设置取消标记
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// 导出到文件，取消或失败时删除不完整的文件
+    pub fn export_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<StreamExportResult> {
+        let path = path.as_ref();
+        let result = File::create(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| self.export(BufWriter::new(file)));
+        match result {
+            Ok(result) if !result.cancelled => Ok(result),
+            other => {
+                let _ = fs::remove_file(path);
+                other
+            }
+        }
+    }
+
+    /// 导出到写入器
+    pub fn export<W: Write>(&mut self, mut writer: W) -> Result<StreamExportResult> {
+        let format = self.options.format;
+        if !Self::supports(format) {
+            bail!("{} 格式不支持流式导出", format.extension());
+        }
+
+        let range = self.options.date_range;
+        let total = self.storage.count_time_entries(range)?;
+        let categories: Vec<Category> = self
+            .storage
+            .get_all_categories()?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let metadata = self.metadata(total, categories.len());
+        let exporter = DataExporter::new(self.options.clone());
+        let category_map = category_names(&categories);
+
+        let result = match format {
+            ExportFormat::Json => {
+                let mut statistics = StatisticsAccumulator::new(&categories);
+                write!(writer, "{{\n  \"metadata\": ")?;
+                serde_json::to_writer(&mut writer, &metadata)?;
+                write!(writer, ",\n  \"categories\": ")?;
+                serde_json::to_writer(&mut writer, &categories)?;
+                write!(writer, ",\n  \"time_entries\": [")?;
+                let mut first = true;
+                let result = self.stream(total, |entry| {
+                    if !std::mem::take(&mut first) {
+                        writer.write_all(b",")?;
+                    }
+                    writer.write_all(b"\n    ")?;
+                    serde_json::to_writer(&mut writer, entry)?;
+                    statistics.add(entry);
+                    Ok(())
+                })?;
+                if !result.cancelled {
+                    write!(writer, "\n  ],\n  \"statistics\": ")?;
+                    if self.options.include_statistics {
+                        serde_json::to_writer(&mut writer, &statistics.finish())?;
+                    } else {
+                        write!(writer, "null")?;
+                    }
+                    writeln!(writer, "\n}}")?;
+                }
+                result
+            }
+            ExportFormat::Csv => {
+                write_csv_header(&mut writer)?;
+                self.stream(total, |entry| {
+                    write_csv_entry(&mut writer, entry, &category_map)
+                })?
+            }
+            ExportFormat::Xml => {
+                exporter.write_xml_head(&mut writer, &metadata, &categories)?;
+                let result = self.stream(total, |entry| write_xml_entry(&mut writer, entry))?;
+                if !result.cancelled {
+                    write_xml_tail(&mut writer)?;
+                }
+                result
+            }
+            ExportFormat::Markdown => {
+                let statistics = if self.options.include_statistics {
+                    match self.scan_statistics(&categories)? {
+                        Some(statistics) => Some(statistics),
+                        None => {
+                            return Ok(StreamExportResult {
+                                entries: 0,
+                                cancelled: true,
+                            })
+                        }
+                    }
+                } else {
+                    None
+                };
+                exporter.write_markdown_head(
+                    &mut writer,
+                    &metadata,
+                    statistics.as_ref(),
+                    &categories,
+                )?;
+                self.stream(total, |entry| {
+                    write_markdown_entry(&mut writer, entry, &category_map)
+                })?
+            }
+            _ => unreachable!(),
+        };
+
+        writer.flush()?;
+        Ok(result)
+    }
+
+    fn metadata(&self, total: usize, total_categories: usize) -> ExportMetadata {
+        let mut filters_applied = Vec::new();
+        if self.options.date_range.is_some() {
+            filters_applied.push("日期范围过滤".to_string());
+        }
+        ExportMetadata {
+            export_time: Local::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            total_entries: total,
+            total_categories,
+            date_range: self.options.date_range,
+            filters_applied,
+        }
+    }
+
+    /// 逐条读取并写出记录，处理进度回调和取消
+    fn stream(
+        &mut self,
+        total: usize,
+        mut write: impl FnMut(&TimeEntry) -> Result<()>,
+    ) -> Result<StreamExportResult> {
+        let storage = self.storage;
+        let cancel = &self.cancel;
+        let mut progress = self.progress.as_mut();
+        let mut report = |processed: usize| {
+            if let Some(callback) = progress.as_mut() {
+                callback(ExportProgress { processed, total });
+            }
+        };
+
+        report(0);
+        let mut processed = 0;
+        let mut cancelled = false;
+        let mut error = None;
+        storage.for_each_time_entry(self.options.date_range, &mut |entry| {
+            if cancel.is_cancelled() {
+                cancelled = true;
+                return false;
+            }
+            if let Err(e) = write(&entry) {
+                error = Some(e);
+                return false;
+            }
+            processed += 1;
+            if processed % PROGRESS_INTERVAL == 0 {
+                report(processed);
+            }
+            true
+        })?;
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+        if !cancelled && processed % PROGRESS_INTERVAL != 0 {
+            report(processed);
+        }
+        Ok(StreamExportResult {
+            entries: processed,
+            cancelled,
+        })
+    }
+
+    /// 预先扫描一遍计算统计信息，取消时返回 `None`
+    fn scan_statistics(&self, categories: &[Category]) -> Result<Option<ExportStatistics>> {
+        let mut statistics = StatisticsAccumulator::new(categories);
+        let mut cancelled = false;
+        self.storage
+            .for_each_time_entry(self.options.date_range, &mut |entry| {
+                if self.cancel.is_cancelled() {
+                    cancelled = true;
+                    return false;
+                }
+                statistics.add(&entry);
+                true
+            })?;
+        Ok((!cancelled).then(|| statistics.finish()))
+    }
+}
+
+/// 把存储中的数据导出到文件
+///
+/// 支持流式导出的格式直接写出；其余格式先读入内存再由 [`DataExporter`] 一次性写出，
+/// 此时只在读取前后回调进度，写出过程中不能取消
+pub fn export_storage_to_file<'a, P: AsRef<Path>>(
+    storage: &'a dyn StorageBackend,
+    options: ExportOptions,
+    path: P,
+    mut progress: impl FnMut(ExportProgress) + 'a,
+    cancel: CancelToken,
+) -> Result<StreamExportResult> {
+    if StreamingExporter::supports(options.format) {
+        return StreamingExporter::new(storage, options)
+            .with_progress(progress)
+            .with_cancel_token(cancel)
+            .export_to_file(path);
+    }
+
+    let total = storage.count_time_entries(options.date_range)?;
+    progress(ExportProgress {
+        processed: 0,
+        total,
+    });
+    let mut entries = Vec::with_capacity(total);
+    let mut cancelled = false;
+    storage.for_each_time_entry(options.date_range, &mut |entry| {
+        if cancel.is_cancelled() {
+            cancelled = true;
+            return false;
+        }
+        entries.push(entry);
+        true
+    })?;
+    if cancelled {
+        return Ok(StreamExportResult {
+            entries: 0,
+            cancelled: true,
+        });
+    }
+
+    let categories: Vec<Category> = storage
+        .get_all_categories()?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let mut data = create_export_data(entries, categories, &options);
+    load_format_data(storage, &options, &mut data)?;
+
+    let path = path.as_ref();
+    if let Err(error) = DataExporter::new(options).export_to_file(&data, path) {
+        let _ = fs::remove_file(path);
+        return Err(error);
+    }
+    let processed = data.time_entries.len();
+    progress(ExportProgress { processed, total });
+    Ok(StreamExportResult {
+        entries: processed,
+        cancelled: false,
+    })
+}
+
+/// 读取导出格式额外需要的数据
+///
+/// 日历导出包含有截止日期的任务；XLSX 包含任务和财务明细工作表；
+/// PDF 和 HTML 共用同一报告模型，包含财务汇总章节
+fn load_format_data(
+    storage: &dyn StorageBackend,
+    options: &ExportOptions,
+    data: &mut ExportData,
+) -> Result<()> {
+    if matches!(options.format, ExportFormat::Ics | ExportFormat::Xlsx) {
+        data.tasks = storage.get_all_tasks()?;
+    }
+    if matches!(
+        options.format,
+        ExportFormat::Xlsx | ExportFormat::Pdf | ExportFormat::Html
+    ) {
+        let today = data.metadata.export_time.date_naive();
+        data.financial = Some(load_financial_summary(storage, options.date_range, today)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::{CategoryStore, TaskStore, TimeEntryStore};
+    use crate::storage::models::{CategoryInsert, TimeEntryInsert};
+    use crate::storage::test_support::{insert_sample_finance, new_task};
+    use crate::storage::MemoryStorage;
+    use crate::utils::zip::ZipArchive;
+    use chrono::{Duration, TimeZone};
+    use uuid::Uuid;
+
+    fn sample_storage(count: usize) -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        let start = Local.with_ymd_and_hms(2015, 1, 1, 9, 0, 0).unwrap();
+        let category = CategoryInsert {
+            id: Uuid::new_v4(),
+            name: "工作".to_string(),
+            description: None,
+            color: "#FF0000".to_string(),
+            icon: "work".to_string(),
+            daily_target_seconds: None,
+            weekly_target_seconds: None,
+            is_active: true,
+            sort_order: 0,
+            parent_id: None,
+            created_at: start,
+        };
+        storage.insert_category(&category).unwrap();
+        for index in 0..count {
+            let start_time = start + Duration::days(index as i64);
+            storage
+                .insert_time_entry(&TimeEntryInsert {
+                    id: Uuid::new_v4(),
+                    task_name: format!("任务, {}", index),
+                    category_id: Some(category.id),
+                    start_time,
+                    end_time: Some(start_time + Duration::minutes(30)),
+                    duration_seconds: 1800,
+                    description: Some("<备注>".to_string()),
+                    tags: vec![],
+                    created_at: start_time,
+                })
+                .unwrap();
+        }
+        storage
+    }
+
+    fn options(format: ExportFormat) -> ExportOptions {
+        ExportOptions {
+            format,
+            include_metadata: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stream_matches_in_memory_export() {
+        let storage = sample_storage(250);
+        let mut entries = storage.get_all_time_entries().unwrap();
+        entries.sort_by_key(|entry| entry.start_time);
+        let categories: Vec<Category> = storage
+            .get_all_categories()
+            .unwrap()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let data =
+            super::super::create_export_data(entries, categories, &options(ExportFormat::Csv));
+
+        // CSV 和 XML 与一次性导出逐字节相同
+        let mut expected = Vec::new();
+        DataExporter::new(options(ExportFormat::Csv))
+            .export_csv(&data, &mut expected)
+            .unwrap();
+        let mut updates = Vec::new();
+        let mut streamed = Vec::new();
+        let result = StreamingExporter::new(&storage, options(ExportFormat::Csv))
+            .with_progress(|progress| updates.push(progress.processed))
+            .export(&mut streamed)
+            .unwrap();
+        assert_eq!(result.entries, 250);
+        assert_eq!(updates, vec![0, 100, 200, 250]);
+        assert_eq!(
+            String::from_utf8(streamed).unwrap(),
+            String::from_utf8(expected).unwrap()
+        );
+
+        let mut expected = Vec::new();
+        DataExporter::new(options(ExportFormat::Xml))
+            .export_xml(&data, &mut expected)
+            .unwrap();
+        let mut streamed = Vec::new();
+        StreamingExporter::new(&storage, options(ExportFormat::Xml))
+            .export(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, expected);
+
+        // JSON 可以按 ExportData 读回，统计信息与一次性计算一致
+        let mut json = Vec::new();
+        StreamingExporter::new(&storage, options(ExportFormat::Json))
+            .export(&mut json)
+            .unwrap();
+        let parsed: ExportData = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed.time_entries, data.time_entries);
+        assert_eq!(parsed.metadata.total_entries, 250);
+        let statistics = parsed.statistics.unwrap();
+        assert_eq!(
+            statistics.total_time,
+            data.statistics.as_ref().unwrap().total_time
+        );
+
+        let mut markdown = Vec::new();
+        StreamingExporter::new(&storage, options(ExportFormat::Markdown))
+            .export(&mut markdown)
+            .unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        let statistics_at = markdown.find("## 统计信息").unwrap();
+        assert!(statistics_at < markdown.find("## 时间记录").unwrap());
+        assert_eq!(markdown.matches("| 任务, ").count(), 250);
+
+        assert!(StreamingExporter::new(&storage, options(ExportFormat::Pdf))
+            .export(Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_stream_cancel_and_date_range() {
+        let storage = sample_storage(250);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.json");
+
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        let result = StreamingExporter::new(&storage, options(ExportFormat::Json))
+            .with_cancel_token(token)
+            .with_progress(move |progress| {
+                if progress.processed >= 100 {
+                    canceller.cancel();
+                }
+            })
+            .export_to_file(&path)
+            .unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.entries, 100);
+        assert!(!path.exists());
+
+        let start = Local.with_ymd_and_hms(2015, 1, 10, 0, 0, 0).unwrap();
+        let end = Local.with_ymd_and_hms(2015, 1, 19, 23, 59, 59).unwrap();
+        let mut last = None;
+        let result = StreamingExporter::new(
+            &storage,
+            ExportOptions {
+                date_range: Some((start, end)),
+                ..options(ExportFormat::Csv)
+            },
+        )
+        .with_progress(|progress| last = Some(progress))
+        .export_to_file(&path)
+        .unwrap();
+        assert_eq!(result.entries, 10);
+        assert_eq!(last.unwrap().fraction(), 1.0);
+        let csv = fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.lines().count(), 11);
+    }
+
+    #[test]
+    fn test_export_storage_to_file() {
+        let storage = sample_storage(150);
+        let dir = tempfile::tempdir().unwrap();
+
+        // 流式格式按间隔回调进度
+        let path = dir.path().join("export.csv");
+        let mut updates = Vec::new();
+        let result = export_storage_to_file(
+            &storage,
+            options(ExportFormat::Csv),
+            &path,
+            |progress| updates.push(progress.processed),
+            CancelToken::new(),
+        )
+        .unwrap();
+        assert_eq!(result.entries, 150);
+        assert_eq!(updates, vec![0, 100, 150]);
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 151);
+
+        // 其他格式一次性写出，只在开始和结束时回调
+        let path = dir.path().join("export.html");
+        let mut updates = Vec::new();
+        let result = export_storage_to_file(
+            &storage,
+            options(ExportFormat::Html),
+            &path,
+            |progress| updates.push(progress),
+            CancelToken::new(),
+        )
+        .unwrap();
+        assert_eq!(result.entries, 150);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].fraction(), 1.0);
+        assert!(fs::read_to_string(&path).unwrap().contains("任务, 149"));
+
+        // 开始前已取消时不生成文件
+        let token = CancelToken::new();
+        token.cancel();
+        for format in [ExportFormat::Json, ExportFormat::Xlsx] {
+            let path = dir.path().join(format!("cancelled.{}", format.extension()));
+            let result =
+                export_storage_to_file(&storage, options(format), &path, |_| {}, token.clone())
+                    .unwrap();
+            assert!(result.cancelled);
+            assert!(!path.exists());
+        }
+    }
+
+    #[test]
+    fn test_export_xlsx_from_storage() {
+        let storage = sample_storage(3);
+        storage.insert_task(&new_task("写周报")).unwrap();
+        insert_sample_finance(&storage);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.xlsx");
+        let mut options = options(ExportFormat::Xlsx);
+        options.date_range = Some((
+            Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            Local.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap(),
+        ));
+        export_storage_to_file(&storage, options, &path, |_| {}, CancelToken::new()).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        let archive = ZipArchive::new(&bytes).unwrap();
+        let read = |name: &str| String::from_utf8(archive.read(name).unwrap()).unwrap();
+        let workbook = read("xl/workbook.xml");
+        for name in ["时间记录", "任务", "交易", "预算", "统计"] {
+            assert!(workbook.contains(&format!("name=\"{}\"", name)), "{}", name);
+        }
+        let tasks = read("xl/worksheets/sheet2.xml");
+        assert!(tasks.contains("写周报"));
+        // 交易表只包含导出范围内的两笔支出
+        let transactions = read("xl/worksheets/sheet3.xml");
+        assert!(transactions.contains("午饭") && transactions.contains("晚饭"));
+        assert!(!transactions.contains("工资"));
+        // 预算按 3 月的支出计算已花费和剩余金额
+        let budgets = read("xl/worksheets/sheet4.xml");
+        assert!(budgets.contains("餐饮预算"));
+        assert!(budgets.contains("<v>80</v>") && budgets.contains("<v>20</v>"));
+    }
+
+    #[test]
+    fn test_report_formats_include_financial_summary() {
+        let storage = sample_storage(3);
+        insert_sample_finance(&storage);
+
+        for format in [ExportFormat::Pdf, ExportFormat::Html] {
+            let format_options = options(format);
+            let mut data = create_export_data(vec![], vec![], &format_options);
+            load_format_data(&storage, &format_options, &mut data).unwrap();
+            let financial = data.financial.unwrap();
+            assert_eq!(financial.stats.total_expense, 80.0);
+            assert_eq!(financial.category_breakdown[0].category_name, "餐饮");
+            assert_eq!(financial.monthly_trends.len(), 2);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.html");
+        let format_options = options(ExportFormat::Html);
+        export_storage_to_file(&storage, format_options, &path, |_| {}, CancelToken::new())
+            .unwrap();
+        let html = fs::read_to_string(&path).unwrap();
+        assert!(html.contains("财务汇总") && html.contains("餐饮"));
+        assert!(html.contains("2024-03"));
+
+        let path = dir.path().join("export.pdf");
+        let format_options = options(ExportFormat::Pdf);
+        export_storage_to_file(&storage, format_options, &path, |_| {}, CancelToken::new())
+            .unwrap();
+        assert!(fs::read(&path).unwrap().starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,1167 @@
+//! # iCalendar (ICS) 工具模块
+//!
+//! - 时间记录导出为 `VEVENT`，带截止日期的任务导出为 `VTODO`
+//! - UID 由记录 ID 生成，重新导出后日历客户端会更新已有事件而不是重复添加
+//! - 导入时把 `VEVENT` 解析为 [`CalendarEvent`]，分类映射由 `DataImporter` 按规则完成
+
+use crate::storage::task_models::TaskModel;
+use crate::utils::export::ExportData;
+use crate::utils::import::ImportError;
+use anyhow::Result;
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Write;
+use uuid::Uuid;
+
+/// UID 的域名部分，`<记录ID>@lifetracker`
+pub const UID_DOMAIN: &str = "lifetracker";
+
+/// 记录分类 ID 的扩展属性，重新导入时优先使用
+pub const CATEGORY_ID_PROPERTY: &str = "X-LIFETRACKER-CATEGORY-ID";
+
+/// 记录标签的扩展属性，用于区分 `CATEGORIES` 中的分类和标签
+pub const TAGS_PROPERTY: &str = "X-LIFETRACKER-TAGS";
+
+const DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// ==================== 写入 ====================
+
+/// 内容行写入器，负责转义后的折行（每行不超过 75 字节）和 CRLF 换行
+struct ContentWriter<'w, W: Write> {
+    writer: &'w mut W,
+}
+
+impl<W: Write> ContentWriter<'_, W> {
+    fn raw(&mut self, name: &str, value: &str) -> Result<()> {
+        let line = format!("{}:{}", name, value);
+        let mut width = 0;
+        let mut folded = String::with_capacity(line.len() + 8);
+        for ch in line.chars() {
+            if width + ch.len_utf8() > 75 {
+                folded.push_str("\r\n ");
+                width = 1;
+            }
+            folded.push(ch);
+            width += ch.len_utf8();
+        }
+        folded.push_str("\r\n");
+        self.writer.write_all(folded.as_bytes())?;
+        Ok(())
+    }
+
+    fn text(&mut self, name: &str, value: &str) -> Result<()> {
+        self.raw(name, &escape_text(value))
+    }
+
+    fn list(&mut self, name: &str, values: &[&str]) -> Result<()> {
+        let values: Vec<String> = values.iter().map(|value| escape_text(value)).collect();
+        self.raw(name, &values.join(","))
+    }
+
+    fn datetime(&mut self, name: &str, value: DateTime<Local>) -> Result<()> {
+        self.raw(
+            name,
+            &value
+                .with_timezone(&Utc)
+                .format(DATETIME_FORMAT)
+                .to_string(),
+        )
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn uid(id: Uuid) -> String {
+    format!("{}@{}", id, UID_DOMAIN)
+}
+
+/// 写出日历：时间记录为 `VEVENT`，有截止日期的任务为 `VTODO`
+///
+/// 未结束且没有时长的时间记录不导出
+pub fn write_calendar<W: Write>(data: &ExportData, writer: &mut W) -> Result<()> {
+    let category_names: HashMap<Uuid, &str> = data
+        .categories
+        .iter()
+        .map(|category| (category.id, category.name.as_str()))
+        .collect();
+    let stamp = data.metadata.export_time;
+
+    let mut out = ContentWriter { writer };
+    out.raw("BEGIN", "VCALENDAR")?;
+    out.raw("VERSION", "2.0")?;
+    out.raw(
+        "PRODID",
+        &format!("-//LifeTracker//LifeTracker {}//ZH", data.metadata.version),
+    )?;
+    out.raw("CALSCALE", "GREGORIAN")?;
+    out.raw("METHOD", "PUBLISH")?;
+    out.text("X-WR-CALNAME", "LifeTracker")?;
+
+    for entry in &data.time_entries {
+        let end = match entry.end_time {
+            Some(end) => end,
+            None if entry.duration_seconds > 0 => {
+                entry.start_time + Duration::seconds(entry.duration_seconds)
+            }
+            None => continue,
+        };
+        out.raw("BEGIN", "VEVENT")?;
+        out.raw("UID", &uid(entry.id))?;
+        out.datetime("DTSTAMP", stamp)?;
+        out.datetime("CREATED", entry.created_at)?;
+        out.datetime(
+            "LAST-MODIFIED",
+            entry.updated_at.unwrap_or(entry.created_at),
+        )?;
+        out.datetime("DTSTART", entry.start_time)?;
+        out.datetime("DTEND", end)?;
+        out.text("SUMMARY", &entry.task_name)?;
+        if let Some(description) = entry.description.as_deref().filter(|d| !d.is_empty()) {
+            out.text("DESCRIPTION", description)?;
+        }
+
+        let category = entry
+            .category_id
+            .and_then(|id| category_names.get(&id).copied());
+        let mut categories: Vec<&str> = category.into_iter().collect();
+        categories.extend(entry.tags.iter().map(String::as_str));
+        if !categories.is_empty() {
+            out.list("CATEGORIES", &categories)?;
+        }
+        if let Some(category_id) = entry.category_id {
+            out.raw(CATEGORY_ID_PROPERTY, &category_id.to_string())?;
+        }
+        if !entry.tags.is_empty() {
+            let tags: Vec<&str> = entry.tags.iter().map(String::as_str).collect();
+            out.list(TAGS_PROPERTY, &tags)?;
+        }
+        out.raw("TRANSP", "OPAQUE")?;
+        out.raw("END", "VEVENT")?;
+    }
+
+    for task in data.tasks.iter().filter(|task| task.due_date.is_some()) {
+        write_todo(&mut out, task, stamp, &category_names)?;
+    }
+
+    out.raw("END", "VCALENDAR")?;
+    Ok(())
+}
+
+fn write_todo<W: Write>(
+    out: &mut ContentWriter<'_, W>,
+    task: &TaskModel,
+    stamp: DateTime<Local>,
+    category_names: &HashMap<Uuid, &str>,
+) -> Result<()> {
+    out.raw("BEGIN", "VTODO")?;
+    out.raw("UID", &uid(task.id))?;
+    out.datetime("DTSTAMP", stamp)?;
+    out.datetime("CREATED", task.created_at)?;
+    out.datetime("LAST-MODIFIED", task.updated_at.unwrap_or(task.created_at))?;
+    if let Some(due) = task.due_date {
+        out.datetime("DUE", due)?;
+    }
+    out.text("SUMMARY", &task.name)?;
+    if let Some(description) = task.description.as_deref().filter(|d| !d.is_empty()) {
+        out.text("DESCRIPTION", description)?;
+    }
+
+    let status = match task.status.as_str() {
+        "completed" => "COMPLETED",
+        "cancelled" => "CANCELLED",
+        "in_progress" => "IN-PROCESS",
+        _ if task.is_completed => "COMPLETED",
+        _ => "NEEDS-ACTION",
+    };
+    out.raw("STATUS", status)?;
+    if let Some(completed_at) = task.completed_at {
+        out.datetime("COMPLETED", completed_at)?;
+    }
+    // RFC 5545：1 最高，9 最低
+    let priority = match task.priority.as_str() {
+        "urgent" => "1",
+        "high" => "3",
+        "low" => "9",
+        _ => "5",
+    };
+    out.raw("PRIORITY", priority)?;
+
+    let tags: Vec<String> = serde_json::from_str(&task.tags).unwrap_or_default();
+    let mut categories: Vec<&str> = task
+        .category_id
+        .and_then(|id| category_names.get(&id).copied())
+        .into_iter()
+        .collect();
+    categories.extend(tags.iter().map(String::as_str));
+    if !categories.is_empty() {
+        out.list("CATEGORIES", &categories)?;
+    }
+    if let Some(category_id) = task.category_id {
+        out.raw(CATEGORY_ID_PROPERTY, &category_id.to_string())?;
+    }
+    out.raw("END", "VTODO")
+}
+
+// ==================== 分类映射规则 ====================
+
+/// 映射规则匹配的事件字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSource {
+    /// `CATEGORIES` 中的任一值与模式相同
+    Categories,
+    /// 标题包含模式
+    Summary,
+    /// 日历名称（`X-WR-CALNAME`）与模式相同
+    Calendar,
+}
+
+/// ICS 导入的分类映射规则，按顺序匹配，第一条匹配的规则生效
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryRule {
+    pub source: RuleSource,
+    /// 匹配模式（忽略大小写）
+    pub pattern: String,
+    /// 目标分类名称，不存在时按 `create_missing_categories` 创建
+    pub category: String,
+}
+
+impl CategoryRule {
+    pub fn new(source: RuleSource, pattern: &str, category: &str) -> Self {
+        Self {
+            source,
+            pattern: pattern.to_string(),
+            category: category.to_string(),
+        }
+    }
+
+    /// 检查事件是否匹配
+    pub fn matches(&self, event: &CalendarEvent) -> bool {
+        let pattern = self.pattern.trim().to_lowercase();
+        match self.source {
+            RuleSource::Categories => event
+                .categories
+                .iter()
+                .any(|category| category.trim().to_lowercase() == pattern),
+            RuleSource::Summary => event.summary.to_lowercase().contains(&pattern),
+            RuleSource::Calendar => event
+                .calendar_name
+                .as_ref()
+                .is_some_and(|name| name.trim().to_lowercase() == pattern),
+        }
+    }
+}
+
+// ==================== 解析 ====================
+
+/// 解析出的日历事件
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    /// 由 UID 得到的记录 ID：本应用导出的 UID 还原为原 ID，其他 UID 生成稳定的 ID
+    pub id: Uuid,
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// `CATEGORIES` 中的所有值
+    pub categories: Vec<String>,
+    /// `X-LIFETRACKER-CATEGORY-ID`
+    pub category_id: Option<Uuid>,
+    /// `X-LIFETRACKER-TAGS`，为空时由 `CATEGORIES` 推断
+    pub tags: Option<Vec<String>>,
+    /// 所在日历的名称
+    pub calendar_name: Option<String>,
+    /// `BEGIN:VEVENT` 所在行
+    pub line: usize,
+}
+
+impl CalendarEvent {
+    /// 可以作为分类的 `CATEGORIES` 值（排除已知的标签）
+    pub fn category_candidates(&self) -> Vec<&str> {
+        self.categories
+            .iter()
+            .map(String::as_str)
+            .filter(|value| {
+                self.tags
+                    .as_ref()
+                    .is_none_or(|tags| !tags.iter().any(|tag| tag == value))
+            })
+            .collect()
+    }
+}
+
+/// 日历解析结果，单个事件的错误不会中断解析
+#[derive(Debug, Clone, Default)]
+pub struct ParsedCalendar {
+    pub events: Vec<CalendarEvent>,
+    pub errors: Vec<ImportError>,
+    pub warnings: Vec<String>,
+}
+
+/// 内容行
+#[derive(Debug, Clone)]
+struct ContentLine {
+    line: usize,
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 日历组件（VCALENDAR、VEVENT、VTIMEZONE 等）
+#[derive(Debug, Clone, Default)]
+struct Component {
+    name: String,
+    line: usize,
+    properties: Vec<ContentLine>,
+    children: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|p| unescape_text(&p.value))
+            .filter(|text| !text.trim().is_empty())
+    }
+}
+
+/// 展开折行，返回（起始行号，逻辑行）
+fn unfold(content: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ if line.is_empty() => {}
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+/// 解析内容行 `NAME;PARAM=value:VALUE`（引号中的 `:` 和 `;` 不作分隔）
+fn parse_content_line(line: usize, text: &str) -> Option<ContentLine> {
+    let mut in_quotes = false;
+    let mut parts: Vec<&str> = Vec::new();
+    let mut start = 0;
+    let mut value_start = None;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                parts.push(&text[start..index]);
+                start = index + 1;
+            }
+            ':' if !in_quotes => {
+                parts.push(&text[start..index]);
+                value_start = Some(index + 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value = &text[value_start?..];
+    let mut parts = parts.into_iter();
+    let name = parts.next()?.trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some(ContentLine {
+        line,
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// 按未转义的逗号拆分列表值
+fn split_list(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for ch in text.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(ch);
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == ',' {
+            values.push(unescape_text(&current));
+            current.clear();
+        } else {
+            current.push(ch);
+        }
+    }
+    values.push(unescape_text(&current));
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn build_tree(content: &str) -> std::result::Result<Component, ImportError> {
+    let mut stack: Vec<Component> = vec![Component::default()];
+    for (line, text) in unfold(content) {
+        let property = parse_content_line(line, &text).ok_or_else(|| {
+            ImportError::new("无法解析内容行".to_string())
+                .with_line(line)
+                .with_data(text.clone())
+        })?;
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: property.value.trim().to_uppercase(),
+                line,
+                ..Default::default()
+            }),
+            "END" => {
+                let name = property.value.trim().to_uppercase();
+                if stack.len() == 1 {
+                    return Err(ImportError::new(format!("多余的 END:{}", name)).with_line(line));
+                }
+                let component = stack.pop().expect("已检查栈深度");
+                if component.name != name {
+                    return Err(ImportError::new(format!(
+                        "END:{} 与第 {} 行的 BEGIN:{} 不匹配",
+                        name, component.line, component.name
+                    ))
+                    .with_line(line));
+                }
+                stack.last_mut().expect("根组件").children.push(component);
+            }
+            _ => stack.last_mut().expect("根组件").properties.push(property),
+        }
+    }
+
+    if stack.len() > 1 {
+        let component = stack.pop().expect("未闭合的组件");
+        return Err(
+            ImportError::new(format!("BEGIN:{} 未闭合", component.name)).with_line(component.line)
+        );
+    }
+    Ok(stack.pop().expect("根组件"))
+}
+
+/// 解析日历内容中的事件
+pub fn parse_calendar(content: &str) -> std::result::Result<ParsedCalendar, ImportError> {
+    let root = build_tree(content.trim_start_matches('\u{feff}'))?;
+    let calendars: Vec<&Component> = root
+        .children
+        .iter()
+        .filter(|component| component.name == "VCALENDAR")
+        .collect();
+    if calendars.is_empty() {
+        return Err(ImportError::new(
+            "不是有效的 iCalendar 文件：缺少 VCALENDAR".to_string(),
+        ));
+    }
+
+    let mut parsed = ParsedCalendar::default();
+    for calendar in calendars {
+        let calendar_name = calendar.text("X-WR-CALNAME");
+        let timezones: HashMap<String, TimeZoneRules> = calendar
+            .children
+            .iter()
+            .filter(|component| component.name == "VTIMEZONE")
+            .filter_map(|component| {
+                let id = component.property("TZID")?.value.clone();
+                Some((id, TimeZoneRules::from_component(component)))
+            })
+            .collect();
+        let resolver = TimeResolver {
+            timezones: &timezones,
+        };
+
+        for event in calendar.children.iter().filter(|c| c.name == "VEVENT") {
+            match parse_event(
+                event,
+                &resolver,
+                calendar_name.clone(),
+                &mut parsed.warnings,
+            ) {
+                Ok(Some(event)) => parsed.events.push(event),
+                Ok(None) => {}
+                Err(error) => parsed.errors.push(error),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_event(
+    component: &Component,
+    resolver: &TimeResolver<'_>,
+    calendar_name: Option<String>,
+    warnings: &mut Vec<String>,
+) -> std::result::Result<Option<CalendarEvent>, ImportError> {
+    let line = component.line;
+    let summary = component.text("SUMMARY").unwrap_or_default();
+    let label = if summary.is_empty() {
+        format!("第 {} 行的事件", line)
+    } else {
+        format!("事件 \"{}\"", summary)
+    };
+
+    if component
+        .property("STATUS")
+        .is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED"))
+    {
+        warnings.push(format!("跳过已取消的{}", label));
+        return Ok(None);
+    }
+
+    let uid = component
+        .property("UID")
+        .map(|p| p.value.trim().to_string())
+        .ok_or_else(|| ImportError::new("事件缺少 UID".to_string()).with_line(line))?;
+
+    let dtstart = component.property("DTSTART").ok_or_else(|| {
+        ImportError::new("事件缺少 DTSTART".to_string())
+            .with_line(line)
+            .with_field("DTSTART".to_string())
+    })?;
+    if is_date_value(dtstart) {
+        warnings.push(format!("跳过全天{}", label));
+        return Ok(None);
+    }
+    let start = resolver.resolve(dtstart, warnings)?;
+
+    let end = match (component.property("DTEND"), component.property("DURATION")) {
+        (Some(dtend), _) => resolver.resolve(dtend, warnings)?,
+        (None, Some(duration)) => {
+            start
+                + parse_duration(&duration.value).ok_or_else(|| {
+                    ImportError::new(format!("无法解析时长: {}", duration.value))
+                        .with_line(duration.line)
+                        .with_field("DURATION".to_string())
+                })?
+        }
+        (None, None) => {
+            warnings.push(format!("跳过没有结束时间的{}", label));
+            return Ok(None);
+        }
+    };
+
+    if component.property("RRULE").is_some() {
+        warnings.push(format!("{}是重复事件，只导入第一次", label));
+    }
+
+    let recurrence_id = component
+        .property("RECURRENCE-ID")
+        .map(|p| p.value.as_str());
+    let categories = component
+        .properties
+        .iter()
+        .filter(|p| p.name == "CATEGORIES")
+        .flat_map(|p| split_list(&p.value))
+        .collect();
+
+    Ok(Some(CalendarEvent {
+        id: uid_to_id(&uid, recurrence_id),
+        uid,
+        summary,
+        description: component.text("DESCRIPTION"),
+        start,
+        end,
+        categories,
+        category_id: component
+            .property(CATEGORY_ID_PROPERTY)
+            .and_then(|p| Uuid::parse_str(p.value.trim()).ok()),
+        tags: component
+            .property(TAGS_PROPERTY)
+            .map(|p| split_list(&p.value)),
+        calendar_name,
+        line,
+    }))
+}
+
+/// 本应用导出的 UID 还原为原 ID，其他 UID（及重复事件的实例）生成稳定的 ID
+fn uid_to_id(uid: &str, recurrence_id: Option<&str>) -> Uuid {
+    if recurrence_id.is_none() {
+        if let Some(id) = uid
+            .strip_suffix(&format!("@{}", UID_DOMAIN))
+            .and_then(|id| Uuid::parse_str(id).ok())
+        {
+            return id;
+        }
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(uid.as_bytes());
+    if let Some(recurrence_id) = recurrence_id {
+        hasher.update(b"\0");
+        hasher.update(recurrence_id.as_bytes());
+    }
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_sha1_bytes(bytes).into_uuid()
+}
+
+fn is_date_value(property: &ContentLine) -> bool {
+    property
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || !property.value.contains('T')
+}
+
+/// 解析 RFC 5545 时长，如 `PT1H30M`、`P1DT2H`、`P2W`
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let rest = text.strip_prefix('P')?;
+    let mut seconds = 0i64;
+    let mut number = String::new();
+    let mut in_time = false;
+    for ch in rest.chars() {
+        match ch {
+            '0'..='9' => number.push(ch),
+            'T' if number.is_empty() => in_time = true,
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let value: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += value
+                    * match (ch, in_time) {
+                        ('W', false) => 7 * 86400,
+                        ('D', false) => 86400,
+                        ('H', true) => 3600,
+                        ('M', true) => 60,
+                        ('S', true) => 1,
+                        _ => return None,
+                    };
+            }
+            _ => return None,
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(Duration::seconds(if negative { -seconds } else { seconds }))
+}
+
+fn parse_naive(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text.trim().trim_end_matches('Z'), "%Y%m%dT%H%M%S").ok()
+}
+
+fn parse_offset(text: &str) -> Option<FixedOffset> {
+    let text = text.trim();
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits = &text[1..];
+    if digits.len() < 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[0..2].parse().ok()?;
+    let minutes: i32 = digits[2..4].parse().ok()?;
+    let seconds: i32 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+// ==================== 时区 ====================
+
+/// VTIMEZONE 中的一条标准时间/夏令时规则
+#[derive(Debug, Clone)]
+struct TimeZoneRule {
+    /// 首次生效的本地时间（按 TZOFFSETFROM）
+    start: NaiveDateTime,
+    offset_from: FixedOffset,
+    offset_to: FixedOffset,
+    /// 每年重复的规则（月份，第几个星期几）
+    yearly: Option<(u32, i32, Weekday)>,
+}
+
+impl TimeZoneRule {
+    /// 某年内的切换时间（本地时间）
+    fn transition_in(&self, year: i32) -> Option<NaiveDateTime> {
+        if year < self.start.year() {
+            return None;
+        }
+        match self.yearly {
+            Some((month, n, weekday)) => {
+                nth_weekday(year, month, n, weekday).map(|date| date.and_time(self.start.time()))
+            }
+            None if year == self.start.year() => Some(self.start),
+            None => None,
+        }
+    }
+}
+
+/// 第 n 个星期几，n 为负数时从月末倒数
+fn nth_weekday(year: i32, month: u32, n: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if n > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, n as u8)
+    } else {
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let last = next_month.pred_opt()?;
+        let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        Some(last - Duration::days(back as i64 + 7 * (-n - 1) as i64))
+    }
+}
+
+/// 解析 `FREQ=YEARLY;BYMONTH=3;BYDAY=2SU`
+fn parse_yearly_rule(rule: &str) -> Option<(u32, i32, Weekday)> {
+    let parts: HashMap<String, &str> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value))
+        .collect();
+    if !parts.get("FREQ")?.eq_ignore_ascii_case("YEARLY") {
+        return None;
+    }
+    let month: u32 = parts.get("BYMONTH")?.parse().ok()?;
+    let by_day = parts.get("BYDAY")?.split(',').next()?.to_uppercase();
+    let (n, day) = by_day.split_at(by_day.len().checked_sub(2)?);
+    let weekday = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let n: i32 = if n.is_empty() { 1 } else { n.parse().ok()? };
+    Some((month, n, weekday))
+}
+
+/// 一个 VTIMEZONE 的所有规则
+#[derive(Debug, Clone, Default)]
+struct TimeZoneRules {
+    rules: Vec<TimeZoneRule>,
+}
+
+impl TimeZoneRules {
+    fn from_component(component: &Component) -> Self {
+        let rules = component
+            .children
+            .iter()
+            .filter(|c| c.name == "STANDARD" || c.name == "DAYLIGHT")
+            .filter_map(|c| {
+                Some(TimeZoneRule {
+                    start: parse_naive(&c.property("DTSTART")?.value)?,
+                    offset_from: parse_offset(&c.property("TZOFFSETFROM")?.value)?,
+                    offset_to: parse_offset(&c.property("TZOFFSETTO")?.value)?,
+                    yearly: c
+                        .property("RRULE")
+                        .and_then(|p| parse_yearly_rule(&p.value)),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// 本地时间对应的 UTC 偏移：取最近一次已发生的切换
+    fn offset_at(&self, local: NaiveDateTime) -> Option<FixedOffset> {
+        let latest = self
+            .rules
+            .iter()
+            .flat_map(|rule| {
+                [local.year(), local.year() - 1]
+                    .into_iter()
+                    .filter_map(move |year| rule.transition_in(year).map(|time| (time, rule)))
+            })
+            .filter(|(time, _)| *time <= local)
+            .max_by_key(|(time, _)| *time);
+        match latest {
+            Some((_, rule)) => Some(rule.offset_to),
+            // 早于所有规则时使用最早规则切换前的偏移
+            None => self
+                .rules
+                .iter()
+                .min_by_key(|rule| rule.start)
+                .map(|rule| rule.offset_from),
+        }
+    }
+}
+
+/// 根据 `Z` 后缀、`TZID` 参数和 VTIMEZONE 解析时间
+struct TimeResolver<'a> {
+    timezones: &'a HashMap<String, TimeZoneRules>,
+}
+
+impl TimeResolver<'_> {
+    fn resolve(
+        &self,
+        property: &ContentLine,
+        warnings: &mut Vec<String>,
+    ) -> std::result::Result<DateTime<Local>, ImportError> {
+        let error = || {
+            ImportError::new(format!("无法解析时间: {}", property.value))
+                .with_line(property.line)
+                .with_field(property.name.clone())
+                .with_data(property.value.clone())
+        };
+        let naive = parse_naive(&property.value).ok_or_else(error)?;
+
+        if property.value.trim().ends_with('Z') {
+            return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+        }
+        let offset = match property.param("TZID") {
+            Some(tzid) => match self.timezones.get(tzid).and_then(|tz| tz.offset_at(naive)) {
+                Some(offset) => Some(offset),
+                None if matches!(tzid, "UTC" | "Etc/UTC" | "GMT" | "Etc/GMT" | "Z") => {
+                    FixedOffset::east_opt(0)
+                }
+                None => {
+                    let warning = format!("未知时区 {}，按本地时间处理", tzid);
+                    if !warnings.contains(&warning) {
+                        warnings.push(warning);
+                    }
+                    None
+                }
+            },
+            // 浮动时间按本地时间处理
+            None => None,
+        };
+
+        match offset {
+            Some(offset) => offset
+                .from_local_datetime(&naive)
+                .single()
+                .map(|time| time.with_timezone(&Local))
+                .ok_or_else(error),
+            None => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .ok_or_else(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+X-WR-CALNAME:工作日历\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:America/New_York\r\n\
+BEGIN:DAYLIGHT\r\n\
+DTSTART:20070311T020000\r\n\
+TZOFFSETFROM:-0500\r\n\
+TZOFFSETTO:-0400\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\n\
+END:DAYLIGHT\r\n\
+BEGIN:STANDARD\r\n\
+DTSTART:20071104T020000\r\n\
+TZOFFSETFROM:-0400\r\n\
+TZOFFSETTO:-0500\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\n\
+END:STANDARD\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+UID:abc-1@example.com\r\n\
+DTSTART;TZID=America/New_York:20240709T090000\r\n\
+DURATION:PT1H30M\r\n\
+SUMMARY:周会\\, 计划\r\n\
+DESCRIPTION:第一行\\n第二行很长很长很长很长很长很长很长很长很长很长很长很长很长很\r\n\
+\x20长很长\r\n\
+CATEGORIES:会议,团队\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:abc-2@example.com\r\n\
+DTSTART;TZID=America/New_York:20240115T090000\r\n\
+DTEND;TZID=America/New_York:20240115T100000\r\n\
+SUMMARY:冬季\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:abc-3@example.com\r\n\
+DTSTART;VALUE=DATE:20240710\r\n\
+SUMMARY:假期\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:abc-4@example.com\r\n\
+DTSTART:20240711T100000Z\r\n\
+DTEND:bad\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_calendar() {
+        let parsed = parse_calendar(CALENDAR).unwrap();
+
+        assert_eq!(parsed.events.len(), 2);
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line_number, Some(42));
+        assert!(parsed.warnings.iter().any(|w| w.contains("全天")));
+
+        let summer = &parsed.events[0];
+        assert_eq!(summer.summary, "周会, 计划");
+        assert_eq!(summer.categories, vec!["会议", "团队"]);
+        assert_eq!(summer.calendar_name.as_deref(), Some("工作日历"));
+        assert!(summer.description.as_ref().unwrap().ends_with("很长很长"));
+        assert_eq!(summer.start.with_timezone(&Utc).hour(), 13, "夏令时 UTC-4");
+        assert_eq!((summer.end - summer.start).num_minutes(), 90);
+        assert_eq!(summer.id, uid_to_id("abc-1@example.com", None));
+
+        let winter = &parsed.events[1];
+        assert_eq!(
+            winter.start.with_timezone(&Utc).hour(),
+            14,
+            "标准时间 UTC-5"
+        );
+
+        let unclosed = "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:x\nEND:VCALENDAR\n";
+        assert_eq!(parse_calendar(unclosed).unwrap_err().line_number, Some(4));
+    }
+
+    #[test]
+    fn test_content_line_folding_and_escaping() {
+        let mut output = Vec::new();
+        let mut writer = ContentWriter {
+            writer: &mut output,
+        };
+        let long = "很长的描述，包含逗号; 分号\n和换行".repeat(5);
+        writer.text("DESCRIPTION", &long).unwrap();
+        let text = String::from_utf8(output).unwrap();
+
+        assert!(text.split("\r\n").all(|line| line.len() <= 75));
+        let lines = unfold(&text);
+        assert_eq!(lines.len(), 1);
+        let property = parse_content_line(1, &lines[0].1).unwrap();
+        assert_eq!(unescape_text(&property.value), long);
+
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+        assert_eq!(parse_duration("P1H"), None);
+    }
+
+    #[test]
+    fn test_folding_boundaries() {
+        // 恰好 75 字节不折行，多 1 字节才折行
+        let mut output = Vec::new();
+        let mut writer = ContentWriter {
+            writer: &mut output,
+        };
+        writer.raw("SUMMARY", &"a".repeat(75 - 8)).unwrap();
+        writer.raw("SUMMARY", &"a".repeat(76 - 8)).unwrap();
+        // 多字节字符不会被拆到两行
+        writer.raw("SUMMARY", &"汉".repeat(40)).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let physical: Vec<&str> = text.split_terminator("\r\n").collect();
+        assert_eq!(physical[0].len(), 75);
+        assert_eq!(physical[1].len(), 75);
+        assert_eq!(physical[2], " a");
+        assert!(physical[3..].iter().all(|line| line.len() <= 75));
+        assert!(physical[4].starts_with(' '));
+
+        let lines = unfold(&text);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].1, format!("SUMMARY:{}", "a".repeat(68)));
+        assert_eq!(lines[2].1, format!("SUMMARY:{}", "汉".repeat(40)));
+        assert_eq!(lines[2].0, 4, "逻辑行的行号取首行");
+
+        // 仅 LF 换行、制表符续行和空行
+        let lines = unfold("A:1\n\nB:first\n\tsecond\n third\nC:3");
+        assert_eq!(
+            lines,
+            vec![
+                (1, "A:1".to_string()),
+                (3, "B:firstsecondthird".to_string()),
+                (6, "C:3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_content_line_params_and_lists() {
+        let property = parse_content_line(
+            7,
+            "dtstart;tzid=\"Custom:Zone;1\";value=DATE-TIME:20240101T090000",
+        )
+        .unwrap();
+        assert_eq!(property.name, "DTSTART");
+        assert_eq!(property.param("TZID"), Some("Custom:Zone;1"));
+        assert_eq!(property.param("VALUE"), Some("DATE-TIME"));
+        assert_eq!(property.value, "20240101T090000");
+        assert_eq!(property.line, 7);
+
+        // 值中的冒号属于值
+        let property = parse_content_line(1, "URL:https://example.com:8080/a").unwrap();
+        assert_eq!(property.value, "https://example.com:8080/a");
+        assert!(parse_content_line(1, "NO VALUE").is_none());
+        assert!(parse_content_line(1, ":value").is_none());
+
+        assert_eq!(
+            split_list("会议\\, 周会, 团队 ,,\\\\备注"),
+            vec!["会议, 周会", "团队", "\\备注"]
+        );
+        assert_eq!(unescape_text(r"a\Nb\;c\"), "a\nb;c");
+        assert_eq!(escape_text("a;b,c\\d\r\ne"), r"a\;b\,c\\d\ne");
+    }
+
+    #[test]
+    fn test_yearly_rules() {
+        assert_eq!(
+            parse_yearly_rule("FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU"),
+            Some((10, -1, Weekday::Sun))
+        );
+        assert_eq!(
+            parse_yearly_rule("freq=yearly;bymonth=4;byday=su"),
+            Some((4, 1, Weekday::Sun))
+        );
+        assert_eq!(
+            parse_yearly_rule("FREQ=YEARLY;BYMONTH=3;BYDAY=2SU,3SU;UNTIL=20061029T060000Z"),
+            Some((3, 2, Weekday::Sun))
+        );
+        assert_eq!(parse_yearly_rule("FREQ=MONTHLY;BYMONTH=3;BYDAY=2SU"), None);
+        assert_eq!(parse_yearly_rule("FREQ=YEARLY;BYDAY=2SU"), None);
+        assert_eq!(parse_yearly_rule("FREQ=YEARLY;BYMONTH=3;BYDAY=2XX"), None);
+        assert_eq!(parse_yearly_rule("FREQ=YEARLY;BYMONTH=3;BYDAY=S"), None);
+
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        assert_eq!(nth_weekday(2024, 3, 2, Weekday::Sun), date(2024, 3, 10));
+        assert_eq!(nth_weekday(2024, 10, -1, Weekday::Sun), date(2024, 10, 27));
+        assert_eq!(nth_weekday(2024, 2, -1, Weekday::Thu), date(2024, 2, 29));
+        assert_eq!(nth_weekday(2024, 12, -2, Weekday::Tue), date(2024, 12, 24));
+        assert_eq!(nth_weekday(2024, 2, 5, Weekday::Mon), None);
+    }
+
+    #[test]
+    fn test_timezone_rrule_and_recurring_events() {
+        let calendar = "BEGIN:VCALENDAR\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:Europe/Berlin\r\n\
+BEGIN:DAYLIGHT\r\n\
+DTSTART:19810329T020000\r\n\
+TZOFFSETFROM:+0100\r\n\
+TZOFFSETTO:+0200\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n\
+END:DAYLIGHT\r\n\
+BEGIN:STANDARD\r\n\
+DTSTART:19961027T030000\r\n\
+TZOFFSETFROM:+0200\r\n\
+TZOFFSETTO:+0100\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+END:STANDARD\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+UID:weekly@example.com\r\n\
+DTSTART;TZID=Europe/Berlin:20241026T120000\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=WEEKLY;COUNT=4\r\n\
+SUMMARY:例会\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:weekly@example.com\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20241102T120000\r\n\
+DTSTART;TZID=Europe/Berlin:20241102T150000\r\n\
+DURATION:PT1H\r\n\
+SUMMARY:例会（改期）\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:old@example.com\r\n\
+DTSTART;TZID=Europe/Berlin:19800601T120000\r\n\
+DURATION:PT1H\r\n\
+SUMMARY:早于时区规则\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:unknown-tz@example.com\r\n\
+DTSTART;TZID=Mars/Olympus:20240601T120000\r\n\
+DURATION:PT1H\r\n\
+SUMMARY:未知时区\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let parsed = parse_calendar(calendar).unwrap();
+        assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+        assert_eq!(parsed.events.len(), 4);
+
+        // 重复事件只导入第一次，并给出警告
+        let master = &parsed.events[0];
+        assert_eq!(master.start.with_timezone(&Utc).hour(), 10, "夏令时 UTC+2");
+        assert!(parsed.warnings.iter().any(|w| w.contains("重复事件")));
+
+        // 改期的实例与主事件 ID 不同，且重复导入时保持稳定
+        let instance = &parsed.events[1];
+        assert_eq!(
+            instance.start.with_timezone(&Utc).hour(),
+            14,
+            "标准时间 UTC+1"
+        );
+        assert_ne!(instance.id, master.id);
+        assert_eq!(
+            instance.id,
+            uid_to_id("weekly@example.com", Some("20241102T120000"))
+        );
+
+        // 早于所有规则时使用最早规则切换前的偏移
+        assert_eq!(parsed.events[2].start.with_timezone(&Utc).hour(), 11);
+
+        assert_eq!(
+            parsed
+                .warnings
+                .iter()
+                .filter(|w| w.contains("未知时区 Mars/Olympus"))
+                .count(),
+            1
+        );
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! 提供各种格式的数据导入功能
 
-use chrono::{DateTime, Local, NaiveDateTime};
+pub mod tracker;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs::File;
@@ -14,8 +16,11 @@ use crate::core::{
     Category,
 };
 use crate::storage::models::TimeEntry;
+use crate::utils::export::{ExportData, ExportMetadata};
 use crate::utils::generate_id;
+use crate::utils::ical::{self, CalendarEvent, CategoryRule};
 use anyhow::Result;
+use tracker::{MappingTarget, ProjectKey, TrackerImportPreview};
 use uuid::Uuid;
 
 /// 导入格式
@@ -24,6 +29,7 @@ pub enum ImportFormat {
     Json,
     Csv,
     Xml,
+    Ics,
 }
 
 impl ImportFormat {
@@ -32,6 +38,7 @@ impl ImportFormat {
             "json" => Some(Self::Json),
             "csv" => Some(Self::Csv),
             "xml" => Some(Self::Xml),
+            "ics" | "ical" => Some(Self::Ics),
             _ => None,
         }
     }
@@ -41,6 +48,7 @@ impl ImportFormat {
             Self::Json => "json",
             Self::Csv => "csv",
             Self::Xml => "xml",
+            Self::Ics => "ics",
         }
     }
 }
@@ -55,6 +63,10 @@ pub struct ImportOptions {
     pub default_category_id: Option<String>,
     pub validate_data: bool,
     pub dry_run: bool,
+    /// Toggl/Clockify CSV 中不带时区的时间所在的时区，为空时使用本地时区
+    pub source_utc_offset: Option<FixedOffset>,
+    /// ICS 事件的分类映射规则
+    pub category_rules: Vec<CategoryRule>,
 }
 
 impl Default for ImportOptions {
@@ -67,6 +79,8 @@ impl Default for ImportOptions {
             default_category_id: None,
             validate_data: true,
             dry_run: false,
+            source_utc_offset: None,
+            category_rules: Vec::new(),
         }
     }
 }
@@ -169,6 +183,13 @@ struct CsvTimeEntry {
     description: Option<String>,
 }
 
+/// 解析后的XML导出文档
+struct XmlExport {
+    data: ExportData,
+    errors: Vec<ImportError>,
+    warnings: Vec<String>,
+}
+
 /// 数据导入器
 pub struct DataImporter {
     options: ImportOptions,
@@ -189,7 +210,7 @@ impl DataImporter {
         let file = File::open(file_path)?;
         let mut reader = BufReader::new(file);
 
-        match self.options.format {
+        let (categories, entries, mut result) = match self.options.format {
             ImportFormat::Json => {
                 self.import_json(&mut reader, existing_categories, existing_entries)
             }
@@ -199,7 +220,22 @@ impl DataImporter {
             ImportFormat::Xml => {
                 self.import_xml(&mut reader, existing_categories, existing_entries)
             }
+            ImportFormat::Ics => {
+                self.import_ics(&mut reader, existing_categories, existing_entries)
+            }
+        }?;
+
+        // 试运行只返回统计结果，不返回需要写入的数据
+        if self.options.dry_run {
+            result.add_warning(format!(
+                "试运行：将导入 {} 个分类和 {} 条记录，未写入任何数据",
+                categories.len(),
+                entries.len()
+            ));
+            return Ok((Vec::new(), Vec::new(), result));
         }
+
+        Ok((categories, entries, result))
     }
 
     /// 导入JSON格式数据
@@ -214,9 +250,13 @@ impl DataImporter {
 
         let mut result = ImportResult::new();
 
+        // Toggl/Clockify 导出按默认映射导入
+        if let Some(preview) = self.preview_tracker_content(&content, existing_categories) {
+            return Ok(self.import_tracker(&preview, existing_categories, existing_entries));
+        }
+
         // 尝试解析为完整的导出数据
-        if let Ok(export_data) = serde_json::from_str::<crate::utils::export::ExportData>(&content)
-        {
+        if let Ok(export_data) = serde_json::from_str::<ExportData>(&content) {
             return self.process_export_data(export_data, existing_categories, existing_entries);
         }
 
@@ -258,6 +298,10 @@ impl DataImporter {
         let mut content = String::new();
         reader.read_to_string(&mut content)?;
 
+        if let Some(preview) = self.preview_tracker_content(&content, existing_categories) {
+            return Ok(self.import_tracker(&preview, existing_categories, existing_entries));
+        }
+
         let mut result = ImportResult::new();
         let mut new_categories = Vec::new();
         let mut new_entries = Vec::new();
@@ -393,66 +437,631 @@ impl DataImporter {
         Ok((new_categories, new_entries, result))
     }
 
-    /// 导入XML格式数据（简化实现）
-    fn import_xml<R: Read>(
+    /// 生成 Toggl/Clockify 导出的映射预览，不是这两种格式时返回 None
+    pub fn preview_tracker_import<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        existing_categories: &[Category],
+    ) -> Result<Option<TrackerImportPreview>> {
+        let content = std::fs::read_to_string(file_path)?;
+        Ok(self.preview_tracker_content(&content, existing_categories))
+    }
+
+    /// 从文本内容生成 Toggl/Clockify 映射预览
+    pub fn preview_tracker_content(
+        &self,
+        content: &str,
+        existing_categories: &[Category],
+    ) -> Option<TrackerImportPreview> {
+        let parsed = tracker::parse(content, self.options.source_utc_offset)?;
+        Some(TrackerImportPreview::new(
+            parsed,
+            existing_categories,
+            self.options.create_missing_categories,
+        ))
+    }
+
+    /// 按预览中的项目映射导入 Toggl/Clockify 记录
+    pub fn import_tracker(
+        &self,
+        preview: &TrackerImportPreview,
+        existing_categories: &[Category],
+        existing_entries: &[TimeEntry],
+    ) -> (Vec<Category>, Vec<TimeEntry>, ImportResult) {
+        let mut result = ImportResult::new();
+        for error in &preview.errors {
+            result.add_error(error.clone());
+        }
+        result.warnings.extend(preview.warnings.iter().cloned());
+
+        let default_category = self
+            .options
+            .default_category_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok());
+
+        let mut new_categories: Vec<Category> = Vec::new();
+        let mut category_ids: HashMap<&ProjectKey, Option<Uuid>> = HashMap::new();
+        for mapping in &preview.mappings {
+            let category_id = match &mapping.target {
+                MappingTarget::Existing(id) => Some(*id),
+                MappingTarget::Uncategorized => default_category,
+                MappingTarget::Create => self
+                    .create_tracker_category(mapping, existing_categories, &mut new_categories)
+                    .or(default_category),
+            };
+            category_ids.insert(&mapping.key, category_id);
+        }
+        result.imported_categories = new_categories.len();
+
+        let mut new_entries = Vec::new();
+        for entry in &preview.entries {
+            let location = entry
+                .line
+                .map(|line| format!(" (行 {})", line))
+                .unwrap_or_default();
+            let task_name = [
+                Some(&entry.description),
+                entry.task.as_ref(),
+                entry.project.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .find(|name| !name.trim().is_empty())
+            .cloned()
+            .unwrap_or_else(|| "未命名".to_string());
+            let description = entry.task.clone().filter(|task| *task != task_name);
+            let now = Local::now();
+            let time_entry = TimeEntry {
+                id: generate_id(),
+                task_name,
+                category_id: category_ids.get(&entry.key()).copied().flatten(),
+                start_time: entry.start,
+                end_time: Some(entry.end),
+                duration_seconds: entry.end.signed_duration_since(entry.start).num_seconds(),
+                description,
+                tags: entry.tags.clone(),
+                created_at: now,
+                updated_at: Some(now),
+            };
+
+            if self.options.validate_data {
+                if let Err(validation_error) = self.validate_time_entry(&time_entry) {
+                    let mut error = ImportError::new(validation_error.to_string())
+                        .with_data(time_entry.task_name.clone());
+                    if let Some(line) = entry.line {
+                        error = error.with_line(line);
+                    }
+                    result.add_error(error);
+                    continue;
+                }
+            }
+
+            if self.options.skip_duplicates
+                && self.is_duplicate_entry(&time_entry, existing_entries, &new_entries)
+            {
+                result.skipped_entries += 1;
+                result.add_warning(format!(
+                    "跳过重复记录: {}{}",
+                    time_entry.task_name, location
+                ));
+                continue;
+            }
+
+            new_entries.push(time_entry);
+        }
+        result.imported_entries = new_entries.len();
+
+        (new_categories, new_entries, result)
+    }
+
+    /// 为映射创建项目分类，有客户时挂在客户分类下（客户分类优先复用同名分类）
+    fn create_tracker_category(
+        &self,
+        mapping: &tracker::ProjectMapping,
+        existing_categories: &[Category],
+        new_categories: &mut Vec<Category>,
+    ) -> Option<Uuid> {
+        let parent_id = mapping.key.client.as_ref().map(|client| {
+            let found = tracker::find_category(existing_categories, client)
+                .or_else(|| tracker::find_category(new_categories, client))
+                .map(|category| category.id);
+            found.unwrap_or_else(|| {
+                let category = new_import_category(client, CategoryColor::Gray, None);
+                let id = category.id;
+                new_categories.push(category);
+                id
+            })
+        });
+
+        let Some(project) = &mapping.key.project else {
+            return parent_id;
+        };
+        if let Some(existing) = existing_categories
+            .iter()
+            .chain(new_categories.iter())
+            .find(|category| category.name == *project && category.parent_id == parent_id)
+        {
+            return Some(existing.id);
+        }
+
+        let color = mapping
+            .color
+            .as_deref()
+            .filter(|hex| CategoryColor::is_valid_hex(hex))
+            .map(CategoryColor::from_hex)
+            .unwrap_or(CategoryColor::Gray);
+        let category = new_import_category(project, color, parent_id);
+        let id = category.id;
+        new_categories.push(category);
+        Some(id)
+    }
+
+    /// 导入iCalendar格式数据，事件按分类映射规则归类
+    fn import_ics<R: Read>(
         &self,
         reader: &mut R,
         existing_categories: &[Category],
         existing_entries: &[TimeEntry],
     ) -> Result<(Vec<Category>, Vec<TimeEntry>, ImportResult)> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+
         let mut result = ImportResult::new();
+        let parsed = match ical::parse_calendar(&content) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                result.add_error(error);
+                return Ok((Vec::new(), Vec::new(), result));
+            }
+        };
+        for error in parsed.errors {
+            result.add_error(error);
+        }
+        result.warnings.extend(parsed.warnings);
+
+        let mut new_categories = Vec::new();
+        let mut entries = Vec::new();
+        for event in &parsed.events {
+            let category =
+                self.resolve_event_category(event, existing_categories, &mut new_categories);
+            let category_name = category.as_ref().map(|c| c.name.to_lowercase());
+            let tags = event.tags.clone().unwrap_or_else(|| {
+                event
+                    .categories
+                    .iter()
+                    .filter(|value| Some(value.to_lowercase()) != category_name)
+                    .cloned()
+                    .collect()
+            });
+
+            let now = Local::now();
+            entries.push(TimeEntry {
+                id: event.id,
+                task_name: if event.summary.trim().is_empty() {
+                    "未命名".to_string()
+                } else {
+                    event.summary.clone()
+                },
+                category_id: category.map(|c| c.id),
+                start_time: event.start,
+                end_time: Some(event.end),
+                duration_seconds: event.end.signed_duration_since(event.start).num_seconds(),
+                description: event.description.clone(),
+                tags,
+                created_at: now,
+                updated_at: Some(now),
+            });
+        }
+
+        let mut known_categories = existing_categories.to_vec();
+        known_categories.extend(new_categories.iter().cloned());
+        let (entries, entry_result) =
+            self.process_time_entries(entries, &known_categories, existing_entries);
+
+        result.imported_categories = new_categories.len();
+        result.imported_entries = entry_result.imported_entries;
+        result.skipped_entries = entry_result.skipped_entries;
+        for error in entry_result.errors {
+            result.add_error(error);
+        }
+        result.warnings.extend(entry_result.warnings);
+
+        Ok((new_categories, entries, result))
+    }
+
+    /// 确定事件的分类：导出时记录的分类ID、映射规则、与 `CATEGORIES` 同名的分类，
+    /// 然后按需创建分类，最后使用默认分类
+    fn resolve_event_category(
+        &self,
+        event: &CalendarEvent,
+        existing_categories: &[Category],
+        new_categories: &mut Vec<Category>,
+    ) -> Option<Category> {
+        let find = |name: &str, new_categories: &[Category]| {
+            tracker::find_category(existing_categories, name)
+                .or_else(|| tracker::find_category(new_categories, name))
+                .cloned()
+        };
+
+        if let Some(category) = event.category_id.and_then(|id| {
+            existing_categories
+                .iter()
+                .chain(new_categories.iter())
+                .find(|c| c.id == id)
+        }) {
+            return Some(category.clone());
+        }
+
+        let rule_target = self
+            .options
+            .category_rules
+            .iter()
+            .find(|rule| rule.matches(event))
+            .map(|rule| rule.category.as_str());
+        let candidates = event.category_candidates();
+        let name = match rule_target {
+            Some(name) => Some(name),
+            None => candidates
+                .iter()
+                .copied()
+                .find(|name| find(name, new_categories).is_some())
+                .or_else(|| candidates.first().copied()),
+        };
 
-        // 读取XML内容
+        if let Some(name) = name {
+            if let Some(category) = find(name, new_categories) {
+                return Some(category);
+            }
+            if self.options.create_missing_categories {
+                let category = new_import_category(name, CategoryColor::Gray, None);
+                new_categories.push(category.clone());
+                return Some(category);
+            }
+        }
+
+        let default_id = self
+            .options
+            .default_category_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())?;
+        existing_categories
+            .iter()
+            .find(|c| c.id == default_id)
+            .cloned()
+    }
+
+    /// 导入XML格式数据（与 `DataExporter::export_xml` 的输出对应）
+    fn import_xml<R: Read>(
+        &self,
+        reader: &mut R,
+        existing_categories: &[Category],
+        existing_entries: &[TimeEntry],
+    ) -> Result<(Vec<Category>, Vec<TimeEntry>, ImportResult)> {
         let mut xml_content = String::new();
         reader.read_to_string(&mut xml_content)?;
 
-        // 检查现有数据
-        let existing_category_count = existing_categories.len();
-        let existing_entry_count = existing_entries.len();
+        let parsed = match self.parse_xml_export(&xml_content) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                let mut result = ImportResult::new();
+                result.add_error(error);
+                return Ok((Vec::new(), Vec::new(), result));
+            }
+        };
+
+        let (categories, entries, mut result) =
+            self.process_export_data(parsed.data, existing_categories, existing_entries)?;
+        for error in parsed.errors {
+            result.add_error(error);
+        }
+        result.warnings.extend(parsed.warnings);
 
-        log::info!(
-            "开始XML导入，现有分类数量: {}, 现有记录数量: {}",
-            existing_category_count,
-            existing_entry_count
-        );
+        Ok((categories, entries, result))
+    }
 
-        // 简化的XML解析 - 目前返回未实现错误
-        // 在实际实现中，这里应该解析XML并创建Category和TimeEntry对象
-        result.add_error(ImportError::new(format!(
-            "XML导入暂未实现，内容长度: {} 字符，现有数据: {} 分类, {} 记录",
-            xml_content.len(),
-            existing_category_count,
-            existing_entry_count
-        )));
+    /// 解析XML导出文档，单个元素的错误不会中断解析
+    fn parse_xml_export(&self, content: &str) -> std::result::Result<XmlExport, ImportError> {
+        let document = roxmltree::Document::parse(content).map_err(|e| {
+            ImportError::new(format!("XML解析错误: {}", e)).with_line(e.pos().row as usize)
+        })?;
+
+        let root = document.root_element();
+        if !root.has_tag_name("timetracker_export") {
+            return Err(ImportError::new(format!(
+                "不是TimeTracker导出文件，根元素为: {}",
+                root.tag_name().name()
+            ))
+            .with_line(xml_line(root)));
+        }
 
-        // 返回空的结果集
-        Ok((Vec::new(), Vec::new(), result))
+        let mut errors = Vec::new();
+
+        let mut categories = Vec::new();
+        for node in xml_children(root, "categories").flat_map(|c| xml_children(c, "category")) {
+            match self.parse_xml_category(node) {
+                Ok(category) => categories.push(category),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let mut time_entries = Vec::new();
+        for node in xml_children(root, "time_entries").flat_map(|c| xml_children(c, "time_entry")) {
+            match self.parse_xml_time_entry(node) {
+                Ok(entry) => time_entries.push(entry),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let mut warnings = Vec::new();
+        let metadata = match xml_children(root, "metadata").next() {
+            Some(node) => {
+                let metadata = self.parse_xml_metadata(node)?;
+                if metadata.total_entries != time_entries.len() {
+                    warnings.push(format!(
+                        "元数据中的记录数 {} 与成功解析的记录数 {} 不一致",
+                        metadata.total_entries,
+                        time_entries.len()
+                    ));
+                }
+                metadata
+            }
+            None => ExportMetadata {
+                export_time: Local::now(),
+                version: String::new(),
+                total_entries: time_entries.len(),
+                total_categories: categories.len(),
+                date_range: None,
+                filters_applied: Vec::new(),
+            },
+        };
+
+        Ok(XmlExport {
+            data: ExportData {
+                metadata,
+                categories,
+                time_entries,
+                statistics: None,
+                financial: None,
+                tasks: Vec::new(),
+            },
+            errors,
+            warnings,
+        })
+    }
+
+    /// 解析XML元数据
+    fn parse_xml_metadata(
+        &self,
+        node: roxmltree::Node<'_, '_>,
+    ) -> std::result::Result<ExportMetadata, ImportError> {
+        let count = |name: &str| -> std::result::Result<usize, ImportError> {
+            match xml_field(node, name) {
+                Some((text, line)) => text.trim().parse().map_err(|_| {
+                    ImportError::new(format!("无效的数量: {}", text))
+                        .with_line(line)
+                        .with_field(name.to_string())
+                        .with_data(text.clone())
+                }),
+                None => Ok(0),
+            }
+        };
+
+        let export_time = match xml_field(node, "export_time") {
+            Some((text, line)) => self.parse_xml_datetime(&text, line, "export_time")?,
+            None => Local::now(),
+        };
+
+        Ok(ExportMetadata {
+            export_time,
+            version: xml_field(node, "version")
+                .map(|(text, _)| text)
+                .unwrap_or_default(),
+            total_entries: count("total_entries")?,
+            total_categories: count("total_categories")?,
+            date_range: None,
+            filters_applied: Vec::new(),
+        })
+    }
+
+    /// 解析XML中的分类元素
+    fn parse_xml_category(
+        &self,
+        node: roxmltree::Node<'_, '_>,
+    ) -> std::result::Result<Category, ImportError> {
+        let id = self.parse_xml_uuid(node, "id")?;
+        let name = xml_required(node, "name")?.0;
+
+        let color = match xml_field(node, "color") {
+            Some((hex, line)) => {
+                if !CategoryColor::is_valid_hex(hex.trim()) {
+                    return Err(ImportError::new(format!("无效的颜色: {}", hex))
+                        .with_line(line)
+                        .with_field("color".to_string())
+                        .with_data(hex));
+                }
+                CategoryColor::from_hex(hex.trim())
+            }
+            None => CategoryColor::Gray,
+        };
+
+        let created_at = match xml_field(node, "created_at") {
+            Some((text, line)) => self.parse_xml_datetime(&text, line, "created_at")?,
+            None => Local::now(),
+        };
+
+        Ok(Category {
+            id,
+            name,
+            description: xml_field(node, "description").map(|(text, _)| text),
+            color,
+            icon: CategoryIcon::Other,
+            created_at,
+            updated_at: created_at,
+            daily_target: None,
+            weekly_target: None,
+            target_duration: None,
+            is_active: true,
+            sort_order: 0,
+            parent_id: None,
+        })
+    }
+
+    /// 解析XML中的时间记录元素
+    fn parse_xml_time_entry(
+        &self,
+        node: roxmltree::Node<'_, '_>,
+    ) -> std::result::Result<TimeEntry, ImportError> {
+        let id = self.parse_xml_uuid(node, "id")?;
+        let task_name = xml_required(node, "task_name")?.0;
+
+        let category_id = match xml_field(node, "category_id") {
+            Some((text, line)) if !text.trim().is_empty() => {
+                Some(Uuid::parse_str(text.trim()).map_err(|_| {
+                    ImportError::new(format!("无效的分类ID: {}", text))
+                        .with_line(line)
+                        .with_field("category_id".to_string())
+                        .with_data(text.clone())
+                })?)
+            }
+            _ => None,
+        };
+
+        let (start_text, start_line) = xml_required(node, "start_time")?;
+        let start_time = self.parse_xml_datetime(&start_text, start_line, "start_time")?;
+        let end_time = match xml_field(node, "end_time") {
+            Some((text, line)) => Some(self.parse_xml_datetime(&text, line, "end_time")?),
+            None => None,
+        };
+
+        Ok(TimeEntry {
+            id,
+            task_name,
+            category_id,
+            start_time,
+            end_time,
+            duration_seconds: end_time
+                .map(|end| end.signed_duration_since(start_time).num_seconds())
+                .unwrap_or(0),
+            description: xml_field(node, "description").map(|(text, _)| text),
+            tags: vec![],
+            created_at: start_time,
+            updated_at: None,
+        })
+    }
+
+    /// 解析XML中的UUID字段
+    fn parse_xml_uuid(
+        &self,
+        node: roxmltree::Node<'_, '_>,
+        name: &str,
+    ) -> std::result::Result<Uuid, ImportError> {
+        let (text, line) = xml_required(node, name)?;
+        Uuid::parse_str(text.trim()).map_err(|_| {
+            ImportError::new(format!("无效的ID: {}", text))
+                .with_line(line)
+                .with_field(name.to_string())
+                .with_data(text.clone())
+        })
+    }
+
+    /// 解析XML中的日期时间字段
+    fn parse_xml_datetime(
+        &self,
+        text: &str,
+        line: usize,
+        name: &str,
+    ) -> std::result::Result<DateTime<Local>, ImportError> {
+        self.parse_datetime(text.trim()).map_err(|e| {
+            ImportError::new(e.to_string())
+                .with_line(line)
+                .with_field(name.to_string())
+                .with_data(text.to_string())
+        })
     }
 
     /// 处理导出数据
     fn process_export_data(
         &self,
-        export_data: crate::utils::export::ExportData,
+        export_data: ExportData,
         existing_categories: &[Category],
         existing_entries: &[TimeEntry],
     ) -> Result<(Vec<Category>, Vec<TimeEntry>, ImportResult)> {
         let mut result = ImportResult::new();
 
-        let (categories, category_result) =
+        // 跳过同名分类时，记录改为引用现有的同名分类
+        let mut category_remap = HashMap::new();
+        if self.options.skip_duplicates {
+            for category in &export_data.categories {
+                if let Some(existing) = existing_categories
+                    .iter()
+                    .find(|c| c.name == category.name && c.id != category.id)
+                {
+                    category_remap.insert(category.id, existing.id);
+                }
+            }
+        }
+
+        let (mut categories, category_result) =
             self.process_categories(export_data.categories, existing_categories);
 
-        let (entries, entry_result) = self.process_time_entries(
-            export_data.time_entries,
-            existing_categories,
-            existing_entries,
-        );
+        let mut time_entries = export_data.time_entries;
+        for entry in &mut time_entries {
+            if let Some(new_id) = entry.category_id.and_then(|id| category_remap.get(&id)) {
+                entry.category_id = Some(*new_id);
+            }
+        }
+
+        // 文件中的分类和现有分类都可以被时间记录引用
+        let mut known_categories = existing_categories.to_vec();
+        known_categories.extend(categories.iter().cloned());
+
+        if self.options.create_missing_categories {
+            for entry in &time_entries {
+                let Some(category_id) = entry.category_id else {
+                    continue;
+                };
+                if known_categories.iter().any(|c| c.id == category_id) {
+                    continue;
+                }
 
-        result.imported_categories = category_result.imported_categories;
+                let now = Local::now();
+                let category = Category {
+                    id: category_id,
+                    name: format!("导入的分类 {}", &category_id.to_string()[..8]),
+                    description: None,
+                    color: CategoryColor::Gray,
+                    icon: CategoryIcon::Other,
+                    created_at: now,
+                    updated_at: now,
+                    daily_target: None,
+                    weekly_target: None,
+                    target_duration: None,
+                    is_active: true,
+                    sort_order: 0,
+                    parent_id: None,
+                };
+                result.imported_categories += 1;
+                result.add_warning(format!("创建缺失的分类: {}", category.name));
+                known_categories.push(category.clone());
+                categories.push(category);
+            }
+        }
+
+        let (entries, entry_result) =
+            self.process_time_entries(time_entries, &known_categories, existing_entries);
+
+        result.imported_categories += category_result.imported_categories;
         result.skipped_categories = category_result.skipped_categories;
         result.imported_entries = entry_result.imported_entries;
         result.skipped_entries = entry_result.skipped_entries;
-        result.errors.extend(category_result.errors);
-        result.errors.extend(entry_result.errors);
+        for error in category_result
+            .errors
+            .into_iter()
+            .chain(entry_result.errors)
+        {
+            result.add_error(error);
+        }
         result.warnings.extend(category_result.warnings);
         result.warnings.extend(entry_result.warnings);
 
@@ -475,20 +1084,21 @@ impl DataImporter {
             existing_categories.iter().map(|c| &c.name).collect();
 
         for category in categories {
-            // 检查ID重复
-            if existing_ids.contains(&category.id) {
-                if self.options.skip_duplicates {
+            // 检查ID重复（允许更新时优先更新现有分类）
+            let is_update = existing_ids.contains(&category.id);
+            if is_update {
+                if self.options.update_existing {
+                    result.add_warning(format!("更新分类: {}", category.name));
+                } else if self.options.skip_duplicates {
                     result.skipped_categories += 1;
                     result.add_warning(format!("跳过重复分类ID: {}", category.id));
                     continue;
-                } else if self.options.update_existing {
-                    // 更新现有分类（这里简化处理，实际应该通过数据库更新）
-                    result.add_warning(format!("更新分类: {}", category.name));
                 }
             }
 
             // 检查名称重复
-            if existing_names.contains(&category.name) && self.options.skip_duplicates {
+            if !is_update && existing_names.contains(&category.name) && self.options.skip_duplicates
+            {
                 result.skipped_categories += 1;
                 result.add_warning(format!("跳过重复分类名称: {}", category.name));
                 continue;
@@ -529,14 +1139,15 @@ impl DataImporter {
             existing_categories.iter().map(|c| &c.id).collect();
 
         for entry in entries {
-            // 检查ID重复
+            // 检查ID重复（允许更新时优先更新现有记录）
+            let is_update = existing_ids.contains(&entry.id) && self.options.update_existing;
             if existing_ids.contains(&entry.id) {
-                if self.options.skip_duplicates {
+                if is_update {
+                    result.add_warning(format!("更新记录: {}", entry.task_name));
+                } else if self.options.skip_duplicates {
                     result.skipped_entries += 1;
                     result.add_warning(format!("跳过重复记录ID: {}", entry.id));
                     continue;
-                } else if self.options.update_existing {
-                    result.add_warning(format!("更新记录: {}", entry.task_name));
                 }
             }
 
@@ -571,6 +1182,7 @@ impl DataImporter {
 
                     // 检查重复
                     if self.options.skip_duplicates
+                        && !is_update
                         && self.is_duplicate_entry(&entry, existing_entries, &processed_entries)
                     {
                         result.skipped_entries += 1;
@@ -709,6 +1321,65 @@ impl DataImporter {
     }
 }
 
+/// XML节点所在的行号
+fn xml_line(node: roxmltree::Node<'_, '_>) -> usize {
+    node.document().text_pos_at(node.range().start).row as usize
+}
+
+/// 指定名称的子元素
+fn xml_children<'a, 'input: 'a>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+/// 子元素的文本和行号
+fn xml_field(node: roxmltree::Node<'_, '_>, name: &str) -> Option<(String, usize)> {
+    xml_children(node, name)
+        .next()
+        .map(|child| (child.text().unwrap_or("").to_string(), xml_line(child)))
+}
+
+/// 必填子元素的文本和行号
+fn xml_required(
+    node: roxmltree::Node<'_, '_>,
+    name: &str,
+) -> std::result::Result<(String, usize), ImportError> {
+    match xml_field(node, name) {
+        Some((text, line)) if !text.trim().is_empty() => Ok((text, line)),
+        Some((_, line)) => Err(ImportError::new(format!("字段不能为空: {}", name))
+            .with_line(line)
+            .with_field(name.to_string())),
+        None => Err(
+            ImportError::new(format!("<{}> 缺少字段: {}", node.tag_name().name(), name))
+                .with_line(xml_line(node))
+                .with_field(name.to_string()),
+        ),
+    }
+}
+
+/// 创建导入时缺失的分类
+fn new_import_category(name: &str, color: CategoryColor, parent_id: Option<Uuid>) -> Category {
+    let now = Local::now();
+    Category {
+        id: generate_id(),
+        name: name.to_string(),
+        description: None,
+        color,
+        icon: CategoryIcon::Other,
+        created_at: now,
+        updated_at: now,
+        daily_target: None,
+        weekly_target: None,
+        target_duration: None,
+        is_active: true,
+        sort_order: 0,
+        parent_id,
+    }
+}
+
 /// 创建默认导入选项
 pub fn create_import_options(format: ImportFormat) -> ImportOptions {
     ImportOptions {
@@ -727,13 +1398,34 @@ pub fn detect_format<P: AsRef<Path>>(file_path: P) -> Option<ImportFormat> {
 }
 
 /// 从JSON文件导入数据
-pub fn import_from_json<P: AsRef<Path>>(file_path: P) -> Result<crate::utils::export::ExportData> {
+pub fn import_from_json<P: AsRef<Path>>(file_path: P) -> Result<ExportData> {
     let file = File::open(file_path)?;
     let reader = BufReader::new(file);
-    let export_data: crate::utils::export::ExportData = serde_json::from_reader(reader)?;
+    let export_data: ExportData = serde_json::from_reader(reader)?;
     Ok(export_data)
 }
 
+/// 从XML文件导入数据
+pub fn import_from_xml<P: AsRef<Path>>(file_path: P) -> Result<ExportData> {
+    let content = std::fs::read_to_string(file_path)?;
+    let importer = DataImporter::new(create_import_options(ImportFormat::Xml));
+    let parsed = importer
+        .parse_xml_export(&content)
+        .map_err(describe_error)?;
+    match parsed.errors.into_iter().next() {
+        Some(error) => Err(describe_error(error)),
+        None => Ok(parsed.data),
+    }
+}
+
+/// 将导入错误转换为带行号的错误信息
+fn describe_error(error: ImportError) -> anyhow::Error {
+    match error.line_number {
+        Some(line) => anyhow::Error::msg(format!("第 {} 行: {}", line, error.message)),
+        None => anyhow::Error::msg(error.message),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -861,4 +1553,289 @@ mod tests {
         invalid_entry.end_time = Some(invalid_entry.start_time - chrono::Duration::hours(1));
         assert!(importer.validate_time_entry(&invalid_entry).is_err());
     }
+
+    fn export_xml(categories: Vec<Category>, entries: Vec<TimeEntry>) -> String {
+        use crate::utils::export::{DataExporter, ExportFormat, ExportOptions};
+
+        let options = ExportOptions {
+            format: ExportFormat::Xml,
+            ..Default::default()
+        };
+        let data = crate::utils::export::create_export_data(entries, categories, &options);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("export.xml");
+        DataExporter::new(options)
+            .export_to_file(&data, &path)
+            .unwrap();
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_import_xml_round_trip() {
+        let category = create_test_category();
+        let mut entry = create_test_entry();
+        entry.category_id = Some(category.id);
+        entry.description = Some("<Rust> & \"XML\"".to_string());
+        let xml = export_xml(vec![category.clone()], vec![entry.clone()]);
+
+        let importer = DataImporter::new(create_import_options(ImportFormat::Xml));
+        let (categories, entries, result) = importer
+            .import_xml(&mut Cursor::new(xml.as_bytes()), &[], &[])
+            .unwrap();
+
+        assert!(result.success, "{:?}", result.errors);
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories[0].id, category.id);
+        assert_eq!(categories[0].name, category.name);
+        assert_eq!(categories[0].description, category.description);
+        assert_eq!(categories[0].color.to_hex(), category.color.to_hex());
+        assert_eq!(categories[0].created_at, category.created_at);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].task_name, entry.task_name);
+        assert_eq!(entries[0].category_id, Some(category.id));
+        assert_eq!(entries[0].start_time, entry.start_time);
+        assert_eq!(entries[0].end_time, entry.end_time);
+        assert_eq!(entries[0].duration_seconds, 3600);
+        assert_eq!(entries[0].description, entry.description);
+
+        // 再次导入时按ID跳过重复数据，允许更新时则返回全部数据
+        let (categories, entries, result) = importer
+            .import_xml(&mut Cursor::new(xml.as_bytes()), &categories, &entries)
+            .unwrap();
+        assert!(categories.is_empty() && entries.is_empty());
+        assert_eq!(result.skipped_categories, 1);
+        assert_eq!(result.skipped_entries, 1);
+
+        let importer = DataImporter::new(ImportOptions {
+            format: ImportFormat::Xml,
+            update_existing: true,
+            ..Default::default()
+        });
+        let (updated_categories, updated_entries, _) = importer
+            .import_xml(&mut Cursor::new(xml.as_bytes()), &[category], &[entry])
+            .unwrap();
+        assert_eq!(updated_categories.len(), 1);
+        assert_eq!(updated_entries.len(), 1);
+    }
+
+    #[test]
+    fn test_import_xml_reports_line_numbers() {
+        let category_id = Uuid::new_v4();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<timetracker_export>
+  <time_entries>
+    <time_entry>
+      <id>{}</id>
+      <task_name>编程</task_name>
+      <category_id>{}</category_id>
+      <start_time>2023-01-01 09:00:00</start_time>
+    </time_entry>
+    <time_entry>
+      <id>{}</id>
+      <task_name>阅读</task_name>
+      <start_time>昨天</start_time>
+    </time_entry>
+  </time_entries>
+</timetracker_export>
+"#,
+            Uuid::new_v4(),
+            category_id,
+            Uuid::new_v4()
+        );
+
+        let importer = DataImporter::new(create_import_options(ImportFormat::Xml));
+        let (categories, entries, result) = importer
+            .import_xml(&mut Cursor::new(xml.as_bytes()), &[], &[])
+            .unwrap();
+
+        // 缺失的分类会自动创建
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories[0].id, category_id);
+        assert_eq!(entries.len(), 1);
+
+        assert!(!result.success);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].line_number, Some(13));
+        assert_eq!(result.errors[0].field.as_deref(), Some("start_time"));
+
+        let (_, _, result) = importer
+            .import_xml(
+                &mut Cursor::new(b"<timetracker_export>\n<categories>\n</category>".as_slice()),
+                &[],
+                &[],
+            )
+            .unwrap();
+        assert_eq!(result.errors[0].line_number, Some(3));
+    }
+
+    #[test]
+    fn test_import_dry_run() {
+        let xml = export_xml(vec![create_test_category()], vec![]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("import.xml");
+        std::fs::write(&path, xml).unwrap();
+
+        let importer = DataImporter::new(ImportOptions {
+            format: ImportFormat::Xml,
+            dry_run: true,
+            ..Default::default()
+        });
+        let (categories, entries, result) = importer.import_from_file(&path, &[], &[]).unwrap();
+
+        assert!(categories.is_empty() && entries.is_empty());
+        assert_eq!(result.imported_categories, 1);
+        assert!(import_from_xml(&path).is_ok());
+    }
+
+    #[test]
+    fn test_import_tracker_with_mapping_preview() {
+        let csv = "Client,Project,Task,Description,Start date,Start time,End date,End time,Duration,Tags\n\
+                   Acme,Website,,首页设计,2024-01-15,09:00:00,2024-01-15,10:00:00,01:00:00,设计\n\
+                   Acme,Backend,,接口,2024-01-15,11:00:00,2024-01-15,12:00:00,01:00:00,\n\
+                   ,工作,,周报,2024-01-15,14:00:00,2024-01-15,14:30:00,00:30:00,\n";
+        let existing = vec![create_test_category()];
+        let importer = DataImporter::new(create_import_options(ImportFormat::Csv));
+
+        let mut preview = importer.preview_tracker_content(csv, &existing).unwrap();
+        assert_eq!(preview.source, tracker::TrackerSource::Toggl);
+        assert_eq!(preview.mappings.len(), 3);
+        assert_eq!(preview.total_seconds(), 9000);
+
+        // 同名项目默认合并到现有分类
+        let work = ProjectKey {
+            client: None,
+            project: Some("工作".to_string()),
+        };
+        let mapping = preview.mappings.iter().find(|m| m.key == work).unwrap();
+        assert_eq!(mapping.target, MappingTarget::Existing(existing[0].id));
+
+        // 手动把 Backend 合并到现有分类
+        let backend = ProjectKey {
+            client: Some("Acme".to_string()),
+            project: Some("Backend".to_string()),
+        };
+        assert!(preview.set_target(&backend, MappingTarget::Existing(existing[0].id)));
+
+        let (categories, entries, result) = importer.import_tracker(&preview, &existing, &[]);
+        assert!(result.success);
+        assert_eq!(result.imported_entries, 3);
+        assert_eq!(categories.len(), 2);
+        let client = categories.iter().find(|c| c.name == "Acme").unwrap();
+        let website = categories.iter().find(|c| c.name == "Website").unwrap();
+        assert_eq!(website.parent_id, Some(client.id));
+
+        let design = entries.iter().find(|e| e.task_name == "首页设计").unwrap();
+        assert_eq!(design.category_id, Some(website.id));
+        assert_eq!(design.tags, vec!["设计".to_string()]);
+        assert_eq!(design.duration_seconds, 3600);
+        assert!(entries
+            .iter()
+            .filter(|e| e.task_name != "首页设计")
+            .all(|e| e.category_id == Some(existing[0].id)));
+
+        // 再次导入时复用已创建的分类并跳过重复记录
+        let mut all_categories = existing.clone();
+        all_categories.extend(categories);
+        let (categories, _, result) = importer.import_tracker(&preview, &all_categories, &entries);
+        assert!(categories.is_empty());
+        assert_eq!(result.skipped_entries, 3);
+    }
+
+    #[test]
+    fn test_import_ics_round_trip() {
+        use crate::storage::task_models::TaskModel;
+        use crate::utils::export::{DataExporter, ExportFormat, ExportOptions};
+
+        let category = create_test_category();
+        let mut entry = create_test_entry();
+        entry.category_id = Some(category.id);
+        entry.tags = vec!["rust".to_string(), "学习, 进阶".to_string()];
+        let mut task = TaskModel::new("写周报".to_string(), None, Some(category.id));
+        task.due_date = Some(Local.with_ymd_and_hms(2023, 1, 6, 18, 0, 0).unwrap());
+        let undated = TaskModel::new("没有截止日期".to_string(), None, None);
+
+        let options = ExportOptions {
+            format: ExportFormat::Ics,
+            ..Default::default()
+        };
+        let mut data = crate::utils::export::create_export_data(
+            vec![entry.clone()],
+            vec![category.clone()],
+            &options,
+        );
+        data.tasks = vec![task.clone(), undated];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("calendar.ics");
+        DataExporter::new(options)
+            .export_to_file(&data, &path)
+            .unwrap();
+
+        let ics = std::fs::read_to_string(&path).unwrap();
+        assert!(ics.contains(&format!("UID:{}@lifetracker", entry.id)));
+        assert!(ics.contains(&format!("UID:{}@lifetracker", task.id)));
+        assert!(ics.contains("DUE:"));
+        assert!(!ics.contains("没有截止日期"));
+        assert_eq!(detect_format(&path), Some(ImportFormat::Ics));
+
+        let importer = DataImporter::new(create_import_options(ImportFormat::Ics));
+        let (categories, entries, result) = importer
+            .import_from_file(&path, std::slice::from_ref(&category), &[])
+            .unwrap();
+        assert!(result.success, "{:?}", result.errors);
+        assert!(categories.is_empty());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, entry.id);
+        assert_eq!(entries[0].category_id, Some(category.id));
+        assert_eq!(entries[0].tags, entry.tags);
+        assert_eq!(entries[0].start_time, entry.start_time);
+        assert_eq!(entries[0].end_time, entry.end_time);
+
+        // 再次导入同一日历不会产生重复记录
+        let (_, entries, result) = importer
+            .import_from_file(&path, &[category], &entries)
+            .unwrap();
+        assert!(entries.is_empty());
+        assert_eq!(result.skipped_entries, 1);
+    }
+
+    #[test]
+    fn test_import_ics_category_rules() {
+        use crate::utils::ical::RuleSource;
+
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+                   BEGIN:VEVENT\r\nUID:1@example.com\r\nDTSTART:20240101T090000Z\r\n\
+                   DTEND:20240101T100000Z\r\nSUMMARY:周会\r\nCATEGORIES:会议,团队\r\nEND:VEVENT\r\n\
+                   BEGIN:VEVENT\r\nUID:2@example.com\r\nDTSTART:20240101T110000Z\r\n\
+                   DTEND:20240101T120000Z\r\nSUMMARY:健身\r\nCATEGORIES:运动\r\nEND:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("external.ics");
+        std::fs::write(&path, ics).unwrap();
+
+        let work = create_test_category();
+        let importer = DataImporter::new(ImportOptions {
+            format: ImportFormat::Ics,
+            category_rules: vec![CategoryRule::new(RuleSource::Summary, "周会", "工作")],
+            ..Default::default()
+        });
+        let (categories, entries, result) = importer
+            .import_from_file(&path, std::slice::from_ref(&work), &[])
+            .unwrap();
+        assert!(result.success, "{:?}", result.errors);
+
+        let meeting = entries.iter().find(|e| e.task_name == "周会").unwrap();
+        assert_eq!(meeting.category_id, Some(work.id));
+        assert_eq!(meeting.tags, vec!["会议".to_string(), "团队".to_string()]);
+
+        // 没有匹配规则时按 CATEGORIES 创建分类
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories[0].name, "运动");
+        let workout = entries.iter().find(|e| e.task_name == "健身").unwrap();
+        assert_eq!(workout.category_id, Some(categories[0].id));
+        assert!(workout.tags.is_empty());
+        assert_ne!(workout.id, meeting.id);
+    }
 }
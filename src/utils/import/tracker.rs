@@ -0,0 +1,700 @@
+//! # Toggl Track / Clockify 导入
+//!
+//! 识别 Toggl Track 和 Clockify 明细报表的 CSV 和 JSON 导出：
+//! - 客户映射为父分类，项目映射为其下的子分类，标签映射为时间记录标签
+//! - CSV 中的时间不带时区，按 [`ImportOptions::source_utc_offset`](super::ImportOptions) 解释
+//! - 导入前生成项目映射预览，可以把项目合并到现有分类
+
+use super::ImportError;
+use crate::core::Category;
+use chrono::TimeZone;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+/// 导出数据的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackerSource {
+    Toggl,
+    Clockify,
+}
+
+impl TrackerSource {
+    /// 显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TrackerSource::Toggl => "Toggl Track",
+            TrackerSource::Clockify => "Clockify",
+        }
+    }
+}
+
+/// 统一格式的时间记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerEntry {
+    pub client: Option<String>,
+    pub project: Option<String>,
+    /// 项目颜色（十六进制）
+    pub project_color: Option<String>,
+    pub task: Option<String>,
+    pub description: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub tags: Vec<String>,
+    /// CSV 中的行号
+    pub line: Option<usize>,
+}
+
+impl TrackerEntry {
+    /// 映射使用的客户/项目键
+    pub fn key(&self) -> ProjectKey {
+        ProjectKey {
+            client: self.client.clone(),
+            project: self.project.clone(),
+        }
+    }
+}
+
+/// 客户和项目组合
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProjectKey {
+    pub client: Option<String>,
+    pub project: Option<String>,
+}
+
+impl ProjectKey {
+    /// 显示名称（客户 / 项目）
+    pub fn display_name(&self) -> String {
+        match (&self.client, &self.project) {
+            (Some(client), Some(project)) => format!("{} / {}", client, project),
+            (None, Some(project)) => project.clone(),
+            (Some(client), None) => client.clone(),
+            (None, None) => "（无项目）".to_string(),
+        }
+    }
+}
+
+/// 项目映射的目标
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingTarget {
+    /// 合并到现有分类
+    Existing(Uuid),
+    /// 创建新分类（有客户时创建在客户分类下）
+    Create,
+    /// 不设置分类
+    Uncategorized,
+}
+
+/// 单个项目的映射
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectMapping {
+    pub key: ProjectKey,
+    /// 项目颜色
+    pub color: Option<String>,
+    /// 记录数
+    pub entry_count: usize,
+    /// 总时长（秒）
+    pub total_seconds: i64,
+    /// 映射目标
+    pub target: MappingTarget,
+}
+
+/// 导入预览
+#[derive(Debug, Clone)]
+pub struct TrackerImportPreview {
+    pub source: TrackerSource,
+    pub entries: Vec<TrackerEntry>,
+    /// 项目映射（按客户、项目排序）
+    pub mappings: Vec<ProjectMapping>,
+    /// 无法解析的记录
+    pub errors: Vec<ImportError>,
+    pub warnings: Vec<String>,
+}
+
+impl TrackerImportPreview {
+    /// 根据解析结果和现有分类生成默认映射：同名分类直接合并，否则新建或不分类
+    pub(super) fn new(
+        parsed: ParsedExport,
+        existing_categories: &[Category],
+        create_missing: bool,
+    ) -> Self {
+        let mut groups: BTreeMap<ProjectKey, ProjectMapping> = BTreeMap::new();
+        for entry in &parsed.entries {
+            let key = entry.key();
+            let mapping = groups.entry(key.clone()).or_insert_with(|| {
+                let name = key.project.as_ref().or(key.client.as_ref());
+                let target = match name.and_then(|name| find_category(existing_categories, name)) {
+                    Some(category) => MappingTarget::Existing(category.id),
+                    None if name.is_some() && create_missing => MappingTarget::Create,
+                    None => MappingTarget::Uncategorized,
+                };
+                ProjectMapping {
+                    key,
+                    color: None,
+                    entry_count: 0,
+                    total_seconds: 0,
+                    target,
+                }
+            });
+            mapping.entry_count += 1;
+            mapping.total_seconds += entry.end.signed_duration_since(entry.start).num_seconds();
+            if mapping.color.is_none() {
+                mapping.color = entry.project_color.clone();
+            }
+        }
+
+        Self {
+            source: parsed.source,
+            entries: parsed.entries,
+            mappings: groups.into_values().collect(),
+            errors: parsed.errors,
+            warnings: parsed.warnings,
+        }
+    }
+
+    /// 修改某个项目的映射目标，项目不存在时返回 false
+    pub fn set_target(&mut self, key: &ProjectKey, target: MappingTarget) -> bool {
+        match self.mappings.iter_mut().find(|mapping| &mapping.key == key) {
+            Some(mapping) => {
+                mapping.target = target;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 记录的时间范围
+    pub fn date_range(&self) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let start = self.entries.iter().map(|entry| entry.start).min()?;
+        let end = self.entries.iter().map(|entry| entry.end).max()?;
+        Some((start, end))
+    }
+
+    /// 总时长（秒）
+    pub fn total_seconds(&self) -> i64 {
+        self.mappings
+            .iter()
+            .map(|mapping| mapping.total_seconds)
+            .sum()
+    }
+}
+
+/// 按名称查找分类（忽略大小写和首尾空白）
+pub(super) fn find_category<'a>(categories: &'a [Category], name: &str) -> Option<&'a Category> {
+    let name = name.trim();
+    categories
+        .iter()
+        .find(|category| category.name.trim().eq_ignore_ascii_case(name))
+}
+
+/// 解析结果
+pub(super) struct ParsedExport {
+    pub source: TrackerSource,
+    pub entries: Vec<TrackerEntry>,
+    pub errors: Vec<ImportError>,
+    pub warnings: Vec<String>,
+}
+
+impl ParsedExport {
+    fn new(source: TrackerSource) -> Self {
+        Self {
+            source,
+            entries: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// 识别并解析导出内容，不是 Toggl/Clockify 格式时返回 None
+pub(super) fn parse(content: &str, offset: Option<FixedOffset>) -> Option<ParsedExport> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        let value: Value = serde_json::from_str(trimmed).ok()?;
+        parse_json(&value)
+    } else {
+        parse_csv(trimmed, offset)
+    }
+}
+
+// ==================== CSV ====================
+
+/// CSV 列索引
+struct CsvColumns {
+    headers: HashMap<String, usize>,
+}
+
+impl CsvColumns {
+    fn new(headers: &csv::StringRecord) -> Self {
+        Self {
+            headers: headers
+                .iter()
+                .enumerate()
+                .map(|(index, name)| (name.trim().to_lowercase(), index))
+                .collect(),
+        }
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.headers.contains_key(name)
+    }
+
+    /// 读取第一个存在且非空的列
+    fn get<'r>(&self, record: &'r csv::StringRecord, names: &[&str]) -> Option<&'r str> {
+        names
+            .iter()
+            .filter_map(|name| self.headers.get(*name))
+            .filter_map(|index| record.get(*index))
+            .map(str::trim)
+            .find(|value| !value.is_empty())
+    }
+}
+
+fn detect_csv(columns: &CsvColumns) -> Option<TrackerSource> {
+    if !columns.has("start date") || !columns.has("start time") {
+        return None;
+    }
+    if columns.has("duration (h)") || columns.has("duration (decimal)") {
+        Some(TrackerSource::Clockify)
+    } else if columns.has("duration") {
+        Some(TrackerSource::Toggl)
+    } else {
+        None
+    }
+}
+
+fn parse_csv(content: &str, offset: Option<FixedOffset>) -> Option<ParsedExport> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let columns = CsvColumns::new(reader.headers().ok()?);
+    let source = detect_csv(&columns)?;
+    let records: Vec<_> = reader.records().collect();
+
+    // Clockify 的日期格式取决于用户设置，只要有一个日期的首段大于 12 就按日/月/年解析
+    let day_first = records.iter().flatten().any(|record| {
+        columns
+            .get(record, &["start date"])
+            .and_then(|date| date.split(['/', '.']).next())
+            .and_then(|day| day.parse::<u32>().ok())
+            .is_some_and(|day| day > 12)
+    });
+
+    let mut parsed = ParsedExport::new(source);
+    for (index, record) in records.into_iter().enumerate() {
+        let line = index + 2;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                parsed
+                    .errors
+                    .push(ImportError::new(format!("CSV解析错误: {}", e)).with_line(line));
+                continue;
+            }
+        };
+        match parse_csv_record(&columns, &record, day_first, offset) {
+            Ok(mut entry) => {
+                entry.line = Some(line);
+                parsed.entries.push(entry);
+            }
+            Err(error) => parsed.errors.push(error.with_line(line)),
+        }
+    }
+    Some(parsed)
+}
+
+fn parse_csv_record(
+    columns: &CsvColumns,
+    record: &csv::StringRecord,
+    day_first: bool,
+    offset: Option<FixedOffset>,
+) -> Result<TrackerEntry, ImportError> {
+    let field_error = |field: &str, value: &str, message: &str| {
+        ImportError::new(format!("{}: {}", message, value))
+            .with_field(field.to_string())
+            .with_data(value.to_string())
+    };
+
+    let start_date_text = columns.get(record, &["start date"]).ok_or_else(|| {
+        ImportError::new("缺少开始日期".to_string()).with_field("Start date".into())
+    })?;
+    let start_time_text = columns.get(record, &["start time"]).unwrap_or("00:00:00");
+    let start_date = parse_date(start_date_text, day_first)
+        .ok_or_else(|| field_error("Start date", start_date_text, "无法解析日期"))?;
+    let start_time = parse_time(start_time_text)
+        .ok_or_else(|| field_error("Start time", start_time_text, "无法解析时间"))?;
+    let start = to_local(start_date.and_time(start_time), offset)
+        .ok_or_else(|| field_error("Start time", start_time_text, "时间在当前时区不存在"))?;
+
+    let duration = match columns.get(record, &["duration", "duration (h)"]) {
+        Some(text) => Some(
+            parse_clock_duration(text)
+                .ok_or_else(|| field_error("Duration", text, "无法解析时长"))?,
+        ),
+        None => columns
+            .get(record, &["duration (decimal)"])
+            .and_then(|text| text.replace(',', ".").parse::<f64>().ok())
+            .map(|hours| Duration::seconds((hours * 3600.0).round() as i64)),
+    };
+
+    let end_date = columns.get(record, &["end date", "stop date"]);
+    let end_time = columns.get(record, &["end time", "stop time"]);
+    let end = match (end_date, end_time) {
+        (date, Some(time_text)) => {
+            let date = match date {
+                Some(text) => parse_date(text, day_first)
+                    .ok_or_else(|| field_error("End date", text, "无法解析日期"))?,
+                None => start_date,
+            };
+            let time = parse_time(time_text)
+                .ok_or_else(|| field_error("End time", time_text, "无法解析时间"))?;
+            let mut end = to_local(date.and_time(time), offset)
+                .ok_or_else(|| field_error("End time", time_text, "时间在当前时区不存在"))?;
+            // 只有时间没有日期的记录跨过午夜
+            if end < start {
+                end += Duration::days(1);
+            }
+            end
+        }
+        _ => start + duration.ok_or_else(|| ImportError::new("缺少结束时间和时长".to_string()))?,
+    };
+
+    Ok(TrackerEntry {
+        client: columns.get(record, &["client"]).map(str::to_string),
+        project: columns.get(record, &["project"]).map(str::to_string),
+        project_color: None,
+        task: columns.get(record, &["task"]).map(str::to_string),
+        description: columns
+            .get(record, &["description"])
+            .unwrap_or_default()
+            .to_string(),
+        start,
+        end,
+        tags: columns
+            .get(record, &["tags"])
+            .map(split_tags)
+            .unwrap_or_default(),
+        line: None,
+    })
+}
+
+fn parse_date(text: &str, day_first: bool) -> Option<NaiveDate> {
+    let slash_formats = if day_first {
+        ["%d/%m/%Y", "%m/%d/%Y"]
+    } else {
+        ["%m/%d/%Y", "%d/%m/%Y"]
+    };
+    ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"]
+        .iter()
+        .chain(slash_formats.iter())
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim().to_uppercase();
+    ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(&text, format).ok())
+}
+
+/// 解析 `h:mm:ss` 格式的时长（小时可以超过 24）
+fn parse_clock_duration(text: &str) -> Option<Duration> {
+    let parts: Vec<i64> = text
+        .split(':')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [hours, minutes, seconds] => Some(Duration::seconds(hours * 3600 + minutes * 60 + seconds)),
+        [hours, minutes] => Some(Duration::seconds(hours * 3600 + minutes * 60)),
+        _ => None,
+    }
+}
+
+fn to_local(naive: NaiveDateTime, offset: Option<FixedOffset>) -> Option<DateTime<Local>> {
+    match offset {
+        Some(offset) => offset
+            .from_local_datetime(&naive)
+            .single()
+            .map(|time| time.with_timezone(&Local)),
+        None => Local.from_local_datetime(&naive).earliest(),
+    }
+}
+
+fn split_tags(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// ==================== JSON ====================
+
+/// 取出记录数组：顶层数组，或 Toggl 报表的 `data`、Clockify 报表的 `timeentries`
+fn json_items(value: &Value) -> Option<&Vec<Value>> {
+    match value {
+        Value::Array(items) => Some(items),
+        Value::Object(map) => ["data", "timeentries", "timeEntries", "time_entries"]
+            .iter()
+            .find_map(|key| map.get(*key).and_then(Value::as_array)),
+        _ => None,
+    }
+}
+
+fn detect_json(items: &[Value]) -> Option<TrackerSource> {
+    let first = items.first()?.as_object()?;
+    if first.contains_key("timeInterval") {
+        Some(TrackerSource::Clockify)
+    } else if first.contains_key("start")
+        && (first.contains_key("dur") || first.contains_key("duration"))
+        || first.contains_key("time_entries")
+    {
+        Some(TrackerSource::Toggl)
+    } else {
+        None
+    }
+}
+
+fn parse_json(value: &Value) -> Option<ParsedExport> {
+    let items = json_items(value)?;
+    let source = detect_json(items)?;
+    let mut parsed = ParsedExport::new(source);
+
+    for (index, item) in items.iter().enumerate() {
+        let record = format!("#{}", index + 1);
+        // Toggl 报表 v3 把同一描述的多条记录分组在 time_entries 中
+        let children: Vec<&Value> = match item.get("time_entries").and_then(Value::as_array) {
+            Some(children) => children.iter().collect(),
+            None => vec![item],
+        };
+
+        for child in children {
+            let result = match source {
+                TrackerSource::Toggl => parse_toggl_json(item, child),
+                TrackerSource::Clockify => parse_clockify_json(item),
+            };
+            match result {
+                Ok(Some(entry)) => parsed.entries.push(entry),
+                Ok(None) => parsed
+                    .warnings
+                    .push(format!("跳过正在计时的记录 {}", record)),
+                Err(error) => parsed.errors.push(error.with_data(record.clone())),
+            }
+        }
+    }
+    Some(parsed)
+}
+
+fn text(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .find(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+fn timestamp(value: &Value, keys: &[&str]) -> Result<Option<DateTime<Local>>, ImportError> {
+    let Some(text) = text(value, keys) else {
+        return Ok(None);
+    };
+    DateTime::parse_from_rfc3339(&text)
+        .map(|time| Some(time.with_timezone(&Local)))
+        .map_err(|_| {
+            ImportError::new(format!("无法解析时间: {}", text)).with_field(keys[0].to_string())
+        })
+}
+
+/// 标签：字符串数组或带 `name` 的对象数组
+fn json_tags(value: &Value, keys: &[&str]) -> Vec<String> {
+    keys.iter()
+        .filter_map(|key| value.get(*key).and_then(Value::as_array))
+        .flatten()
+        .filter_map(|tag| {
+            tag.as_str()
+                .or_else(|| tag.get("name").and_then(Value::as_str))
+                .map(str::trim)
+        })
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 解析 Toggl 记录；`group` 为报表分组（v3）或记录本身
+fn parse_toggl_json(group: &Value, item: &Value) -> Result<Option<TrackerEntry>, ImportError> {
+    let start = timestamp(item, &["start"])?
+        .ok_or_else(|| ImportError::new("缺少开始时间".to_string()).with_field("start".into()))?;
+
+    // 时长：报表 v2 的 dur 为毫秒，API 和报表 v3 为秒，负数表示正在计时
+    let seconds = item
+        .get("dur")
+        .and_then(Value::as_i64)
+        .map(|ms| ms / 1000)
+        .or_else(|| item.get("duration").and_then(Value::as_i64))
+        .or_else(|| item.get("seconds").and_then(Value::as_i64));
+    if seconds.is_some_and(|seconds| seconds < 0) {
+        return Ok(None);
+    }
+
+    let end = match timestamp(item, &["end", "stop"])? {
+        Some(end) => end,
+        None => match seconds {
+            Some(seconds) => start + Duration::seconds(seconds),
+            None => return Ok(None),
+        },
+    };
+
+    Ok(Some(TrackerEntry {
+        client: text(group, &["client", "client_name"]),
+        project: text(group, &["project", "project_name"]),
+        project_color: text(group, &["project_hex_color", "project_color", "hex_color"]),
+        task: text(group, &["task", "task_name"]),
+        description: text(group, &["description"]).unwrap_or_default(),
+        start,
+        end,
+        tags: json_tags(group, &["tags", "tag_names"]),
+        line: None,
+    }))
+}
+
+fn parse_clockify_json(item: &Value) -> Result<Option<TrackerEntry>, ImportError> {
+    let interval = item.get("timeInterval").unwrap_or(&Value::Null);
+    let start = timestamp(interval, &["start"])?.ok_or_else(|| {
+        ImportError::new("缺少开始时间".to_string()).with_field("timeInterval.start".into())
+    })?;
+
+    let end = match timestamp(interval, &["end"])? {
+        Some(end) => end,
+        None => {
+            // 报表中的时长为秒数，API 中为 ISO 8601 时长
+            let duration = match interval.get("duration") {
+                Some(Value::Number(seconds)) => seconds.as_i64().map(Duration::seconds),
+                Some(Value::String(text)) => parse_iso_duration(text),
+                _ => None,
+            };
+            match duration {
+                Some(duration) => start + duration,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    Ok(Some(TrackerEntry {
+        client: text(item, &["clientName"]),
+        project: text(item, &["projectName"]),
+        project_color: text(item, &["projectColor"]),
+        task: text(item, &["taskName"]),
+        description: text(item, &["description"]).unwrap_or_default(),
+        start,
+        end,
+        tags: json_tags(item, &["tags", "tagNames"]),
+        line: None,
+    }))
+}
+
+/// 解析 `PT1H30M15S` 格式的时长
+fn parse_iso_duration(text: &str) -> Option<Duration> {
+    let rest = text.strip_prefix("PT")?;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for ch in rest.chars() {
+        match ch {
+            '0'..='9' | '.' => number.push(ch),
+            'H' | 'M' | 'S' => {
+                let value: f64 = number.parse().ok()?;
+                seconds += value
+                    * match ch {
+                        'H' => 3600.0,
+                        'M' => 60.0,
+                        _ => 1.0,
+                    };
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    number
+        .is_empty()
+        .then(|| Duration::seconds(seconds.round() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_parse_toggl_csv() {
+        let csv = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount (USD)\n\
+                   张三,z@example.com,Acme,Website,,首页设计,Yes,2024-01-15,23:30:00,2024-01-16,00:45:00,01:15:00,\"设计, 客户\",\n\
+                   张三,z@example.com,,,,,No,2024-01-16,bad,2024-01-16,10:00:00,01:00:00,,\n";
+        let offset = FixedOffset::east_opt(8 * 3600);
+        let parsed = parse(csv, offset).unwrap();
+
+        assert_eq!(parsed.source, TrackerSource::Toggl);
+        assert_eq!(parsed.entries.len(), 1);
+        let entry = &parsed.entries[0];
+        assert_eq!(entry.client.as_deref(), Some("Acme"));
+        assert_eq!(entry.project.as_deref(), Some("Website"));
+        assert_eq!(entry.tags, vec!["设计", "客户"]);
+        assert_eq!(
+            entry.end.signed_duration_since(entry.start).num_minutes(),
+            75
+        );
+        assert_eq!(
+            entry.start.with_timezone(&offset.unwrap()).hour(),
+            23,
+            "CSV 时间按指定时区解释"
+        );
+
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(parsed.errors[0].line_number, Some(3));
+    }
+
+    #[test]
+    fn test_parse_clockify_csv_day_first() {
+        let csv = "Project,Client,Description,Task,User,Group,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+                   Docs,,写文档,,李四,,l@example.com,,No,25/01/2024,09:00:00 AM,25/01/2024,10:30:00 AM,01:30:00,1.50\n";
+        let parsed = parse(csv, None).unwrap();
+
+        assert_eq!(parsed.source, TrackerSource::Clockify);
+        let entry = &parsed.entries[0];
+        assert_eq!(
+            entry.start.date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 25).unwrap()
+        );
+        assert_eq!(
+            entry.end.signed_duration_since(entry.start).num_minutes(),
+            90
+        );
+    }
+
+    #[test]
+    fn test_parse_json_exports() {
+        let toggl = r##"{"data": [
+            {"description": "开会", "start": "2024-01-15T09:00:00+01:00", "end": "2024-01-15T10:00:00+01:00",
+             "dur": 3600000, "project": "Internal", "client": null, "project_hex_color": "#2196F3", "tags": ["会议"]},
+            {"description": "计时中", "start": "2024-01-15T11:00:00Z", "duration": -1705316400}
+        ]}"##;
+        let parsed = parse(toggl, None).unwrap();
+        assert_eq!(parsed.source, TrackerSource::Toggl);
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.warnings.len(), 1);
+        assert_eq!(parsed.entries[0].project_color.as_deref(), Some("#2196F3"));
+
+        let clockify = r#"[{"description": "编码", "projectName": "App", "clientName": "Acme",
+            "tags": [{"name": "dev"}],
+            "timeInterval": {"start": "2024-01-15T09:00:00Z", "end": null, "duration": "PT1H30M"}}]"#;
+        let parsed = parse(clockify, None).unwrap();
+        assert_eq!(parsed.source, TrackerSource::Clockify);
+        let entry = &parsed.entries[0];
+        assert_eq!(entry.tags, vec!["dev"]);
+        assert_eq!(
+            entry.end.signed_duration_since(entry.start).num_minutes(),
+            90
+        );
+
+        assert!(parse(r#"[{"id": 1}]"#, None).is_none());
+        assert!(parse("任务名称,分类,开始时间\n", None).is_none());
+    }
+}
@@ -0,0 +1,1874 @@
+//! # 纯文本记账互通模块
+//!
+//! 在记账数据与 Beancount / Ledger 日记账之间转换：
+//! - 账户按类型映射为 `Assets:Bank:招商银行`、`Liabilities:CreditCard:信用卡` 等
+//! - 交易分类树映射为 `Expenses:Food:Groceries`、`Income:Salary`
+//! - 期初余额记为与 `Equity:Opening-Balances` 之间的交易
+//! - 拆分交易（一笔付款对应多个分类）的各部分共享 `split-xxxxxxxx` 标签，
+//!   导出时合并为一笔多过账交易，Beancount 中写为链接 `^split-xxxxxxxx`
+
+use crate::storage::accounting_models::{
+    Account, AccountType, Transaction, TransactionCategory, TransactionStatus, TransactionType,
+};
+use crate::utils::import::ImportError;
+use anyhow::Result;
+use chrono::{Local, NaiveDate};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+use uuid::Uuid;
+
+/// 期初余额对应的权益账户
+pub const OPENING_BALANCE_ACCOUNT: &str = "Equity:Opening-Balances";
+
+/// 拆分交易标签前缀
+pub const SPLIT_TAG_PREFIX: &str = "split-";
+
+const UNCATEGORIZED: &str = "Uncategorized";
+const UNKNOWN_ACCOUNT: &str = "Equity:Unknown";
+const DEFAULT_CATEGORY_COLOR: &str = "#9E9E9E";
+const OPENING_BALANCE_DESCRIPTION: &str = "期初余额";
+
+/// 日记账方言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerDialect {
+    Beancount,
+    Ledger,
+}
+
+impl LedgerDialect {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "beancount" | "bean" => Some(Self::Beancount),
+            "ledger" | "journal" | "dat" => Some(Self::Ledger),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Beancount => "beancount",
+            Self::Ledger => "ledger",
+        }
+    }
+
+    /// 根据内容识别方言，无法确定时按 Ledger 处理
+    pub fn detect(content: &str) -> Self {
+        let is_beancount = content.lines().any(|line| {
+            if line.starts_with("option \"") || line.starts_with("plugin \"") {
+                return true;
+            }
+            let Some((_, rest)) = parse_date_prefix(line) else {
+                return false;
+            };
+            let mut tokens = rest.split_whitespace();
+            match tokens.next() {
+                Some("open" | "close" | "txn" | "balance" | "pad") => true,
+                Some("*" | "!") => tokens.next().is_some_and(|token| token.starts_with('"')),
+                _ => false,
+            }
+        });
+        if is_beancount {
+            Self::Beancount
+        } else {
+            Self::Ledger
+        }
+    }
+}
+
+/// 一组记账数据
+#[derive(Debug, Clone, Default)]
+pub struct LedgerBook {
+    pub accounts: Vec<Account>,
+    pub categories: Vec<TransactionCategory>,
+    pub transactions: Vec<Transaction>,
+}
+
+// ==================== 账户名称 ====================
+
+/// 把名称转换为合法的账户名组成部分：非字母数字字符替换为 `-`，首字母大写
+fn account_component(name: &str) -> String {
+    let mut component = String::new();
+    for ch in name.trim().chars() {
+        if ch.is_alphanumeric() {
+            component.push(ch);
+        } else if !component.is_empty() && !component.ends_with('-') {
+            component.push('-');
+        }
+    }
+    let component = component.trim_end_matches('-');
+
+    let mut chars = component.chars();
+    match chars.next() {
+        None => "X".to_string(),
+        Some(first) if first.is_ascii_lowercase() => {
+            first.to_ascii_uppercase().to_string() + chars.as_str()
+        }
+        Some(first)
+            if first.is_ascii_uppercase() || first.is_ascii_digit() || !first.is_ascii() =>
+        {
+            component.to_string()
+        }
+        Some(_) => format!("X{}", component),
+    }
+}
+
+fn account_root(account_type: AccountType) -> &'static str {
+    match account_type {
+        AccountType::Cash => "Assets:Cash",
+        AccountType::Bank => "Assets:Bank",
+        AccountType::CreditCard => "Liabilities:CreditCard",
+        AccountType::Investment => "Assets:Investment",
+        AccountType::Other => "Assets:Other",
+    }
+}
+
+/// 根据账户路径推断账户类型
+fn infer_account_type(path: &str) -> AccountType {
+    let components: Vec<String> = path.split(':').map(str::to_lowercase).collect();
+    let has = |names: &[&str]| components.iter().any(|c| names.contains(&c.as_str()));
+    if components.first().is_some_and(|root| root == "liabilities") {
+        AccountType::CreditCard
+    } else if has(&["cash", "wallet"]) {
+        AccountType::Cash
+    } else if has(&["bank", "checking", "savings"]) {
+        AccountType::Bank
+    } else if has(&["investment", "investments", "brokerage", "stocks"]) {
+        AccountType::Investment
+    } else {
+        AccountType::Other
+    }
+}
+
+/// 账户路径的顶级类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AccountRoot {
+    Account,
+    Expense,
+    Income,
+    Equity,
+}
+
+impl AccountRoot {
+    fn of(path: &str) -> Option<Self> {
+        let root = path.split(':').next()?.to_lowercase();
+        match root.as_str() {
+            "assets" | "liabilities" => Some(Self::Account),
+            "expenses" | "expense" => Some(Self::Expense),
+            "income" | "revenue" | "revenues" => Some(Self::Income),
+            "equity" => Some(Self::Equity),
+            _ => None,
+        }
+    }
+}
+
+/// 导出时分配的账户路径
+#[derive(Default)]
+struct AccountPaths {
+    paths: HashMap<Uuid, String>,
+    used: HashSet<String>,
+}
+
+impl AccountPaths {
+    /// 分配不重复的路径，重名时追加序号
+    fn assign(&mut self, id: Uuid, base: String) -> String {
+        let mut path = base.clone();
+        let mut index = 2;
+        while !self.used.insert(path.clone()) {
+            path = format!("{}-{}", base, index);
+            index += 1;
+        }
+        self.paths.insert(id, path.clone());
+        path
+    }
+
+    /// 交易分类路径，父分类类型不同或层级过深时挂到根上
+    fn category(
+        &mut self,
+        id: Uuid,
+        categories: &HashMap<Uuid, &TransactionCategory>,
+        depth: usize,
+    ) -> Option<String> {
+        if let Some(path) = self.paths.get(&id) {
+            return Some(path.clone());
+        }
+        let category = categories.get(&id)?;
+        let root = match category.transaction_type {
+            TransactionType::Expense => "Expenses",
+            TransactionType::Income => "Income",
+            TransactionType::Transfer => return None,
+        };
+        let prefix = category
+            .parent_id
+            .filter(|_| depth < 16)
+            .and_then(|parent_id| self.category(parent_id, categories, depth + 1))
+            .filter(|parent| parent.split(':').next() == Some(root))
+            .unwrap_or_else(|| root.to_string());
+        let component = account_component(&category.name);
+        Some(self.assign(id, format!("{}:{}", prefix, component)))
+    }
+}
+
+// ==================== 导出 ====================
+
+/// 导出的一笔日记账交易，资金账户过账总是最后一条
+struct JournalEntry {
+    date: NaiveDate,
+    flag: char,
+    description: String,
+    tags: Vec<String>,
+    split: Option<String>,
+    postings: Vec<(String, f64, String)>,
+}
+
+/// Beancount / Ledger 导出器
+pub struct LedgerExporter<'a> {
+    book: &'a LedgerBook,
+    dialect: LedgerDialect,
+}
+
+impl<'a> LedgerExporter<'a> {
+    pub fn new(book: &'a LedgerBook, dialect: LedgerDialect) -> Self {
+        Self { book, dialect }
+    }
+
+    /// 导出到文件
+    pub fn export_to_file<P: AsRef<Path>>(&self, file_path: P) -> Result<()> {
+        std::fs::write(file_path, self.export())?;
+        Ok(())
+    }
+
+    /// 生成日记账文本，已取消的交易不导出
+    pub fn export(&self) -> String {
+        let mut paths = AccountPaths::default();
+        for reserved in [
+            OPENING_BALANCE_ACCOUNT,
+            UNKNOWN_ACCOUNT,
+            "Expenses:Uncategorized",
+            "Income:Uncategorized",
+        ] {
+            paths.used.insert(reserved.to_string());
+        }
+
+        let mut accounts: Vec<&Account> = self.book.accounts.iter().collect();
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        for account in &accounts {
+            let base = format!(
+                "{}:{}",
+                account_root(account.account_type),
+                account_component(&account.name)
+            );
+            paths.assign(account.id, base);
+        }
+
+        let categories: HashMap<Uuid, &TransactionCategory> = self
+            .book
+            .categories
+            .iter()
+            .map(|category| (category.id, category))
+            .collect();
+        for category in &self.book.categories {
+            paths.category(category.id, &categories, 0);
+        }
+
+        let mut transactions: Vec<&Transaction> = self
+            .book
+            .transactions
+            .iter()
+            .filter(|t| t.status != TransactionStatus::Cancelled)
+            .collect();
+        transactions.sort_by_key(|t| (t.transaction_date, t.created_at));
+
+        let open_date = accounts
+            .iter()
+            .map(|a| a.created_at.date_naive())
+            .chain(
+                self.book
+                    .categories
+                    .iter()
+                    .map(|c| c.created_at.date_naive()),
+            )
+            .chain(transactions.iter().map(|t| t.transaction_date))
+            .min()
+            .unwrap_or_else(|| Local::now().date_naive());
+
+        let mut entries = Vec::new();
+        for account in &accounts {
+            if account.initial_balance.abs() >= 0.005 {
+                entries.push(JournalEntry {
+                    date: open_date,
+                    flag: '*',
+                    description: OPENING_BALANCE_DESCRIPTION.to_string(),
+                    tags: Vec::new(),
+                    split: None,
+                    postings: vec![
+                        (
+                            OPENING_BALANCE_ACCOUNT.to_string(),
+                            -account.initial_balance,
+                            account.currency.clone(),
+                        ),
+                        (
+                            paths.paths[&account.id].clone(),
+                            account.initial_balance,
+                            account.currency.clone(),
+                        ),
+                    ],
+                });
+            }
+        }
+
+        let mut split_groups: HashMap<(String, NaiveDate, Uuid, TransactionType), usize> =
+            HashMap::new();
+        for transaction in transactions {
+            let account = paths
+                .paths
+                .get(&transaction.account_id)
+                .cloned()
+                .unwrap_or_else(|| UNKNOWN_ACCOUNT.to_string());
+            let amount = transaction.amount;
+            let (counter, counter_amount, funding_amount) = match transaction.transaction_type {
+                TransactionType::Expense => (
+                    transaction
+                        .category_id
+                        .and_then(|id| paths.paths.get(&id).cloned())
+                        .unwrap_or_else(|| "Expenses:Uncategorized".to_string()),
+                    amount,
+                    -amount,
+                ),
+                TransactionType::Income => (
+                    transaction
+                        .category_id
+                        .and_then(|id| paths.paths.get(&id).cloned())
+                        .unwrap_or_else(|| "Income:Uncategorized".to_string()),
+                    -amount,
+                    amount,
+                ),
+                TransactionType::Transfer => (
+                    transaction
+                        .to_account_id
+                        .and_then(|id| paths.paths.get(&id).cloned())
+                        .unwrap_or_else(|| UNKNOWN_ACCOUNT.to_string()),
+                    amount,
+                    -amount,
+                ),
+            };
+            let currency = transaction.currency.clone();
+
+            let split = transaction
+                .tags
+                .iter()
+                .find(|tag| tag.starts_with(SPLIT_TAG_PREFIX))
+                .cloned();
+            let group_key = split.clone().map(|split| {
+                (
+                    split,
+                    transaction.transaction_date,
+                    transaction.account_id,
+                    transaction.transaction_type,
+                )
+            });
+            if let Some(&index) = group_key.as_ref().and_then(|key| split_groups.get(key)) {
+                let entry: &mut JournalEntry = &mut entries[index];
+                let funding = entry.postings.len() - 1;
+                entry.postings[funding].1 += funding_amount;
+                entry
+                    .postings
+                    .insert(funding, (counter, counter_amount, currency));
+                continue;
+            }
+
+            if let Some(key) = group_key {
+                split_groups.insert(key, entries.len());
+            }
+            entries.push(JournalEntry {
+                date: transaction.transaction_date,
+                flag: match transaction.status {
+                    TransactionStatus::Pending => '!',
+                    _ => '*',
+                },
+                description: transaction.description.clone(),
+                tags: transaction
+                    .tags
+                    .iter()
+                    .filter(|tag| !tag.starts_with(SPLIT_TAG_PREFIX))
+                    .cloned()
+                    .collect(),
+                split,
+                postings: vec![
+                    (counter, counter_amount, currency.clone()),
+                    (account, funding_amount, currency),
+                ],
+            });
+        }
+
+        // 每个账户使用过的货币，写入开户指令的货币约束
+        let mut currencies: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for account in &accounts {
+            currencies
+                .entry(paths.paths[&account.id].clone())
+                .or_default()
+                .insert(account.currency.clone());
+        }
+        let mut used: BTreeSet<String> = BTreeSet::new();
+        for entry in &entries {
+            for (path, _, currency) in &entry.postings {
+                used.insert(path.clone());
+                if let Some(set) = currencies.get_mut(path) {
+                    set.insert(currency.clone());
+                }
+            }
+        }
+
+        let mut opens: Vec<(String, String)> = Vec::new();
+        for account in &accounts {
+            opens.push((paths.paths[&account.id].clone(), account.name.clone()));
+        }
+        for category in &self.book.categories {
+            if let Some(path) = paths.paths.get(&category.id) {
+                opens.push((path.clone(), category.name.clone()));
+            }
+        }
+        opens.sort();
+        for reserved in [
+            OPENING_BALANCE_ACCOUNT,
+            UNKNOWN_ACCOUNT,
+            "Expenses:Uncategorized",
+            "Income:Uncategorized",
+        ] {
+            if used.contains(reserved) {
+                opens.push((reserved.to_string(), String::new()));
+            }
+        }
+
+        let width = opens
+            .iter()
+            .map(|(path, _)| path.chars().count())
+            .max()
+            .unwrap_or(0)
+            + 2;
+
+        let mut output = String::new();
+        match self.dialect {
+            LedgerDialect::Beancount => {
+                let operating = accounts
+                    .iter()
+                    .find(|a| a.is_default)
+                    .or(accounts.first())
+                    .map(|a| a.currency.as_str())
+                    .unwrap_or("CNY");
+                let _ = writeln!(output, "option \"title\" \"LifeTracker\"");
+                let _ = writeln!(output, "option \"operating_currency\" \"{}\"", operating);
+            }
+            LedgerDialect::Ledger => {
+                let _ = writeln!(output, "; LifeTracker 导出");
+            }
+        }
+        output.push('\n');
+
+        for (path, name) in &opens {
+            let needs_name = !name.is_empty() && path.rsplit(':').next() != Some(name.as_str());
+            match self.dialect {
+                LedgerDialect::Beancount => {
+                    let currencies = currencies
+                        .get(path)
+                        .map(|set| set.iter().cloned().collect::<Vec<_>>().join(","))
+                        .unwrap_or_default();
+                    let _ = writeln!(
+                        output,
+                        "{} open {}{}{}",
+                        open_date,
+                        path,
+                        if currencies.is_empty() { "" } else { " " },
+                        currencies
+                    );
+                    if needs_name {
+                        let _ = writeln!(output, "  name: \"{}\"", escape_string(name));
+                    }
+                }
+                LedgerDialect::Ledger => {
+                    let _ = writeln!(output, "account {}", path);
+                    if needs_name {
+                        let _ = writeln!(output, "    ; name: {}", single_line(name));
+                    }
+                }
+            }
+        }
+
+        for entry in &entries {
+            output.push('\n');
+            self.write_entry(&mut output, entry, width);
+        }
+
+        output
+    }
+
+    fn write_entry(&self, output: &mut String, entry: &JournalEntry, width: usize) {
+        match self.dialect {
+            LedgerDialect::Beancount => {
+                let (valid, invalid): (Vec<&String>, Vec<&String>) =
+                    entry.tags.iter().partition(|tag| is_beancount_tag(tag));
+                let _ = write!(
+                    output,
+                    "{} {} \"{}\"",
+                    entry.date,
+                    entry.flag,
+                    escape_string(&entry.description)
+                );
+                for tag in valid {
+                    let _ = write!(output, " #{}", tag);
+                }
+                if let Some(split) = &entry.split {
+                    let _ = write!(output, " ^{}", split);
+                }
+                output.push('\n');
+                if !invalid.is_empty() {
+                    let tags: Vec<&str> = invalid.iter().map(|tag| tag.as_str()).collect();
+                    let _ = writeln!(output, "  tags: \"{}\"", escape_string(&tags.join(",")));
+                }
+                for (path, amount, currency) in &entry.postings {
+                    let _ = writeln!(
+                        output,
+                        "  {:<width$}{:>12} {}",
+                        path,
+                        format_amount(*amount),
+                        currency
+                    );
+                }
+            }
+            LedgerDialect::Ledger => {
+                let _ = writeln!(
+                    output,
+                    "{} {} {}",
+                    entry.date.format("%Y/%m/%d"),
+                    entry.flag,
+                    single_line(&entry.description)
+                );
+                let tags: Vec<String> = entry
+                    .tags
+                    .iter()
+                    .chain(entry.split.iter())
+                    .map(|tag| {
+                        tag.chars()
+                            .map(|c| {
+                                if c == ':' || c.is_whitespace() {
+                                    '-'
+                                } else {
+                                    c
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+                if !tags.is_empty() {
+                    let _ = writeln!(output, "    ; :{}:", tags.join(":"));
+                }
+                for (path, amount, currency) in &entry.postings {
+                    let _ = writeln!(
+                        output,
+                        "    {:<width$}{:>12} {}",
+                        path,
+                        format_amount(*amount),
+                        currency
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn format_amount(amount: f64) -> String {
+    if amount.abs() < 0.005 {
+        "0.00".to_string()
+    } else {
+        format!("{:.2}", amount)
+    }
+}
+
+fn is_beancount_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '.'))
+}
+
+fn escape_string(text: &str) -> String {
+    single_line(text).replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn single_line(text: &str) -> String {
+    text.split(['\r', '\n'])
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ==================== 解析 ====================
+
+/// 解析出的过账
+struct RawPosting {
+    account: String,
+    amount: Option<(f64, String)>,
+}
+
+/// 解析出的交易
+struct RawEntry {
+    line: usize,
+    date: NaiveDate,
+    flag: Option<char>,
+    description: String,
+    tags: Vec<String>,
+    meta: HashMap<String, String>,
+    postings: Vec<RawPosting>,
+}
+
+/// 解析出的开户指令（Beancount `open` 或 Ledger `account`）
+struct RawOpen {
+    account: String,
+    currencies: Vec<String>,
+    meta: HashMap<String, String>,
+}
+
+/// 解析行首日期，返回日期和剩余内容
+fn parse_date_prefix(line: &str) -> Option<(NaiveDate, &str)> {
+    let token = line.split_whitespace().next()?;
+    if !token.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    // Ledger 的辅助日期 `2024/01/15=2024/01/20`
+    let date_text = token.split('=').next()?;
+    let date = ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date_text, format).ok())?;
+    Some((date, line[token.len()..].trim_start()))
+}
+
+/// 解析元数据行 `key: value`
+fn parse_meta(text: &str) -> Option<(String, String)> {
+    let (key, value) = text.split_once(':')?;
+    let valid_key = key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_key || !value.starts_with([' ', '\t']) {
+        return None;
+    }
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .map(unescape_string)
+        .unwrap_or_else(|| value.to_string());
+    Some((key.to_string(), value))
+}
+
+fn unescape_string(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// 读取 Beancount 交易行中的字符串，返回字符串和剩余内容
+fn parse_quoted(text: &str) -> Option<(String, &str)> {
+    let body = text.strip_prefix('"')?;
+    let mut escaped = false;
+    for (index, ch) in body.char_indices() {
+        match ch {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => {
+                return Some((unescape_string(&body[..index]), &body[index + 1..]));
+            }
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// 去掉行尾注释（Beancount 字符串中的 `;` 不算注释）
+fn strip_comment(text: &str) -> (&str, Option<&str>) {
+    let mut in_string = false;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            ';' if !in_string => return (text[..index].trim_end(), Some(&text[index + 1..])),
+            _ => {}
+        }
+    }
+    (text, None)
+}
+
+/// 解析 Ledger 注释中的 `:tag1:tag2:` 标签或 `key: value` 元数据
+fn parse_ledger_comment(comment: &str, tags: &mut Vec<String>, meta: &mut HashMap<String, String>) {
+    let comment = comment.trim();
+    if comment.starts_with(':') && comment.ends_with(':') && comment.len() > 1 {
+        tags.extend(
+            comment
+                .split(':')
+                .filter(|tag| !tag.trim().is_empty())
+                .map(|tag| tag.trim().to_string()),
+        );
+    } else if let Some((key, value)) = parse_meta(comment) {
+        meta.insert(key, value);
+    }
+}
+
+/// 解析金额，支持 `12.50 CNY`、`CNY 12.50`、`$12.50`、`-¥12.50` 和不带货币的数字
+fn parse_amount(text: &str, default_currency: &str) -> Option<(f64, String)> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let is_number = |token: &str| {
+        token
+            .trim_start_matches(['-', '+'])
+            .starts_with(|c: char| c.is_ascii_digit() || c == '.')
+    };
+    let (number, commodity) = match tokens.as_slice() {
+        [single] => split_commodity(single)?,
+        [a, b] if is_number(a) => (a.to_string(), b.to_string()),
+        [a, b] => (b.to_string(), a.to_string()),
+        _ => return None,
+    };
+    let value: f64 = number.replace(',', "").parse().ok()?;
+    let commodity = commodity.trim_matches('"');
+    let currency = match commodity {
+        "" => default_currency,
+        "$" => "USD",
+        "¥" | "￥" => "CNY",
+        "€" => "EUR",
+        "£" => "GBP",
+        other => other,
+    };
+    Some((value, currency.to_string()))
+}
+
+/// 拆分紧贴在数字前后的货币符号
+fn split_commodity(token: &str) -> Option<(String, String)> {
+    let negative = token.starts_with('-');
+    let body = token.trim_start_matches('-');
+    let start = body.find(|c: char| c.is_ascii_digit() || c == '-' || c == '.')?;
+    let (prefix, rest) = body.split_at(start);
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-')))
+        .unwrap_or(rest.len());
+    let (number, suffix) = rest.split_at(end);
+    let number = if negative {
+        format!("-{}", number)
+    } else {
+        number.to_string()
+    };
+    let commodity = if prefix.is_empty() { suffix } else { prefix };
+    Some((number, commodity.to_string()))
+}
+
+// ==================== 导入 ====================
+
+/// 日记账导入选项
+#[derive(Debug, Clone)]
+pub struct LedgerImportOptions {
+    /// 为空时根据内容识别
+    pub dialect: Option<LedgerDialect>,
+    /// 金额未写货币时使用的货币
+    pub default_currency: String,
+}
+
+impl Default for LedgerImportOptions {
+    fn default() -> Self {
+        Self {
+            dialect: None,
+            default_currency: "CNY".to_string(),
+        }
+    }
+}
+
+/// 日记账导入结果，`book` 中只包含需要新建的记录
+#[derive(Debug, Clone, Default)]
+pub struct LedgerImportResult {
+    pub book: LedgerBook,
+    pub errors: Vec<ImportError>,
+    pub warnings: Vec<String>,
+}
+
+impl LedgerImportResult {
+    pub fn success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// 过账解析后的去向
+enum PostingTarget {
+    Account(Uuid),
+    Category(TransactionType, Option<Uuid>),
+    Equity,
+}
+
+/// Beancount / Ledger 导入器
+pub struct LedgerImporter {
+    options: LedgerImportOptions,
+}
+
+impl LedgerImporter {
+    pub fn new(options: LedgerImportOptions) -> Self {
+        Self { options }
+    }
+
+    /// 从文件导入，未指定方言时先按扩展名、再按内容识别
+    pub fn import_from_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        existing: &LedgerBook,
+    ) -> Result<LedgerImportResult> {
+        let file_path = file_path.as_ref();
+        let content = std::fs::read_to_string(file_path)?;
+        let dialect = self.options.dialect.or_else(|| {
+            file_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(LedgerDialect::from_extension)
+        });
+        Ok(self.import_with_dialect(&content, existing, dialect))
+    }
+
+    /// 导入日记账文本，账户按名称、分类按名称和层级与现有记录合并
+    pub fn import_str(&self, content: &str, existing: &LedgerBook) -> LedgerImportResult {
+        self.import_with_dialect(content, existing, self.options.dialect)
+    }
+
+    fn import_with_dialect(
+        &self,
+        content: &str,
+        existing: &LedgerBook,
+        dialect: Option<LedgerDialect>,
+    ) -> LedgerImportResult {
+        let content = content.trim_start_matches('\u{feff}');
+        let dialect = dialect.unwrap_or_else(|| LedgerDialect::detect(content));
+        let mut result = LedgerImportResult::default();
+        let (opens, entries) = self.parse(content, dialect, &mut result);
+
+        let mut resolver = Resolver {
+            existing,
+            default_currency: &self.options.default_currency,
+            names: HashMap::new(),
+            currencies: HashMap::new(),
+            paths: HashMap::new(),
+            result,
+        };
+        for open in &opens {
+            if let Some(name) = open.meta.get("name") {
+                resolver.names.insert(open.account.clone(), name.clone());
+            }
+            if let Some(currency) = open.currencies.first() {
+                resolver
+                    .currencies
+                    .insert(open.account.clone(), currency.clone());
+            }
+        }
+        // 没有交易的账户和分类同样导入
+        for open in &opens {
+            if open.account == UNKNOWN_ACCOUNT
+                || open.account.rsplit(':').next() == Some(UNCATEGORIZED)
+            {
+                continue;
+            }
+            let _ = resolver.resolve(&open.account);
+        }
+        for entry in entries {
+            if let Err(error) = resolver.import_entry(entry) {
+                resolver.result.errors.push(error);
+            }
+        }
+        resolver.result
+    }
+
+    /// 逐行解析为开户指令和交易
+    fn parse(
+        &self,
+        content: &str,
+        dialect: LedgerDialect,
+        result: &mut LedgerImportResult,
+    ) -> (Vec<RawOpen>, Vec<RawEntry>) {
+        enum Block {
+            None,
+            Entry,
+            Open,
+        }
+
+        let mut opens: Vec<RawOpen> = Vec::new();
+        let mut entries: Vec<RawEntry> = Vec::new();
+        let mut block = Block::None;
+
+        for (index, line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim_end();
+            if line.trim().is_empty() {
+                block = Block::None;
+                continue;
+            }
+
+            if line.starts_with([' ', '\t']) {
+                let text = line.trim();
+                match block {
+                    Block::Entry => {
+                        let entry = entries.last_mut().expect("交易块必有交易");
+                        if let Err(error) = self.parse_entry_line(entry, text, dialect) {
+                            result.errors.push(error.with_line(line_number));
+                        }
+                    }
+                    Block::Open => {
+                        let open = opens.last_mut().expect("开户块必有开户指令");
+                        let text = text.strip_prefix(';').unwrap_or(text).trim();
+                        if let Some((key, value)) = parse_meta(text) {
+                            open.meta.insert(key, value);
+                        }
+                    }
+                    Block::None => {}
+                }
+                continue;
+            }
+
+            block = Block::None;
+            if line.starts_with([';', '#', '%', '|', '*']) {
+                continue;
+            }
+            if let Some(account) = line.strip_prefix("account ") {
+                opens.push(RawOpen {
+                    account: strip_comment(account).0.trim().to_string(),
+                    currencies: Vec::new(),
+                    meta: HashMap::new(),
+                });
+                block = Block::Open;
+                continue;
+            }
+            let Some((date, rest)) = parse_date_prefix(line) else {
+                continue;
+            };
+
+            let mut tokens = rest.split_whitespace();
+            match tokens.next() {
+                Some("open") => {
+                    let Some(account) = tokens.next() else {
+                        result.errors.push(
+                            ImportError::new("开户指令缺少账户".to_string()).with_line(line_number),
+                        );
+                        continue;
+                    };
+                    let currencies = tokens
+                        .next()
+                        .filter(|token| !token.starts_with(';'))
+                        .map(|token| token.split(',').map(str::to_string).collect())
+                        .unwrap_or_default();
+                    opens.push(RawOpen {
+                        account: account.to_string(),
+                        currencies,
+                        meta: HashMap::new(),
+                    });
+                    block = Block::Open;
+                }
+                Some("pad") => result.warnings.push(format!(
+                    "第 {} 行: 不支持 pad 指令，请改用期初余额交易",
+                    line_number
+                )),
+                Some(
+                    "close" | "balance" | "price" | "note" | "document" | "event" | "commodity"
+                    | "custom" | "query",
+                ) => {}
+                _ => match self.parse_header(date, rest, line_number) {
+                    Ok(entry) => {
+                        entries.push(entry);
+                        block = Block::Entry;
+                    }
+                    Err(error) => result.errors.push(error.with_line(line_number)),
+                },
+            }
+        }
+
+        (opens, entries)
+    }
+
+    /// 解析交易首行
+    fn parse_header(
+        &self,
+        date: NaiveDate,
+        rest: &str,
+        line: usize,
+    ) -> std::result::Result<RawEntry, ImportError> {
+        let mut entry = RawEntry {
+            line,
+            date,
+            flag: None,
+            description: String::new(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+            postings: Vec::new(),
+        };
+
+        let mut rest = rest;
+        if let Some(after) = rest.strip_prefix("txn") {
+            entry.flag = Some('*');
+            rest = after.trim_start();
+        } else if let Some(flag) = rest.chars().next().filter(|c| matches!(c, '*' | '!')) {
+            entry.flag = Some(flag);
+            rest = rest[1..].trim_start();
+        }
+        // Ledger 的交易编号 `(123)`
+        if rest.starts_with('(') {
+            if let Some(end) = rest.find(')') {
+                rest = rest[end + 1..].trim_start();
+            }
+        }
+
+        if rest.starts_with('"') {
+            let mut strings = Vec::new();
+            while let Some((text, after)) = parse_quoted(rest) {
+                strings.push(text);
+                rest = after.trim_start();
+            }
+            if rest.starts_with('"') {
+                return Err(
+                    ImportError::new("字符串未闭合".to_string()).with_data(rest.to_string())
+                );
+            }
+            entry.description = match strings.as_slice() {
+                [payee, narration] if payee.is_empty() => narration.clone(),
+                [payee, narration] if narration.is_empty() => payee.clone(),
+                [payee, narration] => format!("{} - {}", payee, narration),
+                [narration] => narration.clone(),
+                _ => String::new(),
+            };
+            let (rest, _) = strip_comment(rest);
+            for token in rest.split_whitespace() {
+                if let Some(tag) = token.strip_prefix('#').or_else(|| token.strip_prefix('^')) {
+                    entry.tags.push(tag.to_string());
+                }
+            }
+        } else {
+            let (payee, comment) = strip_comment(rest);
+            entry.description = payee.trim().to_string();
+            if let Some(comment) = comment {
+                parse_ledger_comment(comment, &mut entry.tags, &mut entry.meta);
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// 解析交易中的缩进行：注释、元数据或过账
+    fn parse_entry_line(
+        &self,
+        entry: &mut RawEntry,
+        text: &str,
+        dialect: LedgerDialect,
+    ) -> std::result::Result<(), ImportError> {
+        if let Some(comment) = text.strip_prefix(';') {
+            parse_ledger_comment(comment, &mut entry.tags, &mut entry.meta);
+            return Ok(());
+        }
+        if let Some((key, value)) = parse_meta(text) {
+            entry.meta.insert(key, value);
+            return Ok(());
+        }
+
+        let (text, comment) = strip_comment(text);
+        if let Some(comment) = comment {
+            parse_ledger_comment(comment, &mut entry.tags, &mut entry.meta);
+        }
+        let text = text
+            .strip_prefix(['*', '!'])
+            .map(str::trim_start)
+            .unwrap_or(text);
+
+        // Beancount 账户名不含空格；Ledger 账户名与金额之间至少两个空格或制表符
+        let (account, amount_text) = match dialect {
+            LedgerDialect::Beancount => text.split_once(char::is_whitespace).unwrap_or((text, "")),
+            LedgerDialect::Ledger => {
+                let split = [text.find("  "), text.find('\t')]
+                    .into_iter()
+                    .flatten()
+                    .min();
+                match split {
+                    Some(index) => (&text[..index], &text[index..]),
+                    None => (text, ""),
+                }
+            }
+        };
+        let account = account.trim_matches(['(', ')', '[', ']']).to_string();
+
+        // 去掉成本 `{...}`、价格 `@` 和余额断言 `=`
+        let amount_text = amount_text
+            .split(['{', '@', '='])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let amount = if amount_text.is_empty() {
+            None
+        } else {
+            Some(
+                parse_amount(amount_text, &self.options.default_currency).ok_or_else(|| {
+                    ImportError::new(format!("无法解析金额: {}", amount_text))
+                        .with_field(account.clone())
+                        .with_data(amount_text.to_string())
+                })?,
+            )
+        };
+
+        entry.postings.push(RawPosting { account, amount });
+        Ok(())
+    }
+}
+
+/// 把账户路径解析为账户和分类，并把交易转换为记录
+struct Resolver<'a> {
+    existing: &'a LedgerBook,
+    default_currency: &'a str,
+    /// 开户指令中的原始名称
+    names: HashMap<String, String>,
+    /// 开户指令中的货币
+    currencies: HashMap<String, String>,
+    /// 已解析的账户/分类路径
+    paths: HashMap<String, Uuid>,
+    result: LedgerImportResult,
+}
+
+impl Resolver<'_> {
+    fn display_name(&self, path: &str) -> String {
+        self.names
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| path.rsplit(':').next().unwrap_or(path).to_string())
+    }
+
+    fn resolve(&mut self, path: &str) -> std::result::Result<PostingTarget, ImportError> {
+        let root = AccountRoot::of(path).ok_or_else(|| {
+            ImportError::new(format!("无法识别的账户: {}", path)).with_field(path.to_string())
+        })?;
+        match root {
+            AccountRoot::Equity => Ok(PostingTarget::Equity),
+            AccountRoot::Account => Ok(PostingTarget::Account(self.resolve_account(path))),
+            AccountRoot::Expense => Ok(PostingTarget::Category(
+                TransactionType::Expense,
+                self.resolve_category(path, TransactionType::Expense),
+            )),
+            AccountRoot::Income => Ok(PostingTarget::Category(
+                TransactionType::Income,
+                self.resolve_category(path, TransactionType::Income),
+            )),
+        }
+    }
+
+    fn resolve_account(&mut self, path: &str) -> Uuid {
+        if let Some(id) = self.paths.get(path) {
+            return *id;
+        }
+        let name = self.display_name(path);
+        let id = match self
+            .existing
+            .accounts
+            .iter()
+            .find(|account| account.name.trim().eq_ignore_ascii_case(name.trim()))
+        {
+            Some(account) => account.id,
+            None => {
+                let currency = self
+                    .currencies
+                    .get(path)
+                    .cloned()
+                    .unwrap_or_else(|| self.default_currency.to_string());
+                let account = Account::new(name, infer_account_type(path), currency, 0.0);
+                let id = account.id;
+                self.result.book.accounts.push(account);
+                id
+            }
+        };
+        self.paths.insert(path.to_string(), id);
+        id
+    }
+
+    /// 逐级解析分类路径，`Expenses:Uncategorized` 表示不分类
+    fn resolve_category(&mut self, path: &str, transaction_type: TransactionType) -> Option<Uuid> {
+        let components: Vec<&str> = path.split(':').collect();
+        if components.len() < 2 || components[1..] == [UNCATEGORIZED] {
+            return None;
+        }
+
+        let mut parent_id = None;
+        for depth in 2..=components.len() {
+            let prefix = components[..depth].join(":");
+            if let Some(id) = self.paths.get(&prefix) {
+                parent_id = Some(*id);
+                continue;
+            }
+            let name = self.display_name(&prefix);
+            let found = self
+                .existing
+                .categories
+                .iter()
+                .chain(self.result.book.categories.iter())
+                .find(|category| {
+                    category.transaction_type == transaction_type
+                        && category.parent_id == parent_id
+                        && category.name.trim().eq_ignore_ascii_case(name.trim())
+                })
+                .map(|category| category.id);
+            let id = found.unwrap_or_else(|| {
+                let mut category = TransactionCategory::new(
+                    name,
+                    transaction_type,
+                    DEFAULT_CATEGORY_COLOR.to_string(),
+                );
+                category.parent_id = parent_id;
+                let id = category.id;
+                self.result.book.categories.push(category);
+                id
+            });
+            self.paths.insert(prefix, id);
+            parent_id = Some(id);
+        }
+        parent_id
+    }
+
+    /// 新建账户的余额随导入的交易变化，已有账户的余额由应用自行维护
+    fn adjust_balance(&mut self, account_id: Uuid, amount: f64, opening: bool) {
+        if let Some(account) = self
+            .result
+            .book
+            .accounts
+            .iter_mut()
+            .find(|account| account.id == account_id)
+        {
+            account.balance += amount;
+            if opening {
+                account.initial_balance += amount;
+            }
+        }
+    }
+
+    fn import_entry(&mut self, entry: RawEntry) -> std::result::Result<(), ImportError> {
+        let line = entry.line;
+        let error = |message: String| {
+            ImportError::new(message)
+                .with_line(line)
+                .with_data(entry.description.clone())
+        };
+
+        // 补全省略的金额并检查借贷平衡
+        let missing: Vec<usize> = entry
+            .postings
+            .iter()
+            .enumerate()
+            .filter(|(_, posting)| posting.amount.is_none())
+            .map(|(index, _)| index)
+            .collect();
+        let mut sums: HashMap<String, f64> = HashMap::new();
+        for posting in &entry.postings {
+            if let Some((amount, currency)) = &posting.amount {
+                *sums.entry(currency.clone()).or_default() += amount;
+            }
+        }
+        let mut postings: Vec<(String, f64, String)> = Vec::new();
+        match missing.len() {
+            0 => {
+                if let Some((currency, sum)) = sums.iter().find(|(_, sum)| sum.abs() >= 0.005) {
+                    return Err(error(format!("交易不平衡: 差额 {:.2} {}", sum, currency)));
+                }
+            }
+            1 if sums.len() <= 1 => {}
+            1 => return Err(error("多币种交易不能省略金额".to_string())),
+            _ => return Err(error("只能有一条过账省略金额".to_string())),
+        }
+        for posting in &entry.postings {
+            let (amount, currency) = posting.amount.clone().unwrap_or_else(|| {
+                let (currency, sum) = sums
+                    .iter()
+                    .next()
+                    .map(|(currency, sum)| (currency.clone(), *sum))
+                    .unwrap_or_else(|| (self.default_currency.to_string(), 0.0));
+                (-sum, currency)
+            });
+            postings.push((posting.account.clone(), amount, currency));
+        }
+
+        let mut accounts = Vec::new();
+        let mut categories = Vec::new();
+        let mut equity = false;
+        for (path, amount, currency) in postings {
+            match self.resolve(&path).map_err(|e| e.with_line(line))? {
+                PostingTarget::Account(id) => accounts.push((id, amount, currency)),
+                PostingTarget::Category(transaction_type, id) => {
+                    categories.push((transaction_type, id, amount, currency))
+                }
+                PostingTarget::Equity => equity = true,
+            }
+        }
+
+        let status = match entry.flag {
+            Some('!') => TransactionStatus::Pending,
+            _ => TransactionStatus::Completed,
+        };
+        let completed = status == TransactionStatus::Completed;
+        let mut tags = entry.tags;
+        if let Some(extra) = entry.meta.get("tags") {
+            tags.extend(
+                extra
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        let split_tag = tags
+            .iter()
+            .find(|tag| tag.starts_with(SPLIT_TAG_PREFIX))
+            .cloned();
+        tags.retain(|tag| !tag.starts_with(SPLIT_TAG_PREFIX));
+        let description = entry.description.clone();
+        let date = entry.date;
+
+        let mut records: Vec<Transaction> = Vec::new();
+        let record =
+            |transaction_type: TransactionType, amount: f64, currency: String, account_id: Uuid| {
+                let mut transaction = Transaction::new(
+                    transaction_type,
+                    amount,
+                    currency,
+                    description.clone(),
+                    account_id,
+                    date,
+                );
+                transaction.status = status;
+                transaction.tags = tags.clone();
+                transaction
+            };
+
+        if equity {
+            if !categories.is_empty() {
+                return Err(error("期初余额交易不能包含收支分类".to_string()));
+            }
+            for (account_id, amount, _) in accounts {
+                if !self.result.book.accounts.iter().any(|a| a.id == account_id) {
+                    self.result
+                        .warnings
+                        .push(format!("第 {} 行: 已有账户的期初余额未修改", line));
+                    continue;
+                }
+                self.adjust_balance(account_id, amount, true);
+            }
+            return Ok(());
+        }
+
+        if categories.is_empty() {
+            // 转账：一个转出账户对多个转入账户，或多个转出账户对一个转入账户
+            let (sources, targets): (Vec<_>, Vec<_>) = accounts
+                .into_iter()
+                .partition(|(_, amount, _)| *amount < 0.0);
+            let pairs: Vec<(Uuid, Uuid, f64, String)> =
+                match (sources.as_slice(), targets.as_slice()) {
+                    ([source], targets) if !targets.is_empty() => targets
+                        .iter()
+                        .map(|(to, amount, currency)| (source.0, *to, *amount, currency.clone()))
+                        .collect(),
+                    (sources, [target]) if !sources.is_empty() => sources
+                        .iter()
+                        .map(|(from, amount, currency)| {
+                            (*from, target.0, -amount, currency.clone())
+                        })
+                        .collect(),
+                    _ => return Err(error("无法识别的转账结构".to_string())),
+                };
+            let split = (pairs.len() > 1).then(|| split_tag.clone().unwrap_or_else(new_split_tag));
+            for (from, to, amount, currency) in pairs {
+                let mut transaction = record(TransactionType::Transfer, amount, currency, from);
+                transaction.to_account_id = Some(to);
+                if let Some(split) = &split {
+                    transaction.tags.push(split.clone());
+                }
+                records.push(transaction);
+                if completed {
+                    self.adjust_balance(from, -amount, false);
+                    self.adjust_balance(to, amount, false);
+                }
+            }
+        } else {
+            let [(account_id, _, _)] = accounts.as_slice() else {
+                return Err(error("收支交易必须有且只有一个资金账户".to_string()));
+            };
+            let account_id = *account_id;
+            let split =
+                (categories.len() > 1).then(|| split_tag.clone().unwrap_or_else(new_split_tag));
+            for (transaction_type, category_id, amount, currency) in categories {
+                // 收入过账为负数，支出过账为正数
+                let amount = match transaction_type {
+                    TransactionType::Income => -amount,
+                    _ => amount,
+                };
+                let mut transaction = record(transaction_type, amount, currency, account_id);
+                transaction.category_id = category_id;
+                if let Some(split) = &split {
+                    transaction.tags.push(split.clone());
+                }
+                records.push(transaction);
+                if completed {
+                    let change = match transaction_type {
+                        TransactionType::Income => amount,
+                        _ => -amount,
+                    };
+                    self.adjust_balance(account_id, change, false);
+                }
+            }
+        }
+
+        self.result.book.transactions.extend(records);
+        Ok(())
+    }
+}
+
+fn new_split_tag() -> String {
+    format!(
+        "{}{}",
+        SPLIT_TAG_PREFIX,
+        &Uuid::new_v4().simple().to_string()[..8]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_book() -> LedgerBook {
+        let mut bank = Account::new(
+            "招商银行".to_string(),
+            AccountType::Bank,
+            "CNY".to_string(),
+            1000.0,
+        );
+        bank.is_default = true;
+        let card = Account::new(
+            "Visa Card".to_string(),
+            AccountType::CreditCard,
+            "CNY".to_string(),
+            0.0,
+        );
+
+        let food = TransactionCategory::new(
+            "Food".to_string(),
+            TransactionType::Expense,
+            "#FF9800".to_string(),
+        );
+        let mut groceries = TransactionCategory::new(
+            "Groceries".to_string(),
+            TransactionType::Expense,
+            "#FF9800".to_string(),
+        );
+        groceries.parent_id = Some(food.id);
+        let dining = TransactionCategory::new(
+            "外出 就餐".to_string(),
+            TransactionType::Expense,
+            "#FF9800".to_string(),
+        );
+        let salary = TransactionCategory::new(
+            "Salary".to_string(),
+            TransactionType::Income,
+            "#4CAF50".to_string(),
+        );
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut income = Transaction::new(
+            TransactionType::Income,
+            8000.0,
+            "CNY".to_string(),
+            "三月工资".to_string(),
+            bank.id,
+            date,
+        );
+        income.category_id = Some(salary.id);
+
+        let mut split_a = Transaction::new(
+            TransactionType::Expense,
+            120.5,
+            "CNY".to_string(),
+            "超市".to_string(),
+            card.id,
+            date,
+        );
+        split_a.category_id = Some(groceries.id);
+        split_a.tags = vec!["split-0001".to_string(), "家庭".to_string()];
+        let mut split_b = split_a.clone();
+        split_b.id = Uuid::new_v4();
+        split_b.amount = 30.0;
+        split_b.category_id = Some(dining.id);
+
+        let mut transfer = Transaction::new(
+            TransactionType::Transfer,
+            150.5,
+            "CNY".to_string(),
+            "还信用卡".to_string(),
+            bank.id,
+            date,
+        );
+        transfer.to_account_id = Some(card.id);
+        transfer.status = TransactionStatus::Pending;
+
+        let mut cancelled = Transaction::new(
+            TransactionType::Expense,
+            99.0,
+            "CNY".to_string(),
+            "已取消".to_string(),
+            bank.id,
+            date,
+        );
+        cancelled.status = TransactionStatus::Cancelled;
+
+        LedgerBook {
+            accounts: vec![bank, card],
+            categories: vec![food, groceries, dining, salary],
+            transactions: vec![income, split_a, split_b, transfer, cancelled],
+        }
+    }
+
+    #[test]
+    fn test_beancount_round_trip() {
+        let book = sample_book();
+        let journal = LedgerExporter::new(&book, LedgerDialect::Beancount).export();
+
+        assert!(journal.contains("open Assets:Bank:招商银行 CNY"));
+        assert!(journal.contains("open Expenses:Food:Groceries"));
+        assert!(journal.contains("open Expenses:外出-就餐\n  name: \"外出 就餐\""));
+        assert!(journal.contains("^split-0001"));
+        assert!(journal.contains("tags: \"家庭\""));
+        assert!(!journal.contains("已取消"));
+        assert_eq!(LedgerDialect::detect(&journal), LedgerDialect::Beancount);
+
+        let result = LedgerImporter::new(LedgerImportOptions::default())
+            .import_str(&journal, &LedgerBook::default());
+        assert!(result.success(), "{:?}", result.errors);
+        let imported = result.book;
+
+        assert_eq!(imported.accounts.len(), 2);
+        let bank = imported
+            .accounts
+            .iter()
+            .find(|a| a.name == "招商银行")
+            .unwrap();
+        assert_eq!(bank.account_type, AccountType::Bank);
+        assert_eq!(bank.initial_balance, 1000.0);
+        assert_eq!(bank.balance, 1000.0 + 8000.0);
+        let card = imported
+            .accounts
+            .iter()
+            .find(|a| a.name == "Visa Card")
+            .unwrap();
+        assert_eq!(card.account_type, AccountType::CreditCard);
+        assert!((card.balance + 150.5).abs() < 1e-9);
+
+        let groceries = imported
+            .categories
+            .iter()
+            .find(|c| c.name == "Groceries")
+            .unwrap();
+        let food = imported
+            .categories
+            .iter()
+            .find(|c| c.name == "Food")
+            .unwrap();
+        assert_eq!(groceries.parent_id, Some(food.id));
+        assert!(imported.categories.iter().any(|c| c.name == "外出 就餐"));
+
+        assert_eq!(imported.transactions.len(), 4);
+        let splits: Vec<_> = imported
+            .transactions
+            .iter()
+            .filter(|t| t.description == "超市")
+            .collect();
+        assert_eq!(splits.len(), 2);
+        assert!(splits
+            .iter()
+            .all(|t| t.tags.contains(&"split-0001".to_string())
+                && t.tags.contains(&"家庭".to_string())));
+        let transfer = imported
+            .transactions
+            .iter()
+            .find(|t| t.transaction_type == TransactionType::Transfer)
+            .unwrap();
+        assert_eq!(transfer.status, TransactionStatus::Pending);
+        assert_eq!(transfer.to_account_id, Some(card.id));
+    }
+
+    #[test]
+    fn test_ledger_export_and_import() {
+        let book = sample_book();
+        let journal = LedgerExporter::new(&book, LedgerDialect::Ledger).export();
+        assert!(journal.contains("2024/03/01 * 三月工资"));
+        assert!(journal.contains("; :家庭:split-0001:"));
+        assert_eq!(LedgerDialect::detect(&journal), LedgerDialect::Ledger);
+
+        // 已有同名账户和分类时合并
+        let existing = LedgerBook {
+            accounts: vec![book.accounts[0].clone()],
+            categories: vec![book.categories[3].clone()],
+            transactions: Vec::new(),
+        };
+        let result =
+            LedgerImporter::new(LedgerImportOptions::default()).import_str(&journal, &existing);
+        assert!(result.success(), "{:?}", result.errors);
+        assert_eq!(result.book.accounts.len(), 1);
+        assert!(!result.book.categories.iter().any(|c| c.name == "Salary"));
+        let income = result
+            .book
+            .transactions
+            .iter()
+            .find(|t| t.transaction_type == TransactionType::Income)
+            .unwrap();
+        assert_eq!(income.account_id, existing.accounts[0].id);
+        assert_eq!(income.category_id, Some(existing.categories[0].id));
+        assert_eq!(income.amount, 8000.0);
+    }
+
+    #[test]
+    fn test_import_hand_written_ledger() {
+        let journal = "\
+; 手写日记账
+2024/01/05 * (1024) Whole Foods  ; :food:
+    Expenses:Food:Groceries      $45.20
+    Expenses:Household           $12.00
+    Assets:Checking
+
+2024-01-06 Coffee
+    Expenses:Food:Coffee    4.50
+    Liabilities:Visa
+
+2024/01/07 * 不平衡
+    Expenses:Food    10 CNY
+    Assets:Cash     -9 CNY
+
+2024/01/08 * 未知账户
+    Travel:Hotel    100
+    Assets:Cash
+";
+        let result = LedgerImporter::new(LedgerImportOptions {
+            default_currency: "USD".to_string(),
+            ..Default::default()
+        })
+        .import_str(journal, &LedgerBook::default());
+
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].line_number, Some(11));
+        assert_eq!(result.errors[1].line_number, Some(15));
+
+        let book = result.book;
+        let checking = book.accounts.iter().find(|a| a.name == "Checking").unwrap();
+        assert_eq!(checking.account_type, AccountType::Bank);
+        assert!((checking.balance + 57.2).abs() < 1e-9);
+
+        let split: Vec<_> = book
+            .transactions
+            .iter()
+            .filter(|t| t.description == "Whole Foods")
+            .collect();
+        assert_eq!(split.len(), 2);
+        assert!(split
+            .iter()
+            .all(|t| t.currency == "USD" && t.tags.contains(&"food".to_string())));
+        let split_tag = split[0]
+            .tags
+            .iter()
+            .find(|t| t.starts_with(SPLIT_TAG_PREFIX));
+        assert!(split_tag.is_some() && split[1].tags.contains(split_tag.unwrap()));
+
+        let coffee = book
+            .transactions
+            .iter()
+            .find(|t| t.description == "Coffee")
+            .unwrap();
+        assert_eq!(coffee.amount, 4.5);
+        let food = book.categories.iter().find(|c| c.name == "Food").unwrap();
+        let coffee_category = book.categories.iter().find(|c| c.name == "Coffee").unwrap();
+        assert_eq!(coffee_category.parent_id, Some(food.id));
+        assert_eq!(
+            book.categories.iter().filter(|c| c.name == "Food").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        let escaped = escape_string("他说 \"你好\"\n路径 C:\\tmp");
+        assert_eq!(escaped, "他说 \\\"你好\\\" 路径 C:\\\\tmp");
+        let quoted = format!("\"{}\" #tag", escaped);
+        let (text, rest) = parse_quoted(&quoted).unwrap();
+        assert_eq!(text, "他说 \"你好\" 路径 C:\\tmp");
+        assert_eq!(rest, " #tag");
+        assert!(parse_quoted("\"未闭合 \\\"").is_none());
+
+        // 字符串中的分号不是注释
+        assert_eq!(
+            strip_comment("\"a; b\" #tag ; 注释"),
+            ("\"a; b\" #tag", Some(" 注释"))
+        );
+        assert_eq!(
+            parse_meta("note: \"第一行\\n第二行\""),
+            Some(("note".to_string(), "第一行\n第二行".to_string()))
+        );
+        assert_eq!(parse_meta("Note: 值"), None);
+        assert_eq!(parse_meta("url:https://example.com"), None);
+
+        // 导出再导入后描述保持不变（换行合并为空格）
+        let mut book = sample_book();
+        book.transactions.truncate(1);
+        book.transactions[0].description = "工资 \"三月\"; 含\\补贴\n税后".to_string();
+        for dialect in [LedgerDialect::Beancount, LedgerDialect::Ledger] {
+            let journal = LedgerExporter::new(&book, dialect).export();
+            let result = LedgerImporter::new(LedgerImportOptions {
+                dialect: Some(dialect),
+                ..Default::default()
+            })
+            .import_str(&journal, &LedgerBook::default());
+            assert!(result.success(), "{:?}: {:?}", dialect, result.errors);
+            assert_eq!(result.book.transactions.len(), 1);
+            let description = &result.book.transactions[0].description;
+            match dialect {
+                LedgerDialect::Beancount => assert_eq!(description, "工资 \"三月\"; 含\\补贴 税后"),
+                // Ledger 的分号开始注释，之后的内容不属于描述
+                LedgerDialect::Ledger => assert_eq!(description, "工资 \"三月\""),
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_malformed_input() {
+        let journal = "\
+2024-01-01 open
+2024-01-01 open Assets:Cash CNY
+
+2024-01-02 * \"未闭合
+  Expenses:Food  10 CNY
+  Assets:Cash
+
+2024-01-03 * \"金额错误\"
+  Expenses:Food  12..5元 CNY
+  Assets:Cash
+
+2024-01-04 * \"两处省略\"
+  Expenses:Food
+  Assets:Cash
+
+2024-01-05 * \"多币种\"
+  Expenses:Food  10 CNY
+  Expenses:Travel  5 USD
+  Assets:Cash
+
+2024-01-06 pad Assets:Cash Equity:Opening-Balances
+
+2024-01-07 * \"正常\"
+  Expenses:Food  8 CNY
+  Assets:Cash
+";
+        let result = LedgerImporter::new(LedgerImportOptions::default())
+            .import_str(journal, &LedgerBook::default());
+
+        let lines: Vec<_> = result.errors.iter().map(|e| e.line_number).collect();
+        assert_eq!(
+            lines,
+            vec![Some(1), Some(4), Some(9), Some(8), Some(12), Some(16)],
+            "{:?}",
+            result.errors
+        );
+        assert!(result.errors[1].message.contains("字符串未闭合"));
+        assert!(result.errors[2].message.contains("无法解析金额"));
+        assert_eq!(result.errors[2].field.as_deref(), Some("Expenses:Food"));
+        // 金额无法解析的过账被丢弃，所在交易随后也不会导入
+        assert_eq!(result.errors[3].data.as_deref(), Some("金额错误"));
+        assert!(result.errors[4].message.contains("只能有一条过账省略金额"));
+        assert!(result.errors[5].message.contains("多币种"));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("pad"));
+
+        // 出错的交易被跳过，其余交易照常导入
+        assert_eq!(result.book.transactions.len(), 1);
+        assert_eq!(result.book.transactions[0].description, "正常");
+        assert_eq!(result.book.transactions[0].amount, 8.0);
+    }
+
+    #[test]
+    fn test_amount_and_commodity_rules() {
+        let parse = |text: &str| parse_amount(text, "CNY");
+        assert_eq!(parse("12.50 CNY"), Some((12.5, "CNY".to_string())));
+        assert_eq!(parse("CNY 12.50"), Some((12.5, "CNY".to_string())));
+        assert_eq!(parse("-3 USD"), Some((-3.0, "USD".to_string())));
+        assert_eq!(parse("$1,234.50"), Some((1234.5, "USD".to_string())));
+        assert_eq!(parse("-¥12.50"), Some((-12.5, "CNY".to_string())));
+        assert_eq!(parse("￥8"), Some((8.0, "CNY".to_string())));
+        assert_eq!(parse("€5"), Some((5.0, "EUR".to_string())));
+        assert_eq!(parse("£5"), Some((5.0, "GBP".to_string())));
+        assert_eq!(parse("12.50EUR"), Some((12.5, "EUR".to_string())));
+        assert_eq!(parse("10 \"VANGUARD 500\""), None);
+        assert_eq!(parse("10 \"VTSAX\""), Some((10.0, "VTSAX".to_string())));
+        assert_eq!(parse("42"), Some((42.0, "CNY".to_string())));
+        assert_eq!(parse("CNY"), None);
+        assert_eq!(parse("CNY abc"), None);
+        assert_eq!(parse("1 2 3"), None);
+
+        assert_eq!(
+            split_commodity("-$4.50"),
+            Some(("-4.50".to_string(), "$".to_string()))
+        );
+        assert_eq!(
+            split_commodity("100JPY"),
+            Some(("100".to_string(), "JPY".to_string()))
+        );
+        assert_eq!(split_commodity("USD"), None);
+    }
+
+    #[test]
+    fn test_account_name_rules() {
+        assert_eq!(account_component("招商银行"), "招商银行");
+        assert_eq!(account_component("外出 就餐"), "外出-就餐");
+        assert_eq!(account_component("visa card"), "Visa-card");
+        assert_eq!(account_component("  Food & Drink!! "), "Food-Drink");
+        assert_eq!(account_component("_hidden"), "Hidden");
+        assert_eq!(account_component("2024 旅行"), "2024-旅行");
+        assert_eq!(account_component("(!)"), "X");
+        assert_eq!(account_component(""), "X");
+
+        assert_eq!(
+            infer_account_type("Liabilities:Visa"),
+            AccountType::CreditCard
+        );
+        assert_eq!(
+            infer_account_type("Liabilities:Bank:Loan"),
+            AccountType::CreditCard
+        );
+        assert_eq!(infer_account_type("Assets:Wallet"), AccountType::Cash);
+        assert_eq!(infer_account_type("Assets:Savings:ING"), AccountType::Bank);
+        assert_eq!(infer_account_type("assets:checking"), AccountType::Bank);
+        assert_eq!(
+            infer_account_type("Assets:Brokerage"),
+            AccountType::Investment
+        );
+        assert_eq!(infer_account_type("Assets:Receivable"), AccountType::Other);
+
+        // 导出的账户路径符合 Beancount 规则：每段以大写字母、数字或非 ASCII 字符开头
+        let mut book = sample_book();
+        book.accounts[1].name = "visa card (旧)".to_string();
+        book.categories[2].name = "餐饮/外卖".to_string();
+        let journal = LedgerExporter::new(&book, LedgerDialect::Beancount).export();
+        assert!(journal.contains("open Liabilities:CreditCard:Visa-card-旧 CNY"));
+        assert!(journal.contains("open Expenses:餐饮-外卖"));
+
+        // 名称清洗后相同的账户导入时按元数据中的原始名称区分
+        let result = LedgerImporter::new(LedgerImportOptions::default())
+            .import_str(&journal, &LedgerBook::default());
+        assert!(result.success(), "{:?}", result.errors);
+        assert!(result
+            .book
+            .accounts
+            .iter()
+            .any(|a| a.name == "visa card (旧)" && a.account_type == AccountType::CreditCard));
+        assert!(result.book.categories.iter().any(|c| c.name == "餐饮/外卖"));
+    }
+}
@@ -7,12 +7,21 @@ use chrono::{DateTime, Duration, Local};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+pub mod archive;
 pub mod crypto;
 pub mod date;
 pub mod export;
 pub mod format;
+pub mod ical;
 pub mod import;
+pub mod ledger;
+pub mod pdf;
+pub mod report;
+pub mod scheduled_report;
 pub mod validation;
+pub mod vault;
+pub mod xlsx;
+pub mod zip;
 
 /// 生成唯一ID
 pub fn generate_id() -> Uuid {
@@ -0,0 +1,627 @@
+//! # TrueType 字体解析
+//!
+//! 读取 TrueType 字体（.ttf 或字体集合 .ttc 中的一款）的字形映射和度量，
+//! 并生成只包含已使用字形的子集，用于嵌入 PDF
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// 子集字体中保留的表（loca 和 glyf 重新生成）
+const KEPT_TABLES: [&[u8; 4]; 6] = [b"cvt ", b"fpgm", b"head", b"hhea", b"hmtx", b"maxp"];
+
+/// 复合字形标志位
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// 字符映射子表
+#[derive(Debug, Clone, Copy)]
+enum Cmap {
+    /// 格式4（基本多文种平面）
+    Segment(usize),
+    /// 格式12（完整 Unicode）
+    Groups(usize),
+}
+
+/// TrueType 字体
+#[derive(Debug, Clone)]
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    tables: BTreeMap<[u8; 4], (usize, usize)>,
+    cmap: Cmap,
+    /// PostScript 名称
+    pub name: String,
+    /// 每 em 的字体单位数
+    pub units_per_em: u16,
+    /// 上行高度（字体单位）
+    pub ascent: i16,
+    /// 下行高度（字体单位，负数）
+    pub descent: i16,
+    /// 字形边界框（字体单位）
+    pub bbox: [i16; 4],
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    long_loca: bool,
+}
+
+impl TrueTypeFont {
+    /// 从文件加载字体，字体集合取第一款字体
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| anyhow!("无法读取字体文件 {}: {}", path.display(), e))?;
+        Self::parse(data, 0)
+    }
+
+    /// 解析字体数据
+    ///
+    /// # 参数
+    /// * `data` - .ttf 或 .ttc 文件内容
+    /// * `index` - 字体集合中的字体序号
+    pub fn parse(data: Vec<u8>, index: usize) -> Result<Self> {
+        let offset = if data.get(0..4) == Some(b"ttcf") {
+            let count = read_u32(&data, 8)? as usize;
+            if index >= count {
+                bail!("字体集合中没有第 {} 款字体", index);
+            }
+            read_u32(&data, 12 + index * 4)? as usize
+        } else {
+            0
+        };
+
+        let num_tables = read_u16(&data, offset + 4)? as usize;
+        let mut tables = BTreeMap::new();
+        for i in 0..num_tables {
+            let record = offset + 12 + i * 16;
+            let tag: [u8; 4] = data
+                .get(record..record + 4)
+                .and_then(|tag| tag.try_into().ok())
+                .ok_or_else(|| anyhow!("字体表目录不完整"))?;
+            let table_offset = read_u32(&data, record + 8)? as usize;
+            let length = read_u32(&data, record + 12)? as usize;
+            if table_offset + length > data.len() {
+                bail!("字体表 {} 超出文件范围", String::from_utf8_lossy(&tag));
+            }
+            tables.insert(tag, (table_offset, length));
+        }
+
+        if !tables.contains_key(b"glyf") {
+            bail!("只支持 TrueType 轮廓字体（缺少 glyf 表）");
+        }
+
+        let table = |tag: &[u8; 4]| {
+            tables
+                .get(tag)
+                .map(|(offset, _)| *offset)
+                .ok_or_else(|| anyhow!("字体缺少 {} 表", String::from_utf8_lossy(tag)))
+        };
+        let head = table(b"head")?;
+        let hhea = table(b"hhea")?;
+        let maxp = table(b"maxp")?;
+        table(b"hmtx")?;
+        table(b"loca")?;
+
+        let units_per_em = read_u16(&data, head + 18)?;
+        let bbox = [
+            read_i16(&data, head + 36)?,
+            read_i16(&data, head + 38)?,
+            read_i16(&data, head + 40)?,
+            read_i16(&data, head + 42)?,
+        ];
+        let long_loca = read_i16(&data, head + 50)? == 1;
+        let ascent = read_i16(&data, hhea + 4)?;
+        let descent = read_i16(&data, hhea + 6)?;
+        let num_h_metrics = read_u16(&data, hhea + 34)?;
+        let num_glyphs = read_u16(&data, maxp + 4)?;
+        let cmap = find_cmap(&data, table(b"cmap")?)?;
+        let name = tables
+            .get(b"name")
+            .and_then(|(offset, _)| postscript_name(&data, *offset))
+            .unwrap_or_else(|| "EmbeddedFont".to_string());
+
+        Ok(Self {
+            data,
+            tables,
+            cmap,
+            name,
+            units_per_em,
+            ascent,
+            descent,
+            bbox,
+            num_glyphs,
+            num_h_metrics,
+            long_loca,
+        })
+    }
+
+    /// 字符对应的字形ID，字体中没有该字符时返回 None
+    pub fn glyph_id(&self, ch: char) -> Option<u16> {
+        let code = ch as u32;
+        let glyph = match self.cmap {
+            Cmap::Segment(offset) => segment_lookup(&self.data, offset, code),
+            Cmap::Groups(offset) => group_lookup(&self.data, offset, code),
+        };
+        glyph.filter(|&glyph| glyph != 0 && glyph < self.num_glyphs)
+    }
+
+    /// 字形的前进宽度（字体单位）
+    pub fn advance(&self, glyph: u16) -> u16 {
+        let hmtx = self.tables[b"hmtx"].0;
+        let index = glyph.min(self.num_h_metrics.saturating_sub(1)) as usize;
+        read_u16(&self.data, hmtx + index * 4).unwrap_or(0)
+    }
+
+    /// 字形在 glyf 表中的数据
+    fn glyph_data(&self, glyph: u16) -> &[u8] {
+        let loca = self.tables[b"loca"].0;
+        let (glyf, glyf_len) = self.tables[b"glyf"];
+        let index = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (
+                read_u32(&self.data, loca + index * 4).unwrap_or(0) as usize,
+                read_u32(&self.data, loca + index * 4 + 4).unwrap_or(0) as usize,
+            )
+        } else {
+            (
+                read_u16(&self.data, loca + index * 2).unwrap_or(0) as usize * 2,
+                read_u16(&self.data, loca + index * 2 + 2).unwrap_or(0) as usize * 2,
+            )
+        };
+        if start >= end || end > glyf_len {
+            return &[];
+        }
+        &self.data[glyf + start..glyf + end]
+    }
+
+    /// 生成只包含指定字形（及其引用的组件字形）的字体文件
+    ///
+    /// 字形ID保持不变，未使用的字形数据被清空
+    pub fn subset(&self, glyphs: &BTreeSet<u16>) -> Vec<u8> {
+        // 收集复合字形引用的组件
+        let mut used: BTreeSet<u16> = glyphs
+            .iter()
+            .copied()
+            .filter(|&glyph| glyph < self.num_glyphs)
+            .collect();
+        used.insert(0);
+        let mut pending: Vec<u16> = used.iter().copied().collect();
+        while let Some(glyph) = pending.pop() {
+            for component in composite_components(self.glyph_data(glyph)) {
+                if component < self.num_glyphs && used.insert(component) {
+                    pending.push(component);
+                }
+            }
+        }
+
+        // 重新生成 glyf 和 loca（长格式）
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity((self.num_glyphs as usize + 1) * 4);
+        for glyph in 0..self.num_glyphs {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            if used.contains(&glyph) {
+                glyf.extend_from_slice(self.glyph_data(glyph));
+                while glyf.len() % 4 != 0 {
+                    glyf.push(0);
+                }
+            }
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let mut tables: BTreeMap<[u8; 4], Vec<u8>> = BTreeMap::new();
+        for tag in KEPT_TABLES {
+            if let Some(&(offset, length)) = self.tables.get(tag) {
+                tables.insert(*tag, self.data[offset..offset + length].to_vec());
+            }
+        }
+        if let Some(head) = tables.get_mut(b"head") {
+            // 清空校验和调整值，改为长格式 loca
+            head[8..12].copy_from_slice(&[0; 4]);
+            head[50..52].copy_from_slice(&1i16.to_be_bytes());
+        }
+        if let Some(&(offset, length)) = self.tables.get(b"prep") {
+            tables.insert(*b"prep", self.data[offset..offset + length].to_vec());
+        }
+        tables.insert(*b"glyf", glyf);
+        tables.insert(*b"loca", loca);
+
+        write_sfnt(&tables)
+    }
+}
+
+/// 写出 sfnt 字体文件
+fn write_sfnt(tables: &BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.max(1).leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let range_shift = num_tables * 16 - search_range;
+
+    let mut output = Vec::new();
+    output.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    output.extend_from_slice(&num_tables.to_be_bytes());
+    output.extend_from_slice(&search_range.to_be_bytes());
+    output.extend_from_slice(&entry_selector.to_be_bytes());
+    output.extend_from_slice(&range_shift.to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        output.extend_from_slice(tag);
+        output.extend_from_slice(&checksum(data).to_be_bytes());
+        output.extend_from_slice(&(offset as u32).to_be_bytes());
+        output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += (data.len() + 3) & !3;
+    }
+    for data in tables.values() {
+        output.extend_from_slice(data);
+        while output.len() % 4 != 0 {
+            output.push(0);
+        }
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&output));
+        output[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    output
+}
+
+/// 字体表校验和
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// 复合字形引用的组件字形ID
+fn composite_components(glyph: &[u8]) -> Vec<u16> {
+    let mut components = Vec::new();
+    if read_i16(glyph, 0).map_or(true, |contours| contours >= 0) {
+        return components;
+    }
+
+    let mut offset = 10;
+    loop {
+        let (Ok(flags), Ok(component)) = (read_u16(glyph, offset), read_u16(glyph, offset + 2))
+        else {
+            break;
+        };
+        components.push(component);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    components
+}
+
+/// 选择 Unicode 字符映射子表（优先完整 Unicode）
+fn find_cmap(data: &[u8], cmap: usize) -> Result<Cmap> {
+    let count = read_u16(data, cmap + 2)? as usize;
+    let mut segment = None;
+    let mut groups = None;
+    for i in 0..count {
+        let record = cmap + 4 + i * 8;
+        let platform = read_u16(data, record)?;
+        let encoding = read_u16(data, record + 2)?;
+        let offset = cmap + read_u32(data, record + 4)? as usize;
+        let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        if !unicode {
+            continue;
+        }
+        match read_u16(data, offset)? {
+            4 => segment = segment.or(Some(Cmap::Segment(offset))),
+            12 => groups = groups.or(Some(Cmap::Groups(offset))),
+            _ => {}
+        }
+    }
+    groups
+        .or(segment)
+        .ok_or_else(|| anyhow!("字体没有 Unicode 字符映射"))
+}
+
+/// 在格式4子表中查找字形
+fn segment_lookup(data: &[u8], offset: usize, code: u32) -> Option<u16> {
+    if code > 0xFFFF {
+        return None;
+    }
+    let code = code as u16;
+    let seg_count = read_u16(data, offset + 6).ok()? as usize / 2;
+    let ends = offset + 14;
+    let starts = ends + seg_count * 2 + 2;
+    let deltas = starts + seg_count * 2;
+    let range_offsets = deltas + seg_count * 2;
+
+    for i in 0..seg_count {
+        let end = read_u16(data, ends + i * 2).ok()?;
+        if end < code {
+            continue;
+        }
+        let start = read_u16(data, starts + i * 2).ok()?;
+        if start > code {
+            return None;
+        }
+        let delta = read_u16(data, deltas + i * 2).ok()?;
+        let range_offset = read_u16(data, range_offsets + i * 2).ok()?;
+        if range_offset == 0 {
+            return Some(code.wrapping_add(delta));
+        }
+        let address = range_offsets + i * 2 + range_offset as usize + (code - start) as usize * 2;
+        let glyph = read_u16(data, address).ok()?;
+        return (glyph != 0).then(|| glyph.wrapping_add(delta));
+    }
+    None
+}
+
+/// 在格式12子表中查找字形
+fn group_lookup(data: &[u8], offset: usize, code: u32) -> Option<u16> {
+    let count = read_u32(data, offset + 12).ok()? as usize;
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let middle = (low + high) / 2;
+        let group = offset + 16 + middle * 12;
+        let start = read_u32(data, group).ok()?;
+        let end = read_u32(data, group + 4).ok()?;
+        if code < start {
+            high = middle;
+        } else if code > end {
+            low = middle + 1;
+        } else {
+            let glyph = read_u32(data, group + 8).ok()? + (code - start);
+            return u16::try_from(glyph).ok();
+        }
+    }
+    None
+}
+
+/// 从 name 表读取 PostScript 名称
+fn postscript_name(data: &[u8], name: usize) -> Option<String> {
+    let count = read_u16(data, name + 2).ok()? as usize;
+    let strings = name + read_u16(data, name + 4).ok()? as usize;
+    for i in 0..count {
+        let record = name + 6 + i * 12;
+        let platform = read_u16(data, record).ok()?;
+        let name_id = read_u16(data, record + 6).ok()?;
+        if name_id != 6 {
+            continue;
+        }
+        let length = read_u16(data, record + 8).ok()? as usize;
+        let start = strings + read_u16(data, record + 10).ok()? as usize;
+        let bytes = data.get(start..start + length)?;
+        let text = if platform == 3 || platform == 0 {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf8_lossy(bytes).to_string()
+        };
+        let cleaned: String = text
+            .chars()
+            .filter(|ch| ch.is_ascii_alphanumeric() || *ch == '-')
+            .collect();
+        if !cleaned.is_empty() {
+            return Some(cleaned);
+        }
+    }
+    None
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| anyhow!("字体数据不完整"))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Result<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| anyhow!("字体数据不完整"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 简单字形：一个轮廓、一个点，以 `x` 区分不同字形
+    fn simple_glyph(x: u8) -> Vec<u8> {
+        let mut glyph = Vec::new();
+        glyph.extend_from_slice(&1i16.to_be_bytes());
+        for value in [0i16, 0, x as i16, 100] {
+            glyph.extend_from_slice(&value.to_be_bytes());
+        }
+        glyph.extend_from_slice(&0u16.to_be_bytes()); // endPtsOfContours
+        glyph.extend_from_slice(&0u16.to_be_bytes()); // instructionLength
+        glyph.extend_from_slice(&[0x37, x, 100, 0]); // flags, x, y, 对齐
+        glyph
+    }
+
+    /// 引用一个组件字形的复合字形
+    fn composite_glyph(component: u16) -> Vec<u8> {
+        let mut glyph = Vec::new();
+        glyph.extend_from_slice(&(-1i16).to_be_bytes());
+        for value in [0i16, 0, 100, 100] {
+            glyph.extend_from_slice(&value.to_be_bytes());
+        }
+        glyph.extend_from_slice(&(ARG_1_AND_2_ARE_WORDS | 0x0002).to_be_bytes());
+        glyph.extend_from_slice(&component.to_be_bytes());
+        glyph.extend_from_slice(&10i16.to_be_bytes());
+        glyph.extend_from_slice(&0i16.to_be_bytes());
+        glyph
+    }
+
+    /// 测试字体：.notdef、A、B、C（复合字形，引用 B）、D，短格式 loca
+    fn fixture_font() -> Vec<u8> {
+        let glyphs = [
+            simple_glyph(10),
+            simple_glyph(20),
+            simple_glyph(30),
+            composite_glyph(2),
+            simple_glyph(50),
+        ];
+
+        let mut glyf = Vec::new();
+        let mut loca = Vec::new();
+        for glyph in &glyphs {
+            loca.extend_from_slice(&((glyf.len() / 2) as u16).to_be_bytes());
+            glyf.extend_from_slice(glyph);
+        }
+        loca.extend_from_slice(&((glyf.len() / 2) as u16).to_be_bytes());
+
+        let mut head = vec![0u8; 54];
+        head[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        head[12..16].copy_from_slice(&0x5F0F_3CF5u32.to_be_bytes());
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[40..42].copy_from_slice(&100i16.to_be_bytes());
+        head[42..44].copy_from_slice(&100i16.to_be_bytes());
+
+        let mut hhea = vec![0u8; 36];
+        hhea[0..4].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+        hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+        hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+        hhea[34..36].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+
+        let mut maxp = vec![0u8; 6];
+        maxp[0..4].copy_from_slice(&0x0000_5000u32.to_be_bytes());
+        maxp[4..6].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+
+        let hmtx: Vec<u8> = (0..glyphs.len() as u16)
+            .flat_map(|glyph| [(500 + glyph * 10).to_be_bytes(), 0u16.to_be_bytes()])
+            .flatten()
+            .collect();
+
+        // 格式4：'A'..='D' 映射到字形 1..=4，加上结尾的 0xFFFF 段
+        let mut cmap = Vec::new();
+        for value in [0u16, 1, 3, 1] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+        cmap.extend_from_slice(&12u32.to_be_bytes());
+        let segments: [(u16, u16, u16); 2] =
+            [(0x41, 0x44, 1u16.wrapping_sub(0x41)), (0xFFFF, 0xFFFF, 1)];
+        for value in [4u16, 32, 0, 4, 4, 1, 0] {
+            cmap.extend_from_slice(&value.to_be_bytes());
+        }
+        segments
+            .iter()
+            .for_each(|(_, end, _)| cmap.extend_from_slice(&end.to_be_bytes()));
+        cmap.extend_from_slice(&0u16.to_be_bytes());
+        segments
+            .iter()
+            .for_each(|(start, _, _)| cmap.extend_from_slice(&start.to_be_bytes()));
+        segments
+            .iter()
+            .for_each(|(_, _, delta)| cmap.extend_from_slice(&delta.to_be_bytes()));
+        segments
+            .iter()
+            .for_each(|_| cmap.extend_from_slice(&0u16.to_be_bytes()));
+
+        let tables = BTreeMap::from([
+            (*b"cmap", cmap),
+            (*b"glyf", glyf),
+            (*b"head", head),
+            (*b"hhea", hhea),
+            (*b"hmtx", hmtx),
+            (*b"loca", loca),
+            (*b"maxp", maxp),
+        ]);
+        write_sfnt(&tables)
+    }
+
+    /// 读取字体文件中的表
+    fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> &'a [u8] {
+        let num_tables = read_u16(font, 4).unwrap() as usize;
+        (0..num_tables)
+            .map(|i| 12 + i * 16)
+            .find(|&record| &font[record..record + 4] == tag)
+            .map(|record| {
+                let offset = read_u32(font, record + 8).unwrap() as usize;
+                let length = read_u32(font, record + 12).unwrap() as usize;
+                &font[offset..offset + length]
+            })
+            .unwrap_or_else(|| panic!("缺少 {} 表", String::from_utf8_lossy(tag)))
+    }
+
+    #[test]
+    fn test_parse_fixture() {
+        let font = TrueTypeFont::parse(fixture_font(), 0).unwrap();
+        assert_eq!(font.units_per_em, 1000);
+        assert_eq!((font.ascent, font.descent), (800, -200));
+        assert_eq!(font.glyph_id('A'), Some(1));
+        assert_eq!(font.glyph_id('D'), Some(4));
+        assert_eq!(font.glyph_id('中'), None);
+        assert_eq!(font.advance(3), 530);
+        assert_eq!(font.name, "EmbeddedFont");
+    }
+
+    #[test]
+    fn test_subset_keeps_used_and_component_glyphs() {
+        let font = TrueTypeFont::parse(fixture_font(), 0).unwrap();
+        let text = "AC";
+        let used: BTreeSet<u16> = text.chars().filter_map(|ch| font.glyph_id(ch)).collect();
+        assert_eq!(
+            used.len(),
+            text.chars().count(),
+            "cmap 应覆盖所有用到的字符"
+        );
+
+        let subset = font.subset(&used);
+        assert_eq!(checksum(&subset), 0xB1B0_AFBA);
+
+        // loca 改为长格式，偏移递增且以 glyf 长度结束
+        let head = table(&subset, b"head");
+        assert_eq!(read_i16(head, 50).unwrap(), 1);
+        let glyf = table(&subset, b"glyf");
+        let loca: Vec<usize> = table(&subset, b"loca")
+            .chunks_exact(4)
+            .map(|offset| u32::from_be_bytes(offset.try_into().unwrap()) as usize)
+            .collect();
+        assert_eq!(loca.len(), 6);
+        assert!(loca.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*loca.last().unwrap(), glyf.len());
+
+        // .notdef、用到的字形和复合字形引用的 B 保留原数据，未用到的 D 被清空
+        for glyph in 0..5u16 {
+            let data = &glyf[loca[glyph as usize]..loca[glyph as usize + 1]];
+            if glyph == 4 {
+                assert!(data.is_empty());
+            } else {
+                // 字形数据按4字节对齐
+                let original = font.glyph_data(glyph);
+                assert!(data.starts_with(original), "字形 {}", glyph);
+                assert!(data.len() - original.len() < 4);
+            }
+        }
+        for ch in text.chars() {
+            let glyph = font.glyph_id(ch).unwrap() as usize;
+            assert!(loca[glyph] < loca[glyph + 1], "字符 {} 的字形应被保留", ch);
+        }
+
+        // 度量表原样保留
+        let (hmtx, hmtx_len) = font.tables[b"hmtx"];
+        assert_eq!(table(&subset, b"hmtx"), &font.data[hmtx..hmtx + hmtx_len]);
+    }
+}
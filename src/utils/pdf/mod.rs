@@ -0,0 +1,813 @@
+//! # PDF 报告生成模块
+//!
+//! 纯 Rust 实现的 PDF 写出器，将报告模型排版为分页的 A4 文档：
+//! - 页眉（报告标题、生成时间）和页脚（页码）
+//! - 自动分页、跨页重复表头的表格
+//! - 横向柱状图
+//! - 中文字体：嵌入 TrueType 字体子集，找不到字体时使用阅读器内置的宋体
+
+pub mod font;
+
+pub use font::TrueTypeFont;
+
+use crate::utils::report::{Report, ReportBlock, ReportChart, ReportTable};
+use anyhow::Result;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+
+/// A4 页面尺寸（磅）
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+/// 页边距
+const MARGIN: f32 = 48.0;
+/// 正文区域上边界和下边界
+const CONTENT_TOP: f32 = PAGE_HEIGHT - 64.0;
+const CONTENT_BOTTOM: f32 = 56.0;
+
+/// 正文字号和行高
+const BODY_SIZE: f32 = 10.0;
+const BODY_LEADING: f32 = 16.0;
+/// 表格字号、行高和单元格内边距
+const TABLE_SIZE: f32 = 8.5;
+const TABLE_LEADING: f32 = 11.0;
+const CELL_PADDING: f32 = 4.0;
+/// 单元格最多显示的行数
+const MAX_CELL_LINES: usize = 6;
+/// 柱状图的柱条高度和间距
+const BAR_HEIGHT: f32 = 11.0;
+const BAR_GAP: f32 = 5.0;
+
+/// 柱状图配色
+const BAR_COLORS: [(f32, f32, f32); 6] = [
+    (0.29, 0.56, 0.85),
+    (0.36, 0.72, 0.47),
+    (0.96, 0.65, 0.26),
+    (0.85, 0.37, 0.37),
+    (0.58, 0.44, 0.80),
+    (0.30, 0.71, 0.75),
+];
+
+/// 系统中常见的中文 TrueType 字体位置
+const SYSTEM_FONTS: [&str; 12] = [
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+    "C:\\Windows\\Fonts\\Deng.ttf",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-zenhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/arphic/uming.ttc",
+];
+
+/// PDF 使用的字体
+#[derive(Debug, Clone)]
+pub enum PdfFont {
+    /// 嵌入的 TrueType 字体（只嵌入用到的字形）
+    Embedded(Box<TrueTypeFont>),
+    /// 不嵌入字体，使用 PDF 阅读器内置的 STSong-Light（Adobe-GB1）
+    Standard,
+}
+
+impl PdfFont {
+    /// 加载指定的 TrueType 字体
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::Embedded(Box::new(TrueTypeFont::load(path)?)))
+    }
+
+    /// 查找系统中的中文字体，找不到时使用阅读器内置字体
+    pub fn find_system() -> Self {
+        for path in SYSTEM_FONTS {
+            if !Path::new(path).exists() {
+                continue;
+            }
+            match TrueTypeFont::load(path) {
+                Ok(font) if font.glyph_id('中').is_some() => {
+                    log::debug!("PDF export uses font: {}", path);
+                    return Self::Embedded(Box::new(font));
+                }
+                Ok(_) => {}
+                Err(e) => log::debug!("Skipping font {}: {}", path, e),
+            }
+        }
+        log::warn!(
+            "No embeddable CJK font found, PDF export falls back to the non-embedded STSong-Light; \
+             text may not display in viewers without Adobe CJK fonts"
+        );
+        Self::Standard
+    }
+}
+
+/// 字体编码器：测量文本宽度、把文本编码为字符码并记录用到的字符
+struct FontEncoder {
+    font: PdfFont,
+    /// 字符码 -> 字符
+    used: BTreeMap<u16, char>,
+}
+
+impl FontEncoder {
+    fn new(font: PdfFont) -> Self {
+        Self {
+            font,
+            used: BTreeMap::new(),
+        }
+    }
+
+    /// 字符的字符码和宽度（千分之一 em）
+    fn code(&self, ch: char) -> (u16, f32) {
+        match &self.font {
+            PdfFont::Embedded(font) => {
+                let glyph = font.glyph_id(ch).unwrap_or(0);
+                let width = font.advance(glyph) as f32 * 1000.0 / font.units_per_em as f32;
+                (glyph, width)
+            }
+            PdfFont::Standard => {
+                let code = u16::try_from(ch as u32).unwrap_or(b'?' as u16);
+                let width = if (0x20..0x7F).contains(&code) {
+                    500.0
+                } else {
+                    1000.0
+                };
+                (code, width)
+            }
+        }
+    }
+
+    fn char_width(&self, ch: char, size: f32) -> f32 {
+        self.code(ch).1 * size / 1000.0
+    }
+
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        text.chars().map(|ch| self.char_width(ch, size)).sum()
+    }
+
+    /// 编码为十六进制字符串
+    fn encode(&mut self, text: &str) -> String {
+        let mut hex = String::with_capacity(text.len() * 4 + 2);
+        hex.push('<');
+        for ch in text.chars() {
+            let (code, _) = self.code(ch);
+            self.used.entry(code).or_insert(ch);
+            let _ = write!(hex, "{:04X}", code);
+        }
+        hex.push('>');
+        hex
+    }
+
+    /// 按宽度折行，超出最大行数时在末行加省略号
+    fn wrap(&self, text: &str, size: f32, width: f32, max_lines: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            let mut line_width = 0.0;
+            for ch in paragraph.chars() {
+                let ch_width = self.char_width(ch, size);
+                if line_width + ch_width > width && !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0.0;
+                }
+                line.push(ch);
+                line_width += ch_width;
+            }
+            lines.push(line);
+        }
+
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+            if let Some(last) = lines.last_mut() {
+                let ellipsis = self.char_width('…', size);
+                while !last.is_empty() && self.text_width(last, size) + ellipsis > width {
+                    last.pop();
+                }
+                last.push('…');
+            }
+        }
+        lines
+    }
+}
+
+/// 排版器：把报告内容写入各页的内容流
+struct Layout {
+    encoder: FontEncoder,
+    pages: Vec<String>,
+    current: String,
+    y: f32,
+}
+
+impl Layout {
+    fn new(font: PdfFont) -> Self {
+        Self {
+            encoder: FontEncoder::new(font),
+            pages: Vec::new(),
+            current: String::new(),
+            y: CONTENT_TOP,
+        }
+    }
+
+    fn content_width() -> f32 {
+        PAGE_WIDTH - MARGIN * 2.0
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(std::mem::take(&mut self.current));
+        self.y = CONTENT_TOP;
+    }
+
+    /// 剩余空间不足时换页，返回是否换了页
+    fn ensure_space(&mut self, height: f32) -> bool {
+        if self.y - height < CONTENT_BOTTOM && self.y < CONTENT_TOP {
+            self.new_page();
+            return true;
+        }
+        false
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, gray: f32, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let encoded = self.encoder.encode(text);
+        let _ = writeln!(
+            self.current,
+            "BT 0 Tr {:.3} g /F1 {:.1} Tf {:.2} {:.2} Td {} Tj ET",
+            gray, size, x, y, encoded
+        );
+    }
+
+    /// 加粗文本（填充并描边）
+    fn bold_text(&mut self, x: f32, y: f32, size: f32, text: &str) {
+        let encoded = self.encoder.encode(text);
+        let _ = writeln!(
+            self.current,
+            "BT 0 g 0 G 2 Tr {:.2} w /F1 {:.1} Tf {:.2} {:.2} Td {} Tj ET",
+            size / 30.0,
+            size,
+            x,
+            y,
+            encoded
+        );
+    }
+
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: (f32, f32, f32)) {
+        let _ = writeln!(
+            self.current,
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f",
+            color.0, color.1, color.2, x, y, width, height
+        );
+    }
+
+    fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let _ = writeln!(
+            self.current,
+            "0.75 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S",
+            x, y, width, height
+        );
+    }
+
+    fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let _ = writeln!(
+            self.current,
+            "0.7 G 0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            x1, y1, x2, y2
+        );
+    }
+
+    /// 排版整份报告
+    fn report(&mut self, report: &Report) {
+        self.bold_text(MARGIN, self.y - 20.0, 20.0, &report.title);
+        self.text(MARGIN, self.y - 40.0, BODY_SIZE, 0.4, &report.subtitle);
+        self.y -= 56.0;
+
+        for section in &report.sections {
+            self.heading(&section.title);
+            for block in &section.blocks {
+                match block {
+                    ReportBlock::Fields(fields) => self.fields(fields),
+                    ReportBlock::Table(table) => self.table(table),
+                    ReportBlock::BarChart(chart) => self.chart(chart),
+                }
+                self.y -= 8.0;
+            }
+        }
+        self.pages.push(std::mem::take(&mut self.current));
+    }
+
+    fn heading(&mut self, title: &str) {
+        // 标题至少要和下面的一行内容在同一页
+        self.ensure_space(26.0 + BODY_LEADING * 2.0);
+        self.y -= 10.0;
+        self.fill_rect(MARGIN, self.y - 14.0, 3.0, 14.0, BAR_COLORS[0]);
+        self.bold_text(MARGIN + 8.0, self.y - 12.0, 13.0, title);
+        self.y -= 24.0;
+    }
+
+    fn fields(&mut self, fields: &[(String, String)]) {
+        let label_width = fields
+            .iter()
+            .map(|(name, _)| self.encoder.text_width(name, BODY_SIZE))
+            .fold(0.0, f32::max)
+            + 16.0;
+
+        for (name, value) in fields {
+            let lines = self.encoder.wrap(
+                value,
+                BODY_SIZE,
+                Self::content_width() - label_width,
+                MAX_CELL_LINES,
+            );
+            self.ensure_space(BODY_LEADING * lines.len() as f32);
+            self.text(MARGIN, self.y - BODY_SIZE, BODY_SIZE, 0.35, name);
+            for line in lines {
+                self.text(
+                    MARGIN + label_width,
+                    self.y - BODY_SIZE,
+                    BODY_SIZE,
+                    0.0,
+                    &line,
+                );
+                self.y -= BODY_LEADING;
+            }
+        }
+    }
+
+    fn table(&mut self, table: &ReportTable) {
+        let total: f32 = table.widths.iter().sum::<f32>().max(f32::EPSILON);
+        let widths: Vec<f32> = (0..table.headers.len())
+            .map(|i| table.widths.get(i).copied().unwrap_or(1.0) / total * Self::content_width())
+            .collect();
+
+        self.ensure_space(self.row_height(&table.headers, &widths) * 2.0);
+        self.table_row(&table.headers, &widths, true);
+
+        for row in &table.rows {
+            let height = self.row_height(row, &widths);
+            if self.ensure_space(height) {
+                // 跨页时重复表头
+                self.table_row(&table.headers, &widths, true);
+            }
+            self.table_row(row, &widths, false);
+        }
+
+        if table.rows.is_empty() {
+            self.ensure_space(BODY_LEADING);
+            self.text(MARGIN, self.y - 12.0, TABLE_SIZE, 0.5, "（无数据）");
+            self.y -= BODY_LEADING;
+        }
+    }
+
+    fn cell_lines(&self, cells: &[String], widths: &[f32]) -> Vec<Vec<String>> {
+        widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| {
+                let text = cells.get(i).map(String::as_str).unwrap_or("");
+                self.encoder
+                    .wrap(text, TABLE_SIZE, width - CELL_PADDING * 2.0, MAX_CELL_LINES)
+            })
+            .collect()
+    }
+
+    fn row_height(&self, cells: &[String], widths: &[f32]) -> f32 {
+        let lines = self
+            .cell_lines(cells, widths)
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(1);
+        lines as f32 * TABLE_LEADING + CELL_PADDING * 2.0
+    }
+
+    fn table_row(&mut self, cells: &[String], widths: &[f32], header: bool) {
+        let height = self.row_height(cells, widths);
+        let top = self.y;
+        if header {
+            self.fill_rect(
+                MARGIN,
+                top - height,
+                Self::content_width(),
+                height,
+                (0.93, 0.94, 0.96),
+            );
+        }
+
+        let mut x = MARGIN;
+        for (lines, width) in self.cell_lines(cells, widths).into_iter().zip(widths) {
+            self.stroke_rect(x, top - height, *width, height);
+            for (index, line) in lines.iter().enumerate() {
+                let baseline = top - CELL_PADDING - TABLE_SIZE - index as f32 * TABLE_LEADING;
+                if header {
+                    self.bold_text(x + CELL_PADDING, baseline, TABLE_SIZE, line);
+                } else {
+                    self.text(x + CELL_PADDING, baseline, TABLE_SIZE, 0.1, line);
+                }
+            }
+            x += width;
+        }
+        self.y -= height;
+    }
+
+    fn chart(&mut self, chart: &ReportChart) {
+        self.ensure_space(BODY_LEADING + BAR_HEIGHT + BAR_GAP);
+        self.bold_text(MARGIN, self.y - BODY_SIZE, BODY_SIZE, &chart.title);
+        self.y -= BODY_LEADING + 2.0;
+
+        let label_width = (chart
+            .bars
+            .iter()
+            .map(|bar| self.encoder.text_width(&bar.label, TABLE_SIZE))
+            .fold(0.0, f32::max)
+            + 10.0)
+            .min(Self::content_width() * 0.3);
+        let value_width = chart
+            .bars
+            .iter()
+            .map(|bar| self.encoder.text_width(&bar.display, TABLE_SIZE))
+            .fold(0.0, f32::max)
+            + 10.0;
+        let bar_area = (Self::content_width() - label_width - value_width).max(40.0);
+        let max_value = chart.max_value();
+
+        for (index, bar) in chart.bars.iter().enumerate() {
+            self.ensure_space(BAR_HEIGHT + BAR_GAP);
+            let bottom = self.y - BAR_HEIGHT;
+            let label = self
+                .encoder
+                .wrap(&bar.label, TABLE_SIZE, label_width - 10.0, 1)
+                .remove(0);
+            self.text(MARGIN, bottom + 2.0, TABLE_SIZE, 0.2, &label);
+
+            let length = if max_value > 0.0 {
+                (bar.value.max(0.0) / max_value) as f32 * bar_area
+            } else {
+                0.0
+            };
+            let color = BAR_COLORS[index % BAR_COLORS.len()];
+            self.fill_rect(
+                MARGIN + label_width,
+                bottom,
+                length.max(1.0),
+                BAR_HEIGHT,
+                color,
+            );
+            self.text(
+                MARGIN + label_width + length + 6.0,
+                bottom + 2.0,
+                TABLE_SIZE,
+                0.35,
+                &bar.display,
+            );
+            self.y -= BAR_HEIGHT + BAR_GAP;
+        }
+    }
+
+    /// 为每页添加页眉和页脚
+    fn decorate(&mut self, report: &Report) {
+        let total = self.pages.len();
+        let mut pages = std::mem::take(&mut self.pages);
+        for (index, page) in pages.iter_mut().enumerate() {
+            self.current = std::mem::take(page);
+
+            let header_y = PAGE_HEIGHT - 36.0;
+            self.text(MARGIN, header_y, 8.0, 0.45, &report.title);
+            let subtitle_width = self.encoder.text_width(&report.subtitle, 8.0);
+            self.text(
+                PAGE_WIDTH - MARGIN - subtitle_width,
+                header_y,
+                8.0,
+                0.45,
+                &report.subtitle,
+            );
+            self.line(MARGIN, header_y - 6.0, PAGE_WIDTH - MARGIN, header_y - 6.0);
+
+            let footer = format!("第 {} 页 / 共 {} 页", index + 1, total);
+            let footer_width = self.encoder.text_width(&footer, 8.0);
+            self.line(MARGIN, 40.0, PAGE_WIDTH - MARGIN, 40.0);
+            self.text((PAGE_WIDTH - footer_width) / 2.0, 28.0, 8.0, 0.45, &footer);
+
+            *page = std::mem::take(&mut self.current);
+        }
+        self.pages = pages;
+    }
+}
+
+/// PDF 对象写出器
+struct PdfWriter {
+    buffer: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn new(object_count: usize) -> Self {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n");
+        Self {
+            buffer,
+            offsets: vec![0; object_count + 1],
+        }
+    }
+
+    fn object(&mut self, id: usize, body: &str) {
+        self.offsets[id] = self.buffer.len();
+        self.buffer
+            .extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", id, body).as_bytes());
+    }
+
+    /// 写出压缩的流对象
+    fn stream(&mut self, id: usize, dictionary: &str, data: &[u8]) -> Result<()> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        self.offsets[id] = self.buffer.len();
+        self.buffer.extend_from_slice(
+            format!(
+                "{} 0 obj\n<< {} /Length {} /Filter /FlateDecode >>\nstream\n",
+                id,
+                dictionary,
+                compressed.len()
+            )
+            .as_bytes(),
+        );
+        self.buffer.extend_from_slice(&compressed);
+        self.buffer.extend_from_slice(b"\nendstream\nendobj\n");
+        Ok(())
+    }
+
+    fn finish(mut self, info: &str) -> Vec<u8> {
+        let info_id = self.offsets.len();
+        self.offsets.push(0);
+        self.object(info_id, info);
+
+        let xref = self.buffer.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len());
+        for offset in &self.offsets[1..] {
+            let _ = writeln!(trailer, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len(),
+            info_id,
+            xref
+        );
+        self.buffer.extend_from_slice(trailer.as_bytes());
+        self.buffer
+    }
+}
+
+/// 字体对象编号
+const CATALOG_ID: usize = 1;
+const PAGES_ID: usize = 2;
+const FONT_ID: usize = 3;
+const CID_FONT_ID: usize = 4;
+const DESCRIPTOR_ID: usize = 5;
+const FONT_FILE_ID: usize = 6;
+const TO_UNICODE_ID: usize = 7;
+const FIRST_PAGE_ID: usize = 8;
+
+/// 将报告写出为 PDF
+pub fn write_report<W: Write>(report: &Report, font: PdfFont, writer: &mut W) -> Result<()> {
+    let mut layout = Layout::new(font);
+    layout.report(report);
+    layout.decorate(report);
+
+    let page_count = layout.pages.len();
+    let mut pdf = PdfWriter::new(FIRST_PAGE_ID + page_count * 2 - 1);
+
+    pdf.object(
+        CATALOG_ID,
+        &format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES_ID),
+    );
+    let kids: Vec<String> = (0..page_count)
+        .map(|i| format!("{} 0 R", FIRST_PAGE_ID + i * 2))
+        .collect();
+    pdf.object(
+        PAGES_ID,
+        &format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            page_count
+        ),
+    );
+
+    for (index, content) in layout.pages.iter().enumerate() {
+        let page_id = FIRST_PAGE_ID + index * 2;
+        pdf.object(
+            page_id,
+            &format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 {} 0 R >> >> /Contents {} 0 R >>",
+                PAGES_ID,
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                FONT_ID,
+                page_id + 1
+            ),
+        );
+        pdf.stream(page_id + 1, "", content.as_bytes())?;
+    }
+
+    write_font(&mut pdf, &layout.encoder)?;
+
+    let info = format!(
+        "<< /Title {} /Producer (LifeTracker {}) /CreationDate (D:{}) >>",
+        text_string(&report.title),
+        env!("CARGO_PKG_VERSION"),
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    );
+    writer.write_all(&pdf.finish(&info))?;
+    Ok(())
+}
+
+/// 写出字体相关对象
+fn write_font(pdf: &mut PdfWriter, encoder: &FontEncoder) -> Result<()> {
+    match &encoder.font {
+        PdfFont::Embedded(font) => {
+            let glyphs: BTreeSet<u16> = encoder.used.keys().copied().collect();
+            let base_font = format!("{}+{}", subset_tag(&glyphs), font.name);
+            let scale = 1000.0 / font.units_per_em as f32;
+
+            pdf.object(
+                FONT_ID,
+                &format!(
+                    "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+                    base_font, CID_FONT_ID, TO_UNICODE_ID
+                ),
+            );
+
+            let widths: Vec<String> = glyphs
+                .iter()
+                .map(|&glyph| format!("{} [{:.0}]", glyph, font.advance(glyph) as f32 * scale))
+                .collect();
+            pdf.object(
+                CID_FONT_ID,
+                &format!(
+                    "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> /FontDescriptor {} 0 R /DW 1000 /W [{}] /CIDToGIDMap /Identity >>",
+                    base_font,
+                    DESCRIPTOR_ID,
+                    widths.join(" ")
+                ),
+            );
+
+            let [x_min, y_min, x_max, y_max] = font.bbox.map(|v| (v as f32 * scale) as i32);
+            let ascent = (font.ascent as f32 * scale) as i32;
+            let descent = (font.descent as f32 * scale) as i32;
+            pdf.object(
+                DESCRIPTOR_ID,
+                &format!(
+                    "<< /Type /FontDescriptor /FontName /{} /Flags 4 /FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 {} 0 R >>",
+                    base_font, x_min, y_min, x_max, y_max, ascent, descent, ascent, FONT_FILE_ID
+                ),
+            );
+
+            let subset = font.subset(&glyphs);
+            pdf.stream(FONT_FILE_ID, &format!("/Length1 {}", subset.len()), &subset)?;
+            pdf.stream(TO_UNICODE_ID, "", to_unicode_cmap(&encoder.used).as_bytes())?;
+        }
+        PdfFont::Standard => {
+            pdf.object(
+                FONT_ID,
+                &format!(
+                    "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light /Encoding /UniGB-UCS2-H /DescendantFonts [{} 0 R] >>",
+                    CID_FONT_ID
+                ),
+            );
+            pdf.object(
+                CID_FONT_ID,
+                &format!(
+                    "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> /FontDescriptor {} 0 R /DW 1000 /W [1 95 500] >>",
+                    DESCRIPTOR_ID
+                ),
+            );
+            pdf.object(
+                DESCRIPTOR_ID,
+                "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 880 /StemV 93 >>",
+            );
+            // 未使用的对象编号写为空对象，保持交叉引用表连续
+            pdf.object(FONT_FILE_ID, "null");
+            pdf.object(TO_UNICODE_ID, "null");
+        }
+    }
+    Ok(())
+}
+
+/// 子集字体名前缀（6个大写字母）
+fn subset_tag(glyphs: &BTreeSet<u16>) -> String {
+    let hash = glyphs.iter().fold(0x811C_9DC5u32, |hash, &glyph| {
+        (hash ^ glyph as u32).wrapping_mul(0x0100_0193)
+    });
+    (0..6)
+        .map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char)
+        .collect()
+}
+
+/// 生成字符码到 Unicode 的映射（用于复制和搜索文本）
+fn to_unicode_cmap(used: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<(&u16, &char)> = used.iter().collect();
+    for chunk in entries.chunks(100) {
+        let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+        for (code, ch) in chunk {
+            let mut units = [0u16; 2];
+            let hex: String = ch
+                .encode_utf16(&mut units)
+                .iter()
+                .map(|unit| format!("{:04X}", unit))
+                .collect();
+            let _ = writeln!(cmap, "<{:04X}> <{}>", code, hex);
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+/// PDF 文本字符串（UTF-16BE 十六进制）
+fn text_string(text: &str) -> String {
+    let hex: String = text
+        .encode_utf16()
+        .map(|unit| format!("{:04X}", unit))
+        .collect();
+    format!("<FEFF{}>", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::report::{ChartBar, ReportSection};
+
+    fn sample_report(rows: usize) -> Report {
+        Report {
+            title: "测试报告".to_string(),
+            subtitle: "生成于 2024-01-01 09:00".to_string(),
+            sections: vec![ReportSection {
+                title: "时间记录".to_string(),
+                blocks: vec![
+                    ReportBlock::BarChart(ReportChart {
+                        title: "分类时间分布".to_string(),
+                        bars: vec![
+                            ChartBar {
+                                label: "工作".to_string(),
+                                value: 3.0,
+                                display: "3h 0m 0s".to_string(),
+                            },
+                            ChartBar {
+                                label: "学习".to_string(),
+                                value: 1.5,
+                                display: "1h 30m 0s".to_string(),
+                            },
+                        ],
+                    }),
+                    ReportBlock::Table(ReportTable {
+                        headers: vec!["任务名称".to_string(), "描述".to_string()],
+                        widths: vec![1.0, 2.0],
+                        rows: (0..rows)
+                            .map(|i| vec![format!("任务{}", i), "编写 Rust 代码".to_string()])
+                            .collect(),
+                    }),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_write_report_paginates() {
+        let mut output = Vec::new();
+        write_report(&sample_report(200), PdfFont::Standard, &mut output).unwrap();
+
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.starts_with("%PDF-1.5"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/BaseFont /STSong-Light"));
+
+        let count: usize = text
+            .split("/Count ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|count| count.parse().ok())
+            .unwrap();
+        assert!(count > 1, "200 行表格应该分成多页");
+    }
+
+    #[test]
+    fn test_wrap_limits_lines() {
+        let encoder = FontEncoder::new(PdfFont::Standard);
+        let lines = encoder.wrap(&"中".repeat(100), 10.0, 50.0, 3);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].ends_with('…'));
+        assert!(lines
+            .iter()
+            .all(|line| encoder.text_width(line, 10.0) <= 50.0));
+    }
+}
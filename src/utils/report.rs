@@ -0,0 +1,614 @@
+//! # 报告模型模块
+//!
+//! 将导出数据整理为与输出格式无关的报告结构（标题、字段、表格和柱状图），
+//! HTML 和 PDF 导出都基于同一个报告模型渲染
+
+use crate::utils::export::{ExportData, ExportOptions};
+use std::collections::HashMap;
+
+/// 报告
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// 报告标题
+    pub title: String,
+    /// 副标题（生成时间等）
+    pub subtitle: String,
+    /// 报告章节
+    pub sections: Vec<ReportSection>,
+}
+
+/// 报告章节
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportSection {
+    /// 章节标题
+    pub title: String,
+    /// 章节内容
+    pub blocks: Vec<ReportBlock>,
+}
+
+/// 报告内容块
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportBlock {
+    /// 键值字段列表
+    Fields(Vec<(String, String)>),
+    /// 表格
+    Table(ReportTable),
+    /// 柱状图
+    BarChart(ReportChart),
+}
+
+/// 报告表格
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTable {
+    /// 列标题
+    pub headers: Vec<String>,
+    /// 各列的相对宽度
+    pub widths: Vec<f32>,
+    /// 数据行
+    pub rows: Vec<Vec<String>>,
+}
+
+/// 报告柱状图
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportChart {
+    /// 图表标题
+    pub title: String,
+    /// 柱条
+    pub bars: Vec<ChartBar>,
+}
+
+/// 柱状图中的一根柱条
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartBar {
+    /// 标签
+    pub label: String,
+    /// 数值（决定柱条长度）
+    pub value: f64,
+    /// 显示的数值文本
+    pub display: String,
+}
+
+impl ReportChart {
+    /// 最大数值（用于计算柱条比例）
+    pub fn max_value(&self) -> f64 {
+        self.bars.iter().map(|bar| bar.value).fold(0.0, f64::max)
+    }
+}
+
+/// 每日时间柱状图最多显示的天数
+const MAX_DAILY_BARS: usize = 31;
+
+impl Report {
+    /// 从导出数据生成报告
+    pub fn from_export(data: &ExportData, options: &ExportOptions) -> Self {
+        let mut sections = Vec::new();
+
+        // 导出信息
+        if options.include_metadata {
+            sections.push(ReportSection {
+                title: "导出信息".to_string(),
+                blocks: vec![ReportBlock::Fields(vec![
+                    (
+                        "导出时间".to_string(),
+                        data.metadata
+                            .export_time
+                            .format("%Y-%m-%d %H:%M:%S")
+                            .to_string(),
+                    ),
+                    ("版本".to_string(), data.metadata.version.clone()),
+                    (
+                        "记录总数".to_string(),
+                        data.metadata.total_entries.to_string(),
+                    ),
+                    (
+                        "分类总数".to_string(),
+                        data.metadata.total_categories.to_string(),
+                    ),
+                ])],
+            });
+        }
+
+        // 时间统计
+        if let Some(ref stats) = data.statistics {
+            let mut fields = vec![
+                (
+                    "总时间".to_string(),
+                    crate::utils::format_duration(stats.total_time),
+                ),
+                (
+                    "平均会话时间".to_string(),
+                    crate::utils::format_duration(stats.average_session_time),
+                ),
+            ];
+            if let Some(ref day) = stats.most_productive_day {
+                fields.push(("最高效的一天".to_string(), day.clone()));
+            }
+            if let Some(ref category) = stats.most_used_category {
+                fields.push(("最常用分类".to_string(), category.clone()));
+            }
+
+            let mut blocks = vec![ReportBlock::Fields(fields)];
+            if !stats.category_breakdown.is_empty() {
+                blocks.push(ReportBlock::BarChart(duration_chart(
+                    "分类时间分布",
+                    &stats.category_breakdown,
+                    true,
+                )));
+            }
+            if !stats.daily_totals.is_empty() {
+                blocks.push(ReportBlock::BarChart(duration_chart(
+                    "每日时间",
+                    &stats.daily_totals,
+                    false,
+                )));
+            }
+
+            sections.push(ReportSection {
+                title: "统计信息".to_string(),
+                blocks,
+            });
+        }
+
+        // 财务汇总
+        if let Some(ref financial) = data.financial {
+            let stats = &financial.stats;
+            let mut blocks = vec![ReportBlock::Fields(vec![
+                (
+                    "统计期间".to_string(),
+                    format!(
+                        "{} 至 {}",
+                        stats.period_start.format("%Y-%m-%d"),
+                        stats.period_end.format("%Y-%m-%d")
+                    ),
+                ),
+                (
+                    "总收入".to_string(),
+                    format_money(stats.total_income, &stats.currency),
+                ),
+                (
+                    "总支出".to_string(),
+                    format_money(stats.total_expense, &stats.currency),
+                ),
+                (
+                    "净收入".to_string(),
+                    format_money(stats.net_income, &stats.currency),
+                ),
+                (
+                    "账户余额".to_string(),
+                    format_money(stats.account_balance, &stats.currency),
+                ),
+                ("交易笔数".to_string(), stats.transaction_count.to_string()),
+            ])];
+
+            if !financial.category_breakdown.is_empty() {
+                blocks.push(ReportBlock::BarChart(ReportChart {
+                    title: "分类支出".to_string(),
+                    bars: financial
+                        .category_breakdown
+                        .iter()
+                        .map(|item| ChartBar {
+                            label: item.category_name.clone(),
+                            value: item.amount,
+                            display: format!(
+                                "{} ({:.1}%)",
+                                format_money(item.amount, &stats.currency),
+                                item.percentage
+                            ),
+                        })
+                        .collect(),
+                }));
+            }
+
+            if !financial.monthly_trends.is_empty() {
+                blocks.push(ReportBlock::Table(ReportTable {
+                    headers: vec![
+                        "月份".to_string(),
+                        "收入".to_string(),
+                        "支出".to_string(),
+                        "净收入".to_string(),
+                    ],
+                    widths: vec![1.0, 1.0, 1.0, 1.0],
+                    rows: financial
+                        .monthly_trends
+                        .iter()
+                        .map(|trend| {
+                            vec![
+                                trend.month.clone(),
+                                format_money(trend.income, &stats.currency),
+                                format_money(trend.expense, &stats.currency),
+                                format_money(trend.net, &stats.currency),
+                            ]
+                        })
+                        .collect(),
+                }));
+            }
+
+            sections.push(ReportSection {
+                title: "财务汇总".to_string(),
+                blocks,
+            });
+        }
+
+        // 分类列表
+        if options.include_categories && !data.categories.is_empty() {
+            sections.push(ReportSection {
+                title: "分类列表".to_string(),
+                blocks: vec![ReportBlock::Table(ReportTable {
+                    headers: vec![
+                        "名称".to_string(),
+                        "描述".to_string(),
+                        "颜色".to_string(),
+                        "创建时间".to_string(),
+                    ],
+                    widths: vec![1.2, 2.4, 0.9, 1.5],
+                    rows: data
+                        .categories
+                        .iter()
+                        .map(|category| {
+                            vec![
+                                category.name.clone(),
+                                category.description.clone().unwrap_or_default(),
+                                category.color.to_hex(),
+                                category.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                            ]
+                        })
+                        .collect(),
+                })],
+            });
+        }
+
+        // 任务
+        if !data.tasks.is_empty() {
+            sections.push(ReportSection {
+                title: "任务".to_string(),
+                blocks: vec![ReportBlock::Table(ReportTable {
+                    headers: TASK_HEADERS.iter().map(|h| h.to_string()).collect(),
+                    widths: vec![2.0, 0.8, 0.8, 1.3, 1.3, 1.0],
+                    rows: task_rows(data),
+                })],
+            });
+        }
+
+        // 时间记录
+        let category_map: HashMap<String, String> = data
+            .categories
+            .iter()
+            .map(|c| (c.id.to_string(), c.name.clone()))
+            .collect();
+
+        let rows = data
+            .time_entries
+            .iter()
+            .map(|entry| {
+                let category_name = category_map
+                    .get(
+                        &entry
+                            .category_id
+                            .as_ref()
+                            .map(|id| id.to_string())
+                            .unwrap_or_default(),
+                    )
+                    .map(|s| s.as_str())
+                    .unwrap_or("未知分类");
+
+                let end_time = entry
+                    .end_time
+                    .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "进行中".to_string());
+
+                let duration = if let Some(end) = entry.end_time {
+                    let dur = end.signed_duration_since(entry.start_time);
+                    crate::utils::format_duration(dur)
+                } else {
+                    "进行中".to_string()
+                };
+
+                vec![
+                    entry.task_name.clone(),
+                    category_name.to_string(),
+                    entry.start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    end_time,
+                    duration,
+                    entry.description.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+
+        sections.push(ReportSection {
+            title: "时间记录".to_string(),
+            blocks: vec![ReportBlock::Table(ReportTable {
+                headers: vec![
+                    "任务名称".to_string(),
+                    "分类".to_string(),
+                    "开始时间".to_string(),
+                    "结束时间".to_string(),
+                    "持续时间".to_string(),
+                    "描述".to_string(),
+                ],
+                widths: vec![1.6, 1.0, 1.6, 1.6, 1.1, 2.0],
+                rows,
+            })],
+        });
+
+        Self {
+            title: "TimeTracker 导出报告".to_string(),
+            subtitle: format!(
+                "生成于 {}",
+                data.metadata.export_time.format("%Y-%m-%d %H:%M")
+            ),
+            sections,
+        }
+    }
+}
+
+/// 由时长统计生成柱状图（按时长降序或按标签升序排列）
+fn duration_chart(
+    title: &str,
+    durations: &HashMap<String, chrono::Duration>,
+    by_value: bool,
+) -> ReportChart {
+    let mut items: Vec<(&String, &chrono::Duration)> = durations.iter().collect();
+    if by_value {
+        items.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    } else {
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        // 只保留最近的若干天
+        let skip = items.len().saturating_sub(MAX_DAILY_BARS);
+        items.drain(..skip);
+    }
+
+    ReportChart {
+        title: title.to_string(),
+        bars: items
+            .into_iter()
+            .map(|(label, duration)| ChartBar {
+                label: label.clone(),
+                value: duration.num_seconds() as f64 / 3600.0,
+                display: crate::utils::format_duration(*duration),
+            })
+            .collect(),
+    }
+}
+
+/// 任务表格的列
+pub(crate) const TASK_HEADERS: [&str; 6] =
+    ["任务", "状态", "优先级", "截止日期", "完成时间", "已用时间"];
+
+/// 任务表格的行（状态按导出时间判断是否逾期）
+pub(crate) fn task_rows(data: &ExportData) -> Vec<Vec<String>> {
+    let format_time = |time: Option<chrono::DateTime<chrono::Local>>| {
+        time.map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default()
+    };
+
+    data.tasks
+        .iter()
+        .map(|task| {
+            let status = if task.is_completed {
+                "已完成"
+            } else if task
+                .due_date
+                .is_some_and(|due| due < data.metadata.export_time)
+            {
+                "已逾期"
+            } else {
+                "未完成"
+            };
+            vec![
+                task.name.clone(),
+                status.to_string(),
+                task.priority.clone(),
+                format_time(task.due_date),
+                format_time(task.completed_at),
+                crate::utils::format_duration(chrono::Duration::seconds(
+                    task.total_duration_seconds,
+                )),
+            ]
+        })
+        .collect()
+}
+
+/// 格式化金额
+fn format_money(amount: f64, currency: &str) -> String {
+    format!("{:.2} {}", amount, currency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::category::{CategoryColor, CategoryIcon};
+    use crate::core::Category;
+    use crate::storage::models::TimeEntry;
+    use crate::storage::task_models::TaskModel;
+    use crate::utils::export::{ExportMetadata, ExportStatistics};
+    use chrono::{Duration, Local, TimeZone};
+    use uuid::Uuid;
+
+    fn time(day: u32, hour: u32, minute: u32) -> chrono::DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn task(name: &str, due: Option<u32>, completed: bool) -> TaskModel {
+        TaskModel {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: None,
+            category_id: None,
+            status: "pending".to_string(),
+            priority: "high".to_string(),
+            estimated_duration_seconds: None,
+            total_duration_seconds: 5400,
+            tags: "[]".to_string(),
+            due_date: due.map(|day| time(day, 12, 0)),
+            is_completed: completed,
+            completed_at: completed.then(|| time(9, 20, 0)),
+            created_at: time(1, 8, 0),
+            updated_at: None,
+        }
+    }
+
+    fn sample_data() -> ExportData {
+        let category_id = Uuid::new_v4();
+        let categories = vec![Category {
+            id: category_id,
+            name: "工作".to_string(),
+            description: None,
+            color: CategoryColor::Blue,
+            icon: CategoryIcon::Work,
+            created_at: time(1, 8, 0),
+            updated_at: time(1, 8, 0),
+            daily_target: None,
+            weekly_target: None,
+            target_duration: None,
+            is_active: true,
+            sort_order: 0,
+            parent_id: None,
+        }];
+        let entry = |name: &str, category_id, end_time| TimeEntry {
+            id: Uuid::new_v4(),
+            task_name: name.to_string(),
+            category_id,
+            start_time: time(10, 9, 0),
+            end_time,
+            duration_seconds: 0,
+            description: None,
+            tags: vec![],
+            created_at: time(10, 9, 0),
+            updated_at: None,
+        };
+
+        ExportData {
+            metadata: ExportMetadata {
+                export_time: time(10, 18, 0),
+                version: "1.0.0".to_string(),
+                total_entries: 2,
+                total_categories: 1,
+                date_range: None,
+                filters_applied: vec![],
+            },
+            categories,
+            time_entries: vec![
+                entry("写代码", Some(category_id), Some(time(10, 10, 30))),
+                entry("散步", Some(Uuid::new_v4()), None),
+            ],
+            statistics: Some(ExportStatistics {
+                total_time: Duration::minutes(90),
+                average_session_time: Duration::minutes(90),
+                category_breakdown: HashMap::from([
+                    ("工作".to_string(), Duration::hours(2)),
+                    ("学习".to_string(), Duration::hours(2)),
+                    ("运动".to_string(), Duration::minutes(30)),
+                ]),
+                daily_totals: HashMap::new(),
+                most_productive_day: None,
+                most_used_category: Some("工作".to_string()),
+            }),
+            financial: None,
+            tasks: vec![
+                task("已完成的任务", Some(5), true),
+                task("逾期的任务", Some(9), false),
+                task("未到期的任务", Some(11), false),
+                task("没有截止日期", None, false),
+            ],
+        }
+    }
+
+    fn section_titles(report: &Report) -> Vec<&str> {
+        report.sections.iter().map(|s| s.title.as_str()).collect()
+    }
+
+    fn table<'a>(report: &'a Report, title: &str) -> &'a ReportTable {
+        let section = report.sections.iter().find(|s| s.title == title).unwrap();
+        match section.blocks.last() {
+            Some(ReportBlock::Table(table)) => table,
+            other => panic!("{} 不是表格: {:?}", title, other),
+        }
+    }
+
+    #[test]
+    fn test_sections_follow_options_and_data() {
+        let data = sample_data();
+        let report = Report::from_export(&data, &ExportOptions::default());
+        assert_eq!(report.title, "TimeTracker 导出报告");
+        assert_eq!(report.subtitle, "生成于 2024-03-10 18:00");
+        assert_eq!(
+            section_titles(&report),
+            vec!["导出信息", "统计信息", "分类列表", "任务", "时间记录"]
+        );
+
+        // 关闭元数据和分类列表、没有统计和任务时只剩时间记录
+        let mut data = data;
+        data.statistics = None;
+        data.tasks.clear();
+        let options = ExportOptions {
+            include_metadata: false,
+            include_categories: false,
+            ..ExportOptions::default()
+        };
+        let report = Report::from_export(&data, &options);
+        assert_eq!(section_titles(&report), vec!["时间记录"]);
+
+        // 不导出分类列表时，时间记录中仍显示分类名称
+        let rows = &table(&report, "时间记录").rows;
+        assert_eq!(
+            rows[0],
+            vec![
+                "写代码",
+                "工作",
+                "2024-03-10 09:00:00",
+                "2024-03-10 10:30:00",
+                "1h 30m 0s",
+                ""
+            ]
+        );
+        // 分类已不存在、记录尚未结束
+        assert_eq!(rows[1][1], "未知分类");
+        assert_eq!(rows[1][3], "进行中");
+        assert_eq!(rows[1][4], "进行中");
+    }
+
+    #[test]
+    fn test_duration_chart_ordering() {
+        let data = sample_data();
+        let breakdown = &data.statistics.as_ref().unwrap().category_breakdown;
+
+        // 按时长降序，时长相同时按标签排序
+        let chart = duration_chart("分类时间分布", breakdown, true);
+        let labels: Vec<&str> = chart.bars.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["学习", "工作", "运动"]);
+        assert_eq!(chart.bars[2].value, 0.5);
+        assert_eq!(chart.bars[2].display, "30m 0s");
+        assert_eq!(chart.max_value(), 2.0);
+
+        // 每日时间按日期排列，只保留最近的 31 天
+        let daily: HashMap<String, Duration> = (0..40)
+            .map(|i| {
+                let day = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Duration::days(i);
+                (day.format("%Y-%m-%d").to_string(), Duration::minutes(i + 1))
+            })
+            .collect();
+        let chart = duration_chart("每日时间", &daily, false);
+        assert_eq!(chart.bars.len(), MAX_DAILY_BARS);
+        assert_eq!(chart.bars[0].label, "2024-01-10");
+        assert_eq!(chart.bars.last().unwrap().label, "2024-02-09");
+        assert!(chart.bars.windows(2).all(|w| w[0].label < w[1].label));
+    }
+
+    #[test]
+    fn test_task_rows_and_money() {
+        let rows = task_rows(&sample_data());
+        let statuses: Vec<&str> = rows.iter().map(|row| row[1].as_str()).collect();
+        // 逾期按导出时间（3 月 10 日）判断
+        assert_eq!(statuses, vec!["已完成", "已逾期", "未完成", "未完成"]);
+        assert_eq!(rows[0][3], "2024-03-05 12:00");
+        assert_eq!(rows[0][4], "2024-03-09 20:00");
+        assert_eq!(rows[3][3], "");
+        assert_eq!(rows[3][5], "1h 30m 0s");
+        assert!(rows.iter().all(|row| row.len() == TASK_HEADERS.len()));
+
+        assert_eq!(format_money(1234.567, "CNY"), "1234.57 CNY");
+        assert_eq!(format_money(-35.5, "USD"), "-35.50 USD");
+    }
+}
@@ -0,0 +1,679 @@
+//! # 定时报告模块
+//!
+//! 按设置中的报告定义生成 HTML、Markdown 等格式的报告文件。日期范围相对于运行时间计算
+//! （如"上周"），报告由 cron 调度器在后台定时生成，每次运行的结果追加到运行日志
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio_cron_scheduler::{Job, JobScheduler};
+use uuid::Uuid;
+
+use crate::config::{ReportModule, ReportRange, ScheduledReport};
+use crate::core::Category;
+use crate::storage::backend::TimeRange;
+use crate::storage::StorageBackend;
+use crate::utils::export::finance::load_financial_summary;
+use crate::utils::export::{
+    create_export_data, DataExporter, ExportData, ExportFormat, ExportOptions,
+};
+
+/// 运行日志文件名
+const RUN_LOG_FILE: &str = "report_runs.jsonl";
+
+/// 报告运行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRunStatus {
+    /// 生成成功
+    Success,
+    /// 生成失败
+    Failed,
+}
+
+/// 运行日志中的一条记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportRunRecord {
+    /// 报告ID
+    pub report_id: String,
+    /// 报告名称
+    pub report_name: String,
+    /// 开始时间
+    pub started_at: DateTime<Local>,
+    /// 结束时间
+    pub finished_at: DateTime<Local>,
+    /// 报告覆盖的日期范围
+    pub range: TimeRange,
+    /// 运行结果
+    pub status: ReportRunStatus,
+    /// 生成的文件
+    pub output: Option<PathBuf>,
+    /// 报告包含的时间记录数
+    pub entries: usize,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 默认的运行日志路径（应用数据目录下）
+pub fn default_run_log_path() -> Result<PathBuf> {
+    Ok(crate::utils::get_app_data_dir()?.join(RUN_LOG_FILE))
+}
+
+/// 读取运行日志（按运行先后排列），无法解析的行会被跳过
+pub fn read_run_log<P: AsRef<Path>>(path: P) -> Result<Vec<ReportRunRecord>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut records = Vec::new();
+    for line in BufReader::new(fs::File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => log::warn!("跳过无法解析的报告运行日志: {}", e),
+        }
+    }
+    Ok(records)
+}
+
+/// 追加一条运行日志
+fn append_run_log(path: &Path, record: &ReportRunRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// 计算相对于运行时间的日期范围（按整天，含两端）
+pub fn resolve_range(range: ReportRange, now: DateTime<Local>) -> TimeRange {
+    let today = now.date_naive();
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let month_start = today.with_day(1).unwrap();
+    let (first, last) = match range {
+        ReportRange::Today => (today, today),
+        ReportRange::Yesterday => (today - Duration::days(1), today - Duration::days(1)),
+        ReportRange::ThisWeek => (monday, today),
+        ReportRange::LastWeek => (monday - Duration::days(7), monday - Duration::days(1)),
+        ReportRange::ThisMonth => (month_start, today),
+        ReportRange::LastMonth => {
+            let last = month_start - Duration::days(1);
+            (last.with_day(1).unwrap(), last)
+        }
+        ReportRange::LastDays(days) => (
+            today - Duration::days(days.max(1) as i64),
+            today - Duration::days(1),
+        ),
+    };
+    (local_time(first, 0, 0, 0), local_time(last, 23, 59, 59))
+}
+
+/// 本地时间（夏令时跳过的时刻按 UTC 偏移换算）
+fn local_time(date: NaiveDate, hour: u32, min: u32, sec: u32) -> DateTime<Local> {
+    let naive = date.and_hms_opt(hour, min, sec).unwrap();
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+/// 按文件名模板生成文件名
+///
+/// 支持 {name}、{id}、{start}、{end}、{date}（运行日期）、{week}（范围开始所在的 ISO 周）
+/// 和 {ext}；模板未以扩展名结尾时自动补上
+pub fn render_filename(
+    report: &ScheduledReport,
+    format: ExportFormat,
+    range: TimeRange,
+    now: DateTime<Local>,
+) -> String {
+    let name = report
+        .filename_template
+        .replace("{name}", &report.name)
+        .replace("{id}", &report.id)
+        .replace("{start}", &range.0.format("%Y-%m-%d").to_string())
+        .replace("{end}", &range.1.format("%Y-%m-%d").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{week}", &range.0.format("%G-W%V").to_string())
+        .replace("{ext}", format.extension());
+    let mut name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+
+    let suffix = format!(".{}", format.extension());
+    if !name.to_lowercase().ends_with(&suffix) {
+        name.push_str(&suffix);
+    }
+    name
+}
+
+/// 收集报告定义所选模块的数据
+pub fn build_report_data(
+    storage: &dyn StorageBackend,
+    report: &ScheduledReport,
+    range: TimeRange,
+    now: DateTime<Local>,
+) -> Result<ExportData> {
+    let has = |module: ReportModule| report.modules.contains(&module);
+    let options = report_options(report)?;
+
+    let mut entries = Vec::new();
+    if has(ReportModule::TimeEntries) || has(ReportModule::Statistics) {
+        storage.for_each_time_entry(Some(range), &mut |entry| {
+            entries.push(entry);
+            true
+        })?;
+    }
+    let categories: Vec<Category> = storage
+        .get_all_categories()?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let mut data = create_export_data(entries, categories, &options);
+    data.metadata.export_time = now;
+    if !has(ReportModule::TimeEntries) {
+        data.time_entries.clear();
+    }
+
+    if has(ReportModule::Tasks) {
+        // 期间内完成的任务，以及截至期末仍未完成的到期任务
+        data.tasks = storage
+            .get_all_tasks()?
+            .into_iter()
+            .filter(|task| {
+                if task.is_completed {
+                    task.completed_at
+                        .is_some_and(|done| done >= range.0 && done <= range.1)
+                } else {
+                    task.due_date.is_some_and(|due| due <= range.1)
+                }
+            })
+            .collect();
+        data.tasks
+            .sort_by_key(|task| (task.is_completed, task.due_date));
+    }
+
+    if has(ReportModule::Finance) {
+        data.financial = Some(load_financial_summary(
+            storage,
+            Some(range),
+            now.date_naive(),
+        )?);
+    }
+
+    Ok(data)
+}
+
+/// 报告定义对应的导出选项
+fn report_options(report: &ScheduledReport) -> Result<ExportOptions> {
+    let format = ExportFormat::from_extension(&report.format)
+        .ok_or_else(|| anyhow!("不支持的报告格式: {}", report.format))?;
+    Ok(ExportOptions {
+        format,
+        // 分类列表不是报告模块，时间记录中的分类名称不依赖该选项
+        include_categories: false,
+        include_statistics: report.modules.contains(&ReportModule::Statistics),
+        ..Default::default()
+    })
+}
+
+/// 定时报告执行器
+#[derive(Clone)]
+pub struct ReportRunner {
+    /// 数据来源
+    storage: Arc<dyn StorageBackend>,
+    /// 运行日志路径
+    log_path: PathBuf,
+}
+
+impl ReportRunner {
+    /// 创建执行器
+    pub fn new(storage: Arc<dyn StorageBackend>, log_path: PathBuf) -> Self {
+        Self { storage, log_path }
+    }
+
+    /// 运行日志路径
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// 以 `now` 为运行时间生成报告并写入运行日志
+    pub fn run(&self, report: &ScheduledReport, now: DateTime<Local>) -> ReportRunRecord {
+        let range = resolve_range(report.range, now);
+        let result = self.generate(report, range, now);
+
+        let record = ReportRunRecord {
+            report_id: report.id.clone(),
+            report_name: report.name.clone(),
+            started_at: now,
+            finished_at: Local::now(),
+            range,
+            status: if result.is_ok() {
+                ReportRunStatus::Success
+            } else {
+                ReportRunStatus::Failed
+            },
+            entries: result.as_ref().map(|(_, entries)| *entries).unwrap_or(0),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+            output: result.ok().map(|(path, _)| path),
+        };
+        match &record.error {
+            None => log::info!("已生成定时报告 {}: {:?}", report.name, record.output),
+            Some(e) => log::error!("定时报告 {} 生成失败: {}", report.name, e),
+        }
+        if let Err(e) = append_run_log(&self.log_path, &record) {
+            log::warn!("写入报告运行日志失败: {}", e);
+        }
+        record
+    }
+
+    /// 生成报告文件，返回文件路径和包含的时间记录数
+    fn generate(
+        &self,
+        report: &ScheduledReport,
+        range: TimeRange,
+        now: DateTime<Local>,
+    ) -> Result<(PathBuf, usize)> {
+        let options = report_options(report)?;
+        let data = build_report_data(self.storage.as_ref(), report, range, now)?;
+
+        fs::create_dir_all(&report.destination)?;
+        let path = report
+            .destination
+            .join(render_filename(report, options.format, range, now));
+        DataExporter::new(options).export_to_file(&data, &path)?;
+        Ok((path, data.time_entries.len()))
+    }
+}
+
+/// 定时报告调度器
+pub struct ReportScheduler {
+    /// 调度器实例
+    scheduler: JobScheduler,
+    /// 报告执行器
+    runner: ReportRunner,
+    /// 报告ID到定时任务ID的映射
+    jobs: Arc<Mutex<HashMap<String, Uuid>>>,
+}
+
+impl ReportScheduler {
+    /// 创建定时报告调度器
+    pub async fn new(runner: ReportRunner) -> Result<Self> {
+        let scheduler = JobScheduler::new()
+            .await
+            .map_err(|e| anyhow!("创建调度器失败: {}", e))?;
+
+        Ok(Self {
+            scheduler,
+            runner,
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// 添加所有已启用的报告，返回成功添加的数量
+    pub async fn schedule_all(&self, reports: &[ScheduledReport]) -> usize {
+        let mut scheduled = 0;
+        for report in reports.iter().filter(|report| report.enabled) {
+            match self.schedule(report).await {
+                Ok(_) => scheduled += 1,
+                Err(e) => log::error!("添加定时报告 {} 失败: {}", report.name, e),
+            }
+        }
+        scheduled
+    }
+
+    /// 按新的报告定义重新调度：移除现有任务后添加所有已启用的报告，返回成功添加的数量
+    pub async fn reschedule_all(&self, reports: &[ScheduledReport]) -> usize {
+        let report_ids: Vec<String> = self.jobs.lock().unwrap().keys().cloned().collect();
+        for report_id in report_ids {
+            if let Err(e) = self.remove(&report_id).await {
+                log::error!("移除定时报告 {} 失败: {}", report_id, e);
+            }
+        }
+        self.schedule_all(reports).await
+    }
+
+    /// 已调度的报告ID
+    pub fn scheduled_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.jobs.lock().unwrap().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// 添加定时报告任务（按本地时间执行），已添加的同一报告会被替换
+    pub async fn schedule(&self, report: &ScheduledReport) -> Result<String> {
+        if !report.enabled {
+            return Err(anyhow!("定时报告未启用"));
+        }
+        report_options(report)?;
+        self.remove(&report.id).await?;
+
+        let runner = self.runner.clone();
+        let definition = report.clone();
+        let job = Job::new_async_tz(report.schedule.as_str(), Local, move |_uuid, _l| {
+            let runner = runner.clone();
+            let report = definition.clone();
+            Box::pin(async move {
+                log::info!("执行定时报告任务: {}", report.name);
+                let result =
+                    tokio::task::spawn_blocking(move || runner.run(&report, Local::now())).await;
+                if let Err(e) = result {
+                    log::error!("定时报告任务异常退出: {}", e);
+                }
+            })
+        })
+        .map_err(|e| anyhow!("创建定时任务失败: {}", e))?;
+
+        let job_id = self
+            .scheduler
+            .add(job)
+            .await
+            .map_err(|e| anyhow!("添加定时任务失败: {}", e))?;
+        self.jobs.lock().unwrap().insert(report.id.clone(), job_id);
+
+        log::info!(
+            "已添加定时报告 {}，执行时间: {}",
+            report.name,
+            report.schedule
+        );
+        Ok(job_id.to_string())
+    }
+
+    /// 移除定时报告任务
+    pub async fn remove(&self, report_id: &str) -> Result<()> {
+        let job_id = self.jobs.lock().unwrap().remove(report_id);
+        if let Some(job_id) = job_id {
+            self.scheduler
+                .remove(&job_id)
+                .await
+                .map_err(|e| anyhow!("移除定时任务失败: {}", e))?;
+            log::info!("已移除定时报告任务: {}", report_id);
+        }
+        Ok(())
+    }
+
+    /// 立即生成一次报告
+    pub async fn run_now(&self, report: &ScheduledReport) -> Result<ReportRunRecord> {
+        let runner = self.runner.clone();
+        let report = report.clone();
+        Ok(tokio::task::spawn_blocking(move || runner.run(&report, Local::now())).await?)
+    }
+
+    /// 启动调度器
+    pub async fn start(&self) -> Result<()> {
+        log::info!("启动定时报告调度器");
+        self.scheduler
+            .start()
+            .await
+            .map_err(|e| anyhow!("启动调度器失败: {}", e))
+    }
+
+    /// 停止调度器
+    pub async fn stop(&mut self) -> Result<()> {
+        log::info!("停止定时报告调度器");
+        self.scheduler
+            .shutdown()
+            .await
+            .map_err(|e| anyhow!("停止调度器失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::{TaskStore, TimeEntryStore};
+    use crate::storage::models::TimeEntryInsert;
+    use crate::storage::task_models::TaskInsert;
+    use crate::storage::test_support::insert_sample_finance;
+    use crate::storage::MemoryStorage;
+
+    fn report(destination: PathBuf) -> ScheduledReport {
+        ScheduledReport {
+            id: "weekly".to_string(),
+            ..ScheduledReport::weekly_review(destination)
+        }
+    }
+
+    #[test]
+    fn test_resolve_range_and_filename() {
+        // 2024-03-13 是周三
+        let now = Local.with_ymd_and_hms(2024, 3, 13, 8, 0, 0).unwrap();
+        let day = |d: u32| Local.with_ymd_and_hms(2024, 3, d, 0, 0, 0).unwrap();
+        let end = |d: u32| Local.with_ymd_and_hms(2024, 3, d, 23, 59, 59).unwrap();
+
+        assert_eq!(resolve_range(ReportRange::LastWeek, now), (day(4), end(10)));
+        assert_eq!(
+            resolve_range(ReportRange::ThisWeek, now),
+            (day(11), end(13))
+        );
+        assert_eq!(
+            resolve_range(ReportRange::LastDays(3), now),
+            (day(10), end(12))
+        );
+        assert_eq!(
+            resolve_range(ReportRange::ThisMonth, now),
+            (day(1), end(13))
+        );
+        let last_month = resolve_range(ReportRange::LastMonth, now);
+        assert_eq!(
+            last_month.0,
+            Local.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            last_month.1,
+            Local.with_ymd_and_hms(2024, 2, 29, 23, 59, 59).unwrap()
+        );
+
+        let mut report = report(PathBuf::from("reports"));
+        let range = resolve_range(ReportRange::LastWeek, now);
+        assert_eq!(
+            render_filename(&report, ExportFormat::Html, range, now),
+            "周回顾-2024-W10.html"
+        );
+        report.filename_template = "{name}/{start}_{end}".to_string();
+        assert_eq!(
+            render_filename(&report, ExportFormat::Markdown, range, now),
+            "周回顾-2024-03-04_2024-03-10.md"
+        );
+    }
+
+    #[test]
+    fn test_run_writes_report_and_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = MemoryStorage::new();
+        let now = Local.with_ymd_and_hms(2024, 3, 11, 8, 0, 0).unwrap();
+
+        for (task_name, start_time) in [
+            (
+                "上周记录",
+                Local.with_ymd_and_hms(2024, 3, 6, 9, 0, 0).unwrap(),
+            ),
+            (
+                "本周记录",
+                Local.with_ymd_and_hms(2024, 3, 11, 7, 0, 0).unwrap(),
+            ),
+        ] {
+            storage
+                .insert_time_entry(&TimeEntryInsert {
+                    id: Uuid::new_v4(),
+                    task_name: task_name.to_string(),
+                    category_id: None,
+                    start_time,
+                    end_time: Some(start_time + Duration::hours(1)),
+                    duration_seconds: 3600,
+                    description: None,
+                    tags: vec![],
+                    created_at: start_time,
+                })
+                .unwrap();
+        }
+        storage
+            .insert_task(&TaskInsert {
+                id: Uuid::new_v4(),
+                name: "写周报".to_string(),
+                description: None,
+                category_id: None,
+                status: "pending".to_string(),
+                priority: "high".to_string(),
+                estimated_duration_seconds: None,
+                total_duration_seconds: 0,
+                tags: "[]".to_string(),
+                due_date: Some(Local.with_ymd_and_hms(2024, 3, 8, 18, 0, 0).unwrap()),
+                is_completed: false,
+                completed_at: None,
+                created_at: now - Duration::days(10),
+            })
+            .unwrap();
+
+        let log_path = dir.path().join(RUN_LOG_FILE);
+        let runner = ReportRunner::new(Arc::new(storage), log_path.clone());
+        let mut report = report(dir.path().join("reports"));
+        report.format = "markdown".to_string();
+
+        let record = runner.run(&report, now);
+        assert_eq!(record.status, ReportRunStatus::Success);
+        assert_eq!(record.entries, 1);
+        let output = record.output.clone().unwrap();
+        assert_eq!(
+            output,
+            dir.path().join("reports").join("周回顾-2024-W10.md")
+        );
+
+        let content = fs::read_to_string(&output).unwrap();
+        assert!(content.contains("上周记录"));
+        assert!(!content.contains("本周记录"));
+        assert!(content.contains("## 财务汇总"));
+        assert!(content.contains("| 写周报 | 已逾期 | high |"));
+
+        report.format = "doc".to_string();
+        let failed = runner.run(&report, now);
+        assert_eq!(failed.status, ReportRunStatus::Failed);
+        assert!(failed.output.is_none());
+
+        let log = read_run_log(&log_path).unwrap();
+        assert_eq!(log, vec![record, failed]);
+    }
+
+    #[test]
+    fn test_build_report_finance() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = MemoryStorage::new();
+        insert_sample_finance(&storage);
+        let mut report = report(dir.path().to_path_buf());
+        report.modules = vec![ReportModule::Finance];
+
+        let now = Local.with_ymd_and_hms(2024, 4, 2, 8, 0, 0).unwrap();
+        let range = resolve_range(ReportRange::LastMonth, now);
+        let data = build_report_data(&storage, &report, range, now).unwrap();
+        let financial = data.financial.unwrap();
+        assert_eq!(financial.stats.total_expense, 80.0);
+        assert_eq!(financial.stats.total_income, 0.0);
+        assert_eq!(financial.category_breakdown.len(), 1);
+        assert_eq!(financial.category_breakdown[0].category_name, "餐饮");
+        assert_eq!(financial.monthly_trends.len(), 1);
+        assert_eq!(financial.monthly_trends[0].month, "2024-03");
+        assert_eq!(financial.accounts.len(), 1);
+        assert_eq!(financial.transactions.len(), 2);
+        // 预算按报告期末所在的 3 月计算
+        assert_eq!(financial.budgets[0].spent_amount, 80.0);
+        assert_eq!(financial.budgets[0].remaining_amount, 20.0);
+    }
+
+    #[test]
+    fn test_report_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = report(dir.path().to_path_buf());
+        let options = report_options(&report).unwrap();
+        assert_eq!(options.format, ExportFormat::Html);
+        assert!(!options.include_categories);
+        assert!(options.include_statistics);
+
+        report.modules = vec![ReportModule::TimeEntries];
+        report.format = "md".to_string();
+        let options = report_options(&report).unwrap();
+        assert_eq!(options.format, ExportFormat::Markdown);
+        assert!(!options.include_categories && !options.include_statistics);
+
+        report.format = "doc".to_string();
+        assert!(report_options(&report).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_schedule_and_reschedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = ReportRunner::new(
+            Arc::new(MemoryStorage::new()),
+            dir.path().join(RUN_LOG_FILE),
+        );
+        let mut scheduler = ReportScheduler::new(runner).await.unwrap();
+
+        let weekly = report(dir.path().join("reports"));
+        let disabled = ScheduledReport {
+            id: "disabled".to_string(),
+            enabled: false,
+            ..weekly.clone()
+        };
+        let bad_cron = ScheduledReport {
+            id: "bad-cron".to_string(),
+            schedule: "every monday".to_string(),
+            ..weekly.clone()
+        };
+        let bad_format = ScheduledReport {
+            id: "bad-format".to_string(),
+            format: "doc".to_string(),
+            ..weekly.clone()
+        };
+        let reports = vec![weekly.clone(), disabled.clone(), bad_cron, bad_format];
+        assert_eq!(scheduler.schedule_all(&reports).await, 1);
+        assert_eq!(scheduler.scheduled_ids(), vec!["weekly"]);
+        assert!(scheduler.schedule(&disabled).await.is_err());
+
+        // 重复添加同一报告时替换原任务
+        scheduler.schedule(&weekly).await.unwrap();
+        assert_eq!(scheduler.scheduled_ids(), vec!["weekly"]);
+        scheduler.start().await.unwrap();
+
+        // 设置变更后按新定义重新调度
+        let daily = ScheduledReport {
+            id: "daily".to_string(),
+            schedule: "0 0 22 * * *".to_string(),
+            range: ReportRange::Today,
+            ..weekly.clone()
+        };
+        let disabled_weekly = ScheduledReport {
+            enabled: false,
+            ..weekly.clone()
+        };
+        assert_eq!(scheduler.reschedule_all(&[disabled_weekly, daily]).await, 1);
+        assert_eq!(scheduler.scheduled_ids(), vec!["daily"]);
+        assert_eq!(scheduler.reschedule_all(&[]).await, 0);
+        assert!(scheduler.scheduled_ids().is_empty());
+
+        // 立即运行写出报告和运行日志
+        let record = scheduler.run_now(&weekly).await.unwrap();
+        assert_eq!(record.status, ReportRunStatus::Success);
+        assert!(record.output.unwrap().exists());
+        assert_eq!(
+            read_run_log(dir.path().join(RUN_LOG_FILE)).unwrap().len(),
+            1
+        );
+
+        scheduler.stop().await.unwrap();
+    }
+}
@@ -0,0 +1,954 @@
+//! # Markdown 笔记库模块
+//!
+//! 将日记导出为可在应用外阅读的 Markdown 文件夹：
+//! - 每条笔记一个 `.md` 文件，按 `<年>/<月>/` 分目录存放
+//! - 文件头部为 YAML front matter（id、标题、心情、标签、收藏、归档、创建/更新时间）
+//!
+//! 导入时递归读取文件夹，同时兼容 Obsidian（front matter 中的 `tags`/`aliases`、
+//! 文件名即标题）和 Day One（`uuid`/`starred` 字段或纯文本导出的 `Date:` 头部）
+//! 风格的 Markdown。ID 与现有笔记相同时更新，否则新建
+
+use crate::storage::models::{Note, NoteUpdate};
+use crate::storage::StorageBackend;
+use crate::utils::import::ImportError;
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Markdown 文件扩展名
+const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+/// 文件名中标题部分的最大字符数
+const MAX_TITLE_CHARS: usize = 60;
+
+// ==================== 导出 ====================
+
+/// Markdown 笔记库导出器
+pub struct VaultExporter<'a> {
+    storage: &'a dyn StorageBackend,
+}
+
+impl<'a> VaultExporter<'a> {
+    pub fn new(storage: &'a dyn StorageBackend) -> Self {
+        Self { storage }
+    }
+
+    /// 导出所有笔记到目录，返回写入的文件路径
+    pub fn export_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>> {
+        let mut notes = self.storage.get_all_notes()?;
+        notes.sort_by_key(|note| (note.created_at, note.id));
+
+        let dir = dir.as_ref();
+        let mut used = HashSet::new();
+        let mut files = Vec::with_capacity(notes.len());
+        for note in &notes {
+            let relative = unique_path(note_path(note), &mut used);
+            let path = dir.join(&relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, render_note(note))?;
+            files.push(path);
+        }
+        Ok(files)
+    }
+}
+
+/// 笔记的相对路径：`<年>/<月>/<日期> <标题>.md`
+pub fn note_path(note: &Note) -> PathBuf {
+    let created = note.created_at;
+    let title = sanitize_file_name(&note.title);
+    let stem = if title.is_empty() {
+        created.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{} {}", created.format("%Y-%m-%d"), title)
+    };
+    PathBuf::from(format!("{:04}", created.year()))
+        .join(format!("{:02}", created.month()))
+        .join(format!("{}.md", stem))
+}
+
+/// 同名文件追加序号，如 `2024-03-01 日记 (2).md`
+fn unique_path(path: PathBuf, used: &mut HashSet<PathBuf>) -> PathBuf {
+    if used.insert(path.clone()) {
+        return path;
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut index = 2;
+    loop {
+        let candidate = path.with_file_name(format!("{} ({}).md", stem, index));
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// 去掉文件系统不允许的字符并截断
+fn sanitize_file_name(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .take(MAX_TITLE_CHARS)
+        .collect();
+    cleaned.trim().trim_matches('.').trim().to_string()
+}
+
+/// 渲染单条笔记为带 front matter 的 Markdown
+pub fn render_note(note: &Note) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("id: {}\n", note.id));
+    out.push_str(&format!("title: {}\n", yaml_scalar(&note.title)));
+    if let Some(mood) = &note.mood {
+        out.push_str(&format!("mood: {}\n", yaml_scalar(mood)));
+    }
+    let tags: Vec<String> = note.tags.iter().map(|tag| yaml_scalar(tag)).collect();
+    out.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    out.push_str(&format!("favorite: {}\n", note.is_favorite));
+    out.push_str(&format!("archived: {}\n", note.is_archived));
+    out.push_str(&format!("created: {}\n", note.created_at.to_rfc3339()));
+    out.push_str(&format!("updated: {}\n", note.updated_at.to_rfc3339()));
+    out.push_str("---\n\n");
+    out.push_str(&note.content);
+    if !note.content.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+/// 需要时为 YAML 标量加双引号
+fn yaml_scalar(value: &str) -> String {
+    let reserved = matches!(
+        value.to_ascii_lowercase().as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    );
+    let needs_quotes = value.is_empty()
+        || reserved
+        || value.parse::<f64>().is_ok()
+        || value.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c) || c.is_whitespace())
+        || value.ends_with(char::is_whitespace)
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.contains(',')
+        || value.chars().any(char::is_control);
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// ==================== 解析 ====================
+
+/// front matter 中的值
+#[derive(Debug, Clone, PartialEq)]
+enum YamlValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl YamlValue {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            YamlValue::Scalar(value) => Some(value),
+            YamlValue::List(items) => items.first().map(String::as_str),
+        }
+    }
+
+    fn as_list(&self) -> Vec<String> {
+        match self {
+            YamlValue::Scalar(value) => value
+                .split([',', ' '])
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+            YamlValue::List(items) => items.clone(),
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self.as_str()?.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// 从 Markdown 文件解析出的笔记
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedNote {
+    /// 文件中记录的 ID（Obsidian 等外部笔记通常没有）
+    pub id: Option<Uuid>,
+    pub title: String,
+    pub content: String,
+    pub mood: Option<String>,
+    pub tags: Vec<String>,
+    pub is_favorite: bool,
+    pub is_archived: bool,
+    pub created_at: Option<DateTime<Local>>,
+    pub updated_at: Option<DateTime<Local>>,
+}
+
+/// 解析单个 Markdown 文件，`file_stem` 用于推断标题和日期
+pub fn parse_note(text: &str, file_stem: &str) -> Result<ParsedNote, ImportError> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let (front, body) = split_front_matter(&text)?;
+    let mut body = body.to_string();
+
+    let mut note = ParsedNote {
+        id: None,
+        title: String::new(),
+        content: String::new(),
+        mood: None,
+        tags: Vec::new(),
+        is_favorite: false,
+        is_archived: false,
+        created_at: None,
+        updated_at: None,
+    };
+
+    let get = |keys: &[&str]| keys.iter().find_map(|key| front.get(*key));
+    if let Some(value) = get(&["id", "uuid"]).and_then(YamlValue::as_str) {
+        note.id = Some(Uuid::parse_str(value).map_err(|_| {
+            ImportError::new(format!("无效的笔记 ID: {}", value)).with_field("id".to_string())
+        })?);
+    }
+    if let Some(title) = get(&["title"]).and_then(YamlValue::as_str) {
+        note.title = title.to_string();
+    }
+    note.mood = get(&["mood"])
+        .and_then(YamlValue::as_str)
+        .filter(|mood| !mood.is_empty())
+        .map(str::to_string);
+    if let Some(tags) = get(&["tags", "tag"]) {
+        note.tags = tags.as_list();
+    }
+    note.is_favorite = get(&["favorite", "starred", "pinned"])
+        .and_then(YamlValue::as_bool)
+        .unwrap_or(false);
+    note.is_archived = get(&["archived"])
+        .and_then(YamlValue::as_bool)
+        .unwrap_or(false);
+    for (keys, target) in [
+        (
+            &["created", "date", "creationDate", "created_at"][..],
+            &mut note.created_at,
+        ),
+        (
+            &["updated", "modified", "modifiedDate", "updated_at"][..],
+            &mut note.updated_at,
+        ),
+    ] {
+        if let Some(value) = get(keys).and_then(YamlValue::as_str) {
+            *target = Some(parse_date(value).ok_or_else(|| {
+                ImportError::new(format!("无法解析日期: {}", value)).with_field(keys[0].to_string())
+            })?);
+        }
+    }
+
+    // Day One 纯文本导出的 `\tDate:\t...` 头部
+    if front.is_empty() {
+        if let Some((header, rest)) = split_day_one_header(&body) {
+            note.created_at = note.created_at.or(header.date);
+            note.tags.extend(header.tags);
+            note.is_favorite |= header.starred;
+            body = rest;
+        }
+    }
+
+    // 没有标题时依次使用正文首个一级标题、Obsidian 别名和文件名
+    if note.title.is_empty() {
+        if let Some((heading, rest)) = split_heading(&body) {
+            note.title = heading;
+            body = rest;
+        } else if let Some(alias) = get(&["aliases", "alias"]).and_then(YamlValue::as_str) {
+            note.title = alias.to_string();
+        } else {
+            note.title = file_stem.trim().to_string();
+        }
+    }
+    if note.created_at.is_none() {
+        note.created_at = file_stem
+            .get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .and_then(|date| local_time(date.and_hms_opt(0, 0, 0)?));
+    }
+
+    for tag in &mut note.tags {
+        *tag = tag.trim_start_matches('#').to_string();
+    }
+    note.tags.retain(|tag| !tag.is_empty());
+    let mut seen = HashSet::new();
+    note.tags.retain(|tag| seen.insert(tag.clone()));
+
+    note.content = body.trim_matches('\n').to_string();
+    Ok(note)
+}
+
+/// 拆分 front matter 和正文
+fn split_front_matter(text: &str) -> Result<(BTreeMap<String, YamlValue>, &str), ImportError> {
+    let Some(rest) = text.strip_prefix("---\n") else {
+        return Ok((BTreeMap::new(), text));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            let front = parse_yaml(&rest[..offset])?;
+            return Ok((front, &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    // 没有结束标记时视为普通正文
+    Ok((BTreeMap::new(), text))
+}
+
+/// 解析 front matter 用到的 YAML 子集：标量、行内列表、块列表和块标量
+fn parse_yaml(text: &str) -> Result<BTreeMap<String, YamlValue>, ImportError> {
+    let mut map = BTreeMap::new();
+    let mut current: Option<String> = None;
+    let mut block: Option<(String, bool, Vec<String>)> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 2;
+        let indented = line.starts_with([' ', '\t']);
+
+        if let Some((_, _, lines)) = block.as_mut() {
+            if indented || line.trim().is_empty() {
+                lines.push(line.trim().to_string());
+                continue;
+            }
+            let (key, folded, lines) = block.take().unwrap();
+            let separator = if folded { " " } else { "\n" };
+            let value = lines.join(separator).trim_end().to_string();
+            map.insert(key, YamlValue::Scalar(value));
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-') {
+            if item.is_empty() || item.starts_with(' ') {
+                if let Some(YamlValue::List(items)) =
+                    current.as_ref().and_then(|key| map.get_mut(key))
+                {
+                    items.push(unquote(item.trim()));
+                }
+                continue;
+            }
+        }
+        if indented {
+            // 嵌套映射等不支持的结构直接忽略
+            continue;
+        }
+
+        let (key, value) = trimmed.split_once(':').ok_or_else(|| {
+            ImportError::new("front matter 格式错误".to_string())
+                .with_line(line_number)
+                .with_data(line.to_string())
+        })?;
+        let key = key.trim().to_string();
+        let value = value.trim();
+        current = Some(key.clone());
+        let value = if value.is_empty() {
+            YamlValue::List(Vec::new())
+        } else if value.starts_with('|') || value.starts_with('>') {
+            block = Some((key, value.starts_with('>'), Vec::new()));
+            continue;
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            YamlValue::List(
+                split_flow(inner)
+                    .into_iter()
+                    .map(|item| unquote(&item))
+                    .filter(|item| !item.is_empty())
+                    .collect(),
+            )
+        } else {
+            YamlValue::Scalar(unquote(value))
+        };
+        map.insert(key, value);
+    }
+
+    if let Some((key, folded, lines)) = block {
+        let separator = if folded { " " } else { "\n" };
+        map.insert(
+            key,
+            YamlValue::Scalar(lines.join(separator).trim_end().to_string()),
+        );
+    }
+    Ok(map)
+}
+
+/// 按逗号拆分行内列表，引号内的逗号不拆分
+fn split_flow(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+    for c in inner.chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                current.push(c);
+            }
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                current.push(c);
+            }
+            None if c == ',' => items.push(std::mem::take(&mut current).trim().to_string()),
+            None => current.push(c),
+        }
+    }
+    items.push(current.trim().to_string());
+    items
+}
+
+/// 去掉标量的引号并处理转义，未加引号的值去掉行尾注释
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        }
+        return out;
+    }
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.replace("''", "'");
+    }
+    match value.find(" #") {
+        Some(index) => value[..index].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Day One 纯文本导出的条目头部
+struct DayOneHeader {
+    date: Option<DateTime<Local>>,
+    tags: Vec<String>,
+    starred: bool,
+}
+
+/// 拆分 Day One 头部（以制表符开头的 `Key:\tValue` 行），要求包含 `Date`
+fn split_day_one_header(body: &str) -> Option<(DayOneHeader, String)> {
+    let mut header = DayOneHeader {
+        date: None,
+        tags: Vec::new(),
+        starred: false,
+    };
+    let mut has_date = false;
+    let mut offset = 0;
+    let trimmed_body = body.trim_start_matches('\n');
+    for line in trimmed_body.split_inclusive('\n') {
+        let Some(field) = line.strip_prefix('\t') else {
+            break;
+        };
+        let field = field.trim_end();
+        if field == "Starred" {
+            header.starred = true;
+        } else if let Some((key, value)) = field.split_once(":\t") {
+            let value = value.trim();
+            match key {
+                "Date" => {
+                    has_date = true;
+                    header.date = parse_date(value);
+                }
+                "Tags" => header
+                    .tags
+                    .extend(value.split(',').map(|tag| tag.trim().to_string())),
+                _ => {}
+            }
+        } else {
+            break;
+        }
+        offset += line.len();
+    }
+    has_date.then(|| (header, trimmed_body[offset..].to_string()))
+}
+
+/// 正文首个非空行为一级标题时拆出标题
+fn split_heading(body: &str) -> Option<(String, String)> {
+    let trimmed = body.trim_start_matches('\n');
+    let (first, rest) = trimmed.split_once('\n').unwrap_or((trimmed, ""));
+    let heading = first.strip_prefix("# ")?.trim();
+    if heading.is_empty() {
+        return None;
+    }
+    Some((heading.to_string(), rest.to_string()))
+}
+
+/// 解析 RFC 3339、常见本地时间格式、纯日期和 Day One 的英文日期
+fn parse_date(value: &str) -> Option<DateTime<Local>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Local));
+    }
+    if let Ok(dt) = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f %z") {
+        return Some(dt.with_timezone(&Local));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return local_time(naive);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return local_time(date.and_hms_opt(0, 0, 0)?);
+    }
+
+    // Day One：`January 5, 2024 at 10:30:12 AM PST`，时区缩写按本地时间处理
+    let without_zone = match value.rsplit_once(' ') {
+        Some((head, zone)) if zone.chars().all(|c| c.is_ascii_uppercase()) && zone.len() > 2 => {
+            head
+        }
+        _ => value,
+    };
+    ["%B %d, %Y at %I:%M:%S %p", "%B %d, %Y at %I:%M %p"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(without_zone, format).ok())
+        .and_then(local_time)
+}
+
+fn local_time(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+// ==================== 导入 ====================
+
+/// 笔记库导入结果
+#[derive(Debug, Clone, Default)]
+pub struct VaultImportReport {
+    /// 新建的笔记数
+    pub created: usize,
+    /// 按 ID 更新的笔记数
+    pub updated: usize,
+    /// 内容未变化而跳过的笔记数
+    pub unchanged: usize,
+    /// 解析失败的文件
+    pub errors: Vec<ImportError>,
+}
+
+impl VaultImportReport {
+    pub fn success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Markdown 笔记库导入器
+pub struct VaultImporter<'a> {
+    storage: &'a dyn StorageBackend,
+}
+
+impl<'a> VaultImporter<'a> {
+    pub fn new(storage: &'a dyn StorageBackend) -> Self {
+        Self { storage }
+    }
+
+    /// 递归导入目录中的 Markdown 文件（跳过 `.obsidian` 等隐藏目录）
+    pub fn import_dir<P: AsRef<Path>>(&self, dir: P) -> Result<VaultImportReport> {
+        let mut files = Vec::new();
+        collect_markdown_files(dir.as_ref(), &mut files)?;
+        files.sort();
+
+        let mut report = VaultImportReport::default();
+        for path in files {
+            let text = fs::read_to_string(&path)?;
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match parse_note(&text, &stem) {
+                Ok(parsed) => {
+                    let fallback = fs::metadata(&path)?
+                        .modified()
+                        .ok()
+                        .map(DateTime::<Local>::from);
+                    self.apply(parsed, fallback, &mut report)?;
+                }
+                Err(error) => report
+                    .errors
+                    .push(error.with_data(path.display().to_string())),
+            }
+        }
+        Ok(report)
+    }
+
+    /// 写入单条解析结果，`fallback_time` 用于缺少时间字段的文件
+    pub fn apply(
+        &self,
+        parsed: ParsedNote,
+        fallback_time: Option<DateTime<Local>>,
+        report: &mut VaultImportReport,
+    ) -> Result<()> {
+        let existing = match parsed.id {
+            Some(id) => self.storage.get_note_by_id(id)?,
+            None => None,
+        };
+
+        if let Some(existing) = existing {
+            let unchanged = existing.title == parsed.title
+                && existing.content == parsed.content
+                && existing.mood == parsed.mood
+                && existing.tags == parsed.tags
+                && existing.is_favorite == parsed.is_favorite
+                && existing.is_archived == parsed.is_archived;
+            if unchanged {
+                report.unchanged += 1;
+                return Ok(());
+            }
+            let update = NoteUpdate {
+                title: Some(parsed.title),
+                content: Some(parsed.content),
+                mood: Some(parsed.mood),
+                tags: Some(parsed.tags),
+                is_favorite: Some(parsed.is_favorite),
+                is_archived: Some(parsed.is_archived),
+                // 外部编辑通常不会改动 `updated` 字段，此时使用文件修改时间
+                updated_at: parsed
+                    .updated_at
+                    .filter(|updated| *updated > existing.updated_at)
+                    .or(fallback_time)
+                    .unwrap_or_else(Local::now),
+            };
+            self.storage.update_note(existing.id, &update)?;
+            report.updated += 1;
+            return Ok(());
+        }
+
+        let created_at = parsed
+            .created_at
+            .or(fallback_time)
+            .unwrap_or_else(Local::now);
+        let note = Note {
+            id: parsed.id.unwrap_or_else(Uuid::new_v4),
+            title: parsed.title,
+            content: parsed.content,
+            mood: parsed.mood,
+            tags: parsed.tags,
+            is_favorite: parsed.is_favorite,
+            is_archived: parsed.is_archived,
+            created_at,
+            updated_at: parsed.updated_at.or(fallback_time).unwrap_or(created_at),
+        };
+        self.storage.insert_note(&note)?;
+        report.created += 1;
+        Ok(())
+    }
+}
+
+fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_markdown_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| MARKDOWN_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::backend::NoteStore;
+    use crate::storage::MemoryStorage;
+
+    fn note(title: &str, day: u32) -> Note {
+        let time = Local.with_ymd_and_hms(2024, 3, day, 21, 30, 0).unwrap();
+        Note {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: "今天完成了: 导出功能\n\n- 第一项\n- 第二项".to_string(),
+            mood: Some("happy".to_string()),
+            tags: vec!["日记".to_string(), "work, life".to_string()],
+            is_favorite: true,
+            is_archived: false,
+            created_at: time,
+            updated_at: time,
+        }
+    }
+
+    #[test]
+    fn test_vault_round_trip_updates_by_id() {
+        let storage = MemoryStorage::new();
+        let first = note("周末: 计划", 2);
+        let second = note("周末: 计划", 2);
+        storage.insert_note(&first).unwrap();
+        storage.insert_note(&second).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let files = VaultExporter::new(&storage)
+            .export_to_dir(dir.path())
+            .unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].starts_with(dir.path().join("2024").join("03")));
+        assert_ne!(files[0], files[1]);
+        let text = fs::read_to_string(&files[0]).unwrap();
+        assert!(text.starts_with("---\nid: "));
+        assert!(text.contains("title: \"周末: 计划\"\n"));
+        assert!(text.contains("tags: [日记, \"work, life\"]\n"));
+
+        // 未修改的文件不会产生更新，修改过的按 ID 更新
+        let edited = files
+            .iter()
+            .find(|path| {
+                fs::read_to_string(path)
+                    .unwrap()
+                    .contains(&first.id.to_string())
+            })
+            .unwrap();
+        let text = fs::read_to_string(edited).unwrap();
+        fs::write(edited, text.replace("mood: happy", "mood: calm")).unwrap();
+        let report = VaultImporter::new(&storage).import_dir(dir.path()).unwrap();
+        assert!(report.success());
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (0, 1, 1)
+        );
+
+        // 导入到空库时完整还原
+        let target = MemoryStorage::new();
+        let report = VaultImporter::new(&target).import_dir(dir.path()).unwrap();
+        assert_eq!(report.created, 2);
+        let restored = target.get_note_by_id(second.id).unwrap().unwrap();
+        assert_eq!(restored, second);
+    }
+
+    #[test]
+    fn test_vault_import_obsidian_and_day_one() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".obsidian")).unwrap();
+        fs::write(dir.path().join(".obsidian/workspace.md"), "ignored").unwrap();
+        fs::write(
+            dir.path().join("2024-05-06 散步.md"),
+            "---\naliases: [晚间散步]\ntags:\n  - '#生活'\n  - health\nstarred: yes\n---\n公园里很安静\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("Journal.md"),
+            "\tDate:\tJanuary 5, 2024 at 10:30:12 AM PST\n\tTags:\t旅行, 家人\n\tStarred\n\n# 回家\n\n见到了爷爷奶奶\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.md"), "---\nid: not-a-uuid\n---\n").unwrap();
+
+        let storage = MemoryStorage::new();
+        let report = VaultImporter::new(&storage).import_dir(dir.path()).unwrap();
+        assert_eq!(report.created, 2);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field.as_deref(), Some("id"));
+
+        let notes = storage.get_all_notes().unwrap();
+        let walk = notes.iter().find(|n| n.content == "公园里很安静").unwrap();
+        assert_eq!(walk.title, "晚间散步");
+        assert_eq!(walk.tags, vec!["生活", "health"]);
+        assert!(walk.is_favorite);
+        assert_eq!(
+            walk.created_at.date_naive(),
+            NaiveDate::from_ymd_opt(2024, 5, 6).unwrap()
+        );
+
+        let trip = notes.iter().find(|n| n.title == "回家").unwrap();
+        assert_eq!(trip.content, "见到了爷爷奶奶");
+        assert_eq!(trip.tags, vec!["旅行", "家人"]);
+        assert!(trip.is_favorite);
+        assert_eq!(
+            trip.created_at.naive_local(),
+            NaiveDate::from_ymd_opt(2024, 1, 5)
+                .unwrap()
+                .and_hms_opt(10, 30, 12)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_front_matter_conflicting_keys() {
+        let text = "\
+---
+title: 第一个标题
+title: 第二个标题
+tag: [忽略]
+tags: [a, '#b', a]
+starred: false
+favorite: yes
+date: 2020-01-01
+created: 2024-02-03T08:00:00+08:00
+---
+
+# 正文标题
+
+---
+
+分隔线之后的内容
+";
+        let parsed = parse_note(text, "2019-12-31 文件名").unwrap();
+        // 重复的键以后出现的为准
+        assert_eq!(parsed.title, "第二个标题");
+        // 同义键按优先级取值：tags 优先于 tag，favorite 优先于 starred，created 优先于 date
+        assert_eq!(parsed.tags, vec!["a", "b"]);
+        assert!(parsed.is_favorite);
+        assert_eq!(
+            parsed.created_at.unwrap(),
+            DateTime::parse_from_rfc3339("2024-02-03T08:00:00+08:00").unwrap()
+        );
+        // front matter 有标题时正文的一级标题保留在内容中，正文中的分隔线不影响 front matter
+        assert_eq!(parsed.content, "# 正文标题\n\n---\n\n分隔线之后的内容");
+
+        // 有 front matter 时不再识别 Day One 头部
+        let parsed = parse_note(
+            "---\ntags: x\n---\n\tDate:\t2024-01-01\n\tStarred\n正文",
+            "n",
+        )
+        .unwrap();
+        assert_eq!(parsed.tags, vec!["x"]);
+        assert!(!parsed.is_favorite);
+        assert!(parsed.content.starts_with("\tDate:"));
+
+        // 没有结束标记时整段视为正文，格式错误时报告行号
+        let parsed = parse_note("---\ntitle: 未闭合\n正文", "文件").unwrap();
+        assert_eq!(parsed.title, "文件");
+        assert!(parsed.content.contains("title: 未闭合"));
+        let error = parse_note("---\ntitle: 好\n这一行没有冒号\n---\n", "n").unwrap_err();
+        assert_eq!(error.line_number, Some(3));
+        let error = parse_note("---\nupdated: 昨天\n---\n", "n").unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("updated"));
+    }
+
+    #[test]
+    fn test_yaml_scalar_round_trip() {
+        for value in [
+            "true",
+            "No",
+            "123",
+            "1e5",
+            "",
+            "#标签",
+            "- 列表",
+            "周末: 计划",
+            "价格 #1",
+            "a, b",
+            " 前导空格",
+            "末尾空格 ",
+            "引号 \"和\" 反斜杠 \\",
+            "多行\n文本\t制表",
+            "普通文本",
+        ] {
+            let text = format!(
+                "---\ntitle: {}\nmood: {}\n---\n正文",
+                yaml_scalar(value),
+                yaml_scalar(value)
+            );
+            let parsed = parse_note(&text, "n").unwrap();
+            if value.is_empty() {
+                assert_eq!(parsed.mood, None);
+                continue;
+            }
+            assert_eq!(parsed.title, value, "{}", text);
+            assert_eq!(parsed.mood.as_deref(), Some(value));
+        }
+        assert_eq!(yaml_scalar("普通文本"), "普通文本");
+        assert_eq!(unquote("值 # 注释"), "值");
+        assert_eq!(unquote("'It''s'"), "It's");
+        assert_eq!(split_flow("\"a, b\", 'c', d"), vec!["\"a, b\"", "'c'", "d"]);
+    }
+
+    #[test]
+    fn test_import_conflicting_ids() {
+        let storage = MemoryStorage::new();
+        let original = note("原标题", 2);
+        storage.insert_note(&original).unwrap();
+
+        // 两个文件使用同一个 ID 时按文件名顺序依次应用，后者覆盖前者
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = original.clone();
+        first.title = "副本一".to_string();
+        let mut second = original.clone();
+        second.title = "副本二".to_string();
+        fs::write(dir.path().join("a.md"), render_note(&first)).unwrap();
+        fs::write(dir.path().join("b.md"), render_note(&second)).unwrap();
+        let report = VaultImporter::new(&storage).import_dir(dir.path()).unwrap();
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (0, 2, 0)
+        );
+        let stored = storage.get_note_by_id(original.id).unwrap().unwrap();
+        assert_eq!(stored.title, "副本二");
+        assert_eq!(stored.created_at, original.created_at);
+
+        // 文件中的 updated 早于库中记录时使用文件修改时间
+        let mut parsed = parse_note(&render_note(&original), "n").unwrap();
+        parsed.title = "外部编辑".to_string();
+        let fallback = Local.with_ymd_and_hms(2024, 4, 1, 8, 0, 0).unwrap();
+        let mut report = VaultImportReport::default();
+        VaultImporter::new(&storage)
+            .apply(parsed.clone(), Some(fallback), &mut report)
+            .unwrap();
+        let stored = storage.get_note_by_id(original.id).unwrap().unwrap();
+        assert_eq!(stored.title, "外部编辑");
+        assert_eq!(stored.updated_at, fallback);
+
+        // 库中没有该 ID 时按文件中的 ID 新建
+        let target = MemoryStorage::new();
+        VaultImporter::new(&target)
+            .apply(parsed, None, &mut report)
+            .unwrap();
+        assert!(target.get_note_by_id(original.id).unwrap().is_some());
+        assert_eq!(report.created, 1);
+    }
+}
@@ -0,0 +1,1227 @@
+//! # XLSX 导出模块
+//!
+//! 手写 SpreadsheetML 并用 [`ZipWriter`] 打包为 Excel 工作簿：
+//! - 时间记录、任务、交易、预算和统计各占一个工作表
+//! - 日期、时长、金额和百分比以数值写入，由数字格式控制显示，可直接参与公式计算
+//! - 表头冻结并开启筛选，列宽按内容估算（中文按两个字符宽度计）
+//!
+//! 文本使用内联字符串（`inlineStr`），不生成 `sharedStrings.xml`
+
+use crate::storage::accounting_models::{Account, TransactionCategory};
+use crate::utils::export::{ExportData, ExportOptions};
+use crate::utils::zip::ZipWriter;
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+use std::io::Write;
+use uuid::Uuid;
+
+/// 工作表名称的最大长度（Excel 限制）
+const MAX_SHEET_NAME_CHARS: usize = 31;
+/// 单元格文本的最大长度（Excel 限制）
+const MAX_CELL_CHARS: usize = 32_767;
+/// 列宽范围（字符数）
+const MIN_COLUMN_WIDTH: f64 = 6.0;
+const MAX_COLUMN_WIDTH: f64 = 60.0;
+
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_REL_NS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+// ==================== 工作簿模型 ====================
+
+/// 单元格值
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Text(String),
+    /// 加粗文本，用于统计表中的小节标题
+    Label(String),
+    Number(f64),
+    Integer(i64),
+    Bool(bool),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// 时长（秒），显示为 `[h]:mm:ss`
+    Duration(i64),
+    /// 金额和货币代码
+    Currency(f64, String),
+    /// 比例（0.25 显示为 25.00%）
+    Percent(f64),
+}
+
+impl CellValue {
+    pub fn text(value: impl Into<String>) -> Self {
+        CellValue::Text(value.into())
+    }
+
+    fn optional_text(value: Option<&str>) -> Self {
+        match value {
+            Some(value) if !value.is_empty() => CellValue::Text(value.to_string()),
+            _ => CellValue::Empty,
+        }
+    }
+
+    fn local(time: &DateTime<Local>) -> Self {
+        CellValue::DateTime(time.naive_local())
+    }
+
+    fn optional_local(time: Option<&DateTime<Local>>) -> Self {
+        time.map(Self::local).unwrap_or(CellValue::Empty)
+    }
+
+    /// 估算显示宽度（字符数）
+    fn display_width(&self) -> f64 {
+        match self {
+            CellValue::Empty => 0.0,
+            CellValue::Text(text) | CellValue::Label(text) => text_width(text),
+            CellValue::Number(value) => format!("{:.2}", value).len() as f64 + 2.0,
+            CellValue::Integer(value) => value.to_string().len() as f64 + 1.0,
+            CellValue::Bool(_) => 6.0,
+            CellValue::Date(_) => 10.0,
+            CellValue::DateTime(_) => 19.0,
+            CellValue::Duration(seconds) => (seconds / 3600).to_string().len() as f64 + 6.0,
+            CellValue::Currency(value, currency) => {
+                format!("{:.2}", value).len() as f64 + 3.0 + currency_symbol(currency).len() as f64
+            }
+            CellValue::Percent(_) => 8.0,
+        }
+    }
+}
+
+/// 工作表
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<CellValue>>,
+    /// 是否为表头开启自动筛选
+    pub auto_filter: bool,
+}
+
+impl Sheet {
+    pub fn new(name: &str, headers: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+            auto_filter: true,
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<CellValue>) {
+        self.rows.push(row);
+    }
+
+    fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn column_widths(&self) -> Vec<f64> {
+        let mut widths = vec![0.0_f64; self.column_count()];
+        for (index, header) in self.headers.iter().enumerate() {
+            widths[index] = text_width(header);
+        }
+        for row in &self.rows {
+            for (index, cell) in row.iter().enumerate() {
+                widths[index] = widths[index].max(cell.display_width());
+            }
+        }
+        widths
+            .into_iter()
+            .map(|w| (w + 2.0).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH))
+            .collect()
+    }
+}
+
+/// 单元格样式表，货币格式按出现的货币代码动态注册
+struct Styles {
+    currencies: Vec<String>,
+}
+
+impl Styles {
+    const GENERAL: usize = 0;
+    const HEADER: usize = 1;
+    const LABEL: usize = 2;
+    const DATE: usize = 3;
+    const DATETIME: usize = 4;
+    const DURATION: usize = 5;
+    const NUMBER: usize = 6;
+    const INTEGER: usize = 7;
+    const PERCENT: usize = 8;
+    const FIRST_CURRENCY: usize = 9;
+
+    /// 自定义数字格式（ID 从 164 开始）
+    const CUSTOM_FORMATS: [&'static str; 3] = ["yyyy-mm-dd", "yyyy-mm-dd hh:mm:ss", "[h]:mm:ss"];
+
+    fn new(sheets: &[Sheet]) -> Self {
+        let mut currencies = Vec::new();
+        for cell in sheets.iter().flat_map(|s| s.rows.iter().flatten()) {
+            if let CellValue::Currency(_, currency) = cell {
+                if !currencies.contains(currency) {
+                    currencies.push(currency.clone());
+                }
+            }
+        }
+        Self { currencies }
+    }
+
+    fn style_of(&self, cell: &CellValue) -> usize {
+        match cell {
+            CellValue::Empty | CellValue::Text(_) | CellValue::Bool(_) => Self::GENERAL,
+            CellValue::Label(_) => Self::LABEL,
+            CellValue::Number(_) => Self::NUMBER,
+            CellValue::Integer(_) => Self::INTEGER,
+            CellValue::Date(_) => Self::DATE,
+            CellValue::DateTime(_) => Self::DATETIME,
+            CellValue::Duration(_) => Self::DURATION,
+            CellValue::Percent(_) => Self::PERCENT,
+            CellValue::Currency(_, currency) => {
+                let index = self.currencies.iter().position(|c| c == currency);
+                Self::FIRST_CURRENCY + index.unwrap_or(0)
+            }
+        }
+    }
+
+    fn to_xml(&self) -> String {
+        let custom_id = |index: usize| 164 + index;
+        let mut formats: Vec<String> = Self::CUSTOM_FORMATS
+            .iter()
+            .map(|code| code.to_string())
+            .collect();
+        formats.extend(self.currencies.iter().map(|c| currency_format(c)));
+
+        let mut xml = xml_header();
+        xml.push_str(&format!("<styleSheet xmlns=\"{}\">", MAIN_NS));
+        xml.push_str(&format!("<numFmts count=\"{}\">", formats.len()));
+        for (index, code) in formats.iter().enumerate() {
+            xml.push_str(&format!(
+                "<numFmt numFmtId=\"{}\" formatCode=\"{}\"/>",
+                custom_id(index),
+                escape_xml(code)
+            ));
+        }
+        xml.push_str("</numFmts>");
+        xml.push_str(
+            "<fonts count=\"2\">\
+             <font><sz val=\"11\"/><name val=\"Calibri\"/><family val=\"2\"/></font>\
+             <font><b/><sz val=\"11\"/><name val=\"Calibri\"/><family val=\"2\"/></font>\
+             </fonts>\
+             <fills count=\"3\">\
+             <fill><patternFill patternType=\"none\"/></fill>\
+             <fill><patternFill patternType=\"gray125\"/></fill>\
+             <fill><patternFill patternType=\"solid\"><fgColor rgb=\"FFDDEBF7\"/><bgColor indexed=\"64\"/></patternFill></fill>\
+             </fills>\
+             <borders count=\"2\">\
+             <border><left/><right/><top/><bottom/><diagonal/></border>\
+             <border><left/><right/><top/><bottom style=\"thin\"><color rgb=\"FF9BC2E6\"/></bottom><diagonal/></border>\
+             </borders>\
+             <cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>",
+        );
+
+        // 顺序与上面的样式常量一致
+        let mut xfs = vec![
+            "<xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/>".to_string(),
+            "<xf numFmtId=\"0\" fontId=\"1\" fillId=\"2\" borderId=\"1\" xfId=\"0\" \
+             applyFont=\"1\" applyFill=\"1\" applyBorder=\"1\"/>"
+                .to_string(),
+            "<xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/>"
+                .to_string(),
+        ];
+        let number_xf = |id: usize| {
+            format!(
+                "<xf numFmtId=\"{}\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyNumberFormat=\"1\"/>",
+                id
+            )
+        };
+        xfs.push(number_xf(custom_id(0)));
+        xfs.push(number_xf(custom_id(1)));
+        xfs.push(number_xf(custom_id(2)));
+        // 内置格式：4 = #,##0.00，3 = #,##0，10 = 0.00%
+        xfs.push(number_xf(4));
+        xfs.push(number_xf(3));
+        xfs.push(number_xf(10));
+        for index in 0..self.currencies.len() {
+            xfs.push(number_xf(custom_id(Self::CUSTOM_FORMATS.len() + index)));
+        }
+
+        xml.push_str(&format!("<cellXfs count=\"{}\">", xfs.len()));
+        for xf in xfs {
+            xml.push_str(&xf);
+        }
+        xml.push_str(
+            "</cellXfs>\
+             <cellStyles count=\"1\"><cellStyle name=\"Normal\" xfId=\"0\" builtinId=\"0\"/></cellStyles>\
+             </styleSheet>",
+        );
+        xml
+    }
+}
+
+/// 工作簿
+#[derive(Debug, Clone, Default)]
+pub struct Workbook {
+    sheets: Vec<Sheet>,
+}
+
+impl Workbook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加工作表，名称会去掉 Excel 不允许的字符并保证唯一
+    pub fn add_sheet(&mut self, mut sheet: Sheet) {
+        let base = sanitize_sheet_name(&sheet.name);
+        let mut name = base.clone();
+        let mut index = 2;
+        while self
+            .sheets
+            .iter()
+            .any(|s| s.name.eq_ignore_ascii_case(&name))
+        {
+            let suffix = format!(" ({})", index);
+            let keep = MAX_SHEET_NAME_CHARS - suffix.chars().count();
+            name = format!("{}{}", base.chars().take(keep).collect::<String>(), suffix);
+            index += 1;
+        }
+        sheet.name = name;
+        self.sheets.push(sheet);
+    }
+
+    pub fn sheets(&self) -> &[Sheet] {
+        &self.sheets
+    }
+
+    /// 写出 XLSX 文件
+    pub fn write<W: Write>(&self, writer: W) -> Result<W> {
+        let styles = Styles::new(&self.sheets);
+        let mut zip = ZipWriter::new(writer);
+        zip.add_file("[Content_Types].xml", self.content_types().as_bytes())?;
+        zip.add_file("_rels/.rels", root_rels().as_bytes())?;
+        zip.add_file("xl/workbook.xml", self.workbook_xml().as_bytes())?;
+        zip.add_file(
+            "xl/_rels/workbook.xml.rels",
+            self.workbook_rels().as_bytes(),
+        )?;
+        zip.add_file("xl/styles.xml", styles.to_xml().as_bytes())?;
+        for (index, sheet) in self.sheets.iter().enumerate() {
+            let xml = sheet_xml(sheet, index == 0, &styles);
+            zip.add_file(
+                &format!("xl/worksheets/sheet{}.xml", index + 1),
+                xml.as_bytes(),
+            )?;
+        }
+        zip.finish()
+    }
+
+    fn content_types(&self) -> String {
+        let mut xml = xml_header();
+        xml.push_str(
+            "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+             <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+             <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+             <Override PartName=\"/xl/workbook.xml\" \
+             ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+             <Override PartName=\"/xl/styles.xml\" \
+             ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+        );
+        for index in 1..=self.sheets.len() {
+            xml.push_str(&format!(
+                "<Override PartName=\"/xl/worksheets/sheet{}.xml\" \
+                 ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+                index
+            ));
+        }
+        xml.push_str("</Types>");
+        xml
+    }
+
+    fn workbook_xml(&self) -> String {
+        let mut xml = xml_header();
+        xml.push_str(&format!(
+            "<workbook xmlns=\"{}\" xmlns:r=\"{}\"><bookViews><workbookView activeTab=\"0\"/></bookViews><sheets>",
+            MAIN_NS, REL_NS
+        ));
+        for (index, sheet) in self.sheets.iter().enumerate() {
+            xml.push_str(&format!(
+                "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+                escape_xml(&sheet.name),
+                index + 1,
+                index + 1
+            ));
+        }
+        xml.push_str("</sheets>");
+
+        // 筛选范围需要对应的隐藏名称，否则 Excel 打开时会提示修复
+        let filters: Vec<String> = self
+            .sheets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, sheet)| {
+                filter_range(sheet).map(|range| {
+                    format!(
+                        "<definedName name=\"_xlnm._FilterDatabase\" localSheetId=\"{}\" hidden=\"1\">'{}'!{}</definedName>",
+                        index,
+                        escape_xml(&sheet.name.replace('\'', "''")),
+                        absolute_range(&range)
+                    )
+                })
+            })
+            .collect();
+        if !filters.is_empty() {
+            xml.push_str("<definedNames>");
+            xml.push_str(&filters.concat());
+            xml.push_str("</definedNames>");
+        }
+        xml.push_str("</workbook>");
+        xml
+    }
+
+    fn workbook_rels(&self) -> String {
+        let mut xml = xml_header();
+        xml.push_str(&format!("<Relationships xmlns=\"{}\">", PACKAGE_REL_NS));
+        for index in 1..=self.sheets.len() {
+            xml.push_str(&format!(
+                "<Relationship Id=\"rId{}\" Type=\"{}/worksheet\" Target=\"worksheets/sheet{}.xml\"/>",
+                index, REL_NS, index
+            ));
+        }
+        xml.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"{}/styles\" Target=\"styles.xml\"/>",
+            self.sheets.len() + 1,
+            REL_NS
+        ));
+        xml.push_str("</Relationships>");
+        xml
+    }
+}
+
+fn xml_header() -> String {
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n".to_string()
+}
+
+fn root_rels() -> String {
+    let mut xml = xml_header();
+    xml.push_str(&format!(
+        "<Relationships xmlns=\"{}\">\
+         <Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"xl/workbook.xml\"/>\
+         </Relationships>",
+        PACKAGE_REL_NS, REL_NS
+    ));
+    xml
+}
+
+fn sheet_xml(sheet: &Sheet, selected: bool, styles: &Styles) -> String {
+    let columns = sheet.column_count().max(1);
+    let last_row = sheet.rows.len() + usize::from(!sheet.headers.is_empty());
+    let mut xml = xml_header();
+    xml.push_str(&format!("<worksheet xmlns=\"{}\">", MAIN_NS));
+    xml.push_str(&format!(
+        "<dimension ref=\"A1:{}{}\"/>",
+        column_name(columns - 1),
+        last_row.max(1)
+    ));
+
+    xml.push_str("<sheetViews><sheetView workbookViewId=\"0\"");
+    if selected {
+        xml.push_str(" tabSelected=\"1\"");
+    }
+    if sheet.headers.is_empty() {
+        xml.push_str("/>");
+    } else {
+        xml.push_str(
+            "><pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>\
+             <selection pane=\"bottomLeft\" activeCell=\"A2\" sqref=\"A2\"/></sheetView>",
+        );
+    }
+    xml.push_str("</sheetViews><sheetFormatPr defaultRowHeight=\"15\"/><cols>");
+    for (index, width) in sheet.column_widths().iter().enumerate() {
+        xml.push_str(&format!(
+            "<col min=\"{0}\" max=\"{0}\" width=\"{1:.1}\" customWidth=\"1\"/>",
+            index + 1,
+            width
+        ));
+    }
+    xml.push_str("</cols><sheetData>");
+
+    let mut row_number = 0;
+    if !sheet.headers.is_empty() {
+        row_number += 1;
+        xml.push_str(&format!("<row r=\"{}\">", row_number));
+        for (index, header) in sheet.headers.iter().enumerate() {
+            let reference = format!("{}{}", column_name(index), row_number);
+            xml.push_str(&inline_string(&reference, Styles::HEADER, header));
+        }
+        xml.push_str("</row>");
+    }
+    for row in &sheet.rows {
+        row_number += 1;
+        xml.push_str(&format!("<row r=\"{}\">", row_number));
+        for (index, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(index), row_number);
+            xml.push_str(&cell_xml(&reference, styles.style_of(cell), cell));
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData>");
+    if let Some(range) = filter_range(sheet) {
+        xml.push_str(&format!("<autoFilter ref=\"{}\"/>", range));
+    }
+    xml.push_str("</worksheet>");
+    xml
+}
+
+fn filter_range(sheet: &Sheet) -> Option<String> {
+    if !sheet.auto_filter || sheet.headers.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "A1:{}{}",
+        column_name(sheet.column_count() - 1),
+        sheet.rows.len() + 1
+    ))
+}
+
+/// `A1:H10` 转为 `$A$1:$H$10`
+fn absolute_range(range: &str) -> String {
+    range
+        .split(':')
+        .map(|cell| {
+            let split = cell
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(cell.len());
+            format!("${}${}", &cell[..split], &cell[split..])
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn cell_xml(reference: &str, style: usize, cell: &CellValue) -> String {
+    let number = |value: f64| {
+        if value.is_finite() {
+            format!(
+                "<c r=\"{}\" s=\"{}\"><v>{}</v></c>",
+                reference, style, value
+            )
+        } else {
+            String::new()
+        }
+    };
+    match cell {
+        CellValue::Empty => String::new(),
+        CellValue::Text(text) | CellValue::Label(text) => inline_string(reference, style, text),
+        CellValue::Bool(value) => format!(
+            "<c r=\"{}\" s=\"{}\" t=\"b\"><v>{}</v></c>",
+            reference,
+            style,
+            u8::from(*value)
+        ),
+        CellValue::Number(value) | CellValue::Currency(value, _) => number(*value),
+        CellValue::Integer(value) => number(*value as f64),
+        CellValue::Percent(value) => number(*value),
+        CellValue::Date(date) => number(excel_serial(date.and_hms_opt(0, 0, 0).unwrap())),
+        CellValue::DateTime(time) => number(excel_serial(*time)),
+        CellValue::Duration(seconds) => number(*seconds as f64 / 86_400.0),
+    }
+}
+
+fn inline_string(reference: &str, style: usize, text: &str) -> String {
+    let text: String = text.chars().take(MAX_CELL_CHARS).collect();
+    let style = if style == Styles::GENERAL {
+        String::new()
+    } else {
+        format!(" s=\"{}\"", style)
+    };
+    format!(
+        "<c r=\"{}\"{} t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+        reference,
+        style,
+        escape_xml(&text)
+    )
+}
+
+/// Excel 日期序列号（1900 日期系统，以 1899-12-30 为零点）
+fn excel_serial(time: NaiveDateTime) -> f64 {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    (time - epoch).num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// 列序号转列名：0 → A，26 → AA
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+fn sanitize_sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(MAX_SHEET_NAME_CHARS)
+        .collect();
+    let cleaned = cleaned.trim_matches('\'').trim().to_string();
+    if cleaned.is_empty() {
+        "Sheet".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// 文本显示宽度，东亚宽字符按 2 计
+fn text_width(text: &str) -> f64 {
+    let longest_line = text.lines().map(|line| {
+        line.chars()
+            .map(|c| if (c as u32) < 0x1100 { 1.0 } else { 2.0 })
+            .sum::<f64>()
+    });
+    longest_line.fold(0.0, f64::max)
+}
+
+/// 转义 XML 文本，并去掉 XML 1.0 不允许的控制字符
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn currency_symbol(currency: &str) -> &str {
+    match currency.to_ascii_uppercase().as_str() {
+        "CNY" | "RMB" | "JPY" => "¥",
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        _ => currency,
+    }
+}
+
+/// 货币数字格式，负数显示为红色
+fn currency_format(currency: &str) -> String {
+    let symbol = currency_symbol(currency);
+    let positive = if symbol == currency {
+        format!("#,##0.00\" {}\"", currency)
+    } else {
+        format!("\"{}\"#,##0.00", symbol)
+    };
+    format!("{0};[Red]-{0}", positive)
+}
+
+// ==================== 导出数据 → 工作簿 ====================
+
+/// 把导出数据写为 XLSX 工作簿
+pub fn write_workbook<W: Write>(
+    data: &ExportData,
+    options: &ExportOptions,
+    writer: W,
+) -> Result<W> {
+    build_workbook(data, options).write(writer)
+}
+
+/// 按导出数据构建工作簿，没有数据的工作表会被省略（时间记录除外）
+pub fn build_workbook(data: &ExportData, options: &ExportOptions) -> Workbook {
+    let category_names: HashMap<Uuid, &str> = data
+        .categories
+        .iter()
+        .map(|c| (c.id, c.name.as_str()))
+        .collect();
+    let category_name = |id: Option<Uuid>| match id {
+        Some(id) => CellValue::text(category_names.get(&id).copied().unwrap_or("未知分类")),
+        None => CellValue::text("未分类"),
+    };
+
+    let mut workbook = Workbook::new();
+
+    let mut entries = Sheet::new(
+        "时间记录",
+        &[
+            "开始时间",
+            "结束时间",
+            "任务",
+            "分类",
+            "时长",
+            "时长（小时）",
+            "标签",
+            "描述",
+        ],
+    );
+    for entry in &data.time_entries {
+        let seconds = match entry.end_time {
+            Some(end) => (end - entry.start_time).num_seconds(),
+            None => entry.duration_seconds,
+        };
+        entries.push(vec![
+            CellValue::local(&entry.start_time),
+            CellValue::optional_local(entry.end_time.as_ref()),
+            CellValue::text(entry.task_name.as_str()),
+            category_name(entry.category_id),
+            CellValue::Duration(seconds),
+            CellValue::Number(seconds as f64 / 3600.0),
+            CellValue::text(entry.tags.join(", ")),
+            CellValue::optional_text(entry.description.as_deref()),
+        ]);
+    }
+    workbook.add_sheet(entries);
+
+    if !data.tasks.is_empty() {
+        let mut tasks = Sheet::new(
+            "任务",
+            &[
+                "名称",
+                "分类",
+                "状态",
+                "优先级",
+                "截止时间",
+                "预估时长",
+                "实际时长",
+                "已完成",
+                "完成时间",
+                "创建时间",
+                "标签",
+                "描述",
+            ],
+        );
+        for task in &data.tasks {
+            let tags: Vec<String> = serde_json::from_str(&task.tags).unwrap_or_default();
+            tasks.push(vec![
+                CellValue::text(task.name.as_str()),
+                category_name(task.category_id),
+                CellValue::text(task.status.as_str()),
+                CellValue::text(task.priority.as_str()),
+                CellValue::optional_local(task.due_date.as_ref()),
+                task.estimated_duration_seconds
+                    .map(CellValue::Duration)
+                    .unwrap_or(CellValue::Empty),
+                CellValue::Duration(task.total_duration_seconds),
+                CellValue::Bool(task.is_completed),
+                CellValue::optional_local(task.completed_at.as_ref()),
+                CellValue::local(&task.created_at),
+                CellValue::text(tags.join(", ")),
+                CellValue::optional_text(task.description.as_deref()),
+            ]);
+        }
+        workbook.add_sheet(tasks);
+    }
+
+    if let Some(financial) = &data.financial {
+        let accounts: HashMap<Uuid, &Account> =
+            financial.accounts.iter().map(|a| (a.id, a)).collect();
+        let account_name = |id: Uuid| {
+            CellValue::text(
+                accounts
+                    .get(&id)
+                    .map(|a| a.name.as_str())
+                    .unwrap_or("未知账户"),
+            )
+        };
+        let categories: HashMap<Uuid, &TransactionCategory> = financial
+            .transaction_categories
+            .iter()
+            .map(|c| (c.id, c))
+            .collect();
+        let transaction_category = |id: Option<Uuid>| match id {
+            Some(id) => CellValue::text(
+                categories
+                    .get(&id)
+                    .map(|c| c.name.as_str())
+                    .unwrap_or("未知分类"),
+            ),
+            None => CellValue::Empty,
+        };
+
+        if !financial.transactions.is_empty() {
+            let mut transactions = Sheet::new(
+                "交易",
+                &[
+                    "日期",
+                    "类型",
+                    "金额",
+                    "货币",
+                    "账户",
+                    "转入账户",
+                    "分类",
+                    "状态",
+                    "描述",
+                    "标签",
+                ],
+            );
+            for transaction in &financial.transactions {
+                transactions.push(vec![
+                    CellValue::Date(transaction.transaction_date),
+                    CellValue::text(transaction.transaction_type.to_string()),
+                    CellValue::Currency(transaction.amount, transaction.currency.clone()),
+                    CellValue::text(transaction.currency.as_str()),
+                    account_name(transaction.account_id),
+                    transaction
+                        .to_account_id
+                        .map(account_name)
+                        .unwrap_or(CellValue::Empty),
+                    transaction_category(transaction.category_id),
+                    CellValue::text(transaction.status.to_string()),
+                    CellValue::text(transaction.description.as_str()),
+                    CellValue::text(transaction.tags.join(", ")),
+                ]);
+            }
+            workbook.add_sheet(transactions);
+        }
+
+        if !financial.budgets.is_empty() {
+            let mut budgets = Sheet::new(
+                "预算",
+                &[
+                    "名称",
+                    "分类",
+                    "周期",
+                    "预算金额",
+                    "已花费",
+                    "剩余",
+                    "使用率",
+                    "开始日期",
+                    "结束日期",
+                    "启用",
+                ],
+            );
+            for budget in &financial.budgets {
+                let usage = if budget.amount > 0.0 {
+                    CellValue::Percent(budget.spent_amount / budget.amount)
+                } else {
+                    CellValue::Empty
+                };
+                budgets.push(vec![
+                    CellValue::text(budget.name.as_str()),
+                    transaction_category(Some(budget.category_id)),
+                    CellValue::text(budget.period.to_string()),
+                    CellValue::Currency(budget.amount, budget.currency.clone()),
+                    CellValue::Currency(budget.spent_amount, budget.currency.clone()),
+                    CellValue::Currency(budget.remaining_amount, budget.currency.clone()),
+                    usage,
+                    CellValue::Date(budget.start_date),
+                    budget
+                        .end_date
+                        .map(CellValue::Date)
+                        .unwrap_or(CellValue::Empty),
+                    CellValue::Bool(budget.is_active),
+                ]);
+            }
+            workbook.add_sheet(budgets);
+        }
+    }
+
+    if options.include_statistics && (data.statistics.is_some() || data.financial.is_some()) {
+        workbook.add_sheet(summary_sheet(data));
+    }
+    workbook
+}
+
+/// 统计汇总表：时间统计、分类时长、财务汇总、支出分类和月度趋势
+fn summary_sheet(data: &ExportData) -> Sheet {
+    let mut sheet = Sheet::new("统计", &["项目", "数值", "占比"]);
+    sheet.auto_filter = false;
+    let item = |name: &str, value: CellValue| vec![CellValue::text(name), value];
+
+    sheet.push(item(
+        "导出时间",
+        CellValue::local(&data.metadata.export_time),
+    ));
+    if let Some((start, end)) = &data.metadata.date_range {
+        sheet.push(item("开始日期", CellValue::Date(start.date_naive())));
+        sheet.push(item("结束日期", CellValue::Date(end.date_naive())));
+    }
+    sheet.push(item(
+        "时间记录数",
+        CellValue::Integer(data.time_entries.len() as i64),
+    ));
+
+    if let Some(stats) = &data.statistics {
+        sheet.push(item(
+            "总时长",
+            CellValue::Duration(stats.total_time.num_seconds()),
+        ));
+        sheet.push(item(
+            "平均时长",
+            CellValue::Duration(stats.average_session_time.num_seconds()),
+        ));
+        sheet.push(item(
+            "最高效的一天",
+            CellValue::optional_text(stats.most_productive_day.as_deref()),
+        ));
+        sheet.push(item(
+            "最常用分类",
+            CellValue::optional_text(stats.most_used_category.as_deref()),
+        ));
+
+        if !stats.category_breakdown.is_empty() {
+            let total = stats.total_time.num_seconds().max(1) as f64;
+            let mut breakdown: Vec<_> = stats.category_breakdown.iter().collect();
+            breakdown.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            sheet.push(Vec::new());
+            sheet.push(vec![CellValue::Label("分类时长".to_string())]);
+            for (name, duration) in breakdown {
+                sheet.push(vec![
+                    CellValue::text(name.as_str()),
+                    CellValue::Duration(duration.num_seconds()),
+                    CellValue::Percent(duration.num_seconds() as f64 / total),
+                ]);
+            }
+        }
+    }
+
+    if let Some(financial) = &data.financial {
+        let stats = &financial.stats;
+        let money = |amount: f64| CellValue::Currency(amount, stats.currency.clone());
+        sheet.push(Vec::new());
+        sheet.push(vec![CellValue::Label("财务汇总".to_string())]);
+        sheet.push(item("总收入", money(stats.total_income)));
+        sheet.push(item("总支出", money(stats.total_expense)));
+        sheet.push(item("净收入", money(stats.net_income)));
+        sheet.push(item("账户余额", money(stats.account_balance)));
+        sheet.push(item("交易数", CellValue::Integer(stats.transaction_count)));
+        sheet.push(item("统计开始", CellValue::Date(stats.period_start)));
+        sheet.push(item("统计结束", CellValue::Date(stats.period_end)));
+
+        if !financial.category_breakdown.is_empty() {
+            sheet.push(Vec::new());
+            sheet.push(vec![
+                CellValue::Label("支出分类".to_string()),
+                CellValue::Label("金额".to_string()),
+                CellValue::Label("占比".to_string()),
+                CellValue::Label("交易数".to_string()),
+            ]);
+            for category in &financial.category_breakdown {
+                sheet.push(vec![
+                    CellValue::text(category.category_name.as_str()),
+                    money(category.amount),
+                    CellValue::Percent(category.percentage / 100.0),
+                    CellValue::Integer(category.transaction_count),
+                ]);
+            }
+        }
+
+        if !financial.monthly_trends.is_empty() {
+            sheet.push(Vec::new());
+            sheet.push(vec![
+                CellValue::Label("月份".to_string()),
+                CellValue::Label("收入".to_string()),
+                CellValue::Label("支出".to_string()),
+                CellValue::Label("净收入".to_string()),
+            ]);
+            for trend in &financial.monthly_trends {
+                sheet.push(vec![
+                    CellValue::text(trend.month.as_str()),
+                    money(trend.income),
+                    money(trend.expense),
+                    money(trend.net),
+                ]);
+            }
+        }
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::accounting_models::{
+        Budget, BudgetPeriod, FinancialStats, Transaction, TransactionStatus, TransactionType,
+    };
+    use crate::storage::models::TimeEntry;
+    use crate::utils::export::{ExportMetadata, FinancialSummary};
+    use crate::utils::zip::ZipArchive;
+    use chrono::TimeZone;
+
+    fn sample_data() -> ExportData {
+        let time = |hour| Local.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let account_id = Uuid::new_v4();
+        let entries = vec![TimeEntry {
+            id: Uuid::new_v4(),
+            task_name: "写周报 & 总结".to_string(),
+            category_id: None,
+            start_time: time(9),
+            end_time: Some(time(11)),
+            duration_seconds: 7200,
+            description: None,
+            tags: vec!["工作".to_string()],
+            created_at: time(9),
+            updated_at: None,
+        }];
+        let transaction = Transaction {
+            id: Uuid::new_v4(),
+            transaction_type: TransactionType::Expense,
+            amount: 35.5,
+            currency: "CNY".to_string(),
+            description: "午餐".to_string(),
+            account_id,
+            category_id: None,
+            to_account_id: None,
+            status: TransactionStatus::Completed,
+            transaction_date: date,
+            tags: vec![],
+            receipt_path: None,
+            created_at: time(12),
+            updated_at: None,
+        };
+        let budget = Budget {
+            id: Uuid::new_v4(),
+            name: "餐饮".to_string(),
+            category_id: Uuid::new_v4(),
+            amount: 1000.0,
+            currency: "USD".to_string(),
+            period: BudgetPeriod::Monthly,
+            start_date: date,
+            end_date: None,
+            spent_amount: 250.0,
+            remaining_amount: 750.0,
+            is_active: true,
+            created_at: time(8),
+            updated_at: None,
+        };
+
+        ExportData {
+            metadata: ExportMetadata {
+                export_time: time(18),
+                version: "1.0.0".to_string(),
+                total_entries: 1,
+                total_categories: 0,
+                date_range: None,
+                filters_applied: vec![],
+            },
+            categories: vec![],
+            statistics: Some(crate::utils::export::calculate_export_statistics(
+                &entries,
+                &[],
+            )),
+            time_entries: entries,
+            financial: Some(FinancialSummary {
+                stats: FinancialStats {
+                    total_income: 0.0,
+                    total_expense: 35.5,
+                    net_income: -35.5,
+                    account_balance: 964.5,
+                    transaction_count: 1,
+                    period_start: date,
+                    period_end: date,
+                    currency: "CNY".to_string(),
+                },
+                category_breakdown: vec![],
+                monthly_trends: vec![],
+                accounts: vec![],
+                transaction_categories: vec![],
+                transactions: vec![transaction],
+                budgets: vec![budget],
+            }),
+            tasks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_write_workbook_sheets_and_cell_types() {
+        let bytes = write_workbook(&sample_data(), &ExportOptions::default(), Vec::new()).unwrap();
+        let archive = ZipArchive::new(&bytes).unwrap();
+        let read = |name: &str| String::from_utf8(archive.read(name).unwrap()).unwrap();
+
+        // 所有部件都是格式正确的 XML
+        for entry in archive.entries() {
+            let text = String::from_utf8(archive.read(&entry.name).unwrap()).unwrap();
+            roxmltree::Document::parse(&text).unwrap();
+        }
+
+        let workbook = read("xl/workbook.xml");
+        for name in ["时间记录", "交易", "预算", "统计"] {
+            assert!(workbook.contains(&format!("name=\"{}\"", name)), "{}", name);
+        }
+        assert!(!workbook.contains("name=\"任务\""));
+        assert!(workbook.contains("'时间记录'!$A$1:$H$2"));
+
+        let entries = read("xl/worksheets/sheet1.xml");
+        assert!(entries.contains("state=\"frozen\""));
+        assert!(entries.contains("<autoFilter ref=\"A1:H2\"/>"));
+        assert!(entries.contains("写周报 &amp; 总结"));
+        // 2024-03-01 09:00 → 45352.375，两小时 → 0.0833…
+        assert!(entries.contains(&format!("<v>{}</v>", 45352.375)));
+        assert!(entries.contains(&format!(
+            "s=\"{}\"><v>{}</v>",
+            Styles::DURATION,
+            7200.0 / 86_400.0
+        )));
+
+        let budgets = read("xl/worksheets/sheet3.xml");
+        assert!(budgets.contains(&format!("s=\"{}\"><v>0.25</v>", Styles::PERCENT)));
+        assert!(budgets.contains("t=\"b\"><v>1</v>"));
+
+        // 人民币和美元各注册一个货币格式
+        let styles = read("xl/styles.xml");
+        assert!(styles.contains("formatCode=\"&quot;¥&quot;#,##0.00;[Red]-&quot;¥&quot;#,##0.00\""));
+        assert!(styles.contains("&quot;$&quot;#,##0.00"));
+    }
+
+    #[test]
+    fn test_sheet_names_and_columns() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+
+        let mut workbook = Workbook::new();
+        workbook.add_sheet(Sheet::new("收入/支出 [2024]", &["A"]));
+        workbook.add_sheet(Sheet::new("收入/支出 [2024]", &["A"]));
+        let names: Vec<&str> = workbook.sheets().iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["收入_支出 _2024_", "收入_支出 _2024_ (2)"]);
+    }
+
+    /// 读出工作表中各单元格的类型属性、样式和值（内联字符串取 `<is><t>` 文本）
+    fn read_cells(xml: &str) -> HashMap<String, (Option<String>, Option<String>, String)> {
+        let document = roxmltree::Document::parse(xml).unwrap();
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name("c"))
+            .map(|cell| {
+                let value = cell
+                    .descendants()
+                    .find(|node| node.has_tag_name("t") || node.has_tag_name("v"))
+                    .and_then(|node| node.text())
+                    .unwrap_or_default()
+                    .to_string();
+                (
+                    cell.attribute("r").unwrap().to_string(),
+                    (
+                        cell.attribute("t").map(str::to_string),
+                        cell.attribute("s").map(str::to_string),
+                        value,
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_cells_use_inline_strings() {
+        let long = "汉".repeat(MAX_CELL_CHARS + 10);
+        let mut sheet = Sheet::new("文本", &["内容", "重复"]);
+        sheet.push(vec![
+            CellValue::text("a & b <c> \"d\" 'e'"),
+            CellValue::text("重复"),
+        ]);
+        sheet.push(vec![
+            CellValue::text("  前后空格\t\n第二行  "),
+            CellValue::text("重复"),
+        ]);
+        sheet.push(vec![
+            CellValue::text("控制\u{1}字符\u{b}"),
+            CellValue::Label("小节".to_string()),
+        ]);
+        sheet.push(vec![CellValue::text(long), CellValue::text("")]);
+        let mut workbook = Workbook::new();
+        workbook.add_sheet(sheet);
+        let bytes = workbook.write(Vec::new()).unwrap();
+        let archive = ZipArchive::new(&bytes).unwrap();
+        let read = |name: &str| String::from_utf8(archive.read(name).unwrap()).unwrap();
+
+        // 不生成共享字符串表，也不在内容类型和关系中引用它
+        assert!(archive
+            .entries()
+            .iter()
+            .all(|entry| !entry.name.contains("sharedStrings")));
+        assert!(!read("[Content_Types].xml").contains("sharedStrings"));
+        assert!(!read("xl/_rels/workbook.xml.rels").contains("sharedStrings"));
+
+        let cells = read_cells(&read("xl/worksheets/sheet1.xml"));
+        assert!(cells
+            .values()
+            .all(|(kind, _, _)| kind.as_deref() == Some("inlineStr")));
+        assert_eq!(cells["A1"].1, Some(Styles::HEADER.to_string()));
+        assert_eq!(cells["A2"].2, "a & b <c> \"d\" 'e'");
+        // 相同文本各自内联保存
+        assert_eq!(cells["B2"].2, "重复");
+        assert_eq!(cells["B3"].2, "重复");
+        assert_eq!(cells["A3"].2, "  前后空格\t\n第二行  ");
+        assert_eq!(cells["A4"].2, "控制字符");
+        assert_eq!(cells["B4"].1, Some(Styles::LABEL.to_string()));
+        // 超长文本按字符截断到单元格上限
+        assert_eq!(cells["A5"].2.chars().count(), MAX_CELL_CHARS);
+        assert_eq!(cells["B5"].2, "");
+    }
+
+    #[test]
+    fn test_date_cells() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let serial =
+            |y, m, d, h, min, s| excel_serial(date(y, m, d).and_hms_opt(h, min, s).unwrap());
+        assert_eq!(serial(1899, 12, 31, 0, 0, 0), 1.0);
+        // 1900 日期系统中 1900-03-01 为 61（Excel 保留了不存在的 1900-02-29）
+        assert_eq!(serial(1900, 3, 1, 0, 0, 0), 61.0);
+        assert_eq!(serial(1970, 1, 1, 0, 0, 0), 25569.0);
+        assert_eq!(serial(2024, 2, 29, 18, 0, 0), 45351.75);
+        assert!((serial(2024, 3, 1, 0, 0, 1) - (45352.0 + 1.0 / 86_400.0)).abs() < 1e-9);
+
+        // 本地时间按显示的墙上时间写出，不换算为 UTC
+        let local = Local.with_ymd_and_hms(2024, 7, 1, 23, 30, 0).unwrap();
+        assert_eq!(
+            CellValue::local(&local),
+            CellValue::DateTime(date(2024, 7, 1).and_hms_opt(23, 30, 0).unwrap())
+        );
+
+        let mut sheet = Sheet::new("日期", &["日期", "时间", "时长"]);
+        sheet.push(vec![
+            CellValue::Date(date(2024, 3, 1)),
+            CellValue::local(&local),
+            CellValue::Duration(36 * 3600 + 90),
+        ]);
+        sheet.push(vec![
+            CellValue::optional_local(None),
+            CellValue::Empty,
+            CellValue::Duration(0),
+        ]);
+        let mut workbook = Workbook::new();
+        workbook.add_sheet(sheet);
+        let bytes = workbook.write(Vec::new()).unwrap();
+        let archive = ZipArchive::new(&bytes).unwrap();
+        let xml = String::from_utf8(archive.read("xl/worksheets/sheet1.xml").unwrap()).unwrap();
+        let cells = read_cells(&xml);
+
+        assert_eq!(
+            cells["A2"],
+            (None, Some(Styles::DATE.to_string()), "45352".to_string())
+        );
+        let (kind, style, value) = &cells["B2"];
+        assert_eq!((kind, style), (&None, &Some(Styles::DATETIME.to_string())));
+        assert_eq!(value.parse::<f64>().unwrap(), serial(2024, 7, 1, 23, 30, 0));
+        let duration: f64 = cells["C2"].2.parse().unwrap();
+        assert!((duration - 1.5 - 90.0 / 86_400.0).abs() < 1e-9);
+        // 空值不写单元格
+        assert!(!cells.contains_key("A3") && !cells.contains_key("B3"));
+        assert_eq!(cells["C3"].2, "0");
+
+        // 日期样式引用对应的自定义数字格式
+        let styles = String::from_utf8(archive.read("xl/styles.xml").unwrap()).unwrap();
+        assert!(styles.contains("<numFmt numFmtId=\"164\" formatCode=\"yyyy-mm-dd\"/>"));
+        assert!(styles.contains("<numFmt numFmtId=\"165\" formatCode=\"yyyy-mm-dd hh:mm:ss\"/>"));
+        assert!(styles.contains("<numFmt numFmtId=\"166\" formatCode=\"[h]:mm:ss\"/>"));
+    }
+}
@@ -0,0 +1,418 @@
+//! # ZIP 文件读写模块
+//!
+//! 最小化的 ZIP 实现，供完整归档和 XLSX 导出使用：
+//! - 写入：Deflate 压缩或直接存储，文件名使用 UTF-8
+//! - 读取：通过中央目录定位条目，解压并校验 CRC32
+//!
+//! 不支持 ZIP64、加密和分卷。条目数量、文件名长度、文件和归档大小超出 ZIP 格式
+//! 16 位/32 位字段的范围（全 1 的值在 ZIP64 中另有含义，同样不可用）时写入返回错误，
+//! 读取时遇到 ZIP64 归档也返回错误
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Local, Timelike};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+
+/// 本地文件头签名
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+/// 中央目录条目签名
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+/// 中央目录结束记录签名
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+/// 解压所需的版本（2.0，支持 Deflate）
+const VERSION_NEEDED: u16 = 20;
+/// 通用标志：文件名为 UTF-8
+const FLAG_UTF8: u16 = 0x0800;
+
+/// 压缩方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZipMethod {
+    /// 不压缩
+    Stored,
+    /// Deflate 压缩
+    Deflated,
+}
+
+impl ZipMethod {
+    fn code(self) -> u16 {
+        match self {
+            ZipMethod::Stored => 0,
+            ZipMethod::Deflated => 8,
+        }
+    }
+}
+
+/// 已写入条目的中央目录信息
+struct CentralEntry {
+    name: String,
+    method: ZipMethod,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// ZIP 写入器
+pub struct ZipWriter<W: Write> {
+    writer: W,
+    entries: Vec<CentralEntry>,
+    names: HashSet<String>,
+    offset: u64,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl<W: Write> ZipWriter<W> {
+    /// 创建写入器，所有条目使用当前时间作为修改时间
+    pub fn new(writer: W) -> Self {
+        let now = Local::now();
+        let dos_time = ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16;
+        let dos_date = (((now.year().clamp(1980, 2107) - 1980) as u32) << 9
+            | (now.month() << 5)
+            | now.day()) as u16;
+
+        Self {
+            writer,
+            entries: Vec::new(),
+            names: HashSet::new(),
+            offset: 0,
+            dos_time,
+            dos_date,
+        }
+    }
+
+    /// 添加使用 Deflate 压缩的文件
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        self.add_file_with_method(name, data, ZipMethod::Deflated)
+    }
+
+    /// 添加文件并指定压缩方式
+    pub fn add_file_with_method(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        method: ZipMethod,
+    ) -> Result<()> {
+        if self.names.contains(name) {
+            bail!("ZIP 条目重复: {}", name);
+        }
+        to_u16(self.entries.len() + 1, "条目数量")?;
+        let name_len = to_u16(name.len(), "文件名长度")?;
+
+        let mut crc = Crc::new();
+        crc.update(data);
+
+        let compressed = match method {
+            ZipMethod::Stored => data.to_vec(),
+            ZipMethod::Deflated => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+        };
+
+        let entry = CentralEntry {
+            name: name.to_string(),
+            method,
+            crc: crc.sum(),
+            compressed_size: to_u32(compressed.len() as u64, "文件大小")?,
+            size: to_u32(data.len() as u64, "文件大小")?,
+            offset: to_u32(self.offset, "归档大小")?,
+        };
+
+        let mut header = Vec::with_capacity(30 + name.len());
+        put_u32(&mut header, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut header, VERSION_NEEDED);
+        put_u16(&mut header, FLAG_UTF8);
+        put_u16(&mut header, method.code());
+        put_u16(&mut header, self.dos_time);
+        put_u16(&mut header, self.dos_date);
+        put_u32(&mut header, entry.crc);
+        put_u32(&mut header, entry.compressed_size);
+        put_u32(&mut header, entry.size);
+        put_u16(&mut header, name_len);
+        put_u16(&mut header, 0);
+        header.extend_from_slice(name.as_bytes());
+
+        self.writer.write_all(&header)?;
+        self.writer.write_all(&compressed)?;
+        self.offset += (header.len() + compressed.len()) as u64;
+        self.names.insert(entry.name.clone());
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// 写入中央目录并返回底层写入器
+    pub fn finish(mut self) -> Result<W> {
+        let directory_offset = to_u32(self.offset, "归档大小")?;
+        let count = to_u16(self.entries.len(), "条目数量")?;
+        let mut directory = Vec::new();
+
+        for entry in &self.entries {
+            put_u32(&mut directory, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut directory, VERSION_NEEDED);
+            put_u16(&mut directory, VERSION_NEEDED);
+            put_u16(&mut directory, FLAG_UTF8);
+            put_u16(&mut directory, entry.method.code());
+            put_u16(&mut directory, self.dos_time);
+            put_u16(&mut directory, self.dos_date);
+            put_u32(&mut directory, entry.crc);
+            put_u32(&mut directory, entry.compressed_size);
+            put_u32(&mut directory, entry.size);
+            put_u16(&mut directory, to_u16(entry.name.len(), "文件名长度")?);
+            put_u16(&mut directory, 0); // 扩展字段长度
+            put_u16(&mut directory, 0); // 注释长度
+            put_u16(&mut directory, 0); // 磁盘编号
+            put_u16(&mut directory, 0); // 内部属性
+            put_u32(&mut directory, 0); // 外部属性
+            put_u32(&mut directory, entry.offset);
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_size = to_u32(directory.len() as u64, "中央目录大小")?;
+        put_u32(&mut directory, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        put_u16(&mut directory, 0);
+        put_u16(&mut directory, 0);
+        put_u16(&mut directory, count);
+        put_u16(&mut directory, count);
+        put_u32(&mut directory, directory_size);
+        put_u32(&mut directory, directory_offset);
+        put_u16(&mut directory, 0);
+
+        self.writer.write_all(&directory)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// ZIP 中的条目
+#[derive(Debug, Clone)]
+pub struct ZipEntry {
+    /// 条目名称（路径使用 `/` 分隔）
+    pub name: String,
+    /// 解压后的大小
+    pub size: u32,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    offset: u32,
+}
+
+/// 内存中的 ZIP 归档读取器
+pub struct ZipArchive<'a> {
+    data: &'a [u8],
+    entries: Vec<ZipEntry>,
+}
+
+impl<'a> ZipArchive<'a> {
+    /// 解析归档的中央目录
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        // 中央目录结束记录位于末尾，后面最多跟 65535 字节的注释
+        let search_start = data.len().saturating_sub(22 + u16::MAX as usize);
+        let end = (search_start..=data.len().saturating_sub(22))
+            .rev()
+            .find(|&pos| read_u32(data, pos) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+            .context("不是有效的 ZIP 文件：找不到中央目录")?;
+
+        let count = read_u16(data, end + 10).context("ZIP 中央目录损坏")?;
+        let directory_offset = read_u32(data, end + 16).context("ZIP 中央目录损坏")?;
+        if count == u16::MAX || directory_offset == u32::MAX {
+            bail!("不支持 ZIP64 格式的归档");
+        }
+        let count = count as usize;
+        let mut pos = directory_offset as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            if read_u32(data, pos) != Some(CENTRAL_HEADER_SIGNATURE) {
+                bail!("ZIP 中央目录损坏：偏移 {} 处的条目无效", pos);
+            }
+            let field = |offset: usize| read_u16(data, pos + offset).context("ZIP 中央目录损坏");
+            let flags = field(8)?;
+            let method = field(10)?;
+            let name_len = field(28)? as usize;
+            let extra_len = field(30)? as usize;
+            let comment_len = field(32)? as usize;
+            let long = |offset: usize| read_u32(data, pos + offset).context("ZIP 中央目录损坏");
+
+            if flags & 0x0001 != 0 {
+                bail!("不支持加密的 ZIP 条目");
+            }
+            let name_bytes = data
+                .get(pos + 46..pos + 46 + name_len)
+                .context("ZIP 中央目录损坏")?;
+
+            let entry = ZipEntry {
+                name: String::from_utf8_lossy(name_bytes).into_owned(),
+                method,
+                crc: long(16)?,
+                compressed_size: long(20)?,
+                size: long(24)?,
+                offset: long(42)?,
+            };
+            if [entry.compressed_size, entry.size, entry.offset].contains(&u32::MAX) {
+                bail!("不支持 ZIP64 格式的归档");
+            }
+            entries.push(entry);
+            pos += 46 + name_len + extra_len + comment_len;
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    /// 所有条目
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// 是否包含指定条目
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// 读取并解压指定条目
+    pub fn read(&self, name: &str) -> Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .with_context(|| format!("ZIP 中不存在条目: {}", name))?;
+
+        let offset = entry.offset as usize;
+        if read_u32(self.data, offset) != Some(LOCAL_HEADER_SIGNATURE) {
+            bail!("ZIP 条目 {} 的本地文件头损坏", name);
+        }
+        let name_len = read_u16(self.data, offset + 26).context("ZIP 文件头损坏")? as usize;
+        let extra_len = read_u16(self.data, offset + 28).context("ZIP 文件头损坏")? as usize;
+        let start = offset + 30 + name_len + extra_len;
+        let compressed = self
+            .data
+            .get(start..start + entry.compressed_size as usize)
+            .with_context(|| format!("ZIP 条目 {} 的数据不完整", name))?;
+
+        let content = match entry.method {
+            0 => compressed.to_vec(),
+            8 => {
+                let mut content = Vec::with_capacity(entry.size as usize);
+                DeflateDecoder::new(compressed)
+                    .read_to_end(&mut content)
+                    .with_context(|| format!("解压 ZIP 条目 {} 失败", name))?;
+                content
+            }
+            method => bail!("ZIP 条目 {} 使用了不支持的压缩方式: {}", name, method),
+        };
+
+        let mut crc = Crc::new();
+        crc.update(&content);
+        if crc.sum() != entry.crc || content.len() != entry.size as usize {
+            bail!("ZIP 条目 {} 校验失败，文件可能已损坏", name);
+        }
+
+        Ok(content)
+    }
+}
+
+/// 转换为 16 位字段，`u16::MAX` 是 ZIP64 标记，不能作为实际值写入
+fn to_u16(value: usize, what: &str) -> Result<u16> {
+    u16::try_from(value)
+        .ok()
+        .filter(|&value| value != u16::MAX)
+        .ok_or_else(|| anyhow::anyhow!("{}超过 ZIP 格式的 {} 限制", what, u16::MAX - 1))
+}
+
+/// 转换为 32 位字段，`u32::MAX` 是 ZIP64 标记，不能作为实际值写入
+fn to_u32(value: u64, what: &str) -> Result<u32> {
+    u32::try_from(value)
+        .ok()
+        .filter(|&value| value != u32::MAX)
+        .ok_or_else(|| anyhow::anyhow!("{}超过 ZIP 格式的 4GB 限制", what))
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_round_trip() {
+        let text = "时间记录".repeat(200);
+        let mut writer = ZipWriter::new(Vec::new());
+        writer.add_file("数据/notes.json", text.as_bytes()).unwrap();
+        writer
+            .add_file_with_method("mimetype", b"application/zip", ZipMethod::Stored)
+            .unwrap();
+        assert!(writer.add_file("mimetype", b"").is_err());
+        let bytes = writer.finish().unwrap();
+
+        let archive = ZipArchive::new(&bytes).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+        assert_eq!(archive.read("数据/notes.json").unwrap(), text.as_bytes());
+        assert_eq!(archive.read("mimetype").unwrap(), b"application/zip");
+        assert!(archive.read("missing").is_err());
+    }
+
+    #[test]
+    fn test_zip_detects_corruption() {
+        let mut writer = ZipWriter::new(Vec::new());
+        writer
+            .add_file_with_method("a.txt", b"hello world", ZipMethod::Stored)
+            .unwrap();
+        let mut bytes = writer.finish().unwrap();
+
+        // 修改存储的数据后 CRC 校验应失败
+        let pos = bytes.windows(5).position(|w| w == b"hello").unwrap();
+        bytes[pos] = b'j';
+        let archive = ZipArchive::new(&bytes).unwrap();
+        assert!(archive.read("a.txt").is_err());
+
+        assert!(ZipArchive::new(b"not a zip").is_err());
+    }
+
+    #[test]
+    fn test_zip_limits() {
+        let mut writer = ZipWriter::new(Vec::new());
+        for index in 0..u16::MAX - 1 {
+            writer
+                .add_file_with_method(&index.to_string(), b"", ZipMethod::Stored)
+                .unwrap();
+        }
+        assert!(writer.add_file("overflow", b"").is_err());
+        assert!(writer
+            .add_file(&"a".repeat(u16::MAX as usize), b"")
+            .is_err());
+        let bytes = writer.finish().unwrap();
+        assert_eq!(ZipArchive::new(&bytes).unwrap().entries().len(), 65534);
+
+        assert!(to_u32(u32::MAX as u64 - 1, "文件大小").is_ok());
+        assert!(to_u32(u32::MAX as u64, "文件大小").is_err());
+        assert!(to_u32(5 << 30, "文件大小").is_err());
+
+        // 条目数量为 0xFFFF 的 ZIP64 归档
+        let mut writer = ZipWriter::new(Vec::new());
+        writer.add_file("a.txt", b"hello").unwrap();
+        let mut bytes = writer.finish().unwrap();
+        let end = bytes.len() - 22;
+        bytes[end + 8..end + 12].copy_from_slice(&[0xFF; 4]);
+        assert!(ZipArchive::new(&bytes).is_err());
+    }
+}